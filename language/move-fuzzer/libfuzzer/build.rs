@@ -39,8 +39,29 @@ fn build_and_link_libfuzzer() {
     }
 }
 
+/// Builds the bounds of the coverage counters the snapshot children send back,
+/// on the targets whose linker defines them. These are the ELF targets, where
+/// the counters live in the `__sancov_cntrs` section; elsewhere snapshots are
+/// left out and refused at startup.
+fn build_coverage_counters() {
+    let family = std::env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+    let os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    println!("cargo:rustc-check-cfg=cfg(move_fuzz_counters)");
+    let elf = family.split(',').any(|family| family == "unix")
+        && !matches!(os.as_str(), "macos" | "ios" | "tvos" | "watchos");
+    if !elf {
+        return;
+    }
+    println!("cargo:rerun-if-changed=src/run_move/counters.c");
+    cc::Build::new()
+        .file("src/run_move/counters.c")
+        .compile("move_fuzz_counters");
+    println!("cargo:rustc-cfg=move_fuzz_counters");
+}
+
 fn main() {
     if cfg!(feature = "link_libfuzzer") {
         build_and_link_libfuzzer();
     }
-}
\ No newline at end of file
+    build_coverage_counters();
+}
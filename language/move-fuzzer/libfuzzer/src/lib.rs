@@ -17,8 +17,14 @@
 pub mod run_move;
 
 pub use arbitrary;
+#[cfg(feature = "proptest-seeds")]
+pub use proptest;
+use move_binary_format::file_format_common;
+use move_core_types::account_address::AccountAddress;
 use once_cell::sync::OnceCell;
-use crate::run_move::move_args::{MoveArg, ToTransactionArgument};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::run_move::move_args::ToTransactionArgument;
 
 /// Indicates whether the input should be kept in the corpus or rejected. This
 /// should be returned by your fuzz target. If your fuzz target does not return
@@ -62,13 +68,25 @@ extern "C" {
 #[doc(hidden)]
 #[export_name = "LLVMFuzzerTestOneInput"]
 pub unsafe fn test_input_wrap(data: *const u8, size: usize) -> i32 {
+    let start = ::std::time::Instant::now();
     let test_input = ::std::panic::catch_unwind(|| {
         let data_slice = ::std::slice::from_raw_parts(data, size);
         rust_fuzzer_test_input(data_slice)
     });
 
     match test_input {
-        Ok(i) => i,
+        Ok(i) => {
+            if let Some(&threshold) = MOVE_FUZZ_HUNT_TIMEOUT_MS.get() {
+                let elapsed = start.elapsed().as_millis();
+                if elapsed > u128::from(threshold) {
+                    // The duration stays out of the panic message so that all
+                    // the slow inputs of a target land in the same crash bucket.
+                    eprintln!("INFO: input took {} ms", elapsed);
+                    panic!("slow input: over the {} ms threshold", threshold);
+                }
+            }
+            i
+        }
         Err(_) => {
             // hopefully the custom panic hook will be called before and abort the
             // process before the stack frames are unwinded.
@@ -80,6 +98,59 @@ pub unsafe fn test_input_wrap(data: *const u8, size: usize) -> i32 {
 #[doc(hidden)]
 pub static RUST_LIBFUZZER_DEBUG_PATH: OnceCell<String> = OnceCell::new();
 
+#[doc(hidden)]
+pub static MOVE_FUZZ_OUTCOME_PATH: OnceCell<String> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_SNAPSHOT: AtomicBool = AtomicBool::new(false);
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_HUNT_TIMEOUT_MS: OnceCell<u64> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_BYTECODE_VERSIONS: OnceCell<Vec<u32>> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_PARANOID: AtomicBool = AtomicBool::new(false);
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_SIGNERS: OnceCell<Vec<AccountAddress>> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_ADDRESSES: OnceCell<Vec<move_arbitrary::AddressStrategy>> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_STATE: OnceCell<PathBuf> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_SAVE_STATE: OnceCell<PathBuf> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_NATIVES: OnceCell<Vec<run_move::natives::NativePackage>> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_PARSER_BUDGET: OnceCell<u64> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_EXPECTED_ABORTS: OnceCell<
+    std::collections::BTreeMap<String, run_move::aborts::ExpectedAborts>,
+> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_CHECK_SPECS: AtomicBool = AtomicBool::new(false);
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_COVERAGE_MAP: OnceCell<PathBuf> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_VERIFIER_LIMITS: OnceCell<run_move::bytecode::VerifierLimits> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_VERIFIER_METER_UNITS: OnceCell<u128> = OnceCell::new();
+
+#[doc(hidden)]
+pub static MOVE_FUZZ_VERIFIER_BACK_EDGES: OnceCell<usize> = OnceCell::new();
+
 #[doc(hidden)]
 #[export_name = "LLVMFuzzerInitialize"]
 pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8) -> isize {
@@ -95,9 +166,41 @@ pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8
     let default_hook = ::std::panic::take_hook();
     ::std::panic::set_hook(Box::new(move |panic_info| {
         default_hook(panic_info);
+        #[cfg(feature = "heap-profile")]
+        run_move::heap_profile::write();
         ::std::process::abort();
     }));
 
+    // When `MOVE_FUZZ_REDUCE_MODULE` is set, the target runs no input: it writes
+    // the reductions of the module `move-fuzz tmin --module` is minimizing to
+    // that directory and exits.
+    if let Some(dir) = std::env::var_os("MOVE_FUZZ_REDUCE_MODULE") {
+        if let Err(e) = run_move::reduce::write_reductions(std::path::Path::new(&dir)) {
+            eprintln!("could not reduce the module: {:#}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // When `MOVE_FUZZ_PROPTEST_SEEDS` is set in a build with the `proptest-seeds`
+    // feature, the target runs no input: it writes the inputs the strategy of
+    // `fuzz_proptest_seeds!` generates to that directory and exits.
+    #[cfg(feature = "proptest-seeds")]
+    if let Some(dir) = std::env::var_os("MOVE_FUZZ_PROPTEST_SEEDS") {
+        match run_move::seeds::write_seeds(std::path::Path::new(&dir)) {
+            Ok((written, left_out)) => {
+                eprintln!(
+                    "wrote {} seeds, left out {} that do not decode back",
+                    written, left_out
+                );
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("could not generate the seeds: {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
     // Initialize the `RUST_LIBFUZZER_DEBUG_PATH` cell with the path so it can be
     // reused with little overhead.
@@ -106,6 +209,189 @@ pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8
             .set(path)
             .expect("Since this is initialize it is only called once so can never fail");
     }
+
+    // When `MOVE_FUZZ_OUTCOME_PATH` is set, the script is executed without
+    // committing its effects and its outcome is written to that file instead.
+    // This is only intended for `move-fuzz diff-run`'s use!
+    if let Ok(path) = std::env::var("MOVE_FUZZ_OUTCOME_PATH") {
+        MOVE_FUZZ_OUTCOME_PATH
+            .set(path)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // When `MOVE_FUZZ_SNAPSHOT` is set, script targets run each input in a
+    // fork of the process taken after the package and VM are set up.
+    // The children send their coverage counters back, which only ELF targets
+    // locate, so anywhere else the variable is refused rather than ignored.
+    if std::env::var_os("MOVE_FUZZ_SNAPSHOT").is_some() {
+        if cfg!(not(move_fuzz_counters)) {
+            panic!(
+                "`MOVE_FUZZ_SNAPSHOT` is not supported on {}: snapshots need the \
+                 coverage counters of ELF targets",
+                std::env::consts::OS
+            );
+        }
+        MOVE_FUZZ_SNAPSHOT.store(true, Ordering::Relaxed);
+    }
+
+    // When `MOVE_FUZZ_HUNT_TIMEOUT_MS` is set, any input running for longer
+    // than that many milliseconds panics, so libFuzzer saves it as a crash.
+    if let Ok(threshold) = std::env::var("MOVE_FUZZ_HUNT_TIMEOUT_MS") {
+        let threshold = threshold
+            .parse()
+            .expect("`MOVE_FUZZ_HUNT_TIMEOUT_MS` must be a number of milliseconds");
+        MOVE_FUZZ_HUNT_TIMEOUT_MS
+            .set(threshold)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // When `MOVE_FUZZ_BYTECODE_VERSIONS` is set, script targets also run every
+    // input with the package published at each of the listed versions, and
+    // panic if the outcomes differ.
+    if let Ok(versions) = std::env::var("MOVE_FUZZ_BYTECODE_VERSIONS") {
+        let versions = versions
+            .split(',')
+            .map(|version| version.trim().parse())
+            .collect::<Result<Vec<u32>, _>>()
+            .expect("`MOVE_FUZZ_BYTECODE_VERSIONS` must be a list of versions, e.g. `5,6`");
+        let supported = file_format_common::VERSION_MIN..=file_format_common::VERSION_MAX;
+        if let Some(version) = versions.iter().find(|v| !supported.contains(v)) {
+            panic!(
+                "bytecode version {} is not supported, expected {} to {}",
+                version,
+                supported.start(),
+                supported.end()
+            );
+        }
+        MOVE_FUZZ_BYTECODE_VERSIONS
+            .set(versions)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // `MOVE_FUZZ_SIGNERS` and `MOVE_FUZZ_ADDRESSES` set the accounts signing
+    // the calls and where address arguments are drawn from, set by `move-fuzz`
+    // from `[fuzz] signers` and `[fuzz] addresses` in `Move.toml`.
+    if let Ok(signers) = std::env::var("MOVE_FUZZ_SIGNERS") {
+        let signers = run_move::addresses::parse_signers(&signers)
+            .unwrap_or_else(|e| panic!("`MOVE_FUZZ_SIGNERS`: {}", e));
+        MOVE_FUZZ_SIGNERS
+            .set(signers)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+    if let Ok(strategies) = std::env::var("MOVE_FUZZ_ADDRESSES") {
+        let strategies = run_move::addresses::parse_strategies(&strategies)
+            .unwrap_or_else(|e| panic!("`MOVE_FUZZ_ADDRESSES`: {}", e));
+        MOVE_FUZZ_ADDRESSES
+            .set(strategies)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // `MOVE_FUZZ_STATE` is a snapshot of the global storage stateful targets
+    // start from, and `MOVE_FUZZ_SAVE_STATE` where they save theirs after every
+    // input.
+    if let Some(path) = std::env::var_os("MOVE_FUZZ_STATE") {
+        MOVE_FUZZ_STATE
+            .set(PathBuf::from(path))
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+    if let Some(path) = std::env::var_os("MOVE_FUZZ_SAVE_STATE") {
+        MOVE_FUZZ_SAVE_STATE
+            .set(PathBuf::from(path))
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // `MOVE_FUZZ_NATIVES` lists the native packages registered in the VM, set
+    // by `move-fuzz` from `[fuzz] natives` in `Move.toml`.
+    if let Ok(packages) = std::env::var("MOVE_FUZZ_NATIVES") {
+        let packages = run_move::natives::NativePackage::parse_list(&packages)
+            .unwrap_or_else(|e| panic!("`MOVE_FUZZ_NATIVES`: {}", e));
+        MOVE_FUZZ_NATIVES
+            .set(packages)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // `MOVE_FUZZ_VERIFIER_LIMITS` has bytecode-verifier targets verify with the
+    // production limits, without any, or both ways comparing the verdicts.
+    // `MOVE_FUZZ_VERIFIER_METER_UNITS` and `MOVE_FUZZ_VERIFIER_BACK_EDGES`
+    // override the production meter budget and back-edge limit.
+    if let Ok(limits) = std::env::var("MOVE_FUZZ_VERIFIER_LIMITS") {
+        let limits = run_move::bytecode::VerifierLimits::parse(&limits)
+            .unwrap_or_else(|e| panic!("`MOVE_FUZZ_VERIFIER_LIMITS`: {}", e));
+        MOVE_FUZZ_VERIFIER_LIMITS
+            .set(limits)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+    if let Ok(units) = std::env::var("MOVE_FUZZ_VERIFIER_METER_UNITS") {
+        let units = units
+            .parse()
+            .expect("`MOVE_FUZZ_VERIFIER_METER_UNITS` must be a number of meter units");
+        MOVE_FUZZ_VERIFIER_METER_UNITS
+            .set(units)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+    if let Ok(back_edges) = std::env::var("MOVE_FUZZ_VERIFIER_BACK_EDGES") {
+        let back_edges = back_edges
+            .parse()
+            .expect("`MOVE_FUZZ_VERIFIER_BACK_EDGES` must be a number of back edges");
+        MOVE_FUZZ_VERIFIER_BACK_EDGES
+            .set(back_edges)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // `MOVE_FUZZ_PARSER_BUDGET` overrides how many instructions parser targets
+    // may retire on an input before it is a finding.
+    if let Ok(budget) = std::env::var("MOVE_FUZZ_PARSER_BUDGET") {
+        let budget = budget
+            .parse()
+            .expect("`MOVE_FUZZ_PARSER_BUDGET` must be a number of instructions");
+        MOVE_FUZZ_PARSER_BUDGET
+            .set(budget)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // When `MOVE_FUZZ_PARANOID` is set, script targets also run every input
+    // with the paranoid type checks of the VM enabled and disabled, and panic if
+    // the outcomes differ.
+    if std::env::var_os("MOVE_FUZZ_PARANOID").is_some() {
+        MOVE_FUZZ_PARANOID.store(true, Ordering::Relaxed);
+    }
+
+    // `MOVE_FUZZ_EXPECTED_ABORTS` declares the abort codes entry functions of
+    // sequence targets may abort with, set by `move-fuzz` from the `aborts` of
+    // the target in `Move.toml`.
+    if let Ok(list) = std::env::var("MOVE_FUZZ_EXPECTED_ABORTS") {
+        let declared = run_move::aborts::ExpectedAborts::parse_list(&list)
+            .unwrap_or_else(|e| panic!("`MOVE_FUZZ_EXPECTED_ABORTS`: {}", e));
+        MOVE_FUZZ_EXPECTED_ABORTS
+            .set(declared)
+            .expect("Since this is initialize it is only called once so can never fail");
+    }
+
+    // When `MOVE_FUZZ_CHECK_SPECS` is set, sequence targets also run every
+    // input in the interpreter of the Move Prover, and panic on the first call
+    // violating a spec condition.
+    if std::env::var_os("MOVE_FUZZ_CHECK_SPECS").is_some() {
+        MOVE_FUZZ_CHECK_SPECS.store(true, Ordering::Relaxed);
+    }
+
+    // When `MOVE_FUZZ_COVERAGE_MAP` is set along with `MOVE_VM_TRACE`, the
+    // trace of the inputs run is written there as a `move coverage` map on exit.
+    if let Some(path) = std::env::var_os("MOVE_FUZZ_COVERAGE_MAP") {
+        MOVE_FUZZ_COVERAGE_MAP
+            .set(PathBuf::from(path))
+            .expect("Since this is initialize it is only called once so can never fail");
+        unsafe {
+            libc::atexit(run_move::coverage_map::write_at_exit);
+        }
+    }
+
+    // When `MOVE_FUZZ_HEAP_PROFILE_PATH` is set in a build with the `heap-profile`
+    // feature, the allocations of the inputs run are profiled and written there
+    // in DHAT's format on exit.
+    #[cfg(feature = "heap-profile")]
+    if let Some(path) = std::env::var_os("MOVE_FUZZ_HEAP_PROFILE_PATH") {
+        run_move::heap_profile::start(PathBuf::from(path));
+    }
     0
 }
 
@@ -113,52 +399,50 @@ pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8
 ///
 /// ## Example
 ///
-/// This example takes a `&[u8]` slice and attempts to parse it. The parsing
-/// might fail and return an `Err`, but it shouldn't ever panic or segfault.
+/// This example takes the input bytes and parses them into the arguments of
+/// the Move script it runs. The parsing might fail and return an `Err`, in
+/// which case the target returns no arguments and the input is skipped.
 ///
 /// ```no_run
 /// #![no_main]
 ///
 /// use libfuzzer::fuzz_target;
+/// use move_core_types::value::MoveValue;
 ///
-/// // Note: `|input|` is short for `|input: &[u8]|`.
+/// // Note: `|input|` is short for `|input: Vec<u8>|`.
 /// fuzz_target!(|input| {
-///     let _result: Result<_, _> = my_crate::parse(input);
+///     match my_crate::parse(&input) {
+///         Ok(amount) => vec![Box::new(MoveValue::U64(amount))],
+///         Err(_) => vec![],
+///     }
 /// });
-/// # mod my_crate { pub fn parse(_: &[u8]) -> Result<(), ()> { unimplemented!() } }
+/// # mod my_crate { pub fn parse(_: &[u8]) -> Result<u64, ()> { unimplemented!() } }
 /// ```
 ///
-/// ## Rejecting Inputs
-///
-/// It may be desirable to reject some inputs, i.e. to not add them to the
-/// corpus.
+/// ## Skipping Inputs
 ///
-/// For example, when fuzzing an API consisting of parsing and other logic,
-/// one may want to allow only those inputs into the corpus that parse
-/// successfully. To indicate whether an input should be kept in or rejected
-/// from the corpus, return either [Corpus::Keep] or [Corpus::Reject] from your
-/// fuzz target. The default behavior (e.g. if `()` is returned) is to keep the
-/// input in the corpus.
+/// An input the target returns no arguments for is not run. Neither is one
+/// whose arguments no script parameter can hold, such as vectors of anything
+/// but `u8`.
 ///
-/// For example:
+/// For example, when fuzzing a function taking a key and a value, one may
+/// want to run only the inputs that split into both:
 ///
 /// ```no_run
 /// #![no_main]
 ///
-/// use libfuzzer::{Corpus, fuzz_target};
-///
-/// fuzz_target!(|input: String| -> Corpus {
-///     let parts: Vec<&str> = input.splitn(2, '=').collect();
-///     if parts.len() != 2 {
-///         return Corpus::Reject;
-///     }
-///
-///     let key = parts[0];
-///     let value = parts[1];
-///     let _result: Result<_, _> = my_crate::parse(key, value);
-///     Corpus::Keep
+/// use libfuzzer::fuzz_target;
+/// use move_core_types::value::MoveValue;
+///
+/// fuzz_target!(|input: String| {
+///     let Some((key, value)) = input.split_once('=') else {
+///         return vec![];
+///     };
+///     vec![
+///         Box::new(MoveValue::vector_u8(key.as_bytes().to_vec())),
+///         Box::new(MoveValue::vector_u8(value.as_bytes().to_vec())),
+///     ]
 /// });
-/// # mod my_crate { pub fn parse(_key: &str, _value: &str) -> Result<(), ()> { unimplemented!() } }
 /// ```
 ///
 /// ## Arbitrary Input Types
@@ -176,6 +460,7 @@ pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8
 /// # mod foo {
 ///
 /// use libfuzzer::{arbitrary::{Arbitrary, Error, Unstructured}, fuzz_target};
+/// use move_core_types::value::MoveValue;
 ///
 /// #[derive(Debug)]
 /// pub struct Rgb {
@@ -197,43 +482,58 @@ pub extern "C" fn initialize(_argc: *const isize, _argv: *const *const *const u8
 ///
 /// // Write a fuzz target that works with RGB colors instead of raw bytes.
 /// fuzz_target!(|color: Rgb| {
-///     my_crate::convert_color(color);
+///     vec![Box::new(MoveValue::vector_u8(vec![color.r, color.g, color.b]))]
 /// });
-/// # mod my_crate {
-/// #     use super::Rgb;
-/// #     pub fn convert_color(_: Rgb) {}
-/// # }
 /// # }
 /// ```
 ///
 /// You can also enable the `arbitrary` crate's custom derive via this crate's
 /// `"arbitrary-derive"` cargo feature.
+///
+/// ## Custom Mutators
+///
+/// A [`MoveMutator`](crate::run_move::mutator::MoveMutator) can be registered
+/// alongside the target with a leading `mutator = ...`, which is shorthand for
+/// a separate [`fuzz_mutator!`] invocation. The target then receives inputs
+/// in the encoding the mutator produces. Values no script argument holds, such
+/// as vectors of anything but `u8`, make the input skipped:
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_target;
+/// use libfuzzer::run_move::mutator::ValueMutator;
+/// use move_core_types::value::{MoveTypeLayout, MoveValue};
+///
+/// fn layout() -> MoveTypeLayout {
+///     MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8))
+/// }
+///
+/// fuzz_target!(mutator = ValueMutator::new(layout()), |data| {
+///     match MoveValue::simple_deserialize(&data, &layout()) {
+///         Ok(value) => vec![Box::new(value)],
+///         Err(_) => vec![],
+///     }
+/// });
+/// ```
 #[macro_export]
 macro_rules! fuzz_target {
+    (mutator = $mutator:expr, $($target:tt)*) => {
+        $crate::fuzz_mutator!(mutator = $mutator);
+        $crate::fuzz_target!($($target)*);
+    };
+
     (|$bytes:ident| $body:expr) => {
         const _: () = {
-            /// Auto-generated function
-            #[no_mangle]
-            pub extern "C" fn rust_fuzzer_test_input(bytes: &[u8]) -> i32 {
-                // When `RUST_LIBFUZZER_DEBUG_PATH` is set, write the debug
-                // formatting of the input to that file. This is only intended for
-                // `cargo fuzz`'s use!
-
-                // `RUST_LIBFUZZER_DEBUG_PATH` is set in initialization.
-                if let Some(path) = $crate::RUST_LIBFUZZER_DEBUG_PATH.get() {
-                    use std::io::Write;
-                    let mut file = std::fs::File::create(path)
-                        .expect("failed to create `RUST_LIBFUZZER_DEBUG_PATH` file");
-                    writeln!(&mut file, "{:?}", bytes)
-                        .expect("failed to write to `RUST_LIBFUZZER_DEBUG_PATH` file");
-                    return 0;
+            $crate::fuzz_target!(@entry |bytes| let bytes = bytes;
+                debug(|file| writeln!(file, "{:?}", bytes)) => 0;
+                {
+                    __libfuzzer_sys_run(get_data(bytes.to_vec()));
+                    0
                 }
+            );
 
-                __libfuzzer_sys_run(get_data(bytes.to_vec()));
-                0
-            }
-
-            fn get_data($bytes: Vec<u8>) -> MoveArg {
+            fn get_data($bytes: Vec<u8>) -> $crate::run_move::move_args::MoveArg {
                 $body
             }
 
@@ -250,7 +550,7 @@ macro_rules! fuzz_target {
             // ideally help prevent oss-fuzz from deduplicate fuzz bugs across
             // distinct targets accidentally.
             #[inline(never)]
-            fn __libfuzzer_sys_run(data: MoveArg) {
+            fn __libfuzzer_sys_run(data: $crate::run_move::move_args::MoveArg) {
                 $crate::run_move::run(data)
             }
         };
@@ -266,71 +566,368 @@ macro_rules! fuzz_target {
 
     (|$($data:ident: $dty:ty),*| -> $rty:ty $body:block) => {
         const _: () = {
-            /// Auto-generated function
-            #[no_mangle]
-            pub extern "C" fn rust_fuzzer_test_input(bytes: &[u8]) -> i32 {
-                use $crate::arbitrary::{Arbitrary, Unstructured};
-
-                // Early exit if we don't have enough bytes for the `Arbitrary`
-                // implementation. This helps the fuzzer avoid exploring all the
-                // different not-enough-input-bytes paths inside the `Arbitrary`
-                // implementation. Additionally, it exits faster, letting the fuzzer
-                // get to longer inputs that actually lead to interesting executions
-                // quicker.
-                let mut total_bytes_nedeed = 0;
-                $(
-                    total_bytes_nedeed += <$dty as Arbitrary>::size_hint(0).0;
-                )*
-                if bytes.len() < total_bytes_nedeed {
-                    return -1;
+            $crate::fuzz_target!(@entry |bytes| let data = {
+                    use $crate::arbitrary::{Arbitrary, Unstructured};
+
+                    // Early exit if we don't have enough bytes for the `Arbitrary`
+                    // implementation. This helps the fuzzer avoid exploring all the
+                    // different not-enough-input-bytes paths inside the `Arbitrary`
+                    // implementation. Additionally, it exits faster, letting the fuzzer
+                    // get to longer inputs that actually lead to interesting executions
+                    // quicker.
+                    let mut total_bytes_nedeed = 0;
+                    $(
+                        total_bytes_nedeed += <$dty as Arbitrary>::size_hint(0).0;
+                    )*
+                    if bytes.len() < total_bytes_nedeed {
+                        return -1;
+                    }
+
+                    let mut u = Unstructured::new(bytes);
+                    (|| -> $crate::arbitrary::Result<_> {
+                        Ok(($(<$dty as Arbitrary>::arbitrary(&mut u)?,)*))
+                    })()
+                };
+                debug(|file| match &data {
+                    Ok(($($data,)*)) => (|| -> std::io::Result<()> {
+                        $(writeln!(file, "{:#?}", $data)?;)*
+                        Ok(())
+                    })(),
+                    Err(err) => writeln!(file, "Arbitrary Error: {}", err),
+                }) => -1;
+                match data {
+                    Ok(($($data,)*)) => {
+                        let result = $crate::Corpus::from(__libfuzzer_sys_run(get_data($($data),*)));
+                        result.to_libfuzzer_code()
+                    }
+                    Err(_) => -1,
                 }
+            );
 
-                let mut u = Unstructured::new(bytes);
-                $(
-                    let $data = <$dty as Arbitrary>::arbitrary(&mut u);
-
-                    // When `RUST_LIBFUZZER_DEBUG_PATH` is set, write the debug
-                    // formatting of the input to that file. This is only intended for
-                    // `cargo fuzz`'s use!
-
-                    // `RUST_LIBFUZZER_DEBUG_PATH` is set in initialization.
-                    if let Some(path) = $crate::RUST_LIBFUZZER_DEBUG_PATH.get() {
-                        use std::io::Write;
-                        let mut file = std::fs::File::create(path)
-                            .expect("failed to create `RUST_LIBFUZZER_DEBUG_PATH` file");
-                        (
-                            match $data {
-                                Ok($data) => writeln!(&mut file, "{:#?}", $data),
-                                Err(err) => writeln!(&mut file, "Arbitrary Error: {}", err),
-                            }
-                        ).expect("failed to write to `RUST_LIBFUZZER_DEBUG_PATH` file");
+            fn get_data($($data: $dty),*) -> $crate::run_move::move_args::MoveArg {
+                $body
+            }
 
-                        return -1;
+            // See above for why this is split to a separate function.
+            #[inline(never)]
+            fn __libfuzzer_sys_run(args: $crate::run_move::move_args::MoveArg) {
+                $crate::run_move::run(args);
+            }
+        };
+    };
+
+    // The entry point libFuzzer calls, shared by the fuzz target macros: it
+    // decodes `bytes` into `$data`, then runs `$run`.
+    //
+    // When `RUST_LIBFUZZER_DEBUG_PATH` is set, it instead writes the debug
+    // formatting of the input to that file with `$debug` and returns `$code`.
+    // This is only intended for `cargo fuzz`'s use!
+    (@entry |$bytes:ident| let $data:ident = $input:expr;
+        debug(|$file:ident| $debug:expr) => $code:expr;
+        $run:expr
+    ) => {
+        /// Auto-generated function
+        #[no_mangle]
+        pub extern "C" fn rust_fuzzer_test_input($bytes: &[u8]) -> i32 {
+            let $data = $input;
+
+            // `RUST_LIBFUZZER_DEBUG_PATH` is set in initialization.
+            if let Some(path) = $crate::RUST_LIBFUZZER_DEBUG_PATH.get() {
+                use std::io::Write;
+                let mut $file = std::fs::File::create(path)
+                    .expect("failed to create `RUST_LIBFUZZER_DEBUG_PATH` file");
+                $debug.expect("failed to write to `RUST_LIBFUZZER_DEBUG_PATH` file");
+                return $code;
+            }
+
+            $run
+        }
+    };
+}
+
+/// Define a fuzz target over a sequence of entry-function calls.
+///
+/// The body decodes the input into a
+/// [`TxnSequence`](crate::run_move::sequence::TxnSequence), usually through
+/// [`decode_sequence`](crate::run_move::sequence::decode_sequence). The calls
+/// then run in order against the same global storage, so multi-step protocol
+/// interactions (e.g. register, deposit, withdraw) can be fuzzed as a whole.
+/// [`signers`](crate::run_move::addresses::signers) are the accounts set by
+/// `[fuzz] signers` in `Move.toml`.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::arbitrary::{Result, Unstructured};
+/// use libfuzzer::fuzz_sequence;
+/// use libfuzzer::run_move::addresses::signers;
+/// use libfuzzer::run_move::move_args::MoveArg;
+/// use libfuzzer::run_move::sequence::{decode_sequence, EntryFunction};
+///
+/// fn amount(u: &mut Unstructured) -> Result<MoveArg> {
+///     Ok(vec![Box::new(u.arbitrary::<u64>()?)])
+/// }
+///
+/// const FUNCTIONS: &[EntryFunction] = &[
+///     EntryFunction::new("bank", "deposit", 1, amount),
+///     EntryFunction::new("bank", "withdraw", 1, amount),
+/// ];
+///
+/// fuzz_sequence!(|u| decode_sequence(u, signers(), FUNCTIONS, 16));
+/// ```
+///
+/// With `cross_interpreter` before the closure, the calls instead run both in
+/// the VM and in the stackless bytecode interpreter of the Move Prover, and a
+/// call the two disagree on, by its status, abort code, return values or
+/// changes to resources, is a crash.
+#[macro_export]
+macro_rules! fuzz_sequence {
+    (|$u:ident| $body:expr) => {
+        $crate::fuzz_sequence!(@run |$u| $body, |sequence| {
+            $crate::run_move::sequence::run(sequence)
+        });
+    };
+    (cross_interpreter, |$u:ident| $body:expr) => {
+        $crate::fuzz_sequence!(@run |$u| $body, |sequence| {
+            $crate::run_move::cross_interpreter::run(sequence)
+        });
+    };
+    (@run |$u:ident| $body:expr, |$sequence:ident| $run:expr) => {
+        const _: () = {
+            $crate::fuzz_target!(@entry |bytes|
+                let sequence = get_sequence(&mut $crate::arbitrary::Unstructured::new(bytes));
+                debug(|file| match &sequence {
+                    Ok(sequence) => writeln!(file, "{:#?}", sequence),
+                    Err(err) => writeln!(file, "Arbitrary Error: {}", err),
+                }) => -1;
+                match sequence {
+                    Ok(sequence) => {
+                        __libfuzzer_sys_run(sequence);
+                        0
                     }
+                    Err(_) => -1,
+                }
+            );
 
-                    let $data = match $data {
-                        Ok(d) => d,
-                        Err(_) => return -1,
-                    };
-                )*
+            fn get_sequence(
+                $u: &mut $crate::arbitrary::Unstructured,
+            ) -> $crate::arbitrary::Result<$crate::run_move::sequence::TxnSequence> {
+                $body
+            }
 
-                let result = ::libfuzzer::Corpus::from(__libfuzzer_sys_run(get_data($($data),*)));
-                result.to_libfuzzer_code()
+            // See `fuzz_target!` for why this is split to a separate function.
+            #[inline(never)]
+            fn __libfuzzer_sys_run($sequence: $crate::run_move::sequence::TxnSequence) {
+                $run
             }
+        };
+    };
+}
 
-            fn get_data($($data: $dty),*) -> MoveArg {
+/// Define a fuzz target whose Move state persists across inputs.
+///
+/// Works like the `Vec<u8>` form of [`fuzz_target!`], but the script runs on a
+/// VM and an in-memory global storage that outlive a single input: resources
+/// published by one input are visible to the next ones, which lets the fuzzer
+/// reach bugs that only show up in a particular state. The state starts over
+/// when the process restarts after a crash and, with `reset_every = N`, every
+/// `N` executions.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_target_stateful;
+///
+/// fuzz_target_stateful!(reset_every = 10_000, |data| {
+///     vec![Box::new(data)]
+/// });
+/// ```
+#[macro_export]
+macro_rules! fuzz_target_stateful {
+    (|$bytes:ident| $body:expr) => {
+        $crate::fuzz_target_stateful!(reset_every = 0, |$bytes| $body);
+    };
+    (reset_every = $reset_every:expr, |$bytes:ident| $body:expr) => {
+        const _: () = {
+            $crate::fuzz_target!(@entry |bytes| let bytes = bytes;
+                debug(|file| writeln!(file, "{:?}", bytes)) => 0;
+                {
+                    __libfuzzer_sys_run(get_data(bytes.to_vec()));
+                    0
+                }
+            );
+
+            fn get_data($bytes: Vec<u8>) -> $crate::run_move::move_args::MoveArg {
                 $body
             }
 
-            // See above for why this is split to a separate function.
+            // See `fuzz_target!` for why this is split to a separate function.
             #[inline(never)]
-            fn __libfuzzer_sys_run(args: MoveArg) {
-                $crate::run_move::run(args);
+            fn __libfuzzer_sys_run(data: $crate::run_move::move_args::MoveArg) {
+                $crate::run_move::stateful::run(data, $reset_every)
+            }
+        };
+    };
+}
+
+/// Define a fuzz target using the native extensions of the VM.
+///
+/// Works like the `Vec<u8>` form of [`fuzz_target!`], but the script runs in a
+/// session carrying the context of the `table` natives, so packages storing
+/// their state in tables can be fuzzed. Finishing the session turns its events
+/// and table changes into effects, and failing to do so is a crash too. Effects
+/// are never committed.
+///
+/// The start of every input is the
+/// [`ExtensionState`](crate::run_move::extensions::ExtensionState) the session
+/// starts from: the hash of the transaction new table handles derive from, and
+/// the counters of the `std::guid::Generator`s of the accounts the script
+/// takes, which new event handles continue from. The body gets the rest.
+///
+/// `natives = [..]` lists the [`NativePackage`](crate::run_move::natives::NativePackage)s
+/// the extensions need, registered on top of those of `[fuzz] natives` in
+/// `Move.toml`: `Table` for tables, which also needs the `table-extension`
+/// feature, and `Nursery` for events.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_target_extensions;
+///
+/// fuzz_target_extensions!(natives = [Table, Nursery], |data| {
+///     vec![Box::new(data)]
+/// });
+/// ```
+#[macro_export]
+macro_rules! fuzz_target_extensions {
+    (natives = [$($package:ident),* $(,)?], |$bytes:ident| $body:expr) => {
+        const _: () = {
+            $crate::fuzz_target!(@entry |bytes| let bytes = bytes;
+                debug(|file| writeln!(file, "{:?}", bytes)) => 0;
+                {
+                    __libfuzzer_sys_run(bytes);
+                    0
+                }
+            );
+
+            fn get_data($bytes: Vec<u8>) -> $crate::run_move::move_args::MoveArg {
+                $body
+            }
+
+            // See `fuzz_target!` for why this is split to a separate function.
+            #[inline(never)]
+            fn __libfuzzer_sys_run(bytes: &[u8]) {
+                $crate::run_move::extensions::run(
+                    bytes,
+                    &[$($crate::run_move::natives::NativePackage::$package),*],
+                    get_data,
+                )
+            }
+        };
+    };
+    (|$bytes:ident| $body:expr) => {
+        $crate::fuzz_target_extensions!(natives = [], |$bytes| $body);
+    };
+}
+
+/// Define a fuzz target checking gas metering.
+///
+/// Works like the `Vec<u8>` form of [`fuzz_target!`], but every input is
+/// executed several times without committing its effects: twice metered, to
+/// catch nondeterministic gas usage, and once unmetered, to catch metering that
+/// changes the result of an execution. With `schedules = (a, b)` the script is
+/// instead compared under the two given gas schedules. With `fuzzed_schedule`
+/// the schedule itself is drawn from the start of the input, and the script is
+/// compared under it and a costlier variant, to catch panics in the cost math
+/// and charging that is not monotonic. Any divergence is reported as a crash.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_target_gas;
+///
+/// fuzz_target_gas!(|data| {
+///     vec![Box::new(data)]
+/// });
+/// ```
+#[macro_export]
+macro_rules! fuzz_target_gas {
+    (|$bytes:ident| $body:expr) => {
+        $crate::fuzz_target_gas!(@run |$bytes| $body, |data| $crate::run_move::gas::run(data));
+    };
+    (schedules = ($schedule:expr, $other:expr), |$bytes:ident| $body:expr) => {
+        $crate::fuzz_target_gas!(@run |$bytes| $body, |data| {
+            $crate::run_move::gas::run_with_schedules(data, &$schedule, &$other)
+        });
+    };
+    (fuzzed_schedule, |$bytes:ident| $body:expr) => {
+        const _: () = {
+            $crate::fuzz_target!(@entry |bytes| let bytes = bytes;
+                debug(|file| writeln!(file, "{:?}", bytes)) => 0;
+                {
+                    __libfuzzer_sys_run(bytes);
+                    0
+                }
+            );
+
+            fn get_data($bytes: Vec<u8>) -> $crate::run_move::move_args::MoveArg {
+                $body
+            }
+
+            // See `fuzz_target!` for why this is split to a separate function.
+            #[inline(never)]
+            fn __libfuzzer_sys_run(bytes: &[u8]) {
+                $crate::run_move::gas::run_with_fuzzed_schedule(bytes, get_data)
+            }
+        };
+    };
+    (@run |$bytes:ident| $body:expr, |$data:ident| $run:expr) => {
+        const _: () = {
+            $crate::fuzz_target!(@entry |bytes| let bytes = bytes;
+                debug(|file| writeln!(file, "{:?}", bytes)) => 0;
+                {
+                    __libfuzzer_sys_run(get_data(bytes.to_vec()));
+                    0
+                }
+            );
+
+            fn get_data($bytes: Vec<u8>) -> $crate::run_move::move_args::MoveArg {
+                $body
+            }
+
+            // See `fuzz_target!` for why this is split to a separate function.
+            #[inline(never)]
+            fn __libfuzzer_sys_run($data: $crate::run_move::move_args::MoveArg) {
+                $run
             }
         };
     };
 }
 
+/// Define a fuzz target checking paranoid type checks.
+///
+/// Works like the `Vec<u8>` form of [`fuzz_target!`], but every input is
+/// executed twice without committing its effects, with the paranoid type checks
+/// of the VM enabled and disabled. The checks must never change the outcome of
+/// a script that passed the bytecode verifier, so any divergence is reported as
+/// a crash.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_target_paranoid;
+///
+/// fuzz_target_paranoid!(|data| {
+///     vec![Box::new(data)]
+/// });
+/// ```
+#[macro_export]
+macro_rules! fuzz_target_paranoid {
+    (|$bytes:ident| $body:expr) => {
+        $crate::fuzz_target_gas!(@run |$bytes| $body, |data| $crate::run_move::paranoid::run(data));
+    };
+}
+
 /// Define a custom mutator.
 ///
 /// This is optional, and libFuzzer will use its own, default mutation strategy
@@ -403,15 +1000,16 @@ macro_rules! fuzz_target {
 ///
 /// use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 /// use libfuzzer::{fuzz_mutator, fuzz_target};
+/// use move_core_types::value::MoveValue;
 /// use std::io::{Read, Write};
 ///
 /// fuzz_target!(|data: &[u8]| {
 ///     // Decompress the input data and crash if it starts with "boom".
-///     if let Some(data) = decompress(data) {
-///         if data.starts_with(b"boom") {
-///             panic!();
-///         }
+///     let data = decompress(data).unwrap_or_default();
+///     if data.starts_with(b"boom") {
+///         panic!();
 ///     }
+///     vec![Box::new(MoveValue::vector_u8(data))]
 /// });
 ///
 /// fuzz_mutator!(
@@ -472,8 +1070,32 @@ macro_rules! fuzz_target {
 ///
 /// * An HTTP request custom mutator that inserts, replaces, and removes a
 ///   header from an HTTP request.
+///
+/// ## Move Value Mutators
+///
+/// Instead of a closure, the macro also takes any
+/// [`MoveMutator`](crate::run_move::mutator::MoveMutator) with `mutator =
+/// ...`. The input is decoded, mutated as a value and encoded again; inputs
+/// that fail to decode get libFuzzer's default mutation. The expression is
+/// evaluated for every mutation, so it should be cheap to build.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::fuzz_mutator;
+/// use libfuzzer::run_move::mutator::ValueMutator;
+/// use move_core_types::value::MoveTypeLayout;
+///
+/// fuzz_mutator!(mutator = ValueMutator::new(MoveTypeLayout::U128));
+/// ```
 #[macro_export]
 macro_rules! fuzz_mutator {
+    (mutator = $mutator:expr $(,)?) => {
+        $crate::fuzz_mutator!(|data: &mut [u8], size: usize, max_size: usize, seed: u32| {
+            $crate::run_move::mutator::mutate_with(&$mutator, data, size, max_size, seed)
+        });
+    };
+
     (
         |
         $data:ident : &mut [u8] ,
@@ -591,6 +1213,7 @@ pub fn fuzzer_mutate(data: &mut [u8], size: usize, max_size: usize) -> usize {
 /// #![no_main]
 ///
 /// use libfuzzer::{fuzz_crossover, fuzz_mutator, fuzz_target, fuzzer_mutate};
+/// use move_core_types::value::MoveValue;
 /// use rand::{rngs::StdRng, Rng, SeedableRng};
 /// use std::mem::size_of;
 ///
@@ -605,6 +1228,7 @@ pub fn fuzzer_mutate(data: &mut [u8], size: usize, max_size: usize) -> usize {
 ///         !res.is_nan(),
 ///         "The sum of the following floats resulted in a NaN: {floats:?}"
 ///     );
+///     vec![Box::new(MoveValue::vector_u8(data.to_vec()))]
 /// });
 ///
 /// // Inject some ...potentially problematic values to make the example close
@@ -705,4 +1329,157 @@ macro_rules! fuzz_crossover {
             custom_crossover($data1, $data2, $out, $seed)
         }
     };
-}
\ No newline at end of file
+}
+
+/// Declare the abort codes entry functions of sequence targets may abort with.
+///
+/// Takes `"module::function" => expected` pairs, where `expected` is an
+/// [`ExpectedAborts`](crate::run_move::aborts::ExpectedAborts), and declares
+/// them when the fuzz target starts, over those of `aborts` in `Move.toml`. A
+/// call aborting with another code, failing otherwise, or succeeding when it
+/// must abort is then a crash, while the expected aborts are not.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::arbitrary::{Result, Unstructured};
+/// use libfuzzer::run_move::aborts::ExpectedAborts;
+/// use libfuzzer::run_move::addresses::signers;
+/// use libfuzzer::run_move::sequence::{decode_sequence, EntryFunction};
+/// use libfuzzer::{fuzz_expected_aborts, fuzz_sequence};
+///
+/// fuzz_expected_aborts!(
+///     "bank::withdraw" => ExpectedAborts::codes([1, 2]),
+///     "bank::freeze" => ExpectedAborts::always([3]),
+/// );
+///
+/// # const FUNCTIONS: &[EntryFunction] = &[];
+/// fuzz_sequence!(|u| decode_sequence(u, signers(), FUNCTIONS, 16));
+/// ```
+#[macro_export]
+macro_rules! fuzz_expected_aborts {
+    ($($function:expr => $expected:expr),* $(,)?) => {
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            windows
+        )))]
+        compile_error!("registering from the initializers of the binary is only supported on Linux, Android, macOS and Windows");
+        const _: () = {
+            // Run from the initializers of the binary, like `fuzz_natives!`.
+            #[used]
+            #[cfg_attr(any(target_os = "linux", target_os = "android"), link_section = ".init_array")]
+            #[cfg_attr(target_os = "macos", link_section = "__DATA,__mod_init_func")]
+            #[cfg_attr(windows, link_section = ".CRT$XCU")]
+            static EXPECT_ABORTS: extern "C" fn() = {
+                extern "C" fn expect_aborts() {
+                    $($crate::run_move::aborts::expect_aborts($function, $expected);)*
+                }
+                expect_aborts
+            };
+        };
+    };
+}
+
+/// Generate the seed inputs of the target with a proptest strategy.
+///
+/// `move-fuzz corpus seed-from-proptest` runs the target to add the inputs the
+/// strategy generates to its corpus. The values are written as
+/// `Arbitrary::arbitrary` reads them back, through
+/// [`FuzzInput`](crate::run_move::seeds::FuzzInput); with `bytes = ` the
+/// strategy generates the raw inputs of a target taking bytes. Needs the
+/// `proptest-seeds` feature.
+///
+/// ```ignore
+/// #![no_main]
+///
+/// use libfuzzer::proptest::prelude::*;
+/// use libfuzzer::{fuzz_proptest_seeds, fuzz_target};
+///
+/// fuzz_proptest_seeds!((1..1_000_000u64, prop::collection::vec(any::<u8>(), 0..32)));
+///
+/// fuzz_target!(|amount: u64, memo: Vec<u8>| {
+///     vec![Box::new(amount), Box::new(memo)]
+/// });
+/// ```
+#[cfg(feature = "proptest-seeds")]
+#[macro_export]
+macro_rules! fuzz_proptest_seeds {
+    (@register $register:expr) => {
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            windows
+        )))]
+        compile_error!("registering from the initializers of the binary is only supported on Linux, Android, macOS and Windows");
+        const _: () = {
+            // Run from the initializers of the binary, like `fuzz_natives!`.
+            #[used]
+            #[cfg_attr(any(target_os = "linux", target_os = "android"), link_section = ".init_array")]
+            #[cfg_attr(target_os = "macos", link_section = "__DATA,__mod_init_func")]
+            #[cfg_attr(windows, link_section = ".CRT$XCU")]
+            static REGISTER_STRATEGY: extern "C" fn() = {
+                extern "C" fn register_strategy() {
+                    $register;
+                }
+                register_strategy
+            };
+        };
+    };
+    (bytes = $strategy:expr $(,)?) => {
+        $crate::fuzz_proptest_seeds!(@register $crate::run_move::seeds::register_bytes_strategy($strategy));
+    };
+    ($strategy:expr $(,)?) => {
+        $crate::fuzz_proptest_seeds!(@register $crate::run_move::seeds::register_strategy($strategy));
+    };
+}
+
+/// Register the native functions of the package under test in the VM.
+///
+/// Takes an expression evaluating to the natives, as
+/// [`NativeFunctionRecord`](crate::run_move::natives::NativeFunctionRecord)s, and
+/// registers them when the fuzz target starts, before the first input runs.
+/// They come after the packages listed in `MOVE_FUZZ_NATIVES`, which
+/// `move-fuzz` sets from `[fuzz] natives` in `Move.toml`.
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use libfuzzer::{fuzz_natives, fuzz_target};
+///
+/// fuzz_natives!(my_natives::all_natives());
+///
+/// fuzz_target!(|data: Vec<u8>| {
+///     vec![Box::new(data)]
+/// });
+/// # mod my_natives {
+/// #     pub fn all_natives() -> Vec<libfuzzer::run_move::natives::NativeFunctionRecord> { vec![] }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fuzz_natives {
+    ($natives:expr $(,)?) => {
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            windows
+        )))]
+        compile_error!("registering from the initializers of the binary is only supported on Linux, Android, macOS and Windows");
+        const _: () = {
+            // Run from the initializers of the binary, like global constructors.
+            #[used]
+            #[cfg_attr(any(target_os = "linux", target_os = "android"), link_section = ".init_array")]
+            #[cfg_attr(target_os = "macos", link_section = "__DATA,__mod_init_func")]
+            #[cfg_attr(windows, link_section = ".CRT$XCU")]
+            static REGISTER_NATIVES: extern "C" fn() = {
+                extern "C" fn register_natives() {
+                    $crate::run_move::natives::register_natives($natives);
+                }
+                register_natives
+            };
+        };
+    };
+}
@@ -0,0 +1,128 @@
+use anyhow::{bail, Result};
+use move_binary_format::errors::VMError;
+use move_core_types::vm_status::StatusCode;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+///
+/// The outcomes an entry function is expected to have, any other being a finding
+///
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExpectedAborts {
+    /// Abort codes the function may abort with
+    pub codes: BTreeSet<u64>,
+    /// Whether the function may also succeed
+    pub success: bool,
+}
+
+impl ExpectedAborts {
+    ///
+    /// A function that may succeed or abort with one of `codes`
+    ///
+    pub fn codes(codes: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            codes: codes.into_iter().collect(),
+            success: true,
+        }
+    }
+
+    ///
+    /// A function that always aborts, with one of `codes`
+    ///
+    pub fn always(codes: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            codes: codes.into_iter().collect(),
+            success: false,
+        }
+    }
+
+    ///
+    /// Parses the `;`-separated declarations of `MOVE_FUZZ_EXPECTED_ABORTS`, e.g.
+    /// `bank::withdraw=1,2,success;bank::freeze=3`, where `success` lets the
+    /// function succeed too
+    ///
+    pub fn parse_list(list: &str) -> Result<BTreeMap<String, ExpectedAborts>, String> {
+        let mut declared = BTreeMap::new();
+        for declaration in list.split(';').map(str::trim).filter(|d| !d.is_empty()) {
+            let (function, outcomes) = declaration
+                .split_once('=')
+                .ok_or_else(|| format!("`{}` is not `module::function=codes`", declaration))?;
+            let mut expected = ExpectedAborts::default();
+            for outcome in outcomes.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+                if outcome == "success" {
+                    expected.success = true;
+                } else {
+                    let code = outcome
+                        .parse()
+                        .map_err(|_| format!("`{}` is not an abort code or `success`", outcome))?;
+                    expected.codes.insert(code);
+                }
+            }
+            declared.insert(function.trim().to_owned(), expected);
+        }
+        Ok(declared)
+    }
+}
+
+static REGISTERED: Lazy<Mutex<BTreeMap<String, ExpectedAborts>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+///
+/// Declares the expected outcomes of `function`, as in `bank::withdraw`, over
+/// those of `MOVE_FUZZ_EXPECTED_ABORTS`.
+///
+/// Call it through [`fuzz_expected_aborts!`](crate::fuzz_expected_aborts), which
+/// does so before the first input runs.
+///
+pub fn expect_aborts(function: &str, expected: ExpectedAborts) {
+    REGISTERED
+        .lock()
+        .unwrap()
+        .insert(function.to_owned(), expected);
+}
+
+fn expected(module: &str, function: &str) -> Option<ExpectedAborts> {
+    let name = format!("{}::{}", module, function);
+    if let Some(expected) = REGISTERED.lock().unwrap().get(&name) {
+        return Some(expected.clone());
+    }
+    crate::MOVE_FUZZ_EXPECTED_ABORTS
+        .get()
+        .and_then(|declared| declared.get(&name))
+        .cloned()
+}
+
+///
+/// Checks the outcome of a call to `module::function`, `error` if it failed,
+/// against the declared expectations.
+///
+/// Without a declaration any failure is unexpected, as it always was.
+///
+pub(crate) fn check(module: &str, function: &str, error: Option<&VMError>) -> Result<()> {
+    let Some(expected) = expected(module, function) else {
+        return match error {
+            Some(error) => bail!(
+                "{}::{} failed with {:?}",
+                module,
+                function,
+                error.major_status()
+            ),
+            None => Ok(()),
+        };
+    };
+    match error {
+        None if expected.success => Ok(()),
+        None => bail!("{}::{} succeeded instead of aborting", module, function),
+        Some(error) => match (error.major_status(), error.sub_status()) {
+            (StatusCode::ABORTED, Some(code)) if expected.codes.contains(&code) => Ok(()),
+            (StatusCode::ABORTED, Some(code)) => bail!(
+                "{}::{} aborted with unexpected code {}",
+                module,
+                function,
+                code
+            ),
+            (status, _) => bail!("{}::{} failed with {:?}", module, function, status),
+        },
+    }
+}
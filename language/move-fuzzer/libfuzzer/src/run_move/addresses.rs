@@ -0,0 +1,112 @@
+use move_arbitrary::{AddressStrategy, GenConfig, MoveValueGen};
+use move_compiler::compiled_unit::CompiledUnitEnum;
+use move_core_types::account_address::AccountAddress;
+use once_cell::sync::Lazy;
+use std::collections::BTreeSet;
+
+use crate::run_move::package;
+
+///
+/// The accounts signing the calls when `MOVE_FUZZ_SIGNERS` is not set, also
+/// read by `move-fuzz`
+///
+pub const DEFAULT_SIGNERS: &str = include_str!("default_signers.txt");
+
+///
+/// The accounts signing the calls when `MOVE_FUZZ_SIGNERS` is not set
+///
+pub fn default_signers() -> Vec<AccountAddress> {
+    parse_signers(DEFAULT_SIGNERS).expect("the default signers are addresses")
+}
+
+///
+/// Parses the comma-separated addresses of `MOVE_FUZZ_SIGNERS`, e.g. `0xa11ce,0xb0b`
+///
+pub fn parse_signers(list: &str) -> Result<Vec<AccountAddress>, String> {
+    let signers = list
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            AccountAddress::from_hex_literal(address)
+                .map_err(|_| format!("`{}` is not an address, e.g. `0xa11ce`", address))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if signers.is_empty() {
+        return Err("no signers listed".to_owned());
+    }
+    Ok(signers)
+}
+
+///
+/// Parses the comma-separated strategies of `MOVE_FUZZ_ADDRESSES`, e.g. `pool,reserved`
+///
+pub fn parse_strategies(list: &str) -> Result<Vec<AddressStrategy>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            AddressStrategy::from_name(name).ok_or_else(|| {
+                format!(
+                    "unknown address strategy `{}`, expected `pool`, `arbitrary`, `published` or `reserved`",
+                    name
+                )
+            })
+        })
+        .collect()
+}
+
+///
+/// The accounts signing the calls of the harness, from `MOVE_FUZZ_SIGNERS`
+///
+pub fn signers() -> &'static [AccountAddress] {
+    static DEFAULT: Lazy<Vec<AccountAddress>> = Lazy::new(default_signers);
+    crate::MOVE_FUZZ_SIGNERS.get().unwrap_or(&DEFAULT)
+}
+
+///
+/// The generator of the harness: addresses follow the strategies of
+/// `MOVE_FUZZ_ADDRESSES`, any address by default, where the pool is [`signers`]
+///
+pub fn gen() -> &'static MoveValueGen {
+    static GEN: Lazy<MoveValueGen> = Lazy::new(|| {
+        let strategies = crate::MOVE_FUZZ_ADDRESSES
+            .get()
+            .cloned()
+            .unwrap_or_else(|| vec![AddressStrategy::Arbitrary]);
+        let published_addresses = if strategies.contains(&AddressStrategy::Published) {
+            published_addresses()
+        } else {
+            Vec::new()
+        };
+        MoveValueGen::new(GenConfig {
+            address_pool: signers().to_vec(),
+            address_strategies: strategies,
+            published_addresses,
+            ..GenConfig::default()
+        })
+    });
+    &GEN
+}
+
+///
+/// An address drawn from `u` by [`gen`], for the address arguments of the calls
+///
+pub fn address(u: &mut arbitrary::Unstructured) -> arbitrary::Result<AccountAddress> {
+    gen().address(u)
+}
+
+/// The addresses of the modules of the fuzz package and its dependencies.
+fn published_addresses() -> Vec<AccountAddress> {
+    let context =
+        package::context().unwrap_or_else(|e| panic!("could not build the fuzz package: {}", e));
+    let addresses: BTreeSet<_> = context
+        .package()
+        .all_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnitEnum::Module(m) => Some(*m.module.self_id().address()),
+            CompiledUnitEnum::Script(_) => None,
+        })
+        .collect();
+    addresses.into_iter().collect()
+}
@@ -0,0 +1,44 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+pub use move_core_types::value::{MoveStructLayout, MoveTypeLayout, MoveValue};
+
+///
+/// Checks that BCS encoding of `T` round-trips on `data`.
+///
+/// Inputs that do not decode as a `T` are ignored. For the others, encoding
+/// the decoded value must give back exactly `data` (BCS is canonical), and
+/// decoding that encoding must give back the same value.
+///
+pub fn round_trip<T>(data: &[u8])
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let value = match bcs::from_bytes::<T>(data) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let bytes = bcs::to_bytes(&value).expect("a decoded value must encode");
+    assert_eq!(bytes, data, "re-encoding {:?} changed its bytes", value);
+    let decoded: T = bcs::from_bytes(&bytes).expect("an encoded value must decode");
+    assert_eq!(decoded, value, "decoding the encoding of a value changed it");
+}
+
+///
+/// Checks that BCS encoding of Move values of `layout` round-trips on `data`.
+///
+/// Same as [`round_trip`] for a [`MoveValue`] decoded against a layout.
+///
+pub fn round_trip_move(data: &[u8], layout: &MoveTypeLayout) {
+    let value = match MoveValue::simple_deserialize(data, layout) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let bytes = value
+        .simple_serialize()
+        .expect("a decoded Move value must encode");
+    assert_eq!(bytes, data, "re-encoding {:?} changed its bytes", value);
+    let decoded = MoveValue::simple_deserialize(&bytes, layout)
+        .expect("an encoded Move value must decode");
+    assert_eq!(decoded, value, "decoding the encoding of a Move value changed it");
+}
@@ -0,0 +1,550 @@
+use move_binary_format::file_format::{
+    Bytecode, CodeOffset, CompiledModule, ConstantPoolIndex, FieldHandleIndex, FunctionHandleIndex,
+    LocalIndex, SignatureIndex, SignatureToken, StructDefinitionIndex, StructHandleIndex,
+    TableIndex,
+};
+use move_binary_format::errors::VMResult;
+use move_bytecode_verifier::{verify_module_with_config, VerifierConfig};
+use move_core_types::u256::U256;
+use move_core_types::vm_status::StatusCode;
+
+use crate::run_move::mutator::{MoveMutator, MutationRng};
+
+///
+/// The limits the verifier of [`verify`] runs with, set by `MOVE_FUZZ_VERIFIER_LIMITS`
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum VerifierLimits {
+    /// The metering and limits of production, see [`verifier_config`]
+    #[default]
+    Production,
+    /// No metering and no limits
+    Unbounded,
+    /// Both, with a finding when the verdicts differ other than by a limit
+    Compare,
+}
+
+impl VerifierLimits {
+    ///
+    /// Parses the value of `MOVE_FUZZ_VERIFIER_LIMITS`
+    ///
+    pub fn parse(name: &str) -> Result<VerifierLimits, String> {
+        match name.trim() {
+            "production" => Ok(VerifierLimits::Production),
+            "unbounded" => Ok(VerifierLimits::Unbounded),
+            "compare" => Ok(VerifierLimits::Compare),
+            name => Err(format!(
+                "unknown verifier limits `{}`, expected `production`, `unbounded` or `compare`",
+                name
+            )),
+        }
+    }
+}
+
+///
+/// The production configuration of the verifier, where `MOVE_FUZZ_VERIFIER_METER_UNITS`
+/// sets the meter budget of functions and modules and `MOVE_FUZZ_VERIFIER_BACK_EDGES`
+/// the back edges allowed per function
+///
+pub fn verifier_config() -> VerifierConfig {
+    let mut config = VerifierConfig::default();
+    if let Some(&units) = crate::MOVE_FUZZ_VERIFIER_METER_UNITS.get() {
+        config.max_per_fun_meter_units = Some(units);
+        config.max_per_mod_meter_units = Some(units);
+    }
+    if let Some(&back_edges) = crate::MOVE_FUZZ_VERIFIER_BACK_EDGES.get() {
+        config.max_back_edges_per_function = Some(back_edges);
+    }
+    config
+}
+
+///
+/// Runs the bytecode verifier on `data` decoded as a module.
+///
+/// Inputs that do not deserialize and modules the verifier rejects are
+/// expected and ignored; only a panic or a crash of the verifier is a finding.
+/// With `MOVE_FUZZ_VERIFIER_LIMITS=compare`, so is a module verified
+/// differently with and without limits, other than rejected by a limit.
+///
+pub fn verify(data: &[u8]) {
+    let module = match CompiledModule::deserialize(data) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+    match crate::MOVE_FUZZ_VERIFIER_LIMITS.get().copied().unwrap_or_default() {
+        VerifierLimits::Production => {
+            let _ = verify_module_with_config(&verifier_config(), &module);
+        }
+        VerifierLimits::Unbounded => {
+            let _ = verify_module_with_config(&VerifierConfig::unbounded(), &module);
+        }
+        VerifierLimits::Compare => {
+            let limited = verify_module_with_config(&verifier_config(), &module);
+            let unbounded = verify_module_with_config(&VerifierConfig::unbounded(), &module);
+            let (limited, unbounded) = (status(&limited), status(&unbounded));
+            if limited != unbounded && !limited.is_some_and(is_limit) {
+                panic!(
+                    "the verifier verdict depends on its limits\n\
+                     with limits:    {}\n\
+                     without limits: {}",
+                    verdict(limited),
+                    verdict(unbounded)
+                );
+            }
+        }
+    }
+}
+
+/// The status code a verification failed with, `None` if it passed.
+fn status(result: &VMResult<()>) -> Option<StatusCode> {
+    result.as_ref().err().map(|e| e.major_status())
+}
+
+fn verdict(status: Option<StatusCode>) -> String {
+    match status {
+        Some(status) => format!("rejected with {:?}", status),
+        None => "verified".to_owned(),
+    }
+}
+
+/// Whether the verifier rejects with `status` when a module goes over a limit
+/// or its meter budget, which only the production configuration has.
+fn is_limit(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::CONSTRAINT_NOT_SATISFIED | StatusCode::TOO_MANY_BACK_EDGES
+    )
+}
+
+///
+/// Built-in mutator for inputs holding a serialized [`CompiledModule`].
+///
+/// The module is deserialized, mutated at the level of its tables and code
+/// and serialized again, so almost every mutated input gets past the
+/// deserializer and exercises the verifier passes behind it. A mutation is
+/// one of:
+///
+/// * replacing an instruction with another of the same family (`Add` with
+///   `Mul`, `BrTrue` with `BrFalse`, `CastU8` with `CastU64`, ...), swapping,
+///   duplicating or removing instructions, or changing their immediates;
+/// * replacing, wrapping or unwrapping a token of a signature;
+/// * changing the type, or rewriting the bytes, of a constant;
+/// * re-wiring an index (call target, struct definition, local, branch
+///   target, handle signature) to another in-bounds entry of its table.
+///
+#[derive(Debug, Clone, Default)]
+pub struct BytecodeMutator;
+
+impl MoveMutator for BytecodeMutator {
+    type Value = CompiledModule;
+
+    fn decode(&self, data: &[u8]) -> Option<CompiledModule> {
+        CompiledModule::deserialize(data).ok()
+    }
+
+    fn encode(&self, module: &CompiledModule) -> Vec<u8> {
+        let mut binary = Vec::new();
+        match module.serialize_for_version(Some(module.version), &mut binary) {
+            Ok(()) => binary,
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn mutate(&self, module: &mut CompiledModule, rng: &mut MutationRng) {
+        // A mutation can find nothing to act on (e.g. no code), retry with another kind.
+        for _ in 0..8 {
+            let mutated = match rng.below(4) {
+                0 => mutate_code(module, rng),
+                1 => mutate_signature(module, rng),
+                2 => mutate_constant(module, rng),
+                _ => rewire_handle(module, rng),
+            };
+            if mutated {
+                return;
+            }
+        }
+    }
+}
+
+fn mutate_code(module: &mut CompiledModule, rng: &mut MutationRng) -> bool {
+    let tables = Tables::of(module);
+    let with_code = module
+        .function_defs
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| def.code.is_some())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if with_code.is_empty() {
+        return false;
+    }
+    let def = &mut module.function_defs[with_code[rng.below(with_code.len())]];
+    let code = &mut def.code.as_mut().unwrap().code;
+    if code.is_empty() {
+        return false;
+    }
+    let i = rng.below(code.len());
+    match rng.below(5) {
+        0 => code[i] = swap_opcode(&code[i], rng),
+        1 => {
+            let j = rng.below(code.len());
+            code.swap(i, j);
+        }
+        2 => code.insert(i, code[i].clone()),
+        3 if code.len() > 1 => {
+            code.remove(i);
+        }
+        _ => code[i] = rewire_instruction(&code[i], &tables, code.len(), rng),
+    }
+    true
+}
+
+/// Replaces an instruction with another one of the same family and arity.
+fn swap_opcode(instr: &Bytecode, rng: &mut MutationRng) -> Bytecode {
+    use Bytecode::*;
+
+    const ARITHMETIC: &[Bytecode] = &[Add, Sub, Mul, Mod, Div, BitOr, BitAnd, Xor, Shl, Shr];
+    const COMPARISON: &[Bytecode] = &[Eq, Neq, Lt, Gt, Le, Ge, Or, And];
+    const CAST: &[Bytecode] = &[CastU8, CastU16, CastU32, CastU64, CastU128, CastU256];
+    const NULLARY: &[Bytecode] = &[LdTrue, LdFalse, Nop, Ret, Pop, Abort, ReadRef, WriteRef];
+
+    let pick = |family: &[Bytecode], rng: &mut MutationRng| family[rng.below(family.len())].clone();
+    match instr {
+        i if ARITHMETIC.contains(i) => pick(ARITHMETIC, rng),
+        i if COMPARISON.contains(i) => pick(COMPARISON, rng),
+        i if CAST.contains(i) => pick(CAST, rng),
+        i if NULLARY.contains(i) => pick(NULLARY, rng),
+        BrTrue(o) => [BrFalse(*o), Branch(*o)][rng.below(2)].clone(),
+        BrFalse(o) => [BrTrue(*o), Branch(*o)][rng.below(2)].clone(),
+        Branch(o) => [BrTrue(*o), BrFalse(*o)][rng.below(2)].clone(),
+        CopyLoc(l) => [MoveLoc(*l), StLoc(*l), ImmBorrowLoc(*l)][rng.below(3)].clone(),
+        MoveLoc(l) => [CopyLoc(*l), StLoc(*l), MutBorrowLoc(*l)][rng.below(3)].clone(),
+        StLoc(l) => [CopyLoc(*l), MoveLoc(*l)][rng.below(2)].clone(),
+        MutBorrowLoc(l) => ImmBorrowLoc(*l),
+        ImmBorrowLoc(l) => MutBorrowLoc(*l),
+        MutBorrowField(f) => ImmBorrowField(*f),
+        ImmBorrowField(f) => MutBorrowField(*f),
+        Pack(s) => Unpack(*s),
+        Unpack(s) => Pack(*s),
+        MutBorrowGlobal(s) => [ImmBorrowGlobal(*s), Exists(*s), MoveFrom(*s)][rng.below(3)].clone(),
+        ImmBorrowGlobal(s) => [MutBorrowGlobal(*s), Exists(*s), MoveTo(*s)][rng.below(3)].clone(),
+        Exists(s) | MoveFrom(s) | MoveTo(s) => {
+            [Exists(*s), MoveFrom(*s), MoveTo(*s)][rng.below(3)].clone()
+        }
+        VecLen(s) | VecImmBorrow(s) | VecMutBorrow(s) | VecPushBack(s) | VecPopBack(s)
+        | VecSwap(s) => [
+            VecLen(*s),
+            VecImmBorrow(*s),
+            VecMutBorrow(*s),
+            VecPushBack(*s),
+            VecPopBack(*s),
+            VecSwap(*s),
+        ][rng.below(6)]
+        .clone(),
+        VecPack(s, n) => VecUnpack(*s, *n),
+        VecUnpack(s, n) => VecPack(*s, *n),
+        LdU8(_) | LdU16(_) | LdU32(_) | LdU64(_) | LdU128(_) | LdU256(_) => load_int(rng),
+        other => other.clone(),
+    }
+}
+
+/// An integer load of a random width, biased towards boundary values.
+fn load_int(rng: &mut MutationRng) -> Bytecode {
+    let n = match rng.below(4) {
+        0 => 0,
+        1 => u64::MAX,
+        2 => rng.below(16) as u64,
+        _ => rng.next_u64(),
+    };
+    match rng.below(6) {
+        0 => Bytecode::LdU8(n as u8),
+        1 => Bytecode::LdU16(n as u16),
+        2 => Bytecode::LdU32(n as u32),
+        3 => Bytecode::LdU64(n),
+        4 => Bytecode::LdU128(u128::from(n) << rng.below(64)),
+        _ => Bytecode::LdU256(U256::from(n)),
+    }
+}
+
+/// Sizes of the tables an index can point into.
+struct Tables {
+    function_handles: usize,
+    struct_defs: usize,
+    field_handles: usize,
+    constants: usize,
+    signatures: usize,
+}
+
+impl Tables {
+    fn of(module: &CompiledModule) -> Self {
+        Self {
+            function_handles: module.function_handles.len(),
+            struct_defs: module.struct_defs.len(),
+            field_handles: module.field_handles.len(),
+            constants: module.constant_pool.len(),
+            signatures: module.signatures.len(),
+        }
+    }
+}
+
+/// A random in-bounds index into a table of `len` entries, `old` if the table is empty.
+fn index(len: usize, old: TableIndex, rng: &mut MutationRng) -> TableIndex {
+    if len == 0 {
+        old
+    } else {
+        rng.below(len) as TableIndex
+    }
+}
+
+/// Points the immediate of an instruction to another entry of the same table.
+fn rewire_instruction(
+    instr: &Bytecode,
+    tables: &Tables,
+    code_len: usize,
+    rng: &mut MutationRng,
+) -> Bytecode {
+    use Bytecode::*;
+
+    let offset = |rng: &mut MutationRng| rng.below(code_len) as CodeOffset;
+    // Local indices are not bounded by a table, stay close to the usual counts.
+    let local = |rng: &mut MutationRng| rng.below(8) as LocalIndex;
+    match instr {
+        BrTrue(_) => BrTrue(offset(rng)),
+        BrFalse(_) => BrFalse(offset(rng)),
+        Branch(_) => Branch(offset(rng)),
+        CopyLoc(_) => CopyLoc(local(rng)),
+        MoveLoc(_) => MoveLoc(local(rng)),
+        StLoc(_) => StLoc(local(rng)),
+        MutBorrowLoc(_) => MutBorrowLoc(local(rng)),
+        ImmBorrowLoc(_) => ImmBorrowLoc(local(rng)),
+        Call(f) => Call(FunctionHandleIndex(index(
+            tables.function_handles,
+            f.0,
+            rng,
+        ))),
+        LdConst(c) => LdConst(ConstantPoolIndex(index(tables.constants, c.0, rng))),
+        MutBorrowField(f) => {
+            MutBorrowField(FieldHandleIndex(index(tables.field_handles, f.0, rng)))
+        }
+        ImmBorrowField(f) => {
+            ImmBorrowField(FieldHandleIndex(index(tables.field_handles, f.0, rng)))
+        }
+        Pack(s) => Pack(struct_def(tables, *s, rng)),
+        Unpack(s) => Unpack(struct_def(tables, *s, rng)),
+        MutBorrowGlobal(s) => MutBorrowGlobal(struct_def(tables, *s, rng)),
+        ImmBorrowGlobal(s) => ImmBorrowGlobal(struct_def(tables, *s, rng)),
+        Exists(s) => Exists(struct_def(tables, *s, rng)),
+        MoveFrom(s) => MoveFrom(struct_def(tables, *s, rng)),
+        MoveTo(s) => MoveTo(struct_def(tables, *s, rng)),
+        VecPack(s, n) => VecPack(signature(tables, *s, rng), *n ^ rng.below(4) as u64),
+        VecUnpack(s, n) => VecUnpack(signature(tables, *s, rng), *n ^ rng.below(4) as u64),
+        VecLen(s) => VecLen(signature(tables, *s, rng)),
+        VecPushBack(s) => VecPushBack(signature(tables, *s, rng)),
+        VecPopBack(s) => VecPopBack(signature(tables, *s, rng)),
+        other => swap_opcode(other, rng),
+    }
+}
+
+fn struct_def(
+    tables: &Tables,
+    old: StructDefinitionIndex,
+    rng: &mut MutationRng,
+) -> StructDefinitionIndex {
+    StructDefinitionIndex(index(tables.struct_defs, old.0, rng))
+}
+
+fn signature(tables: &Tables, old: SignatureIndex, rng: &mut MutationRng) -> SignatureIndex {
+    SignatureIndex(index(tables.signatures, old.0, rng))
+}
+
+fn mutate_signature(module: &mut CompiledModule, rng: &mut MutationRng) -> bool {
+    let struct_handles = module.struct_handles.len();
+    if module.signatures.is_empty() {
+        return false;
+    }
+    let i = rng.below(module.signatures.len());
+    let tokens = &mut module.signatures[i].0;
+    if tokens.is_empty() || rng.below(4) == 0 {
+        tokens.push(random_token(struct_handles, rng));
+        return true;
+    }
+    let j = rng.below(tokens.len());
+    let token = &tokens[j];
+    tokens[j] = match rng.below(4) {
+        0 => random_token(struct_handles, rng),
+        1 => SignatureToken::Vector(Box::new(token.clone())),
+        2 => match token {
+            SignatureToken::Reference(t) => SignatureToken::MutableReference(t.clone()),
+            SignatureToken::MutableReference(t) => SignatureToken::Reference(t.clone()),
+            t => SignatureToken::Reference(Box::new(t.clone())),
+        },
+        _ => match token {
+            SignatureToken::Vector(t)
+            | SignatureToken::Reference(t)
+            | SignatureToken::MutableReference(t) => (**t).clone(),
+            SignatureToken::StructInstantiation(s, _) => SignatureToken::Struct(*s),
+            t => t.clone(),
+        },
+    };
+    true
+}
+
+fn random_token(struct_handles: usize, rng: &mut MutationRng) -> SignatureToken {
+    match rng.below(12) {
+        0 => SignatureToken::Bool,
+        1 => SignatureToken::U8,
+        2 => SignatureToken::U16,
+        3 => SignatureToken::U32,
+        4 => SignatureToken::U64,
+        5 => SignatureToken::U128,
+        6 => SignatureToken::U256,
+        7 => SignatureToken::Address,
+        8 => SignatureToken::Signer,
+        9 => SignatureToken::TypeParameter(rng.below(2) as u16),
+        _ if struct_handles > 0 => {
+            SignatureToken::Struct(StructHandleIndex(rng.below(struct_handles) as TableIndex))
+        }
+        _ => SignatureToken::Vector(Box::new(SignatureToken::U8)),
+    }
+}
+
+fn mutate_constant(module: &mut CompiledModule, rng: &mut MutationRng) -> bool {
+    let struct_handles = module.struct_handles.len();
+    if module.constant_pool.is_empty() {
+        return false;
+    }
+    let i = rng.below(module.constant_pool.len());
+    let constant = &mut module.constant_pool[i];
+    match rng.below(4) {
+        0 => constant.type_ = random_token(struct_handles, rng),
+        1 if !constant.data.is_empty() => {
+            let j = rng.below(constant.data.len());
+            constant.data[j] ^= 1 << rng.below(8);
+        }
+        2 if !constant.data.is_empty() => {
+            let len = rng.below(constant.data.len());
+            constant.data.truncate(len);
+        }
+        _ => constant.data.push(rng.next_u64() as u8),
+    }
+    true
+}
+
+fn rewire_handle(module: &mut CompiledModule, rng: &mut MutationRng) -> bool {
+    let tables = Tables::of(module);
+    match rng.below(3) {
+        0 if !module.function_handles.is_empty() => {
+            let i = rng.below(module.function_handles.len());
+            let handle = &mut module.function_handles[i];
+            if rng.coin() {
+                handle.parameters = signature(&tables, handle.parameters, rng);
+            } else {
+                handle.return_ = signature(&tables, handle.return_, rng);
+            }
+        }
+        1 if !module.field_handles.is_empty() => {
+            let i = rng.below(module.field_handles.len());
+            let handle = &mut module.field_handles[i];
+            handle.owner = struct_def(&tables, handle.owner, rng);
+            handle.field = handle.field.wrapping_add(1) % 4;
+        }
+        _ if !module.function_defs.is_empty() => {
+            let i = rng.below(module.function_defs.len());
+            let def = &mut module.function_defs[i];
+            if let (true, Some(code)) = (rng.coin(), def.code.as_mut()) {
+                code.locals = signature(&tables, code.locals, rng);
+            } else {
+                def.function =
+                    FunctionHandleIndex(index(tables.function_handles, def.function.0, rng));
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_binary_format::file_format::basic_test_module;
+
+    #[test]
+    fn parse_verifier_limits() {
+        assert_eq!(
+            VerifierLimits::parse(" compare\n"),
+            Ok(VerifierLimits::Compare)
+        );
+        assert_eq!(
+            VerifierLimits::parse("unbounded"),
+            Ok(VerifierLimits::Unbounded)
+        );
+        assert!(VerifierLimits::parse("strict").is_err());
+    }
+
+    #[test]
+    fn verify_ignores_what_does_not_deserialize() {
+        verify(&[0xa1, 0x1c, 0xeb, 0x0b, 0xff]);
+        verify(&BytecodeMutator.encode(&basic_test_module()));
+    }
+
+    #[test]
+    fn encoded_modules_decode() {
+        let module = basic_test_module();
+        let decoded = BytecodeMutator.decode(&BytecodeMutator.encode(&module));
+        assert_eq!(decoded, Some(module));
+    }
+
+    #[test]
+    fn mutations_change_the_module() {
+        let changed = (0..50).any(|seed| {
+            let mut mutated = basic_test_module();
+            BytecodeMutator.mutate(&mut mutated, &mut MutationRng::new(seed));
+            mutated != basic_test_module()
+        });
+        assert!(changed);
+    }
+
+    #[test]
+    fn swapped_opcodes_stay_in_their_family() {
+        let mut rng = MutationRng::new(3);
+        for _ in 0..100 {
+            assert!(matches!(
+                swap_opcode(&Bytecode::CastU8, &mut rng),
+                Bytecode::CastU8
+                    | Bytecode::CastU16
+                    | Bytecode::CastU32
+                    | Bytecode::CastU64
+                    | Bytecode::CastU128
+                    | Bytecode::CastU256
+            ));
+            assert!(matches!(
+                swap_opcode(&Bytecode::BrTrue(4), &mut rng),
+                Bytecode::BrFalse(4) | Bytecode::Branch(4)
+            ));
+        }
+    }
+
+    #[test]
+    fn rewired_instructions_stay_in_bounds() {
+        let tables = Tables::of(&basic_test_module());
+        let mut rng = MutationRng::new(7);
+        for _ in 0..100 {
+            match rewire_instruction(&Bytecode::Branch(0), &tables, 3, &mut rng) {
+                Bytecode::Branch(offset) => assert!(offset < 3),
+                other => panic!("unexpected {:?}", other),
+            }
+            let pack = Bytecode::Pack(StructDefinitionIndex(0));
+            match rewire_instruction(&pack, &tables, 3, &mut rng) {
+                Bytecode::Pack(def) => assert_eq!(def, StructDefinitionIndex(0)),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn empty_tables_keep_the_index() {
+        let mut rng = MutationRng::new(1);
+        assert_eq!(index(0, 5, &mut rng), 5);
+        assert!((0..100).all(|_| index(2, 5, &mut rng) < 2));
+    }
+}
@@ -0,0 +1,17 @@
+use move_compiler::{shared::NumericalAddress, Compiler};
+use std::collections::BTreeMap;
+
+///
+/// Compiles `source` as a standalone Move package up to bytecode generation.
+///
+/// Diagnostics are expected for most generated programs and are ignored; only
+/// a panic or a crash of the compiler is a finding.
+///
+pub fn check_source(source: &str) {
+    let path = std::env::temp_dir().join(format!("move-fuzz-{}.move", std::process::id()));
+    std::fs::write(&path, source)
+        .unwrap_or_else(|e| panic!("failed to write to {}: {}", path.display(), e));
+    let targets = vec![path.to_string_lossy().to_string()];
+    let _ = Compiler::from_files(targets, vec![], BTreeMap::<String, NumericalAddress>::new())
+        .check();
+}
@@ -0,0 +1,12 @@
+// Bounds of the SanitizerCoverage inline 8-bit counters of the binary.
+//
+// The linker only defines the `__start_`/`__stop_` symbols when the section
+// exists, i.e. when the binary is built with coverage instrumentation, hence
+// the weak references: both resolve to NULL in uninstrumented builds.
+
+extern unsigned char __start___sancov_cntrs[] __attribute__((weak));
+extern unsigned char __stop___sancov_cntrs[] __attribute__((weak));
+
+unsigned char *move_fuzz_counters_start(void) { return __start___sancov_cntrs; }
+
+unsigned char *move_fuzz_counters_stop(void) { return __stop___sancov_cntrs; }
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use move_coverage::coverage_map::{output_map_to_file, CoverageMap};
+use std::path::Path;
+
+///
+/// Writes the Move VM trace of `MOVE_VM_TRACE` to `MOVE_FUZZ_COVERAGE_MAP` as a
+/// coverage map, registered with `atexit` so it covers every input run.
+///
+/// The VM only traces in builds with debug assertions, and leaves the code of
+/// scripts out of coverage maps.
+///
+pub extern "C" fn write_at_exit() {
+    if let Err(e) = write() {
+        eprintln!("could not write the Move coverage map: {:#}", e);
+    }
+}
+
+fn write() -> Result<()> {
+    let (map, trace) = match (
+        crate::MOVE_FUZZ_COVERAGE_MAP.get(),
+        std::env::var_os("MOVE_VM_TRACE"),
+    ) {
+        (Some(map), Some(trace)) => (map, trace),
+        _ => return Ok(()),
+    };
+    // No input executed a Move instruction.
+    if !Path::new(&trace).exists() {
+        return Ok(());
+    }
+    let coverage = CoverageMap::from_trace_file(Path::new(&trace));
+    output_map_to_file(map, &coverage)
+}
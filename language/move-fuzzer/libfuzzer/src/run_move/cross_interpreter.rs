@@ -0,0 +1,153 @@
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::errors::VMResult;
+use move_binary_format::CompiledModule;
+use move_core_types::effects::ChangeSet;
+use move_core_types::identifier::IdentStr;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_core_types::value::MoveValue;
+use move_stackless_bytecode_interpreter::concrete::value::GlobalState;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::fmt::Write;
+
+use crate::run_move::move_args::ToTransactionArgument;
+use crate::run_move::sequence::TxnSequence;
+use crate::run_move::spec::{move_value, INTERPRETER};
+use crate::run_move::{natives, package};
+
+///
+/// Runs the calls of `sequence` both in the VM and in the stackless bytecode
+/// interpreter of the Move Prover, panicking at the first call they disagree on.
+///
+pub fn run(sequence: TxnSequence) {
+    if let Err(e) = compare(&sequence) {
+        panic!("{}", e);
+    }
+}
+
+///
+/// Runs the calls of `sequence` in the VM, over an in-memory storage with the
+/// package published, and in the interpreter, over a global state of its own.
+///
+/// Each call must fail with the same status and abort code in both, or succeed
+/// in both with the same return values and the same changes to resources. The
+/// changes of a successful call are kept for the calls after it.
+///
+pub fn compare(sequence: &TxnSequence) -> Result<()> {
+    let context = package::context()?;
+    let mut storage = InMemoryStorage::new();
+    for unit in context.package().all_modules() {
+        let bytes = unit.unit.serialize(None);
+        let module = CompiledModule::deserialize(&bytes)
+            .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
+        storage.publish_or_overwrite_module(module.self_id(), bytes);
+    }
+    let vm = MoveVM::new(natives()).map_err(|e| anyhow!("{:?}", e))?;
+    let mut state = GlobalState::default();
+
+    for (i, call) in sequence.calls.iter().enumerate() {
+        // Calls are to the root package, as in sequences.
+        let module = &package::root_module(&context, &call.module)?;
+        let function = IdentStr::new(&call.function)?;
+        let mut signers = Vec::new();
+        for &idx in &call.signers {
+            let signer = sequence
+                .accounts
+                .get(idx)
+                .ok_or_else(|| anyhow!("signer index {} is out of bounds", idx))?;
+            signers.push(*signer);
+        }
+        let args = call.args.to_transaction_argument();
+
+        let mut vm_args: Vec<Vec<u8>> = signers
+            .iter()
+            .map(|signer| MoveValue::Signer(*signer).simple_serialize().unwrap())
+            .collect();
+        vm_args.extend(convert_txn_args(&args));
+        let (vm_result, vm_changes) = {
+            let mut session = vm.new_session(&storage);
+            let result = session
+                .execute_entry_function(
+                    module,
+                    function,
+                    call.type_args.clone(),
+                    vm_args,
+                    &mut UnmeteredGasMeter,
+                )
+                .map(|values| {
+                    values
+                        .return_values
+                        .into_iter()
+                        .map(|(bytes, _layout)| bytes)
+                        .collect::<Vec<_>>()
+                });
+            let changes = match result {
+                Ok(_) => Some(
+                    session
+                        .finish()
+                        .map_err(|e| anyhow!("{:?}", e.into_vm_status()))?
+                        .0,
+                ),
+                Err(_) => None,
+            };
+            (result, changes)
+        };
+
+        let interpreter_args: Vec<_> = signers
+            .into_iter()
+            .map(MoveValue::Signer)
+            .chain(args.into_iter().map(move_value))
+            .collect();
+        let (interpreter_result, interpreter_changes, new_state) =
+            INTERPRETER.with(|interpreter| {
+                interpreter.interpret(module, function, &call.type_args, &interpreter_args, &state)
+            });
+
+        let vm_outcome = outcome(&vm_result, vm_changes.as_ref())?;
+        let interpreter_outcome = outcome(&interpreter_result, Some(&interpreter_changes))?;
+        if vm_outcome != interpreter_outcome {
+            let mut report = format!(
+                "the VM and the stackless bytecode interpreter disagree on call {} ({}::{})\n",
+                i, call.module, call.function
+            );
+            for (engine, outcome) in [("vm", vm_outcome), ("interpreter", interpreter_outcome)] {
+                writeln!(report, "{}:", engine)?;
+                for line in outcome.lines() {
+                    writeln!(report, "    {}", line)?;
+                }
+            }
+            bail!(report);
+        }
+
+        if let Some(changes) = vm_changes {
+            storage.apply(changes)?;
+            state = new_state;
+        }
+    }
+    Ok(())
+}
+
+/// What tells a call apart from outside: its status and abort code if it
+/// failed, its return values and changes to resources if it did not.
+fn outcome(result: &VMResult<Vec<Vec<u8>>>, changes: Option<&ChangeSet>) -> Result<String> {
+    let mut outcome = String::new();
+    match result {
+        Ok(values) => {
+            writeln!(outcome, "status: EXECUTED")?;
+            for bytes in values {
+                writeln!(outcome, "return: {:02x?}", bytes)?;
+            }
+            for (address, tag, op) in changes.into_iter().flat_map(ChangeSet::resources) {
+                writeln!(outcome, "resource {}::{}: {:02x?}", address, tag, op)?;
+            }
+        }
+        Err(err) => {
+            writeln!(outcome, "status: {:?}", err.major_status())?;
+            if let Some(code) = err.sub_status() {
+                writeln!(outcome, "abort code: {}", code)?;
+            }
+        }
+    }
+    Ok(outcome)
+}
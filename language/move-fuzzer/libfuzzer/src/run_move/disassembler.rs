@@ -0,0 +1,42 @@
+use move_binary_format::{binary_views::BinaryIndexedView, file_format::CompiledModule};
+use move_disassembler::disassembler::Disassembler;
+use move_ir_types::location::Spanned;
+
+///
+/// Disassembles `data` decoded as a verified module and checks the listing survives a round trip.
+///
+/// The module is serialized and deserialized again, and the two listings must
+/// be identical. Inputs that do not deserialize or verify are ignored; a
+/// panic, a disassembler error on a verified module or a listing mismatch is a
+/// finding.
+///
+pub fn round_trip(data: &[u8]) {
+    let module = match CompiledModule::deserialize(data) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+    if move_bytecode_verifier::verify_module(&module).is_err() {
+        return;
+    }
+    let listing = disassemble(&module);
+
+    let mut binary = Vec::new();
+    module
+        .serialize_for_version(Some(module.version), &mut binary)
+        .expect("a verified module must serialize");
+    let reloaded = CompiledModule::deserialize(&binary)
+        .expect("a serialized verified module must deserialize");
+    let reloaded_listing = disassemble(&reloaded);
+
+    assert_eq!(
+        listing, reloaded_listing,
+        "disassembly differs after a serialization round trip"
+    );
+}
+
+fn disassemble(module: &CompiledModule) -> String {
+    let no_loc = Spanned::unsafe_no_loc(()).loc;
+    Disassembler::from_view(BinaryIndexedView::Module(module), no_loc)
+        .and_then(|d| d.disassemble())
+        .unwrap_or_else(|e| panic!("failed to disassemble a verified module: {}", e))
+}
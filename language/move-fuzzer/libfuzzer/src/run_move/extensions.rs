@@ -0,0 +1,230 @@
+use anyhow::{anyhow, Context, Result};
+use arbitrary::Unstructured;
+use move_binary_format::CompiledModule;
+use move_cli::sandbox::utils::get_gas_status;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::effects::{ChangeSet, Op};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::resolver::ModuleResolver;
+use move_core_types::transaction_argument::{convert_txn_args, TransactionArgument};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::{DeltaStorage, InMemoryStorage};
+use once_cell::unsync::OnceCell;
+
+use crate::run_move::move_args::{is_passable, MoveArg, ToTransactionArgument};
+use crate::run_move::natives::{self, NativePackage};
+use crate::run_move::outcome::{self, Outcome};
+use crate::run_move::{frames, package, script_file};
+
+thread_local! {
+    static RUNNER: OnceCell<ExtensionRunner> = OnceCell::new();
+}
+
+///
+/// The state of the VM extensions an input starts from, drawn from its first bytes.
+///
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExtensionState {
+    /// The hash of the transaction, which the handles of new tables derive from
+    pub txn_hash: [u8; 32],
+    /// For the first address arguments of the script, in order, the counter
+    /// of the `std::guid::Generator` published under it if any, which the
+    /// event handles created for that account continue from
+    pub guid_counters: Vec<Option<u32>>,
+}
+
+impl ExtensionState {
+    /// The most address arguments given a `Generator`
+    const MAX_GENERATORS: usize = 4;
+
+    fn arbitrary(u: &mut Unstructured) -> Self {
+        let mut state = ExtensionState::default();
+        // Short inputs leave the rest of the state empty, which cannot fail.
+        let _ = u.fill_buffer(&mut state.txn_hash);
+        let generators = u.int_in_range(0..=Self::MAX_GENERATORS).unwrap_or(0);
+        state.guid_counters = (0..generators)
+            .map(|_| u.arbitrary().unwrap_or_default())
+            .collect();
+        state
+    }
+
+    /// The resources to publish before the script runs, under the `addresses`
+    /// it takes.
+    fn resources(&self, addresses: &[AccountAddress]) -> Result<ChangeSet> {
+        let tag = StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("guid")?,
+            name: Identifier::new("Generator")?,
+            type_params: vec![],
+        };
+        let mut resources = ChangeSet::new();
+        for (address, counter) in addresses.iter().zip(&self.guid_counters) {
+            if let Some(counter) = counter {
+                let blob = bcs::to_bytes(&u64::from(*counter))?;
+                // Addresses passed twice keep the counter drawn first.
+                let _ = resources.add_resource_op(*address, tag.clone(), Op::New(blob));
+            }
+        }
+        Ok(resources)
+    }
+}
+
+///
+/// A VM and an in-memory global storage with the package published, whose
+/// sessions carry the native extensions of the packages of `MOVE_FUZZ_NATIVES`
+/// and of those the harness wires in.
+///
+/// Unlike the storage of stateful targets, effects are never committed: every
+/// input runs against the same storage, with the resources of its
+/// [`ExtensionState`] on top.
+///
+struct ExtensionRunner {
+    vm: MoveVM,
+    storage: InMemoryStorage,
+    script: Vec<u8>,
+    /// Whether the package depends on `std::guid`, which event handles need
+    events: bool,
+}
+
+impl ExtensionRunner {
+    fn new(packages: &[NativePackage]) -> Result<Self> {
+        let context = package::context()?;
+        let mut storage = InMemoryStorage::new();
+        for unit in context.package().all_modules() {
+            let bytes = unit.unit.serialize(None);
+            let module = CompiledModule::deserialize(&bytes)
+                .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
+            storage.publish_or_overwrite_module(module.self_id(), bytes);
+        }
+        let guid = ModuleId::new(AccountAddress::ONE, Identifier::new("guid")?);
+        let events = matches!(storage.get_module(&guid), Ok(Some(_)));
+
+        let script_file = script_file();
+        let script = std::fs::read(&script_file)
+            .with_context(|| format!("could not read script {}", script_file.display()))?;
+
+        Ok(Self {
+            vm: MoveVM::new(natives::natives_with(packages)).map_err(|e| anyhow!("{:?}", e))?,
+            storage,
+            script,
+            events,
+        })
+    }
+
+    ///
+    /// Runs the script on `data` in a session over the storage with the
+    /// resources of `state` on top, returning its outcome, the gas it used out
+    /// of `gas_budget` and, if it failed, the failure with its Move frames.
+    ///
+    fn execute(
+        &self,
+        data: MoveArg,
+        state: &ExtensionState,
+        gas_budget: Option<u64>,
+    ) -> Result<(Outcome, u64, Option<String>)> {
+        let txn_args = data.to_transaction_argument();
+        let resources = if self.events {
+            let addresses: Vec<_> = txn_args
+                .iter()
+                .filter_map(|arg| match arg {
+                    TransactionArgument::Address(address) => Some(*address),
+                    _ => None,
+                })
+                .collect();
+            state.resources(&addresses)?
+        } else {
+            ChangeSet::new()
+        };
+        let storage = DeltaStorage::new(&self.storage, &resources);
+        let args = convert_txn_args(&txn_args);
+        let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
+        let mut gas_status = get_gas_status(cost_table, gas_budget)?;
+        let budget = u64::from(gas_status.remaining_gas());
+
+        let extensions = natives::new_extensions(state.txn_hash, &storage);
+        let mut session = self.vm.new_session_with_extensions(&storage, extensions);
+        let (outcome, failure) =
+            match session.execute_script(self.script.as_slice(), vec![], args, &mut gas_status) {
+                Ok(values) => {
+                    // Finishing checks the events and table changes of the
+                    // session can be turned into effects, as they would be
+                    // before being committed.
+                    let (_changeset, events, extensions) = session
+                        .finish_with_extensions()
+                        .map_err(|e| anyhow!("{:?}", e.into_vm_status()))?;
+                    #[cfg(feature = "table-extension")]
+                    natives::table_changes(extensions)?;
+                    #[cfg(not(feature = "table-extension"))]
+                    drop(extensions);
+                    let outcome = Outcome::Executed {
+                        return_values: values
+                            .return_values
+                            .into_iter()
+                            .map(|(bytes, _layout)| bytes)
+                            .collect(),
+                        events: events.iter().map(|event| format!("{:?}", event)).collect(),
+                    };
+                    (outcome, None)
+                }
+                Err(err) => {
+                    let failure = frames::describe(&err, &storage);
+                    let outcome = Outcome::Failed {
+                        status: err.major_status(),
+                        sub_status: err.sub_status(),
+                        location: format!("{:?}", err.location()),
+                    };
+                    (outcome, Some(failure))
+                }
+            };
+        let gas_used = budget - u64::from(gas_status.remaining_gas());
+        Ok((outcome, gas_used, failure))
+    }
+}
+
+///
+/// Runs the script of the target on the arguments `get_data` makes of `bytes`,
+/// in a session carrying the native extensions, panicking on failure.
+///
+/// The natives of `packages` are registered on top of those of
+/// `MOVE_FUZZ_NATIVES`. The start of the input is the [`ExtensionState`] of the
+/// session, `get_data` gets the rest. With `MOVE_FUZZ_BYTECODE_VERSIONS`, the
+/// arguments are first compared across the versions as script targets do, and
+/// with `MOVE_FUZZ_OUTCOME_PATH` the outcome is written there instead.
+///
+pub fn run(bytes: &[u8], packages: &[NativePackage], get_data: impl FnOnce(Vec<u8>) -> MoveArg) {
+    let mut u = Unstructured::new(bytes);
+    let state = ExtensionState::arbitrary(&mut u);
+    let data = get_data(u.take_rest().to_vec());
+    if !is_passable(&data) {
+        return;
+    }
+    let outcome_path = crate::MOVE_FUZZ_OUTCOME_PATH.get();
+    if outcome_path.is_none() {
+        if let Some(versions) = crate::MOVE_FUZZ_BYTECODE_VERSIONS.get() {
+            if let Err(e) = outcome::compare_versions(&data, versions) {
+                panic!("{}", e);
+            }
+        }
+    }
+    let result = RUNNER.with(|runner| {
+        let runner = runner.get_or_init(|| {
+            ExtensionRunner::new(packages)
+                .unwrap_or_else(|e| panic!("could not set up the extension runner: {:#}", e))
+        });
+        match outcome_path {
+            Some(path) => {
+                let (outcome, gas_used, _) =
+                    runner.execute(data, &state, Some(outcome::GAS_BUDGET))?;
+                outcome::write(path, &outcome, gas_used)
+            }
+            None => match runner.execute(data, &state, None)? {
+                (_, _, Some(failure)) => Err(anyhow!(failure)),
+                _ => Ok(()),
+            },
+        }
+    });
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,104 @@
+//! The Move frames a failed execution stopped in, for crash reports.
+//!
+//! The VM reports the bytecode offset an execution failed at and, when it
+//! keeps the execution state, the Move call stack leading there. They are
+//! described as lines like
+//!
+//! ```text
+//!     in 0x1::bank::withdraw at code offset 7
+//! ```
+//!
+//! innermost first, which `move-fuzz` splices into the native backtraces so
+//! that reports point at the Move code rather than at the interpreter.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{Location, VMError},
+    file_format::FunctionDefinitionIndex,
+    CompiledModule,
+};
+use move_core_types::{
+    language_storage::ModuleId, resolver::ModuleResolver, vm_status::StatusCode,
+};
+
+/// A Move function being run, and where in its bytecode.
+struct Frame {
+    /// `None` in a script
+    module: Option<ModuleId>,
+    function: FunctionDefinitionIndex,
+    offset: u16,
+}
+
+/// Describes `error` along with the Move frames it failed in, naming their
+/// functions from the modules `modules` resolves.
+pub(crate) fn describe(error: &VMError, modules: &impl ModuleResolver) -> String {
+    let description = match (error.major_status(), error.sub_status()) {
+        (StatusCode::ABORTED, Some(code)) => format!("Move execution aborted with code {}", code),
+        (status, _) => format!("Move execution failed with {:?}", status),
+    };
+    description + &backtrace(error, modules)
+}
+
+/// The lines of the Move frames `error` failed in, each starting with a
+/// newline.
+pub(crate) fn backtrace(error: &VMError, modules: &impl ModuleResolver) -> String {
+    let mut description = String::new();
+    for frame in frames(error) {
+        description.push_str("\n    in ");
+        match &frame.module {
+            Some(id) => {
+                let name = function_name(modules, id, frame.function)
+                    .unwrap_or_else(|| format!("<function #{}>", frame.function.0));
+                description.push_str(&format!(
+                    "0x{}::{}::{}",
+                    id.address().short_str_lossless(),
+                    id.name(),
+                    name
+                ));
+            }
+            None => description.push_str("script"),
+        }
+        description.push_str(&format!(" at code offset {}", frame.offset));
+    }
+    description
+}
+
+/// The frames of `error`, innermost first.
+fn frames(error: &VMError) -> Vec<Frame> {
+    let module = match error.location() {
+        Location::Module(id) => Some(id.clone()),
+        Location::Script | Location::Undefined => None,
+    };
+    // The offsets are those of the frame the error happened in, the stack
+    // trace holds its callers, innermost first.
+    let failing = error.offsets().iter().map(|(function, offset)| Frame {
+        module: module.clone(),
+        function: *function,
+        offset: *offset,
+    });
+    let callers = error
+        .exec_state()
+        .into_iter()
+        .flat_map(|state| state.stack_trace())
+        .map(|(module, function, offset)| Frame {
+            module: module.clone(),
+            function: *function,
+            offset: *offset,
+        });
+    failing.chain(callers).collect()
+}
+
+fn function_name(
+    modules: &impl ModuleResolver,
+    id: &ModuleId,
+    function: FunctionDefinitionIndex,
+) -> Option<String> {
+    let bytes = modules.get_module(id).ok()??;
+    let module = CompiledModule::deserialize(&bytes).ok()?;
+    let def = module.function_defs.get(function.0 as usize)?;
+    Some(
+        module
+            .identifier_at(module.function_handle_at(def.function).name)
+            .to_string(),
+    )
+}
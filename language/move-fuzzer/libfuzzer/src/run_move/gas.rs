@@ -0,0 +1,210 @@
+use anyhow::{anyhow, bail, Context, Result};
+use arbitrary::Unstructured;
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::DEFAULT_STORAGE_DIR;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::gas_schedule::{CostTable, Gas, GasStatus, INITIAL_COST_SCHEDULE};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::run_move::move_args::{is_passable, MoveArg, ToTransactionArgument};
+use crate::run_move::outcome::{self, Outcome};
+use crate::run_move::{natives, package, script_file};
+
+/// Gas budget of a metered execution, high enough to never run out on sane inputs.
+const GAS_BUDGET: u64 = 1_000_000_000;
+
+/// Largest cost of an instruction in a fuzzed gas schedule, low enough for the
+/// cost of an instruction times the size of its operands not to overflow.
+const MAX_FUZZED_COST: u64 = 1 << 20;
+
+/// Largest number of instructions whose costs a fuzz input changes.
+const MAX_FUZZED_COSTS: usize = 16;
+
+struct Execution {
+    vm: MoveVM,
+    state: Rc<OnDiskStateView>,
+    script: Vec<u8>,
+    args: Vec<Vec<u8>>,
+}
+
+impl Execution {
+    fn new(data: MoveArg) -> Result<Self> {
+        let state = package::state(None, Path::new(DEFAULT_STORAGE_DIR))?;
+        let script_file = script_file();
+        let script = std::fs::read(&script_file)
+            .with_context(|| format!("could not read script {}", script_file.display()))?;
+
+        Ok(Self {
+            vm: MoveVM::new(natives()).map_err(|e| anyhow!("{:?}", e))?,
+            state,
+            script,
+            args: convert_txn_args(&data.to_transaction_argument()),
+        })
+    }
+
+    /// Runs the script without committing its effects, returning its outcome and the gas used.
+    fn execute(&self, gas_status: GasStatus) -> Result<(Outcome, u64)> {
+        outcome::execute(
+            &self.vm,
+            &self.state,
+            &self.script,
+            self.args.clone(),
+            gas_status,
+        )
+    }
+}
+
+///
+/// Runs the script of the target on `data` unmetered and twice metered, panicking on divergence.
+///
+/// Metering must neither change the result of an execution, except by running
+/// out of gas, nor charge a different amount for the same execution.
+///
+pub fn run(data: MoveArg) {
+    if !is_passable(&data) {
+        return;
+    }
+    if let Err(e) = run_aux(data, &INITIAL_COST_SCHEDULE, None) {
+        panic!("{}", e);
+    }
+}
+
+///
+/// Runs the script of the target on `data` under two gas schedules, panicking on divergence.
+///
+/// Executions that run out of gas under either schedule are not compared.
+///
+pub fn run_with_schedules(data: MoveArg, schedule: &CostTable, other: &CostTable) {
+    if !is_passable(&data) {
+        return;
+    }
+    if let Err(e) = run_aux(data, schedule, Some(other)) {
+        panic!("{}", e);
+    }
+}
+
+fn run_aux(data: MoveArg, schedule: &CostTable, other: Option<&CostTable>) -> Result<()> {
+    let execution = Execution::new(data)?;
+    let metered = || GasStatus::new(schedule, Gas::new(GAS_BUDGET));
+
+    let (first, first_gas) = execution.execute(metered())?;
+    let (second, second_gas) = execution.execute(metered())?;
+    if first != second || first_gas != second_gas {
+        bail!(
+            "Nondeterministic metered execution: {:?} using {} gas, then {:?} using {} gas",
+            first,
+            first_gas,
+            second,
+            second_gas
+        );
+    }
+
+    let (reference, name) = match other {
+        Some(other) => (
+            execution
+                .execute(GasStatus::new(other, Gas::new(GAS_BUDGET)))?
+                .0,
+            "the other gas schedule",
+        ),
+        None => (
+            execution.execute(GasStatus::new_unmetered())?.0,
+            "an unmetered execution",
+        ),
+    };
+    if !first.out_of_gas() && !reference.out_of_gas() && first != reference {
+        bail!(
+            "Metering changed the outcome: {:?} metered, {:?} under {}",
+            first,
+            reference,
+            name
+        );
+    }
+    Ok(())
+}
+
+///
+/// Runs the script of the target, on the arguments `get_data` makes of `bytes`,
+/// under a gas schedule drawn from the input and under a costlier variant of
+/// it, panicking if charging is not monotonic.
+///
+/// The input starts with the schedule: the costs of a few instructions of the
+/// initial schedule replaced, within sane bounds, then the costs raised in the
+/// variant. A panic in the cost math, such as an overflow, is a crash like any
+/// other. Every cost of the variant is at least that of the schedule, so an
+/// execution must not use less gas under it, or only run out of gas under the
+/// cheaper schedule.
+///
+pub fn run_with_fuzzed_schedule(bytes: &[u8], get_data: impl FnOnce(Vec<u8>) -> MoveArg) {
+    let mut u = Unstructured::new(bytes);
+    let Ok((schedule, raised)) = fuzzed_schedules(&mut u) else {
+        return;
+    };
+    let data = get_data(u.take_rest().to_vec());
+    if !is_passable(&data) {
+        return;
+    }
+    if let Err(e) = check_monotonic(data, &schedule, &raised) {
+        panic!("{}", e);
+    }
+}
+
+/// A schedule with the costs of a few instructions of the initial one drawn
+/// from `u`, and a variant with some of them raised.
+fn fuzzed_schedules(u: &mut Unstructured) -> arbitrary::Result<(CostTable, CostTable)> {
+    let mut schedule = INITIAL_COST_SCHEDULE.clone();
+    let len = schedule.instruction_table.len();
+    for _ in 0..u.int_in_range(0..=MAX_FUZZED_COSTS)? {
+        let cost = &mut schedule.instruction_table[u.choose_index(len)?];
+        cost.instruction_gas = u.int_in_range(0..=MAX_FUZZED_COST)?;
+        cost.memory_gas = u.int_in_range(0..=MAX_FUZZED_COST)?;
+    }
+    let mut raised = schedule.clone();
+    for _ in 0..u.int_in_range(1..=MAX_FUZZED_COSTS)? {
+        let cost = &mut raised.instruction_table[u.choose_index(len)?];
+        cost.instruction_gas += u.int_in_range(0..=MAX_FUZZED_COST)?;
+        cost.memory_gas += u.int_in_range(0..=MAX_FUZZED_COST)?;
+    }
+    Ok((schedule, raised))
+}
+
+fn check_monotonic(data: MoveArg, schedule: &CostTable, raised: &CostTable) -> Result<()> {
+    let execution = Execution::new(data)?;
+    let (outcome, gas) = execution.execute(GasStatus::new(schedule, Gas::new(GAS_BUDGET)))?;
+    let (raised_outcome, raised_gas) =
+        execution.execute(GasStatus::new(raised, Gas::new(GAS_BUDGET)))?;
+
+    if outcome.out_of_gas() {
+        if !raised_outcome.out_of_gas() {
+            bail!(
+                "Non-monotonic charging: out of gas under the fuzzed schedule, \
+                 {:?} using {} gas under a costlier one",
+                raised_outcome,
+                raised_gas
+            );
+        }
+        return Ok(());
+    }
+    if raised_outcome.out_of_gas() {
+        return Ok(());
+    }
+    if outcome != raised_outcome {
+        bail!(
+            "The gas schedule changed the outcome: {:?} under the fuzzed schedule, \
+             {:?} under a costlier one",
+            outcome,
+            raised_outcome
+        );
+    }
+    if raised_gas < gas {
+        bail!(
+            "Non-monotonic charging: {:?} used {} gas under the fuzzed schedule, \
+             {} under a costlier one",
+            outcome,
+            gas,
+            raised_gas
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
+
+/// Frames kept per allocation, enough to reach the loader through the collections
+/// it allocates in.
+const MAX_FRAMES: usize = 24;
+
+static PROFILER: Mutex<Option<dhat::Profiler>> = Mutex::new(None);
+
+///
+/// Starts profiling the allocations of the inputs run, written to `path` on
+/// exit, or before aborting on a crash.
+///
+pub fn start(path: PathBuf) {
+    let profiler = dhat::Profiler::builder()
+        .file_name(path)
+        .trim_backtraces(Some(MAX_FRAMES))
+        .build();
+    *PROFILER.lock().unwrap() = Some(profiler);
+    unsafe {
+        libc::atexit(write_at_exit);
+    }
+}
+
+///
+/// Writes the profile, if still profiling: dropping the profiler writes it.
+///
+pub fn write() {
+    if let Ok(mut profiler) = PROFILER.lock() {
+        drop(profiler.take());
+    }
+}
+
+extern "C" fn write_at_exit() {
+    write();
+}
@@ -1,18 +1,14 @@
 use anyhow::{Result, Error, anyhow};
 use move_cli::{Move, DEFAULT_STORAGE_DIR, sandbox};
-use move_core_types::{account_address::AccountAddress, errmap::ErrorMapping, language_storage::TypeTag, transaction_argument::TransactionArgument};
-use move_stdlib::natives::{all_natives, nursery_natives, GasParameters, NurseryGasParameters};
+use move_core_types::{errmap::ErrorMapping, language_storage::TypeTag, transaction_argument::TransactionArgument};
 use move_package::BuildConfig;
 
 use std::{env, fmt, path::PathBuf, process::Command};
 use std::fmt::Debug;
-use move_cli::sandbox::utils::PackageContext;
-use move_core_types::identifier::Identifier;
 
 use move_core_types::u256::U256;
 use move_core_types::vm_status::StatusCode;
 use move_core_types::vm_status::StatusCode::ABORTED;
-use move_vm_runtime::native_functions::NativeFunction;
 use crate::run_move::move_args::ToTransactionArgument;
 use crate::run_move::move_args::MoveArg;
 
@@ -21,11 +17,167 @@ use crate::run_move::move_args::MoveArg;
 ///
 pub mod move_args;
 
+///
+/// Fuzzing of entry-function call sequences sharing the same accounts and storage
+///
+pub mod sequence;
+
+///
+/// Abort codes entry functions are expected to abort with, the others being findings
+///
+pub mod aborts;
+
+///
+/// Type arguments of generic entry functions, drawn from the types in scope
+///
+pub mod type_args;
+
+///
+/// Fuzzing against a VM and global storage that persist across inputs
+///
+pub mod stateful;
+
+///
+/// Fuzzing in sessions carrying the native extensions, with arbitrary-driven state
+///
+pub mod extensions;
+
+///
+/// Snapshots of the global storage of stateful targets
+///
+pub mod state;
+
+///
+/// Fork-server execution of inputs from a snapshot taken after the setup
+///
+#[cfg(move_fuzz_counters)]
+pub mod snapshot;
+
+///
+/// Compiler harness helpers for source-level fuzz targets
+///
+pub mod compiler;
+
+///
+/// Front-end harness helpers feeding inputs as Move source to the lexer, parser and expansion
+///
+pub mod parser;
+
+///
+/// Structure-aware custom mutators working on decoded Move values
+///
+pub mod mutator;
+
+///
+/// Bytecode-verifier harness helpers and a mutator working on compiled modules
+///
+pub mod bytecode;
+
+///
+/// Module-level reductions of crashing inputs for `move-fuzz tmin --module`
+///
+pub mod reduce;
+
+///
+/// Differential oracle comparing metered and unmetered executions
+///
+pub mod gas;
+
+///
+/// Differential oracle comparing executions with and without paranoid type checks
+///
+pub mod paranoid;
+
+///
+/// Disassembler round-trip harness helpers
+///
+pub mod disassembler;
+
+///
+/// BCS serialization round-trip harness helpers
+///
+pub mod bcs_round_trip;
+
+///
+/// The native functions registered in the VM, selected by `MOVE_FUZZ_NATIVES`
+///
+pub mod natives;
+
+///
+/// The signers and address arguments of the harnesses, set by `MOVE_FUZZ_SIGNERS`
+/// and `MOVE_FUZZ_ADDRESSES`
+///
+pub mod addresses;
+
+///
+/// Oracle checking the spec conditions of the functions called by sequences
+///
+pub mod spec;
+
+///
+/// Differential oracle comparing the VM with the stackless bytecode interpreter
+///
+pub mod cross_interpreter;
+
+///
+/// Move coverage maps of the inputs run, in the format of `move coverage`
+///
+pub mod coverage_map;
+
+///
+/// Allocation profiles of the inputs run, in the format of DHAT's viewer
+///
+#[cfg(feature = "heap-profile")]
+pub mod heap_profile;
+
+///
+/// Seed inputs generated by proptest strategies
+///
+#[cfg(feature = "proptest-seeds")]
+pub mod seeds;
+
+mod outcome;
+
+///
+/// The package of the fuzz directory and its storage, prepared once per process
+///
+mod package;
+
+///
+/// The Move frames of failed executions, for crash reports
+///
+mod frames;
+
+pub(crate) use natives::natives;
+
 
 ///
 /// todo: docs
 ///
 pub fn run(data: MoveArg) {
+    if !move_args::is_passable(&data) {
+        return;
+    }
+    if let Some(path) = crate::MOVE_FUZZ_OUTCOME_PATH.get() {
+        if let Err(e) = outcome::record(data, path) {
+            panic!("{}", e);
+        }
+        return;
+    }
+    if let Some(versions) = crate::MOVE_FUZZ_BYTECODE_VERSIONS.get() {
+        if let Err(e) = outcome::compare_versions(&data, versions) {
+            panic!("{}", e);
+        }
+    }
+    if crate::MOVE_FUZZ_PARANOID.load(std::sync::atomic::Ordering::Relaxed) {
+        if let Err(e) = outcome::compare_paranoid(&data) {
+            panic!("{}", e);
+        }
+    }
+    #[cfg(move_fuzz_counters)]
+    if crate::MOVE_FUZZ_SNAPSHOT.load(std::sync::atomic::Ordering::Relaxed) {
+        return snapshot::run(data);
+    }
     if let Err(e) = run_aux(data) {
         panic!("{}", e);
     }
@@ -46,9 +198,8 @@ fn run_aux(data: MoveArg) -> Result<()> {
     let target_name = target_path.file_name().unwrap();
 
     let target_dir = PathBuf::from("./fuzz");
-    let mut script_file = target_dir.clone();
     //script_file.push(format!("sources/{}.move", target_name.to_str().unwrap()));
-    script_file.push(format!("build/move-fuzz_target/bytecode_scripts/main.mv"));
+    let script_file = script_file();
     let move_args = Move {
         package_path: Some(target_dir),
         verbose: false,
@@ -60,18 +211,14 @@ fn run_aux(data: MoveArg) -> Result<()> {
     let error_descriptions: ErrorMapping = bcs::from_bytes(move_stdlib::error_descriptions())?;
 
     let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
-    let addr = AccountAddress::from_hex_literal("0x1").unwrap();
-    let natives : Vec<(AccountAddress, Identifier, Identifier, NativeFunction)> = all_natives(addr, GasParameters::zeros())
-        .into_iter()
-        .chain(nursery_natives(addr, NurseryGasParameters::zeros()))
-        .collect();
+    let natives = natives();
 
-    let context = PackageContext::new(&move_args.package_path, &move_args.build_config)?;
+    let context = package::context()?;
 
-    let state = context.prepare_state(bytecode_version, &storage_dir)?;
+    let state = package::state(bytecode_version, &storage_dir)?;
 
 
-    match sandbox::commands::run_and_check (
+    let error = sandbox::commands::run_and_report(
         natives,
         cost_table,
         &error_descriptions,
@@ -86,17 +233,16 @@ fn run_aux(data: MoveArg) -> Result<()> {
         bytecode_version,
         dry_run,
         move_args.verbose,
-    ) {
-        Ok(res) => {
-            if ! res {
-                Err(anyhow!("Terminating execution..."))
-            }
-            else {
-                Ok(())
-            }
-        }
-        Err(err) => {
-            Err(err)
-        }
+    )?;
+    match error {
+        Some(error) => Err(anyhow!(
+            "Terminating execution: {}",
+            frames::describe(&error, &*state)
+        )),
+        None => Ok(()),
     }
-}
\ No newline at end of file
+}
+/// The compiled script run by script targets.
+pub(crate) fn script_file() -> PathBuf {
+    PathBuf::from("./fuzz").join("build/move-fuzz_target/bytecode_scripts/main.mv")
+}
@@ -1,6 +1,9 @@
 use std::fmt::Debug;
+use anyhow::Result;
+use move_core_types::account_address::AccountAddress;
 use move_core_types::transaction_argument::TransactionArgument;
 use move_core_types::u256::U256;
+use move_core_types::value::{MoveStruct, MoveValue};
 
 ///
 /// Trait to map which rust type can be an input for a move script
@@ -10,6 +13,14 @@ pub trait ToTransactionArgument: Debug {
     /// Method to convert a type into a transaction argument
     ///
     fn to_transaction_argument(&self) -> Vec<TransactionArgument>;
+
+    ///
+    /// Same as `to_transaction_argument`, failing on values no transaction
+    /// argument holds, such as vectors of anything but `u8`
+    ///
+    fn try_to_transaction_argument(&self) -> Result<Vec<TransactionArgument>> {
+        Ok(self.to_transaction_argument())
+    }
 }
 
 ///
@@ -59,6 +70,12 @@ impl ToTransactionArgument for bool {
     }
 }
 
+impl ToTransactionArgument for AccountAddress {
+    fn to_transaction_argument(&self) -> Vec<TransactionArgument> {
+        return vec![TransactionArgument::Address(*self)]
+    }
+}
+
 impl ToTransactionArgument for Vec<u8> {
     fn to_transaction_argument(&self) -> Vec<TransactionArgument> {
         return vec![TransactionArgument::U8Vector(self.to_vec())]
@@ -75,4 +92,80 @@ impl ToTransactionArgument for Vec<Box<dyn ToTransactionArgument>> {
         }
         res
     }
-}
\ No newline at end of file
+
+    fn try_to_transaction_argument(&self) -> Result<Vec<TransactionArgument>> {
+        let mut res = Vec::new();
+        for arg in self {
+            res.extend(arg.try_to_transaction_argument()?);
+        }
+        Ok(res)
+    }
+}
+
+///
+/// Values no transaction argument holds give none, the harnesses skip the
+/// inputs made of them after checking with `try_to_transaction_argument`.
+///
+impl ToTransactionArgument for MoveValue {
+    fn to_transaction_argument(&self) -> Vec<TransactionArgument> {
+        self.try_to_transaction_argument().unwrap_or_default()
+    }
+
+    fn try_to_transaction_argument(&self) -> Result<Vec<TransactionArgument>> {
+        match self {
+            // Structs cannot be script arguments, pass their fields one by one instead.
+            MoveValue::Struct(MoveStruct::Runtime(fields)) => fields
+                .iter()
+                .map(|f| f.try_to_transaction_argument())
+                .collect::<Result<Vec<_>>>()
+                .map(|args| args.concat()),
+            MoveValue::Struct(MoveStruct::WithFields(fields))
+            | MoveValue::Struct(MoveStruct::WithTypes { fields, .. }) => fields
+                .iter()
+                .map(|(_, f)| f.try_to_transaction_argument())
+                .collect::<Result<Vec<_>>>()
+                .map(|args| args.concat()),
+            MoveValue::Signer(address) => Ok(vec![TransactionArgument::Address(*address)]),
+            value => Ok(vec![TransactionArgument::try_from(value.clone())?]),
+        }
+    }
+}
+
+///
+/// Whether every value of `data` can be passed to a script. The harnesses
+/// skip the inputs that cannot rather than report them.
+///
+pub(crate) fn is_passable(data: &MoveArg) -> bool {
+    data.try_to_transaction_argument().is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn structs_pass_their_fields() {
+        let value = MoveValue::Struct(MoveStruct::Runtime(vec![
+            MoveValue::U64(3),
+            MoveValue::Vector(vec![MoveValue::U8(1), MoveValue::U8(2)]),
+        ]));
+        assert_eq!(
+            value.try_to_transaction_argument().unwrap(),
+            [
+                TransactionArgument::U64(3),
+                TransactionArgument::U8Vector(vec![1, 2])
+            ]
+        );
+    }
+
+    #[test]
+    fn vectors_of_other_values_are_not_passable() {
+        let value = MoveValue::Vector(vec![MoveValue::U64(1)]);
+        assert!(value.try_to_transaction_argument().is_err());
+        assert!(value.to_transaction_argument().is_empty());
+
+        let data: MoveArg = vec![Box::new(1u8), Box::new(value)];
+        assert!(!is_passable(&data));
+        assert!(is_passable(&vec![Box::new(1u8) as Box<dyn ToTransactionArgument>]));
+    }
+}
@@ -0,0 +1,362 @@
+use move_core_types::account_address::AccountAddress;
+use move_core_types::u256::U256;
+use move_core_types::value::{MoveStruct, MoveTypeLayout, MoveValue};
+
+///
+/// A structure-aware mutator plugged into libFuzzer's `LLVMFuzzerCustomMutator`.
+///
+/// The input bytes are decoded into a [`MoveMutator::Value`], mutated in that
+/// form and encoded back, so the fuzzer explores well-formed values instead of
+/// spending its time on byte strings the harness rejects. Inputs that do not
+/// decode fall back to libFuzzer's byte-level mutation.
+///
+/// Mutations must be deterministic: everything random has to come from the
+/// [`MutationRng`] handed to [`MoveMutator::mutate`].
+///
+pub trait MoveMutator {
+    ///
+    /// The decoded form of an input
+    ///
+    type Value;
+
+    ///
+    /// Decodes an input, returning `None` if it is not a valid encoding
+    ///
+    fn decode(&self, data: &[u8]) -> Option<Self::Value>;
+
+    ///
+    /// Encodes a value back into an input the harness can decode
+    ///
+    fn encode(&self, value: &Self::Value) -> Vec<u8>;
+
+    ///
+    /// Applies one mutation to `value`
+    ///
+    fn mutate(&self, value: &mut Self::Value, rng: &mut MutationRng);
+}
+
+///
+/// Deterministic random number generator seeded by libFuzzer for each mutation
+///
+#[derive(Debug, Clone)]
+pub struct MutationRng {
+    state: u64,
+}
+
+impl MutationRng {
+    ///
+    /// Creates a generator from the seed libFuzzer passes to the mutator
+    ///
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: u64::from(seed) ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    ///
+    /// Returns the next random 64-bit value (splitmix64)
+    ///
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    ///
+    /// Returns a value in `0..n`, or `0` if `n` is `0`
+    ///
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+
+    ///
+    /// Returns `true` with probability one half
+    ///
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+}
+
+///
+/// Runs `mutator` on `data[..size]` following the `fuzz_mutator!` contract and returns the new size
+///
+pub fn mutate_with<M: MoveMutator>(
+    mutator: &M,
+    data: &mut [u8],
+    size: usize,
+    max_size: usize,
+    seed: u32,
+) -> usize {
+    let mut rng = MutationRng::new(seed);
+    if let Some(mut value) = mutator.decode(&data[..size]) {
+        mutator.mutate(&mut value, &mut rng);
+        let encoded = mutator.encode(&value);
+        if encoded.len() <= max_size {
+            data[..encoded.len()].copy_from_slice(&encoded);
+            return encoded.len();
+        }
+    }
+    crate::fuzzer_mutate(data, size, max_size)
+}
+
+///
+/// Built-in mutator for inputs holding a single BCS-encoded [`MoveValue`] of a known layout.
+///
+/// Integers get boundary values, bit flips and small increments, booleans are
+/// toggled, addresses get a byte rewritten, vectors grow, shrink and have
+/// elements duplicated, and struct fields are mutated recursively.
+///
+#[derive(Debug, Clone)]
+pub struct ValueMutator {
+    layout: MoveTypeLayout,
+    max_vector_len: usize,
+}
+
+impl ValueMutator {
+    ///
+    /// Creates a mutator for values of `layout`
+    ///
+    pub fn new(layout: MoveTypeLayout) -> Self {
+        Self {
+            layout,
+            max_vector_len: 64,
+        }
+    }
+
+    ///
+    /// Caps the length vectors can grow to
+    ///
+    pub fn max_vector_len(mut self, max_vector_len: usize) -> Self {
+        self.max_vector_len = max_vector_len;
+        self
+    }
+
+    fn mutate_value(&self, value: &mut MoveValue, layout: &MoveTypeLayout, rng: &mut MutationRng) {
+        match (value, layout) {
+            (MoveValue::Bool(b), _) => *b = !*b,
+            (MoveValue::U8(n), _) => *n = mutate_int(u128::from(*n), u8::MAX.into(), rng) as u8,
+            (MoveValue::U16(n), _) => *n = mutate_int(u128::from(*n), u16::MAX.into(), rng) as u16,
+            (MoveValue::U32(n), _) => *n = mutate_int(u128::from(*n), u32::MAX.into(), rng) as u32,
+            (MoveValue::U64(n), _) => *n = mutate_int(u128::from(*n), u64::MAX.into(), rng) as u64,
+            (MoveValue::U128(n), _) => *n = mutate_int(*n, u128::MAX, rng),
+            (MoveValue::U256(n), _) => {
+                let mut bytes = n.to_le_bytes();
+                let i = rng.below(bytes.len());
+                bytes[i] = match rng.below(3) {
+                    0 => 0,
+                    1 => u8::MAX,
+                    _ => bytes[i] ^ (1 << rng.below(8)),
+                };
+                *n = U256::from_le_bytes(&bytes);
+            }
+            (MoveValue::Address(a), _) | (MoveValue::Signer(a), _) => {
+                let mut bytes = a.into_bytes();
+                let i = rng.below(bytes.len());
+                bytes[i] = rng.next_u64() as u8;
+                *a = AccountAddress::new(bytes);
+            }
+            (MoveValue::Vector(elems), MoveTypeLayout::Vector(elem_layout)) => {
+                match rng.below(4) {
+                    0 if !elems.is_empty() => {
+                        let i = rng.below(elems.len());
+                        elems.remove(i);
+                    }
+                    1 if !elems.is_empty() && elems.len() < self.max_vector_len => {
+                        let i = rng.below(elems.len());
+                        elems.insert(i, elems[i].clone());
+                    }
+                    2 if !elems.is_empty() => {
+                        let i = rng.below(elems.len());
+                        self.mutate_value(&mut elems[i], elem_layout, rng);
+                    }
+                    _ if elems.len() < self.max_vector_len => {
+                        // Start from a zero value of the element type and mutate it once.
+                        if let Some(mut elem) = zero_value(elem_layout) {
+                            self.mutate_value(&mut elem, elem_layout, rng);
+                            elems.push(elem);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            (MoveValue::Struct(s), MoveTypeLayout::Struct(struct_layout)) => {
+                let fields = match s {
+                    MoveStruct::Runtime(fields) => fields.iter_mut().collect::<Vec<_>>(),
+                    MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => {
+                        fields.iter_mut().map(|(_, v)| v).collect()
+                    }
+                };
+                let field_layouts = struct_layout.fields();
+                if !fields.is_empty() && fields.len() == field_layouts.len() {
+                    let i = rng.below(fields.len());
+                    self.mutate_value(fields.into_iter().nth(i).unwrap(), &field_layouts[i], rng);
+                }
+            }
+            // The value does not match its layout, leave it alone.
+            _ => {}
+        }
+    }
+}
+
+impl MoveMutator for ValueMutator {
+    type Value = MoveValue;
+
+    fn decode(&self, data: &[u8]) -> Option<MoveValue> {
+        MoveValue::simple_deserialize(data, &self.layout).ok()
+    }
+
+    fn encode(&self, value: &MoveValue) -> Vec<u8> {
+        value.simple_serialize().unwrap_or_default()
+    }
+
+    fn mutate(&self, value: &mut MoveValue, rng: &mut MutationRng) {
+        self.mutate_value(value, &self.layout, rng)
+    }
+}
+
+/// Mutates an unsigned integer bounded by `max`.
+fn mutate_int(n: u128, max: u128, rng: &mut MutationRng) -> u128 {
+    match rng.below(5) {
+        0 => [0, 1, max / 2, max - 1, max][rng.below(5)],
+        1 => n ^ (1 << rng.below((128 - max.leading_zeros()) as usize)),
+        2 => n.wrapping_add(1) & max,
+        3 => n.wrapping_sub(1) & max,
+        _ => u128::from(rng.next_u64()) & max,
+    }
+}
+
+/// The zero value of a layout, `None` for layouts a value cannot be built from.
+fn zero_value(layout: &MoveTypeLayout) -> Option<MoveValue> {
+    Some(match layout {
+        MoveTypeLayout::Bool => MoveValue::Bool(false),
+        MoveTypeLayout::U8 => MoveValue::U8(0),
+        MoveTypeLayout::U16 => MoveValue::U16(0),
+        MoveTypeLayout::U32 => MoveValue::U32(0),
+        MoveTypeLayout::U64 => MoveValue::U64(0),
+        MoveTypeLayout::U128 => MoveValue::U128(0),
+        MoveTypeLayout::U256 => MoveValue::U256(U256::zero()),
+        MoveTypeLayout::Address => MoveValue::Address(AccountAddress::ZERO),
+        MoveTypeLayout::Signer => MoveValue::Signer(AccountAddress::ZERO),
+        MoveTypeLayout::Vector(_) => MoveValue::Vector(Vec::new()),
+        MoveTypeLayout::Struct(s) => MoveValue::Struct(MoveStruct::Runtime(
+            s.fields().iter().map(zero_value).collect::<Option<_>>()?,
+        )),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_core_types::value::MoveStructLayout;
+
+    fn layout() -> MoveTypeLayout {
+        MoveTypeLayout::Struct(MoveStructLayout::Runtime(vec![
+            MoveTypeLayout::U8,
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64)),
+            MoveTypeLayout::Address,
+        ]))
+    }
+
+    fn value() -> MoveValue {
+        MoveValue::Struct(MoveStruct::Runtime(vec![
+            MoveValue::U8(7),
+            MoveValue::Vector(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+            MoveValue::Address(AccountAddress::ONE),
+        ]))
+    }
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut rng = MutationRng::new(42);
+        let numbers: Vec<_> = (0..8).map(|_| rng.next_u64()).collect();
+        let mut again = MutationRng::new(42);
+        assert_eq!(
+            numbers,
+            (0..8).map(|_| again.next_u64()).collect::<Vec<_>>()
+        );
+        assert_ne!(numbers[0], MutationRng::new(43).next_u64());
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = MutationRng::new(0);
+        assert_eq!(rng.below(0), 0);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+    }
+
+    #[test]
+    fn mutated_values_keep_their_layout() {
+        let mutator = ValueMutator::new(layout());
+        let encoded = mutator.encode(&value());
+        assert_eq!(mutator.decode(&encoded), Some(value()));
+        for seed in 0..200 {
+            let mut mutated = value();
+            mutator.mutate(&mut mutated, &mut MutationRng::new(seed));
+            assert!(mutator.decode(&mutator.encode(&mutated)).is_some());
+        }
+    }
+
+    #[test]
+    fn mutations_change_the_value() {
+        let mutator = ValueMutator::new(layout());
+        let changed = (0..50).any(|seed| {
+            let mut mutated = value();
+            mutator.mutate(&mut mutated, &mut MutationRng::new(seed));
+            mutated != value()
+        });
+        assert!(changed);
+    }
+
+    #[test]
+    fn vectors_do_not_grow_past_the_cap() {
+        let layout = MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Bool));
+        let mutator = ValueMutator::new(layout).max_vector_len(3);
+        let mut value = MoveValue::Vector(Vec::new());
+        let mut rng = MutationRng::new(1);
+        for _ in 0..100 {
+            mutator.mutate(&mut value, &mut rng);
+            match &value {
+                MoveValue::Vector(elems) => assert!(elems.len() <= 3),
+                value => panic!("unexpected {:?}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn integers_stay_below_their_bound() {
+        let mut rng = MutationRng::new(5);
+        for n in [0, 1, 200, 255] {
+            assert!((0..100).all(|_| mutate_int(n, u8::MAX.into(), &mut rng) <= 255));
+        }
+    }
+
+    #[test]
+    fn mutate_with_writes_the_mutated_encoding() {
+        let mutator = ValueMutator::new(layout());
+        let encoded = mutator.encode(&value());
+        let mut data = encoded.clone();
+        data.resize(256, 0);
+        let size = mutate_with(&mutator, &mut data, encoded.len(), 256, 9);
+        let mut expected = value();
+        mutator.mutate(&mut expected, &mut MutationRng::new(9));
+        assert_eq!(&data[..size], mutator.encode(&expected).as_slice());
+    }
+
+    #[test]
+    fn zero_values_of_layouts() {
+        assert_eq!(
+            zero_value(&layout()),
+            Some(MoveValue::Struct(MoveStruct::Runtime(vec![
+                MoveValue::U8(0),
+                MoveValue::Vector(Vec::new()),
+                MoveValue::Address(AccountAddress::ZERO),
+            ])))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use move_stdlib::natives::{all_natives, nursery_natives, GasParameters, NurseryGasParameters};
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+use move_vm_runtime::native_functions::NativeFunction;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+#[cfg(feature = "table-extension")]
+use move_table_extension::{NativeTableContext, TableChangeSet, TableResolver};
+
+///
+/// A native function registered in the VM, with its address, module and name
+///
+pub type NativeFunctionRecord = (AccountAddress, Identifier, Identifier, NativeFunction);
+
+///
+/// A package of native functions the harnesses can register in the VM
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NativePackage {
+    /// The standard library natives, at `0x1`
+    Stdlib,
+    /// The nursery natives at `0x1`, `event` and `debug` among them
+    Nursery,
+    /// The natives of the `table` extension, at `0x2`
+    Table,
+}
+
+impl NativePackage {
+    /// The packages registered when `MOVE_FUZZ_NATIVES` is not set
+    pub const DEFAULT: &'static [NativePackage] = &[NativePackage::Stdlib, NativePackage::Nursery];
+
+    ///
+    /// Parses the comma-separated package names of `MOVE_FUZZ_NATIVES`, e.g. `stdlib,table`
+    ///
+    pub fn parse_list(list: &str) -> Result<Vec<NativePackage>, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "stdlib" => Ok(NativePackage::Stdlib),
+                "nursery" => Ok(NativePackage::Nursery),
+                "table" => Ok(NativePackage::Table),
+                _ => Err(format!(
+                    "unknown native package `{}`, expected `stdlib`, `nursery` or `table`",
+                    name
+                )),
+            })
+            .collect()
+    }
+}
+
+static CUSTOM_NATIVES: Lazy<Mutex<Vec<NativeFunctionRecord>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+///
+/// Registers natives of the package under test besides those of `MOVE_FUZZ_NATIVES`.
+///
+/// Call it through [`fuzz_natives!`](crate::fuzz_natives), which does so before the
+/// first input runs.
+///
+pub fn register_natives(natives: impl IntoIterator<Item = NativeFunctionRecord>) {
+    CUSTOM_NATIVES.lock().unwrap().extend(natives);
+}
+
+///
+/// The natives of the packages of `MOVE_FUZZ_NATIVES`, by default the standard
+/// library and the nursery, then the registered ones.
+///
+pub(crate) fn natives() -> Vec<NativeFunctionRecord> {
+    natives_with(&[])
+}
+
+///
+/// The natives of [`natives`], with those of the `extra` packages not listed
+/// in `MOVE_FUZZ_NATIVES` before the registered ones.
+///
+pub(crate) fn natives_with(extra: &[NativePackage]) -> Vec<NativeFunctionRecord> {
+    let std_addr = AccountAddress::from_hex_literal("0x1").unwrap();
+    let mut packages = crate::MOVE_FUZZ_NATIVES
+        .get()
+        .map(Vec::as_slice)
+        .unwrap_or(NativePackage::DEFAULT)
+        .to_vec();
+    for package in extra {
+        if !packages.contains(package) {
+            packages.push(*package);
+        }
+    }
+    let mut natives = Vec::new();
+    for package in packages {
+        match package {
+            NativePackage::Stdlib => natives.extend(all_natives(std_addr, GasParameters::zeros())),
+            NativePackage::Nursery => {
+                natives.extend(nursery_natives(std_addr, NurseryGasParameters::zeros()))
+            }
+            #[cfg(feature = "table-extension")]
+            NativePackage::Table => natives.extend(move_table_extension::table_natives(
+                AccountAddress::from_hex_literal("0x2").unwrap(),
+                move_table_extension::GasParameters::zeros(),
+            )),
+            #[cfg(not(feature = "table-extension"))]
+            NativePackage::Table => {
+                panic!("the `table` natives need the `table-extension` feature of libfuzzer")
+            }
+        }
+    }
+    natives.extend(CUSTOM_NATIVES.lock().unwrap().iter().cloned());
+    natives
+}
+
+///
+/// The native context of a session on `storage`: the table natives only work in
+/// sessions created with it, which are those of stateful and extension targets.
+/// The handles of the tables created in the session derive from `txn_hash`.
+///
+#[cfg(feature = "table-extension")]
+pub(crate) fn new_extensions(
+    txn_hash: [u8; 32],
+    storage: &dyn TableResolver,
+) -> NativeContextExtensions<'_> {
+    let mut extensions = NativeContextExtensions::default();
+    extensions.add(NativeTableContext::new(txn_hash, storage));
+    extensions
+}
+
+///
+/// The changes to tables made in a session, to apply to its storage.
+///
+#[cfg(feature = "table-extension")]
+pub(crate) fn table_changes(mut extensions: NativeContextExtensions) -> anyhow::Result<TableChangeSet> {
+    extensions
+        .remove::<NativeTableContext>()
+        .into_change_set()
+        .map_err(|e| anyhow::anyhow!("{:?}", e))
+}
+
+///
+/// The native context of a session, empty without the `table-extension` feature.
+///
+#[cfg(not(feature = "table-extension"))]
+pub(crate) fn new_extensions<S>(_txn_hash: [u8; 32], _storage: &S) -> NativeContextExtensions<'_> {
+    NativeContextExtensions::default()
+}
@@ -0,0 +1,205 @@
+use anyhow::{anyhow, bail, Context, Result};
+use move_binary_format::file_format::CompiledScript;
+use move_cli::sandbox::utils::get_gas_status;
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::DEFAULT_STORAGE_DIR;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::config::VMConfig;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::gas_schedule::GasStatus;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::run_move::move_args::{MoveArg, ToTransactionArgument};
+use crate::run_move::{natives, package, script_file};
+
+/// Gas budget of a recorded execution, high enough to never run out on sane inputs.
+pub(crate) const GAS_BUDGET: u64 = 1_000_000_000;
+
+/// Runs the script of the target on `data` without committing its effects and
+/// writes what an observer could tell apart to `path`: the status and abort
+/// code, the return values, the emitted events and the gas used.
+pub(crate) fn record(data: MoveArg, path: &str) -> Result<()> {
+    let outcome = observe(&data, None, &PathBuf::from(DEFAULT_STORAGE_DIR), false)?;
+    std::fs::write(path, outcome).with_context(|| format!("failed to write to {}", path))
+}
+
+/// Writes `outcome` and the gas it used to `path`, as [`record`] does.
+pub(crate) fn write(path: &str, outcome: &Outcome, gas_used: u64) -> Result<()> {
+    std::fs::write(path, format!("{}gas used: {}\n", outcome, gas_used))
+        .with_context(|| format!("failed to write to {}", path))
+}
+
+/// Runs the script of the target on `data` with the package published at each
+/// of `versions`, failing if the outcomes differ. Every version gets its own
+/// storage, next to the default one.
+pub(crate) fn compare_versions(data: &MoveArg, versions: &[u32]) -> Result<()> {
+    let mut outcomes = Vec::with_capacity(versions.len());
+    for &version in versions {
+        let storage_dir = PathBuf::from(format!("{}-v{}", DEFAULT_STORAGE_DIR, version));
+        let outcome = observe(data, Some(version), &storage_dir, false)
+            .unwrap_or_else(|e| format!("error: {:#}\n", e));
+        outcomes.push((format!("version {}", version), outcome));
+    }
+    check_same("the bytecode version", &outcomes)
+}
+
+/// Runs the script of the target on `data` with the paranoid type checks of the
+/// VM enabled and disabled, failing if the outcomes differ. The checks only
+/// re-verify at runtime what the bytecode verifier guarantees, so any difference
+/// is a type-safety hole in one of them.
+pub(crate) fn compare_paranoid(data: &MoveArg) -> Result<()> {
+    let storage_dir = PathBuf::from(DEFAULT_STORAGE_DIR);
+    let [paranoid, unchecked] = [true, false].map(|paranoid| {
+        observe(data, None, &storage_dir, paranoid).unwrap_or_else(|e| format!("error: {:#}\n", e))
+    });
+    check_same(
+        "paranoid type checks",
+        &[
+            ("paranoid".to_owned(), paranoid),
+            ("unchecked".to_owned(), unchecked),
+        ],
+    )
+}
+
+/// Fails with the `outcomes` of each run, by label, unless they are all the
+/// same, saying they depend on `setting`.
+pub(crate) fn check_same(setting: &str, outcomes: &[(String, String)]) -> Result<()> {
+    if outcomes.windows(2).all(|pair| pair[0].1 == pair[1].1) {
+        return Ok(());
+    }
+    let mut report = format!("the outcome depends on {}\n", setting);
+    for (label, outcome) in outcomes {
+        writeln!(report, "{}:", label)?;
+        for line in outcome.lines() {
+            writeln!(report, "    {}", line)?;
+        }
+    }
+    bail!(report)
+}
+
+/// What an execution looks like from outside, besides the gas it used.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Executed {
+        return_values: Vec<Vec<u8>>,
+        events: Vec<String>,
+    },
+    Failed {
+        status: StatusCode,
+        sub_status: Option<u64>,
+        location: String,
+    },
+}
+
+impl Outcome {
+    pub(crate) fn out_of_gas(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Failed {
+                status: StatusCode::OUT_OF_GAS,
+                ..
+            }
+        )
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Executed {
+                return_values,
+                events,
+            } => {
+                writeln!(f, "status: EXECUTED")?;
+                for bytes in return_values {
+                    writeln!(f, "return: {:02x?}", bytes)?;
+                }
+                for event in events {
+                    writeln!(f, "event: {}", event)?;
+                }
+            }
+            Outcome::Failed {
+                status,
+                sub_status,
+                location,
+            } => {
+                writeln!(f, "status: {:?}", status)?;
+                if let Some(code) = sub_status {
+                    writeln!(f, "abort code: {}", code)?;
+                }
+                writeln!(f, "location: {}", location)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `script` on `args` against `state` without committing its effects,
+/// returning its outcome and the gas `gas_status` charged.
+pub(crate) fn execute(
+    vm: &MoveVM,
+    state: &OnDiskStateView,
+    script: &[u8],
+    args: Vec<Vec<u8>>,
+    mut gas_status: GasStatus,
+) -> Result<(Outcome, u64)> {
+    let budget = u64::from(gas_status.remaining_gas());
+    let mut session = vm.new_session(state);
+    let outcome = match session.execute_script(script, vec![], args, &mut gas_status) {
+        Ok(values) => {
+            let return_values = values
+                .return_values
+                .into_iter()
+                .map(|(bytes, _layout)| bytes)
+                .collect();
+            let (_changeset, events) = session
+                .finish()
+                .map_err(|e| anyhow!("{:?}", e.into_vm_status()))?;
+            Outcome::Executed {
+                return_values,
+                events: events.iter().map(|event| format!("{:?}", event)).collect(),
+            }
+        }
+        Err(err) => Outcome::Failed {
+            status: err.major_status(),
+            sub_status: err.sub_status(),
+            location: format!("{:?}", err.location()),
+        },
+    };
+    Ok((outcome, budget - u64::from(gas_status.remaining_gas())))
+}
+
+/// Runs the script of the target on `data`, with the package published at
+/// `bytecode_version` in `storage_dir` and the paranoid type checks of the VM
+/// enabled or not, and returns the outcome `record` writes.
+fn observe(
+    data: &MoveArg,
+    bytecode_version: Option<u32>,
+    storage_dir: &Path,
+    paranoid_type_checks: bool,
+) -> Result<String> {
+    let state = package::state(bytecode_version, storage_dir)?;
+    let script_file = script_file();
+    let mut script = std::fs::read(&script_file)
+        .with_context(|| format!("could not read script {}", script_file.display()))?;
+    if let Some(version) = bytecode_version {
+        let compiled = CompiledScript::deserialize(&script)
+            .map_err(|e| anyhow!("Error deserializing script: {:?}", e))?;
+        script.clear();
+        compiled.serialize_for_version(Some(version), &mut script)?;
+    }
+
+    let config = VMConfig {
+        paranoid_type_checks,
+        ..VMConfig::default()
+    };
+    let vm = MoveVM::new_with_config(natives(), config).map_err(|e| anyhow!("{:?}", e))?;
+    let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
+    let gas_status = get_gas_status(cost_table, Some(GAS_BUDGET))?;
+    let args = convert_txn_args(&data.to_transaction_argument());
+
+    let (outcome, gas_used) = execute(&vm, &state, &script, args, gas_status)?;
+    Ok(format!("{}gas used: {}\n", outcome, gas_used))
+}
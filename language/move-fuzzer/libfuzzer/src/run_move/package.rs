@@ -0,0 +1,89 @@
+use anyhow::{anyhow, bail, Result};
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::sandbox::utils::PackageContext;
+use move_compiler::compiled_unit::CompiledUnitEnum;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::ModuleId;
+use move_package::BuildConfig;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+thread_local! {
+    static PACKAGE: RefCell<Option<Rc<PackageContext>>> = RefCell::new(None);
+    static STATES: RefCell<HashMap<(Option<u32>, PathBuf), Rc<OnDiskStateView>>> =
+        RefCell::new(HashMap::new());
+}
+
+///
+/// The package of the fuzz directory, compiled on first use and kept for the
+/// inputs that follow.
+///
+pub(crate) fn context() -> Result<Rc<PackageContext>> {
+    PACKAGE.with(|package| {
+        let mut package = package.borrow_mut();
+        if package.is_none() {
+            let context =
+                PackageContext::new(&Some(PathBuf::from("./fuzz")), &BuildConfig::default())?;
+            *package = Some(Rc::new(context));
+        }
+        Ok(package.as_ref().unwrap().clone())
+    })
+}
+
+///
+/// The storage in `storage_dir` with the package and its dependencies
+/// published at `bytecode_version`, prepared on first use.
+///
+/// Publishing only adds the modules missing from the storage, so the view
+/// stays valid as executions commit their effects to it.
+///
+pub(crate) fn state(
+    bytecode_version: Option<u32>,
+    storage_dir: &Path,
+) -> Result<Rc<OnDiskStateView>> {
+    let key = (bytecode_version, storage_dir.to_owned());
+    if let Some(state) = STATES.with(|states| states.borrow().get(&key).cloned()) {
+        return Ok(state);
+    }
+    let state = Rc::new(context()?.prepare_state(bytecode_version, storage_dir)?);
+    STATES.with(|states| states.borrow_mut().insert(key, state.clone()));
+    Ok(state)
+}
+
+///
+/// The module of the root package a call names: `name`, or `address::name`
+/// to tell apart modules of the same name at different addresses.
+///
+pub(crate) fn root_module(context: &PackageContext, name: &str) -> Result<ModuleId> {
+    let (address, name) = match name.rsplit_once("::") {
+        Some((address, name)) => {
+            let address = AccountAddress::from_hex_literal(address)
+                .map_err(|_| anyhow!("{} is not an address", address))?;
+            (Some(address), name)
+        }
+        None => (None, name),
+    };
+    let ids: Vec<ModuleId> = context
+        .package()
+        .root_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnitEnum::Module(m) => Some(m.module.self_id()),
+            CompiledUnitEnum::Script(_) => None,
+        })
+        .filter(|id| id.name().as_str() == name && address.map_or(true, |a| *id.address() == a))
+        .collect();
+    match &ids[..] {
+        [id] => Ok(id.clone()),
+        [] => bail!("the package has no module {}", name),
+        _ => bail!(
+            "the package has several modules {}, at {}: qualify it with its address",
+            name,
+            ids.iter()
+                .map(|id| id.address().to_hex_literal())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
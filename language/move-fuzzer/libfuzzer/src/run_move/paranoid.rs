@@ -0,0 +1,17 @@
+use crate::run_move::move_args::{is_passable, MoveArg};
+use crate::run_move::outcome;
+
+///
+/// Runs the script of the target on `data` with the paranoid type checks of the
+/// VM enabled and disabled, panicking if the outcomes differ.
+///
+/// Effects are not committed, so every input starts from the same storage.
+///
+pub fn run(data: MoveArg) {
+    if !is_passable(&data) {
+        return;
+    }
+    if let Err(e) = outcome::compare_paranoid(&data) {
+        panic!("{}", e);
+    }
+}
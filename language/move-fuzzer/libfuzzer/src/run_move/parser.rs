@@ -0,0 +1,207 @@
+use move_compiler::command_line::compiler::PASS_EXPANSION;
+use move_compiler::shared::{NumberFormat, NumericalAddress};
+use move_compiler::Compiler;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::run_move::package;
+
+/// How many instructions the front end may retire on an input when
+/// `MOVE_FUZZ_PARSER_BUDGET` is not set, about a second of work.
+pub const DEFAULT_BUDGET: u64 = 2_000_000_000;
+
+thread_local! {
+    static FRONT_END: FrontEnd = FrontEnd::new();
+}
+
+///
+/// What every input is compiled with: the file it is written to and the named
+/// addresses of the package, kept for the inputs that follow.
+///
+struct FrontEnd {
+    path: PathBuf,
+    named_addresses: BTreeMap<String, NumericalAddress>,
+    counter: Option<InstructionCounter>,
+}
+
+impl FrontEnd {
+    fn new() -> Self {
+        // The package of the fuzz directory declares the addresses its sources
+        // use, which the seeds from `--seed-from-sources` need to expand.
+        let named_addresses = match package::context() {
+            Ok(context) => context
+                .package()
+                .compiled_package_info
+                .address_alias_instantiation
+                .iter()
+                .map(|(name, address)| {
+                    (
+                        name.to_string(),
+                        NumericalAddress::new(address.into_bytes(), NumberFormat::Hex),
+                    )
+                })
+                .collect(),
+            Err(_) => move_stdlib::move_stdlib_named_addresses(),
+        };
+        let counter = InstructionCounter::open();
+        if counter.is_none() {
+            eprintln!(
+                "WARNING: instructions cannot be counted here, parser targets only catch \
+                 hangs through the `-timeout` of libFuzzer"
+            );
+        }
+        Self {
+            path: std::env::temp_dir()
+                .join(format!("move-fuzz-parser-{}.move", std::process::id())),
+            named_addresses,
+            counter,
+        }
+    }
+}
+
+///
+/// Runs `data`, as Move source text, through the lexer, the parser and the
+/// expansion of the compiler, with the named addresses of the package.
+///
+/// Diagnostics are expected for most inputs and are ignored. A panic of the
+/// front end is a finding, and so is an input on which it retires more
+/// instructions than the budget, `MOVE_FUZZ_PARSER_BUDGET` or
+/// [`DEFAULT_BUDGET`], which is a hang in the making. Counting instructions
+/// rather than time keeps the verdict the same from one run to the next; where
+/// they cannot be counted, the hangs are only caught by the `-timeout` of
+/// libFuzzer.
+///
+pub fn check_source(data: &[u8]) {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    FRONT_END.with(|front_end| {
+        std::fs::write(&front_end.path, source).unwrap_or_else(|e| {
+            panic!("failed to write to {}: {}", front_end.path.display(), e)
+        });
+        let targets = vec![front_end.path.to_string_lossy().to_string()];
+
+        if let Some(counter) = &front_end.counter {
+            counter.start();
+        }
+        let _ = Compiler::from_files(targets, vec![], front_end.named_addresses.clone())
+            .run::<PASS_EXPANSION>();
+        let instructions = front_end.counter.as_ref().map(InstructionCounter::stop);
+        let _ = std::fs::remove_file(&front_end.path);
+
+        let budget = crate::MOVE_FUZZ_PARSER_BUDGET
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_BUDGET);
+        if let Some(instructions) = instructions.filter(|&count| count > budget) {
+            panic!(
+                "the front end retired {} instructions on {} bytes of source, over the budget of {}",
+                instructions,
+                data.len(),
+                budget
+            );
+        }
+    });
+}
+
+///
+/// A hardware counter of the instructions the thread retires in user space,
+/// through `perf_event_open`.
+///
+struct InstructionCounter {
+    fd: libc::c_int,
+}
+
+/// The start of `struct perf_event_attr`, as of its first version.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    type_: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl InstructionCounter {
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    /// The `disabled`, `exclude_kernel` and `exclude_hv` bits
+    const FLAGS: u64 = 1 | 1 << 5 | 1 << 6;
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 8;
+
+    /// The counter of the calling thread, `None` if the kernel does not let
+    /// it be opened, as in most containers.
+    fn open() -> Option<Self> {
+        let attr = PerfEventAttr {
+            type_: Self::PERF_TYPE_HARDWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config: Self::PERF_COUNT_HW_INSTRUCTIONS,
+            flags: Self::FLAGS,
+            ..Default::default()
+        };
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0,
+                -1,
+                -1,
+                Self::PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        (fd >= 0).then_some(Self {
+            fd: fd as libc::c_int,
+        })
+    }
+
+    fn start(&self) {
+        unsafe {
+            libc::ioctl(self.fd, Self::PERF_EVENT_IOC_RESET, 0);
+            libc::ioctl(self.fd, Self::PERF_EVENT_IOC_ENABLE, 0);
+        }
+    }
+
+    /// The instructions retired since [`start`](Self::start).
+    fn stop(&self) -> u64 {
+        let mut count = 0u64;
+        unsafe {
+            libc::ioctl(self.fd, Self::PERF_EVENT_IOC_DISABLE, 0);
+            libc::read(
+                self.fd,
+                &mut count as *mut u64 as *mut libc::c_void,
+                std::mem::size_of::<u64>(),
+            );
+        }
+        count
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl InstructionCounter {
+    fn open() -> Option<Self> {
+        None
+    }
+
+    fn start(&self) {}
+
+    fn stop(&self) -> u64 {
+        0
+    }
+}
+
+impl Drop for InstructionCounter {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
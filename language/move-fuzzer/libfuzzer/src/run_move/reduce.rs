@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Context, Result};
+use move_binary_format::file_format::{Bytecode, CompiledModule};
+use std::fs;
+use std::path::Path;
+
+///
+/// Writes the reductions of the module at `<dir>/module.mv` to `<dir>/<n>.mv`,
+/// in the order of [`reductions`], for `move-fuzz tmin --module` to try.
+///
+/// Reductions that do not serialize are left out, so numbers can be skipped.
+///
+pub fn write_reductions(dir: &Path) -> Result<()> {
+    let path = dir.join("module.mv");
+    let bytes = fs::read(&path).with_context(|| format!("could not read {}", path.display()))?;
+    let module = CompiledModule::deserialize(&bytes)
+        .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
+    for (i, reduced) in reductions(&module).iter().enumerate() {
+        let mut binary = Vec::new();
+        if reduced
+            .serialize_for_version(Some(reduced.version), &mut binary)
+            .is_ok()
+        {
+            let path = dir.join(format!("{}.mv", i));
+            fs::write(&path, binary)
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+///
+/// The modules one step smaller than `module`, the largest steps first: without
+/// one of its function definitions, without one of its struct definitions, then
+/// without one of the instructions of a function, from its last one.
+///
+/// Handles are kept, so removing a definition leaves the rest of the module
+/// in bounds, and branches past a removed instruction are moved back by one.
+/// A reduction can still be rejected by the deserializer or the verifier, in
+/// which case it simply does not reproduce the crash.
+///
+pub fn reductions(module: &CompiledModule) -> Vec<CompiledModule> {
+    let mut reductions = Vec::new();
+    for i in 0..module.function_defs.len() {
+        let mut reduced = module.clone();
+        reduced.function_defs.remove(i);
+        reductions.push(reduced);
+    }
+    for i in 0..module.struct_defs.len() {
+        let mut reduced = module.clone();
+        reduced.struct_defs.remove(i);
+        reductions.push(reduced);
+    }
+    for (i, def) in module.function_defs.iter().enumerate() {
+        let len = def.code.as_ref().map_or(0, |code| code.code.len());
+        for offset in (0..len).rev() {
+            let mut reduced = module.clone();
+            if let Some(code) = &mut reduced.function_defs[i].code {
+                remove_instruction(&mut code.code, offset);
+            }
+            reductions.push(reduced);
+        }
+    }
+    reductions
+}
+
+fn remove_instruction(code: &mut Vec<Bytecode>, offset: usize) {
+    code.remove(offset);
+    for instr in code.iter_mut() {
+        if let Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target) =
+            instr
+        {
+            if usize::from(*target) > offset {
+                *target -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_binary_format::file_format::basic_test_module;
+
+    #[test]
+    fn reductions_remove_definitions_then_instructions() {
+        let module = basic_test_module();
+        let reductions = reductions(&module);
+        assert_eq!(reductions.len(), 3);
+        assert!(reductions[0].function_defs.is_empty());
+        assert_eq!(reductions[0].struct_defs, module.struct_defs);
+        assert!(reductions[1].struct_defs.is_empty());
+        assert_eq!(reductions[1].function_defs.len(), 1);
+        assert_eq!(
+            reductions[2].function_defs[0].code.as_ref().unwrap().code,
+            []
+        );
+    }
+
+    #[test]
+    fn removing_an_instruction_moves_later_branches_back() {
+        let mut code = vec![
+            Bytecode::BrTrue(3),
+            Bytecode::LdTrue,
+            Bytecode::Pop,
+            Bytecode::Ret,
+            Bytecode::Branch(0),
+        ];
+        remove_instruction(&mut code, 1);
+        assert_eq!(
+            code,
+            [
+                Bytecode::BrTrue(2),
+                Bytecode::Pop,
+                Bytecode::Ret,
+                Bytecode::Branch(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_numbered_reductions() {
+        let dir = std::env::temp_dir().join(format!("move-fuzz-reduce-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let module = basic_test_module();
+        let mut binary = Vec::new();
+        module.serialize(&mut binary).unwrap();
+        fs::write(dir.join("module.mv"), binary).unwrap();
+
+        write_reductions(&dir).unwrap();
+        let first = CompiledModule::deserialize(&fs::read(dir.join("0.mv")).unwrap()).unwrap();
+        assert!(first.function_defs.is_empty());
+        assert!(dir.join("2.mv").exists());
+        assert!(!dir.join("3.mv").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Inputs generated when `MOVE_FUZZ_PROPTEST_CASES` is not set.
+const DEFAULT_CASES: usize = 256;
+
+type Generator = Box<dyn Fn(&mut TestRunner) -> Option<Vec<u8>> + Send>;
+
+static GENERATOR: Mutex<Option<Generator>> = Mutex::new(None);
+
+///
+/// Values written as the input bytes [`Arbitrary::arbitrary`] reads them back
+/// from, so that proptest strategies can generate the inputs of typed targets.
+///
+/// Implement it for the input types of your targets from the implementations
+/// for their fields. `String` and other types whose length `arbitrary` reads
+/// from the end of the input have none.
+///
+pub trait FuzzInput {
+    /// Appends the bytes of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_fuzz_input_for_integers {
+    ($($ty:ty),*) => {
+        $(
+            impl FuzzInput for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_fuzz_input_for_integers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl FuzzInput for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl<T: FuzzInput> FuzzInput for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.is_some().encode(out);
+        if let Some(value) = self {
+            value.encode(out);
+        }
+    }
+}
+
+impl<T: FuzzInput> FuzzInput for Vec<T> {
+    /// `Vec<T>` reads a continuation flag before each element.
+    fn encode(&self, out: &mut Vec<u8>) {
+        for elem in self {
+            out.push(1);
+            elem.encode(out);
+        }
+        out.push(0);
+    }
+}
+
+impl<T: FuzzInput, const N: usize> FuzzInput for [T; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        for elem in self {
+            elem.encode(out);
+        }
+    }
+}
+
+impl<T: FuzzInput> FuzzInput for Box<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+macro_rules! impl_fuzz_input_for_tuples {
+    ($(($($name:ident),+)),*) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($name: FuzzInput),+> FuzzInput for ($($name,)+) {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    let ($($name,)+) = self;
+                    $($name.encode(out);)+
+                }
+            }
+        )*
+    };
+}
+
+impl_fuzz_input_for_tuples!((A), (A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E));
+
+///
+/// Generates the inputs of the target with `strategy`, for
+/// `move-fuzz corpus seed-from-proptest`. The values that do not decode back
+/// to themselves, from a wrong [`FuzzInput`] implementation, are left out.
+///
+/// Call it through [`fuzz_proptest_seeds!`](crate::fuzz_proptest_seeds).
+///
+pub fn register_strategy<S>(strategy: S)
+where
+    S: Strategy + Send + 'static,
+    S::Value: FuzzInput + for<'a> Arbitrary<'a> + PartialEq,
+{
+    register(Box::new(move |runner| {
+        let value = strategy.new_tree(runner).ok()?.current();
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes);
+        let decoded = S::Value::arbitrary(&mut Unstructured::new(&bytes)).ok()?;
+        (decoded == value).then_some(bytes)
+    }));
+}
+
+///
+/// Generates the raw inputs of a target taking bytes with `strategy`.
+///
+/// Call it through [`fuzz_proptest_seeds!`](crate::fuzz_proptest_seeds).
+///
+pub fn register_bytes_strategy<S>(strategy: S)
+where
+    S: Strategy<Value = Vec<u8>> + Send + 'static,
+{
+    register(Box::new(move |runner| {
+        Some(strategy.new_tree(runner).ok()?.current())
+    }));
+}
+
+fn register(generator: Generator) {
+    *GENERATOR.lock().unwrap() = Some(generator);
+}
+
+///
+/// Writes `MOVE_FUZZ_PROPTEST_CASES` inputs of the registered strategy to `dir`,
+/// drawn from `MOVE_FUZZ_PROPTEST_RNG_SEED` if set, and returns how many
+/// were written and left out.
+///
+pub fn write_seeds(dir: &Path) -> Result<(usize, usize)> {
+    let generator = GENERATOR.lock().unwrap();
+    let generator = generator
+        .as_ref()
+        .context("the target registers no strategy with `fuzz_proptest_seeds!`")?;
+    let cases = match std::env::var("MOVE_FUZZ_PROPTEST_CASES") {
+        Ok(cases) => cases
+            .parse()
+            .context("`MOVE_FUZZ_PROPTEST_CASES` must be a number")?,
+        Err(_) => DEFAULT_CASES,
+    };
+    let mut runner = match std::env::var("MOVE_FUZZ_PROPTEST_RNG_SEED") {
+        Ok(seed) => {
+            let seed: u64 = seed
+                .parse()
+                .context("`MOVE_FUZZ_PROPTEST_RNG_SEED` must be a number")?;
+            let mut bytes = [0; 32];
+            bytes[..8].copy_from_slice(&seed.to_le_bytes());
+            let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &bytes);
+            TestRunner::new_with_rng(Config::default(), rng)
+        }
+        Err(_) => TestRunner::default(),
+    };
+
+    let (mut written, mut left_out) = (0, 0);
+    for case in 0..cases {
+        match generator(&mut runner) {
+            Some(input) => {
+                let path = dir.join(format!("proptest-{}", case));
+                std::fs::write(&path, input)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                written += 1;
+            }
+            None => left_out += 1,
+        }
+    }
+    Ok((written, left_out))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode<T: for<'a> Arbitrary<'a>>(value: &impl FuzzInput) -> T {
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes);
+        T::arbitrary(&mut Unstructured::new(&bytes)).unwrap()
+    }
+
+    #[test]
+    fn integers_decode_back() {
+        assert_eq!(
+            decode::<u64>(&0x0123_4567_89ab_cdef_u64),
+            0x0123_4567_89ab_cdef
+        );
+        assert_eq!(decode::<i16>(&-2_i16), -2);
+        assert!(decode::<bool>(&true));
+    }
+
+    #[test]
+    fn vectors_decode_back() {
+        let value = vec![3_u32, 0, u32::MAX];
+        assert_eq!(decode::<Vec<u32>>(&value), value);
+        assert!(decode::<Vec<u8>>(&Vec::<u8>::new()).is_empty());
+    }
+
+    #[test]
+    fn options_and_tuples_decode_back() {
+        let value = (Some(7_u8), None::<u64>, [1_u16, 2]);
+        assert_eq!(decode::<(Option<u8>, Option<u64>, [u16; 2])>(&value), value);
+    }
+
+    #[test]
+    fn write_the_inputs_of_the_strategy() {
+        let dir = std::env::temp_dir().join(format!("move-fuzz-seeds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        register_bytes_strategy(proptest::collection::vec(0_u8..4, 1..8));
+        let (written, left_out) = write_seeds(&dir).unwrap();
+        assert_eq!((written, left_out), (DEFAULT_CASES, 0));
+        let input = std::fs::read(dir.join("proptest-0")).unwrap();
+        assert!((1..8).contains(&input.len()));
+        assert!(input.iter().all(|&byte| byte < 4));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
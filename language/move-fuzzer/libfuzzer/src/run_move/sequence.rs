@@ -0,0 +1,414 @@
+use anyhow::{anyhow, bail, Context, Result};
+use arbitrary::Unstructured;
+use move_binary_format::errors::VMError;
+use move_binary_format::CompiledModule;
+use move_cli::sandbox::utils::get_gas_status;
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::DEFAULT_STORAGE_DIR;
+use move_core_types::effects::{ChangeSet, Event, Op};
+use move_core_types::identifier::IdentStr;
+use move_core_types::language_storage::TypeTag;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_core_types::value::MoveValue;
+use move_vm_runtime::move_vm::MoveVM;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::run_move::move_args::{is_passable, MoveArg, ToTransactionArgument};
+use crate::run_move::{aborts, frames, natives, outcome, package, spec};
+
+pub use move_core_types::account_address::AccountAddress;
+
+///
+/// A call to a `public entry fun` of a module of the fuzzed package
+///
+#[derive(Debug)]
+pub struct EntryCall {
+    /// Name of the module of the root package declaring the function, as
+    /// `0x42::bank` when modules of the same name live at different addresses
+    pub module: String,
+    /// Name of the entry function
+    pub function: String,
+    /// Indices into [`TxnSequence::accounts`] of the signers passed first to the function
+    pub signers: Vec<usize>,
+    /// Type arguments of the function
+    pub type_args: Vec<TypeTag>,
+    /// Non-signer arguments of the function
+    pub args: MoveArg,
+}
+
+///
+/// An ordered list of entry-function calls sharing a pool of accounts.
+///
+/// The calls run one after the other against the same global storage, so a
+/// call observes every resource published or modified by the calls before it.
+///
+#[derive(Debug, Default)]
+pub struct TxnSequence {
+    /// Accounts the calls can sign with
+    pub accounts: Vec<AccountAddress>,
+    /// Calls, in execution order
+    pub calls: Vec<EntryCall>,
+}
+
+impl TxnSequence {
+    ///
+    /// Creates an empty sequence over the given accounts
+    ///
+    pub fn new(accounts: Vec<AccountAddress>) -> Self {
+        Self {
+            accounts,
+            calls: Vec::new(),
+        }
+    }
+
+    ///
+    /// Appends a call to `module::function`
+    ///
+    pub fn call(
+        &mut self,
+        module: &str,
+        function: &str,
+        signers: Vec<usize>,
+        args: MoveArg,
+    ) -> &mut Self {
+        self.call_generic(module, function, Vec::new(), signers, args)
+    }
+
+    ///
+    /// Appends a call to `module::function` instantiated with `type_args`
+    ///
+    pub fn call_generic(
+        &mut self,
+        module: &str,
+        function: &str,
+        type_args: Vec<TypeTag>,
+        signers: Vec<usize>,
+        args: MoveArg,
+    ) -> &mut Self {
+        self.calls.push(EntryCall {
+            module: module.to_owned(),
+            function: function.to_owned(),
+            signers,
+            type_args,
+            args,
+        });
+        self
+    }
+}
+
+///
+/// Describes an entry function the fuzzer can pick when decoding a sequence,
+/// built with [`EntryFunction::new`]
+///
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub struct EntryFunction {
+    /// Name of the module declaring the function
+    pub module: &'static str,
+    /// Name of the entry function
+    pub function: &'static str,
+    /// Number of `&signer` parameters the function takes
+    pub signers: usize,
+    /// Generates the remaining arguments of a call
+    pub args: fn(&mut Unstructured) -> arbitrary::Result<MoveArg>,
+    /// Generates the type arguments of a call, [`no_type_args`] for functions
+    /// that are not generic
+    pub type_args: fn(&mut Unstructured) -> arbitrary::Result<Vec<TypeTag>>,
+}
+
+///
+/// The type arguments of a function that is not generic
+///
+pub fn no_type_args(_u: &mut Unstructured) -> arbitrary::Result<Vec<TypeTag>> {
+    Ok(Vec::new())
+}
+
+impl EntryFunction {
+    ///
+    /// The function `module::function` taking `signers` signers, followed by
+    /// the arguments `args` generates, and no type arguments
+    ///
+    pub const fn new(
+        module: &'static str,
+        function: &'static str,
+        signers: usize,
+        args: fn(&mut Unstructured) -> arbitrary::Result<MoveArg>,
+    ) -> Self {
+        EntryFunction {
+            module,
+            function,
+            signers,
+            args,
+            type_args: no_type_args,
+        }
+    }
+
+    ///
+    /// The same function, generic, instantiated with the type arguments
+    /// `type_args` generates
+    ///
+    pub const fn with_type_args(
+        self,
+        type_args: fn(&mut Unstructured) -> arbitrary::Result<Vec<TypeTag>>,
+    ) -> Self {
+        EntryFunction { type_args, ..self }
+    }
+}
+
+impl fmt::Debug for EntryFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntryFunction")
+            .field("module", &self.module)
+            .field("function", &self.function)
+            .field("signers", &self.signers)
+            .finish()
+    }
+}
+
+///
+/// Decodes a fuzz input into at most `max_calls` calls to `functions`, signed by `accounts`
+///
+pub fn decode_sequence(
+    u: &mut Unstructured,
+    accounts: &[AccountAddress],
+    functions: &[EntryFunction],
+    max_calls: usize,
+) -> arbitrary::Result<TxnSequence> {
+    if accounts.is_empty() || functions.is_empty() {
+        return Err(arbitrary::Error::EmptyChoose);
+    }
+    let mut sequence = TxnSequence::new(accounts.to_vec());
+    let len = u.int_in_range(1..=max_calls.max(1))?;
+    for _ in 0..len {
+        if u.is_empty() {
+            break;
+        }
+        let function = u.choose(functions)?;
+        let signers = (0..function.signers)
+            .map(|_| u.choose_index(accounts.len()))
+            .collect::<arbitrary::Result<_>>()?;
+        let args = (function.args)(u)?;
+        if !is_passable(&args) {
+            return Err(arbitrary::Error::IncorrectFormat);
+        }
+        let type_args = (function.type_args)(u)?;
+        sequence.call_generic(function.module, function.function, type_args, signers, args);
+    }
+    Ok(sequence)
+}
+
+///
+/// Runs every call of the sequence, panicking on the first failing one, unless
+/// it aborts as declared with [`fuzz_expected_aborts!`](crate::fuzz_expected_aborts)
+/// or `MOVE_FUZZ_EXPECTED_ABORTS`, then with `MOVE_FUZZ_CHECK_SPECS` on the
+/// first violating a spec condition, and with `MOVE_FUZZ_BYTECODE_VERSIONS`
+/// if the calls end differently with the package published at each version.
+/// With `MOVE_FUZZ_OUTCOME_PATH`, how each call ends is written there instead.
+///
+pub fn run(sequence: TxnSequence) {
+    if let Some(path) = crate::MOVE_FUZZ_OUTCOME_PATH.get() {
+        if let Err(e) = record(&sequence, path) {
+            panic!("{}", e);
+        }
+        return;
+    }
+    // The interpreter checking the specs runs the sequence from the state the
+    // VM runs it from, taken before the VM commits its effects.
+    let spec_state = crate::MOVE_FUZZ_CHECK_SPECS
+        .load(std::sync::atomic::Ordering::Relaxed)
+        .then(spec::starting_state);
+    if let Err(e) = run_aux(&sequence) {
+        panic!("{}", e);
+    }
+    if let Some(state) = spec_state {
+        if let Err(e) = state.and_then(|state| spec::check_sequence(&sequence, state)) {
+            panic!("{}", e);
+        }
+    }
+    if let Some(versions) = crate::MOVE_FUZZ_BYTECODE_VERSIONS.get() {
+        if let Err(e) = compare_versions(&sequence, versions) {
+            panic!("{}", e);
+        }
+    }
+}
+
+fn run_aux(sequence: &TxnSequence) -> Result<()> {
+    execute(
+        sequence,
+        None,
+        Path::new(DEFAULT_STORAGE_DIR),
+        |i, call, error, state| {
+            if let Err(e) = aborts::check(&call.module, &call.function, error) {
+                bail!(
+                    "Terminating execution at call {} ({}::{}): {}{}",
+                    i,
+                    call.module,
+                    call.function,
+                    e,
+                    error
+                        .map(|error| frames::backtrace(error, state))
+                        .unwrap_or_default()
+                );
+            }
+            Ok(())
+        },
+    )
+}
+
+///
+/// Runs the calls of `sequence` with the package published at each of
+/// `versions`, failing if a call ends differently. Every version gets its own
+/// storage, next to the default one.
+///
+fn compare_versions(sequence: &TxnSequence, versions: &[u32]) -> Result<()> {
+    let mut outcomes = Vec::with_capacity(versions.len());
+    for &version in versions {
+        let storage_dir = PathBuf::from(format!("{}-v{}", DEFAULT_STORAGE_DIR, version));
+        let calls = describe_calls(sequence, Some(version), &storage_dir)?;
+        outcomes.push((format!("version {}", version), calls));
+    }
+    outcome::check_same("the bytecode version", &outcomes)
+}
+
+///
+/// Writes how each call of `sequence` ends to `path`, running them against a
+/// fresh storage removed afterwards, since the calls commit their effects.
+///
+fn record(sequence: &TxnSequence, path: &str) -> Result<()> {
+    let storage_dir =
+        std::env::temp_dir().join(format!("move-fuzz-outcome-{}", std::process::id()));
+    let calls = describe_calls(sequence, None, &storage_dir);
+    let _ = std::fs::remove_dir_all(&storage_dir);
+    std::fs::write(path, calls?).with_context(|| format!("failed to write to {}", path))
+}
+
+///
+/// How each call of `sequence` ends, a line per call, when run against the
+/// storage in `storage_dir` with the package published at `bytecode_version`.
+///
+fn describe_calls(
+    sequence: &TxnSequence,
+    bytecode_version: Option<u32>,
+    storage_dir: &Path,
+) -> Result<String> {
+    let mut calls = String::new();
+    let result = execute(
+        sequence,
+        bytecode_version,
+        storage_dir,
+        |i, call, error, _| {
+            write!(calls, "call {} ({}::{}): ", i, call.module, call.function)?;
+            match error {
+                Some(error) => {
+                    write!(calls, "{:?}", error.major_status())?;
+                    if let Some(code) = error.sub_status() {
+                        write!(calls, " with code {}", code)?;
+                    }
+                    writeln!(calls)?;
+                }
+                None => writeln!(calls, "EXECUTED")?,
+            }
+            Ok(())
+        },
+    );
+    if let Err(e) = result {
+        writeln!(calls, "error: {:#}", e)?;
+    }
+    Ok(calls)
+}
+
+///
+/// Runs the calls of `sequence` one after the other against the storage in
+/// `storage_dir`, with the package published at `bytecode_version`, handing
+/// `on_call` each call with its error if it failed.
+///
+fn execute(
+    sequence: &TxnSequence,
+    bytecode_version: Option<u32>,
+    storage_dir: &Path,
+    mut on_call: impl FnMut(usize, &EntryCall, Option<&VMError>, &OnDiskStateView) -> Result<()>,
+) -> Result<()> {
+    let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
+    let vm = MoveVM::new(natives()).map_err(|e| anyhow!("{:?}", e))?;
+
+    let context = package::context()?;
+    let state = package::state(bytecode_version, storage_dir)?;
+
+    let modules_dir = PathBuf::from("./fuzz")
+        .join("build")
+        .join(
+            context
+                .package()
+                .compiled_package_info
+                .package_name
+                .to_string(),
+        )
+        .join("bytecode_modules");
+
+    for (i, call) in sequence.calls.iter().enumerate() {
+        let id = package::root_module(&context, &call.module)?;
+        let module_file = modules_dir.join(format!("{}.mv", id.name()));
+        let mut module_bytes = std::fs::read(&module_file)
+            .with_context(|| format!("could not read module {}", module_file.display()))?;
+        let module = CompiledModule::deserialize(&module_bytes)
+            .map_err(|e| anyhow!("Error deserializing module {}: {:?}", call.module, e))?;
+        if let Some(version) = bytecode_version {
+            module_bytes.clear();
+            module.serialize_for_version(Some(version), &mut module_bytes)?;
+        }
+        // Republish the root module so a rebuilt package is picked up.
+        state.save_module(&module.self_id(), &module_bytes)?;
+
+        let mut args: Vec<Vec<u8>> = Vec::with_capacity(call.signers.len());
+        for &idx in &call.signers {
+            let signer = sequence
+                .accounts
+                .get(idx)
+                .ok_or_else(|| anyhow!("signer index {} is out of bounds", idx))?;
+            args.push(MoveValue::Signer(*signer).simple_serialize().unwrap());
+        }
+        args.extend(convert_txn_args(&call.args.to_transaction_argument()));
+
+        // The call runs in a session of its own, committed to the storage if it
+        // succeeds, like `move sandbox run` does but without printing every
+        // failure, as the calls aborting as expected would flood the output.
+        let mut gas_status = get_gas_status(cost_table, None)?;
+        let mut session = vm.new_session(&*state);
+        let result = session.execute_entry_function(
+            &module.self_id(),
+            IdentStr::new(&call.function)?,
+            call.type_args.clone(),
+            args,
+            &mut gas_status,
+        );
+        let error = match result {
+            Ok(_) => {
+                let (changeset, events) = session
+                    .finish()
+                    .map_err(|e| anyhow!("{:?}", e.into_vm_status()))?;
+                commit(&state, changeset, events)?;
+                None
+            }
+            Err(error) => Some(error),
+        };
+        on_call(i, call, error.as_ref(), &state)?;
+    }
+    Ok(())
+}
+
+/// Writes the resources and events of a successful call to `state`.
+fn commit(state: &OnDiskStateView, changeset: ChangeSet, events: Vec<Event>) -> Result<()> {
+    for (address, account) in changeset.into_inner() {
+        for (tag, op) in account.into_resources() {
+            match op {
+                Op::New(blob) | Op::Modify(blob) => state.save_resource(address, tag, &blob)?,
+                Op::Delete => state.delete_resource(address, tag)?,
+            }
+        }
+    }
+    for (key, sequence_number, ty, data) in events {
+        state.save_event(&key, sequence_number, ty, data)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, Context, Result};
+use move_cli::sandbox::utils::get_gas_status;
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::DEFAULT_STORAGE_DIR;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_vm_runtime::move_vm::MoveVM;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::run_move::move_args::{MoveArg, ToTransactionArgument};
+use crate::run_move::{frames, natives, package, script_file};
+
+extern "C" {
+    // Defined in `counters.c`.
+    fn move_fuzz_counters_start() -> *mut u8;
+    fn move_fuzz_counters_stop() -> *mut u8;
+}
+
+thread_local! {
+    static SNAPSHOT: RefCell<Option<Snapshot>> = RefCell::new(None);
+}
+
+///
+/// Everything that is expensive to set up for an execution: the compiled
+/// package, the storage with its modules published and the VM.
+///
+/// The snapshot is built once in the fuzzer process; every input then runs in
+/// a forked copy of it, so an execution starts from the snapshot state and its
+/// effects are discarded with the child process.
+///
+struct Snapshot {
+    vm: MoveVM,
+    state: Rc<OnDiskStateView>,
+    script: Vec<u8>,
+}
+
+impl Snapshot {
+    fn new() -> Result<Self> {
+        let state = package::state(None, Path::new(DEFAULT_STORAGE_DIR))?;
+        let script_file = script_file();
+        let script = std::fs::read(&script_file)
+            .with_context(|| format!("could not read script {}", script_file.display()))?;
+
+        Ok(Self {
+            vm: MoveVM::new(natives()).map_err(|e| anyhow!("{:?}", e))?,
+            state,
+            script,
+        })
+    }
+
+    fn execute(&self, data: MoveArg) -> Result<()> {
+        let args = convert_txn_args(&data.to_transaction_argument());
+        let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
+        let mut gas_status = get_gas_status(cost_table, None)?;
+
+        let mut session = self.vm.new_session(&*self.state);
+        session
+            .execute_script(self.script.as_slice(), vec![], args, &mut gas_status)
+            .map_err(|e| anyhow!("{}", frames::describe(&e, &*self.state)))?;
+        Ok(())
+    }
+}
+
+/// The coverage counters libFuzzer reads after each execution.
+///
+/// # Safety
+///
+/// The slice aliases memory the instrumentation writes to; it must not be
+/// held across calls into instrumented code.
+unsafe fn counters() -> &'static mut [u8] {
+    let start = move_fuzz_counters_start();
+    let stop = move_fuzz_counters_stop();
+    if start.is_null() || stop <= start {
+        return &mut [];
+    }
+    std::slice::from_raw_parts_mut(start, stop.offset_from(start) as usize)
+}
+
+///
+/// Runs the script of the target on `data` in a fork of the snapshot, panicking on failure.
+///
+/// The child sends its coverage counters back through a pipe so libFuzzer
+/// still sees the code the input reached. Comparison tracing (`-use_cmp`,
+/// `-use_value_profile`) stays in the child and is lost.
+///
+pub fn run(data: MoveArg) {
+    let result = SNAPSHOT.with(|snapshot| {
+        let mut snapshot = snapshot.borrow_mut();
+        if snapshot.is_none() {
+            *snapshot = Some(Snapshot::new()?);
+        }
+        fork_and_execute(snapshot.as_ref().unwrap(), data)
+    });
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
+
+fn fork_and_execute(snapshot: &Snapshot, data: MoveArg) -> Result<()> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error()).context("could not create a pipe");
+    }
+    let [read_fd, write_fd] = fds;
+
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()).context("could not fork the snapshot"),
+        0 => {
+            unsafe { libc::close(read_fd) };
+            // A failing execution panics, and the panic hook aborts the child.
+            if let Err(e) = snapshot.execute(data) {
+                panic!("{}", e);
+            }
+            let mut pipe = unsafe { File::from_raw_fd(write_fd) };
+            let code = match pipe.write_all(unsafe { counters() }) {
+                Ok(()) => 0,
+                Err(_) => 1,
+            };
+            // Skip destructors and libFuzzer's exit handlers, the parent owns them.
+            unsafe { libc::_exit(code) }
+        }
+        pid => {
+            unsafe { libc::close(write_fd) };
+            let mut child_counters = Vec::new();
+            unsafe { File::from_raw_fd(read_fd) }
+                .read_to_end(&mut child_counters)
+                .context("could not read the coverage of the snapshot child")?;
+
+            let mut status = 0;
+            if unsafe { libc::waitpid(pid, &mut status, 0) } == -1 {
+                return Err(io::Error::last_os_error())
+                    .context("could not wait for the snapshot child");
+            }
+            if !libc::WIFEXITED(status) || libc::WEXITSTATUS(status) != 0 {
+                // The child already printed its report; crash here so that
+                // libFuzzer saves the input as an artifact.
+                eprintln!(
+                    "snapshot child {} failed with wait status {:#x}",
+                    pid, status
+                );
+                std::process::abort();
+            }
+
+            for (counter, child) in unsafe { counters() }.iter_mut().zip(child_counters) {
+                *counter = (*counter).max(child);
+            }
+            Ok(())
+        }
+    }
+}
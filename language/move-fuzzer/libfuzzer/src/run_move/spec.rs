@@ -0,0 +1,158 @@
+use anyhow::{anyhow, bail, Context, Result};
+use move_cli::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use move_cli::DEFAULT_STORAGE_DIR;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::IdentStr;
+use move_core_types::parser::parse_struct_tag;
+use move_core_types::transaction_argument::TransactionArgument;
+use move_core_types::value::{MoveStruct, MoveValue};
+use move_package::{BuildConfig, ModelConfig};
+use move_stackless_bytecode_interpreter::concrete::runtime::{
+    convert_move_struct_tag, convert_move_value,
+};
+use move_stackless_bytecode_interpreter::concrete::settings::InterpreterSettings;
+use move_stackless_bytecode_interpreter::concrete::ty::BaseType;
+use move_stackless_bytecode_interpreter::concrete::value::GlobalState;
+use move_stackless_bytecode_interpreter::StacklessBytecodeInterpreter;
+use std::path::Path;
+
+use crate::run_move::move_args::ToTransactionArgument;
+use crate::run_move::package;
+use crate::run_move::sequence::TxnSequence;
+
+thread_local! {
+    // Building the model and running the pipeline of the interpreter is slow, so
+    // it is done once. Every module is a target, so the specs of the package
+    // under test are checked too, not only those of the fuzz package.
+    pub(crate) static INTERPRETER: StacklessBytecodeInterpreter<'static> = {
+        let config = ModelConfig {
+            all_files_as_targets: true,
+            target_filter: None,
+        };
+        let env = BuildConfig::default()
+            .move_model_for_package(Path::new("./fuzz"), config)
+            .unwrap_or_else(|e| panic!("could not build the Move model of the fuzz package: {}", e));
+        StacklessBytecodeInterpreter::new(Box::leak(Box::new(env)), None, InterpreterSettings::default())
+    };
+}
+
+///
+/// The resources of the storage sequences run against, as the interpreter
+/// holds them, to take before running a sequence so that the interpreter runs
+/// it from the same state as the VM.
+///
+pub fn starting_state() -> Result<GlobalState> {
+    let storage = package::state(None, Path::new(DEFAULT_STORAGE_DIR))?;
+    INTERPRETER.with(|interpreter| global_state(interpreter, &storage))
+}
+
+fn global_state(
+    interpreter: &StacklessBytecodeInterpreter,
+    storage: &OnDiskStateView,
+) -> Result<GlobalState> {
+    let mut state = GlobalState::default();
+    for path in storage.resource_paths() {
+        if !storage.is_resource_path(&path) {
+            continue;
+        }
+        // Resources are stored in `0x<address>/resources/<struct tag>.bcs`.
+        let address = path
+            .parent()
+            .and_then(Path::parent)
+            .and_then(Path::file_name)
+            .and_then(|name| AccountAddress::from_hex_literal(&name.to_string_lossy()).ok())
+            .ok_or_else(|| anyhow!("no address in the resource path {}", path.display()))?;
+        let tag = path
+            .file_stem()
+            .map(|stem| parse_struct_tag(&stem.to_string_lossy()))
+            .ok_or_else(|| anyhow!("no struct tag in the resource path {}", path.display()))??;
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("could not read resource {}", path.display()))?;
+
+        let inst = convert_move_struct_tag(interpreter.env, &tag)
+            .map_err(|e| anyhow!("the Move model has no struct {}: {:?}", tag, e))?;
+        let value = MoveStruct::simple_deserialize(&bytes, &inst.to_move_struct_layout())
+            .with_context(|| format!("could not decode resource {}", path.display()))?;
+        let value = convert_move_value(
+            &MoveValue::Struct(value),
+            &BaseType::mk_struct(inst.clone()),
+        )
+        .map_err(|e| anyhow!("the interpreter cannot hold resource {}: {:?}", tag, e))?;
+        state.put_resource(address, inst, value);
+    }
+    Ok(state)
+}
+
+///
+/// Runs the calls of `sequence` again in the stackless bytecode interpreter of
+/// the Move Prover, from `state`, which evaluates the `aborts_if`, `ensures`
+/// and invariants of the functions they go through, and fails at the first
+/// call violating one.
+///
+/// A call aborting in the interpreter leaves the global state as it was, like
+/// it does in the VM.
+///
+pub fn check_sequence(sequence: &TxnSequence, mut state: GlobalState) -> Result<()> {
+    INTERPRETER.with(|interpreter| {
+        let env = interpreter.env;
+        for (i, call) in sequence.calls.iter().enumerate() {
+            let module = env
+                .find_module_by_name(env.symbol_pool().make(&call.module))
+                .ok_or_else(|| anyhow!("the Move model has no module {}", call.module))?;
+            let function = IdentStr::new(&call.function)?;
+
+            let mut args = Vec::new();
+            for &idx in &call.signers {
+                let signer = sequence
+                    .accounts
+                    .get(idx)
+                    .ok_or_else(|| anyhow!("signer index {} is out of bounds", idx))?;
+                args.push(MoveValue::Signer(*signer));
+            }
+            args.extend(
+                call.args
+                    .to_transaction_argument()
+                    .into_iter()
+                    .map(move_value),
+            );
+
+            let (result, _, new_state) = interpreter.interpret(
+                &module.get_verified_module().self_id(),
+                function,
+                &call.type_args,
+                &args,
+                &state,
+            );
+            if let Some(report) = interpreter.report_property_checking_results() {
+                bail!(
+                    "call {} ({}::{}) violates a spec condition\n{}",
+                    i,
+                    call.module,
+                    call.function,
+                    report
+                );
+            }
+            if result.is_ok() {
+                state = new_state;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// The value of a transaction argument, as the interpreter takes it.
+pub(crate) fn move_value(arg: TransactionArgument) -> MoveValue {
+    match arg {
+        TransactionArgument::Bool(v) => MoveValue::Bool(v),
+        TransactionArgument::U8(v) => MoveValue::U8(v),
+        TransactionArgument::U16(v) => MoveValue::U16(v),
+        TransactionArgument::U32(v) => MoveValue::U32(v),
+        TransactionArgument::U64(v) => MoveValue::U64(v),
+        TransactionArgument::U128(v) => MoveValue::U128(v),
+        TransactionArgument::U256(v) => MoveValue::U256(v),
+        TransactionArgument::Address(v) => MoveValue::Address(v),
+        TransactionArgument::U8Vector(v) => {
+            MoveValue::Vector(v.into_iter().map(MoveValue::U8).collect())
+        }
+    }
+}
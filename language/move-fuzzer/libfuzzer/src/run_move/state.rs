@@ -0,0 +1,284 @@
+use anyhow::{Context, Result};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::effects::{ChangeSet, Op};
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_vm_test_utils::InMemoryStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+///
+/// The global storage of a stateful target: the modules and resources written
+/// by the inputs it ran, on top of the snapshot it started from.
+///
+/// `MOVE_FUZZ_SAVE_STATE` has the target write it as it goes, and
+/// `MOVE_FUZZ_STATE` has it start from one instead of genesis. The package is
+/// published over it, so a snapshot outlives changes to the package code.
+/// Table entries are not part of it.
+///
+/// On disk, a snapshot is a [`StateJournal`]: the state it started from
+/// followed by the changes of every input since.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// The modules, by id
+    pub modules: BTreeMap<ModuleId, Vec<u8>>,
+    /// The resources, by account and type
+    pub resources: BTreeMap<(AccountAddress, StructTag), Vec<u8>>,
+}
+
+///
+/// The writes of an input to the global storage, `None` for a deletion.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateChanges {
+    /// The modules written, by id
+    pub modules: Vec<(ModuleId, Option<Vec<u8>>)>,
+    /// The resources written, by account and type
+    pub resources: Vec<((AccountAddress, StructTag), Option<Vec<u8>>)>,
+}
+
+impl StateChanges {
+    /// Whether the input left the storage as it was.
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty() && self.resources.is_empty()
+    }
+}
+
+impl StateSnapshot {
+    ///
+    /// Reads the snapshot saved at `path`, replaying the changes recorded
+    /// after it. A record cut short, as by a fuzzer killed while writing it,
+    /// ends the journal.
+    ///
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("could not read state snapshot {}", path.display()))?;
+        let mut state = Self::default();
+        let mut rest = bytes.as_slice();
+        while rest.len() >= 4 {
+            let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+            let Some(record) = rest[4..].get(..len) else {
+                break;
+            };
+            let changes: StateChanges = bcs::from_bytes(record)
+                .with_context(|| format!("could not decode state snapshot {}", path.display()))?;
+            state.replay(changes);
+            rest = &rest[4 + len..];
+        }
+        Ok(state)
+    }
+
+    ///
+    /// Writes the snapshot to `path`
+    ///
+    pub fn save(&self, path: &Path) -> Result<()> {
+        StateJournal::create(path, self).map(drop)
+    }
+
+    /// Records the effects of a successful execution, returning them as
+    /// changes to the snapshot.
+    pub(crate) fn apply(&mut self, changeset: &ChangeSet) -> StateChanges {
+        let mut changes = StateChanges::default();
+        for (address, name, op) in changeset.modules() {
+            let blob = match op {
+                Op::New(blob) | Op::Modify(blob) => Some(blob.to_vec()),
+                Op::Delete => None,
+            };
+            changes
+                .modules
+                .push((ModuleId::new(address, name.clone()), blob));
+        }
+        for (address, tag, op) in changeset.resources() {
+            let blob = match op {
+                Op::New(blob) | Op::Modify(blob) => Some(blob.to_vec()),
+                Op::Delete => None,
+            };
+            changes.resources.push(((address, tag.clone()), blob));
+        }
+        self.replay(changes.clone());
+        changes
+    }
+
+    fn replay(&mut self, changes: StateChanges) {
+        for (id, blob) in changes.modules {
+            match blob {
+                Some(blob) => self.modules.insert(id, blob),
+                None => self.modules.remove(&id),
+            };
+        }
+        for (key, blob) in changes.resources {
+            match blob {
+                Some(blob) => self.resources.insert(key, blob),
+                None => self.resources.remove(&key),
+            };
+        }
+    }
+
+    fn to_changes(&self) -> StateChanges {
+        StateChanges {
+            modules: self
+                .modules
+                .iter()
+                .map(|(id, blob)| (id.clone(), Some(blob.clone())))
+                .collect(),
+            resources: self
+                .resources
+                .iter()
+                .map(|(key, blob)| (key.clone(), Some(blob.clone())))
+                .collect(),
+        }
+    }
+
+    /// Writes the modules and resources of the snapshot to `storage`.
+    pub(crate) fn publish(&self, storage: &mut InMemoryStorage) {
+        for (id, blob) in &self.modules {
+            storage.publish_or_overwrite_module(id.clone(), blob.clone());
+        }
+        for ((address, tag), blob) in &self.resources {
+            storage.publish_or_overwrite_resource(*address, tag.clone(), blob.clone());
+        }
+    }
+}
+
+///
+/// A state snapshot being saved as a stateful target runs: the state it
+/// started from, written once, then the changes of each input appended as it
+/// succeeds, so saving costs what the input wrote rather than the whole state.
+///
+/// Every record is its length as 4 little-endian bytes followed by the BCS of
+/// its [`StateChanges`].
+///
+#[derive(Debug)]
+pub struct StateJournal {
+    path: PathBuf,
+    file: File,
+}
+
+impl StateJournal {
+    ///
+    /// Starts the journal at `path` over, from `state`
+    ///
+    pub fn create(path: &Path, state: &StateSnapshot) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let mut journal = Self {
+            path: path.to_owned(),
+            file,
+        };
+        journal.append(&state.to_changes())?;
+        Ok(journal)
+    }
+
+    ///
+    /// Records the `changes` of an input
+    ///
+    pub fn append(&mut self, changes: &StateChanges) -> Result<()> {
+        let bytes = bcs::to_bytes(changes)?;
+        let mut record = Vec::with_capacity(4 + bytes.len());
+        record.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        record.extend_from_slice(&bytes);
+        self.file
+            .write_all(&record)
+            .with_context(|| format!("failed to write to {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_core_types::identifier::Identifier;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("move-fuzz-state-{}-{}", std::process::id(), name))
+    }
+
+    fn balance(address: AccountAddress) -> StructTag {
+        StructTag {
+            address,
+            module: Identifier::new("bank").unwrap(),
+            name: Identifier::new("Balance").unwrap(),
+            type_params: vec![],
+        }
+    }
+
+    fn changeset(address: AccountAddress, op: Op<Vec<u8>>) -> ChangeSet {
+        let mut changeset = ChangeSet::new();
+        changeset
+            .add_resource_op(address, balance(address), op)
+            .unwrap();
+        changeset
+    }
+
+    #[test]
+    fn apply_returns_the_writes() {
+        let mut state = StateSnapshot::default();
+        let changes = state.apply(&changeset(AccountAddress::ONE, Op::New(vec![5])));
+        assert_eq!(
+            changes.resources,
+            [(
+                (AccountAddress::ONE, balance(AccountAddress::ONE)),
+                Some(vec![5])
+            )]
+        );
+        let changes = state.apply(&changeset(AccountAddress::ONE, Op::Delete));
+        assert_eq!(
+            changes.resources,
+            [((AccountAddress::ONE, balance(AccountAddress::ONE)), None)]
+        );
+        assert!(state.resources.is_empty());
+    }
+
+    #[test]
+    fn journal_replays_to_the_state() {
+        let path = temp_path("journal");
+        let mut state = StateSnapshot::default();
+        state.apply(&changeset(AccountAddress::ONE, Op::New(vec![5])));
+        let mut journal = StateJournal::create(&path, &state).unwrap();
+        for changeset in [
+            changeset(AccountAddress::TWO, Op::New(vec![7])),
+            changeset(AccountAddress::ONE, Op::Delete),
+            changeset(AccountAddress::TWO, Op::Modify(vec![8])),
+        ] {
+            journal.append(&state.apply(&changeset)).unwrap();
+        }
+        assert_eq!(StateSnapshot::load(&path).unwrap(), state);
+        assert_eq!(state.resources.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn journal_cut_short_ends_at_the_last_record() {
+        let path = temp_path("cut");
+        let mut state = StateSnapshot::default();
+        let mut journal = StateJournal::create(&path, &state).unwrap();
+        journal
+            .append(&state.apply(&changeset(AccountAddress::ONE, Op::New(vec![5]))))
+            .unwrap();
+        let saved = state.clone();
+        journal
+            .append(&state.apply(&changeset(AccountAddress::TWO, Op::New(vec![7]))))
+            .unwrap();
+        let len = std::fs::metadata(&path).unwrap().len();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+        assert_eq!(StateSnapshot::load(&path).unwrap(), saved);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn saved_snapshot_loads() {
+        let path = temp_path("save");
+        let mut state = StateSnapshot::default();
+        state.apply(&changeset(AccountAddress::ONE, Op::New(vec![5])));
+        state.save(&path).unwrap();
+        assert_eq!(StateSnapshot::load(&path).unwrap(), state);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
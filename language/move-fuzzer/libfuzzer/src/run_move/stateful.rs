@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Context, Result};
+use move_binary_format::CompiledModule;
+use move_cli::sandbox::utils::get_gas_status;
+use move_core_types::transaction_argument::convert_txn_args;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use std::cell::RefCell;
+
+use crate::run_move::move_args::{is_passable, MoveArg, ToTransactionArgument};
+use crate::run_move::outcome::{self, Outcome};
+use crate::run_move::state::{StateJournal, StateSnapshot};
+use crate::run_move::{frames, natives, package, script_file};
+
+thread_local! {
+    static RUNNER: RefCell<Option<StatefulRunner>> = RefCell::new(None);
+}
+
+///
+/// A VM and an in-memory global storage kept alive across fuzz inputs.
+///
+/// The effects of every successful script execution are applied to the
+/// storage, so later inputs run against the state left by earlier ones. The
+/// state lives in memory: it starts over, from genesis or the snapshot of
+/// `MOVE_FUZZ_STATE`, whenever the fuzzer process restarts after a crash, and
+/// every `reset_every` executions.
+///
+struct StatefulRunner {
+    vm: MoveVM,
+    storage: InMemoryStorage,
+    state: StateSnapshot,
+    journal: Option<StateJournal>,
+    script: Vec<u8>,
+    execs: u64,
+}
+
+impl StatefulRunner {
+    fn new() -> Result<Self> {
+        let context = package::context()?;
+
+        // Publish the package and its dependencies to a fresh storage, or over
+        // the snapshot to start from.
+        let mut storage = InMemoryStorage::new();
+        let state = match crate::MOVE_FUZZ_STATE.get() {
+            Some(path) => StateSnapshot::load(path)?,
+            None => StateSnapshot::default(),
+        };
+        state.publish(&mut storage);
+        let journal = match crate::MOVE_FUZZ_SAVE_STATE.get() {
+            Some(path) => Some(StateJournal::create(path, &state)?),
+            None => None,
+        };
+        for unit in context.package().all_modules() {
+            let bytes = unit.unit.serialize(None);
+            let module = CompiledModule::deserialize(&bytes)
+                .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
+            storage.publish_or_overwrite_module(module.self_id(), bytes);
+        }
+
+        let script_file = script_file();
+        let script = std::fs::read(&script_file)
+            .with_context(|| format!("could not read script {}", script_file.display()))?;
+
+        Ok(Self {
+            vm: MoveVM::new(natives()).map_err(|e| anyhow!("{:?}", e))?,
+            storage,
+            state,
+            journal,
+            script,
+            execs: 0,
+        })
+    }
+
+    ///
+    /// Runs the script on `data` and applies its effects if it succeeded,
+    /// returning its outcome, the gas it used out of `gas_budget` and, if it
+    /// failed, the failure with its Move frames.
+    ///
+    fn execute(
+        &mut self,
+        data: MoveArg,
+        gas_budget: Option<u64>,
+    ) -> Result<(Outcome, u64, Option<String>)> {
+        self.execs += 1;
+        let args = convert_txn_args(&data.to_transaction_argument());
+        let cost_table = &move_vm_test_utils::gas_schedule::INITIAL_COST_SCHEDULE;
+        let mut gas_status = get_gas_status(cost_table, gas_budget)?;
+        let budget = u64::from(gas_status.remaining_gas());
+
+        let extensions = natives::new_extensions([0; 32], &self.storage);
+        let mut session = self
+            .vm
+            .new_session_with_extensions(&self.storage, extensions);
+        let values =
+            match session.execute_script(self.script.as_slice(), vec![], args, &mut gas_status) {
+                Ok(values) => values,
+                Err(err) => {
+                    let failure = frames::describe(&err, &self.storage);
+                    let outcome = Outcome::Failed {
+                        status: err.major_status(),
+                        sub_status: err.sub_status(),
+                        location: format!("{:?}", err.location()),
+                    };
+                    let gas_used = budget - u64::from(gas_status.remaining_gas());
+                    return Ok((outcome, gas_used, Some(failure)));
+                }
+            };
+        let (changeset, events, extensions) = session
+            .finish_with_extensions()
+            .map_err(|e| anyhow!("{:?}", e.into_vm_status()))?;
+        #[cfg(feature = "table-extension")]
+        let table_changes = natives::table_changes(extensions)?;
+        #[cfg(not(feature = "table-extension"))]
+        drop(extensions);
+        let changes = self.state.apply(&changeset);
+        self.storage.apply_extended(
+            changeset,
+            #[cfg(feature = "table-extension")]
+            table_changes,
+        )?;
+        if let Some(journal) = self.journal.as_mut().filter(|_| !changes.is_empty()) {
+            journal.append(&changes)?;
+        }
+        let outcome = Outcome::Executed {
+            return_values: values
+                .return_values
+                .into_iter()
+                .map(|(bytes, _layout)| bytes)
+                .collect(),
+            events: events.iter().map(|event| format!("{:?}", event)).collect(),
+        };
+        let gas_used = budget - u64::from(gas_status.remaining_gas());
+        Ok((outcome, gas_used, None))
+    }
+}
+
+///
+/// Runs the script of the target against the persistent storage, panicking on failure.
+///
+/// The storage is rebuilt from the compiled package every `reset_every`
+/// executions; `0` keeps it for the whole fuzzing session. With
+/// `MOVE_FUZZ_BYTECODE_VERSIONS`, every input is first compared across the
+/// versions as script targets do. With `MOVE_FUZZ_OUTCOME_PATH`, the outcome of
+/// the input on a fresh storage is written there instead.
+///
+pub fn run(data: MoveArg, reset_every: u64) {
+    if !is_passable(&data) {
+        return;
+    }
+    // The outcome is recorded from the state the fuzzing session starts from.
+    if let Some(path) = crate::MOVE_FUZZ_OUTCOME_PATH.get() {
+        let result = StatefulRunner::new().and_then(|mut runner| {
+            let (outcome, gas_used, _) = runner.execute(data, Some(outcome::GAS_BUDGET))?;
+            outcome::write(path, &outcome, gas_used)
+        });
+        if let Err(e) = result {
+            panic!("{}", e);
+        }
+        return;
+    }
+    // The versions are compared from the published package, not from the
+    // state the inputs before left.
+    if let Some(versions) = crate::MOVE_FUZZ_BYTECODE_VERSIONS.get() {
+        if let Err(e) = outcome::compare_versions(&data, versions) {
+            panic!("{}", e);
+        }
+    }
+    let result = RUNNER.with(|runner| {
+        let mut runner = runner.borrow_mut();
+        let expired = match &*runner {
+            Some(r) => reset_every != 0 && r.execs >= reset_every,
+            None => true,
+        };
+        if expired {
+            *runner = Some(StatefulRunner::new()?);
+        }
+        match runner.as_mut().unwrap().execute(data, None)? {
+            (_, _, Some(failure)) => Err(anyhow!(failure)),
+            _ => Ok(()),
+        }
+    });
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,181 @@
+use arbitrary::Unstructured;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+
+pub use move_core_types::language_storage::TypeTag;
+
+///
+/// A set of abilities, as in `copy + drop`
+///
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Abilities(u8);
+
+impl Abilities {
+    /// No ability
+    pub const NONE: Abilities = Abilities(0);
+    /// `copy`
+    pub const COPY: Abilities = Abilities(0x1);
+    /// `drop`
+    pub const DROP: Abilities = Abilities(0x2);
+    /// `store`
+    pub const STORE: Abilities = Abilities(0x4);
+    /// `key`
+    pub const KEY: Abilities = Abilities(0x8);
+    /// The abilities of primitive types and of vectors of them
+    pub const PRIMITIVES: Abilities = Abilities(0x7);
+
+    ///
+    /// The abilities of both sets
+    ///
+    pub const fn with(self, other: Abilities) -> Abilities {
+        Abilities(self.0 | other.0)
+    }
+
+    ///
+    /// Whether every ability of `other` is in the set
+    ///
+    pub const fn contains(self, other: Abilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The abilities the type arguments of a type with these abilities need:
+    /// `key` needs `store`, every other ability itself.
+    fn required_of_arguments(self) -> Abilities {
+        let mut required = Abilities(self.0 & !Self::KEY.0);
+        if self.contains(Self::KEY) {
+            required = required.with(Self::STORE);
+        }
+        required
+    }
+}
+
+///
+/// A type parameter of a [`StructType`]
+///
+#[derive(Clone, Copy, Debug)]
+pub struct TypeParam {
+    /// The abilities its type arguments need
+    pub constraints: Abilities,
+    /// Whether it is declared `phantom`
+    pub phantom: bool,
+}
+
+///
+/// A struct declared in the fuzzed package, that type arguments can instantiate
+///
+#[derive(Clone, Copy, Debug)]
+pub struct StructType {
+    /// Address of the module declaring the struct, as in `0x42`
+    pub address: &'static str,
+    /// Name of the module declaring the struct
+    pub module: &'static str,
+    /// Name of the struct
+    pub name: &'static str,
+    /// The abilities the struct is declared with
+    pub abilities: Abilities,
+    /// Its type parameters, instantiated in turn when it is picked
+    pub type_params: &'static [TypeParam],
+}
+
+///
+/// Draws a type argument with the abilities of `constraints`: a primitive type,
+/// a vector or one of `structs`, nested at most `max_depth` levels deep.
+///
+/// One input in four instantiates as deeply as `max_depth` allows, to reach
+/// the limits of the loader on generic instantiations.
+///
+pub fn type_arg(
+    u: &mut Unstructured,
+    structs: &[StructType],
+    constraints: Abilities,
+    max_depth: usize,
+) -> arbitrary::Result<TypeTag> {
+    let deep = u.ratio(1, 4)?;
+    type_arg_at(u, structs, constraints, max_depth, deep)
+}
+
+/// The shapes a type argument can take.
+#[derive(Clone, Copy)]
+enum Shape {
+    Primitive,
+    Vector,
+    Struct(StructType),
+}
+
+fn type_arg_at(
+    u: &mut Unstructured,
+    structs: &[StructType],
+    constraints: Abilities,
+    depth: usize,
+    deep: bool,
+) -> arbitrary::Result<TypeTag> {
+    let mut shapes = Vec::new();
+    if Abilities::PRIMITIVES.contains(constraints) {
+        shapes.push(Shape::Primitive);
+        if depth > 0 {
+            shapes.push(Shape::Vector);
+        }
+    }
+    for s in structs {
+        if s.abilities.contains(constraints) && (depth > 0 || s.type_params.is_empty()) {
+            shapes.push(Shape::Struct(*s));
+        }
+    }
+    // Deep instantiations only go down nested shapes while there are some.
+    let nested: Vec<_> = shapes
+        .iter()
+        .copied()
+        .filter(|shape| match shape {
+            Shape::Primitive => false,
+            Shape::Vector => true,
+            Shape::Struct(s) => !s.type_params.is_empty(),
+        })
+        .collect();
+    let shape = if deep && !nested.is_empty() {
+        *u.choose(&nested)?
+    } else {
+        *u.choose(&shapes)?
+    };
+
+    Ok(match shape {
+        Shape::Primitive => u
+            .choose(&[
+                TypeTag::Bool,
+                TypeTag::U8,
+                TypeTag::U16,
+                TypeTag::U32,
+                TypeTag::U64,
+                TypeTag::U128,
+                TypeTag::U256,
+                TypeTag::Address,
+            ])?
+            .clone(),
+        Shape::Vector => TypeTag::Vector(Box::new(type_arg_at(
+            u,
+            structs,
+            constraints,
+            depth - 1,
+            deep,
+        )?)),
+        Shape::Struct(s) => {
+            let required = constraints.required_of_arguments();
+            let mut type_params = Vec::with_capacity(s.type_params.len());
+            for param in s.type_params {
+                let constraints = if param.phantom {
+                    param.constraints
+                } else {
+                    param.constraints.with(required)
+                };
+                type_params.push(type_arg_at(u, structs, constraints, depth - 1, deep)?);
+            }
+            TypeTag::Struct(Box::new(StructTag {
+                address: AccountAddress::from_hex_literal(s.address)
+                    .expect("struct types are declared at valid addresses"),
+                module: Identifier::new(s.module).expect("module names are identifiers"),
+                name: Identifier::new(s.name).expect("struct names are identifiers"),
+                type_params,
+            }))
+        }
+    })
+}
@@ -0,0 +1,431 @@
+//! Structure-aware generators for Move runtime values.
+//!
+//! Fuzz harnesses receive raw bytes from libFuzzer; this crate turns them into
+//! well-formed Move values (addresses, signers, bounded integers, vectors,
+//! nested structs and type tags) on top of [`arbitrary::Unstructured`], so the
+//! mutator explores the VM instead of the argument decoder. The [`scenario`]
+//! combinators draw several signers and the order of their calls.
+//!
+//! ```ignore
+//! let gen = MoveValueGen::new(GenConfig::adversarial());
+//! let amount: u64 = gen.int_in_range(u, 0..=1_000_000)?;
+//! let owner = gen.signer(u)?;
+//! ```
+#![deny(missing_docs, missing_debug_implementations)]
+
+use arbitrary::{unstructured::Int, Result, Unstructured};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    u256::U256,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use std::ops::RangeInclusive;
+
+pub use arbitrary;
+
+pub mod scenario;
+pub mod source;
+
+/// How values are drawn from the fuzzer input.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum Strategy {
+    /// Only values a well-behaved client would submit: addresses from the
+    /// configured pool, short vectors and shallow type tags.
+    #[default]
+    ValidOnly,
+    /// Bias towards edge cases: boundary integers, reserved addresses,
+    /// maximum-length vectors and deeply nested type tags.
+    Adversarial,
+}
+
+const RESERVED_ADDRESSES: [AccountAddress; 3] = [
+    AccountAddress::ZERO,
+    AccountAddress::ONE,
+    AccountAddress::new([0xff; AccountAddress::LENGTH]),
+];
+
+/// Where addresses are drawn from, see [`GenConfig::address_strategies`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AddressStrategy {
+    /// One of [`GenConfig::address_pool`], e.g. the accounts of the harness.
+    Pool,
+    /// Any address.
+    Arbitrary,
+    /// One of [`GenConfig::published_addresses`], colliding with the modules
+    /// published by the package.
+    Published,
+    /// `0x0`, `0x1` or the maximum address.
+    Reserved,
+}
+
+impl AddressStrategy {
+    /// The name of the strategy, as in `pool`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AddressStrategy::Pool => "pool",
+            AddressStrategy::Arbitrary => "arbitrary",
+            AddressStrategy::Published => "published",
+            AddressStrategy::Reserved => "reserved",
+        }
+    }
+
+    /// The strategy named `name`, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            AddressStrategy::Pool,
+            AddressStrategy::Arbitrary,
+            AddressStrategy::Published,
+            AddressStrategy::Reserved,
+        ]
+        .into_iter()
+        .find(|strategy| strategy.name() == name)
+    }
+}
+
+/// Limits and strategy shared by all generators.
+#[derive(Clone, Debug)]
+pub struct GenConfig {
+    /// How values are drawn from the fuzzer input.
+    pub strategy: Strategy,
+    /// Upper bound on the length of generated vectors.
+    pub max_vector_len: usize,
+    /// Upper bound on the nesting of generated type tags.
+    pub max_depth: usize,
+    /// Upper bound on the number of type parameters of generated struct tags.
+    pub max_type_params: usize,
+    /// Addresses to pick from with [`Strategy::ValidOnly`] and
+    /// [`AddressStrategy::Pool`]; when empty any address can be generated.
+    pub address_pool: Vec<AccountAddress>,
+    /// Identifiers to pick module and struct names from; when empty random
+    /// valid identifiers are generated.
+    pub identifiers: Vec<Identifier>,
+    /// Where addresses are drawn from, each equally likely; when empty it
+    /// depends on [`GenConfig::strategy`].
+    pub address_strategies: Vec<AddressStrategy>,
+    /// Addresses the package publishes modules at, for
+    /// [`AddressStrategy::Published`].
+    pub published_addresses: Vec<AccountAddress>,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::ValidOnly,
+            max_vector_len: 64,
+            max_depth: 4,
+            max_type_params: 2,
+            address_pool: vec![],
+            identifiers: vec![],
+            address_strategies: vec![],
+            published_addresses: vec![],
+        }
+    }
+}
+
+impl GenConfig {
+    /// The default limits with the [`Strategy::Adversarial`] strategy.
+    pub fn adversarial() -> Self {
+        Self {
+            strategy: Strategy::Adversarial,
+            max_vector_len: 1024,
+            max_depth: 16,
+            ..Self::default()
+        }
+    }
+
+    fn is_adversarial(&self) -> bool {
+        self.strategy == Strategy::Adversarial
+    }
+}
+
+/// Generates Move values from fuzzer input according to a [`GenConfig`].
+#[derive(Clone, Debug, Default)]
+pub struct MoveValueGen {
+    config: GenConfig,
+}
+
+impl MoveValueGen {
+    /// Creates a generator with the given configuration.
+    pub fn new(config: GenConfig) -> Self {
+        Self { config }
+    }
+
+    /// The configuration of this generator.
+    pub fn config(&self) -> &GenConfig {
+        &self.config
+    }
+
+    /// An account address.
+    pub fn address(&self, u: &mut Unstructured) -> Result<AccountAddress> {
+        if !self.config.address_strategies.is_empty() {
+            let pool = match u.choose(&self.config.address_strategies)? {
+                AddressStrategy::Pool => &self.config.address_pool[..],
+                AddressStrategy::Published => &self.config.published_addresses[..],
+                AddressStrategy::Reserved => &RESERVED_ADDRESSES[..],
+                AddressStrategy::Arbitrary => &[],
+            };
+            // An empty pool falls back to any address.
+            if !pool.is_empty() {
+                return Ok(*u.choose(pool)?);
+            }
+            return Ok(AccountAddress::new(u.arbitrary()?));
+        }
+        if self.config.is_adversarial() && u.ratio(1, 4)? {
+            return Ok(*u.choose(&RESERVED_ADDRESSES)?);
+        }
+        if !self.config.is_adversarial() && !self.config.address_pool.is_empty() {
+            return Ok(*u.choose(&self.config.address_pool)?);
+        }
+        Ok(AccountAddress::new(u.arbitrary()?))
+    }
+
+    /// A signer value for an address drawn like [`MoveValueGen::address`].
+    pub fn signer(&self, u: &mut Unstructured) -> Result<MoveValue> {
+        Ok(MoveValue::Signer(self.address(u)?))
+    }
+
+    /// An integer within `range`; the adversarial strategy favours the bounds.
+    pub fn int_in_range<T: Int>(
+        &self,
+        u: &mut Unstructured,
+        range: RangeInclusive<T>,
+    ) -> Result<T> {
+        if self.config.is_adversarial() && u.ratio(1, 4)? {
+            return Ok(*u.choose(&[*range.start(), *range.end()])?);
+        }
+        u.int_in_range(range)
+    }
+
+    /// A `u256`; the adversarial strategy favours zero, one and the maximum.
+    pub fn u256(&self, u: &mut Unstructured) -> Result<U256> {
+        if self.config.is_adversarial() && u.ratio(1, 4)? {
+            return Ok(*u.choose(&[U256::zero(), U256::one(), U256::max_value()])?);
+        }
+        Ok(U256::from_le_bytes(&u.arbitrary()?))
+    }
+
+    /// The length of a vector, at most `max_vector_len`.
+    pub fn vector_len(&self, u: &mut Unstructured) -> Result<usize> {
+        let max = self.config.max_vector_len;
+        if self.config.is_adversarial() && u.ratio(1, 4)? {
+            return Ok(*u.choose(&[0, max])?);
+        }
+        u.int_in_range(0..=max)
+    }
+
+    /// A vector whose elements are produced by `elem`.
+    pub fn vector<T>(
+        &self,
+        u: &mut Unstructured,
+        mut elem: impl FnMut(&mut Unstructured) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let len = self.vector_len(u)?;
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            // Stop early instead of padding with zeroes once the input runs out.
+            if u.is_empty() {
+                break;
+            }
+            values.push(elem(u)?);
+        }
+        Ok(values)
+    }
+
+    /// A valid Move identifier.
+    pub fn identifier(&self, u: &mut Unstructured) -> Result<Identifier> {
+        if !self.config.identifiers.is_empty() {
+            return Ok(u.choose(&self.config.identifiers)?.clone());
+        }
+        const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+        let max_len = if self.config.is_adversarial() {
+            255
+        } else {
+            16
+        };
+        let len = u.int_in_range(1..=max_len)?;
+        let mut name = String::with_capacity(len);
+        name.push(*u.choose(FIRST)? as char);
+        for _ in 1..len {
+            name.push(*u.choose(REST)? as char);
+        }
+        Ok(Identifier::new(name).expect("generated identifiers are valid"))
+    }
+
+    /// A type tag nested at most `max_depth` levels deep.
+    pub fn type_tag(&self, u: &mut Unstructured) -> Result<TypeTag> {
+        self.type_tag_at(u, self.config.max_depth)
+    }
+
+    fn type_tag_at(&self, u: &mut Unstructured, depth: usize) -> Result<TypeTag> {
+        // Leaves only once the depth budget or the input is exhausted.
+        let variants = if depth == 0 || u.is_empty() { 9 } else { 11 };
+        Ok(match u.choose_index(variants)? {
+            0 => TypeTag::Bool,
+            1 => TypeTag::U8,
+            2 => TypeTag::U16,
+            3 => TypeTag::U32,
+            4 => TypeTag::U64,
+            5 => TypeTag::U128,
+            6 => TypeTag::U256,
+            7 => TypeTag::Address,
+            8 => TypeTag::Signer,
+            9 => TypeTag::Vector(Box::new(self.type_tag_at(u, depth - 1)?)),
+            _ => TypeTag::Struct(Box::new(self.struct_tag_at(u, depth - 1)?)),
+        })
+    }
+
+    /// A struct tag whose type parameters are nested at most `max_depth` levels deep.
+    pub fn struct_tag(&self, u: &mut Unstructured) -> Result<StructTag> {
+        self.struct_tag_at(u, self.config.max_depth)
+    }
+
+    fn struct_tag_at(&self, u: &mut Unstructured, depth: usize) -> Result<StructTag> {
+        let address = self.address(u)?;
+        let module = self.identifier(u)?;
+        let name = self.identifier(u)?;
+        let type_params_len = if depth == 0 {
+            0
+        } else {
+            self.int_in_range(u, 0..=self.config.max_type_params)?
+        };
+        let type_params = (0..type_params_len)
+            .map(|_| self.type_tag_at(u, depth))
+            .collect::<Result<_>>()?;
+        Ok(StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+
+    /// A value of the given layout, including nested vectors and structs.
+    pub fn value(&self, u: &mut Unstructured, layout: &MoveTypeLayout) -> Result<MoveValue> {
+        Ok(match layout {
+            MoveTypeLayout::Bool => MoveValue::Bool(u.arbitrary()?),
+            MoveTypeLayout::U8 => MoveValue::U8(self.int_in_range(u, 0..=u8::MAX)?),
+            MoveTypeLayout::U16 => MoveValue::U16(self.int_in_range(u, 0..=u16::MAX)?),
+            MoveTypeLayout::U32 => MoveValue::U32(self.int_in_range(u, 0..=u32::MAX)?),
+            MoveTypeLayout::U64 => MoveValue::U64(self.int_in_range(u, 0..=u64::MAX)?),
+            MoveTypeLayout::U128 => MoveValue::U128(self.int_in_range(u, 0..=u128::MAX)?),
+            MoveTypeLayout::U256 => MoveValue::U256(self.u256(u)?),
+            MoveTypeLayout::Address => MoveValue::Address(self.address(u)?),
+            MoveTypeLayout::Signer => self.signer(u)?,
+            MoveTypeLayout::Vector(elem) => {
+                MoveValue::Vector(self.vector(u, |u| self.value(u, elem))?)
+            }
+            MoveTypeLayout::Struct(layout) => MoveValue::Struct(self.move_struct(u, layout)?),
+        })
+    }
+
+    fn move_struct(&self, u: &mut Unstructured, layout: &MoveStructLayout) -> Result<MoveStruct> {
+        Ok(match layout {
+            MoveStructLayout::Runtime(fields) => MoveStruct::Runtime(
+                fields
+                    .iter()
+                    .map(|field| self.value(u, field))
+                    .collect::<Result<_>>()?,
+            ),
+            MoveStructLayout::WithFields(fields) => MoveStruct::WithFields(
+                fields
+                    .iter()
+                    .map(|field| Ok((field.name.clone(), self.value(u, &field.layout)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            MoveStructLayout::WithTypes { type_, fields } => MoveStruct::WithTypes {
+                type_: type_.clone(),
+                fields: fields
+                    .iter()
+                    .map(|field| Ok((field.name.clone(), self.value(u, &field.layout)?)))
+                    .collect::<Result<_>>()?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_core_types::value::MoveFieldLayout;
+
+    fn depth(tag: &TypeTag) -> usize {
+        match tag {
+            TypeTag::Vector(inner) => 1 + depth(inner),
+            TypeTag::Struct(s) => 1 + s.type_params.iter().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn type_tags_respect_max_depth() {
+        let data: Vec<u8> = (0..4096u32).map(|i| (i * 7 + 10) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        let gen = MoveValueGen::new(GenConfig {
+            max_depth: 3,
+            ..GenConfig::default()
+        });
+        while !u.is_empty() {
+            let tag = gen.type_tag(&mut u).unwrap();
+            assert!(depth(&tag) <= 3, "{} is too deep", tag);
+        }
+    }
+
+    #[test]
+    fn values_match_layout() {
+        let layout = MoveTypeLayout::Struct(MoveStructLayout::WithFields(vec![
+            MoveFieldLayout::new(Identifier::new("owner").unwrap(), MoveTypeLayout::Signer),
+            MoveFieldLayout::new(
+                Identifier::new("coins").unwrap(),
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64)),
+            ),
+        ]));
+        let pool = vec![AccountAddress::ONE];
+        let gen = MoveValueGen::new(GenConfig {
+            max_vector_len: 4,
+            address_pool: pool.clone(),
+            ..GenConfig::default()
+        });
+        let data = [0xabu8; 256];
+        let value = gen.value(&mut Unstructured::new(&data), &layout).unwrap();
+
+        let MoveValue::Struct(MoveStruct::WithFields(fields)) = value else {
+            panic!("expected a struct, got {:?}", value);
+        };
+        assert_eq!(
+            fields[0],
+            (
+                Identifier::new("owner").unwrap(),
+                MoveValue::Signer(pool[0])
+            )
+        );
+        assert!(matches!(&fields[1].1, MoveValue::Vector(v) if v.len() <= 4));
+    }
+
+    #[test]
+    fn addresses_follow_strategies() {
+        let published = AccountAddress::from_hex_literal("0x42").unwrap();
+        let gen = MoveValueGen::new(GenConfig {
+            address_strategies: vec![AddressStrategy::Published, AddressStrategy::Reserved],
+            published_addresses: vec![published],
+            ..GenConfig::default()
+        });
+        let data: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let address = gen.address(&mut u).unwrap();
+            assert!(
+                address == published || RESERVED_ADDRESSES.contains(&address),
+                "{} is neither published nor reserved",
+                address
+            );
+        }
+        assert_eq!(
+            AddressStrategy::from_name("published"),
+            Some(AddressStrategy::Published)
+        );
+        assert_eq!(AddressStrategy::from_name("any"), None);
+    }
+}
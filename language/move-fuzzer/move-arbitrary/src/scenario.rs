@@ -0,0 +1,323 @@
+//! Multi-agent scenarios: distinct signers playing roles, the order their
+//! calls run in and the resources they share.
+//!
+//! Access-control bugs hide between accounts: a user reaching an admin
+//! function, a withdrawal from a vault the caller does not own. Rather than
+//! drawing each signer on its own, a harness draws a [`Cast`] of distinct
+//! accounts for the roles it declares, interleaves their calls, and has calls
+//! pick the resources other actors created through [`Handles`].
+//!
+//! ```ignore
+//! let roles = [Role::new("admin").from(vec![publisher]), Role::new("user").count(1..=3)];
+//! let cast = gen.cast(u, &roles)?;
+//! for step in gen.interleaving(u, &[2, 4, 4])? {
+//!     let caller = cast.actors()[step.actor].signer();
+//!     ...
+//! }
+//! ```
+
+use crate::MoveValueGen;
+use arbitrary::{Result, Unstructured};
+use move_core_types::{account_address::AccountAddress, value::MoveValue};
+use std::ops::RangeInclusive;
+
+/// Addresses drawn for an actor before falling back to one derived from its
+/// index, so that casts stay distinct on short inputs and small pools.
+const MAX_ATTEMPTS: usize = 8;
+
+/// A part actors play in a scenario, such as `admin` or `user`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Role {
+    /// Name of the role
+    pub name: String,
+    /// How many actors play it
+    pub count: RangeInclusive<usize>,
+    /// Addresses its actors are drawn from; when empty they are drawn like
+    /// any address
+    pub addresses: Vec<AccountAddress>,
+}
+
+impl Role {
+    /// A role played by exactly one actor with any address.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            count: 1..=1,
+            addresses: vec![],
+        }
+    }
+
+    /// Has between `count.start()` and `count.end()` actors play the role.
+    pub fn count(mut self, count: RangeInclusive<usize>) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Draws the actors of the role from `addresses`, e.g. the publisher of
+    /// the package for an admin. There are no more actors than addresses.
+    pub fn from(mut self, addresses: Vec<AccountAddress>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+}
+
+/// An account playing a role.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Actor {
+    /// Index of its role in the roles the cast was drawn for
+    pub role: usize,
+    /// Its address, distinct from those of the other actors
+    pub address: AccountAddress,
+}
+
+impl Actor {
+    /// The signer of the actor.
+    pub fn signer(&self) -> MoveValue {
+        MoveValue::Signer(self.address)
+    }
+}
+
+/// The actors of a scenario, by role in the order the roles were given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cast {
+    actors: Vec<Actor>,
+}
+
+impl Cast {
+    /// Every actor.
+    pub fn actors(&self) -> &[Actor] {
+        &self.actors
+    }
+
+    /// The actors playing the role at index `role`.
+    pub fn playing(&self, role: usize) -> impl Iterator<Item = &Actor> {
+        self.actors.iter().filter(move |actor| actor.role == role)
+    }
+
+    /// The signers of the actors playing the role at index `role`.
+    pub fn signers(&self, role: usize) -> Vec<MoveValue> {
+        self.playing(role).map(Actor::signer).collect()
+    }
+}
+
+/// A call of an interleaving: the `call`th call of the actor at `actor`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// Index of the actor making the call
+    pub actor: usize,
+    /// Index of the call among those of the actor
+    pub call: usize,
+}
+
+/// Resources created during a scenario, each owned by an actor, for later
+/// calls to pick by handle whether or not the caller owns them.
+#[derive(Clone, Debug)]
+pub struct Handles<T> {
+    entries: Vec<(usize, T)>,
+}
+
+impl<T> Default for Handles<T> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+impl<T> Handles<T> {
+    /// Records `resource`, created by the actor at `owner`, and returns its handle.
+    pub fn insert(&mut self, owner: usize, resource: T) -> usize {
+        self.entries.push((owner, resource));
+        self.entries.len() - 1
+    }
+
+    /// The number of resources recorded.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no resource was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The owner and resource of `handle`.
+    pub fn get(&self, handle: usize) -> Option<(usize, &T)> {
+        self.entries
+            .get(handle)
+            .map(|(owner, resource)| (*owner, resource))
+    }
+
+    /// Any resource, with its owner.
+    pub fn pick(&self, u: &mut Unstructured) -> Result<Option<(usize, &T)>> {
+        self.pick_where(u, |_| true)
+    }
+
+    /// A resource owned by the actor at `actor`.
+    pub fn pick_owned(&self, u: &mut Unstructured, actor: usize) -> Result<Option<&T>> {
+        Ok(self
+            .pick_where(u, |owner| owner == actor)?
+            .map(|(_, resource)| resource))
+    }
+
+    /// A resource owned by another actor than the one at `actor`, the calls
+    /// access control should reject.
+    pub fn pick_foreign(&self, u: &mut Unstructured, actor: usize) -> Result<Option<(usize, &T)>> {
+        self.pick_where(u, |owner| owner != actor)
+    }
+
+    fn pick_where(
+        &self,
+        u: &mut Unstructured,
+        owned: impl Fn(usize) -> bool,
+    ) -> Result<Option<(usize, &T)>> {
+        let matching: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(owner, _)| owned(*owner))
+            .collect();
+        if matching.is_empty() {
+            return Ok(None);
+        }
+        let (owner, resource) = u.choose(&matching)?;
+        Ok(Some((*owner, resource)))
+    }
+}
+
+impl MoveValueGen {
+    /// Distinct actors for `roles`, as many for each as its count allows.
+    pub fn cast(&self, u: &mut Unstructured, roles: &[Role]) -> Result<Cast> {
+        let mut actors: Vec<Actor> = Vec::new();
+        for (role, spec) in roles.iter().enumerate() {
+            let count = self.int_in_range(u, spec.count.clone())?;
+            for _ in 0..count {
+                let taken =
+                    |address: &AccountAddress| actors.iter().any(|actor| actor.address == *address);
+                let address = if spec.addresses.is_empty() {
+                    let mut drawn = None;
+                    for _ in 0..MAX_ATTEMPTS {
+                        let address = self.address(u)?;
+                        if !taken(&address) {
+                            drawn = Some(address);
+                            break;
+                        }
+                    }
+                    match drawn {
+                        Some(address) => address,
+                        None => derived_address(actors.len(), taken),
+                    }
+                } else {
+                    let free: Vec<_> = spec.addresses.iter().filter(|a| !taken(a)).collect();
+                    // The addresses of the role are all cast already.
+                    if free.is_empty() {
+                        break;
+                    }
+                    **u.choose(&free)?
+                };
+                actors.push(Actor { role, address });
+            }
+        }
+        Ok(Cast { actors })
+    }
+
+    /// An order of the calls of actors making `calls[i]` calls each, keeping
+    /// the calls of each actor in order.
+    pub fn interleaving(&self, u: &mut Unstructured, calls: &[usize]) -> Result<Vec<Step>> {
+        let mut next = vec![0; calls.len()];
+        let mut steps = Vec::with_capacity(calls.iter().sum());
+        loop {
+            let pending: Vec<usize> = (0..calls.len()).filter(|&a| next[a] < calls[a]).collect();
+            if pending.is_empty() {
+                return Ok(steps);
+            }
+            let actor = *u.choose(&pending)?;
+            steps.push(Step {
+                actor,
+                call: next[actor],
+            });
+            next[actor] += 1;
+        }
+    }
+
+    /// A permutation of `0..len`, for calls whose order is free.
+    pub fn permutation(&self, u: &mut Unstructured, len: usize) -> Result<Vec<usize>> {
+        let mut order: Vec<usize> = (0..len).collect();
+        for i in (1..len).rev() {
+            order.swap(i, u.int_in_range(0..=i)?);
+        }
+        Ok(order)
+    }
+}
+
+/// An address none of the actors has, derived from `index`.
+fn derived_address(index: usize, taken: impl Fn(&AccountAddress) -> bool) -> AccountAddress {
+    let mut seed = 0x1000 + index as u64;
+    loop {
+        let mut bytes = [0; AccountAddress::LENGTH];
+        bytes[AccountAddress::LENGTH - 8..].copy_from_slice(&seed.to_be_bytes());
+        let address = AccountAddress::new(bytes);
+        if !taken(&address) {
+            return address;
+        }
+        seed += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GenConfig;
+    use std::collections::HashSet;
+
+    #[test]
+    fn casts_are_distinct() {
+        let publisher = AccountAddress::from_hex_literal("0x42").unwrap();
+        let roles = [
+            Role::new("admin").from(vec![publisher]),
+            Role::new("user").count(2..=4),
+            Role::new("auditor").count(0..=1),
+        ];
+        // A single-address pool forces the fallback to derived addresses.
+        let gen = MoveValueGen::new(GenConfig {
+            address_pool: vec![AccountAddress::ONE],
+            ..GenConfig::default()
+        });
+        for data in [vec![], vec![0xff; 64], (0..=255).collect()] {
+            let cast = gen.cast(&mut Unstructured::new(&data), &roles).unwrap();
+            let addresses: HashSet<_> = cast.actors().iter().map(|a| a.address).collect();
+            assert_eq!(addresses.len(), cast.actors().len());
+            assert_eq!(cast.signers(0), [MoveValue::Signer(publisher)]);
+            assert!((2..=4).contains(&cast.playing(1).count()));
+            assert!(cast.playing(2).count() <= 1);
+        }
+    }
+
+    #[test]
+    fn interleavings_keep_each_actor_in_order() {
+        let gen = MoveValueGen::default();
+        let data: Vec<u8> = (0..64u32).map(|i| (i * 37) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        let steps = gen.interleaving(&mut u, &[3, 0, 2]).unwrap();
+        assert_eq!(steps.len(), 5);
+        for actor in [0, 2] {
+            let calls: Vec<_> = steps
+                .iter()
+                .filter(|s| s.actor == actor)
+                .map(|s| s.call)
+                .collect();
+            assert_eq!(calls, (0..calls.len()).collect::<Vec<_>>());
+        }
+
+        let mut order = gen.permutation(&mut u, 6).unwrap();
+        order.sort();
+        assert_eq!(order, [0, 1, 2, 3, 4, 5]);
+
+        let mut vaults = Handles::default();
+        vaults.insert(0, "vault of 0");
+        vaults.insert(1, "vault of 1");
+        assert_eq!(
+            vaults.pick_foreign(&mut u, 0).unwrap(),
+            Some((1, &"vault of 1"))
+        );
+        assert_eq!(vaults.pick_owned(&mut u, 0).unwrap(), Some(&"vault of 0"));
+        assert_eq!(vaults.pick_owned(&mut u, 2).unwrap(), None);
+    }
+}
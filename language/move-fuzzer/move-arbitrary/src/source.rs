@@ -0,0 +1,814 @@
+//! Grammar-based generation of Move source code.
+//!
+//! Inputs decode to a small Move AST that always prints as syntactically
+//! valid source, so the compiler gets past the lexer and parser and spends the
+//! fuzzing time in expansion, naming and typing. Names come from small pools
+//! (`v0`, `f1`, `S2`, ...) so that references resolve often enough to be
+//! interesting while still producing plenty of ill-typed programs.
+
+use arbitrary::{Result, Unstructured};
+use std::fmt;
+
+/// A module at address `0x42`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Module {
+    /// Name of the module
+    pub name: String,
+    /// Struct declarations, named `S0`, `S1`, ...
+    pub structs: Vec<StructDef>,
+    /// Function declarations, named `f0`, `f1`, ...
+    pub functions: Vec<Function>,
+}
+
+/// A struct declaration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructDef {
+    /// Name of the struct
+    pub name: String,
+    /// Declared abilities
+    pub abilities: Vec<Ability>,
+    /// Fields and their types
+    pub fields: Vec<(String, Type)>,
+}
+
+/// A struct ability.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Ability {
+    Copy,
+    Drop,
+    Store,
+    Key,
+}
+
+/// A function visibility.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Visibility {
+    Private,
+    Public,
+    Friend,
+    Entry,
+    PublicEntry,
+}
+
+/// A function declaration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Function {
+    /// Name of the function
+    pub name: String,
+    /// Visibility and `entry` modifier
+    pub visibility: Visibility,
+    /// Parameters and their types
+    pub params: Vec<(String, Type)>,
+    /// Return type, `None` for `()`
+    pub ret: Option<Type>,
+    /// Statements of the body
+    pub body: Vec<Stmt>,
+    /// Trailing expression of the body
+    pub tail: Option<Expr>,
+}
+
+/// A type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Type {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<Type>),
+    Struct(String),
+    Ref(bool, Box<Type>),
+}
+
+/// A binary operator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    BitAnd,
+    BitOr,
+    Xor,
+    Shl,
+    Shr,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    And,
+    Or,
+}
+
+/// An expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Expr {
+    Bool(bool),
+    /// A number with an optional type suffix
+    Number(u128, Option<Type>),
+    Address(u64),
+    Var(String),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Call(String, Vec<Expr>),
+    Pack(String, Vec<(String, Expr)>),
+    Field(Box<Expr>, String),
+    Vector(Vec<Expr>),
+    /// `&e` or, when the flag is set, `&mut e`
+    Borrow(bool, Box<Expr>),
+    Deref(Box<Expr>),
+    Cast(Box<Expr>, Type),
+    IfElse(Box<Expr>, Box<Expr>, Box<Expr>),
+    Block(Vec<Stmt>, Box<Expr>),
+}
+
+/// A statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Stmt {
+    Let(String, Option<Type>, Expr),
+    Assign(String, Expr),
+    /// `*lhs = rhs`
+    Mutate(Expr, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Loop(Vec<Stmt>),
+    Break,
+    Continue,
+    Abort(Expr),
+    Return(Option<Expr>),
+    Expr(Expr),
+}
+
+/// Limits of the generated programs.
+#[derive(Clone, Debug)]
+pub struct SourceConfig {
+    /// Upper bound on the nesting of expressions, statements and types.
+    pub max_depth: usize,
+    /// Upper bound on the number of structs in a module.
+    pub max_structs: usize,
+    /// Upper bound on the number of functions in a module.
+    pub max_functions: usize,
+    /// Upper bound on the number of fields of a struct and of parameters of a function.
+    pub max_fields: usize,
+    /// Upper bound on the number of statements of a block.
+    pub max_statements: usize,
+    /// Upper bound on the number of local variable names.
+    pub max_locals: usize,
+    /// Upper bound on the number of AST mutations applied by [`SourceGen::mutated_module`].
+    pub max_mutations: usize,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_structs: 3,
+            max_functions: 4,
+            max_fields: 4,
+            max_statements: 6,
+            max_locals: 6,
+            max_mutations: 8,
+        }
+    }
+}
+
+/// Generates and mutates Move modules from fuzzer input.
+#[derive(Clone, Debug, Default)]
+pub struct SourceGen {
+    config: SourceConfig,
+}
+
+/// Sizes of the name pools of the module being generated.
+#[derive(Copy, Clone)]
+struct Scope {
+    structs: usize,
+    functions: usize,
+}
+
+impl SourceGen {
+    /// Creates a generator with the given limits.
+    pub fn new(config: SourceConfig) -> Self {
+        Self { config }
+    }
+
+    /// A module generated from the grammar.
+    pub fn module(&self, u: &mut Unstructured) -> Result<Module> {
+        let scope = Scope {
+            structs: u.int_in_range(0..=self.config.max_structs)?,
+            functions: u.int_in_range(1..=self.config.max_functions.max(1))?,
+        };
+        let structs = (0..scope.structs)
+            .map(|i| self.struct_def(u, scope, i))
+            .collect::<Result<_>>()?;
+        let functions = (0..scope.functions)
+            .map(|i| self.function(u, scope, i))
+            .collect::<Result<_>>()?;
+        Ok(Module {
+            name: "m".to_owned(),
+            structs,
+            functions,
+        })
+    }
+
+    /// A module generated from the grammar, then rewritten by up to
+    /// `max_mutations` AST mutations driven by the rest of the input.
+    pub fn mutated_module(&self, u: &mut Unstructured) -> Result<Module> {
+        let mut module = self.module(u)?;
+        for _ in 0..self.config.max_mutations {
+            if u.is_empty() {
+                break;
+            }
+            self.mutate(&mut module, u)?;
+        }
+        Ok(module)
+    }
+
+    /// Applies one AST-level mutation: regenerating, swapping, deleting or
+    /// duplicating a statement, or changing a declared type.
+    pub fn mutate(&self, module: &mut Module, u: &mut Unstructured) -> Result<()> {
+        let scope = Scope {
+            structs: module.structs.len(),
+            functions: module.functions.len(),
+        };
+        if module.functions.is_empty() {
+            return Ok(());
+        }
+        let function = u.choose_index(module.functions.len())?;
+        let function = &mut module.functions[function];
+        let len = function.body.len();
+        match u.choose_index(6)? {
+            0 => {
+                let stmt = self.stmt(u, scope, self.config.max_depth)?;
+                let at = u.int_in_range(0..=len)?;
+                function.body.insert(at, stmt);
+            }
+            1 if len > 0 => {
+                let at = u.choose_index(len)?;
+                function.body[at] = self.stmt(u, scope, self.config.max_depth)?;
+            }
+            2 if len > 1 => {
+                let (a, b) = (u.choose_index(len)?, u.choose_index(len)?);
+                function.body.swap(a, b);
+            }
+            3 if len > 0 => {
+                function.body.remove(u.choose_index(len)?);
+            }
+            4 if len > 0 => {
+                let stmt = function.body[u.choose_index(len)?].clone();
+                function.body.insert(u.int_in_range(0..=len)?, stmt);
+            }
+            _ => {
+                if function.params.is_empty() {
+                    function.ret = Some(self.ty(u, scope, self.config.max_depth)?);
+                } else {
+                    let at = u.choose_index(function.params.len())?;
+                    function.params[at].1 = self.ty(u, scope, self.config.max_depth)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn struct_def(&self, u: &mut Unstructured, scope: Scope, index: usize) -> Result<StructDef> {
+        let mut abilities = Vec::new();
+        for ability in [Ability::Copy, Ability::Drop, Ability::Store, Ability::Key] {
+            if u.arbitrary()? {
+                abilities.push(ability);
+            }
+        }
+        let fields = (0..u.int_in_range(1..=self.config.max_fields.max(1))?)
+            .map(|i| Ok((format!("f{}", i), self.ty(u, scope, 1)?)))
+            .collect::<Result<_>>()?;
+        Ok(StructDef {
+            name: format!("S{}", index),
+            abilities,
+            fields,
+        })
+    }
+
+    fn function(&self, u: &mut Unstructured, scope: Scope, index: usize) -> Result<Function> {
+        let visibility = *u.choose(&[
+            Visibility::Private,
+            Visibility::Public,
+            Visibility::Friend,
+            Visibility::Entry,
+            Visibility::PublicEntry,
+        ])?;
+        let params = (0..u.int_in_range(0..=self.config.max_fields)?)
+            .map(|i| Ok((format!("v{}", i), self.param_ty(u, scope)?)))
+            .collect::<Result<_>>()?;
+        let ret = if u.arbitrary()? {
+            Some(self.param_ty(u, scope)?)
+        } else {
+            None
+        };
+        let body = self.block(u, scope, self.config.max_depth)?;
+        let tail = if ret.is_some() {
+            Some(self.expr(u, scope, self.config.max_depth)?)
+        } else {
+            None
+        };
+        Ok(Function {
+            name: format!("f{}", index),
+            visibility,
+            params,
+            ret,
+            body,
+            tail,
+        })
+    }
+
+    fn param_ty(&self, u: &mut Unstructured, scope: Scope) -> Result<Type> {
+        let ty = self.ty(u, scope, self.config.max_depth.min(2))?;
+        Ok(match u.choose_index(4)? {
+            0 => Type::Ref(false, Box::new(ty)),
+            1 => Type::Ref(true, Box::new(ty)),
+            _ => ty,
+        })
+    }
+
+    fn ty(&self, u: &mut Unstructured, scope: Scope, depth: usize) -> Result<Type> {
+        let leaves = 9 + usize::from(scope.structs > 0);
+        let variants = if depth == 0 || u.is_empty() {
+            leaves
+        } else {
+            leaves + 1
+        };
+        Ok(match u.choose_index(variants)? {
+            0 => Type::Bool,
+            1 => Type::U8,
+            2 => Type::U16,
+            3 => Type::U32,
+            4 => Type::U64,
+            5 => Type::U128,
+            6 => Type::U256,
+            7 => Type::Address,
+            8 => Type::Signer,
+            9 if scope.structs > 0 => Type::Struct(format!("S{}", u.choose_index(scope.structs)?)),
+            _ => Type::Vector(Box::new(self.ty(u, scope, depth - 1)?)),
+        })
+    }
+
+    fn block(&self, u: &mut Unstructured, scope: Scope, depth: usize) -> Result<Vec<Stmt>> {
+        let len = if depth == 0 {
+            0
+        } else {
+            u.int_in_range(0..=self.config.max_statements)?
+        };
+        let mut stmts = Vec::with_capacity(len);
+        for _ in 0..len {
+            if u.is_empty() {
+                break;
+            }
+            stmts.push(self.stmt(u, scope, depth)?);
+        }
+        Ok(stmts)
+    }
+
+    fn stmt(&self, u: &mut Unstructured, scope: Scope, depth: usize) -> Result<Stmt> {
+        let depth = depth.saturating_sub(1);
+        Ok(match u.choose_index(11)? {
+            0 | 1 => Stmt::Let(
+                self.var(u)?,
+                if u.arbitrary()? {
+                    Some(self.ty(u, scope, depth)?)
+                } else {
+                    None
+                },
+                self.expr(u, scope, depth)?,
+            ),
+            2 => Stmt::Assign(self.var(u)?, self.expr(u, scope, depth)?),
+            3 => Stmt::Mutate(self.expr(u, scope, depth)?, self.expr(u, scope, depth)?),
+            4 => Stmt::If(
+                self.expr(u, scope, depth)?,
+                self.block(u, scope, depth)?,
+                self.block(u, scope, depth)?,
+            ),
+            5 => Stmt::While(self.expr(u, scope, depth)?, self.block(u, scope, depth)?),
+            6 => Stmt::Loop(self.block(u, scope, depth)?),
+            7 if u.arbitrary()? => Stmt::Break,
+            7 => Stmt::Continue,
+            8 => Stmt::Abort(self.expr(u, scope, depth)?),
+            9 => Stmt::Return(if u.arbitrary()? {
+                Some(self.expr(u, scope, depth)?)
+            } else {
+                None
+            }),
+            _ => Stmt::Expr(self.expr(u, scope, depth)?),
+        })
+    }
+
+    fn expr(&self, u: &mut Unstructured, scope: Scope, depth: usize) -> Result<Expr> {
+        // Leaves only once the depth budget or the input is exhausted.
+        if depth == 0 || u.is_empty() {
+            return Ok(match u.choose_index(4)? {
+                0 => Expr::Bool(u.arbitrary()?),
+                1 => self.number(u)?,
+                2 => Expr::Address(u.arbitrary()?),
+                _ => Expr::Var(self.var(u)?),
+            });
+        }
+        let depth = depth - 1;
+        Ok(match u.choose_index(15)? {
+            0 => Expr::Bool(u.arbitrary()?),
+            1 => self.number(u)?,
+            2 => Expr::Address(u.arbitrary()?),
+            3 | 4 => Expr::Var(self.var(u)?),
+            5 | 6 => Expr::Binary(
+                *u.choose(&[
+                    BinOp::Add,
+                    BinOp::Sub,
+                    BinOp::Mul,
+                    BinOp::Div,
+                    BinOp::Mod,
+                    BinOp::BitAnd,
+                    BinOp::BitOr,
+                    BinOp::Xor,
+                    BinOp::Shl,
+                    BinOp::Shr,
+                    BinOp::Eq,
+                    BinOp::Neq,
+                    BinOp::Lt,
+                    BinOp::Gt,
+                    BinOp::Le,
+                    BinOp::Ge,
+                    BinOp::And,
+                    BinOp::Or,
+                ])?,
+                Box::new(self.expr(u, scope, depth)?),
+                Box::new(self.expr(u, scope, depth)?),
+            ),
+            7 => Expr::Not(Box::new(self.expr(u, scope, depth)?)),
+            8 => Expr::Call(
+                format!("f{}", u.choose_index(scope.functions.max(1))?),
+                self.exprs(u, scope, depth)?,
+            ),
+            9 if scope.structs > 0 => {
+                let fields = (0..u.int_in_range(0..=self.config.max_fields)?)
+                    .map(|i| Ok((format!("f{}", i), self.expr(u, scope, depth)?)))
+                    .collect::<Result<_>>()?;
+                Expr::Pack(format!("S{}", u.choose_index(scope.structs)?), fields)
+            }
+            9 => Expr::Field(
+                Box::new(self.expr(u, scope, depth)?),
+                format!("f{}", u.choose_index(self.config.max_fields.max(1))?),
+            ),
+            10 => Expr::Vector(self.exprs(u, scope, depth)?),
+            11 => Expr::Borrow(u.arbitrary()?, Box::new(self.expr(u, scope, depth)?)),
+            12 => Expr::Deref(Box::new(self.expr(u, scope, depth)?)),
+            13 => Expr::Cast(
+                Box::new(self.expr(u, scope, depth)?),
+                self.ty(u, scope, depth)?,
+            ),
+            _ => {
+                if u.arbitrary()? {
+                    Expr::IfElse(
+                        Box::new(self.expr(u, scope, depth)?),
+                        Box::new(self.expr(u, scope, depth)?),
+                        Box::new(self.expr(u, scope, depth)?),
+                    )
+                } else {
+                    Expr::Block(
+                        self.block(u, scope, depth)?,
+                        Box::new(self.expr(u, scope, depth)?),
+                    )
+                }
+            }
+        })
+    }
+
+    fn exprs(&self, u: &mut Unstructured, scope: Scope, depth: usize) -> Result<Vec<Expr>> {
+        (0..u.int_in_range(0..=self.config.max_fields)?)
+            .map(|_| self.expr(u, scope, depth))
+            .collect()
+    }
+
+    fn number(&self, u: &mut Unstructured) -> Result<Expr> {
+        let suffix = u
+            .choose(&[
+                None,
+                Some(Type::U8),
+                Some(Type::U16),
+                Some(Type::U32),
+                Some(Type::U64),
+                Some(Type::U128),
+                Some(Type::U256),
+            ])?
+            .clone();
+        // Small values and the bounds of each integer type are the interesting ones.
+        let value = *u.choose(&[
+            0,
+            1,
+            u128::from(u8::MAX),
+            u128::from(u16::MAX),
+            u128::from(u32::MAX),
+            u128::from(u64::MAX),
+            u128::MAX,
+        ])?;
+        let value = if u.arbitrary()? {
+            value
+        } else {
+            u.arbitrary::<u8>()?.into()
+        };
+        Ok(Expr::Number(value, suffix))
+    }
+
+    fn var(&self, u: &mut Unstructured) -> Result<String> {
+        Ok(format!(
+            "v{}",
+            u.choose_index(self.config.max_locals.max(1))?
+        ))
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "module 0x42::{} {{", self.name)?;
+        for (i, def) in self.structs.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "    struct {}", def.name)?;
+            if !def.abilities.is_empty() {
+                let abilities: Vec<_> = def.abilities.iter().map(|a| a.to_string()).collect();
+                write!(f, " has {}", abilities.join(", "))?;
+            }
+            writeln!(f, " {{")?;
+            for (name, ty) in &def.fields {
+                writeln!(f, "        {}: {},", name, ty)?;
+            }
+            writeln!(f, "    }}")?;
+        }
+        for (i, function) in self.functions.iter().enumerate() {
+            if i > 0 || !self.structs.is_empty() {
+                writeln!(f)?;
+            }
+            let params: Vec<_> = function
+                .params
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .collect();
+            write!(
+                f,
+                "    {}fun {}({})",
+                function.visibility,
+                function.name,
+                params.join(", ")
+            )?;
+            if let Some(ret) = &function.ret {
+                write!(f, ": {}", ret)?;
+            }
+            writeln!(f, " {{")?;
+            for stmt in &function.body {
+                writeln!(f, "        {}", stmt)?;
+            }
+            if let Some(tail) = &function.tail {
+                writeln!(f, "        {}", tail)?;
+            }
+            writeln!(f, "    }}")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Ability::Copy => "copy",
+            Ability::Drop => "drop",
+            Ability::Store => "store",
+            Ability::Key => "key",
+        })
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Visibility::Private => "",
+            Visibility::Public => "public ",
+            Visibility::Friend => "public(friend) ",
+            Visibility::Entry => "entry ",
+            Visibility::PublicEntry => "public entry ",
+        })
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Bool => f.write_str("bool"),
+            Type::U8 => f.write_str("u8"),
+            Type::U16 => f.write_str("u16"),
+            Type::U32 => f.write_str("u32"),
+            Type::U64 => f.write_str("u64"),
+            Type::U128 => f.write_str("u128"),
+            Type::U256 => f.write_str("u256"),
+            Type::Address => f.write_str("address"),
+            Type::Signer => f.write_str("signer"),
+            Type::Vector(elem) => write!(f, "vector<{}>", elem),
+            Type::Struct(name) => f.write_str(name),
+            Type::Ref(false, ty) => write!(f, "&{}", ty),
+            Type::Ref(true, ty) => write!(f, "&mut {}", ty),
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Mod => "%",
+            BinOp::BitAnd => "&",
+            BinOp::BitOr => "|",
+            BinOp::Xor => "^",
+            BinOp::Shl => "<<",
+            BinOp::Shr => ">>",
+            BinOp::Eq => "==",
+            BinOp::Neq => "!=",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::Le => "<=",
+            BinOp::Ge => ">=",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+        })
+    }
+}
+
+/// Writes `items` separated by `, `.
+fn comma_separated<T: fmt::Display>(f: &mut fmt::Formatter, items: &[T]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Writes `{ stmts }` on a single line.
+fn block(f: &mut fmt::Formatter, stmts: &[Stmt], tail: Option<&Expr>) -> fmt::Result {
+    f.write_str("{")?;
+    for stmt in stmts {
+        write!(f, " {}", stmt)?;
+    }
+    if let Some(tail) = tail {
+        write!(f, " {}", tail)?;
+    }
+    f.write_str(" }")
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Compound expressions are parenthesized so that printing never
+        // depends on operator precedence.
+        match self {
+            Expr::Bool(b) => write!(f, "{}", b),
+            Expr::Number(n, None) => write!(f, "{}", n),
+            Expr::Number(n, Some(suffix)) => write!(f, "{}{}", n, suffix),
+            Expr::Address(a) => write!(f, "@0x{:x}", a),
+            Expr::Var(name) => f.write_str(name),
+            Expr::Binary(op, lhs, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+            Expr::Not(e) => write!(f, "!{}", e),
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                comma_separated(f, args)?;
+                f.write_str(")")
+            }
+            Expr::Pack(name, fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, e)| format!("{}: {}", name, e))
+                    .collect();
+                write!(f, "{} {{ ", name)?;
+                comma_separated(f, &fields)?;
+                f.write_str(" }")
+            }
+            Expr::Field(e, field) => write!(f, "({}).{}", e, field),
+            Expr::Vector(elems) => {
+                f.write_str("vector[")?;
+                comma_separated(f, elems)?;
+                f.write_str("]")
+            }
+            Expr::Borrow(false, e) => write!(f, "(&{})", e),
+            Expr::Borrow(true, e) => write!(f, "(&mut {})", e),
+            Expr::Deref(e) => write!(f, "(*{})", e),
+            Expr::Cast(e, ty) => write!(f, "({} as {})", e, ty),
+            Expr::IfElse(cond, then, els) => {
+                write!(f, "(if ({}) {{ {} }} else {{ {} }})", cond, then, els)
+            }
+            Expr::Block(stmts, tail) => block(f, stmts, Some(tail)),
+        }
+    }
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stmt::Let(name, None, e) => write!(f, "let {} = {};", name, e),
+            Stmt::Let(name, Some(ty), e) => write!(f, "let {}: {} = {};", name, ty, e),
+            Stmt::Assign(name, e) => write!(f, "{} = {};", name, e),
+            Stmt::Mutate(lhs, rhs) => write!(f, "*{} = {};", lhs, rhs),
+            Stmt::If(cond, then, els) => {
+                write!(f, "if ({}) ", cond)?;
+                block(f, then, None)?;
+                f.write_str(" else ")?;
+                block(f, els, None)?;
+                f.write_str(";")
+            }
+            Stmt::While(cond, body) => {
+                write!(f, "while ({}) ", cond)?;
+                block(f, body, None)?;
+                f.write_str(";")
+            }
+            Stmt::Loop(body) => {
+                f.write_str("loop ")?;
+                block(f, body, None)?;
+                f.write_str(";")
+            }
+            Stmt::Break => f.write_str("break;"),
+            Stmt::Continue => f.write_str("continue;"),
+            Stmt::Abort(e) => write!(f, "abort {};", e),
+            Stmt::Return(None) => f.write_str("return;"),
+            Stmt::Return(Some(e)) => write!(f, "return {};", e),
+            Stmt::Expr(e) => write!(f, "{};", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prints_move_syntax() {
+        let module = Module {
+            name: "m".to_owned(),
+            structs: vec![StructDef {
+                name: "S0".to_owned(),
+                abilities: vec![Ability::Copy, Ability::Drop],
+                fields: vec![("f0".to_owned(), Type::Vector(Box::new(Type::U8)))],
+            }],
+            functions: vec![Function {
+                name: "f0".to_owned(),
+                visibility: Visibility::PublicEntry,
+                params: vec![("v0".to_owned(), Type::Ref(true, Box::new(Type::U64)))],
+                ret: Some(Type::U64),
+                body: vec![Stmt::Mutate(
+                    Expr::Var("v0".to_owned()),
+                    Expr::Binary(
+                        BinOp::Add,
+                        Box::new(Expr::Deref(Box::new(Expr::Var("v0".to_owned())))),
+                        Box::new(Expr::Number(1, Some(Type::U64))),
+                    ),
+                )],
+                tail: Some(Expr::Cast(Box::new(Expr::Address(0x1)), Type::U64)),
+            }],
+        };
+        assert_eq!(
+            module.to_string(),
+            "\
+module 0x42::m {
+    struct S0 has copy, drop {
+        f0: vector<u8>,
+    }
+
+    public entry fun f0(v0: &mut u64): u64 {
+        *v0 = ((*v0) + 1u64);
+        (@0x1 as u64)
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn mutations_keep_modules_printable() {
+        let data: Vec<u8> = (0..8192u32).map(|i| (i * 31 + 7) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        let gen = SourceGen::default();
+        while !u.is_empty() {
+            let module = gen.mutated_module(&mut u).unwrap();
+            let source = module.to_string();
+            assert!(source.starts_with("module 0x42::m {"));
+            assert_eq!(source.matches('{').count(), source.matches('}').count());
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! The audit of the Move dependencies of the fuzz package, run before every
+//! build and reported in full by `check --audit`.
+//!
+//! It follows the `[dependencies]` of `Move.toml` without building anything,
+//! and warns about what hurts a campaign: dependencies that cannot be found,
+//! native functions the harnesses do not register, natives reading the time or
+//! randomness, and dependency closures too large to publish quickly.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Modules in the dependency closure past which it is considered enormous.
+const LARGE_CLOSURE_MODULES: usize = 150;
+
+/// The modules whose natives each native package registers, and their address.
+const NATIVE_MODULES: &[(&str, &str, &[&str])] = &[
+    (
+        "stdlib",
+        "0x1",
+        &[
+            "bcs",
+            "hash",
+            "signer",
+            "string",
+            "type_name",
+            "vector",
+            "unit_test",
+        ],
+    ),
+    ("nursery", "0x1", &["debug", "event"]),
+    ("table", "0x2", &["table"]),
+];
+
+/// Name segments of natives whose results change from one run to the next.
+const NONDETERMINISTIC: &[&str] = &[
+    "time",
+    "timestamp",
+    "now",
+    "clock",
+    "rand",
+    "random",
+    "randomness",
+    "entropy",
+];
+
+/// A package of the dependency closure.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct AuditedPackage {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) modules: usize,
+    pub(crate) natives: usize,
+}
+
+/// The packages of the dependency closure and the problems found in it.
+#[derive(Debug, Default)]
+pub(crate) struct Audit {
+    pub(crate) packages: Vec<AuditedPackage>,
+    /// Git dependencies not fetched yet, which the next build fetches
+    pub(crate) unfetched: Vec<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+/// A `native fun` declared in a package.
+struct Native {
+    package: String,
+    address: String,
+    module: String,
+    function: String,
+}
+
+/// Audits the dependency closure of the Move package at `root`, whose harnesses
+/// register the native packages `natives`. Git dependencies are looked up where
+/// `move build` fetches them, under `move_home`.
+pub(crate) fn audit(root: &Path, natives: &[String], move_home: &Path) -> Result<Audit> {
+    let mut audit = Audit::default();
+    let mut addresses = BTreeMap::new();
+    let mut declared = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::from([(None, root.to_owned(), false)]);
+    while let Some((dependent, dir, git)) = queue.pop_front() {
+        let dir = fs::canonicalize(&dir).unwrap_or(dir);
+        if !seen.insert(dir.clone()) {
+            continue;
+        }
+        let manifest_path = dir.join("Move.toml");
+        let manifest = match fs::read_to_string(&manifest_path) {
+            Ok(manifest) => manifest,
+            Err(_) => {
+                match dependent {
+                    Some((_, name)) if git => audit.unfetched.push(name),
+                    Some((dependent, name)) => audit.warnings.push(format!(
+                        "dependency `{}` of `{}` is not at {}",
+                        name,
+                        dependent,
+                        dir.display()
+                    )),
+                    None => audit
+                        .warnings
+                        .push(format!("{} does not exist", manifest_path.display())),
+                }
+                continue;
+            }
+        };
+        let manifest: toml::Value = toml::from_str(&manifest)
+            .with_context(|| format!("could not parse {}", manifest_path.display()))?;
+        let name = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("<unnamed>")
+            .to_owned();
+        if let Some(table) = manifest.get("addresses").and_then(|a| a.as_table()) {
+            for (named, value) in table {
+                if let Some(value) = value.as_str().filter(|v| *v != "_") {
+                    addresses.insert(named.clone(), normalize_address(value));
+                }
+            }
+        }
+        if let Some(table) = manifest.get("dependencies").and_then(|d| d.as_table()) {
+            for (dep, info) in table {
+                if let Some(path) = dependency_path(&dir, info, move_home) {
+                    let git = info.get("git").is_some();
+                    queue.push_back((Some((name.clone(), dep.clone())), path, git));
+                }
+            }
+        }
+
+        let mut package = AuditedPackage {
+            name: name.clone(),
+            path: dir.clone(),
+            modules: 0,
+            natives: 0,
+        };
+        for file in move_files(&dir.join("sources"))? {
+            let source = fs::read_to_string(&file)
+                .with_context(|| format!("could not read {}", file.display()))?;
+            let (modules, natives) = scan_source(&source);
+            package.modules += modules;
+            package.natives += natives.len();
+            declared.extend(
+                natives
+                    .into_iter()
+                    .map(|(address, module, function)| Native {
+                        package: name.clone(),
+                        address,
+                        module,
+                        function,
+                    }),
+            );
+        }
+        audit.packages.push(package);
+    }
+
+    let registered: Vec<(&str, &[&str])> = NATIVE_MODULES
+        .iter()
+        .filter(|(package, ..)| match natives {
+            [] => ["stdlib", "nursery"].contains(package),
+            natives => natives.iter().any(|n| n == package),
+        })
+        .map(|(_, address, modules)| (*address, *modules))
+        .collect();
+    let mut unregistered: BTreeMap<(String, String, String), Vec<&str>> = BTreeMap::new();
+    for native in &declared {
+        let address = if native.address.starts_with("0x") {
+            native.address.clone()
+        } else {
+            addresses
+                .get(&native.address)
+                .cloned()
+                .unwrap_or_else(|| native.address.clone())
+        };
+        let id = format!("{}::{}::{}", address, native.module, native.function);
+        if [&native.module, &native.function]
+            .iter()
+            .flat_map(|name| name.split('_'))
+            .any(|segment| NONDETERMINISTIC.contains(&segment))
+        {
+            audit.warnings.push(format!(
+                "native function {} of `{}` looks nondeterministic, inputs calling it may not \
+                 reproduce",
+                id, native.package
+            ));
+        }
+        if !registered
+            .iter()
+            .any(|(a, modules)| *a == address && modules.contains(&native.module.as_str()))
+        {
+            unregistered
+                .entry((native.package.clone(), address, native.module.clone()))
+                .or_default()
+                .push(&native.function);
+        }
+    }
+    for ((package, address, module), functions) in unregistered {
+        audit.warnings.push(format!(
+            "native functions of {}::{} in `{}` are not registered ({}), calls to them abort \
+             unless the harnesses register them with `libfuzzer::fuzz_natives!`",
+            address,
+            module,
+            package,
+            functions.join(", ")
+        ));
+    }
+
+    let modules: usize = audit.packages.iter().map(|p| p.modules).sum();
+    if modules > LARGE_CLOSURE_MODULES {
+        audit.warnings.push(format!(
+            "the dependency closure has {} modules in {} packages, which slows down builds and \
+             the setup of every harness",
+            modules,
+            audit.packages.len()
+        ));
+    }
+    Ok(audit)
+}
+
+/// The manifest and the Move sources of every package of the dependency
+/// closure of the package at `root` found locally, each with the directory of
+/// its package.
+pub(crate) fn package_sources(root: &Path, move_home: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut sources = Vec::new();
+    for package in audit(root, &[], move_home)?.packages {
+        sources.push((package.path.clone(), package.path.join("Move.toml")));
+        for file in move_files(&package.path.join("sources"))? {
+            sources.push((package.path.clone(), file));
+        }
+    }
+    Ok(sources)
+}
+
+/// Where the dependency `info` of the package at `dir` is found locally.
+fn dependency_path(dir: &Path, info: &toml::Value, move_home: &Path) -> Option<PathBuf> {
+    if let Some(local) = info.get("local").and_then(|l| l.as_str()) {
+        return Some(dir.join(local));
+    }
+    let git = info.get("git").and_then(|g| g.as_str())?;
+    let rev = info.get("rev").and_then(|r| r.as_str()).unwrap_or_default();
+    // Fetched like `move build` does, at `<sanitized url>_<rev>`.
+    let sanitized: String = git
+        .chars()
+        .map(|c| if "/:.@".contains(c) { '_' } else { c })
+        .collect();
+    let mut path = move_home.join(format!("{}_{}", sanitized, rev.replace('/', "__")));
+    if let Some(subdir) = info.get("subdir").and_then(|s| s.as_str()) {
+        path.push(subdir);
+    }
+    Some(path)
+}
+
+/// The `.move` files under `dir`, none if it does not exist.
+fn move_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(files),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(move_files(&path)?);
+        } else if path.extension() == Some(OsStr::new("move")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The number of modules declared in `source`, and its natives as `(address,
+/// module, function)`, where the address is as written.
+fn scan_source(source: &str) -> (usize, Vec<(String, String, String)>) {
+    let code: String = source
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let tokens: Vec<_> = code
+        .split(|c: char| c.is_whitespace() || "{}()<>;,".contains(c))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut modules = 0;
+    let mut natives = Vec::new();
+    let mut block_address = String::new();
+    let mut current = (String::new(), String::new());
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1).copied().unwrap_or_default();
+        match *token {
+            "address" => block_address = next.to_owned(),
+            "module" => {
+                modules += 1;
+                current = match next.split_once("::") {
+                    Some((address, module)) => (address.to_owned(), module.to_owned()),
+                    None => (block_address.clone(), next.to_owned()),
+                };
+            }
+            "native" => {
+                if let Some(fun) = tokens[i + 1..].iter().take(3).position(|t| *t == "fun") {
+                    if let Some(function) = tokens.get(i + fun + 2) {
+                        natives.push((current.0.clone(), current.1.clone(), function.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let natives = natives
+        .into_iter()
+        .map(|(address, module, function)| {
+            let address = if address.starts_with("0x") {
+                normalize_address(&address)
+            } else {
+                address
+            };
+            (address, module, function)
+        })
+        .collect();
+    (modules, natives)
+}
+
+/// `0x0001` as `0x1`, named addresses as they are.
+fn normalize_address(address: &str) -> String {
+    match address.strip_prefix("0x") {
+        Some(hex) => {
+            let hex = hex.trim_start_matches('0').to_lowercase();
+            format!("0x{}", if hex.is_empty() { "0" } else { &hex })
+        }
+        None => address.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// The fuzz package in `dir/fuzz`, depending on `dir/bank`, a missing
+    /// package and a package of git.
+    fn packages(dir: &Path) -> PathBuf {
+        let root = dir.join("fuzz");
+        write(
+            &root.join("Move.toml"),
+            r#"
+[package]
+name = "fuzz"
+
+[dependencies]
+Bank = { local = "../bank" }
+Missing = { local = "../missing" }
+MoveStdlib = { git = "https://github.com/move-language/move.git", subdir = "language/move-stdlib", rev = "main" }
+"#,
+        );
+        write(
+            &dir.join("bank/Move.toml"),
+            r#"
+[package]
+name = "Bank"
+
+[addresses]
+bank = "0x0042"
+std = "0x1"
+
+[dependencies]
+Fuzz = { local = "../fuzz" }
+"#,
+        );
+        write(
+            &dir.join("bank/sources/bank.move"),
+            r#"
+module bank::bank {
+    // native fun in_a_comment();
+    native public fun now_microseconds(): u64;
+    public fun deposit() {}
+}
+module std::vector {
+    native public fun length<T>(v: &vector<T>): u64;
+}
+address 0x42 {
+module oracle {
+    native fun price(): u64;
+}
+}
+"#,
+        );
+        root
+    }
+
+    #[test]
+    fn list_the_packages_and_unfetched_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let found = audit(&packages(dir.path()), &[], dir.path()).unwrap();
+        let packages: Vec<_> = found
+            .packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.modules, p.natives))
+            .collect();
+        assert_eq!(packages, [("fuzz", 0, 0), ("Bank", 3, 3)]);
+        assert_eq!(found.unfetched, ["MoveStdlib"]);
+    }
+
+    #[test]
+    fn warn_about_missing_packages_and_unregistered_natives() {
+        let dir = tempfile::tempdir().unwrap();
+        let found = audit(&packages(dir.path()), &[], dir.path()).unwrap();
+        assert_eq!(found.warnings.len(), 4, "{:#?}", found.warnings);
+        assert!(found.warnings[0].starts_with("dependency `Missing` of `fuzz` is not at"));
+        assert!(found.warnings[1].starts_with("native function 0x42::bank::now_microseconds"));
+        assert!(found.warnings[2].starts_with(
+            "native functions of 0x42::bank in `Bank` are not registered (now_microseconds)"
+        ));
+        assert!(found.warnings[3].starts_with("native functions of 0x42::oracle"));
+    }
+
+    #[test]
+    fn stdlib_natives_need_the_stdlib_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = packages(dir.path());
+        let found = audit(&root, &["table".to_owned()], dir.path()).unwrap();
+        assert_eq!(found.warnings.len(), 5, "{:#?}", found.warnings);
+        assert!(found.warnings[2].starts_with("native functions of 0x1::vector"));
+    }
+
+    #[test]
+    fn list_the_sources_of_the_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = package_sources(&packages(dir.path()), dir.path()).unwrap();
+        let bank = fs::canonicalize(dir.path().join("bank")).unwrap();
+        assert_eq!(sources.len(), 3);
+        assert_eq!(
+            sources[2],
+            (bank.clone(), bank.join("sources").join("bank.move"))
+        );
+    }
+}
@@ -0,0 +1,602 @@
+use crate::modules;
+use crate::transactions::ArgType;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{CompiledModule, SignatureToken, Visibility};
+use std::collections::BTreeSet;
+
+/// Name of the attribute opting a Move function into fuzzing, as in `#[fuzz]`.
+pub const FUZZ_ATTRIBUTE: &str = "fuzz";
+
+/// A `public entry fun` found in a compiled module, or a `public fun`
+/// annotated with `#[fuzz]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryFunction {
+    /// Address of the module, without the `0x` prefix
+    pub address: String,
+    pub module: String,
+    pub function: String,
+    /// Type parameters, instantiated by the fuzzer
+    pub type_params: Vec<TypeParam>,
+    /// Types of the leading signer parameters, `&signer` or `signer`
+    pub signers: Vec<String>,
+    pub params: Vec<ArgType>,
+}
+
+/// A type parameter, as in `phantom T: copy + drop`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeParam {
+    pub name: String,
+    /// The abilities its type arguments need, e.g. `copy`
+    pub constraints: Vec<String>,
+    pub phantom: bool,
+}
+
+/// A struct declared in a module, that type arguments can be drawn from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StructDecl {
+    /// Address of the module, without the `0x` prefix
+    pub address: String,
+    pub module: String,
+    pub name: String,
+    /// The abilities it is declared with, e.g. `key`
+    pub abilities: Vec<String>,
+    pub type_params: Vec<TypeParam>,
+}
+
+impl EntryFunction {
+    /// Name of the fuzz target generated for this function alone.
+    pub fn target_name(&self) -> String {
+        format!("{}_{}", self.module, self.function)
+    }
+}
+
+/// An entry function that no harness can be generated for, and why.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Skipped {
+    pub function: String,
+    pub reason: String,
+}
+
+/// The functions of a package that harnesses are generated for.
+pub enum Selection<'a> {
+    /// Every public entry function
+    Entry,
+    /// The public functions annotated with `#[fuzz]`, as `(module, function)`
+    Annotated(&'a BTreeSet<(String, String)>),
+}
+
+/// The functions of the compiled `module` picked by `selection`, and those
+/// picked that no harness can be generated for.
+pub fn entry_functions(
+    module: &CompiledModule,
+    selection: &Selection,
+) -> (Vec<EntryFunction>, Vec<Skipped>) {
+    let address = module.address().short_str_lossless();
+    let module_name = module.name().to_string();
+
+    let mut functions = Vec::new();
+    let mut skipped = Vec::new();
+    for def in module.function_defs() {
+        if def.visibility != Visibility::Public {
+            continue;
+        }
+        let handle = module.function_handle_at(def.function);
+        let function = module.identifier_at(handle.name).to_string();
+        let selected = match selection {
+            Selection::Entry => def.is_entry,
+            Selection::Annotated(annotated) => {
+                annotated.contains(&(module_name.clone(), function.clone()))
+            }
+        };
+        if !selected {
+            continue;
+        }
+
+        let mut signers = Vec::new();
+        let mut params = Vec::new();
+        let mut unsupported = None;
+        for ty in &module.signature_at(handle.parameters).0 {
+            if let Some(signer) = signer_type(ty) {
+                if !params.is_empty() {
+                    unsupported =
+                        Some("signers after other parameters are not supported".to_owned());
+                }
+                signers.push(signer.to_owned());
+                continue;
+            }
+            match ArgType::from_signature(ty) {
+                Some(arg) if rust_generator(&arg).is_some() => params.push(arg),
+                _ => {
+                    unsupported = Some(format!(
+                        "parameters of type `{}` are not supported",
+                        modules::type_name(module, ty)
+                    ))
+                }
+            }
+        }
+        match unsupported {
+            Some(reason) => skipped.push(Skipped {
+                function: format!("{}::{}", module_name, function),
+                reason,
+            }),
+            None => functions.push(EntryFunction {
+                address: address.clone(),
+                module: module_name.clone(),
+                function,
+                type_params: handle
+                    .type_parameters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, constraints)| TypeParam {
+                        name: format!("Ty{}", i),
+                        constraints: modules::ability_names(*constraints),
+                        phantom: false,
+                    })
+                    .collect(),
+                signers,
+                params,
+            }),
+        }
+    }
+    (functions, skipped)
+}
+
+/// `signer` or `&signer` if `ty` is one of them.
+fn signer_type(ty: &SignatureToken) -> Option<&'static str> {
+    match ty {
+        SignatureToken::Signer => Some("signer"),
+        SignatureToken::Reference(inner) if **inner == SignatureToken::Signer => Some("&signer"),
+        _ => None,
+    }
+}
+
+/// The structs declared in the compiled `module`.
+pub fn structs(module: &CompiledModule) -> Vec<StructDecl> {
+    let address = module.address().short_str_lossless();
+    module
+        .struct_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.struct_handle_at(def.struct_handle);
+            StructDecl {
+                address: address.clone(),
+                module: module.name().to_string(),
+                name: module.identifier_at(handle.name).to_string(),
+                abilities: modules::ability_names(handle.abilities),
+                type_params: handle
+                    .type_parameters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, param)| TypeParam {
+                        name: format!("Ty{}", i),
+                        constraints: modules::ability_names(param.constraints),
+                        phantom: param.is_phantom,
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// The `(module, function)` pairs of the functions annotated with `#[fuzz]` in
+/// Move source, the way `#[test]` marks unit tests.
+///
+/// The source is only tokenized, not parsed: the attribute applies to the next
+/// `fun` unless another item such as a `struct` comes first.
+pub fn fuzz_annotated(source: &str) -> Vec<(String, String)> {
+    let tokens = tokenize(source);
+    let mut found = Vec::new();
+    let mut module = None;
+    let mut pending = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            "#" if tokens.get(i + 1) == Some(&"[") => {
+                // Attribute names start the list or follow a comma, outside of
+                // the parentheses of their arguments.
+                let mut depth = 0;
+                i += 1;
+                while i < tokens.len() {
+                    match tokens[i] {
+                        "[" | "(" => depth += 1,
+                        "]" | ")" => depth -= 1,
+                        name if depth == 1
+                            && name == FUZZ_ATTRIBUTE
+                            && matches!(tokens[i - 1], "[" | ",") =>
+                        {
+                            pending = true
+                        }
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    i += 1;
+                }
+            }
+            "module" => {
+                module = tokens.get(i + 1).map(|name| {
+                    let name = name.rsplit("::").next().unwrap_or(name);
+                    name.to_owned()
+                });
+                pending = false;
+            }
+            "fun" => {
+                if let (true, Some(module), Some(function)) = (pending, &module, tokens.get(i + 1))
+                {
+                    found.push((module.clone(), function.to_string()));
+                }
+                pending = false;
+            }
+            "struct" | "const" | "use" | "spec" | "script" => pending = false,
+            _ => {}
+        }
+        i += 1;
+    }
+    found
+}
+
+/// Splits Move source into identifiers, paths such as `0x1::bank` and single
+/// punctuation characters, dropping comments and string literals.
+fn tokenize(source: &str) -> Vec<&str> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == ':';
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let len = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            rest.find("*/").map_or(rest.len(), |end| end + 2)
+        } else if c == '"' {
+            rest[1..].find('"').map_or(rest.len(), |end| end + 2)
+        } else if is_ident(c) {
+            let len = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+            tokens.push(rest[..len].trim_end_matches(':'));
+            len
+        } else {
+            if !c.is_whitespace() {
+                tokens.push(&rest[..c.len_utf8()]);
+            }
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Rust expression drawing a value of `ty` from `u`, for the types the Move
+/// runner can pass as transaction arguments.
+fn rust_generator(ty: &ArgType) -> Option<&'static str> {
+    Some(match ty {
+        ArgType::Bool => "u.arbitrary::<bool>()?",
+        ArgType::U8 => "u.arbitrary::<u8>()?",
+        ArgType::U16 => "u.arbitrary::<u16>()?",
+        ArgType::U32 => "u.arbitrary::<u32>()?",
+        ArgType::U64 => "u.arbitrary::<u64>()?",
+        ArgType::U128 => "u.arbitrary::<u128>()?",
+        ArgType::Address => "address(u)?",
+        ArgType::Vector(elem) if **elem == ArgType::U8 => "u.arbitrary::<Vec<u8>>()?",
+        ArgType::Vector(_) => return None,
+    })
+}
+
+fn move_type(ty: &ArgType) -> String {
+    match ty {
+        ArgType::Bool => "bool".to_owned(),
+        ArgType::U8 => "u8".to_owned(),
+        ArgType::U16 => "u16".to_owned(),
+        ArgType::U32 => "u32".to_owned(),
+        ArgType::U64 => "u64".to_owned(),
+        ArgType::U128 => "u128".to_owned(),
+        ArgType::Address => "address".to_owned(),
+        ArgType::Vector(elem) => format!("vector<{}>", move_type(elem)),
+    }
+}
+
+/// How deep the type arguments of generated harnesses nest, e.g.
+/// `vector<vector<u8>>` is 2 deep.
+const MAX_TYPE_DEPTH: usize = 8;
+
+/// Rust expression of the `Abilities` of `libfuzzer`, as in
+/// `Abilities::COPY.with(Abilities::DROP)`.
+fn rust_abilities(abilities: &[String]) -> String {
+    let mut consts = abilities
+        .iter()
+        .map(|a| format!("Abilities::{}", a.to_uppercase()));
+    match consts.next() {
+        Some(first) => consts.fold(first, |expr, next| format!("{}.with({})", expr, next)),
+        None => "Abilities::NONE".to_owned(),
+    }
+}
+
+/// A harness calling `functions` through the wrapper module `wrapper`, where
+/// the wrapper of each function is named by `wrapper_fun`. The type arguments
+/// of generic functions are drawn from `structs`, besides primitive types and
+/// vectors.
+pub struct Harness<'a> {
+    pub wrapper: &'a str,
+    pub functions: &'a [EntryFunction],
+    pub structs: &'a [StructDecl],
+    pub wrapper_fun: fn(&EntryFunction) -> String,
+}
+
+impl Harness<'_> {
+    /// The argument generators and `FUNCTIONS` entries of the Rust harness.
+    pub fn rust_functions(&self) -> (String, String) {
+        let mut generators = String::new();
+        let mut entries = String::new();
+        if self.functions.iter().any(|f| !f.type_params.is_empty()) {
+            generators.push_str(&self.rust_structs());
+        }
+        for f in self.functions {
+            let name = (self.wrapper_fun)(f);
+            let args: Vec<_> = f
+                .params
+                .iter()
+                .filter_map(rust_generator)
+                .map(|g| format!("Box::new({})", g))
+                .collect();
+            generators.push_str(&format!(
+                "fn {name}_args(u: &mut Unstructured) -> Result<MoveArg> {{\n    \
+                 Ok(vec![{args}])\n}}\n\n",
+                name = name,
+                args = args.join(", "),
+            ));
+            let type_args = if f.type_params.is_empty() {
+                String::new()
+            } else {
+                let type_args: Vec<_> = f
+                    .type_params
+                    .iter()
+                    .map(|param| {
+                        format!(
+                            "type_arg(u, STRUCTS, {}, MAX_TYPE_DEPTH)?",
+                            rust_abilities(&param.constraints)
+                        )
+                    })
+                    .collect();
+                generators.push_str(&format!(
+                    "fn {name}_type_args(u: &mut Unstructured) -> Result<Vec<TypeTag>> {{\n    \
+                     Ok(vec![{type_args}])\n}}\n\n",
+                    name = name,
+                    type_args = type_args.join(", "),
+                ));
+                format!(".with_type_args({}_type_args)", name)
+            };
+            entries.push_str(&format!(
+                "    EntryFunction::new(\"{module}\", \"{name}\", {signers}, {name}_args){type_args},\n",
+                module = self.wrapper,
+                name = name,
+                signers = f.signers.len(),
+                type_args = type_args,
+            ));
+        }
+        (generators, entries)
+    }
+
+    /// The `STRUCTS` the type arguments of the Rust harness are drawn from.
+    fn rust_structs(&self) -> String {
+        let generic = self.structs.iter().any(|s| !s.type_params.is_empty());
+        let mut out = format!(
+            "use libfuzzer::run_move::type_args::{{type_arg, Abilities, StructType, {}TypeTag}};\n\n\
+             // The structs of the package type arguments are drawn from\n\
+             const STRUCTS: &[StructType] = &[\n",
+            if generic { "TypeParam, " } else { "" },
+        );
+        for s in self.structs {
+            let type_params: Vec<_> = s
+                .type_params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "TypeParam {{ constraints: {}, phantom: {} }}",
+                        rust_abilities(&param.constraints),
+                        param.phantom
+                    )
+                })
+                .collect();
+            out.push_str(&format!(
+                "    StructType {{\n        \
+                 address: \"0x{address}\",\n        \
+                 module: \"{module}\",\n        \
+                 name: \"{name}\",\n        \
+                 abilities: {abilities},\n        \
+                 type_params: &[{type_params}],\n    \
+                 }},\n",
+                address = s.address,
+                module = s.module,
+                name = s.name,
+                abilities = rust_abilities(&s.abilities),
+                type_params = type_params.join(", "),
+            ));
+        }
+        out.push_str(&format!(
+            "];\n\n// How deep type arguments nest, e.g. `vector<vector<u8>>` is 2 deep\n\
+             const MAX_TYPE_DEPTH: usize = {};\n\n",
+            MAX_TYPE_DEPTH
+        ));
+        out
+    }
+
+    /// The functions of the Move wrapper module, each forwarding to its entry function.
+    pub fn move_functions(&self) -> String {
+        let mut functions = Vec::new();
+        for f in self.functions {
+            let signers = f
+                .signers
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| (format!("s{}", i), ty.clone()));
+            let params = f
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| (format!("a{}", i), move_type(ty)));
+            let params: Vec<_> = signers.chain(params).collect();
+            let (type_decl, type_args) = if f.type_params.is_empty() {
+                (String::new(), String::new())
+            } else {
+                let decl: Vec<_> = f
+                    .type_params
+                    .iter()
+                    .map(|param| match &param.constraints[..] {
+                        [] => param.name.clone(),
+                        constraints => format!("{}: {}", param.name, constraints.join(" + ")),
+                    })
+                    .collect();
+                let args: Vec<_> = f.type_params.iter().map(|p| p.name.as_str()).collect();
+                (
+                    format!("<{}>", decl.join(", ")),
+                    format!("<{}>", args.join(", ")),
+                )
+            };
+            functions.push(format!(
+                "    public entry fun {name}{type_decl}({decl}) {{\n        \
+                 0x{address}::{module}::{function}{type_args}({args});\n    \
+                 }}\n",
+                name = (self.wrapper_fun)(f),
+                type_decl = type_decl,
+                type_args = type_args,
+                decl = params
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {}", name, ty))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                address = f.address,
+                module = f.module,
+                function = f.function,
+                args = params
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+        functions.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bank() -> CompiledModule {
+        modules::test::compile(
+            "module 0x42.bank {
+                struct Account has key { balance: u64 }
+                struct Pool<phantom T: store> has store, key { reserve: u64 }
+
+                public entry deposit(s: &signer, amount: u64) { label b0: return; }
+                public entry transfer(s: &signer, to: address, data: vector<u8>) {
+                    label b0: return;
+                }
+                public entry swap<T: copy + drop>(s: &signer) { label b0: return; }
+                public entry batch(s: &signer, amounts: vector<u64>) { label b0: return; }
+                public balance(a: address): u64 { label b0: return 0; }
+                entry init(s: &signer) { label b0: return; }
+            }",
+        )
+    }
+
+    #[test]
+    fn parse_entry_functions() {
+        let (functions, skipped) = entry_functions(&bank(), &Selection::Entry);
+        let names: Vec<_> = functions.iter().map(|f| f.target_name()).collect();
+        assert_eq!(names, ["bank_deposit", "bank_transfer", "bank_swap"]);
+        assert_eq!(functions[1].address, "42");
+        assert_eq!(functions[1].signers, ["&signer"]);
+        assert_eq!(
+            functions[1].params,
+            [ArgType::Address, ArgType::Vector(Box::new(ArgType::U8))]
+        );
+        let skipped: Vec<_> = skipped.iter().map(|s| s.function.as_str()).collect();
+        assert_eq!(skipped, ["bank::batch"]);
+        assert_eq!(
+            functions[2].type_params,
+            [TypeParam {
+                name: "Ty0".to_owned(),
+                constraints: vec!["copy".to_owned(), "drop".to_owned()],
+                phantom: false,
+            }]
+        );
+        let structs = structs(&bank());
+        let names: Vec<_> = structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Account", "Pool"]);
+        assert_eq!(structs[1].abilities, ["store", "key"]);
+        assert!(structs[1].type_params[0].phantom);
+    }
+
+    #[test]
+    fn select_annotated_functions() {
+        let source = r##"
+module 0x42::bank {
+    // #[fuzz] in a comment does not count
+    #[fuzz]
+    public fun balance(a: address): u64 { 0 }
+
+    #[test_only, fuzz(runs = 10)]
+    struct Account has key { balance: u64 }
+
+    public entry fun deposit(s: &signer, amount: u64) { let _ = b"#[fuzz]"; }
+
+    #[fuzz]
+    /* no harness */ entry fun init(s: &signer) {}
+}
+"##;
+        let annotated = fuzz_annotated(source);
+        assert_eq!(
+            annotated,
+            [
+                ("bank".to_owned(), "balance".to_owned()),
+                ("bank".to_owned(), "init".to_owned())
+            ]
+        );
+        let annotated = annotated.into_iter().collect();
+        let (functions, _) = entry_functions(&bank(), &Selection::Annotated(&annotated));
+        let names: Vec<_> = functions.iter().map(|f| f.target_name()).collect();
+        assert_eq!(names, ["bank_balance"]);
+    }
+
+    #[test]
+    fn generate_wrappers() {
+        let (functions, _) = entry_functions(&bank(), &Selection::Entry);
+        let structs = structs(&bank());
+        let harness = Harness {
+            wrapper: "bank_deposit",
+            functions: &functions[..1],
+            structs: &structs,
+            wrapper_fun: |f| f.function.clone(),
+        };
+        assert_eq!(
+            harness.move_functions(),
+            "    public entry fun deposit(s0: &signer, a0: u64) {\n        \
+             0x42::bank::deposit(s0, a0);\n    }\n"
+        );
+        let (generators, entries) = harness.rust_functions();
+        assert!(generators.contains("fn deposit_args(u: &mut Unstructured) -> Result<MoveArg> {\n    Ok(vec![Box::new(u.arbitrary::<u64>()?)])\n}"));
+        assert_eq!(
+            entries,
+            "    EntryFunction::new(\"bank_deposit\", \"deposit\", 1, deposit_args),\n"
+        );
+        assert!(!generators.contains("STRUCTS"));
+
+        let harness = Harness {
+            wrapper: "bank_swap",
+            functions: &functions[2..],
+            structs: &structs,
+            wrapper_fun: |f| f.function.clone(),
+        };
+        assert_eq!(
+            harness.move_functions(),
+            "    public entry fun swap<Ty0: copy + drop>(s0: &signer) {\n        \
+             0x42::bank::swap<Ty0>(s0);\n    }\n"
+        );
+        let (generators, entries) = harness.rust_functions();
+        assert!(generators.contains("abilities: Abilities::STORE.with(Abilities::KEY),\n        type_params: &[TypeParam { constraints: Abilities::STORE, phantom: true }],"));
+        assert!(generators.contains("Ok(vec![type_arg(u, STRUCTS, Abilities::COPY.with(Abilities::DROP), MAX_TYPE_DEPTH)?])"));
+        assert_eq!(
+            entries,
+            "    EntryFunction::new(\"bank_swap\", \"swap\", 1, swap_args).with_type_args(swap_type_args),\n"
+        );
+    }
+}
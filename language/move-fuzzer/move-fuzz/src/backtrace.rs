@@ -0,0 +1,242 @@
+//! The backtraces of crashes, stored raw in the metadata of their artifacts
+//! and pretty-printed by `fmt --backtrace`.
+//!
+//! Fuzz targets run with `RUST_BACKTRACE=1`, so that a panic prints the
+//! backtrace of the standard library before libFuzzer aborts:
+//!
+//! ```text
+//! stack backtrace:
+//!    0: rust_begin_unwind
+//!              at /rustc/.../library/std/src/panicking.rs:652:5
+//!   12: bank::deposit
+//!              at ./src/bank.rs:14:9
+//! ```
+//!
+//! Other crashes only have the backtrace of the sanitizer, a frame per line
+//! such as `#3 0x55d1 in bank::deposit /src/bank.rs:14:9`.
+
+use crate::symbolize;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lines of source shown around the line of a frame of the workspace.
+const SNIPPET_CONTEXT: u32 = 2;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// A frame of a backtrace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    pub number: usize,
+    /// The function, demangled
+    pub function: String,
+    /// The source file and line, when the backtrace tells
+    pub location: Option<(PathBuf, u32)>,
+}
+
+/// The raw backtrace in the output `lines` of a crash: the one of the panic if
+/// there is one, the first one of the sanitizer otherwise.
+pub fn capture<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let lines: Vec<&str> = lines.into_iter().collect();
+    if let Some(start) = lines
+        .iter()
+        .position(|line| line.trim() == "stack backtrace:")
+    {
+        let frames: Vec<&str> = lines[start + 1..]
+            .iter()
+            .take_while(|line| panic_frame(line).is_some() || location(line).is_some())
+            .copied()
+            .collect();
+        if !frames.is_empty() {
+            return Some(frames.join("\n"));
+        }
+    }
+    let start = lines
+        .iter()
+        .position(|line| symbolize::backtrace_frame(line).is_some())?;
+    let frames: Vec<&str> = lines[start..]
+        .iter()
+        .take_while(|line| symbolize::backtrace_frame(line).is_some())
+        .copied()
+        .collect();
+    Some(frames.join("\n"))
+}
+
+/// The frames of a backtrace `capture` returned.
+pub fn frames(backtrace: &str) -> Vec<Frame> {
+    let mut frames: Vec<Frame> = Vec::new();
+    for line in backtrace.lines() {
+        if let Some((number, function)) = panic_frame(line) {
+            frames.push(Frame {
+                number,
+                function: demangle(function),
+                location: None,
+            });
+        } else if let Some(location) = location(line) {
+            if let Some(frame) = frames.last_mut() {
+                frame.location.get_or_insert(location);
+            }
+        } else if let Some((number, function)) = symbolize::backtrace_frame(line) {
+            let rest = line.split_once(" in ").map_or("", |(_, rest)| rest);
+            frames.push(Frame {
+                number,
+                function: demangle(function),
+                location: rest.split_whitespace().nth(1).and_then(file_line),
+            });
+        }
+    }
+    frames
+}
+
+/// Renders `backtrace` a frame after the other, with the source around the
+/// frames in files under one of `workspace`, in color if `color`.
+pub fn render(backtrace: &str, workspace: &[&Path], color: bool) -> String {
+    let paint = |style: &str, text: &str| {
+        if color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    };
+    let mut text = String::new();
+    for frame in frames(backtrace) {
+        let source = frame.location.as_ref().and_then(|(path, line)| {
+            let path = workspace.iter().find_map(|root| in_workspace(root, path))?;
+            Some((fs::read_to_string(path).ok()?, *line))
+        });
+        let function = match &source {
+            Some(_) => paint(&format!("{}{}", BOLD, CYAN), &frame.function),
+            None => frame.function.clone(),
+        };
+        let _ = writeln!(text, "{:>4}: {}", frame.number, function);
+        if let Some((path, line)) = &frame.location {
+            let at = format!("at {}:{}", path.display(), line);
+            let _ = writeln!(text, "      {}", paint(DIM, &at));
+        }
+        let Some((source, line)) = source else {
+            continue;
+        };
+        let first = line.saturating_sub(SNIPPET_CONTEXT).max(1);
+        for (number, code) in source
+            .lines()
+            .enumerate()
+            .map(|(i, code)| (i as u32 + 1, code))
+            .skip(first as usize - 1)
+            .take_while(|(number, _)| *number <= line + SNIPPET_CONTEXT)
+        {
+            if number == line {
+                let marked = format!("> {:>5} | {}", number, code);
+                let _ = writeln!(
+                    text,
+                    "      {}",
+                    paint(&format!("{}{}", BOLD, RED), &marked)
+                );
+            } else {
+                let _ = writeln!(
+                    text,
+                    "      {}",
+                    paint(DIM, &format!("  {:>5} | {}", number, code))
+                );
+            }
+        }
+    }
+    text
+}
+
+/// The number and function of a frame of the backtrace of a panic, as in
+/// `  12: bank::deposit`.
+fn panic_frame(line: &str) -> Option<(usize, &str)> {
+    let (number, function) = line.trim_start().split_once(": ")?;
+    Some((number.parse().ok()?, function.trim()))
+}
+
+/// The location of the frame above, as in `at ./src/bank.rs:14:9`.
+fn location(line: &str) -> Option<(PathBuf, u32)> {
+    file_line(line.trim_start().strip_prefix("at ")?)
+}
+
+/// The file and line of `path:line:column` or `path:line`.
+fn file_line(location: &str) -> Option<(PathBuf, u32)> {
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next()?;
+    let before = parts.next()?;
+    match (before.parse::<u32>(), parts.next()) {
+        (Ok(line), Some(path)) if last.parse::<u32>().is_ok() => Some((PathBuf::from(path), line)),
+        _ => Some((PathBuf::from(before), last.parse().ok()?)),
+    }
+}
+
+/// The file at `path` if it is under `root`, `path` being relative to it or
+/// absolute.
+fn in_workspace(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = root.join(path).canonicalize().ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+fn demangle(function: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(function))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capture_and_render_backtraces() {
+        let output = "\
+thread '<unnamed>' panicked at src/bank.rs:3:5:
+attempt to add with overflow
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:652:5
+   1: bank::deposit
+             at ./src/bank.rs:3:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+==1== ERROR: libFuzzer: deadly signal
+    #0 0x55d1 in __sanitizer_print_stack_trace
+    #1 0x55d2 in _ZN4bank7deposit17h0123456789abcdefE /src/bank.rs:3:5";
+        let backtrace = capture(output.lines()).unwrap();
+        assert_eq!(backtrace.lines().count(), 4);
+        let frames = frames(&backtrace);
+        assert_eq!(frames[1].function, "bank::deposit");
+        assert_eq!(
+            frames[1].location,
+            Some((PathBuf::from("./src/bank.rs"), 3))
+        );
+
+        let sanitizer = capture(output.lines().skip(8)).unwrap();
+        let frames = super::frames(&sanitizer);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].location, None);
+        assert_eq!(frames[1].function, "bank::deposit");
+        assert_eq!(frames[1].location, Some((PathBuf::from("/src/bank.rs"), 3)));
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("src/bank.rs"),
+            "fn deposit(a: u8) -> u8 {\n    let b = 255;\n    a + b\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            render(&backtrace, &[dir.path()], false),
+            "   0: rust_begin_unwind
+      at /rustc/abc/library/std/src/panicking.rs:652
+   1: bank::deposit
+      at ./src/bank.rs:3
+            1 | fn deposit(a: u8) -> u8 {
+            2 |     let b = 255;
+      >     3 |     a + b
+            4 | }
+"
+        );
+        assert!(render(&backtrace, &[dir.path()], true).contains("\x1b[1m\x1b[31m>     3 |"));
+        assert_eq!(capture(["no backtrace"]), None);
+    }
+}
@@ -0,0 +1,390 @@
+//! The commands going through several targets, `run --all`, `build
+//! --keep-going`, `check --keep-going`, `smoke`, `cmin --all-targets`,
+//! `coverage --all` and `triage`, and what they do when one of the targets
+//! fails.
+//!
+//! By default they go on with the next targets and fail at the end, with
+//! `--fail-fast` they skip the targets left after the first failure. Either
+//! way they end with a summary of how each target went, and exit with the
+//! code of the worst outcome whatever the order of the targets: failures to
+//! build first, then other failures, then crashes.
+
+use crate::error::{self, Error};
+use anyhow::Result;
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+static FAIL_FAST: AtomicBool = AtomicBool::new(false);
+
+/// Makes the commands going through several targets stop at the first failure.
+pub fn set_fail_fast(fail_fast: bool) {
+    FAIL_FAST.store(fail_fast, Ordering::Relaxed);
+}
+
+/// How a target of a batch went.
+#[derive(Debug)]
+pub enum Outcome {
+    Ok,
+    /// The target crashed, leaving these artifacts
+    Crashed(Vec<PathBuf>),
+    /// The target could not be built
+    BuildFailed(String),
+    Failed(anyhow::Error),
+    /// An earlier target failed with `--fail-fast`
+    Skipped,
+}
+
+impl Outcome {
+    fn of(result: Result<()>) -> Self {
+        let Err(e) = result else {
+            return Outcome::Ok;
+        };
+        match error::find(&e) {
+            Some(Error::CrashFound { artifacts, .. }) => Outcome::Crashed(artifacts.clone()),
+            Some(Error::BuildFailed { .. }) => Outcome::BuildFailed(format!("{:#}", e)),
+            _ => Outcome::Failed(e),
+        }
+    }
+
+    fn failed(&self) -> bool {
+        !matches!(self, Outcome::Ok | Outcome::Skipped)
+    }
+
+    /// What went wrong, on one line.
+    fn detail(&self) -> String {
+        match self {
+            Outcome::Crashed(artifacts) if !artifacts.is_empty() => {
+                format!("{} artifacts", artifacts.len())
+            }
+            Outcome::BuildFailed(e) => e.lines().next().unwrap_or_default().to_owned(),
+            Outcome::Failed(e) => format!("{:#}", e)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Outcome::Ok => "ok",
+            Outcome::Crashed(_) => "crashed",
+            Outcome::BuildFailed(_) => "build failed",
+            Outcome::Failed(_) => "failed",
+            Outcome::Skipped => "skipped",
+        })
+    }
+}
+
+/// A target of a batch and how it went.
+#[derive(Debug)]
+struct Entry {
+    target: String,
+    outcome: Outcome,
+    /// How long the target took, if known
+    time: Option<Duration>,
+    /// What to show of the outcome rather than its own detail
+    detail: Option<String>,
+}
+
+/// The outcomes of the targets of a command, in the order they were first run.
+#[derive(Debug)]
+pub struct Batch {
+    fail_fast: bool,
+    entries: Vec<Entry>,
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Batch {
+            fail_fast: FAIL_FAST.load(Ordering::Relaxed),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Batch {
+    /// Whether the targets left are skipped, an earlier one having failed
+    /// with `--fail-fast`.
+    pub fn stopped(&self) -> bool {
+        self.fail_fast && self.entries.iter().any(|entry| entry.outcome.failed())
+    }
+
+    /// Runs `f` for `target`, or skips it if the batch stopped.
+    pub fn run(&mut self, target: &str, f: impl FnOnce() -> Result<()>) {
+        if self.stopped() {
+            self.record(target, Outcome::Skipped, None, None);
+            return;
+        }
+        let start = Instant::now();
+        let outcome = Outcome::of(f());
+        if let Outcome::BuildFailed(_) | Outcome::Failed(_) = &outcome {
+            warn!("{} failed: {}", target, outcome.detail());
+        }
+        self.record(target, outcome, Some(start.elapsed()), None);
+    }
+
+    /// Records the `result` of another run of `target`, for the commands
+    /// running targets several times. The first failure of a target sticks.
+    pub fn record_result(&mut self, target: &str, result: Result<()>) {
+        self.record(target, Outcome::of(result), None, None);
+    }
+
+    /// Records the `outcome` of `target`, which took `time`, for the commands
+    /// telling more of how a target went than whether it failed, shown as
+    /// `detail` in the summary.
+    pub fn record_outcome(
+        &mut self,
+        target: &str,
+        outcome: Outcome,
+        time: Duration,
+        detail: Option<String>,
+    ) {
+        self.record(target, outcome, Some(time), detail);
+    }
+
+    /// Marks the `targets` that were not run as skipped.
+    pub fn skip_rest<'a>(&mut self, targets: impl IntoIterator<Item = &'a String>) {
+        for target in targets {
+            if self.outcome(target).is_none() {
+                self.record(target, Outcome::Skipped, None, None);
+            }
+        }
+    }
+
+    pub fn outcome(&self, target: &str) -> Option<&Outcome> {
+        self.entries
+            .iter()
+            .find(|entry| entry.target == target)
+            .map(|entry| &entry.outcome)
+    }
+
+    /// The targets that ran without failing.
+    pub fn succeeded(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.outcome, Outcome::Ok))
+            .map(|entry| entry.target.as_str())
+    }
+
+    fn record(
+        &mut self,
+        target: &str,
+        outcome: Outcome,
+        time: Option<Duration>,
+        detail: Option<String>,
+    ) {
+        match self.entries.iter_mut().find(|entry| entry.target == target) {
+            Some(entry) if entry.outcome.failed() => {}
+            Some(entry) => {
+                entry.outcome = outcome;
+                entry.time = time.map(|time| entry.time.unwrap_or_default() + time);
+                entry.detail = detail;
+            }
+            None => self.entries.push(Entry {
+                target: target.to_owned(),
+                outcome,
+                time,
+                detail,
+            }),
+        }
+    }
+
+    /// The table of the outcomes of the targets.
+    pub fn summary(&self) -> String {
+        let mut text = format!("{:<30} {:<14} {:>8}  DETAIL\n", "TARGET", "OUTCOME", "TIME");
+        for entry in &self.entries {
+            let time = entry.time.map_or_else(
+                || "-".to_owned(),
+                |time| format!("{:.1}s", time.as_secs_f64()),
+            );
+            let detail = match &entry.detail {
+                Some(detail) => detail.lines().next().unwrap_or_default().to_owned(),
+                None => entry.outcome.detail(),
+            };
+            let row = format!(
+                "{:<30} {:<14} {:>8}  {}",
+                entry.target, entry.outcome, time, detail
+            );
+            let _ = writeln!(text, "{}", row.trim_end());
+        }
+        text
+    }
+
+    /// The error of the worst outcome, if any target failed: failing to
+    /// build, then the other failure with the highest exit code, then crashing.
+    pub fn into_result(self) -> Result<()> {
+        let total = self.entries.len();
+        let targets = |f: fn(&Outcome) -> bool| -> Vec<String> {
+            self.entries
+                .iter()
+                .filter(|entry| f(&entry.outcome))
+                .map(|entry| entry.target.clone())
+                .collect()
+        };
+        let failed = targets(|o| matches!(o, Outcome::BuildFailed(_) | Outcome::Failed(_)));
+        let message = format!(
+            "{} of {} targets failed: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        );
+        if !targets(|o| matches!(o, Outcome::BuildFailed(_))).is_empty() {
+            return Err(Error::BuildFailed { command: message }.into());
+        }
+        let crashed = targets(|o| matches!(o, Outcome::Crashed(_)));
+        let mut artifacts = Vec::new();
+        let mut worst: Option<anyhow::Error> = None;
+        for entry in self.entries {
+            match entry.outcome {
+                Outcome::Crashed(found) => artifacts.extend(found),
+                Outcome::Failed(e)
+                    if worst
+                        .as_ref()
+                        .is_none_or(|worst| error::exit_code(&e) > error::exit_code(worst)) =>
+                {
+                    worst = Some(e);
+                }
+                _ => {}
+            }
+        }
+        if let Some(e) = worst {
+            return Err(e.context(message));
+        }
+        if !crashed.is_empty() {
+            return Err(Error::CrashFound {
+                targets: crashed,
+                artifacts,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::{anyhow, bail};
+
+    fn batch(fail_fast: bool) -> Batch {
+        Batch {
+            fail_fast,
+            entries: Vec::new(),
+        }
+    }
+
+    fn build_failure() -> Result<()> {
+        Err(Error::BuildFailed {
+            command: "cargo".to_owned(),
+        }
+        .into())
+    }
+
+    fn crash() -> Result<()> {
+        Err(Error::crash("withdraw", vec![PathBuf::from("crash-1")]).into())
+    }
+
+    #[test]
+    fn go_on_after_a_failure() {
+        let mut batch = batch(false);
+        batch.run("deposit", || Ok(()));
+        batch.run("swap", || bail!("could not read the corpus"));
+        batch.run("withdraw", crash);
+        assert!(!batch.stopped());
+        assert_eq!(batch.succeeded().collect::<Vec<_>>(), ["deposit"]);
+        assert!(matches!(
+            batch.outcome("withdraw"),
+            Some(Outcome::Crashed(_))
+        ));
+    }
+
+    #[test]
+    fn stop_at_the_first_failure_with_fail_fast() {
+        let mut batch = batch(true);
+        batch.run("withdraw", crash);
+        batch.run("swap", || bail!("could not read the corpus"));
+        batch.skip_rest(&["withdraw".to_owned(), "mint".to_owned()]);
+        assert!(batch.stopped());
+        assert!(matches!(batch.outcome("swap"), Some(Outcome::Skipped)));
+        assert!(matches!(batch.outcome("mint"), Some(Outcome::Skipped)));
+        match error::find(&batch.into_result().unwrap_err()) {
+            Some(Error::CrashFound { targets, artifacts }) => {
+                assert_eq!(targets, &["withdraw"]);
+                assert_eq!(artifacts, &[PathBuf::from("crash-1")]);
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn summary_table() {
+        let mut batch = batch(false);
+        batch.record_outcome("deposit", Outcome::Ok, Duration::from_millis(1250), None);
+        batch.record_result("withdraw", crash());
+        batch.record_result("swap", Err(anyhow!("could not read the corpus")));
+        batch.record_outcome(
+            "mint",
+            Outcome::Crashed(Vec::new()),
+            Duration::from_millis(500),
+            Some("setup failed: no natives".to_owned()),
+        );
+        assert_eq!(
+            batch.summary(),
+            "TARGET                         OUTCOME            TIME  DETAIL
+deposit                        ok                 1.2s
+withdraw                       crashed               -  1 artifacts
+swap                           failed                -  could not read the corpus
+mint                           crashed            0.5s  setup failed: no natives
+"
+        );
+    }
+
+    #[test]
+    fn build_failures_come_first() {
+        let mut batch = batch(false);
+        batch.run("withdraw", crash);
+        batch.run("swap", || bail!("could not read the corpus"));
+        batch.run("mint", build_failure);
+        let e = batch.into_result().unwrap_err();
+        assert_eq!(error::exit_code(&e), 5);
+        assert_eq!(
+            e.to_string(),
+            "failed to build fuzz script: 2 of 3 targets failed: swap, mint"
+        );
+    }
+
+    #[test]
+    fn other_failures_keep_their_exit_code() {
+        let mut batch = batch(false);
+        batch.run("withdraw", crash);
+        batch.run("swap", || bail!("could not read the corpus"));
+        batch.run("mint", || {
+            Err(Error::ToolchainMissing {
+                tool: "move".to_owned(),
+            }
+            .into())
+        });
+        let e = batch.into_result().unwrap_err();
+        assert_eq!(error::exit_code(&e), 4);
+        assert_eq!(e.to_string(), "2 of 3 targets failed: swap, mint");
+    }
+
+    #[test]
+    fn the_first_failure_of_a_target_sticks() {
+        let mut batch = batch(false);
+        batch.record_result("deposit", Ok(()));
+        batch.record_result("deposit", Err(Error::crash("deposit", Vec::new()).into()));
+        batch.record_result("deposit", Ok(()));
+        assert!(matches!(
+            batch.outcome("deposit"),
+            Some(Outcome::Crashed(_))
+        ));
+    }
+}
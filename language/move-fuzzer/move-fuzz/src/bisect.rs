@@ -0,0 +1,218 @@
+//! Driving `git bisect` for `move-fuzz bisect`, to find the commit that
+//! introduced a fuzz finding.
+//!
+//! The bisection runs in the working tree of the repository, which must not
+//! have changes to tracked files, and `git bisect reset` puts it back on the
+//! revision it started from however the bisection ends. Each revision git
+//! checks out is judged by a test: good, bad, or skipped when it cannot be
+//! tested, e.g. when the target does not build there.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// How a revision fares.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// The finding does not reproduce
+    Good,
+    /// The finding reproduces
+    Bad,
+    /// The revision cannot be tested
+    Skip,
+}
+
+/// How a bisection ended.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The first bad commit, as `<hash> <subject>`
+    FirstBad(String),
+    /// The commits left, only skipped ones, any of which may be the first bad one
+    Inconclusive(Vec<String>),
+}
+
+/// Bisects the commits between `good` and `bad` in the repository holding
+/// `dir`, judging each revision with `test`, which gets its commit hash.
+pub fn run(
+    dir: &Path,
+    good: &str,
+    bad: &str,
+    mut test: impl FnMut(&str) -> Result<Verdict>,
+) -> Result<Outcome> {
+    let good = git(
+        dir,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", good)],
+    )
+    .with_context(|| format!("{:?} is not a revision", good))?;
+    let bad = git(
+        dir,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", bad)],
+    )
+    .with_context(|| format!("{:?} is not a revision", bad))?;
+    if git(dir, &["merge-base", "--is-ancestor", &good, &bad]).is_err() {
+        bail!(
+            "the good revision {} is not an ancestor of the bad one {}",
+            &good[..12],
+            &bad[..12]
+        );
+    }
+    let bisecting = git(dir, &["rev-parse", "--git-path", "BISECT_START"])?;
+    if dir.join(bisecting).exists() {
+        bail!("a bisection is already in progress, end it with `git bisect reset`");
+    }
+    if !git(dir, &["status", "--porcelain", "--untracked-files=no"])?.is_empty() {
+        bail!("the working tree has changes, commit or stash them before bisecting");
+    }
+
+    let output = git(dir, &["bisect", "start", &bad, &good])?;
+    let outcome = match outcome(dir, &output) {
+        Ok(Some(outcome)) => Ok(outcome),
+        Ok(None) => bisect(dir, &mut test),
+        Err(e) => Err(e),
+    };
+    let reset = git(dir, &["bisect", "reset"]);
+    let outcome = outcome?;
+    reset.context("could not end the bisection")?;
+    Ok(outcome)
+}
+
+fn bisect(dir: &Path, test: &mut impl FnMut(&str) -> Result<Verdict>) -> Result<Outcome> {
+    loop {
+        let commit = git(dir, &["rev-parse", "HEAD"])?;
+        let verdict = test(&commit)?;
+        // Building may touch tracked files, such as lock files, which would
+        // keep git from checking out the next revision.
+        git(dir, &["reset", "-q", "--hard"])?;
+        let verdict = match verdict {
+            Verdict::Good => "good",
+            Verdict::Bad => "bad",
+            Verdict::Skip => "skip",
+        };
+        // git fails once only skipped commits are left, telling which.
+        let output = git(dir, &["bisect", verdict]);
+        let printed = match &output {
+            Ok(output) => output.clone(),
+            Err(e) => format!("{:#}", e),
+        };
+        if let Some(outcome) = outcome(dir, &printed)? {
+            return Ok(outcome);
+        }
+        output?;
+    }
+}
+
+/// How the bisection ended after git printed `output`, `None` if it goes on.
+fn outcome(dir: &Path, output: &str) -> Result<Option<Outcome>> {
+    if let Some(line) = output
+        .lines()
+        .find(|line| line.ends_with(" is the first bad commit"))
+    {
+        let commit = line.split_whitespace().next().unwrap_or_default();
+        let summary = git(dir, &["log", "-1", "--format=%h %s", commit])?;
+        return Ok(Some(Outcome::FirstBad(summary)));
+    }
+    if output.contains("only 'skip'ped commits left") {
+        let commits = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.len() >= 40 && line.chars().take(40).all(|c| c.is_ascii_hexdigit()))
+            .map(|line| {
+                let commit = &line[..40];
+                git(dir, &["log", "-1", "--format=%h %s", commit])
+            })
+            .collect::<Result<_>>()?;
+        return Ok(Some(Outcome::Inconclusive(commits)));
+    }
+    Ok(None)
+}
+
+/// Runs git in `dir`, returning its trimmed standard output.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir).args(args);
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", cmd))?;
+    if !output.status.success() {
+        // `git bisect` tells of the commits left on its standard output.
+        let printed = [&output.stdout[..], &output.stderr[..]].concat();
+        bail!(
+            "{:?} failed: {}",
+            cmd,
+            String::from_utf8_lossy(&printed).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn first_bad_and_skipped_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let commit = |n: u32| {
+            let content = if n < 5 { "safe" } else { "overflow" };
+            fs::write(repo.join("bank.move"), format!("{} {}", content, n)).unwrap();
+            git(repo, &["add", "bank.move"]).unwrap();
+            let message = format!("commit {}", n);
+            git(
+                repo,
+                &[
+                    "-c",
+                    "user.name=t",
+                    "-c",
+                    "user.email=t@t",
+                    "commit",
+                    "-qm",
+                    &message,
+                ],
+            )
+            .unwrap();
+        };
+        git(repo, &["init", "-q"]).unwrap();
+        (0..8).for_each(commit);
+        let head = git(repo, &["rev-parse", "HEAD"]).unwrap();
+        let crashes = |_: &str| {
+            let content = fs::read_to_string(repo.join("bank.move")).unwrap();
+            Ok(if content.starts_with("overflow") {
+                Verdict::Bad
+            } else {
+                Verdict::Good
+            })
+        };
+
+        match run(repo, "HEAD~7", "HEAD", crashes).unwrap() {
+            Outcome::FirstBad(summary) => assert!(summary.ends_with(" commit 5"), "{}", summary),
+            outcome => panic!("unexpected {:?}", outcome),
+        }
+        assert_eq!(git(repo, &["rev-parse", "HEAD"]).unwrap(), head);
+
+        // Commit 5 cannot be told apart from its neighbours when neither 4 nor 5
+        // builds.
+        let mut tested = 0;
+        let outcome = run(repo, "HEAD~7", "HEAD", |commit| {
+            tested += 1;
+            let subject = git(repo, &["log", "-1", "--format=%s", commit]).unwrap();
+            if subject == "commit 4" || subject == "commit 5" {
+                return Ok(Verdict::Skip);
+            }
+            crashes(commit)
+        })
+        .unwrap();
+        match outcome {
+            Outcome::Inconclusive(commits) => {
+                assert!(commits.iter().any(|c| c.ends_with(" commit 5")));
+                assert!(!commits.iter().any(|c| c.ends_with(" commit 3")));
+            }
+            outcome => panic!("unexpected {:?}", outcome),
+        }
+        assert!(tested > 2);
+
+        fs::write(repo.join("bank.move"), "dirty").unwrap();
+        assert!(run(repo, "HEAD~7", "HEAD", crashes).is_err());
+    }
+}
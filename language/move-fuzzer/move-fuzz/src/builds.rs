@@ -0,0 +1,186 @@
+//! The sources each fuzz target was last built from, stored in `fuzz/builds.json`.
+//!
+//! Cargo rebuilds a target when its harness changes but knows nothing of the
+//! Move package it exercises, so a binary can outlive the code it was built
+//! from. The hash of the sources of a target is recorded whenever its binary
+//! is rebuilt, and compared with the current one to tell stale binaries.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const BUILD_LOG_FILE: &str = "builds.json";
+
+/// The last build of each target.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BuildLog {
+    pub targets: BTreeMap<String, BuildRecord>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub binary: PathBuf,
+    /// When the binary was written, in nanoseconds since the Unix epoch.
+    pub modified: u64,
+    /// SHA-1 of the sources the binary was built from.
+    pub sources: String,
+}
+
+/// Why a target is not up to date.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Staleness {
+    /// There is no binary, or it was not built by move-fuzz
+    NeverBuilt,
+    /// The sources changed since the binary was built
+    Changed,
+}
+
+impl fmt::Display for Staleness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Staleness::NeverBuilt => "never built",
+                Staleness::Changed => "sources changed since the last build",
+            }
+        )
+    }
+}
+
+impl BuildLog {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("could not decode the build log at {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(path, data).with_context(|| format!("failed to write to {}", path.display()))
+    }
+
+    /// Records that `target` was built into `binary` from `sources`, unless
+    /// the binary was left as it was. Returns `false` if it was although the
+    /// sources changed, which Cargo does when only Move code did.
+    pub fn record(&mut self, target: &str, binary: &Path, sources: &str) -> Result<bool> {
+        let modified = modified_nanos(binary)?;
+        if let Some(record) = self.targets.get(target) {
+            if record.binary == binary && record.modified == modified {
+                return Ok(record.sources == sources);
+            }
+        }
+        self.targets.insert(
+            target.to_owned(),
+            BuildRecord {
+                binary: binary.to_owned(),
+                modified,
+                sources: sources.to_owned(),
+            },
+        );
+        Ok(true)
+    }
+
+    /// Whether the last binary of `target` is stale, `sources` being the hash
+    /// of its current sources.
+    pub fn staleness(&self, target: &str, sources: &str) -> Option<Staleness> {
+        match self.targets.get(target) {
+            Some(record) if !record.binary.exists() => Some(Staleness::NeverBuilt),
+            Some(record) if record.sources != sources => Some(Staleness::Changed),
+            Some(_) => None,
+            None => Some(Staleness::NeverBuilt),
+        }
+    }
+}
+
+/// SHA-1 of the names and contents of the `sources`, skipping those that do
+/// not exist. Each is named by its path from the directory it comes with, so
+/// that the hash stays the same wherever the project is checked out.
+pub fn hash_sources(sources: &[(PathBuf, PathBuf)]) -> Result<String> {
+    let mut hash = sha1_smol::Sha1::new();
+    for (dir, path) in sources {
+        if !path.is_file() {
+            continue;
+        }
+        let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let name: Vec<_> = path
+            .strip_prefix(dir)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        hash.update(name.join("/").as_bytes());
+        hash.update(&(data.len() as u64).to_le_bytes());
+        hash.update(&data);
+    }
+    Ok(hash.digest().to_string())
+}
+
+fn modified_nanos(path: &Path) -> Result<u64> {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("failed to read the metadata of {}", path.display()))?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_builds_and_tell_stale_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("bank.move");
+        let binary = dir.path().join("deposit");
+        fs::write(&source, "module 0x1::bank {}").unwrap();
+        fs::write(&binary, "v1").unwrap();
+
+        let mut log = BuildLog::default();
+        let root = dir.path().to_owned();
+        let built = hash_sources(&[
+            (root.clone(), source.clone()),
+            (root.clone(), root.join("missing")),
+        ])
+        .unwrap();
+        assert_eq!(
+            log.staleness("deposit", &built),
+            Some(Staleness::NeverBuilt)
+        );
+        assert!(log.record("deposit", &binary, &built).unwrap());
+        assert_eq!(log.staleness("deposit", &built), None);
+
+        // Only the Move module changed, so the binary was not rebuilt.
+        fs::write(&source, "module 0x1::bank { fun f() {} }").unwrap();
+        let changed = hash_sources(&[(root.clone(), source.clone())]).unwrap();
+        assert_ne!(built, changed);
+
+        // The same sources elsewhere hash the same.
+        let moved = tempfile::tempdir().unwrap();
+        fs::copy(&source, moved.path().join("bank.move")).unwrap();
+        assert_eq!(
+            hash_sources(&[(moved.path().to_owned(), moved.path().join("bank.move"))]).unwrap(),
+            changed
+        );
+        assert!(!log.record("deposit", &binary, &changed).unwrap());
+        assert_eq!(log.staleness("deposit", &changed), Some(Staleness::Changed));
+
+        let path = dir.path().join(BUILD_LOG_FILE);
+        log.save(&path).unwrap();
+        assert_eq!(BuildLog::load(&path).unwrap(), log);
+        fs::remove_file(&binary).unwrap();
+        assert_eq!(
+            log.staleness("deposit", &built),
+            Some(Staleness::NeverBuilt)
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! The annotated bytecode view of `repro --view-bytecode`.
+//!
+//! The Move VM traces every instruction before running it, so the last line
+//! of the trace of a crashing input is the instruction that aborted or failed.
+//! The view is the code of its function, read from the compiled module or
+//! script, with that instruction marked.
+
+use crate::modules;
+use move_binary_format::access::{ModuleAccess, ScriptAccess};
+use move_binary_format::file_format::{
+    Bytecode, CompiledModule, CompiledScript, FunctionHandleIndex, Visibility,
+};
+use move_core_types::account_address::AccountAddress;
+use std::fmt;
+use std::fmt::Write;
+
+/// An instruction the Move VM ran, as a line of its trace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Location {
+    /// The module of the function, as in `0x42::bank`, `None` in a script
+    pub module: Option<String>,
+    pub function: String,
+    pub offset: u16,
+    pub instruction: String,
+}
+
+impl Location {
+    /// The address and name of the module of the function, `None` in a script.
+    pub fn module_id(&self) -> Option<(AccountAddress, &str)> {
+        let (address, name) = self.module.as_deref()?.split_once("::")?;
+        Some((AccountAddress::from_hex_literal(address).ok()?, name))
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{}::{}", module, self.function)?,
+            None => write!(f, "script {}", self.function)?,
+        }
+        write!(f, " at code offset {}: {}", self.offset, self.instruction)
+    }
+}
+
+/// The last instruction of a trace written with `MOVE_VM_TRACE`, whose lines
+/// are `<execution>,<function>,<offset>,<instruction>`.
+pub fn last_location(trace: &str) -> Option<Location> {
+    let line = trace.lines().rev().find(|line| !line.trim().is_empty())?;
+    let mut fields = line.splitn(4, ',');
+    let _execution = fields.next()?;
+    let function = fields.next()?;
+    let offset = fields.next()?.parse().ok()?;
+    let instruction = fields.next()?.to_owned();
+    let (module, function) = match function.rsplit_once("::")? {
+        ("Script", function) => (None, function),
+        (module, function) => (Some(module.to_owned()), function),
+    };
+    Some(Location {
+        module,
+        function: function.to_owned(),
+        offset,
+        instruction,
+    })
+}
+
+/// The code of the function of `location` in the compiled `module`, with its
+/// instruction marked by `>>`, `None` if the module does not define the
+/// function or it is native.
+pub fn annotate_module(module: &CompiledModule, location: &Location) -> Option<String> {
+    let def = module.function_defs().iter().find(|def| {
+        let handle = module.function_handle_at(def.function);
+        module.identifier_at(handle.name).as_str() == location.function
+    })?;
+    let handle = module.function_handle_at(def.function);
+    let types = |index| {
+        module
+            .signature_at(index)
+            .0
+            .iter()
+            .map(|ty| modules::type_name(module, ty))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let visibility = match def.visibility {
+        Visibility::Public => "public ",
+        Visibility::Friend => "public(friend) ",
+        Visibility::Private => "",
+    };
+    let returns = types(handle.return_);
+    let header = format!(
+        "{}{}fun {}({}){}",
+        visibility,
+        if def.is_entry { "entry " } else { "" },
+        location.function,
+        types(handle.parameters),
+        if returns.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", returns)
+        }
+    );
+    let callee = |index: FunctionHandleIndex| {
+        let handle = module.function_handle_at(index);
+        let name = module.identifier_at(handle.name);
+        if handle.module == module.self_handle_idx() {
+            name.to_string()
+        } else {
+            let id = module.module_id_for_handle(module.module_handle_at(handle.module));
+            format!("{}::{}", id.short_str_lossless(), name)
+        }
+    };
+    let code = &def.code.as_ref()?.code;
+    Some(render(
+        &header,
+        code,
+        location.offset,
+        |instruction| match instruction {
+            Bytecode::Call(index) => Some(callee(*index)),
+            Bytecode::CallGeneric(index) => {
+                Some(callee(module.function_instantiation_at(*index).handle))
+            }
+            _ => None,
+        },
+    ))
+}
+
+/// The code of the compiled `script`, with the instruction of `location`
+/// marked by `>>`.
+pub fn annotate_script(script: &CompiledScript, location: &Location) -> String {
+    let callee = |index: FunctionHandleIndex| {
+        let handle = script.function_handle_at(index);
+        let module = script.module_handle_at(handle.module);
+        format!(
+            "{}::{}::{}",
+            script
+                .address_identifier_at(module.address)
+                .short_str_lossless(),
+            script.identifier_at(module.name),
+            script.identifier_at(handle.name)
+        )
+    };
+    let header = format!("script fun {}", location.function);
+    render(
+        &header,
+        &script.code().code,
+        location.offset,
+        |instruction| match instruction {
+            Bytecode::Call(index) => Some(callee(*index)),
+            Bytecode::CallGeneric(index) => {
+                Some(callee(script.function_instantiation_at(*index).handle))
+            }
+            _ => None,
+        },
+    )
+}
+
+/// A line per instruction of `code` under `header`, calls naming the function
+/// `callee` gives for them.
+fn render(
+    header: &str,
+    code: &[Bytecode],
+    offset: u16,
+    callee: impl Fn(&Bytecode) -> Option<String>,
+) -> String {
+    let mut view = format!("   {} {{\n", header);
+    for (i, instruction) in code.iter().enumerate() {
+        let mark = if i == offset as usize { ">> " } else { "   " };
+        let text = match callee(instruction) {
+            Some(function) => format!("Call {}", function),
+            None => format!("{:?}", instruction),
+        };
+        let _ = writeln!(view, "{}\t{}: {}", mark, i, text);
+    }
+    view.push_str("   }\n");
+    view
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn annotate_last_instruction() {
+        let trace = "\
+12-ThreadId(1),Script::main,0,MoveLoc(0)
+12-ThreadId(1),0x42::bank::withdraw,0,CopyLoc(0)
+12-ThreadId(1),0x42::bank::withdraw,1,Call(FunctionHandleIndex(0), [0, 1])
+12-ThreadId(1),0x42::bank::withdraw,2,Sub
+";
+        let location = last_location(trace).unwrap();
+        assert_eq!(location.module.as_deref(), Some("0x42::bank"));
+        assert_eq!(
+            location.module_id(),
+            Some((AccountAddress::from_hex_literal("0x42").unwrap(), "bank"))
+        );
+        assert_eq!(location.offset, 2);
+        assert_eq!(
+            location.to_string(),
+            "0x42::bank::withdraw at code offset 2: Sub"
+        );
+        let script = last_location(trace.lines().next().unwrap()).unwrap();
+        assert_eq!(script.module_id(), None);
+        assert_eq!(script.function, "main");
+
+        let module = modules::test::compile(
+            "module 0x42.bank {
+                public balance(a: address): u64 { label b0: return 0; }
+                public entry withdraw(s: &signer, amount: u64) {
+                label b0:
+                    _ = Self.balance(0x1) - copy(amount);
+                    return;
+                }
+            }",
+        );
+        let sub = Location {
+            offset: 3,
+            ..location.clone()
+        };
+        let view = annotate_module(&module, &sub).unwrap();
+        assert!(view.starts_with("   public entry fun withdraw(&signer, u64) {\n"));
+        assert!(view.contains("   \t1: Call balance\n"));
+        assert!(view.contains("\n>> \t3: Sub\n"), "{}", view);
+        assert!(view.ends_with("Ret\n   }\n"));
+        let missing = Location {
+            function: "deposit".to_owned(),
+            ..location
+        };
+        assert!(annotate_module(&module, &missing).is_none());
+    }
+}
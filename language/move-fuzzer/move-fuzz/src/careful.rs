@@ -0,0 +1,212 @@
+//! What `--careful` builds the fuzz targets with, after what
+//! [cargo-careful](https://github.com/RalfJung/cargo-careful) configures.
+//!
+//! The standard library is rebuilt with `-Zbuild-std`, debug assertions and
+//! the `debug_refcell` feature, recording where a `RefCell` was borrowed for
+//! its double-borrow panics. Everything gets the extra const-UB and init checks
+//! and `cfg(careful)`, and the package under test and its harnesses also get
+//! the strict provenance lints. All of these are nightly-only: the toolchain is
+//! checked for them before building.
+
+use anyhow::{bail, Context, Result};
+use rustc_version::Channel;
+use std::env;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// The `-Z` flags of rustc `--careful` needs.
+const CHECKS: [&str; 2] = ["extra-const-ub-checks", "strict-init-checks"];
+
+/// The lints flagging the integer/pointer casts strict provenance rules out.
+const PROVENANCE_LINTS: [&str; 2] = ["fuzzy_provenance_casts", "lossy_provenance_casts"];
+
+/// The features the standard library is built with, the default ones and
+/// `debug_refcell`.
+pub const STD_FEATURES: &str = "backtrace,panic-unwind,debug_refcell";
+
+/// What `rustc` tells of itself.
+#[derive(Debug, Default)]
+pub struct Toolchain {
+    /// `release` of `rustc -vV`, such as `1.77.0-nightly`
+    pub release: String,
+    pub nightly: bool,
+    pub minor: u64,
+    /// Whether the `rust-src` component is installed
+    pub has_src: bool,
+    /// The output of `rustc -Z help`, empty on stable
+    pub z_help: String,
+    /// The output of `rustc -W help`
+    pub w_help: String,
+}
+
+impl Toolchain {
+    /// The toolchain `cargo` builds with.
+    pub fn current() -> Result<Self> {
+        let meta = rustc_version::version_meta().context("could not query the rustc version")?;
+        let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+        let help = |flag: &str| {
+            Command::new(&rustc)
+                .args([flag, "help"])
+                .stderr(Stdio::null())
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default()
+        };
+        let sysroot = Command::new(&rustc)
+            .args(["--print", "sysroot"])
+            .output()
+            .context("could not query the rustc sysroot")?;
+        let sysroot = String::from_utf8_lossy(&sysroot.stdout).trim().to_owned();
+        Ok(Toolchain {
+            release: meta.semver.to_string(),
+            nightly: matches!(meta.channel, Channel::Nightly | Channel::Dev),
+            minor: meta.semver.minor,
+            has_src: Path::new(&sysroot)
+                .join("lib/rustlib/src/rust/library")
+                .is_dir(),
+            z_help: help("-Z"),
+            w_help: help("-W"),
+        })
+    }
+}
+
+/// The flags of a careful build, for a toolchain supporting them.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Careful {
+    check_cfg: bool,
+    /// The feature gating the provenance lints, `None` if they are unknown
+    provenance_feature: Option<&'static str>,
+}
+
+impl Careful {
+    /// Checks that `toolchain` can build carefully.
+    pub fn new(toolchain: &Toolchain) -> Result<Self> {
+        if !toolchain.nightly {
+            bail!(
+                "`--careful` needs a nightly toolchain, rustc is {}: \
+                 run with `cargo +nightly` or `rustup override set nightly`",
+                toolchain.release
+            );
+        }
+        if !toolchain.has_src {
+            bail!(
+                "`--careful` rebuilds the standard library, install its sources with \
+                 `rustup component add rust-src --toolchain nightly`"
+            );
+        }
+        let missing: Vec<_> = CHECKS
+            .iter()
+            .filter(|check| !toolchain.z_help.contains(&format!(" {}=", check)))
+            .map(|check| format!("-Z{}", check))
+            .collect();
+        if !missing.is_empty() {
+            bail!(
+                "rustc {} does not support {}, which `--careful` needs: update the nightly toolchain",
+                toolchain.release,
+                missing.join(" and ")
+            );
+        }
+
+        let lints_known = PROVENANCE_LINTS
+            .iter()
+            .all(|lint| toolchain.w_help.contains(&lint.replace('_', "-")));
+        if !lints_known {
+            warn!(
+                "rustc {} has no strict provenance lints, building without them",
+                toolchain.release
+            );
+        }
+        Ok(Careful {
+            check_cfg: toolchain.minor >= 80,
+            // The lints got a feature of their own in 1.84.
+            provenance_feature: lints_known.then_some(if toolchain.minor >= 84 {
+                "strict_provenance_lints"
+            } else {
+                "strict_provenance"
+            }),
+        })
+    }
+
+    /// The flags of every crate, the standard library included.
+    pub fn rustflags(&self) -> String {
+        let mut rustflags = CHECKS
+            .iter()
+            .map(|check| format!(" -Z{}", check))
+            .collect::<String>();
+        rustflags.push_str(" --cfg careful");
+        if self.check_cfg {
+            rustflags.push_str(" --check-cfg=cfg(careful)");
+        }
+        rustflags
+    }
+
+    /// The flags of the package under test and its harnesses, enabling the
+    /// provenance lints. The standard library enables their feature itself.
+    pub fn package_rustflags(&self) -> Vec<String> {
+        let Some(feature) = self.provenance_feature else {
+            return vec![];
+        };
+        let mut rustflags = vec![format!("-Zcrate-attr=feature({})", feature)];
+        rustflags.extend(PROVENANCE_LINTS.iter().map(|lint| format!("-W{}", lint)));
+        rustflags
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn careful_flags_by_toolchain() {
+        let nightly = Toolchain {
+            release: "1.86.0-nightly".to_owned(),
+            nightly: true,
+            minor: 86,
+            has_src: true,
+            z_help: "    -Z                    extra-const-ub-checks=val -- turns on more checks\n\
+                     -Z                    strict-init-checks=val -- be more strict\n"
+                .to_owned(),
+            w_help: "  fuzzy-provenance-casts  allow\n  lossy-provenance-casts  allow\n".to_owned(),
+        };
+        let careful = Careful::new(&nightly).unwrap();
+        assert_eq!(
+            careful.rustflags(),
+            " -Zextra-const-ub-checks -Zstrict-init-checks --cfg careful --check-cfg=cfg(careful)"
+        );
+        assert_eq!(
+            careful.package_rustflags(),
+            [
+                "-Zcrate-attr=feature(strict_provenance_lints)",
+                "-Wfuzzy_provenance_casts",
+                "-Wlossy_provenance_casts"
+            ]
+        );
+
+        let old = Toolchain {
+            minor: 70,
+            w_help: String::new(),
+            ..nightly
+        };
+        let careful = Careful::new(&old).unwrap();
+        assert!(!careful.rustflags().contains("check-cfg"));
+        assert!(careful.package_rustflags().is_empty());
+
+        let stable = Toolchain {
+            nightly: false,
+            ..Toolchain::default()
+        };
+        assert!(Careful::new(&stable)
+            .unwrap_err()
+            .to_string()
+            .contains("nightly"));
+        let no_src = Toolchain {
+            has_src: false,
+            ..old
+        };
+        assert!(Careful::new(&no_src)
+            .unwrap_err()
+            .to_string()
+            .contains("rust-src"));
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2016 rust-fuzz developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The `move-fuzz` command line, shared by the `move-fuzz` binary and the
+//! tools embedding it such as `move fuzz`.
+
+use crate::options::MessageFormat;
+use crate::{batch, compat, config, logging, messages, options, plugins, progress, RunCommand};
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::ffi::OsString;
+use std::{env, process};
+
+// It turns out that `clap`'s `long_about()` makes `cargo fuzz --help`
+// unreadable, and its `before_help()` injects our long about text before the
+// version, so change the default template slightly.
+const LONG_ABOUT_TEMPLATE: &str = "\
+{bin} {version}
+{about}
+
+USAGE:
+    {usage}
+
+{before-help}
+
+{all-args}
+
+{after-help}";
+
+const RUN_BEFORE_HELP: &str = "\
+The fuzz target name is the same as the name of the fuzz target script in
+fuzz/fuzz_targets/, i.e. the name picked when running `cargo fuzz add`.
+
+This will run the script inside the fuzz target with varying inputs until it
+finds a crash, at which point it will save the crash input to the artifact
+directory, print some output, and exit. Unless you configure it otherwise (see
+libFuzzer options below), this will run indefinitely.
+
+By default fuzz targets are built with optimizations equivalent to
+`cargo build --release`, but with debug assertions and overflow checks enabled.
+Address Sanitizer is also enabled by default.";
+
+const RUN_AFTER_HELP: &str = "\
+A full list of libFuzzer options can be found at
+http://llvm.org/docs/LibFuzzer.html#options
+
+You can also get this by running `cargo fuzz run fuzz_target -- -help=1`
+
+Some useful options (to be used as `cargo fuzz run fuzz_target -- <options>`)
+include:
+
+  * `-max_len=<len>`: Will limit the length of the input string to `<len>`
+
+  * `-runs=<number>`: Will limit the number of tries (runs) before it gives up
+
+  * `-max_total_time=<time>`: Will limit the amount of time (seconds) to
+    fuzz before it gives up
+
+  * `-timeout=<time>`: Will limit the amount of time (seconds) for a single
+    run before it considers that run a failure
+
+  * `-only_ascii`: Only provide ASCII input
+
+  * `-dict=<file>`: Use a keyword dictionary from specified file. See
+    http://llvm.org/docs/LibFuzzer.html#dictionaries\
+";
+
+const BUILD_BEFORE_HELP: &str = "\
+By default fuzz targets are built with optimizations equivalent to
+`cargo build --release`, but with debug assertions and overflow checks enabled.
+Address Sanitizer is also enabled by default.";
+
+const BUILD_AFTER_HELP: &str = "\
+Sanitizers perform checks necessary for detecting bugs in unsafe code
+at the cost of some performance. For more information on sanitizers see
+https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html\
+";
+
+const CONFIG_AFTER_HELP: &str = "\
+Every `--some-option` can also be set with a `MOVE_FUZZ_OPT_SOME_OPTION`
+environment variable, or under `[package.metadata.move-fuzz.defaults]` in the
+fuzz manifest, in a `[package.metadata.move-fuzz.defaults.<command>]` table for
+a single command, or under `[fuzz.defaults]` in the `Move.toml` of the fuzz
+package. The command line wins over the environment, which wins over the
+manifest, which wins over `Move.toml`.\
+";
+
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+#[command(after_help = CONFIG_AFTER_HELP)]
+#[command(subcommand_required = true)]
+#[command(arg_required_else_help = true)]
+#[command(propagate_version = true)]
+struct Cli {
+    #[command(flatten)]
+    log: options::LogOptions,
+
+    #[command(flatten)]
+    batch: options::BatchOptions,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+enum Command {
+    /// Initialize the fuzz directory
+    Init(options::Init),
+
+    /// Add a new fuzz target
+    Add(options::Add),
+
+    /// Generate fuzz targets for the `#[fuzz]` or public entry functions of a Move package
+    Autoharness(options::Autoharness),
+
+    /// Generate a libFuzzer dictionary from the names and constants of the modules
+    /// of a Move package
+    GenDict(options::GenDict),
+
+    /// Remove a fuzz target along with its corpus and artifacts
+    Remove(options::Remove),
+
+    /// Rename a fuzz target along with its corpus and artifacts
+    Rename(options::Rename),
+
+    /// Remove the corpus, artifacts, coverage data or builds of the fuzz targets
+    Clean(options::Clean),
+
+    #[command(
+        help_template(LONG_ABOUT_TEMPLATE),
+        before_help(BUILD_BEFORE_HELP),
+        after_help(BUILD_AFTER_HELP)
+    )]
+    /// Build fuzz targets
+    Build(options::Build),
+
+    #[command(help_template(LONG_ABOUT_TEMPLATE))]
+    /// Type-check the fuzz targets, warning about the sanitizer features the platform lacks
+    Check(options::Check),
+
+    /// Build every fuzz target and fuzz it briefly, to check that its harness starts,
+    /// decodes inputs and runs them
+    Smoke(options::Smoke),
+
+    /// Print the `std::fmt::Debug` output for an input, or convert it to and from text
+    Fmt(options::Fmt),
+
+    /// List all the existing fuzz targets
+    List(options::List),
+
+    #[command(
+        help_template(LONG_ABOUT_TEMPLATE),
+        before_help(RUN_BEFORE_HELP),
+        after_help(RUN_AFTER_HELP)
+    )]
+    /// Run a fuzz target
+    Run(Box<options::Run>),
+
+    /// Reproduce a single input, optionally recording the execution
+    Repro(options::Repro),
+
+    /// Find the commit that made an input crash with `git bisect`, rebuilding the
+    /// fuzz target at each revision
+    Bisect(options::Bisect),
+
+    /// Minify a corpus
+    Cmin(options::Cmin),
+
+    /// Merge corpora into the corpus of a target, keeping inputs that add coverage.
+    /// Interrupted merges resume where they stopped
+    Merge(options::Merge),
+
+    /// Merge the corpora of targets with the same input type, keeping inputs that add coverage
+    Distill(options::Distill),
+
+    /// Manage the corpus of a fuzz target
+    #[command(subcommand_required = true)]
+    Corpus(options::Corpus),
+
+    /// Minify a test case
+    Tmin(options::Tmin),
+
+    /// Run program on the generated corpus and generate coverage information
+    Coverage(options::Coverage),
+
+    /// Reproduce all artifacts, bucket them by crash signature and report
+    Triage(options::Triage),
+
+    /// Re-run crash artifacts against the current build and mark the fixed ones in the triage database
+    Verify(options::Verify),
+
+    /// Turn a crash artifact into a regression test in `fuzz/regressions/`
+    GenRegression(options::GenRegression),
+
+    /// Minimize a crash artifact, decode it, reproduce it, add a regression test
+    /// and record it in the triage database, leaving a bundle ready to file
+    ProcessCrash(options::ProcessCrash),
+
+    /// Execute inputs against the package and a baseline revision and report diverging results
+    DiffRun(options::DiffRun),
+
+    /// Replay the corpus without mutation and report the throughput of the harness
+    Bench(options::Bench),
+
+    /// Summarize targets, corpora, coverage and open crashes in a single report
+    Report(options::Report),
+
+    /// Build a container image running a fuzz target on its corpus
+    Docker(options::Docker),
+
+    /// Hand out targets to workers and collect the inputs and crashes they find
+    Serve(options::Serve),
+
+    /// Fuzz the targets assigned by a `serve` coordinator and send back what is found.
+    ///
+    /// Workers build the targets from their own checkout of the package, which
+    /// must match the coordinator's.
+    Worker(options::Worker),
+
+    /// Print the completions of the commands and their options for a shell
+    Completions(options::Completions),
+
+    /// Print or write the man pages of the commands
+    Man(options::Man),
+
+    /// Print the JSON schema of the messages, artifact metadata or triage database
+    Schema(options::Schema),
+
+    /// Run `move-fuzz-<name>` from the PATH, with the fuzz project in its environment
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+impl RunCommand for Command {
+    fn run_command(&mut self) -> Result<()> {
+        match self {
+            Command::Init(x) => x.run_command(),
+            Command::Add(x) => x.run_command(),
+            Command::Autoharness(x) => x.run_command(),
+            Command::GenDict(x) => x.run_command(),
+            Command::Remove(x) => x.run_command(),
+            Command::Rename(x) => x.run_command(),
+            Command::Clean(x) => x.run_command(),
+            Command::Build(x) => x.run_command(),
+            Command::Check(x) => x.run_command(),
+            Command::Smoke(x) => x.run_command(),
+            Command::List(x) => x.run_command(),
+            Command::Fmt(x) => x.run_command(),
+            Command::Run(x) => x.run_command(),
+            Command::Repro(x) => x.run_command(),
+            Command::Bisect(x) => x.run_command(),
+            Command::Cmin(x) => x.run_command(),
+            Command::Merge(x) => x.run_command(),
+            Command::Distill(x) => x.run_command(),
+            Command::Corpus(x) => x.run_command(),
+            Command::Tmin(x) => x.run_command(),
+            Command::Coverage(x) => x.run_command(),
+            Command::Triage(x) => x.run_command(),
+            Command::Verify(x) => x.run_command(),
+            Command::GenRegression(x) => x.run_command(),
+            Command::ProcessCrash(x) => x.run_command(),
+            Command::DiffRun(x) => x.run_command(),
+            Command::Bench(x) => x.run_command(),
+            Command::Report(x) => x.run_command(),
+            Command::Docker(x) => x.run_command(),
+            Command::Serve(x) => x.run_command(),
+            Command::Worker(x) => x.run_command(),
+            Command::Completions(x) => x.run_command(),
+            Command::Man(x) => x.run_command(),
+            Command::Schema(x) => x.run_command(),
+            Command::External(args) => plugins::run(args),
+        }
+    }
+}
+
+/// Parses and runs a command line, `args[0]` being the name of the program
+/// shown in the help.
+///
+/// Options fall back to their `MOVE_FUZZ_*` environment variable, then to the
+/// defaults of the fuzz manifest, see [`config`]. Invalid command lines and
+/// `--help` print their message and exit the process, as in the binary.
+pub fn run(args: Vec<OsString>) -> Result<()> {
+    let defaults = config::manifest_defaults(&args)?;
+    let matches = config::layer(Cli::command(), &defaults).get_matches_from(args);
+    config::record_explicit(&matches);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    logging::init(&cli.log)?;
+    if cli.log.no_progress {
+        progress::Progress::hide_terminal();
+    }
+    batch::set_fail_fast(cli.batch.fail_fast());
+    let json = matches.subcommand().is_some_and(|(_, command)| {
+        command
+            .try_get_one::<MessageFormat>("message_format")
+            .ok()
+            .flatten()
+            == Some(&MessageFormat::Json)
+    });
+    if json {
+        messages::reserve_stdout()?;
+    }
+    cargo_fuzz_compat(cli.command).run_command()
+}
+
+/// The command doing what a cargo-fuzz command line asks, see [`compat`].
+fn cargo_fuzz_compat(command: Command) -> Command {
+    match command {
+        Command::Run(run) => match compat::libfuzzer_mode(&run) {
+            Some(compat::Mode::MinimizeCrash(tmin)) => Command::Tmin(tmin),
+            Some(compat::Mode::Merge(merge)) => Command::Merge(merge),
+            None => Command::Run(run),
+        },
+        command => command,
+    }
+}
+
+/// The `move-fuzz` command line, for the completions and the man pages.
+pub(crate) fn command() -> clap::Command {
+    Cli::command()
+}
+
+/// A command running `move-fuzz` again, to which the arguments are added.
+pub(crate) fn self_command() -> Result<process::Command> {
+    Ok(process::Command::new(env::current_exe()?))
+}
@@ -0,0 +1,350 @@
+//! Distributed fuzzing: `move-fuzz serve` hands out time slices of fuzzing on
+//! the targets of a project and keeps their corpora and crashes, and
+//! `move-fuzz worker` runs the slices, syncing inputs with the coordinator.
+//!
+//! The two talk a small subset of HTTP/1.1 over plain TCP, one request per
+//! connection. Lines, bodies and stalls are all bounded, as the coordinator
+//! reads a request before it can tell whether the worker holds the token.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Largest request body the coordinator accepts, to bound its memory.
+pub const MAX_REQUEST_LEN: usize = 64 << 20;
+
+/// Largest response body a worker accepts, a whole corpus.
+const MAX_RESPONSE_LEN: usize = 1 << 30;
+
+/// Longest request, status or header line either side reads.
+pub const MAX_LINE_LEN: usize = 8 << 10;
+
+/// Most headers either side reads before the body.
+const MAX_HEADERS: usize = 100;
+
+/// How long either side waits on a stalled connection.
+pub const IO_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A time slice of fuzzing on one target.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub target: String,
+    pub seconds: u64,
+}
+
+/// Hands out the targets round-robin, so every target gets the same share of the cluster.
+#[derive(Debug)]
+pub struct Scheduler {
+    targets: Vec<String>,
+    slice: u64,
+    next: AtomicUsize,
+}
+
+impl Scheduler {
+    pub fn new(targets: Vec<String>, slice: u64) -> Self {
+        Self {
+            targets,
+            slice,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    pub fn assign(&self) -> Assignment {
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        let target = self.targets[next % self.targets.len()].clone();
+        Assignment {
+            target,
+            seconds: self.slice,
+        }
+    }
+}
+
+/// A request from `move-fuzz worker` to `move-fuzz serve`, one of:
+///
+/// - `GET /assignment`, answered with the next [`Assignment`] as JSON
+/// - `GET /corpus/<target>`, answered with the corpus of a target as a tar archive
+/// - `POST /corpus/<target>` with a tar archive of inputs to add to the corpus
+/// - `POST /crashes/<target>/<name>` with a crashing input to store as an artifact
+///
+/// Every request carries the token shared by the cluster as a bearer token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub token: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The segments of the path, e.g. `["crashes", "a", "crash-1"]`.
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+
+    /// Whether the request carries `token`, compared in constant time.
+    pub fn is_authorized(&self, token: &str) -> bool {
+        let given = self.token.as_deref().unwrap_or_default().as_bytes();
+        given.len() == token.len()
+            && given
+                .iter()
+                .zip(token.as_bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// The token shared by the coordinator and its workers, the first line of `path`.
+pub fn read_token(path: &Path) -> Result<String> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read the token file {}", path.display()))?;
+    let token = text.lines().next().unwrap_or_default().trim();
+    if token.is_empty() {
+        bail!("the token file {} is empty", path.display());
+    }
+    Ok(token.to_owned())
+}
+
+pub fn read_request(reader: impl Read) -> Result<Request> {
+    let mut reader = BufReader::new(reader);
+    let line = read_line(&mut reader)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_owned(), path.to_owned()),
+        _ => bail!("malformed request line `{}`", line.trim()),
+    };
+    let (len, token) = read_headers(&mut reader)?;
+    let body = read_body(&mut reader, len, MAX_REQUEST_LEN)?;
+    Ok(Request {
+        method,
+        path,
+        token,
+        body,
+    })
+}
+
+/// Reads the headers, returning the `Content-Length` and the bearer token.
+fn read_headers(reader: &mut impl BufRead) -> Result<(usize, Option<String>)> {
+    let mut len = 0;
+    let mut token = None;
+    for _ in 0..=MAX_HEADERS {
+        let header = read_line(reader)?;
+        if header.trim().is_empty() {
+            return Ok((len, token));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                len = value.trim().parse().context("invalid Content-Length")?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                token = value
+                    .trim()
+                    .strip_prefix("Bearer ")
+                    .map(|token| token.trim().to_owned());
+            }
+        }
+    }
+    bail!("more than {} headers", MAX_HEADERS)
+}
+
+/// Reads a line, at most [`MAX_LINE_LEN`] bytes with its line break, or what
+/// is left of the stream.
+fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if line.len() > MAX_LINE_LEN {
+        bail!("line longer than {} bytes", MAX_LINE_LEN);
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// Reads a body of `len` bytes, at most `max_len`. The body grows as it
+/// arrives, so a peer announcing more than it sends does not get the memory.
+fn read_body(reader: &mut impl Read, len: usize, max_len: usize) -> Result<Vec<u8>> {
+    if len > max_len {
+        bail!("body of {} bytes is too large", len);
+    }
+    let mut body = Vec::new();
+    reader.take(len as u64).read_to_end(&mut body)?;
+    if body.len() < len {
+        bail!("body cut short at {} of {} bytes", body.len(), len);
+    }
+    Ok(body)
+}
+
+pub fn write_response(mut writer: impl Write, status: u16, body: &[u8]) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    )?;
+    writer.write_all(body)?;
+    Ok(writer.flush()?)
+}
+
+/// Sends a request to the coordinator at `addr`, returning the body of a `200 OK` response.
+pub fn request(addr: &str, token: &str, method: &str, path: &str, body: &[u8]) -> Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(addr)
+        .with_context(|| format!("could not connect to the coordinator at {}", addr))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        method,
+        path,
+        addr,
+        token,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let status_line = read_line(&mut reader)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("malformed response `{}`", status_line.trim()))?;
+    let (len, _) = read_headers(&mut reader)?;
+    let body = read_body(&mut reader, len, MAX_RESPONSE_LEN)?;
+    if status != 200 {
+        bail!(
+            "{} {} failed with {}: {}",
+            method,
+            path,
+            status,
+            String::from_utf8_lossy(&body)
+        );
+    }
+    Ok(body)
+}
+
+/// Packs inputs into a tar archive, naming each entry by its position.
+pub fn pack(inputs: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (i, data) in inputs.iter().enumerate() {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, i.to_string(), data.as_slice())?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+pub fn unpack(archive: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut inputs = Vec::new();
+    for entry in tar::Archive::new(archive).entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file() {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            inputs.push(data);
+        }
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let scheduler = Scheduler::new(vec!["a".to_owned(), "b".to_owned()], 60);
+        let targets: Vec<_> = (0..3).map(|_| scheduler.assign().target).collect();
+        assert_eq!(targets, ["a", "b", "a"]);
+    }
+
+    #[test]
+    fn parse_request() {
+        let raw = b"POST /crashes/a/crash-1 HTTP/1.1\r\nHost: x\r\ncontent-length: 3\r\n\r\nabcdef";
+        let request = read_request(&raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.segments(), ["crashes", "a", "crash-1"]);
+        assert_eq!(request.body, b"abc");
+    }
+
+    #[test]
+    fn authorize_requests() {
+        let raw = b"GET /assignment HTTP/1.1\r\nAuthorization: Bearer s3cret\r\n\r\n";
+        let request = read_request(&raw[..]).unwrap();
+        assert!(request.is_authorized("s3cret"));
+        assert!(!request.is_authorized("s3cre"));
+        assert!(!request.is_authorized("s3cres"));
+
+        let raw = b"GET /assignment HTTP/1.1\r\n\r\n";
+        assert!(!read_request(&raw[..]).unwrap().is_authorized("s3cret"));
+    }
+
+    #[test]
+    fn reject_oversized_or_short_bodies() {
+        let raw = format!(
+            "POST /corpus/a HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_REQUEST_LEN + 1
+        );
+        let e = read_request(raw.as_bytes()).unwrap_err();
+        assert!(e.to_string().contains("too large"), "{}", e);
+
+        let raw = b"POST /corpus/a HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc";
+        let e = read_request(&raw[..]).unwrap_err();
+        assert_eq!(e.to_string(), "body cut short at 3 of 10 bytes");
+    }
+
+    #[test]
+    fn reject_overlong_lines() {
+        let raw = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LEN));
+        let e = read_request(raw.as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line longer than 8192 bytes");
+
+        let raw = format!(
+            "GET / HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_LEN)
+        );
+        let e = read_request(raw.as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "line longer than 8192 bytes");
+    }
+
+    #[test]
+    fn reject_endless_headers() {
+        let raw = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-Padding: a\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let e = read_request(raw.as_bytes()).unwrap_err();
+        assert_eq!(e.to_string(), "more than 100 headers");
+
+        let raw = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-Padding: a\r\n".repeat(MAX_HEADERS)
+        );
+        assert!(read_request(raw.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn pack_unpack() {
+        let inputs = vec![b"one".to_vec(), Vec::new()];
+        assert_eq!(unpack(&pack(&inputs).unwrap()).unwrap(), inputs);
+    }
+}
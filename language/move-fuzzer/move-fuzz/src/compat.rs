@@ -0,0 +1,206 @@
+//! Taking the command lines of cargo-fuzz, so that its users, their scripts
+//! and the OSS-Fuzz build helpers can switch to `move-fuzz` as they are.
+//!
+//! Most of it comes with the fork: `move-fuzz` has the same commands, such as
+//! `cmin` and `tmin`, and build flags, and finds the project and its `fuzz`
+//! directory the same way, walking up from the current directory. Scripts
+//! only need `cargo fuzz` replaced with `move-fuzz`, a leading `fuzz` argument
+//! being dropped as Cargo passes it to subcommands. What is left is here:
+//!
+//! - the libFuzzer modes cargo-fuzz users reach through `run`, such as
+//!   `cargo fuzz run target crash -- -minimize_crash=1`, run the commands
+//!   doing the same here, see [`libfuzzer_mode`];
+//! - `-O` and `-D` given on the command line win over the other one set in
+//!   the defaults of the manifest, which cargo-fuzz does not have, rather than
+//!   both applying, see [`profile_default`].
+
+use crate::options::{Merge, Run, Tmin};
+use clap::builder::ArgPredicate;
+use clap::Arg;
+use std::path::PathBuf;
+use tracing::info;
+
+/// The `tmin` runs when `-runs` is not given, as in `tmin --runs`.
+const DEFAULT_TMIN_RUNS: u32 = 255;
+
+/// A command standing for a `run` in a libFuzzer mode.
+#[derive(Debug)]
+pub enum Mode {
+    /// `-minimize_crash=1` on a single input
+    MinimizeCrash(Tmin),
+    /// `-merge=1` of corpora into the first one
+    Merge(Merge),
+}
+
+/// The command doing what `run` does with the libFuzzer flags it passes
+/// through, `None` if it just fuzzes.
+pub fn libfuzzer_mode(run: &Run) -> Option<Mode> {
+    let target = run.target.clone()?;
+    let flag = |name: &str| {
+        run.args.iter().find_map(|arg| {
+            let (flag, value) = arg.strip_prefix('-')?.split_once('=')?;
+            (flag == name).then_some(value)
+        })
+    };
+    let without = |names: &[&str]| -> Vec<String> {
+        run.args
+            .iter()
+            .filter(|arg| {
+                let flag = arg.trim_start_matches('-');
+                let name = flag.split_once('=').map_or(flag, |(name, _)| name);
+                !names.contains(&name)
+            })
+            .cloned()
+            .collect()
+    };
+
+    if flag("minimize_crash") == Some("1") && run.corpus.len() == 1 {
+        info!(
+            "Minimizing {} with `tmin`, as `-minimize_crash=1` asks",
+            run.corpus[0]
+        );
+        return Some(Mode::MinimizeCrash(Tmin {
+            build: run.build.clone(),
+            fuzz_dir_wrapper: run.fuzz_dir_wrapper.clone(),
+            state_dirs: run.state_dirs.clone(),
+            target,
+            runs: flag("runs")
+                .and_then(|runs| runs.parse().ok())
+                .filter(|runs| *runs > 0)
+                .unwrap_or(DEFAULT_TMIN_RUNS),
+            hunt_timeouts: None,
+            test_case: Some(PathBuf::from(&run.corpus[0])),
+            module: None,
+            message_format: run.message_format,
+            args: without(&["minimize_crash", "runs"]),
+        }));
+    }
+    if flag("merge") == Some("1") && run.corpus.len() > 1 {
+        info!(
+            "Merging into {} with `merge`, as `-merge=1` asks",
+            run.corpus[0]
+        );
+        return Some(Mode::Merge(Merge {
+            build: run.build.clone(),
+            fuzz_dir_wrapper: run.fuzz_dir_wrapper.clone(),
+            state_dirs: run.state_dirs.clone(),
+            lock: run.lock.clone(),
+            target,
+            from: run.corpus[1..].iter().map(PathBuf::from).collect(),
+            into: Some(PathBuf::from(&run.corpus[0])),
+            control_file: flag("merge_control_file").map(PathBuf::from),
+            restart: false,
+            args: without(&["merge", "merge_control_file"]),
+        }));
+    }
+    None
+}
+
+/// Makes the `dev` or `release` option `arg` with the long name `long` yield
+/// to the other one given on the command line, over its default from the
+/// manifest.
+pub fn profile_default(long: &str, arg: Arg) -> Arg {
+    let other = match long {
+        "dev" => "release",
+        "release" => "dev",
+        _ => return arg,
+    };
+    arg.default_value_if(other, ArgPredicate::Equals("true".into()), Some("false"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn run(args: &[&str], defaults: &str) -> Run {
+        let defaults: toml::value::Table = toml::from_str(defaults).unwrap();
+        let cmd = config::layer(Run::command(), &defaults);
+        Run::from_arg_matches(&cmd.get_matches_from(args)).unwrap()
+    }
+
+    #[test]
+    fn minimize_crash_runs_tmin() {
+        let minimize = run(
+            &[
+                "run",
+                "deposit",
+                "crash-1",
+                "--",
+                "-minimize_crash=1",
+                "-runs=50",
+                "-timeout=5",
+            ],
+            "",
+        );
+        match libfuzzer_mode(&minimize) {
+            Some(Mode::MinimizeCrash(tmin)) => {
+                assert_eq!(tmin.target, "deposit");
+                assert_eq!(tmin.test_case, Some(PathBuf::from("crash-1")));
+                assert_eq!(tmin.runs, 50);
+                assert_eq!(tmin.args, ["-timeout=5"]);
+                assert_eq!(tmin.message_format, minimize.message_format);
+            }
+            mode => panic!("unexpected {:?}", mode),
+        }
+    }
+
+    #[test]
+    fn merge_runs_merge() {
+        let merge = run(
+            &[
+                "run",
+                "deposit",
+                "main",
+                "new",
+                "-O",
+                "--",
+                "-merge=1",
+                "-merge_control_file=m.txt",
+            ],
+            "",
+        );
+        match libfuzzer_mode(&merge) {
+            Some(Mode::Merge(merge)) => {
+                assert_eq!(merge.into, Some(PathBuf::from("main")));
+                assert_eq!(merge.from, [PathBuf::from("new")]);
+                assert_eq!(merge.control_file, Some(PathBuf::from("m.txt")));
+                assert!(merge.args.is_empty());
+                assert!(merge.build.cargo_options.release);
+            }
+            mode => panic!("unexpected {:?}", mode),
+        }
+    }
+
+    #[test]
+    fn other_flags_run_the_fuzzer() {
+        assert!(libfuzzer_mode(&run(&["run", "deposit", "--", "-runs=10"], "")).is_none());
+    }
+
+    #[test]
+    fn concurrent_runs_take_no_corpus() {
+        let concurrent = run(
+            &[
+                "run",
+                "--concurrent",
+                "deposit",
+                "withdraw",
+                "--",
+                "-merge=1",
+            ],
+            "",
+        );
+        assert_eq!(concurrent.concurrent, ["deposit", "withdraw"]);
+        assert!(libfuzzer_mode(&concurrent).is_none());
+    }
+
+    #[test]
+    fn profile_flags_override_the_other_default() {
+        let release = run(&["run", "-O", "deposit"], "dev = true\n");
+        assert!(release.build.cargo_options.release && !release.build.dev);
+        let dev = run(&["run", "-D", "deposit"], "release = true\n");
+        assert!(dev.build.dev && !dev.build.cargo_options.release);
+        assert!(run(&["run", "deposit"], "dev = true\n").build.dev);
+    }
+}
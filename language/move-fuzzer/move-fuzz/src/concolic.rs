@@ -0,0 +1,183 @@
+use crate::corpus::{self, CorpusEntry};
+use crate::provenance::{self, Origin, ProvenanceIndex};
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Corpus inputs replayed through the concolic binary in each round.
+const BATCH: usize = 16;
+
+/// Limit on a single replay, symbolic execution is orders of magnitude slower than fuzzing.
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Replays corpus inputs through a concolically instrumented build of a
+/// target and feeds the inputs solved from its branch constraints back into
+/// the corpus, where libFuzzer picks them up on its next reload.
+///
+/// The binary follows the SymCC conventions: it reads the input file given
+/// as its argument and writes one new input per solved constraint to
+/// `SYMCC_OUTPUT_DIR`. SymSan builds get the same paths through `TAINT_OPTIONS`.
+#[derive(Clone, Debug)]
+pub struct Concolic {
+    pub binary: PathBuf,
+    pub corpus: PathBuf,
+    /// Scratch directory for the solver output.
+    pub work_dir: PathBuf,
+    /// Where the inputs solved are recorded, if the corpus is that of the target.
+    pub provenance: Option<ProvenanceIndex>,
+}
+
+impl Concolic {
+    /// Solves the smallest corpus inputs not replayed yet, returning how many
+    /// new inputs were added to the corpus. Stops early once `stop` is set.
+    pub fn round(&self, seen: &mut HashSet<PathBuf>, stop: &AtomicBool) -> Result<usize> {
+        let entries = corpus::entries(&self.corpus)?;
+        let mut added = 0;
+        for entry in pick(&entries, seen, BATCH) {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            seen.insert(entry.path.clone());
+            let mut solved = Vec::new();
+            for input in self.solve(&entry.path)? {
+                if let Some(path) = corpus::insert(&self.corpus, &input)? {
+                    let from = provenance::entry_name(&entry.path);
+                    solved.push((provenance::entry_name(&path), Origin::Concolic { from }));
+                }
+            }
+            added += solved.len();
+            if let Some(index) = &self.provenance {
+                index.record(solved)?;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Replays `input` and returns the inputs the solver generated.
+    fn solve(&self, input: &Path) -> Result<Vec<Vec<u8>>> {
+        let output_dir = self.work_dir.join("out");
+        if output_dir.exists() {
+            fs::remove_dir_all(&output_dir)
+                .with_context(|| format!("failed to remove {}", output_dir.display()))?;
+        }
+        fs::create_dir_all(&output_dir)
+            .with_context(|| format!("failed to create directory {}", output_dir.display()))?;
+
+        let mut child = Command::new(&self.binary)
+            .arg(input)
+            .env("SYMCC_INPUT_FILE", input)
+            .env("SYMCC_OUTPUT_DIR", &output_dir)
+            .env(
+                "TAINT_OPTIONS",
+                format!(
+                    "taint_file={} output_dir={}",
+                    input.display(),
+                    output_dir.display()
+                ),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to spawn {}", self.binary.display()))?;
+        let start = Instant::now();
+        // Crashes and timeouts are the fuzzer's business, whatever was solved
+        // before they happened is still worth keeping.
+        while child.try_wait()?.is_none() {
+            if start.elapsed() > REPLAY_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let mut inputs = Vec::new();
+        for entry in corpus::entries(&output_dir)? {
+            inputs.push(
+                fs::read(&entry.path)
+                    .with_context(|| format!("failed to read {}", entry.path.display()))?,
+            );
+        }
+        Ok(inputs)
+    }
+
+    /// Runs a round every `interval` until `stop` is set.
+    pub fn spawn(self, interval: Duration, stop: Arc<AtomicBool>) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let mut seen = HashSet::new();
+            let mut last_round = Instant::now();
+            while !stop.load(Ordering::Relaxed) {
+                if last_round.elapsed() < interval {
+                    thread::sleep(Duration::from_millis(200));
+                    continue;
+                }
+                match self.round(&mut seen, &stop) {
+                    Ok(0) => {}
+                    Ok(added) => {
+                        tracing::info!("concolic: added {} solved inputs to the corpus", added)
+                    }
+                    Err(e) => tracing::warn!("concolic round failed: {:#}", e),
+                }
+                last_round = Instant::now();
+            }
+        })
+    }
+}
+
+/// Checks the concolic binary before the fuzzer starts, so a typo does not
+/// go unnoticed for a whole campaign.
+pub fn check_binary(binary: &Path) -> Result<()> {
+    if !binary.is_file() {
+        bail!("could not find the concolic binary {}", binary.display());
+    }
+    Ok(())
+}
+
+/// Up to `count` entries not in `seen`, smallest first: they are the
+/// cheapest to solve and what mutation grows the rest of the corpus from.
+fn pick<'a>(
+    entries: &'a [CorpusEntry],
+    seen: &HashSet<PathBuf>,
+    count: usize,
+) -> Vec<&'a CorpusEntry> {
+    let mut fresh: Vec<_> = entries
+        .iter()
+        .filter(|entry| !seen.contains(&entry.path))
+        .collect();
+    fresh.sort_by_key(|entry| (entry.size, &entry.path));
+    fresh.truncate(count);
+    fresh
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn picks_smallest_unseen_inputs() {
+        let entry = |name: &str, size| CorpusEntry {
+            path: PathBuf::from(name),
+            size,
+        };
+        let entries = [
+            entry("a", 30),
+            entry("b", 10),
+            entry("c", 20),
+            entry("d", 10),
+        ];
+        let mut seen = HashSet::new();
+        seen.insert(PathBuf::from("d"));
+        let picked: Vec<_> = pick(&entries, &seen, 2)
+            .iter()
+            .map(|entry| entry.path.to_str().unwrap())
+            .collect();
+        assert_eq!(picked, ["b", "c"]);
+    }
+}
@@ -0,0 +1,229 @@
+//! Option values from the environment and the fuzz manifest.
+//!
+//! Every option `--some-option` of every command can also be given as the
+//! `MOVE_FUZZ_OPT_SOME_OPTION` environment variable, or in the fuzz manifest:
+//!
+//! ```toml
+//! [package.metadata.move-fuzz.defaults]
+//! sanitizer = "none"
+//!
+//! [package.metadata.move-fuzz.defaults.run]
+//! jobs = 4
+//! ```
+//!
+//! The command line wins over the environment, which wins over the manifest,
+//! where the table of a command wins over the top-level values. The same
+//! tables can also go under `[fuzz.defaults]` in the `Move.toml` of the fuzz
+//! package, below those of the fuzz manifest. What the manifest pins for a
+//! single target, such as its sanitizer, wins over all of those but the
+//! command line and the environment, which [`is_explicit`] tells apart.
+
+use crate::compat;
+use crate::plugins;
+use crate::project::FuzzProject;
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Prefix of the environment variables setting options, apart from the
+/// `MOVE_FUZZ_*` variables move-fuzz hands the harnesses.
+pub const ENV_PREFIX: &str = "MOVE_FUZZ_OPT_";
+
+/// The ids of the options given on the command line or in the environment.
+static EXPLICIT: OnceLock<Vec<String>> = OnceLock::new();
+
+/// The option values under `[fuzz.defaults]` in `Move.toml` and
+/// `[package.metadata.move-fuzz.defaults]` of the fuzz project the command line
+/// `args` point to, empty outside of one.
+pub fn manifest_defaults(args: &[OsString]) -> Result<toml::value::Table> {
+    let args: Vec<_> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let fuzz_dir = plugins::fuzz_dir_arg(&args)
+        .or_else(|| env::var_os(env_var("fuzz-dir")).map(PathBuf::from));
+    let project = match FuzzProject::new(fuzz_dir) {
+        Ok(project) => project,
+        Err(_) => return Ok(Default::default()),
+    };
+    let mut defaults = project.fuzz_section()?.defaults.clone();
+    let manifest = project.manifest()?;
+    if let Some(cargo) = manifest
+        .get("package")
+        .and_then(|v| v.get("metadata"))
+        .and_then(|v| v.get("move-fuzz"))
+        .and_then(|v| v.get("defaults"))
+    {
+        let cargo = cargo
+            .as_table()
+            .context("`package.metadata.move-fuzz.defaults` must be a table")?;
+        for (key, value) in cargo {
+            match (defaults.get_mut(key), value) {
+                (Some(toml::Value::Table(own)), toml::Value::Table(value)) => {
+                    own.extend(value.clone())
+                }
+                _ => {
+                    defaults.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+    Ok(defaults)
+}
+
+/// Makes every option of `cmd` and its subcommands read its environment
+/// variable, then fall back to `defaults`.
+pub fn layer(cmd: clap::Command, defaults: &toml::value::Table) -> clap::Command {
+    let cmd = cmd.mut_args(|arg| {
+        let long = match arg.get_long() {
+            Some(long) if !matches!(long, "help" | "version") => long.to_owned(),
+            _ => return arg,
+        };
+        let arg = arg.env(env_var(&long));
+        let arg = match defaults.get(&long).and_then(default_values) {
+            Some(values) => arg.default_values(values),
+            None => arg,
+        };
+        compat::profile_default(&long, arg)
+    });
+    cmd.mut_subcommands(|sub| {
+        // The values of the command override the top-level ones.
+        let mut sub_defaults: toml::value::Table = defaults
+            .iter()
+            .filter(|(_, value)| !value.is_table())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Some(own) = defaults.get(sub.get_name()).and_then(|v| v.as_table()) {
+            sub_defaults.extend(own.clone());
+        }
+        layer(sub, &sub_defaults)
+    })
+}
+
+/// Records the options `matches` were given explicitly, for [`is_explicit`].
+pub fn record_explicit(matches: &clap::ArgMatches) {
+    let _ = EXPLICIT.set(explicit_ids(matches));
+}
+
+/// Whether the option with id `id`, e.g. `sanitizer`, was given on the
+/// command line or in the environment rather than falling back to the
+/// manifest or its default value.
+pub fn is_explicit(id: &str) -> bool {
+    EXPLICIT
+        .get()
+        .is_some_and(|explicit| explicit.iter().any(|e| e == id))
+}
+
+fn explicit_ids(matches: &clap::ArgMatches) -> Vec<String> {
+    let mut explicit = Vec::new();
+    let mut next = Some(matches);
+    while let Some(matches) = next {
+        explicit.extend(
+            matches
+                .ids()
+                .filter(|id| {
+                    matches!(
+                        matches.value_source(id.as_str()),
+                        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                    )
+                })
+                .map(|id| id.to_string()),
+        );
+        next = matches.subcommand().map(|(_, sub)| sub);
+    }
+    explicit
+}
+
+/// `MOVE_FUZZ_OPT_SOME_OPTION` for `some-option`.
+fn env_var(long: &str) -> String {
+    format!("{}{}", ENV_PREFIX, long.to_uppercase().replace('-', "_"))
+}
+
+fn default_values(value: &toml::Value) -> Option<Vec<String>> {
+    let scalar = |value: &toml::Value| match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+            Some(value.to_string())
+        }
+        _ => None,
+    };
+    match value {
+        toml::Value::Array(values) => values.iter().map(scalar).collect(),
+        value => scalar(value).map(|value| vec![value]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{Arg, ArgAction, Command};
+
+    fn cmd() -> Command {
+        Command::new("move-fuzz").subcommand(
+            Command::new("run")
+                .arg(Arg::new("jobs").long("jobs").default_value("1"))
+                .arg(Arg::new("sanitizer").long("sanitizer"))
+                .arg(Arg::new("dev").long("dev").action(ArgAction::SetTrue))
+                .arg(Arg::new("target")),
+        )
+    }
+
+    fn defaults() -> toml::value::Table {
+        toml::from_str("sanitizer = \"none\"\njobs = 2\ndev = true\n[run]\njobs = 3\n").unwrap()
+    }
+
+    fn run(args: &[&str]) -> clap::ArgMatches {
+        let matches = layer(cmd(), &defaults()).get_matches_from(args);
+        matches.subcommand_matches("run").unwrap().clone()
+    }
+
+    #[test]
+    fn command_table_wins_over_top_level() {
+        let run = run(&["move-fuzz", "run", "t"]);
+        assert_eq!(run.get_one::<String>("jobs").unwrap(), "3");
+        assert_eq!(run.get_one::<String>("sanitizer").unwrap(), "none");
+        assert!(run.get_flag("dev"));
+    }
+
+    #[test]
+    fn command_line_wins_over_manifest() {
+        let run = run(&["move-fuzz", "run", "--jobs", "5", "t"]);
+        assert_eq!(run.get_one::<String>("jobs").unwrap(), "5");
+    }
+
+    #[test]
+    fn options_read_their_env_var() {
+        let cmd = layer(cmd(), &defaults());
+        let run = cmd.find_subcommand("run").unwrap();
+        let jobs = run.get_arguments().find(|arg| arg.get_id() == "jobs");
+        assert_eq!(
+            jobs.unwrap().get_env(),
+            Some(OsString::from("MOVE_FUZZ_OPT_JOBS").as_os_str())
+        );
+        assert_eq!(env_var("fuzz-dir"), "MOVE_FUZZ_OPT_FUZZ_DIR");
+    }
+
+    #[test]
+    fn env_vars_leave_harness_vars_alone() {
+        for harness_var in [
+            "MOVE_FUZZ_SNAPSHOT",
+            "MOVE_FUZZ_STATE",
+            "MOVE_FUZZ_PARANOID",
+        ] {
+            assert!(!harness_var.starts_with(ENV_PREFIX));
+        }
+        assert_eq!(env_var("save-state"), "MOVE_FUZZ_OPT_SAVE_STATE");
+    }
+
+    #[test]
+    fn explicit_options_exclude_defaults() {
+        let matches =
+            layer(cmd(), &defaults()).get_matches_from(["move-fuzz", "run", "--dev", "t"]);
+        let mut explicit = explicit_ids(&matches);
+        explicit.sort();
+        assert_eq!(explicit, ["dev", "target"]);
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A single input stored in a corpus directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorpusEntry {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Returns the name libFuzzer gives to an input: the SHA-1 of its content.
+pub fn content_hash(data: &[u8]) -> String {
+    sha1_smol::Sha1::from(data).digest().to_string()
+}
+
+/// Lists the files of a corpus directory, sorted by name.
+pub fn entries(dir: &Path) -> Result<Vec<CorpusEntry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let entry = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            entries.push(CorpusEntry {
+                path: entry.path(),
+                size: metadata.len(),
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Stores `data` in `dir` under its content hash, returning `None` if an
+/// identical input is already present.
+pub fn insert(dir: &Path, data: &[u8]) -> Result<Option<PathBuf>> {
+    let path = dir.join(content_hash(data));
+    if path.exists() {
+        return Ok(None);
+    }
+    fs::write(&path, data).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// What migrating a corpus did with its entries.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Migration {
+    pub converted: usize,
+    /// Entries converted into an input already in the new corpus.
+    pub duplicates: usize,
+    /// Entries the conversion rejected.
+    pub failed: Vec<PathBuf>,
+}
+
+/// Converts the entries of the corpus in `from` into a corpus in `to` with
+/// `convert`, which returns `None` for the entries it cannot convert.
+pub fn migrate(
+    from: &Path,
+    to: &Path,
+    mut convert: impl FnMut(&[u8]) -> Result<Option<Vec<u8>>>,
+) -> Result<Migration> {
+    let mut migration = Migration::default();
+    for entry in entries(from)? {
+        let data = fs::read(&entry.path)
+            .with_context(|| format!("failed to read {}", entry.path.display()))?;
+        match convert(&data)? {
+            Some(converted) => match insert(to, &converted)? {
+                Some(_) => migration.converted += 1,
+                None => migration.duplicates += 1,
+            },
+            None => migration.failed.push(entry.path),
+        }
+    }
+    Ok(migration)
+}
+
+/// What deduplicating corpus directories did.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Dedupe {
+    /// Entries replaced with a hard link to an identical one.
+    pub linked: usize,
+    /// Bytes no longer stored twice.
+    pub reclaimed: u64,
+    /// Duplicates that could not be linked, e.g. across file systems.
+    pub failed: Vec<PathBuf>,
+}
+
+/// Replaces the entries of `dirs` identical to an earlier one with hard links
+/// to it, so that their content is stored once.
+pub fn dedupe(dirs: &[PathBuf]) -> Result<Dedupe> {
+    let mut dedupe = Dedupe::default();
+    let mut first: HashMap<(u64, String), PathBuf> = HashMap::new();
+    for dir in dirs {
+        for entry in entries(dir)? {
+            let data = fs::read(&entry.path)
+                .with_context(|| format!("failed to read {}", entry.path.display()))?;
+            let key = (entry.size, content_hash(&data));
+            let Some(original) = first.get(&key) else {
+                first.insert(key, entry.path);
+                continue;
+            };
+            if same_file(original, &entry.path)? {
+                continue;
+            }
+            match link(original, &entry.path) {
+                Ok(()) => {
+                    dedupe.linked += 1;
+                    dedupe.reclaimed += entry.size;
+                }
+                Err(_) => dedupe.failed.push(entry.path),
+            }
+        }
+    }
+    Ok(dedupe)
+}
+
+/// Replaces `path` with a hard link to `original`, atomically.
+fn link(original: &Path, path: &Path) -> Result<()> {
+    let name = path.file_name().expect("corpus entries are files");
+    let tmp = path.with_file_name(format!(".{}.link", name.to_string_lossy()));
+    fs::hard_link(original, &tmp)
+        .with_context(|| format!("failed to link {} to {}", tmp.display(), original.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+}
+
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (fs::metadata(a)?, fs::metadata(b)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+#[cfg(not(unix))]
+fn same_file(_: &Path, _: &Path) -> Result<bool> {
+    Ok(false)
+}
+
+/// Formats a byte count for humans.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Reads a byte count such as `1048576`, `512KiB`, `1.5GiB` or `2G`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    const UNITS: [(&str, u64); 9] = [
+        ("KiB", 1 << 10),
+        ("MiB", 1 << 20),
+        ("GiB", 1 << 30),
+        ("TiB", 1 << 40),
+        ("K", 1 << 10),
+        ("M", 1 << 20),
+        ("G", 1 << 30),
+        ("T", 1 << 40),
+        ("B", 1),
+    ];
+    let size = size.trim();
+    let (number, unit) = UNITS
+        .iter()
+        .find_map(|(suffix, unit)| Some((size.strip_suffix(suffix)?, *unit)))
+        .unwrap_or((size, 1));
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0).then_some((number * unit as f64) as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_dedups_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = insert(dir.path(), b"move").unwrap();
+        assert_eq!(
+            first,
+            Some(dir.path().join("379d6ce99a8a95a7096ab579d454784c82a994d8"))
+        );
+        assert_eq!(insert(dir.path(), b"move").unwrap(), None);
+        assert_eq!(entries(dir.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("1.5 KiB"), Some(1536));
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("lots"), None);
+    }
+
+    #[test]
+    fn migrate_converts_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("old"), dir.path().join("new"));
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        for input in [&b"ab"[..], b"abc", b"x"] {
+            insert(&from, input).unwrap();
+        }
+        // Keeps the first two bytes, rejecting shorter inputs.
+        let migration = migrate(&from, &to, |data| Ok(data.get(..2).map(<[u8]>::to_vec))).unwrap();
+        assert_eq!(migration.converted, 1);
+        assert_eq!(migration.duplicates, 1);
+        assert_eq!(migration.failed, [from.join(content_hash(b"x"))]);
+        assert_eq!(fs::read(to.join(content_hash(b"ab"))).unwrap(), b"ab");
+    }
+
+    #[test]
+    fn dedupe_links_identical_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (corpus, seeds) = (dir.path().join("corpus"), dir.path().join("seeds"));
+        fs::create_dir_all(&corpus).unwrap();
+        fs::create_dir_all(&seeds).unwrap();
+        insert(&corpus, b"deposit").unwrap();
+        insert(&corpus, b"withdraw").unwrap();
+        fs::write(seeds.join("deposit"), b"deposit").unwrap();
+        fs::write(seeds.join("copy"), b"deposit").unwrap();
+
+        let dirs = [corpus.clone(), seeds.clone()];
+        let dedupe = dedupe(&dirs).unwrap();
+        assert_eq!(dedupe.linked, 2);
+        assert_eq!(dedupe.reclaimed, 14);
+        assert!(dedupe.failed.is_empty());
+        assert_eq!(fs::read(seeds.join("copy")).unwrap(), b"deposit");
+        assert_eq!(entries(&seeds).unwrap().len(), 2);
+        #[cfg(unix)]
+        {
+            assert!(
+                same_file(&seeds.join("copy"), &corpus.join(content_hash(b"deposit"))).unwrap()
+            );
+            assert_eq!(super::dedupe(&dirs).unwrap(), Dedupe::default());
+        }
+    }
+}
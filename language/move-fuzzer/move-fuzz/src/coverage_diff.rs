@@ -0,0 +1,347 @@
+//! Comparing the Move coverage of two corpora, for `move-fuzz coverage diff`.
+//!
+//! Both sides are the CSV summaries `move coverage summary --csv` prints, a
+//! line per non-native function:
+//!
+//! ```text
+//! ModuleName,FunctionName,Covered,Uncovered
+//! 0x42::bank,deposit,12,14
+//! ```
+//!
+//! where the last column is the total number of instructions of the function
+//! despite its name.
+
+use crate::messages::Annotation;
+use crate::options::DiffFormat;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Where `coverage --coverage-map` writes the summary, next to the profile data.
+pub const MOVE_COVERAGE_SUMMARY_FILE: &str = "move-coverage.csv";
+
+/// Covered and total instructions of each function, by module.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    modules: BTreeMap<String, BTreeMap<String, (u64, u64)>>,
+}
+
+impl Summary {
+    pub fn parse_csv(csv: &str) -> Result<Self> {
+        let mut summary = Summary::default();
+        for (number, line) in csv.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with("ModuleName,") {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [module, function, covered, total] = fields[..] else {
+                bail!("line {} is not `module,function,covered,total`", number + 1);
+            };
+            let count = |field: &str| {
+                field.parse::<u64>().with_context(|| {
+                    format!("line {} has an invalid count {:?}", number + 1, field)
+                })
+            };
+            summary
+                .modules
+                .entry(module.to_owned())
+                .or_default()
+                .insert(function.to_owned(), (count(covered)?, count(total)?));
+        }
+        Ok(summary)
+    }
+
+    fn module_percent(&self, module: &str) -> Option<f64> {
+        let functions = self.modules.get(module)?;
+        let (covered, total) = functions
+            .values()
+            .fold((0, 0), |(c, t), (covered, total)| (c + covered, t + total));
+        Some(percent(covered, total))
+    }
+
+    fn total_percent(&self) -> f64 {
+        let (covered, total) = self
+            .modules
+            .values()
+            .flat_map(|functions| functions.values())
+            .fold((0, 0), |(c, t), (covered, total)| (c + covered, t + total));
+        percent(covered, total)
+    }
+}
+
+fn percent(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        100.0 * covered as f64 / total as f64
+    }
+}
+
+/// How the coverage of a module changed.
+#[derive(Debug, PartialEq)]
+pub struct ModuleDiff {
+    pub module: String,
+    /// `None` if the module is new
+    pub baseline: Option<f64>,
+    /// `None` if the module is gone
+    pub new: Option<f64>,
+    /// Functions not covered anymore, or added without being covered
+    pub newly_uncovered: Vec<String>,
+}
+
+impl ModuleDiff {
+    fn delta(&self) -> Option<f64> {
+        Some(self.new? - self.baseline?)
+    }
+
+    fn changed(&self) -> bool {
+        !self.newly_uncovered.is_empty()
+            || self.baseline.is_none() != self.new.is_none()
+            || self.delta().is_some_and(|delta| delta.abs() >= 0.005)
+    }
+
+    /// Whether the module lost coverage: it is gone, covers less, or has
+    /// functions left uncovered.
+    pub fn regressed(&self) -> bool {
+        !self.newly_uncovered.is_empty()
+            || (self.baseline.is_some() && self.new.is_none())
+            || self.delta().is_some_and(|delta| delta <= -0.005)
+    }
+}
+
+/// The modules of `baseline` and `new`, by name.
+pub fn diff(baseline: &Summary, new: &Summary) -> Vec<ModuleDiff> {
+    let mut modules: Vec<&String> = baseline.modules.keys().chain(new.modules.keys()).collect();
+    modules.sort();
+    modules.dedup();
+    modules
+        .into_iter()
+        .map(|module| {
+            let before = baseline.modules.get(module);
+            let newly_uncovered = new
+                .modules
+                .get(module)
+                .into_iter()
+                .flatten()
+                .filter(|(function, (covered, total))| {
+                    *covered == 0
+                        && *total > 0
+                        && match before.and_then(|functions| functions.get(*function)) {
+                            Some((covered, _)) => *covered > 0,
+                            None => true,
+                        }
+                })
+                .map(|(function, _)| function.clone())
+                .collect();
+            ModuleDiff {
+                module: module.clone(),
+                baseline: baseline.module_percent(module),
+                new: new.module_percent(module),
+                newly_uncovered,
+            }
+        })
+        .collect()
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    percent.map_or_else(|| "–".to_owned(), |percent| format!("{:.2}%", percent))
+}
+
+fn format_delta(delta: Option<f64>) -> String {
+    delta.map_or_else(|| "–".to_owned(), |delta| format!("{:+.2}", delta))
+}
+
+/// The modules whose coverage changed, with the totals of both sides.
+pub fn render(baseline: &Summary, new: &Summary, format: DiffFormat) -> String {
+    let (before, after) = (baseline.total_percent(), new.total_percent());
+    let modules = diff(baseline, new);
+    let changed: Vec<_> = modules.iter().filter(|module| module.changed()).collect();
+    let unchanged = modules.len() - changed.len();
+
+    let mut text = String::new();
+    match format {
+        DiffFormat::Human => {
+            let _ = writeln!(
+                text,
+                "Move coverage: {:.2}% -> {:.2}% ({:+.2})",
+                before,
+                after,
+                after - before
+            );
+            for module in &changed {
+                let _ = writeln!(
+                    text,
+                    "  {:<40} {:>8} -> {:>8} ({})",
+                    module.module,
+                    format_percent(module.baseline),
+                    format_percent(module.new),
+                    format_delta(module.delta())
+                );
+                for function in &module.newly_uncovered {
+                    let _ = writeln!(text, "    newly uncovered: {}", function);
+                }
+            }
+            if unchanged > 0 {
+                let _ = writeln!(text, "  unchanged modules: {}", unchanged);
+            }
+        }
+        DiffFormat::Markdown => {
+            let _ = writeln!(
+                text,
+                "### Move coverage: {:.2}% → {:.2}% ({:+.2})\n",
+                before,
+                after,
+                after - before
+            );
+            if changed.is_empty() {
+                let _ = writeln!(text, "No module changed coverage.");
+                return text;
+            }
+            let _ = writeln!(
+                text,
+                "| Module | Baseline | New | Δ | Newly uncovered functions |\n\
+                 |:--|--:|--:|--:|:--|"
+            );
+            for module in &changed {
+                let functions: Vec<_> = module
+                    .newly_uncovered
+                    .iter()
+                    .map(|function| format!("`{}`", function))
+                    .collect();
+                let _ = writeln!(
+                    text,
+                    "| `{}` | {} | {} | {} | {} |",
+                    module.module,
+                    format_percent(module.baseline),
+                    format_percent(module.new),
+                    format_delta(module.delta()),
+                    functions.join(", ")
+                );
+            }
+            if unchanged > 0 {
+                let _ = writeln!(text, "\nUnchanged modules: {}", unchanged);
+            }
+        }
+        DiffFormat::Github => {
+            for module in modules.iter().filter(|module| module.regressed()) {
+                let mut message = format!(
+                    "Move coverage of {}: {} -> {} ({})",
+                    module.module,
+                    format_percent(module.baseline),
+                    format_percent(module.new),
+                    format_delta(module.delta())
+                );
+                if !module.newly_uncovered.is_empty() {
+                    let _ = write!(
+                        message,
+                        "\nNewly uncovered: {}",
+                        module.newly_uncovered.join(", ")
+                    );
+                }
+                let annotation = Annotation {
+                    warning: true,
+                    title: format!("Coverage regression in {}", module.module),
+                    message,
+                    ..Annotation::default()
+                };
+                let _ = writeln!(text, "{}", annotation);
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn baseline() -> Summary {
+        Summary::parse_csv(
+            "ModuleName,FunctionName,Covered,Uncovered\n\
+             0x42::bank,deposit,10,10\n\
+             0x42::bank,withdraw,5,10\n\
+             0x42::vault,open,4,4\n\
+             0x42::old,gone,1,2\n",
+        )
+        .unwrap()
+    }
+
+    fn new() -> Summary {
+        Summary::parse_csv(
+            "0x42::bank,deposit,10,10\n\
+             0x42::bank,withdraw,0,10\n\
+             0x42::bank,audit,0,6\n\
+             0x42::vault,open,4,4\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reject_rows_missing_a_column() {
+        assert!(Summary::parse_csv("0x42::bank,deposit,10\n").is_err());
+    }
+
+    #[test]
+    fn diff_every_module_by_name() {
+        let modules = diff(&baseline(), &new());
+        let names: Vec<_> = modules.iter().map(|m| m.module.as_str()).collect();
+        assert_eq!(names, ["0x42::bank", "0x42::old", "0x42::vault"]);
+        assert_eq!(modules[0].newly_uncovered, ["audit", "withdraw"]);
+        assert_eq!(modules[1].new, None);
+    }
+
+    #[test]
+    fn markdown_headline_of_the_total_coverage() {
+        let markdown = render(&baseline(), &new(), DiffFormat::Markdown);
+        assert!(markdown.starts_with("### Move coverage: 76.92% → 46.67% (-30.26)\n"));
+    }
+
+    #[test]
+    fn markdown_row_per_changed_module() {
+        let markdown = render(&baseline(), &new(), DiffFormat::Markdown);
+        assert!(markdown
+            .contains("| `0x42::bank` | 75.00% | 38.46% | -36.54 | `audit`, `withdraw` |\n"));
+        assert!(markdown.contains("| `0x42::old` | 50.00% | – | – |  |\n"));
+        assert!(!markdown.contains("vault"));
+    }
+
+    #[test]
+    fn markdown_count_of_unchanged_modules() {
+        let markdown = render(&baseline(), &new(), DiffFormat::Markdown);
+        assert!(markdown.ends_with("\nUnchanged modules: 1\n"));
+    }
+
+    #[test]
+    fn github_warnings_for_regressed_modules() {
+        let baseline = Summary::parse_csv(
+            "0x42::bank,deposit,10,10\n\
+             0x42::bank,withdraw,5,10\n\
+             0x42::vault,open,2,4\n\
+             0x42::old,gone,1,2\n",
+        )
+        .unwrap();
+        let new = Summary::parse_csv(
+            "0x42::bank,deposit,10,10\n\
+             0x42::bank,withdraw,0,10\n\
+             0x42::vault,open,4,4\n\
+             0x42::fresh,open,1,1\n",
+        )
+        .unwrap();
+
+        let regressed: Vec<_> = diff(&baseline, &new)
+            .into_iter()
+            .filter(ModuleDiff::regressed)
+            .map(|module| module.module)
+            .collect();
+        assert_eq!(regressed, ["0x42::bank", "0x42::old"]);
+
+        assert_eq!(
+            render(&baseline, &new, DiffFormat::Github),
+            "::warning title=Coverage regression in 0x42%3A%3Abank::\
+             Move coverage of 0x42::bank: 75.00%25 -> 50.00%25 (-25.00)%0A\
+             Newly uncovered: withdraw\n\
+             ::warning title=Coverage regression in 0x42%3A%3Aold::\
+             Move coverage of 0x42::old: 50.00%25 -> – (–)\n"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! The `report.yaml` `triage` writes for each crash bucket, in
+//! `triage/<target>/<bucket>/` of the fuzz directory.
+//!
+//! A report holds everything an issue about the crash needs without access to
+//! the fuzzing machine: the input, raw and decoded by the `Debug` formatting of
+//! the target, the symbolized backtrace with its Move frames, the summary line
+//! of the sanitizer, how the target was built and the command reproducing the
+//! crash.
+
+use crate::symbolize;
+use crate::triage::{Bucket, Status};
+use crate::utils::command_output;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The report of a bucket, in its directory.
+pub const CRASH_REPORT_FILE: &str = "report.yaml";
+
+/// The directory of the reports, in the fuzz directory.
+pub const CRASH_REPORTS_DIR: &str = "triage";
+
+#[derive(Debug, Serialize)]
+pub struct CrashReport {
+    pub target: String,
+    pub signature: String,
+    pub status: Status,
+    /// Seconds since the Unix epoch.
+    pub first_seen: u64,
+    /// Seconds since the Unix epoch.
+    pub last_seen: u64,
+    /// Runs crashing out of those `triage --stability` made, e.g. `3/5`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stability: Option<String>,
+    pub artifacts: Vec<PathBuf>,
+    pub input: Input,
+    /// The `SUMMARY:` line of the sanitizer, or the panic message
+    pub summary: Option<String>,
+    /// The frames of the first backtrace, demangled, innermost first
+    pub backtrace: Vec<String>,
+    /// The Move frames the harness described, innermost first
+    pub move_frames: Vec<String>,
+    pub build: BuildInfo,
+    /// The command reproducing the crash, from the project directory
+    pub reproduce: String,
+    /// The whole crash report, symbolized
+    pub crash_report: String,
+}
+
+/// The input a report was made from, the first artifact of its bucket.
+#[derive(Debug, Serialize)]
+pub struct Input {
+    pub path: PathBuf,
+    pub size: u64,
+    pub base64: String,
+    /// The `Debug` formatting of the input, when the target could decode it
+    pub decoded: Option<String>,
+}
+
+/// How the target reproducing the crash was built.
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    /// The build options, as `move-fuzz` flags
+    pub options: String,
+    pub git_commit: Option<String>,
+    pub rustc_version: Option<String>,
+    pub move_version: Option<String>,
+    /// The build and libFuzzer flags of the run that found the input, from its
+    /// metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub found_with: Option<String>,
+}
+
+impl BuildInfo {
+    /// The build `options` with the commit checked out and the versions of
+    /// the toolchains found on the path, as far as they can be told.
+    pub fn current(options: String) -> Self {
+        let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+        BuildInfo {
+            options,
+            git_commit: command_output(Command::new("git").args(["rev-parse", "HEAD"])),
+            rustc_version: command_output(Command::new(rustc).arg("--version")),
+            move_version: command_output(Command::new("move").arg("--version")),
+            found_with: None,
+        }
+    }
+}
+
+impl CrashReport {
+    /// The report of `bucket` from the symbolized crash `report` of its input,
+    /// but for the command reproducing it.
+    pub fn new(bucket: &Bucket, input: Input, report: String, build: BuildInfo) -> Self {
+        CrashReport {
+            target: bucket.target.clone(),
+            signature: bucket.signature.clone(),
+            status: bucket.status,
+            first_seen: bucket.first_seen,
+            last_seen: bucket.last_seen,
+            stability: bucket.stability.map(|s| s.to_string()),
+            artifacts: bucket.artifacts.clone(),
+            input,
+            summary: summary(&report),
+            backtrace: backtrace(&report),
+            move_frames: symbolize::move_frames(&report)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            build,
+            reproduce: String::new(),
+            crash_report: report,
+        }
+    }
+
+    /// Writes the report to `dir`, returning its path.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        let path = dir.join(CRASH_REPORT_FILE);
+        let yaml = serde_yaml::to_string(self).context("could not encode the crash report")?;
+        fs::write(&path, yaml).with_context(|| format!("failed to write to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// The directory of the bucket with `signature`, named after its hash so that
+/// it stays the same across triages.
+pub fn bucket_dir(signature: &str) -> String {
+    let hash = sha1_smol::Sha1::from(signature).digest().to_string();
+    hash[..12].to_owned()
+}
+
+/// The `SUMMARY:` line of a sanitizer report, the first line of its error
+/// otherwise, or the message of a panic.
+fn summary(report: &str) -> Option<String> {
+    let lines: Vec<&str> = report.lines().collect();
+    if let Some(summary) = lines.iter().find_map(|l| l.split_once("SUMMARY: ")) {
+        return Some(summary.1.trim().to_owned());
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((_, error)) = line.split_once("ERROR: ") {
+            return Some(error.trim().to_owned());
+        }
+        if line.contains("panicked at ") {
+            return Some(panic_message(&lines, i));
+        }
+    }
+    None
+}
+
+/// The panic starting at the `i`th of `lines`, with its message.
+pub(crate) fn panic_message(lines: &[&str], i: usize) -> String {
+    // The message follows the location since Rust 1.73.
+    match lines.get(i + 1) {
+        Some(next) if lines[i].ends_with(':') => format!("{} {}", lines[i].trim(), next.trim()),
+        _ => lines[i].trim().to_owned(),
+    }
+}
+
+/// The frames of the first backtrace of `report`.
+fn backtrace(report: &str) -> Vec<String> {
+    report
+        .lines()
+        .skip_while(|line| symbolize::backtrace_frame(line).is_none())
+        .take_while(|line| symbolize::backtrace_frame(line).is_some())
+        .map(|line| line.trim().to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ASAN_OUTPUT: &str = "==1==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602\n\
+                               READ of size 1 at 0x602 thread T0\n    \
+                               #0 0x55 in bank::deposit src/bank.rs:3\n    \
+                               #1 0x56 in rust_fuzzer_test_input src/lib.rs:9\n\
+                               \n\
+                               SUMMARY: AddressSanitizer: heap-buffer-overflow src/bank.rs:3 in bank::deposit\n\
+                               Move execution failed with ARITHMETIC_ERROR\n    \
+                               in 0x1::bank::deposit at code offset 7\n";
+
+    fn report() -> CrashReport {
+        let bucket = Bucket {
+            target: "deposit".to_owned(),
+            signature: "AddressSanitizer: heap-buffer-overflow in bank::deposit".to_owned(),
+            status: Status::New,
+            artifacts: vec![PathBuf::from("fuzz/artifacts/deposit/crash-1")],
+            first_seen: 10,
+            last_seen: 20,
+            stability: None,
+            legacy_signature: false,
+        };
+        let input = Input {
+            path: PathBuf::from("fuzz/artifacts/deposit/crash-1"),
+            size: 3,
+            base64: "AQID".to_owned(),
+            decoded: Some("Deposit {\n    amount: 1,\n}".to_owned()),
+        };
+        let build = BuildInfo {
+            options: "-D".to_owned(),
+            git_commit: Some("abc".to_owned()),
+            rustc_version: None,
+            move_version: None,
+            found_with: None,
+        };
+        let mut report = CrashReport::new(&bucket, input, symbolize::symbolize(ASAN_OUTPUT), build);
+        report.reproduce = "move-fuzz repro -D deposit fuzz/artifacts/deposit/crash-1".to_owned();
+        report
+    }
+
+    #[test]
+    fn report_of_a_sanitizer_crash() {
+        let report = report();
+        assert_eq!(
+            report.summary.as_deref(),
+            Some("AddressSanitizer: heap-buffer-overflow src/bank.rs:3 in bank::deposit")
+        );
+        assert_eq!(report.move_frames, ["0x1::bank::deposit at code offset 7"]);
+    }
+
+    #[test]
+    fn yaml_report_of_a_bucket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = report().write(dir.path()).unwrap();
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(yaml["status"].as_str(), Some("new"));
+        assert_eq!(
+            yaml["input"]["decoded"].as_str(),
+            Some("Deposit {\n    amount: 1,\n}")
+        );
+        assert_eq!(yaml["build"]["git_commit"].as_str(), Some("abc"));
+        assert!(yaml["build"].get("found_with").is_none());
+        let frames = yaml["backtrace"].as_sequence().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            frames[0].as_str(),
+            Some("#0 <move> in 0x1::bank::deposit at code offset 7")
+        );
+        assert!(frames[1]
+            .as_str()
+            .unwrap()
+            .starts_with("#1 0x55 in bank::deposit"));
+    }
+
+    #[test]
+    fn summary_of_a_panic() {
+        assert_eq!(
+            summary("thread 'main' panicked at src/lib.rs:4:5:\nattempt to add with overflow\n")
+                .as_deref(),
+            Some("thread 'main' panicked at src/lib.rs:4:5: attempt to add with overflow")
+        );
+        assert_eq!(
+            summary("thread 'main' panicked at 'overflow', src/lib.rs:4:5\n").as_deref(),
+            Some("thread 'main' panicked at 'overflow', src/lib.rs:4:5")
+        );
+    }
+
+    #[test]
+    fn bucket_dirs_are_stable() {
+        let dir = bucket_dir("panic at src/lib.rs:4:5");
+        assert_eq!(dir.len(), 12);
+        assert_eq!(dir, bucket_dir("panic at src/lib.rs:4:5"));
+        assert_ne!(dir, bucket_dir("panic at src/lib.rs:5:5"));
+    }
+}
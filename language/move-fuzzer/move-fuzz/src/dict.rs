@@ -0,0 +1,299 @@
+//! libFuzzer dictionaries of the names, struct tags and constants of compiled
+//! modules, for `move-fuzz gen-dict`.
+//!
+//! The tokens are encoded the way the inputs of the targets hold them: harness
+//! arguments decode integers little-endian like BCS does, while compiled
+//! modules also hold identifiers and constants behind a ULEB128 length.
+
+use crate::options::DictKind;
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{
+    Bytecode, CompiledModule, Constant, SignatureToken, StructFieldInformation,
+};
+use move_core_types::account_address::AccountAddress;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// The magic number compiled modules and scripts start with.
+const BYTECODE_MAGIC: [u8; 4] = [0xa1, 0x1c, 0xeb, 0x0b];
+
+pub struct Dictionary {
+    kind: DictKind,
+    /// Named tokens, in the order they were first found
+    entries: Vec<(String, Vec<u8>)>,
+    seen: HashSet<Vec<u8>>,
+}
+
+impl Dictionary {
+    pub fn new(kind: DictKind) -> Self {
+        let mut dictionary = Dictionary {
+            kind,
+            entries: Vec::new(),
+            seen: HashSet::new(),
+        };
+        if kind == DictKind::Bytecode {
+            dictionary.add("magic".to_owned(), BYTECODE_MAGIC.to_vec());
+        }
+        dictionary
+    }
+
+    pub fn token_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn add(&mut self, name: String, token: Vec<u8>) {
+        if !token.is_empty() && self.seen.insert(token.clone()) {
+            self.entries.push((name, token));
+        }
+    }
+
+    /// An identifier, only found in bytecode.
+    fn add_identifier(&mut self, kind: &str, name: &str) {
+        if self.kind == DictKind::Bytecode {
+            let mut token = uleb128(name.len());
+            token.extend_from_slice(name.as_bytes());
+            self.add(format!("{}_{}", kind, name), token);
+        }
+    }
+
+    fn add_address(&mut self, name: &str, address: &AccountAddress) {
+        self.add(format!("address_{}", name), address.to_vec());
+    }
+
+    /// Adds the names, addresses, struct tags and constants of the compiled `module`.
+    pub fn add_module(&mut self, module: &CompiledModule) {
+        let name = module.name().as_str();
+        for handle in module.module_handles() {
+            let id = module.module_id_for_handle(handle);
+            self.add_address(id.name().as_str(), id.address());
+            self.add_identifier("module", id.name().as_str());
+        }
+        for def in module.struct_defs() {
+            let handle = module.struct_handle_at(def.struct_handle);
+            let struct_name = module.identifier_at(handle.name).as_str();
+            self.add_identifier("struct", struct_name);
+            if let StructFieldInformation::Declared(fields) = &def.field_information {
+                for field in fields {
+                    self.add_identifier("field", module.identifier_at(field.name).as_str());
+                }
+            }
+            // The BCS of its `StructTag`, as type arguments are passed, for
+            // the structs that take none.
+            if self.kind == DictKind::Arguments && handle.type_parameters.is_empty() {
+                let mut tag = module.address().to_vec();
+                for ident in [name, struct_name] {
+                    tag.extend(uleb128(ident.len()));
+                    tag.extend_from_slice(ident.as_bytes());
+                }
+                tag.extend(uleb128(0));
+                self.add(format!("tag_{}_{}", name, struct_name), tag);
+            }
+        }
+        for def in module.function_defs() {
+            let handle = module.function_handle_at(def.function);
+            self.add_identifier("fn", module.identifier_at(handle.name).as_str());
+        }
+
+        // Error codes are the constants loaded right before an `Abort`.
+        let mut errors = HashSet::new();
+        let mut loads = Vec::new();
+        for code in module
+            .function_defs()
+            .iter()
+            .filter_map(|def| def.code.as_ref())
+        {
+            for (i, instruction) in code.code.iter().enumerate() {
+                let aborts = code.code.get(i + 1) == Some(&Bytecode::Abort);
+                match instruction {
+                    Bytecode::LdConst(index) if aborts => {
+                        errors.insert(index.0);
+                    }
+                    _ => loads.push((instruction, aborts)),
+                }
+            }
+        }
+        for (index, constant) in module.constant_pool().iter().enumerate() {
+            let kind = if errors.contains(&(index as u16)) {
+                "error"
+            } else {
+                "const"
+            };
+            self.add_constant(format!("{}_{}_{}", kind, name, index), constant);
+        }
+        for (instruction, aborts) in loads {
+            self.add_load(name, instruction, aborts);
+        }
+    }
+
+    fn add_constant(&mut self, name: String, constant: &Constant) {
+        let data = constant.data.clone();
+        match self.kind {
+            DictKind::Arguments => {
+                // The bytes of a `vector<u8>` constant, such as a message,
+                // without their length.
+                if constant.type_ == SignatureToken::Vector(Box::new(SignatureToken::U8)) {
+                    let len = data.iter().position(|b| b & 0x80 == 0).map_or(0, |i| i + 1);
+                    self.add(format!("{}_bytes", name), data[len..].to_vec());
+                }
+                self.add(name, data);
+            }
+            DictKind::Bytecode => {
+                let mut token = uleb128(data.len());
+                token.extend(data);
+                self.add(name, token);
+            }
+        }
+    }
+
+    /// Adds the integer an instruction such as `LdU64(42)` loads, named as an
+    /// error code if `aborts` with it.
+    fn add_load(&mut self, module: &str, instruction: &Bytecode, aborts: bool) {
+        let (bits, value, token) = match instruction {
+            Bytecode::LdU16(v) => (16, v.to_string(), v.to_le_bytes().to_vec()),
+            Bytecode::LdU32(v) => (32, v.to_string(), v.to_le_bytes().to_vec()),
+            Bytecode::LdU64(v) => (64, v.to_string(), v.to_le_bytes().to_vec()),
+            Bytecode::LdU128(v) => (128, v.to_string(), v.to_le_bytes().to_vec()),
+            Bytecode::LdU256(v) => (256, v.to_string(), v.to_le_bytes().to_vec()),
+            _ => return,
+        };
+        if aborts {
+            self.add(format!("error_{}_{}", module, value), token);
+        } else if token[1..].iter().any(|&b| b != 0) {
+            // Single bytes are left out, libFuzzer finds them on its own.
+            self.add(format!("u{}_{}", bits, value), token);
+        }
+    }
+
+    /// The dictionary in libFuzzer's format, headed by `comment`.
+    pub fn render(&self, comment: &str) -> String {
+        let mut text = String::new();
+        for line in comment.lines() {
+            let _ = writeln!(text, "# {}", line);
+        }
+        for (name, token) in &self.entries {
+            let _ = writeln!(text, "{}=\"{}\"", name, escape(token));
+        }
+        text
+    }
+}
+
+fn uleb128(mut value: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn escape(token: &[u8]) -> String {
+    token
+        .iter()
+        .map(|&b| match b {
+            b'"' | b'\\' => format!("\\{}", b as char),
+            0x20..=0x7e => (b as char).to_string(),
+            _ => format!("\\x{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use move_binary_format::file_format::ConstantPoolIndex;
+    use move_ir_compiler::Compiler;
+
+    /// The module `bank`, calling into `0x1::signer` and aborting with the
+    /// code `9` of its constants from `check`.
+    fn bank() -> CompiledModule {
+        let signer = Compiler::new(vec![])
+            .into_compiled_module(
+                "module 0x1.signer { public address_of(s: &signer): address { label b0: abort(0); } }",
+            )
+            .unwrap();
+        let mut bank = Compiler::new(vec![&signer])
+            .into_compiled_module(
+                "module 0x42.bank {
+                    import 0x1.signer;
+                    struct Account has key { balance: u64 }
+                    struct Pool<phantom T> has store { reserve: u64 }
+
+                    public entry deposit(s: &signer, amount: u64) {
+                    label b0:
+                        _ = signer.address_of(move(s));
+                        _ = 1000;
+                        _ = 3;
+                        _ = h\"6f6b\";
+                        abort(7);
+                    }
+                    check(a: u64) { label b0: return; }
+                }",
+            )
+            .unwrap();
+        bank.constant_pool.push(Constant {
+            type_: SignatureToken::U64,
+            data: 9u64.to_le_bytes().to_vec(),
+        });
+        let index = ConstantPoolIndex(bank.constant_pool.len() as u16 - 1);
+        bank.function_defs[1].code.as_mut().unwrap().code =
+            vec![Bytecode::LdConst(index), Bytecode::Abort];
+        bank
+    }
+
+    #[test]
+    fn argument_tokens() {
+        let mut dictionary = Dictionary::new(DictKind::Arguments);
+        dictionary.add_module(&bank());
+        let names: Vec<_> = dictionary.entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "address_bank",
+                "address_signer",
+                "tag_bank_Account",
+                "const_bank_0_bytes",
+                "const_bank_0",
+                "error_bank_1",
+                "u64_1000",
+                "error_bank_7",
+            ]
+        );
+        let dict = dictionary.render("bank");
+        assert!(dict.starts_with("# bank\n"));
+        assert!(dict.contains("u64_1000=\"\\xE8\\x03\\x00\\x00\\x00\\x00\\x00\\x00\"\n"));
+        assert!(dict.contains("const_bank_0_bytes=\"ok\"\n"));
+        assert!(dict.contains(&format!(
+            "address_bank=\"{}B\"\n",
+            "\\x00".repeat(AccountAddress::LENGTH - 1)
+        )));
+        assert!(dict.contains(&format!(
+            "tag_bank_Account=\"{}B\\x04bank\\x07Account\\x00\"\n",
+            "\\x00".repeat(AccountAddress::LENGTH - 1)
+        )));
+    }
+
+    #[test]
+    fn bytecode_tokens() {
+        let mut dictionary = Dictionary::new(DictKind::Bytecode);
+        dictionary.add_module(&bank());
+        let dict = dictionary.render("bank");
+        for entry in [
+            "magic=\"\\xA1\\x1C\\xEB\\x0B\"",
+            "module_bank=\"\\x04bank\"",
+            "module_signer=\"\\x06signer\"",
+            "struct_Account=\"\\x07Account\"",
+            "field_balance=\"\\x07balance\"",
+            "fn_deposit=\"\\x07deposit\"",
+            "fn_check=\"\\x05check\"",
+            "const_bank_0=\"\\x03\\x02ok\"",
+        ] {
+            assert!(dict.contains(entry), "missing {}", entry);
+        }
+        assert!(!dict.contains("tag_"));
+        assert!(!dict.contains("fn_address_of"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Text encodings of inputs, for `fmt --encode` and `fmt --decode`.
+//!
+//! Decoding is lenient about what pasting may add: whitespace and line breaks
+//! anywhere, a `0x` prefix on hex, missing base64 padding, and the `&`,
+//! `vec!`, type suffixes and comments of a Rust array.
+
+use crate::options::InputEncoding;
+use anyhow::{anyhow, bail, Context, Result};
+
+/// Bytes per line of a Rust array.
+const ARRAY_LINE: usize = 16;
+
+pub fn encode(encoding: InputEncoding, data: &[u8]) -> String {
+    match encoding {
+        InputEncoding::Hex => data.iter().map(|b| format!("{:02x}", b)).collect(),
+        InputEncoding::Base64 => base64::encode(data),
+        InputEncoding::RustArray => {
+            let lines: Vec<_> = data
+                .chunks(ARRAY_LINE)
+                .map(|line| {
+                    let bytes: Vec<_> = line.iter().map(|b| format!("0x{:02x}", b)).collect();
+                    format!("    {},", bytes.join(", "))
+                })
+                .collect();
+            if lines.is_empty() {
+                "[]".to_owned()
+            } else {
+                format!("[\n{}\n]", lines.join("\n"))
+            }
+        }
+    }
+}
+
+pub fn decode(encoding: InputEncoding, text: &str) -> Result<Vec<u8>> {
+    match encoding {
+        InputEncoding::Hex => {
+            let hex: String = text.split_whitespace().collect();
+            let hex = hex.strip_prefix("0x").unwrap_or(&hex);
+            if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+                bail!("invalid hex digit `{}`", c);
+            }
+            if hex.len() % 2 == 1 {
+                bail!("odd number of hex digits");
+            }
+            Ok(hex
+                .as_bytes()
+                .chunks(2)
+                .map(|pair| (hex_digit(pair[0]) << 4) | hex_digit(pair[1]))
+                .collect())
+        }
+        InputEncoding::Base64 => decode_base64(text),
+        InputEncoding::RustArray => decode_array(text),
+    }
+}
+
+/// The value of an ASCII hex digit.
+fn hex_digit(digit: u8) -> u8 {
+    (digit as char).to_digit(16).unwrap_or_default() as u8
+}
+
+/// Decodes standard or URL-safe base64, padded or not.
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    let config = base64::STANDARD_NO_PAD.decode_allow_trailing_bits(true);
+    base64::decode_config(&text, config).map_err(|e| anyhow!("invalid base64: {}", e))
+}
+
+/// Decodes the bytes of a Rust array or vector literal, in decimal or hex.
+fn decode_array(text: &str) -> Result<Vec<u8>> {
+    let code: String = text
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let code = code
+        .trim()
+        .trim_start_matches('&')
+        .trim_start_matches("vec!");
+    let items = code
+        .trim()
+        .strip_prefix('[')
+        .and_then(|code| code.trim_end().trim_end_matches(';').strip_suffix(']'))
+        .context("expected a Rust array such as `[0x01, 0x02]`")?;
+    items
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let number = item.trim_end_matches("u8").trim_end_matches('_');
+            let byte = match number.strip_prefix("0x") {
+                Some(hex) => u8::from_str_radix(hex, 16),
+                None => number.parse(),
+            };
+            byte.map_err(|_| anyhow!("`{}` is not a byte", item))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encodings_round_trip() {
+        let inputs: [&[u8]; 4] = [
+            b"",
+            b"f",
+            b"fo",
+            &[
+                0, 0xff, 0x10, 0x7f, 3, 200, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11,
+            ],
+        ];
+        for encoding in [
+            InputEncoding::Hex,
+            InputEncoding::Base64,
+            InputEncoding::RustArray,
+        ] {
+            for input in inputs {
+                assert_eq!(decode(encoding, &encode(encoding, input)).unwrap(), input);
+            }
+        }
+        assert_eq!(encode(InputEncoding::Base64, b"move"), "bW92ZQ==");
+        assert_eq!(encode(InputEncoding::Hex, b"move"), "6d6f7665");
+        assert_eq!(
+            encode(InputEncoding::RustArray, b"move"),
+            "[\n    0x6d, 0x6f, 0x76, 0x65,\n]"
+        );
+    }
+
+    #[test]
+    fn lenient_decoding() {
+        assert_eq!(
+            decode(InputEncoding::Hex, "0x6d6f\n7665\n").unwrap(),
+            b"move"
+        );
+        assert_eq!(decode(InputEncoding::Base64, "bW92\nZQ").unwrap(), b"move");
+        assert_eq!(decode(InputEncoding::Base64, "-_8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(
+            decode(
+                InputEncoding::RustArray,
+                "&[\n    109, 0x6f, // m o\n    118u8, 0x65_u8,\n];"
+            )
+            .unwrap(),
+            b"move"
+        );
+        assert_eq!(decode(InputEncoding::RustArray, "vec![]").unwrap(), b"");
+        assert!(decode(InputEncoding::Hex, "abc").is_err());
+        assert!(decode(InputEncoding::Hex, "aéb").is_err());
+        assert!(decode(InputEncoding::Hex, "zz").is_err());
+        assert!(decode(InputEncoding::Base64, "b").is_err());
+        assert!(decode(InputEncoding::Base64, "bW9é").is_err());
+        assert!(decode(InputEncoding::RustArray, "[256]").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::error;
+use crate::logging::{self, Source};
+use crate::metadata;
+use crate::options::BuildOptions;
+use crate::project::FuzzProject;
+use crate::schedule;
+use crate::shutdown;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::SystemTime;
+
+/// Figures read from the status lines of a fuzzing engine, the latest ones win.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Stats {
+    pub execs: Option<u64>,
+    pub coverage: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_entries: Option<u64>,
+}
+
+impl Stats {
+    fn update(&mut self, other: Stats) {
+        self.execs = other.execs.or(self.execs);
+        self.coverage = other.coverage.or(self.coverage);
+        self.features = other.features.or(self.features);
+        self.corpus_entries = other.corpus_entries.or(self.corpus_entries);
+    }
+}
+
+/// What a run of the engine left behind.
+#[derive(Debug)]
+pub struct Outcome {
+    pub status: ExitStatus,
+    pub stats: Stats,
+    /// The last lines of output, for the artifact metadata
+    pub tail: VecDeque<String>,
+    /// The artifacts written during the run
+    pub artifacts: HashSet<PathBuf>,
+}
+
+/// Runs fuzz targets: fuzzing, minimizing test cases and merging corpora all
+/// go through it.
+pub trait FuzzEngine {
+    /// The command running `target`, to which the engine flags are added.
+    fn command(&self, target: &str) -> Result<Command>;
+
+    /// The stats in a line of output, all `None` if there are none.
+    fn parse_stats(&self, line: &str) -> Stats;
+
+    /// The artifacts of `target` written since `since`.
+    fn artifacts_since(&self, target: &str, since: SystemTime) -> Result<HashSet<PathBuf>>;
+
+    /// Adds the inputs of the `from` corpora that add coverage to `into`,
+    /// telling `runs` how many inputs were run so far.
+    fn merge(
+        &self,
+        target: &str,
+        into: &Path,
+        from: &[PathBuf],
+        args: &[String],
+        runs: &mut dyn FnMut(u64),
+    ) -> Result<()>;
+
+    /// Runs `cmd`, from [`FuzzEngine::command`], to completion, logging its
+    /// stderr while parsing the stats out of it.
+    fn run(&self, target: &str, cmd: &mut Command) -> Result<Outcome> {
+        self.run_with(target, cmd, &mut |_| {})
+    }
+
+    /// Runs `cmd` like [`FuzzEngine::run`], passing the lines of its stderr to
+    /// `on_line` too.
+    fn run_with(
+        &self,
+        target: &str,
+        cmd: &mut Command,
+        on_line: &mut dyn FnMut(&str),
+    ) -> Result<Outcome> {
+        cmd.stderr(Stdio::piped());
+        // Artifacts are told apart from older ones by their modification time.
+        let started = SystemTime::now();
+        shutdown::prepare(cmd);
+        let mut child = cmd.spawn().map_err(|e| error::spawn_failed(cmd, e))?;
+        let _tracked = shutdown::track(target, &child);
+        let mut stats = Stats::default();
+        let mut tail = VecDeque::new();
+        if let Some(stderr) = child.stderr.take() {
+            for line in BufReader::new(stderr).lines() {
+                let line = line?;
+                logging::output(Source::LibFuzzer, &line);
+                on_line(&line);
+                stats.update(self.parse_stats(&line));
+                if tail.len() == metadata::OUTPUT_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        }
+        let status = child
+            .wait()
+            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+        Ok(Outcome {
+            status,
+            stats,
+            tail,
+            artifacts: self.artifacts_since(target, started)?,
+        })
+    }
+}
+
+/// Appends `stats`, read at `time` seconds since the Unix epoch, to a stream of
+/// JSON lines, unless there are none.
+pub(crate) fn write_stats(out: &mut impl Write, time: u64, stats: Stats) -> Result<()> {
+    if stats == Stats::default() {
+        return Ok(());
+    }
+    #[derive(Serialize)]
+    struct Line {
+        time: u64,
+        #[serde(flatten)]
+        stats: Stats,
+    }
+    serde_json::to_writer(&mut *out, &Line { time, stats })?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// libFuzzer, or the LibAFL runtime which takes the same flags, linked into
+/// the targets of a project.
+pub struct LibFuzzer<'a> {
+    project: &'a FuzzProject,
+    build: &'a BuildOptions,
+}
+
+impl<'a> LibFuzzer<'a> {
+    pub fn new(project: &'a FuzzProject, build: &'a BuildOptions) -> Self {
+        LibFuzzer { project, build }
+    }
+}
+
+impl FuzzEngine for LibFuzzer<'_> {
+    fn command(&self, target: &str) -> Result<Command> {
+        self.project.cargo_run(self.build, target)
+    }
+
+    fn parse_stats(&self, line: &str) -> Stats {
+        parse_libfuzzer_stats(line)
+    }
+
+    fn artifacts_since(&self, target: &str, since: SystemTime) -> Result<HashSet<PathBuf>> {
+        self.project.get_artifacts_since(target, &since)
+    }
+
+    fn merge(
+        &self,
+        target: &str,
+        into: &Path,
+        from: &[PathBuf],
+        args: &[String],
+        runs: &mut dyn FnMut(u64),
+    ) -> Result<()> {
+        let mut cmd = self.command(target)?;
+        cmd.args(args).arg("-merge=1").arg(into).args(from);
+        let status = logging::replay_status(&mut cmd, Source::LibFuzzer, |line| {
+            if let Some(execs) = parse_libfuzzer_stats(line).execs {
+                runs(execs);
+            }
+        })?;
+        if !status.success() {
+            bail!("merging into {} failed: {}", into.display(), status);
+        }
+        Ok(())
+    }
+}
+
+/// Parses a status line such as
+/// `#4096 pulse cov: 120 ft: 340 corp: 20/1Kb exec/s: 2048 rss: 40Mb`.
+pub(crate) fn parse_libfuzzer_stats(line: &str) -> Stats {
+    let mut stats = Stats::default();
+    let Some(rest) = line.strip_prefix('#') else {
+        return stats;
+    };
+    let mut words = rest.split_whitespace();
+    stats.execs = words.next().and_then(|n| n.parse().ok());
+    let value = |key: &str| {
+        let mut words = line.split_whitespace();
+        words.find(|w| *w == key)?;
+        words.next()
+    };
+    stats.coverage = value("cov:").and_then(|n| n.parse().ok());
+    stats.features = schedule::parse_features(line);
+    stats.corpus_entries = value("corp:")
+        .and_then(|corp| corp.split('/').next())
+        .and_then(|n| n.parse().ok());
+    stats
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::env;
+
+    const FAKE_TOOL_STDOUT: &str = "MOVE_FUZZ_TEST_FAKE_TOOL_STDOUT";
+    const FAKE_TOOL_STDERR: &str = "MOVE_FUZZ_TEST_FAKE_TOOL_STDERR";
+    const FAKE_TOOL_CODE: &str = "MOVE_FUZZ_TEST_FAKE_TOOL_CODE";
+
+    /// A command standing for a tool that prints the lines `stdout` and
+    /// `stderr` and exits with `code`, without a shell: the test binary running
+    /// only [`fake_tool_process`]. Its stdout starts with the lines of the test
+    /// harness.
+    pub(crate) fn fake_tool(stdout: &[&str], stderr: &[&str], code: i32) -> Command {
+        let mut cmd = Command::new(env::current_exe().unwrap());
+        cmd.args(["engine::test::fake_tool_process", "--exact", "--nocapture"])
+            .env(FAKE_TOOL_STDOUT, stdout.join("\n"))
+            .env(FAKE_TOOL_STDERR, stderr.join("\n"))
+            .env(FAKE_TOOL_CODE, code.to_string());
+        cmd
+    }
+
+    /// The tool of [`fake_tool`], doing nothing when run as a test.
+    #[test]
+    fn fake_tool_process() {
+        let Ok(code) = env::var(FAKE_TOOL_CODE) else {
+            return;
+        };
+        // Ends the line the test harness started.
+        println!();
+        for line in env::var(FAKE_TOOL_STDOUT).unwrap().lines() {
+            println!("{}", line);
+        }
+        for line in env::var(FAKE_TOOL_STDERR).unwrap().lines() {
+            eprintln!("{}", line);
+        }
+        std::process::exit(code.parse().unwrap());
+    }
+
+    /// Stands in for a fuzz target, printing status lines and failing.
+    struct Fake {
+        artifacts_asked: Cell<bool>,
+    }
+
+    impl FuzzEngine for Fake {
+        fn command(&self, _target: &str) -> Result<Command> {
+            Ok(fake_tool(
+                &[],
+                &[
+                    "#2 INITED cov: 3 ft: 4 corp: 1/1b",
+                    "#64 NEW cov: 5 ft: 9 corp: 2/3b exec/s: 0",
+                    "==1== ERROR: deadly signal",
+                ],
+                77,
+            ))
+        }
+
+        fn parse_stats(&self, line: &str) -> Stats {
+            parse_libfuzzer_stats(line)
+        }
+
+        fn artifacts_since(&self, _target: &str, _since: SystemTime) -> Result<HashSet<PathBuf>> {
+            self.artifacts_asked.set(true);
+            Ok(HashSet::from([PathBuf::from("crash-1")]))
+        }
+
+        fn merge(
+            &self,
+            _: &str,
+            _: &Path,
+            _: &[PathBuf],
+            _: &[String],
+            _: &mut dyn FnMut(u64),
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn runs_and_parses_stats() {
+        let engine = Fake {
+            artifacts_asked: Cell::new(false),
+        };
+        let outcome = engine.run("t", &mut engine.command("t").unwrap()).unwrap();
+        assert_eq!(outcome.status.code(), Some(77));
+        assert_eq!(
+            outcome.stats,
+            Stats {
+                execs: Some(64),
+                coverage: Some(5),
+                features: Some(9),
+                corpus_entries: Some(2),
+            }
+        );
+        assert_eq!(outcome.tail.back().unwrap(), "==1== ERROR: deadly signal");
+        assert!(engine.artifacts_asked.get());
+        assert_eq!(outcome.artifacts.len(), 1);
+        assert_eq!(parse_libfuzzer_stats("INFO: Seed: 1"), Stats::default());
+
+        let mut stream = Vec::new();
+        write_stats(&mut stream, 7, Stats::default()).unwrap();
+        write_stats(&mut stream, 8, outcome.stats).unwrap();
+        assert_eq!(
+            String::from_utf8(stream).unwrap(),
+            "{\"time\":8,\"execs\":64,\"coverage\":5,\"features\":9,\"corpus_entries\":2}\n"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The failures of the commands that callers tell apart, for exit codes and
+/// the `error_kind` of JSON messages.
+///
+/// They travel in `anyhow::Error` like any other error, find them with [`find`].
+#[derive(Debug)]
+pub enum Error {
+    /// There is no cargo package above the current directory
+    ProjectNotFound { dir: PathBuf },
+    /// No fuzz target with this name in the fuzz manifest
+    TargetNotFound {
+        target: String,
+        available: Vec<String>,
+    },
+    /// A command needing a fuzz target was given none, and none could be asked for
+    TargetMissing { available: Vec<String> },
+    /// A tool that move-fuzz runs is not installed
+    ToolchainMissing { tool: String },
+    /// `cargo` or `move` failed to build the fuzz targets
+    BuildFailed { command: String },
+    /// Fuzz targets crashed, or an input still makes one crash
+    CrashFound {
+        targets: Vec<String>,
+        artifacts: Vec<PathBuf>,
+    },
+}
+
+impl Error {
+    /// The exit code of `move-fuzz` when failing with this error.
+    ///
+    /// A crash exits with 77 like libFuzzer's `-error_exitcode`, the other
+    /// errors get small numbers and any error not listed here exits with 1.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::ProjectNotFound { .. } => 2,
+            Error::TargetNotFound { .. } | Error::TargetMissing { .. } => 3,
+            Error::ToolchainMissing { .. } => 4,
+            Error::BuildFailed { .. } => 5,
+            Error::CrashFound { .. } => 77,
+        }
+    }
+
+    /// Name of the error in JSON messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::ProjectNotFound { .. } => "project-not-found",
+            Error::TargetNotFound { .. } => "target-not-found",
+            Error::TargetMissing { .. } => "target-missing",
+            Error::ToolchainMissing { .. } => "toolchain-missing",
+            Error::BuildFailed { .. } => "build-failed",
+            Error::CrashFound { .. } => "crash-found",
+        }
+    }
+
+    pub(crate) fn crash(target: &str, artifacts: Vec<PathBuf>) -> Self {
+        Error::CrashFound {
+            targets: vec![target.to_owned()],
+            artifacts,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ProjectNotFound { dir } => {
+                write!(f, "could not find a cargo project above {}", dir.display())
+            }
+            Error::TargetNotFound { target, available } => write!(
+                f,
+                "could not find fuzz target {:?}, available targets: {}",
+                target,
+                available.join(", ")
+            ),
+            Error::TargetMissing { available } if available.is_empty() => write!(
+                f,
+                "no fuzz target given and the project has none, add one with `move-fuzz add`"
+            ),
+            Error::TargetMissing { available } => write!(
+                f,
+                "no fuzz target given, pass one of: {}",
+                available.join(", ")
+            ),
+            Error::ToolchainMissing { tool } => {
+                write!(f, "failed to run {}, is it installed?", tool)
+            }
+            Error::BuildFailed { command } => write!(f, "failed to build fuzz script: {}", command),
+            Error::CrashFound { targets, .. } => match targets.as_slice() {
+                [target] => write!(f, "fuzz target {} crashed", target),
+                targets => write!(
+                    f,
+                    "{} fuzz targets crashed: {}",
+                    targets.len(),
+                    targets.join(", ")
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The [`Error`] behind `error`, if any.
+pub fn find(error: &anyhow::Error) -> Option<&Error> {
+    error.chain().find_map(|e| e.downcast_ref::<Error>())
+}
+
+/// The exit code of `move-fuzz` when failing with `error`.
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    find(error).map_or(1, Error::exit_code)
+}
+
+/// The error of spawning `cmd`, [`Error::ToolchainMissing`] when its program
+/// does not exist.
+pub(crate) fn spawn_failed(cmd: &Command, error: io::Error) -> anyhow::Error {
+    if error.kind() == io::ErrorKind::NotFound {
+        return Error::ToolchainMissing {
+            tool: cmd.get_program().to_string_lossy().into_owned(),
+        }
+        .into();
+    }
+    anyhow::Error::new(error).context(format!("failed to run command: {:?}", cmd))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn found_through_context() {
+        let error = Err::<(), _>(Error::crash("t", vec![]))
+            .context("while fuzzing")
+            .unwrap_err();
+        assert_eq!(exit_code(&error), 77);
+        assert_eq!(find(&error).map(Error::kind), Some("crash-found"));
+        assert_eq!(exit_code(&anyhow::anyhow!("other")), 1);
+
+        let error = anyhow::Error::from(Error::TargetMissing {
+            available: vec!["a".to_owned(), "b".to_owned()],
+        });
+        assert_eq!(exit_code(&error), 3);
+        assert_eq!(error.to_string(), "no fuzz target given, pass one of: a, b");
+
+        let mut cmd = Command::new("move-fuzz-surely-not-installed");
+        let spawn_error = cmd.output().unwrap_err();
+        let error = spawn_failed(&cmd, spawn_error);
+        assert_eq!(exit_code(&error), 4);
+        assert_eq!(
+            error.to_string(),
+            "failed to run move-fuzz-surely-not-installed, is it installed?"
+        );
+    }
+}
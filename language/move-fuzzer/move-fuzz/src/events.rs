@@ -0,0 +1,123 @@
+use crate::triage::unix_now;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::{env, time};
+
+pub const EVENTS_LOG_FILE: &str = "logs/events.jsonl";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Build,
+    Run,
+    /// A failing input found by a run
+    Crash,
+    Cmin,
+    Coverage,
+}
+
+/// One line of `fuzz/logs/events.jsonl`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub kind: EventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Command line of the `move-fuzz` invocation.
+    pub args: Vec<String>,
+    /// For crashes, the time from the start of the run to the crash.
+    pub duration_ms: u64,
+    pub success: bool,
+    /// Error of a failed command, or the artifact of a crash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, target: Option<&str>, duration: time::Duration) -> Self {
+        Self {
+            timestamp: unix_now(),
+            kind,
+            target: target.map(str::to_owned),
+            args: env::args().skip(1).collect(),
+            duration_ms: duration.as_millis() as u64,
+            success: true,
+            detail: None,
+        }
+    }
+}
+
+pub fn append(path: &Path, event: &Event) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("could not make a log directory at {}", dir.display()))?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)
+        .with_context(|| format!("failed to write to {}", path.display()))
+}
+
+/// Reads the events of a log, skipping lines that don't decode, e.g. one cut short by a crash.
+pub fn load(path: &Path) -> Result<Vec<Event>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Runs `f` and appends an event recording how long it took and whether it succeeded.
+/// Failing to write the log only prints a warning.
+pub fn logged<T>(
+    path: &Path,
+    kind: EventKind,
+    target: Option<&str>,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let start = time::Instant::now();
+    let result = f();
+    let mut event = Event::new(kind, target, start.elapsed());
+    if let Err(e) = &result {
+        event.success = false;
+        event.detail = Some(format!("{:#}", e));
+    }
+    if let Err(e) = append(path, &event) {
+        tracing::warn!("could not record the event: {:#}", e);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn append_and_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(EVENTS_LOG_FILE);
+        let _ = logged(&path, EventKind::Build, Some("a"), || Ok(()));
+        let _ = logged::<()>(&path, EventKind::Cmin, None, || anyhow::bail!("boom"));
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"timestamp\":").unwrap();
+
+        let events = load(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::Build);
+        assert_eq!(events[0].target.as_deref(), Some("a"));
+        assert!(events[0].success);
+        assert!(!events[1].success);
+        assert_eq!(events[1].detail.as_deref(), Some("boom"));
+    }
+}
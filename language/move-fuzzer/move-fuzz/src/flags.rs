@@ -0,0 +1,347 @@
+//! The flags of the fuzzing engines, passed through after `--`.
+//!
+//! They go to the engine as they are, but are first checked against the flags
+//! the engine knows so that a typo does not silently do nothing, and against
+//! the ones `move-fuzz` sets itself, which they would override.
+
+use crate::options::Engine;
+
+/// The flags of libFuzzer, with what they do.
+const LIBFUZZER: &[(&str, &str)] = &[
+    ("verbosity", "Verbosity level"),
+    ("seed", "Random seed, 0 to pick one"),
+    ("runs", "Number of individual test runs, -1 for infinite"),
+    ("max_len", "Maximum length of the test input, guessed if 0"),
+    (
+        "len_control",
+        "Grow the length of inputs slowly, 0 to try long inputs right away",
+    ),
+    (
+        "seed_inputs",
+        "Comma-separated list of input files to use as an additional seed corpus",
+    ),
+    (
+        "keep_seed",
+        "Keep the seed inputs in the corpus even if they add no coverage",
+    ),
+    ("cross_over", "Cross over inputs"),
+    (
+        "cross_over_uniform_dist",
+        "Pick the inputs to cross over uniformly",
+    ),
+    (
+        "mutate_depth",
+        "Apply this number of consecutive mutations to each input",
+    ),
+    (
+        "reduce_depth",
+        "Reduce the depth of mutations of inputs that add no feature",
+    ),
+    ("shuffle", "Shuffle the initial corpus"),
+    (
+        "prefer_small",
+        "Prefer small inputs when shuffling the corpus",
+    ),
+    ("timeout", "Timeout in seconds of an input, 0 to disable"),
+    ("error_exitcode", "Exit code of a crash"),
+    ("timeout_exitcode", "Exit code of a timeout"),
+    (
+        "max_total_time",
+        "Run for at most this number of seconds, 0 for no limit",
+    ),
+    ("help", "Print the help of the engine"),
+    ("fork", "Fuzz in this number of subprocesses"),
+    (
+        "fork_corpus_groups",
+        "Split the corpus into groups for the subprocesses of -fork",
+    ),
+    ("ignore_timeouts", "Keep fuzzing after a timeout with -fork"),
+    (
+        "ignore_ooms",
+        "Keep fuzzing after running out of memory with -fork",
+    ),
+    ("ignore_crashes", "Keep fuzzing after a crash with -fork"),
+    ("merge", "Merge the corpora given into the first one"),
+    (
+        "set_cover_merge",
+        "Merge like -merge, keeping a minimal set covering all features",
+    ),
+    ("stop_file", "Stop fuzzing as soon as this file exists"),
+    ("merge_inner", "Used internally by -merge"),
+    (
+        "merge_control_file",
+        "File to store the state of a merge, to resume it",
+    ),
+    ("minimize_crash", "Minimize the crashing input given"),
+    (
+        "cleanse_crash",
+        "Replace the bytes of the crashing input that do not matter",
+    ),
+    (
+        "minimize_crash_internal_step",
+        "Used internally by -minimize_crash",
+    ),
+    (
+        "minimize_timeout",
+        "Timeout in seconds of an input with -minimize_crash",
+    ),
+    ("use_counters", "Use coverage counters"),
+    ("use_memmem", "Use hints from memmem"),
+    ("use_value_profile", "Use value profile to guide fuzzing"),
+    (
+        "use_cmp",
+        "Use the operands of comparisons to guide mutations",
+    ),
+    (
+        "shrink",
+        "Replace inputs of the corpus with smaller ones covering the same features",
+    ),
+    (
+        "reduce_inputs",
+        "Reduce the size of inputs while keeping their features",
+    ),
+    ("jobs", "Number of jobs to run, each in its own process"),
+    (
+        "workers",
+        "Number of simultaneous processes running the -jobs",
+    ),
+    ("reload", "Reload the corpus every this number of seconds"),
+    (
+        "report_slow_units",
+        "Report inputs running for longer than this number of seconds",
+    ),
+    ("only_ascii", "Only generate ASCII inputs"),
+    ("dict", "Dictionary file of tokens to insert"),
+    (
+        "artifact_prefix",
+        "Prefix of the paths of crashes, timeouts and slow inputs",
+    ),
+    (
+        "exact_artifact_path",
+        "Path of the single artifact, overriding -artifact_prefix",
+    ),
+    ("print_pcs", "Print newly covered PCs"),
+    (
+        "print_funcs",
+        "Print this number of newly covered functions",
+    ),
+    ("print_final_stats", "Print statistics at exit"),
+    (
+        "print_corpus_stats",
+        "Print statistics of the corpus at exit",
+    ),
+    ("print_coverage", "Print the coverage of the corpus at exit"),
+    (
+        "print_full_coverage",
+        "Print the full coverage of the corpus at exit",
+    ),
+    ("dump_coverage", "Deprecated"),
+    ("handle_segv", "Handle SIGSEGV"),
+    ("handle_bus", "Handle SIGBUS"),
+    ("handle_abrt", "Handle SIGABRT"),
+    ("handle_ill", "Handle SIGILL"),
+    ("handle_fpe", "Handle SIGFPE"),
+    ("handle_int", "Handle SIGINT"),
+    ("handle_term", "Handle SIGTERM"),
+    ("handle_xfsz", "Handle SIGXFSZ"),
+    ("handle_usr1", "Handle SIGUSR1"),
+    ("handle_usr2", "Handle SIGUSR2"),
+    ("handle_winexcept", "Handle Windows exceptions"),
+    (
+        "close_fd_mask",
+        "Close stdout (1), stderr (2) or both (3) of the target",
+    ),
+    (
+        "detect_leaks",
+        "Report memory leaks, with the leak sanitizer",
+    ),
+    (
+        "purge_allocator_interval",
+        "Purge the allocator caches every this number of seconds",
+    ),
+    ("trace_malloc", "Trace allocations, 2 for stack traces too"),
+    ("rss_limit_mb", "Memory limit in MB, 0 for none"),
+    (
+        "malloc_limit_mb",
+        "Limit in MB of a single allocation, -rss_limit_mb by default",
+    ),
+    (
+        "exit_on_src_pos",
+        "Exit once this source position is covered",
+    ),
+    (
+        "exit_on_item",
+        "Exit once an input with this SHA-1 is found",
+    ),
+    (
+        "ignore_remaining_args",
+        "Ignore the arguments after this flag",
+    ),
+    ("focus_function", "Focus fuzzing on this function"),
+    ("entropic", "Use the entropic power schedule"),
+    (
+        "entropic_feature_frequency_threshold",
+        "Frequency of a feature to be considered rare",
+    ),
+    (
+        "entropic_number_of_rarest_features",
+        "Number of rare features the entropic schedule considers",
+    ),
+    (
+        "entropic_scale_per_exec_time",
+        "Scale the energy of inputs by their execution time",
+    ),
+    ("analyze_dict", "Analyze the dictionary given and exit"),
+    ("use_clang_coverage", "Deprecated"),
+    ("data_flow_trace", "Directory of data flow traces"),
+    (
+        "collect_data_flow",
+        "Collect data flow traces with this binary",
+    ),
+    (
+        "create_missing_dirs",
+        "Create the directories given that do not exist",
+    ),
+    (
+        "features_dir",
+        "Write the features of the inputs to this directory",
+    ),
+    (
+        "mutation_graph_file",
+        "Write the graph of mutations to this file",
+    ),
+];
+
+/// The flags the LibAFL libFuzzer runtime takes on top of those of libFuzzer.
+const LIBAFL: &[(&str, &str)] = &[
+    (
+        "grimoire",
+        "Use the Grimoire mutator for structured text inputs",
+    ),
+    ("unicode", "Use the Unicode-aware mutators"),
+    ("tui", "Show the terminal UI of LibAFL"),
+    ("skip_tracing", "Do not trace comparisons"),
+];
+
+/// The flags of `engine`, with what they do.
+pub fn known(engine: Engine) -> Vec<(&'static str, &'static str)> {
+    let mut flags = LIBFUZZER.to_vec();
+    if engine == Engine::Libafl {
+        flags.extend_from_slice(LIBAFL);
+    }
+    flags
+}
+
+/// Prints the flags of `engine`, for `run --engine-help`.
+pub fn print_help(engine: Engine) {
+    println!("Flags of {}, passed after `--` as -flag=value:\n", engine);
+    for (flag, help) in known(engine) {
+        println!("  -{:<38} {}", flag, help);
+    }
+}
+
+/// Checks the arguments passed through to `engine`, the flags `move-fuzz`
+/// already sets included, and returns what is wrong with them.
+///
+/// Arguments not starting with `-` are corpus directories or inputs.
+pub fn check(engine: Engine, args: &[String], set: &[&str]) -> Vec<String> {
+    let known = known(engine);
+    let mut problems = Vec::new();
+    for arg in args {
+        let Some(flag) = arg.strip_prefix('-') else {
+            continue;
+        };
+        if let Some(flag) = flag.strip_prefix('-') {
+            problems.push(format!(
+                "`{}` starts with two dashes, the engine takes `-{}`",
+                arg, flag
+            ));
+            continue;
+        }
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (flag, None),
+        };
+        if !known.iter().any(|(known, _)| *known == name) {
+            let closest = known
+                .iter()
+                .map(|(known, _)| (distance(name, known), *known))
+                .min()
+                .filter(|(distance, _)| *distance <= 2);
+            problems.push(match closest {
+                Some((_, known)) => format!(
+                    "{} does not know `-{}`, did you mean `-{}`?",
+                    engine, name, known
+                ),
+                None => format!(
+                    "{} does not know `-{}`, see `move-fuzz run --engine-help`",
+                    engine, name
+                ),
+            });
+            continue;
+        }
+        if value.is_none() {
+            problems.push(format!(
+                "`-{}` is ignored without a value, pass `-{}=VALUE`",
+                name, name
+            ));
+        }
+        if set.contains(&name) {
+            problems.push(format!(
+                "`-{}` overrides the value move-fuzz sets itself",
+                name
+            ));
+        }
+    }
+    problems
+}
+
+/// The edit distance between `a` and `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<_> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_passed_through_flags() {
+        let args: Vec<_> = [
+            "-max_len=64",
+            "corpus/extra",
+            "-max_lenn=64",
+            "--runs=10",
+            "-runs",
+            "-artifact_prefix=x/",
+            "-grimoire=1",
+            "-frobnicate=1",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        assert_eq!(
+            check(Engine::Libfuzzer, &args, &["artifact_prefix"]),
+            [
+                "libfuzzer does not know `-max_lenn`, did you mean `-max_len`?",
+                "`--runs=10` starts with two dashes, the engine takes `-runs=10`",
+                "`-runs` is ignored without a value, pass `-runs=VALUE`",
+                "`-artifact_prefix` overrides the value move-fuzz sets itself",
+                "libfuzzer does not know `-grimoire`, see `move-fuzz run --engine-help`",
+                "libfuzzer does not know `-frobnicate`, see `move-fuzz run --engine-help`",
+            ]
+        );
+        assert!(check(Engine::Libafl, &args[6..7], &[]).is_empty());
+    }
+}
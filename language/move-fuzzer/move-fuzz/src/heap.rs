@@ -0,0 +1,182 @@
+//! Reports of the allocation profiles `repro --heap-profile` records.
+//!
+//! Targets built with `libfuzzer/heap-profile` write DHAT's JSON: a program
+//! point per allocating backtrace in `pps`, its frames indexing into `ftbl`,
+//! with the bytes and blocks it allocated in total (`tb`, `tbk`) and that were
+//! live when the heap was at its peak (`gb`, `gbk`).
+
+use crate::corpus::human_size;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fmt::Write;
+
+#[derive(Deserialize)]
+struct Profile {
+    pps: Vec<ProgramPoint>,
+    ftbl: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ProgramPoint {
+    tb: u64,
+    tbk: u64,
+    #[serde(default)]
+    gb: u64,
+    #[serde(default)]
+    gbk: u64,
+    fs: Vec<usize>,
+}
+
+/// The allocations of a function, at the peak and over the whole run.
+#[derive(Debug, Default, PartialEq)]
+pub struct Site {
+    /// The function and its location, the first frame of the backtraces
+    /// outside the standard library and the allocator
+    pub frame: String,
+    pub peak_bytes: u64,
+    pub peak_blocks: u64,
+    pub total_bytes: u64,
+    pub total_blocks: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct HeapReport {
+    pub peak_bytes: u64,
+    pub peak_blocks: u64,
+    pub total_bytes: u64,
+    pub total_blocks: u64,
+    /// By bytes live at the peak, then bytes allocated
+    pub sites: Vec<Site>,
+}
+
+impl HeapReport {
+    pub fn parse(json: &str) -> Result<Self> {
+        let profile: Profile = serde_json::from_str(json).context("invalid DHAT profile")?;
+        let mut report = HeapReport::default();
+        for point in &profile.pps {
+            report.peak_bytes += point.gb;
+            report.peak_blocks += point.gbk;
+            report.total_bytes += point.tb;
+            report.total_blocks += point.tbk;
+
+            let frames: Vec<&str> = point
+                .fs
+                .iter()
+                .filter_map(|&i| profile.ftbl.get(i))
+                .map(|frame| frame.split_once(": ").map_or(frame.as_str(), |(_, f)| f))
+                .collect();
+            let frame = frames
+                .iter()
+                .find(|frame| !is_library(frame))
+                .or_else(|| frames.first())
+                .map_or("[unknown]", |frame| frame);
+            let index = match report.sites.iter().position(|site| site.frame == frame) {
+                Some(index) => index,
+                None => {
+                    report.sites.push(Site {
+                        frame: frame.to_owned(),
+                        ..Site::default()
+                    });
+                    report.sites.len() - 1
+                }
+            };
+            let site = &mut report.sites[index];
+            site.peak_bytes += point.gb;
+            site.peak_blocks += point.gbk;
+            site.total_bytes += point.tb;
+            site.total_blocks += point.tbk;
+        }
+        report.sites.sort_by(|a, b| {
+            (b.peak_bytes, b.total_bytes)
+                .cmp(&(a.peak_bytes, a.total_bytes))
+                .then_with(|| a.frame.cmp(&b.frame))
+        });
+        Ok(report)
+    }
+
+    /// The peak and the `top` sites allocating the most.
+    pub fn render(&self, top: usize) -> String {
+        let mut text = format!(
+            "Peak heap: {} in {} blocks\nTotal allocated: {} in {} blocks\n\n\
+             Hottest allocation sites, by bytes live at the peak:\n\n",
+            human_size(self.peak_bytes),
+            self.peak_blocks,
+            human_size(self.total_bytes),
+            self.total_blocks,
+        );
+        for site in self.sites.iter().take(top) {
+            let share = if self.peak_bytes == 0 {
+                0.0
+            } else {
+                100.0 * site.peak_bytes as f64 / self.peak_bytes as f64
+            };
+            let _ = writeln!(
+                text,
+                "{:>10} {:>5.1}%  {:>10} total  {}",
+                human_size(site.peak_bytes),
+                share,
+                human_size(site.total_bytes),
+                site.frame
+            );
+        }
+        text
+    }
+}
+
+/// Whether a frame is in the collections or allocator everything allocates
+/// through, rather than in the code allocating.
+fn is_library(frame: &str) -> bool {
+    let name = frame.trim_start_matches('<');
+    [
+        "alloc::",
+        "core::",
+        "std::",
+        "hashbrown::",
+        "dhat::",
+        "__rg_",
+    ]
+    .iter()
+    .any(|prefix| name.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sites_by_peak() {
+        let json = r#"{
+            "dhatFileVersion": 2,
+            "mode": "rust-heap",
+            "pps": [
+                {"tb": 4096, "tbk": 4, "mb": 2048, "gb": 2048, "gbk": 2, "fs": [1, 2, 3]},
+                {"tb": 100, "tbk": 1, "gb": 0, "gbk": 0, "fs": [1, 4]},
+                {"tb": 1024, "tbk": 1, "gb": 1024, "gbk": 1, "fs": [5, 3]}
+            ],
+            "ftbl": [
+                "[root]",
+                "0x1: alloc::raw_vec::RawVec<T,A>::allocate_in (raw_vec.rs:1:1)",
+                "0x2: <alloc::vec::Vec<T> as core::clone::Clone>::clone (vec.rs:2:2)",
+                "0x3: move_vm_runtime::loader::Loader::load_module (loader.rs:3:3)",
+                "0x4: libfuzzer::run_move::parse (mod.rs:4:4)",
+                "0x5: std::collections::hash::map::HashMap<K,V>::insert (map.rs:5:5)"
+            ]
+        }"#;
+        let report = HeapReport::parse(json).unwrap();
+        assert_eq!(report.peak_bytes, 3072);
+        assert_eq!(report.total_bytes, 5220);
+        assert_eq!(report.total_blocks, 6);
+        let frames: Vec<_> = report.sites.iter().map(|site| &site.frame).collect();
+        assert_eq!(
+            frames,
+            [
+                "move_vm_runtime::loader::Loader::load_module (loader.rs:3:3)",
+                "libfuzzer::run_move::parse (mod.rs:4:4)",
+            ]
+        );
+        assert_eq!(report.sites[0].peak_bytes, 3072);
+        assert_eq!(report.sites[0].total_blocks, 5);
+        assert!(report.render(1).contains("3.0 KiB 100.0%"));
+        assert!(HeapReport::parse("{}").is_err());
+    }
+}
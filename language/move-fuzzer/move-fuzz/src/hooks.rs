@@ -0,0 +1,194 @@
+use crate::utils::shell;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// When a hook fires.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HookPoint {
+    PreBuild,
+    PostBuild,
+    PreRun,
+    PostRun,
+}
+
+impl HookPoint {
+    const ALL: [HookPoint; 4] = [
+        HookPoint::PreBuild,
+        HookPoint::PostBuild,
+        HookPoint::PreRun,
+        HookPoint::PostRun,
+    ];
+}
+
+impl fmt::Display for HookPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                HookPoint::PreBuild => "pre-build",
+                HookPoint::PostBuild => "post-build",
+                HookPoint::PreRun => "pre-run",
+                HookPoint::PostRun => "post-run",
+            }
+        )
+    }
+}
+
+/// What a hook is told about the command it fires around.
+#[derive(Clone, Debug)]
+pub struct HookContext<'a> {
+    pub point: HookPoint,
+    pub fuzz_dir: &'a Path,
+    /// The target being built or run, `None` when the command covers all of them.
+    pub target: Option<&'a str>,
+    /// Whether the command succeeded, for the `post-*` hooks.
+    pub success: Option<bool>,
+}
+
+/// A hook registered through the library API.
+pub type Callback = Box<dyn Fn(&HookContext) -> Result<()>>;
+
+/// The hooks of a fuzz project: shell commands declared in the fuzz manifest,
+/// then callbacks registered by library users.
+///
+/// ```toml
+/// [package.metadata.move-fuzz.hooks]
+/// pre-build = "move build --path .."
+/// post-run = ["./upload-artifacts.sh"]
+/// ```
+///
+/// Commands run from the fuzz directory with `MOVE_FUZZ_HOOK`, `MOVE_FUZZ_DIR`
+/// and, when there is one, `MOVE_FUZZ_TARGET` set. The `post-*` hooks also get
+/// `MOVE_FUZZ_SUCCESS`, `true` or `false`.
+#[derive(Default)]
+pub struct Hooks {
+    commands: HashMap<HookPoint, Vec<String>>,
+    callbacks: Vec<(HookPoint, Callback)>,
+}
+
+impl Hooks {
+    /// Reads the hooks under `[package.metadata.move-fuzz.hooks]`.
+    pub fn from_manifest(manifest: &toml::Value) -> Result<Self> {
+        let mut hooks = Hooks::default();
+        let table = match manifest
+            .get("package")
+            .and_then(|v| v.get("metadata"))
+            .and_then(|v| v.get("move-fuzz"))
+            .and_then(|v| v.get("hooks"))
+        {
+            Some(table) => table
+                .as_table()
+                .context("`package.metadata.move-fuzz.hooks` must be a table")?,
+            None => return Ok(hooks),
+        };
+        for (key, value) in table {
+            let point = match HookPoint::ALL.iter().find(|p| p.to_string() == *key) {
+                Some(point) => *point,
+                None => bail!(
+                    "unknown hook `{}`, expected one of pre-build, post-build, pre-run, post-run",
+                    key
+                ),
+            };
+            let commands = match value {
+                toml::Value::String(command) => vec![command.clone()],
+                toml::Value::Array(commands) => commands
+                    .iter()
+                    .map(|c| c.as_str().map(String::from))
+                    .collect::<Option<_>>()
+                    .with_context(|| {
+                        format!("the commands of the `{}` hook must be strings", key)
+                    })?,
+                _ => bail!("the `{}` hook must be a command or a list of commands", key),
+            };
+            hooks.commands.insert(point, commands);
+        }
+        Ok(hooks)
+    }
+
+    pub fn add_callback(&mut self, point: HookPoint, callback: Callback) {
+        self.callbacks.push((point, callback));
+    }
+
+    /// Runs the hooks of `context.point`, stopping at the first one that fails.
+    pub fn fire(&self, context: &HookContext) -> Result<()> {
+        for command in self.commands.get(&context.point).into_iter().flatten() {
+            let mut cmd = shell(command);
+            cmd.current_dir(context.fuzz_dir)
+                .env("MOVE_FUZZ_HOOK", context.point.to_string())
+                .env("MOVE_FUZZ_DIR", context.fuzz_dir);
+            if let Some(target) = context.target {
+                cmd.env("MOVE_FUZZ_TARGET", target);
+            }
+            if let Some(success) = context.success {
+                cmd.env("MOVE_FUZZ_SUCCESS", success.to_string());
+            }
+            let status = cmd.status().with_context(|| {
+                format!("failed to run the `{}` hook `{}`", context.point, command)
+            })?;
+            if !status.success() {
+                bail!(
+                    "the `{}` hook `{}` exited with {}",
+                    context.point,
+                    command,
+                    status
+                );
+            }
+        }
+        for (_, callback) in self.callbacks.iter().filter(|(p, _)| *p == context.point) {
+            callback(context).with_context(|| format!("the `{}` hook failed", context.point))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn manifest_hooks_and_callbacks() {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+            [package.metadata.move-fuzz.hooks]
+            pre-build = "move build"
+            post-run = ["a", "b"]
+            "#,
+        )
+        .unwrap();
+        let mut hooks = Hooks::from_manifest(&manifest).unwrap();
+        assert_eq!(hooks.commands[&HookPoint::PreBuild], ["move build"]);
+        assert_eq!(hooks.commands[&HookPoint::PostRun], ["a", "b"]);
+
+        let bad: toml::Value =
+            toml::from_str("[package.metadata.move-fuzz.hooks]\npre-fuzz = \"x\"").unwrap();
+        assert!(Hooks::from_manifest(&bad).is_err());
+
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let seen = fired.clone();
+        hooks.commands.clear();
+        hooks.add_callback(
+            HookPoint::PostRun,
+            Box::new(move |ctx| {
+                seen.borrow_mut()
+                    .push((ctx.target.map(String::from), ctx.success));
+                Ok(())
+            }),
+        );
+        let context = |point, success| HookContext {
+            point,
+            fuzz_dir: Path::new("."),
+            target: Some("t"),
+            success,
+        };
+        hooks.fire(&context(HookPoint::PreRun, None)).unwrap();
+        hooks
+            .fire(&context(HookPoint::PostRun, Some(false)))
+            .unwrap();
+        assert_eq!(*fired.borrow(), [(Some("t".to_owned()), Some(false))]);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2016 rust-fuzz developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The commands of the `move-fuzz` CLI, for tools that embed them instead
+//! of shelling out.
+//!
+//! Every subcommand is an option struct in [`options`] implementing
+//! [`RunCommand`], so `move-fuzz run` is `options::Run::parse_from(...).run_command()`.
+//! The functions below cover the most common entry points directly.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+#[macro_use]
+mod templates;
+mod audit;
+mod autoharness;
+mod backtrace;
+mod batch;
+mod bisect;
+mod builds;
+mod bytecode_view;
+mod careful;
+pub mod cli;
+mod cluster;
+mod compat;
+mod concolic;
+pub mod config;
+mod corpus;
+mod coverage_diff;
+mod crash_report;
+mod dict;
+mod encoding;
+pub mod engine;
+pub mod error;
+mod events;
+mod flags;
+mod heap;
+pub mod hooks;
+mod lock;
+pub mod logging;
+mod merge;
+mod messages;
+mod metadata;
+mod modules;
+mod move_package;
+pub mod move_toml;
+mod notify;
+pub mod options;
+mod platform;
+pub mod plugins;
+pub mod progress;
+pub mod project;
+mod provenance;
+mod quota;
+mod rare;
+mod report;
+mod sarif;
+mod schedule;
+mod scheduling;
+mod schema;
+mod shutdown;
+mod smoke;
+mod symbolize;
+mod transactions;
+mod triage;
+mod utils;
+
+use crate::options::{BuildMode, BuildOptions};
+use crate::project::{FuzzProject, FuzzTarget};
+
+static FUZZ_TARGETS_DIR_OLD: &str = "fuzzers";
+static FUZZ_TARGETS_DIR: &str = "fuzz_targets";
+static MOVE_TARGETS_DIR: &str = "sources";
+
+/// A trait for running our various commands.
+pub trait RunCommand {
+    /// Run this command!
+    fn run_command(&mut self) -> Result<()>;
+}
+
+/// Builds `target`, or all the targets if `None`, of the fuzz project in
+/// `fuzz_dir`, or in `fuzz/` under the current package if `None`.
+pub fn build(fuzz_dir: Option<PathBuf>, build: &BuildOptions, target: Option<&str>) -> Result<()> {
+    FuzzProject::new(fuzz_dir)?.exec_build(BuildMode::Build, build, target)
+}
+
+/// Runs a fuzz target, or all of them with `--all`, like `move-fuzz run`.
+pub fn run(run: &options::Run) -> Result<()> {
+    FuzzProject::new(run.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+        .with_state_dirs(&run.state_dirs)
+        .exec_fuzz(run)
+}
+
+/// The fuzz targets of the fuzz project in `fuzz_dir`, with their corpus,
+/// artifacts and manifest entry.
+pub fn list_targets(fuzz_dir: &Path) -> Result<Vec<FuzzTarget>> {
+    FuzzProject::new(Some(fuzz_dir.to_owned()))?.fuzz_targets()
+}
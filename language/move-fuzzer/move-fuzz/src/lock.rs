@@ -0,0 +1,163 @@
+//! Advisory locks keeping concurrent invocations of `move-fuzz` off the same
+//! corpus and artifacts.
+//!
+//! The locks are files in `<fuzz dir>/.locks`. A run holds the lock of its
+//! target and shares the one of the project with the runs of other targets;
+//! corpus minimization and `run --all` hold the lock of the project alone. The
+//! operating system releases them when the process exits, however it exits.
+
+use crate::options::LockOptions;
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use tracing::info;
+
+pub const LOCKS_DIR: &str = ".locks";
+
+const PROJECT_LOCK: &str = "project.lock";
+
+/// What an invocation locks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Scope<'a> {
+    /// The corpus and artifacts of one target
+    Target(&'a str),
+    /// The whole project
+    Project,
+}
+
+impl fmt::Display for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scope::Target(target) => write!(f, "fuzz target {}", target),
+            Scope::Project => write!(f, "fuzz project"),
+        }
+    }
+}
+
+/// The locks of an invocation, released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+    _files: Vec<File>,
+}
+
+impl Lock {
+    /// Locks `scope` in the project at `fuzz_dir`, as `options` say: fails if
+    /// another invocation holds it, or waits for it with `--wait`, and locks
+    /// nothing with `--no-lock`.
+    pub fn acquire(fuzz_dir: &Path, scope: Scope, options: &LockOptions) -> Result<Lock> {
+        let mut files = Vec::new();
+        if options.no_lock {
+            return Ok(Lock { _files: files });
+        }
+        let dir = fuzz_dir.join(LOCKS_DIR);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+
+        let project = open(&dir.join(PROJECT_LOCK))?;
+        let shared = matches!(scope, Scope::Target(_));
+        let locked = if shared {
+            project.try_lock_shared()
+        } else {
+            project.try_lock()
+        };
+        match locked {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) if options.wait => {
+                info!("Waiting for the lock of the fuzz project");
+                if shared {
+                    project.lock_shared()?;
+                } else {
+                    project.lock()?;
+                }
+            }
+            Err(TryLockError::WouldBlock) => bail!(
+                "the fuzz project is locked by another move-fuzz invocation, \
+                pass --wait to wait for it or --no-lock to ignore the lock"
+            ),
+            Err(TryLockError::Error(e)) => {
+                return Err(e).context(format!("failed to lock the {}", scope))
+            }
+        }
+        files.push(project);
+
+        if let Scope::Target(target) = scope {
+            let mut file = open(&dir.join(format!("{}.lock", target)))?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) if options.wait => {
+                    info!("Waiting for the lock of {}", scope);
+                    file.lock()?;
+                }
+                Err(TryLockError::WouldBlock) => bail!(
+                    "the {} is locked by another move-fuzz invocation{}, \
+                    pass --wait to wait for it or --no-lock to ignore the lock",
+                    scope,
+                    holder(&mut file).map_or(String::new(), |pid| format!(" (pid {})", pid))
+                ),
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).context(format!("failed to lock the {}", scope))
+                }
+            }
+            // Only the holder writes, so the pid in the file is the holder's.
+            file.set_len(0)?;
+            file.rewind()?;
+            write!(file, "{}", std::process::id())?;
+            files.push(file);
+        }
+        Ok(Lock { _files: files })
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
+
+/// The pid of the process holding the lock in `file`.
+fn holder(file: &mut File) -> Option<u32> {
+    let mut pid = String::new();
+    file.read_to_string(&mut pid).ok()?;
+    pid.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exclude_other_invocations() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = LockOptions::default();
+        let run = Lock::acquire(dir.path(), Scope::Target("a"), &options).unwrap();
+        // Runs of other targets go on, a second run of the same one does not.
+        let other = Lock::acquire(dir.path(), Scope::Target("b"), &options).unwrap();
+        let error = Lock::acquire(dir.path(), Scope::Target("a"), &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "the fuzz target a is locked by another move-fuzz invocation (pid {}), \
+                pass --wait to wait for it or --no-lock to ignore the lock",
+                std::process::id()
+            )
+        );
+        assert!(Lock::acquire(dir.path(), Scope::Project, &options).is_err());
+        let no_lock = LockOptions {
+            no_lock: true,
+            ..LockOptions::default()
+        };
+        assert!(Lock::acquire(dir.path(), Scope::Project, &no_lock).is_ok());
+
+        drop((run, other));
+        let project = Lock::acquire(dir.path(), Scope::Project, &options).unwrap();
+        assert!(Lock::acquire(dir.path(), Scope::Target("a"), &options).is_err());
+        drop(project);
+        assert!(Lock::acquire(dir.path(), Scope::Target("a"), &options).is_ok());
+    }
+}
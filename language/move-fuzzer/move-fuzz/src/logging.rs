@@ -0,0 +1,265 @@
+//! The log of `move-fuzz` and of the tools it runs.
+//!
+//! Messages go through `tracing`, to stderr up to the level of `--log-level`
+//! and, with `--log-file`, to a file too. The output of cargo, of the Move
+//! build, of libFuzzer and of docker is logged line by line at the info level,
+//! tagged with where it comes from, and with the target of `run --concurrent`
+//! and of `worker`, so that it can be told apart once interleaved. The reports
+//! of the commands, like the summary of a crash, are logged at the info level
+//! too, so that `--quiet` leaves only the errors.
+
+use crate::error;
+use crate::options::{LogLevel, LogOptions};
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// A tool whose output `move-fuzz` logs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    Cargo,
+    Move,
+    LibFuzzer,
+    Docker,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Source::Cargo => "cargo",
+                Source::Move => "move",
+                Source::LibFuzzer => "libfuzzer",
+                Source::Docker => "docker",
+            }
+        )
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Sets up the log as `options` say.
+///
+/// Does nothing if the process already logs elsewhere, as tools embedding
+/// `move-fuzz` may.
+pub fn init(options: &LogOptions) -> Result<()> {
+    let level = LevelFilter::from(options.level());
+    let stderr = tracing_subscriber::fmt::layer()
+        .event_format(Plain)
+        .with_writer(io::stderr)
+        .with_filter(level);
+    let file = match &options.log_file {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_target(false)
+                .with_writer(Mutex::new(file))
+                .with_filter(level);
+            Some(layer)
+        }
+        None => None,
+    };
+    let _ = tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .try_init();
+    Ok(())
+}
+
+thread_local! {
+    static PREFIX: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, tagging the output logged on this thread with `prefix` too.
+pub(crate) fn with_prefix<T>(prefix: &str, f: impl FnOnce() -> T) -> T {
+    let previous = PREFIX.replace(Some(prefix.to_owned()));
+    let result = f();
+    PREFIX.set(previous);
+    result
+}
+
+/// Logs a line of output of `source`.
+pub(crate) fn output(source: Source, line: &str) {
+    PREFIX.with_borrow(|prefix| match prefix {
+        Some(prefix) => tracing::info!("[{} {}] {}", source, prefix, line),
+        None => tracing::info!("[{}] {}", source, line),
+    })
+}
+
+/// Runs `cmd` to completion like [`Command::status`], logging its stdout and
+/// stderr as the output of `source`.
+pub(crate) fn status(cmd: &mut Command, source: Source) -> Result<ExitStatus> {
+    run(cmd, move |line| output(source, line), |_| {})
+}
+
+/// Runs `cmd` like [`status`], for a replay whose progress a bar stands for:
+/// the lines of its stderr are passed to `on_line`, and its output is logged
+/// once it exits, at the debug level if it succeeded and as the output of
+/// `source` if it failed, so that what went wrong shows by default.
+pub(crate) fn replay_status(
+    cmd: &mut Command,
+    source: Source,
+    on_line: impl FnMut(&str),
+) -> Result<ExitStatus> {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let log = {
+        let lines = lines.clone();
+        move |line: &str| lines.lock().unwrap().push(line.to_owned())
+    };
+    let status = run(cmd, log, on_line)?;
+    for line in lines.lock().unwrap().iter() {
+        if status.success() {
+            tracing::debug!("[{}] {}", source, line);
+        } else {
+            output(source, line);
+        }
+    }
+    Ok(status)
+}
+
+fn run(
+    cmd: &mut Command,
+    log: impl Fn(&str) + Clone + Send + 'static,
+    mut on_line: impl FnMut(&str),
+) -> Result<ExitStatus> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd.spawn().map_err(|e| error::spawn_failed(cmd, e))?;
+    // The thread logs where this one does, should that not be the global log,
+    // and with the same prefix.
+    let dispatch = tracing::dispatcher::get_default(|dispatch| dispatch.clone());
+    let prefix = PREFIX.with_borrow(Clone::clone);
+    let stdout = child.stdout.take().map(|stdout| {
+        let log = log.clone();
+        thread::spawn(move || {
+            PREFIX.set(prefix);
+            tracing::dispatcher::with_default(&dispatch, || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    log(&line);
+                }
+            })
+        })
+    });
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            log(&line);
+            on_line(&line);
+        }
+    }
+    if let Some(stdout) = stdout {
+        let _ = stdout.join();
+    }
+    child
+        .wait()
+        .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))
+}
+
+/// The format of the log on stderr: the messages as they were printed before
+/// there was a log, `warning: ` and `error: ` ones included.
+struct Plain;
+
+impl<S, N> FormatEvent<S, N> for Plain
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        match *event.metadata().level() {
+            Level::ERROR => write!(writer, "error: ")?,
+            Level::WARN => write!(writer, "warning: ")?,
+            Level::INFO => {}
+            Level::DEBUG => write!(writer, "debug: ")?,
+            Level::TRACE => write!(writer, "trace: ")?,
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::test::fake_tool;
+    use std::fs;
+
+    #[test]
+    fn tag_the_output_of_tools() {
+        let log = tempfile::NamedTempFile::new().unwrap();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(Plain)
+            .with_writer(Mutex::new(log.reopen().unwrap()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!("careful");
+            let mut cmd = fake_tool(&["built"], &["failed"], 3);
+            assert_eq!(status(&mut cmd, Source::Cargo).unwrap().code(), Some(3));
+            with_prefix("t1", || output(Source::LibFuzzer, "#1 INITED"));
+            let mut cmd = fake_tool(&["Done"], &[], 0);
+            with_prefix("t2", || status(&mut cmd, Source::LibFuzzer)).unwrap();
+        });
+        let log = fs::read_to_string(log.path()).unwrap();
+        assert!(log.starts_with("warning: careful\n"));
+        assert!(log.contains("[cargo] built\n"));
+        assert!(log.contains("[cargo] failed\n"));
+        assert!(log.contains("[libfuzzer t1] #1 INITED\n"));
+        assert!(log.contains("[libfuzzer t2] Done\n"));
+    }
+
+    #[test]
+    fn show_the_output_of_failed_replays() {
+        let log = tempfile::NamedTempFile::new().unwrap();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(Plain)
+            .with_max_level(Level::INFO)
+            .with_writer(Mutex::new(log.reopen().unwrap()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let mut lines = Vec::new();
+            let mut cmd = fake_tool(&[], &["#1 INITED"], 0);
+            let status = replay_status(&mut cmd, Source::LibFuzzer, |line| {
+                lines.push(line.to_owned())
+            });
+            assert!(status.unwrap().success());
+            assert_eq!(lines, ["#1 INITED"]);
+            let mut cmd = fake_tool(&[], &["ERROR: out of memory"], 1);
+            let status = replay_status(&mut cmd, Source::LibFuzzer, |_| {});
+            assert!(!status.unwrap().success());
+        });
+        let log = fs::read_to_string(log.path()).unwrap();
+        // Only the failed replay is logged, its stdout included.
+        assert!(log.starts_with("[libfuzzer] "));
+        assert!(log.contains("[libfuzzer] ERROR: out of memory\n"));
+        assert!(!log.contains("INITED"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Resuming the libFuzzer merges of `merge` and `cmin`.
+//!
+//! Merging with `-merge_control_file` makes libFuzzer record its progress in
+//! the control file, whose format the [`quota`](crate::quota) module describes,
+//! and pick up after the last input it started when run again with the same
+//! file. It resumes with the inputs the file lists though, whatever the
+//! corpora passed: a control file is only kept when its inputs are still those
+//! of the corpora, and removed once the merge is done.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The merges of each target in progress, in the fuzz directory.
+pub const MERGE_DIR: &str = "merge";
+
+/// Where `merge` keeps its control file, in the merge directory of a target.
+pub const MERGE_CONTROL_FILE: &str = "merge.txt";
+
+/// Where `cmin` keeps its control file, in the merge directory of a target.
+pub const CMIN_CONTROL_FILE: &str = "cmin.txt";
+
+/// The progress a control file records.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Control {
+    /// The inputs of the corpus merged into, then those of the other corpora
+    pub inputs: Vec<PathBuf>,
+    /// Inputs started, the first one is run again on resuming
+    pub started: usize,
+}
+
+impl Control {
+    /// `None` if `control` is not a control file, libFuzzer then starts over.
+    pub fn parse(control: &str) -> Option<Self> {
+        let mut lines = control.lines();
+        let inputs = lines.next()?.trim().parse::<usize>().ok()?;
+        let first = lines.next()?.trim().parse::<usize>().ok()?;
+        if first > inputs {
+            return None;
+        }
+        let listed: Vec<PathBuf> = lines.by_ref().take(inputs).map(PathBuf::from).collect();
+        if listed.len() < inputs {
+            return None;
+        }
+        let started = lines
+            .filter_map(|line| line.strip_prefix("STARTED "))
+            .filter_map(|rest| rest.split_whitespace().next()?.parse::<usize>().ok())
+            .map(|index| index + 1)
+            .max()
+            .unwrap_or(0);
+        Some(Control {
+            inputs: listed,
+            started,
+        })
+    }
+
+    /// Whether the merge of `files` can resume from this control file: it must
+    /// list all of them, and only inputs that are still there.
+    pub fn resumable(&self, files: &[PathBuf]) -> bool {
+        let listed: HashSet<&Path> = self.inputs.iter().map(PathBuf::as_path).collect();
+        files.iter().all(|file| listed.contains(file.as_path()))
+            && self.inputs.iter().all(|input| input.is_file())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn resume_merges_of_the_same_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let corpus = dir.path().join("corpus");
+        fs::create_dir(&corpus).unwrap();
+        let files: Vec<PathBuf> = ["3f2a", "9c1b", "d00d"]
+            .iter()
+            .map(|name| {
+                let path = corpus.join(name);
+                fs::write(&path, name).unwrap();
+                path
+            })
+            .collect();
+        let listed: Vec<_> = files.iter().map(|f| f.to_string_lossy()).collect();
+        let control = format!(
+            "3\n0\n{}\nSTARTED 0 12\nFT 0 17 204\nSTARTED 1 40\n",
+            listed.join("\n")
+        );
+        let control = Control::parse(&control).unwrap();
+        assert_eq!(control.inputs, files);
+        assert_eq!(control.started, 2);
+        assert!(control.resumable(&files));
+
+        // The corpus gained an input since.
+        let new = corpus.join("beef");
+        fs::write(&new, "beef").unwrap();
+        assert!(!control.resumable(&[files.clone(), vec![new]].concat()));
+        // Or lost one.
+        fs::remove_file(&files[0]).unwrap();
+        assert!(!control.resumable(&files[1..]));
+
+        assert_eq!(Control::parse("garbage"), None);
+        assert_eq!(Control::parse("2\n0\nc/3f2a\n"), None);
+        assert_eq!(
+            Control::parse("1\n0\nc/3f2a\n").map(|control| control.started),
+            Some(0)
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use crate::error::{self, Error};
+use crate::metadata::{self, ArtifactMetadata};
+use crate::options::MessageFormat;
+use crate::triage::crash_signature;
+use crate::utils;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+/// Version of the messages, bumped on any incompatible change to `schema/messages.json`.
+pub const MESSAGE_VERSION: u32 = 1;
+
+/// Outcome of a command, printed as a line of JSON by `--message-format json`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message {
+    BuildFinished {
+        targets: Vec<String>,
+    },
+    TargetList {
+        targets: Vec<String>,
+    },
+    RunFinished {
+        targets: Vec<String>,
+        /// Artifacts written while fuzzing
+        artifacts: Vec<PathBuf>,
+    },
+    CoverageFinished {
+        target: String,
+        profdata: PathBuf,
+    },
+    CminFinished {
+        target: String,
+        corpus: PathBuf,
+        inputs_before: usize,
+        inputs_after: usize,
+    },
+    TminFinished {
+        target: String,
+        test_case: PathBuf,
+        minimized: Option<PathBuf>,
+    },
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_kind: Option<&'static str>,
+    #[serde(flatten)]
+    message: &'a Message,
+}
+
+fn to_json(message: &Message, result: &Result<()>) -> Result<String> {
+    Ok(serde_json::to_string(&Envelope {
+        version: MESSAGE_VERSION,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        error_kind: result
+            .as_ref()
+            .err()
+            .and_then(error::find)
+            .map(error::Error::kind),
+        message,
+    })?)
+}
+
+/// Where the messages of `--message-format json` go, once [`reserve_stdout`]
+/// took stdout for them.
+static JSON_OUT: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Gives stdout to the messages of `--message-format json`, as Cargo does:
+/// what the command and the tools it runs print there goes to stderr instead.
+/// Only on Unix, elsewhere the messages share stdout.
+pub fn reserve_stdout() -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd;
+        io::stdout().flush()?;
+        let stdout = io::stdout()
+            .as_fd()
+            .try_clone_to_owned()
+            .context("failed to duplicate stdout")?;
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(io::Error::last_os_error()).context("failed to redirect stdout");
+        }
+        let _ = JSON_OUT.set(Mutex::new(File::from(stdout)));
+    }
+    Ok(())
+}
+
+fn print_json(line: &str) -> Result<()> {
+    match JSON_OUT.get() {
+        Some(out) => writeln!(out.lock().unwrap(), "{}", line)?,
+        None => println!("{}", line),
+    }
+    Ok(())
+}
+
+/// Prints the message describing the outcome of a command when `format` asks
+/// for JSON, or the annotations of its failure when it asks for GitHub
+/// workflow commands, and hands back the `result` of the command. The files of
+/// the annotations are located from `fuzz_dir`.
+pub fn report(
+    format: MessageFormat,
+    fuzz_dir: &Path,
+    result: Result<()>,
+    message: impl FnOnce() -> Result<Message>,
+) -> Result<()> {
+    match format {
+        MessageFormat::Human => {}
+        MessageFormat::Json => {
+            // The error of the command matters more than the one of its message.
+            let message = match message() {
+                Ok(message) => message,
+                Err(e) => return result.and(Err(e)),
+            };
+            print_json(&to_json(&message, &result)?)?;
+        }
+        MessageFormat::Github => {
+            if let Err(e) = &result {
+                let root = utils::command_output(
+                    Command::new("git")
+                        .arg("-C")
+                        .arg(fuzz_dir)
+                        .args(["rev-parse", "--show-toplevel"]),
+                )
+                .map(PathBuf::from);
+                for annotation in annotations(e, fuzz_dir, root.as_deref()) {
+                    println!("{}", annotation);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// A GitHub Actions workflow command annotating a finding or a failure.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Annotation {
+    pub(crate) warning: bool,
+    pub(crate) title: String,
+    pub(crate) message: String,
+    /// Relative to the root of the repository, as GitHub locates annotations
+    pub(crate) file: Option<String>,
+    pub(crate) line: Option<u32>,
+    pub(crate) col: Option<u32>,
+}
+
+impl std::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "::{}", if self.warning { "warning" } else { "error" })?;
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        if let Some(line) = self.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(col) = self.col {
+            properties.push(format!("col={}", col));
+        }
+        properties.push(format!("title={}", escape_property(&self.title)));
+        write!(
+            f,
+            " {}::{}",
+            properties.join(","),
+            escape_data(&self.message)
+        )
+    }
+}
+
+/// The annotations of the failure of a command: one per artifact of a crash,
+/// located where the crash happened when its signature says so, and one for
+/// any other error. Crashes are located in the repository at `root`, if any.
+fn annotations(error: &anyhow::Error, fuzz_dir: &Path, root: Option<&Path>) -> Vec<Annotation> {
+    let Some(Error::CrashFound { targets, artifacts }) = error::find(error) else {
+        return vec![Annotation {
+            title: format!(
+                "move-fuzz: {}",
+                error::find(error).map_or("error", Error::kind)
+            ),
+            message: format!("{:#}", error),
+            ..Annotation::default()
+        }];
+    };
+    if artifacts.is_empty() {
+        return vec![Annotation {
+            title: format!("Crash in fuzz target {}", targets.join(", ")),
+            message: format!("{:#}", error),
+            ..Annotation::default()
+        }];
+    }
+    artifacts
+        .iter()
+        .map(|artifact| crash_annotation(targets, artifact, fuzz_dir, root))
+        .collect()
+}
+
+fn crash_annotation(
+    targets: &[String],
+    artifact: &Path,
+    fuzz_dir: &Path,
+    root: Option<&Path>,
+) -> Annotation {
+    let meta = ArtifactMetadata::load(artifact).ok();
+    let target = meta
+        .as_ref()
+        .map(|meta| meta.target.clone())
+        .unwrap_or_else(|| targets.join(", "));
+    let class = metadata::class(artifact);
+    let signature = meta
+        .as_ref()
+        .and_then(|meta| crash_signature(&meta.sanitizer_output));
+    let located = signature
+        .as_deref()
+        .and_then(location)
+        .and_then(|(file, line, col)| {
+            Some((repo_path(Path::new(&file), fuzz_dir, root?)?, line, col))
+        });
+    let (file, line, col) = match located {
+        Some((file, line, col)) => (Some(file), Some(line), col),
+        None => (None, None, None),
+    };
+    let mut message = String::new();
+    if let Some(signature) = &signature {
+        message.push_str(signature);
+        message.push('\n');
+    }
+    message.push_str(&format!("Artifact: {}", artifact.display()));
+    Annotation {
+        // Resource exhaustion is worth a look, but is not a bug of the package.
+        warning: matches!(class.as_deref(), Some("timeout" | "oom" | "slow-unit")),
+        title: format!(
+            "{} in fuzz target {}",
+            match class.as_deref() {
+                Some("leak") => "Memory leak",
+                Some("timeout") => "Timeout",
+                Some("oom") => "Out of memory",
+                Some("slow-unit") => "Slow input",
+                _ => "Crash",
+            },
+            target
+        ),
+        message,
+        file,
+        line,
+        col,
+    }
+}
+
+/// The source location in a crash signature, as in `panic at src/lib.rs:3:5: ...`.
+fn location(signature: &str) -> Option<(String, u32, Option<u32>)> {
+    signature.split_whitespace().find_map(|word| {
+        let word = word.trim_matches(|c| matches!(c, '\'' | ',' | ':' | '(' | ')'));
+        let mut parts = word.split(':');
+        let file = parts.next()?;
+        if !(file.ends_with(".rs") || file.ends_with(".move")) {
+            return None;
+        }
+        let line = parts.next()?.parse().ok()?;
+        let col = parts.next().and_then(|col| col.parse().ok());
+        Some((file.to_owned(), line, col))
+    })
+}
+
+/// `file` as a path from the repository at `root`, or `None` if it is outside.
+/// Relative paths, as panics print them, are from the fuzz crate: a workspace
+/// of its own.
+fn repo_path(file: &Path, fuzz_dir: &Path, root: &Path) -> Option<String> {
+    let file = fuzz_dir.join(file);
+    let file = file.canonicalize().unwrap_or(file);
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_owned());
+    let relative = file.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::schema;
+    use anyhow::anyhow;
+
+    #[test]
+    fn annotate_failures_for_github() {
+        let dir = tempfile::tempdir().unwrap();
+        let fuzz_dir = dir.path().join("fuzz");
+        std::fs::create_dir(&fuzz_dir).unwrap();
+        let crash = dir.path().join("crash-1");
+        let timeout = dir.path().join("timeout-2");
+        std::fs::write(&crash, b"").unwrap();
+        let meta = ArtifactMetadata {
+            target: "a".to_owned(),
+            class: Some("crash".to_owned()),
+            timestamp: 0,
+            git_commit: None,
+            build: Default::default(),
+            libfuzzer_args: vec![],
+            sanitizer_output: "thread '<unnamed>' panicked at src/lib.rs:12:5:\n\
+                attempt to subtract with overflow"
+                .to_owned(),
+            rustc_version: None,
+            move_version: None,
+            elapsed_ms: None,
+            peak_rss_mb: None,
+            backtrace: None,
+        };
+        meta.write(&crash).unwrap();
+
+        let error = error::Error::crash("a", vec![crash.clone(), timeout.clone()]).into();
+        let lines: Vec<_> = annotations(&error, &fuzz_dir, Some(dir.path()))
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                format!(
+                    "::error file=fuzz/src/lib.rs,line=12,col=5,title=Crash in fuzz target a::\
+                    panic at src/lib.rs:12:5: attempt to subtract with overflow%0A\
+                    Artifact: {}",
+                    crash.display()
+                ),
+                format!(
+                    "::warning title=Timeout in fuzz target a::Artifact: {}",
+                    timeout.display()
+                ),
+            ]
+        );
+        assert_eq!(
+            annotations(&anyhow!("boom"), &fuzz_dir, None)
+                .remove(0)
+                .to_string(),
+            "::error title=move-fuzz%3A error::boom"
+        );
+    }
+
+    #[test]
+    fn annotations_are_located_from_the_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let fuzz_dir = dir.path().join("language").join("fuzz");
+        std::fs::create_dir_all(fuzz_dir.join("fuzz_targets")).unwrap();
+        std::fs::write(fuzz_dir.join("fuzz_targets").join("a.rs"), "").unwrap();
+
+        assert_eq!(
+            repo_path(Path::new("fuzz_targets/a.rs"), &fuzz_dir, dir.path()).as_deref(),
+            Some("language/fuzz/fuzz_targets/a.rs")
+        );
+        assert_eq!(
+            repo_path(&fuzz_dir.join("src/lib.rs"), &fuzz_dir, dir.path()).as_deref(),
+            Some("language/fuzz/src/lib.rs")
+        );
+        let registry = tempfile::tempdir().unwrap();
+        assert_eq!(
+            repo_path(&registry.path().join("lib.rs"), &fuzz_dir, dir.path()),
+            None
+        );
+    }
+
+    #[test]
+    fn messages_follow_the_schema() {
+        let schema: serde_json::Value = serde_json::from_str(schema::MESSAGES).unwrap();
+        assert_eq!(schema["properties"]["version"]["const"], MESSAGE_VERSION);
+        let messages = [
+            Message::BuildFinished {
+                targets: vec!["a".to_owned()],
+            },
+            Message::TargetList { targets: vec![] },
+            Message::RunFinished {
+                targets: vec!["a".to_owned()],
+                artifacts: vec![PathBuf::from("fuzz/artifacts/a/crash-1")],
+            },
+            Message::CoverageFinished {
+                target: "a".to_owned(),
+                profdata: PathBuf::from("fuzz/coverage/a/coverage.profdata"),
+            },
+            Message::CminFinished {
+                target: "a".to_owned(),
+                corpus: PathBuf::from("fuzz/corpus/a"),
+                inputs_before: 10,
+                inputs_after: 4,
+            },
+            Message::TminFinished {
+                target: "a".to_owned(),
+                test_case: PathBuf::from("crash-1"),
+                minimized: None,
+            },
+        ];
+
+        let common = schema["properties"].as_object().unwrap();
+        for (message, result) in messages.iter().zip(
+            [
+                Ok(()),
+                Err(anyhow!("boom")),
+                Err(error::Error::crash("a", vec![]).into()),
+            ]
+            .iter()
+            .cycle(),
+        ) {
+            let json: serde_json::Value =
+                serde_json::from_str(&to_json(message, result).unwrap()).unwrap();
+            let reason = json["reason"].as_str().unwrap();
+            let variant = schema["oneOf"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|v| v["properties"]["reason"]["const"] == reason)
+                .unwrap_or_else(|| panic!("`{}` is not in the schema", reason));
+            let properties = variant["properties"].as_object().unwrap();
+            for field in json.as_object().unwrap().keys() {
+                assert!(
+                    common.contains_key(field) || properties.contains_key(field),
+                    "`{}` of `{}` is not in the schema",
+                    field,
+                    reason
+                );
+            }
+            if let Some(kind) = json.get("error_kind") {
+                assert!(common["error_kind"]["enum"]
+                    .as_array()
+                    .unwrap()
+                    .contains(kind));
+            }
+            for field in variant["required"].as_array().unwrap() {
+                assert!(json.get(field.as_str().unwrap()).is_some());
+            }
+        }
+    }
+}
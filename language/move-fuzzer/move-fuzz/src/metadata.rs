@@ -0,0 +1,296 @@
+use crate::options::BuildOptions;
+use crate::schema::Versioned;
+use crate::symbolize;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Suffix of the file written next to each artifact, e.g. `crash-1234.meta.json`.
+pub const META_SUFFIX: &str = ".meta.json";
+
+/// Lines of fuzzer output kept to extract the crash report from.
+pub const OUTPUT_TAIL_LINES: usize = 1000;
+
+/// Version of `schema/artifact-metadata.json` the metadata files follow.
+pub const METADATA_VERSION: u32 = 1;
+
+/// Everything needed to reproduce an artifact long after it was found.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactMetadata {
+    pub target: String,
+    /// What libFuzzer found, `crash`, `leak`, `timeout`, `oom` or `slow-unit`.
+    pub class: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Commit of the package, if it is in a git repository.
+    pub git_commit: Option<String>,
+    pub build: BuildOptions,
+    /// Arguments the fuzz target binary was run with.
+    pub libfuzzer_args: Vec<String>,
+    /// The sanitizer report or panic message of the crash.
+    pub sanitizer_output: String,
+    /// Output of `rustc --version`.
+    pub rustc_version: Option<String>,
+    /// Output of `move --version`.
+    pub move_version: Option<String>,
+    /// Time the input took to run on its own, for `run --hunt-timeouts` findings.
+    pub elapsed_ms: Option<u64>,
+    /// Peak memory use of the input on its own, for `run --hunt-oom` findings.
+    pub peak_rss_mb: Option<u64>,
+    /// The backtrace of the crash as the target printed it, see `fmt --backtrace`.
+    pub backtrace: Option<String>,
+}
+
+impl ArtifactMetadata {
+    pub fn load(artifact: &Path) -> Result<Self> {
+        let path = sidecar_path(artifact);
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data).with_context(|| {
+            format!(
+                "could not decode the artifact metadata at {}",
+                path.display()
+            )
+        })
+    }
+
+    pub fn write(&self, artifact: &Path) -> Result<PathBuf> {
+        let path = sidecar_path(artifact);
+        let data = serde_json::to_string_pretty(&Versioned {
+            version: METADATA_VERSION,
+            value: self,
+        })?;
+        fs::write(&path, data).with_context(|| format!("failed to write to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+pub fn sidecar_path(artifact: &Path) -> PathBuf {
+    let mut path = OsString::from(artifact.as_os_str());
+    path.push(META_SUFFIX);
+    PathBuf::from(path)
+}
+
+pub fn is_sidecar(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(META_SUFFIX)
+}
+
+/// The prefixes libFuzzer names artifacts with, e.g. `crash-<sha1>`.
+const CLASSES: [&str; 5] = ["crash", "leak", "timeout", "oom", "slow-unit"];
+
+/// What libFuzzer found in `artifact`, from its metadata or else from the
+/// libFuzzer name it may still have.
+pub fn class(artifact: &Path) -> Option<String> {
+    if let Ok(meta) = ArtifactMetadata::load(artifact) {
+        if meta.class.is_some() {
+            return meta.class;
+        }
+    }
+    libfuzzer_class(&artifact.file_name()?.to_string_lossy()).map(str::to_owned)
+}
+
+/// The class in an artifact name given by libFuzzer.
+pub fn libfuzzer_class(name: &str) -> Option<&'static str> {
+    CLASSES.into_iter().find(|class| {
+        name.strip_prefix(class)
+            .is_some_and(|rest| rest.starts_with('-'))
+    })
+}
+
+/// A template for the names of artifacts, from `run --artifact-name`.
+///
+/// `{target}`, `{class}`, `{hash}` and `{timestamp}` stand for the fuzz target,
+/// the libFuzzer class of the artifact, the first 12 hex digits of the SHA-1 of
+/// the input and the seconds since the Unix epoch.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameTemplate(String);
+
+impl NameTemplate {
+    const PLACEHOLDERS: [&'static str; 4] = ["target", "class", "hash", "timestamp"];
+
+    pub fn parse(template: &str) -> Result<Self> {
+        if template.contains(['/', '\\']) {
+            bail!(
+                "artifact name template {:?} must not contain a path separator",
+                template
+            );
+        }
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').with_context(|| {
+                format!("unclosed `{{` in artifact name template {:?}", template)
+            })?;
+            let placeholder = &rest[start + 1..start + end];
+            if !Self::PLACEHOLDERS.contains(&placeholder) {
+                bail!(
+                    "unknown placeholder `{{{}}}` in artifact name template {:?}, expected one of: {}",
+                    placeholder,
+                    template,
+                    Self::PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                );
+            }
+            rest = &rest[start + end + 1..];
+        }
+        Ok(NameTemplate(template.to_owned()))
+    }
+
+    /// The name of the input `data` that `target` failed on with `class`.
+    pub fn render(&self, target: &str, class: Option<&str>, data: &[u8], timestamp: u64) -> String {
+        let hash = sha1_smol::Sha1::from(data).digest().to_string();
+        self.0
+            .replace("{target}", target)
+            .replace("{class}", class.unwrap_or("crash"))
+            .replace("{hash}", &hash[..12])
+            .replace("{timestamp}", &timestamp.to_string())
+    }
+}
+
+/// The arguments after `--` in a `cargo run` command line.
+pub fn libfuzzer_args<'a>(args: impl Iterator<Item = &'a std::ffi::OsStr>) -> Vec<String> {
+    args.skip_while(|arg| *arg != "--")
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// The last lines of the `output` of a fuzzer, as many as runs keep.
+pub fn tail(output: &str) -> VecDeque<String> {
+    let lines: Vec<_> = output.lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Cuts the crash report out of the last lines of fuzzer output: everything
+/// from the first sanitizer error or panic on, or the whole tail if there is
+/// none, symbolized.
+pub fn crash_report(tail: &VecDeque<String>) -> String {
+    let start = tail
+        .iter()
+        .position(|line| line.contains("ERROR: ") || line.contains("panicked at "))
+        .unwrap_or(0);
+    let report = tail
+        .iter()
+        .skip(start)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    symbolize::symbolize(&report)
+}
+
+/// Reads the duration libFuzzer reports after running a single input,
+/// from its `Executed <file> in <n> ms` line.
+pub fn executed_ms(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        line.strip_prefix("Executed ")?
+            .rsplit_once(" in ")?
+            .1
+            .strip_suffix(" ms")?
+            .parse()
+            .ok()
+    })
+}
+
+/// Reads the peak RSS libFuzzer prints in its final stats, or in the report
+/// of an input going over `-rss_limit_mb`.
+pub fn peak_rss_mb(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        if let Some(peak) = line.trim().strip_prefix("stat::peak_rss_mb:") {
+            return peak.trim().parse().ok();
+        }
+        line.split("out-of-memory (used: ")
+            .nth(1)?
+            .split("Mb")
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report_and_args() {
+        let tail: VecDeque<_> = [
+            "#1024 pulse ft: 10",
+            "thread '<unnamed>' panicked at src/lib.rs:1:1:",
+            "boom",
+            "==1== ERROR: libFuzzer: deadly signal",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(
+            crash_report(&tail),
+            "thread '<unnamed>' panicked at src/lib.rs:1:1:\nboom\n==1== ERROR: libFuzzer: deadly signal"
+        );
+
+        let args = ["run", "--bin", "a", "--", "-artifact_prefix=x/", "-runs=1"];
+        assert_eq!(
+            libfuzzer_args(args.iter().map(std::ffi::OsStr::new)),
+            ["-artifact_prefix=x/", "-runs=1"]
+        );
+        assert!(is_sidecar(&sidecar_path(Path::new("crash-1"))));
+        assert_eq!(
+            executed_ms("Running: slow-1\nExecuted slow-1 in 1523 ms\n***"),
+            Some(1523)
+        );
+        assert_eq!(
+            peak_rss_mb("stat::number_of_executed_units: 1\nstat::peak_rss_mb:              917"),
+            Some(917)
+        );
+        assert_eq!(
+            peak_rss_mb("==9== ERROR: libFuzzer: out-of-memory (used: 4100Mb; exceeds: 4096Mb)"),
+            Some(4100)
+        );
+    }
+
+    #[test]
+    fn name_artifacts() {
+        assert_eq!(libfuzzer_class("slow-unit-0a1b"), Some("slow-unit"));
+        assert_eq!(libfuzzer_class("crashy"), None);
+        let template = NameTemplate::parse("{target}-{class}-{hash}-{timestamp}").unwrap();
+        assert_eq!(
+            template.render("t", Some("oom"), b"abc", 1700000000),
+            "t-oom-a9993e364706-1700000000"
+        );
+        assert!(NameTemplate::parse("{target}/{hash}").is_err());
+        assert_eq!(
+            NameTemplate::parse("{name}").unwrap_err().to_string(),
+            "unknown placeholder `{name}` in artifact name template \"{name}\", \
+            expected one of: {target}, {class}, {hash}, {timestamp}"
+        );
+    }
+
+    #[test]
+    fn metadata_follows_the_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("crash-1");
+        let meta = ArtifactMetadata {
+            target: "a".to_owned(),
+            class: Some("crash".to_owned()),
+            timestamp: 1,
+            git_commit: None,
+            build: Default::default(),
+            libfuzzer_args: vec!["-runs=1".to_owned()],
+            sanitizer_output: "boom".to_owned(),
+            rustc_version: None,
+            move_version: None,
+            elapsed_ms: Some(3),
+            peak_rss_mb: None,
+            backtrace: Some("   0: bank::deposit".to_owned()),
+        };
+        let path = meta.write(&artifact).unwrap();
+        assert_eq!(ArtifactMetadata::load(&artifact).unwrap(), meta);
+
+        let schema = serde_json::from_str(crate::schema::ARTIFACT_METADATA).unwrap();
+        let json = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        crate::schema::assert_follows(&schema, &json, METADATA_VERSION);
+    }
+}
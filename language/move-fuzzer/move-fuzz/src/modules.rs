@@ -0,0 +1,182 @@
+//! Compiled Move modules and scripts, read from the `.mv` files `move build`
+//! writes under `build/<package>/`, for the commands that look into their
+//! functions, structs and constants.
+
+use anyhow::{anyhow, Context, Result};
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{
+    Ability, AbilitySet, CompiledModule, CompiledScript, SignatureToken, StructHandleIndex,
+};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reads the compiled module at `path`.
+pub fn read_module(path: &Path) -> Result<CompiledModule> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    CompiledModule::deserialize(&bytes)
+        .map_err(|e| anyhow!("{}", e))
+        .with_context(|| format!("could not deserialize the module {}", path.display()))
+}
+
+/// Reads the compiled script at `path`.
+pub fn read_script(path: &Path) -> Result<CompiledScript> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    CompiledScript::deserialize(&bytes)
+        .map_err(|e| anyhow!("{}", e))
+        .with_context(|| format!("could not deserialize the script {}", path.display()))
+}
+
+/// Where `move build` writes the package `name` built in `package`.
+pub fn build_dir(package: &Path, name: &str) -> PathBuf {
+    package.join("build").join(name)
+}
+
+/// The `.mv` files of the modules of the package built in `build_dir`, sorted
+/// by path, followed by those of its dependencies if `dependencies` is set.
+pub fn module_files(build_dir: &Path, dependencies: bool) -> Result<Vec<PathBuf>> {
+    let modules_dir = build_dir.join("bytecode_modules");
+    let mut files = mv_files(&modules_dir)?;
+    if dependencies {
+        let dependencies_dir = modules_dir.join("dependencies");
+        if dependencies_dir.is_dir() {
+            let mut dirs: Vec<_> = fs::read_dir(&dependencies_dir)
+                .with_context(|| {
+                    format!(
+                        "failed to read directory entries of {}",
+                        dependencies_dir.display()
+                    )
+                })?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<_>>()?;
+            dirs.sort();
+            for dir in dirs {
+                files.extend(mv_files(&dir)?);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn mv_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension() == Some(OsStr::new("mv")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The names of `abilities`, as declared in Move, e.g. `copy`.
+pub fn ability_names(abilities: AbilitySet) -> Vec<String> {
+    abilities
+        .into_iter()
+        .map(|ability| {
+            match ability {
+                Ability::Copy => "copy",
+                Ability::Drop => "drop",
+                Ability::Store => "store",
+                Ability::Key => "key",
+            }
+            .to_owned()
+        })
+        .collect()
+}
+
+/// The struct `handle` of `module` names, as in `0x1::string::String`.
+pub fn struct_name(module: &CompiledModule, handle: StructHandleIndex) -> String {
+    let handle = module.struct_handle_at(handle);
+    let declaring = module.module_id_for_handle(module.module_handle_at(handle.module));
+    format!(
+        "{}::{}",
+        declaring.short_str_lossless(),
+        module.identifier_at(handle.name)
+    )
+}
+
+/// `token` written as a Move type, with type parameters named `Ty0`, `Ty1`...
+/// as `move disassemble` names them.
+pub fn type_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Bool => "bool".to_owned(),
+        SignatureToken::U8 => "u8".to_owned(),
+        SignatureToken::U16 => "u16".to_owned(),
+        SignatureToken::U32 => "u32".to_owned(),
+        SignatureToken::U64 => "u64".to_owned(),
+        SignatureToken::U128 => "u128".to_owned(),
+        SignatureToken::U256 => "u256".to_owned(),
+        SignatureToken::Address => "address".to_owned(),
+        SignatureToken::Signer => "signer".to_owned(),
+        SignatureToken::Vector(elem) => format!("vector<{}>", type_name(module, elem)),
+        SignatureToken::Struct(handle) => struct_name(module, *handle),
+        SignatureToken::StructInstantiation(handle, type_args) => format!(
+            "{}<{}>",
+            struct_name(module, *handle),
+            type_args
+                .iter()
+                .map(|ty| type_name(module, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SignatureToken::Reference(ty) => format!("&{}", type_name(module, ty)),
+        SignatureToken::MutableReference(ty) => format!("&mut {}", type_name(module, ty)),
+        SignatureToken::TypeParameter(index) => format!("Ty{}", index),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use move_ir_compiler::Compiler;
+
+    /// A module compiled from Move IR, standing in for one `move build` wrote.
+    pub(crate) fn compile(code: &str) -> CompiledModule {
+        Compiler::new(vec![]).into_compiled_module(code).unwrap()
+    }
+
+    #[test]
+    fn read_modules_of_a_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = build_dir(dir.path(), "bank");
+        let modules = build.join("bytecode_modules");
+        fs::create_dir_all(modules.join("dependencies").join("Std")).unwrap();
+        let module = compile("module 0x42.bank { public balance(): u64 { label b0: return 0; } }");
+        let mut bytes = Vec::new();
+        module.serialize(&mut bytes).unwrap();
+        fs::write(modules.join("bank.mv"), &bytes).unwrap();
+        fs::write(modules.join("dependencies/Std/signer.mv"), &bytes).unwrap();
+        fs::write(modules.join("bank.txt"), "").unwrap();
+
+        assert_eq!(
+            module_files(&build, false).unwrap(),
+            [modules.join("bank.mv")]
+        );
+        assert_eq!(module_files(&build, true).unwrap().len(), 2);
+        let read = read_module(&modules.join("bank.mv")).unwrap();
+        assert_eq!(read.self_id().short_str_lossless(), "0x42::bank");
+        assert!(read_module(&modules.join("bank.txt")).is_err());
+    }
+
+    #[test]
+    fn name_types_as_declared() {
+        let module = compile(
+            "module 0x42.bank {
+                struct Pool<phantom T> has store { reserve: u64 }
+                public pool<T>(p: &mut Self.Pool<T>, v: vector<u8>) { label b0: return; }
+            }",
+        );
+        let signature = module.signature_at(module.function_handles[0].parameters);
+        let types: Vec<_> = signature
+            .0
+            .iter()
+            .map(|ty| type_name(&module, ty))
+            .collect();
+        assert_eq!(types, ["&mut 0x42::bank::Pool<Ty0>", "vector<u8>"]);
+        assert_eq!(ability_names(module.struct_handles[0].abilities), ["store"]);
+    }
+}
@@ -0,0 +1,265 @@
+//! The Move package under test, which `init` finds by walking up from the
+//! current directory to the first `Move.toml` that is not the one of a fuzz
+//! package, i.e. has no `[fuzz]` table.
+//!
+//! The fuzz package `init` generates depends on it locally, assigns the named
+//! addresses it leaves unassigned (`"_"`), and the Move script of the first
+//! target `use`s its modules.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where unassigned named addresses are published in the fuzz package when the
+/// package has no `[dev-addresses]` for them, the next ones for the next names.
+const FIRST_PLACEHOLDER_ADDRESS: u64 = 0x42;
+
+/// A Move package, as its `Move.toml` declares it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct MovePackage {
+    /// The directory of the `Move.toml`
+    pub root: PathBuf,
+    pub name: String,
+    /// The named addresses, `None` for the unassigned ones
+    pub addresses: BTreeMap<String, Option<String>>,
+    pub dev_addresses: BTreeMap<String, String>,
+    /// The address and name of the modules in `sources`, sorted
+    pub modules: Vec<(String, String)>,
+}
+
+impl MovePackage {
+    /// The package of the first `Move.toml` in `dir` or its ancestors, `None`
+    /// if there is none.
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        let mut dir = dir.to_owned();
+        loop {
+            let manifest_path = dir.join("Move.toml");
+            if manifest_path.is_file() {
+                let manifest: toml::Value = toml::from_str(
+                    &fs::read_to_string(&manifest_path)
+                        .with_context(|| format!("failed to read {}", manifest_path.display()))?,
+                )
+                .with_context(|| format!("could not decode {}", manifest_path.display()))?;
+                if manifest.get("fuzz").is_none() {
+                    return Self::from_manifest(&dir, &manifest).map(Some);
+                }
+            }
+            if !dir.pop() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// The package of the current directory or one of its ancestors.
+    pub fn find_from_current_dir() -> Result<Option<Self>> {
+        Self::find(&env::current_dir()?)
+    }
+
+    fn from_manifest(root: &Path, manifest: &toml::Value) -> Result<Self> {
+        let name = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(toml::Value::as_str)
+            .with_context(|| format!("{}/Move.toml has no package name", root.display()))?;
+        let table = |key: &str| -> BTreeMap<String, String> {
+            manifest
+                .get(key)
+                .and_then(toml::Value::as_table)
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, address)| Some((name.clone(), address.as_str()?.to_owned())))
+                .collect()
+        };
+        let addresses = table("addresses")
+            .into_iter()
+            .map(|(name, address)| (name, Some(address).filter(|a| a != "_")))
+            .collect();
+
+        let mut modules = Vec::new();
+        for source in sources(&root.join("sources"))? {
+            let text = fs::read_to_string(&source)
+                .with_context(|| format!("failed to read {}", source.display()))?;
+            modules.extend(module_decls(&text));
+        }
+        modules.sort();
+        modules.dedup();
+
+        Ok(MovePackage {
+            root: root.to_owned(),
+            name: name.to_owned(),
+            addresses,
+            dev_addresses: table("dev-addresses"),
+            modules,
+        })
+    }
+
+    /// The `[addresses]` the fuzz package must assign: the unassigned ones, at
+    /// their dev address or a placeholder. The assigned ones are inherited.
+    pub fn unassigned_addresses(&self) -> Vec<(String, String)> {
+        let mut placeholder = FIRST_PLACEHOLDER_ADDRESS;
+        self.addresses
+            .iter()
+            .filter(|(_, address)| address.is_none())
+            .map(|(name, _)| {
+                let address = self.dev_addresses.get(name).cloned().unwrap_or_else(|| {
+                    placeholder += 1;
+                    format!("{:#x}", placeholder - 1)
+                });
+                (name.clone(), address)
+            })
+            .collect()
+    }
+
+    /// The `use` declarations of a script calling into the modules of the package.
+    pub fn script_uses(&self) -> String {
+        self.modules
+            .iter()
+            .map(|(address, module)| format!("    use {}::{};\n", address, module))
+            .collect()
+    }
+}
+
+/// The address and name of the modules `text` declares, as
+/// `module <address>::<name>` or as `module <name>` in an
+/// `address <address> { ... }` block.
+fn module_decls(text: &str) -> Vec<(String, String)> {
+    let code: String = text
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let code = code
+        .replace('{', " { ")
+        .replace('}', " } ")
+        .replace(';', " ; ");
+    let mut tokens = code.split_whitespace().peekable();
+    let is_name = |token: &&str| !["{", "}", ";"].contains(token);
+    let mut decls = Vec::new();
+    let mut depth = 0usize;
+    // The address of the `address` block being read and the depth of its body.
+    let mut block: Option<(&str, usize)> = None;
+    while let Some(token) = tokens.next() {
+        match token {
+            "{" => depth += 1,
+            "}" => {
+                depth = depth.saturating_sub(1);
+                if block.is_some_and(|(_, body)| depth < body) {
+                    block = None;
+                }
+            }
+            "address" => {
+                if let Some(address) = tokens.next_if(is_name) {
+                    if tokens.peek() == Some(&"{") {
+                        block = Some((address, depth + 1));
+                    }
+                }
+            }
+            "module" => {
+                let Some(decl) = tokens.next_if(is_name) else {
+                    continue;
+                };
+                let decl = match decl.split_once("::") {
+                    Some(decl) => Some(decl),
+                    None => block.map(|(address, _)| (address, decl)),
+                };
+                if let Some((address, module)) = decl {
+                    decls.push((address.to_owned(), module.to_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+    decls
+}
+
+/// The `.move` files under `dir`, none if it does not exist.
+fn sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)
+            .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "move") {
+                sources.push(path);
+            }
+        }
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_the_package_above() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("bank");
+        fs::create_dir_all(root.join("sources/coins")).unwrap();
+        fs::create_dir_all(root.join("fuzz")).unwrap();
+        fs::write(
+            root.join("Move.toml"),
+            r#"[package]
+name = "Bank"
+version = "0.0.0"
+
+[addresses]
+std = "0x1"
+bank = "_"
+admin = "_"
+
+[dev-addresses]
+admin = "0xad"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("sources/bank.move"),
+            "module bank::deposit {\n    public fun f() {}\n}\n\nmodule bank::withdraw {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("sources/coins/coin.move"),
+            "// module not::this\nmodule 0xc0::coin{\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("sources/coins/legacy.move"),
+            "address 0xc1 {\nmodule gold {\n    spec module {}\n}\nmodule silver {}\n}\n",
+        )
+        .unwrap();
+        // The fuzz package is no package under test.
+        fs::write(
+            root.join("fuzz/Move.toml"),
+            "[package]\nname = \"bank_target\"\n\n[fuzz.targets.deposit]\n",
+        )
+        .unwrap();
+
+        let package = MovePackage::find(&root.join("fuzz")).unwrap().unwrap();
+        assert_eq!(package.root, root);
+        assert_eq!(package.name, "Bank");
+        assert_eq!(package.addresses["std"].as_deref(), Some("0x1"));
+        assert_eq!(
+            package.unassigned_addresses(),
+            [
+                ("admin".to_owned(), "0xad".to_owned()),
+                ("bank".to_owned(), "0x42".to_owned())
+            ]
+        );
+        assert_eq!(
+            package.script_uses(),
+            "    use 0xc0::coin;\n    use 0xc1::gold;\n    use 0xc1::silver;\n    use bank::deposit;\n    use bank::withdraw;\n"
+        );
+
+        assert_eq!(MovePackage::find(dir.path()).unwrap(), None);
+    }
+}
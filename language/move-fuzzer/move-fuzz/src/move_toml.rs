@@ -0,0 +1,1112 @@
+//! The `[fuzz]` section of the `Move.toml` of the fuzz package.
+//!
+//! ```toml
+//! [fuzz]
+//! natives = ["stdlib", "table"]
+//! signers = ["0xa11ce", "0xb0b"]
+//! addresses = ["pool", "published"]
+//!
+//! [fuzz.defaults]
+//! sanitizer = "none"
+//!
+//! [fuzz.sanitizer.address]
+//! detect_leaks = false
+//! allocator_may_return_null = true
+//! suppressions = "asan.supp"
+//!
+//! [fuzz.targets.deposit]
+//! entry = "0x1::bank::deposit"
+//! description = "Deposits into random accounts"
+//! tags = ["defi", "vm"]
+//! seeds = ["seeds/deposit"]
+//! max_len = 65536
+//! args = ["-use_value_profile=1"]
+//!
+//! [fuzz.targets.verify]
+//! input = "bytecode"
+//!
+//! [fuzz.targets.deposit.aborts]
+//! "bank::withdraw" = [1, 2]
+//! "bank::freeze" = { codes = [3], success = false }
+//!
+//! [fuzz.targets.deposit.sanitizer.leak]
+//! detect_leaks = true
+//!
+//! [fuzz.targets.deposit.env]
+//! MOVE_VM_PARANOID = 1
+//! NATIVES_CONFIG = { path = "natives.toml" }
+//!
+//! [fuzz.targets.deserialize.build]
+//! sanitizer = "memory"
+//! features = ["testing"]
+//!
+//! [fuzz.quota]
+//! max_corpus_size = "2GiB"
+//! max_artifacts_per_bucket = 10
+//! max_coverage_history = 100
+//! eviction = "least-features"
+//! ```
+//!
+//! `[fuzz.defaults]` holds option values like `[package.metadata.move-fuzz.defaults]`
+//! in the fuzz manifest, which wins over it. `init` and `add` declare their
+//! targets under `[fuzz.targets]`. `natives` selects the packages of native
+//! functions the harnesses register in the VM, by default `stdlib` and `nursery`;
+//! natives of the package under test go through `libfuzzer::fuzz_natives!`.
+//! `signers` are the accounts signing the calls of the harnesses, and
+//! `addresses` where their address arguments are drawn from: the `pool` of
+//! signers, `arbitrary` addresses, the addresses modules are `published` at or
+//! `reserved` ones like `0x1`. The `aborts` of a sequence target are the abort
+//! codes its entry functions may abort with, any other failure being a finding,
+//! and whether they may also succeed, as they may by default. `max_len` is the
+//! size of the largest input of a target, which `run`, `cmin` and `tmin` pass
+//! to libFuzzer as `-max_len` unless given another one. `[fuzz.sanitizer.<kind>]`
+//! holds the runtime options of the `address`, `leak`, `memory`, `thread` and
+//! `undefined` sanitizers, passed in `ASAN_OPTIONS` and the like to the targets
+//! `run`, `tmin`, `cmin` and `repro` run, and a target can add its own under
+//! `[fuzz.targets.<name>.sanitizer.<kind>]`; a relative `suppressions` file is
+//! from the fuzz directory. The `env` of a target are environment variables
+//! `run`, `repro`, `cmin`, `tmin` and `coverage` always set for it, so that
+//! findings reproduce in the environment they were found in; a `path` value is
+//! made absolute from the fuzz directory, since the targets run from the
+//! project one. The `build` of a target pins the sanitizer it is built with,
+//! unless `--sanitizer` is given on the command line or in the environment,
+//! and Cargo features it is always built with, besides those of `--features`;
+//! its binary then goes to a target directory of its own, under
+//! `target/pinned`, and the commands building several targets build those
+//! sharing a configuration together. The `description` and `tags` of a target
+//! are shown by `list`, and `build --tag` and `run --tag` operate on the targets with a tag.
+//! The `input` of a raw target names the format of its bytes, which its
+//! harness cannot tell, so that `distill` shares corpora only between raw
+//! targets of the same format; `add` declares it for the templates it knows.
+//! `[fuzz.quota]` bounds the disk space of each target, enforced by `corpus gc` and at
+//! the end of `run`: the size of its corpus, evicting the least recently
+//! modified entries (`lru`, the default) or those adding the fewest coverage
+//! features (`least-features`), the artifacts kept per crash bucket, the newest
+//! ones, and the snapshots of its coverage history, the latest ones.
+
+use crate::corpus;
+use crate::options::Sanitizer;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, Document, InlineTable, Item, Table};
+
+/// A target declared under `[fuzz.targets.<name>]`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeclaredTarget {
+    /// The Move function the target exercises, e.g. `0x1::bank::deposit`
+    pub entry: Option<String>,
+    /// What the target is about, for `list`
+    pub description: Option<String>,
+    /// Groups the target belongs to, e.g. `vm` or `verifier`
+    pub tags: Vec<String>,
+    /// Directories of inputs the target starts from besides its corpus, from
+    /// the fuzz directory
+    pub seeds: Vec<PathBuf>,
+    /// Size in bytes of the largest input, passed as `-max_len`
+    pub max_len: Option<u64>,
+    /// The format of the inputs of a raw target, e.g. `bytecode`
+    pub input: Option<String>,
+    /// libFuzzer flags passed before those of the command line
+    pub args: Vec<String>,
+    /// The expected aborts of entry functions, by `module::function`
+    pub aborts: BTreeMap<String, DeclaredAborts>,
+    /// Sanitizer options added to those of the project
+    pub sanitizer: SanitizerOptions,
+    /// Environment variables the target always runs with
+    pub env: BTreeMap<String, String>,
+    /// How the target is built
+    pub build: DeclaredBuild,
+}
+
+/// The `[fuzz.targets.<name>.build]` table.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeclaredBuild {
+    pub sanitizer: Option<Sanitizer>,
+    pub features: Vec<String>,
+}
+
+impl DeclaredTarget {
+    /// The expected aborts as `MOVE_FUZZ_EXPECTED_ABORTS` lists them, e.g.
+    /// `bank::withdraw=1,2,success;bank::freeze=3`, `None` without any.
+    pub fn expected_aborts(&self) -> Option<String> {
+        if self.aborts.is_empty() {
+            return None;
+        }
+        let declarations = self.aborts.iter().map(|(function, aborts)| {
+            let mut outcomes: Vec<String> = aborts.codes.iter().map(u64::to_string).collect();
+            if aborts.success {
+                outcomes.push("success".to_owned());
+            }
+            format!("{}={}", function, outcomes.join(","))
+        });
+        Some(declarations.collect::<Vec<_>>().join(";"))
+    }
+
+    /// The `-max_len` flag of the target, to pass before any other flag so
+    /// that those win.
+    pub fn max_len_arg(&self) -> Option<String> {
+        self.max_len.map(|max_len| format!("-max_len={}", max_len))
+    }
+}
+
+/// The outcomes an entry function of a target is expected to have.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DeclaredAborts {
+    pub codes: Vec<u64>,
+    /// Whether the function may also succeed
+    pub success: bool,
+}
+
+/// Runtime options of the sanitizers, as `(option, value)` pairs by the kind of
+/// sanitizer they are for.
+pub type SanitizerOptions = BTreeMap<String, Vec<(String, String)>>;
+
+/// The kinds of sanitizer options, with the variable passing them.
+pub(crate) const SANITIZER_VARIABLES: &[(&str, &str)] = &[
+    ("address", "ASAN_OPTIONS"),
+    ("leak", "LSAN_OPTIONS"),
+    ("memory", "MSAN_OPTIONS"),
+    ("thread", "TSAN_OPTIONS"),
+    ("undefined", "UBSAN_OPTIONS"),
+];
+
+/// The tables of `Move.toml` declaring dependencies.
+const DEPENDENCY_TABLES: [&str; 2] = ["dependencies", "dev-dependencies"];
+
+/// The packages of natives `natives` can list.
+pub(crate) const NATIVE_PACKAGES: &[&str] = &["stdlib", "nursery", "table"];
+
+/// The strategies `addresses` can list.
+pub(crate) const ADDRESS_STRATEGIES: &[&str] = &["pool", "arbitrary", "published", "reserved"];
+
+/// The accounts of the harnesses when `signers` is not set, the list `libfuzzer`
+/// falls back to.
+pub(crate) const DEFAULT_SIGNERS: &str =
+    include_str!("../../libfuzzer/src/run_move/default_signers.txt");
+
+/// How entries are picked for eviction when a corpus is over its quota.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Eviction {
+    /// The least recently modified entries first
+    #[default]
+    Lru,
+    /// The entries adding the fewest coverage features first
+    LeastFeatures,
+}
+
+/// The `[fuzz.quota]` table, the limits of each target.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Quota {
+    /// Bytes of corpus
+    pub max_corpus_size: Option<u64>,
+    pub max_artifacts_per_bucket: Option<usize>,
+    /// Snapshots of the coverage history
+    pub max_coverage_history: Option<usize>,
+    pub eviction: Eviction,
+}
+
+impl Quota {
+    /// Whether any limit is set.
+    pub fn is_set(&self) -> bool {
+        self.max_corpus_size.is_some()
+            || self.max_artifacts_per_bucket.is_some()
+            || self.max_coverage_history.is_some()
+    }
+}
+
+/// The `[fuzz]` section, empty if there is none.
+#[derive(Debug, Default)]
+pub(crate) struct FuzzSection {
+    pub(crate) natives: Option<Vec<String>>,
+    pub(crate) signers: Option<Vec<String>>,
+    pub(crate) addresses: Option<Vec<String>>,
+    pub(crate) defaults: toml::value::Table,
+    pub(crate) sanitizer: SanitizerOptions,
+    pub(crate) quota: Quota,
+    pub(crate) targets: BTreeMap<String, DeclaredTarget>,
+}
+
+impl FuzzSection {
+    /// The accounts signing the calls of the harnesses.
+    pub(crate) fn signers(&self) -> Vec<String> {
+        match &self.signers {
+            Some(signers) => signers.clone(),
+            None => DEFAULT_SIGNERS
+                .split(',')
+                .map(|s| s.trim().to_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Reads the `[fuzz]` section of the `Move.toml` at `path`, if it exists.
+pub(crate) fn read(path: &Path) -> Result<FuzzSection> {
+    let manifest = match fs::read_to_string(path) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(FuzzSection::default()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let value: toml::Value = toml::from_str(&manifest)
+        .with_context(|| format!("could not decode {}", path.display()))?;
+    let Some(fuzz) = value.get("fuzz") else {
+        return Ok(FuzzSection::default());
+    };
+
+    // Suppression files are relative to the fuzz directory, not to where the
+    // targets run.
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+
+    let mut section = FuzzSection {
+        natives: known_names(fuzz, "natives", NATIVE_PACKAGES)?,
+        signers: strings(fuzz, "fuzz", "signers")?,
+        addresses: known_names(fuzz, "addresses", ADDRESS_STRATEGIES)?,
+        sanitizer: sanitizer_options(fuzz, "fuzz", &dir)?,
+        quota: quota(fuzz)?,
+        ..FuzzSection::default()
+    };
+    if let Some(signers) = &section.signers {
+        if let Some(invalid) = signers.iter().find(|signer| !is_address(signer)) {
+            bail!(
+                "`{}` in `fuzz.signers` is not an address, e.g. `0xa11ce`",
+                invalid
+            );
+        }
+    }
+    if let Some(defaults) = fuzz.get("defaults") {
+        section.defaults = defaults
+            .as_table()
+            .cloned()
+            .context("`fuzz.defaults` must be a table")?;
+    }
+    let targets = fuzz
+        .get("targets")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flatten();
+    for (name, target) in targets {
+        let table = format!("fuzz.targets.{}", name);
+        let declared = DeclaredTarget {
+            entry: target
+                .get("entry")
+                .and_then(toml::Value::as_str)
+                .map(str::to_owned),
+            description: match target.get("description") {
+                Some(description) => Some(
+                    description
+                        .as_str()
+                        .map(str::to_owned)
+                        .with_context(|| format!("`{}.description` must be a string", table))?,
+                ),
+                None => None,
+            },
+            tags: strings(target, &table, "tags")?.unwrap_or_default(),
+            seeds: strings(target, &table, "seeds")?
+                .unwrap_or_default()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            max_len: match target.get("max_len") {
+                Some(max_len) => Some(
+                    max_len
+                        .as_integer()
+                        .and_then(|n| u64::try_from(n).ok())
+                        .filter(|&n| n > 0)
+                        .with_context(|| {
+                            format!("`{}.max_len` must be a positive integer", table)
+                        })?,
+                ),
+                None => None,
+            },
+            input: match target.get("input") {
+                Some(input) => Some(
+                    input
+                        .as_str()
+                        .map(str::to_owned)
+                        .with_context(|| format!("`{}.input` must be a string", table))?,
+                ),
+                None => None,
+            },
+            args: strings(target, &table, "args")?.unwrap_or_default(),
+            aborts: aborts(target, &table)?,
+            sanitizer: sanitizer_options(target, &table, &dir)?,
+            env: env_vars(target, &table, &dir)?,
+            build: declared_build(target, &table)?,
+        };
+        section.targets.insert(name.clone(), declared);
+    }
+    Ok(section)
+}
+
+/// The strings under `key` in `value`, the `table` of the manifest.
+fn strings(value: &toml::Value, table: &str, key: &str) -> Result<Option<Vec<String>>> {
+    let Some(values) = value.get(key) else {
+        return Ok(None);
+    };
+    values
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().map(str::to_owned))
+                .collect()
+        })
+        .map(Some)
+        .with_context(|| format!("`{}.{}` must be strings", table, key))
+}
+
+/// The `aborts` of `target`, the `table` of the manifest.
+fn aborts(target: &toml::Value, table: &str) -> Result<BTreeMap<String, DeclaredAborts>> {
+    let Some(aborts) = target.get("aborts") else {
+        return Ok(BTreeMap::new());
+    };
+    let aborts = aborts
+        .as_table()
+        .with_context(|| format!("`{}.aborts` must be a table", table))?;
+    let mut declared = BTreeMap::new();
+    for (function, expected) in aborts {
+        let key = format!("{}.aborts.\"{}\"", table, function);
+        if !function.contains("::") {
+            bail!("`{}` must be `module::function`", key);
+        }
+        let expected = match expected {
+            toml::Value::Array(_) => DeclaredAborts {
+                codes: codes_of(expected, &key)?,
+                success: true,
+            },
+            toml::Value::Table(fields) => DeclaredAborts {
+                codes: match fields.get("codes") {
+                    Some(codes) => codes_of(codes, &key)?,
+                    None => Vec::new(),
+                },
+                success: match fields.get("success") {
+                    Some(success) => success
+                        .as_bool()
+                        .with_context(|| format!("`{}.success` must be a boolean", key))?,
+                    None => true,
+                },
+            },
+            _ => bail!("`{}` must be abort codes or a table", key),
+        };
+        declared.insert(function.clone(), expected);
+    }
+    Ok(declared)
+}
+
+/// The `build` of `target`, the `table` of the manifest.
+fn declared_build(target: &toml::Value, table: &str) -> Result<DeclaredBuild> {
+    let Some(build) = target.get("build") else {
+        return Ok(DeclaredBuild::default());
+    };
+    let table = format!("{}.build", table);
+    let fields = build
+        .as_table()
+        .with_context(|| format!("`{}` must be a table", table))?;
+    if let Some(unknown) = fields
+        .keys()
+        .find(|key| !matches!(key.as_str(), "sanitizer" | "features"))
+    {
+        bail!("unknown key `{}` in `{}`", unknown, table);
+    }
+    let sanitizer = match fields.get("sanitizer") {
+        Some(sanitizer) => Some(
+            sanitizer
+                .as_str()
+                .and_then(|s| clap::ValueEnum::from_str(s, false).ok())
+                .with_context(|| {
+                    format!(
+                        "`{}.sanitizer` must be one of address, leak, memory, thread, none",
+                        table
+                    )
+                })?,
+        ),
+        None => None,
+    };
+    let features = strings(build, &table, "features")?.unwrap_or_default();
+    if let Some(invalid) = features
+        .iter()
+        .find(|feature| feature.is_empty() || feature.contains([',', ' ']))
+    {
+        bail!("`{}` in `{}.features` is not a feature", invalid, table);
+    }
+    Ok(DeclaredBuild {
+        sanitizer,
+        features,
+    })
+}
+
+/// The `env` of `target`, the `table` of the manifest, with `path` values made
+/// relative to `dir`.
+fn env_vars(target: &toml::Value, table: &str, dir: &Path) -> Result<BTreeMap<String, String>> {
+    let Some(vars) = target.get("env") else {
+        return Ok(BTreeMap::new());
+    };
+    let vars = vars
+        .as_table()
+        .with_context(|| format!("`{}.env` must be a table", table))?;
+    let mut declared = BTreeMap::new();
+    for (name, value) in vars {
+        let key = format!("{}.env.{}", table, name);
+        if name.is_empty() || name.contains(['=', '\0']) {
+            bail!("`{}` is not an environment variable", key);
+        }
+        // move-fuzz sets those itself, from the options and the manifest.
+        if name.starts_with("MOVE_FUZZ_") {
+            bail!("`{}` is reserved for move-fuzz", key);
+        }
+        if let Some((kind, _)) = SANITIZER_VARIABLES.iter().find(|(_, v)| v == name) {
+            bail!(
+                "set `{}` under `{}.sanitizer.{}` instead of `{}`",
+                name,
+                table,
+                kind,
+                key
+            );
+        }
+        let value = match value {
+            toml::Value::Boolean(b) => if *b { "1" } else { "0" }.to_owned(),
+            toml::Value::Integer(n) => n.to_string(),
+            toml::Value::Float(x) => x.to_string(),
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Table(fields) => match (fields.get("path"), fields.len()) {
+                (Some(toml::Value::String(path)), 1) => {
+                    dir.join(path).to_string_lossy().into_owned()
+                }
+                _ => bail!("`{}` must be a value or `{{ path = \"...\" }}`", key),
+            },
+            _ => bail!("`{}` must be a boolean, a number or a string", key),
+        };
+        declared.insert(name.clone(), value);
+    }
+    Ok(declared)
+}
+
+/// `value` as sanitizers read it from their options: quoted if it holds a
+/// separator, such as the `:` of a Windows path, since they split the options
+/// on colons, commas and whitespace but read a quoted value whole. `None` if
+/// it needs quoting but holds both kinds of quotes.
+fn quote_sanitizer_value(value: &str) -> Option<String> {
+    if !value.contains([':', ',', ' ', '\t', '\n', '\r']) {
+        return Some(value.to_owned());
+    }
+    let quote = ['"', '\'']
+        .into_iter()
+        .find(|quote| !value.contains(*quote))?;
+    Some(format!("{}{}{}", quote, value, quote))
+}
+
+/// The `sanitizer` options of `value`, the `table` of the manifest, with
+/// relative `suppressions` files made relative to `dir`.
+fn sanitizer_options(value: &toml::Value, table: &str, dir: &Path) -> Result<SanitizerOptions> {
+    let Some(kinds) = value.get("sanitizer") else {
+        return Ok(SanitizerOptions::new());
+    };
+    let kinds = kinds
+        .as_table()
+        .with_context(|| format!("`{}.sanitizer` must be a table", table))?;
+    let mut declared = SanitizerOptions::new();
+    for (kind, options) in kinds {
+        if !SANITIZER_VARIABLES.iter().any(|(k, _)| k == kind) {
+            bail!(
+                "unknown sanitizer `{}` in `{}.sanitizer`, expected one of {}",
+                kind,
+                table,
+                SANITIZER_VARIABLES
+                    .iter()
+                    .map(|(k, _)| *k)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let options = options
+            .as_table()
+            .with_context(|| format!("`{}.sanitizer.{}` must be a table", table, kind))?;
+        let mut values = Vec::new();
+        for (option, value) in options {
+            let key = format!("{}.sanitizer.{}.{}", table, kind, option);
+            if option.is_empty()
+                || !option
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                bail!("`{}` is not a sanitizer option", key);
+            }
+            let value = match value {
+                toml::Value::Boolean(b) => if *b { "1" } else { "0" }.to_owned(),
+                toml::Value::Integer(n) => n.to_string(),
+                toml::Value::Float(x) => x.to_string(),
+                toml::Value::String(s) if option == "suppressions" => {
+                    dir.join(s).to_string_lossy().into_owned()
+                }
+                toml::Value::String(s) => s.clone(),
+                _ => bail!("`{}` must be a boolean, a number or a string", key),
+            };
+            let value = quote_sanitizer_value(&value)
+                .with_context(|| format!("`{}` cannot hold both kinds of quotes", key))?;
+            values.push((option.clone(), value));
+        }
+        declared.insert(kind.clone(), values);
+    }
+    Ok(declared)
+}
+
+/// The abort codes in `codes`, the value of `key` in the manifest.
+fn codes_of(codes: &toml::Value, key: &str) -> Result<Vec<u64>> {
+    codes
+        .as_array()
+        .and_then(|codes| {
+            codes
+                .iter()
+                .map(|c| c.as_integer().and_then(|c| u64::try_from(c).ok()))
+                .collect()
+        })
+        .with_context(|| format!("`{}` must be abort codes", key))
+}
+
+/// The `quota` table of `fuzz`.
+fn quota(fuzz: &toml::Value) -> Result<Quota> {
+    let Some(quota) = fuzz.get("quota") else {
+        return Ok(Quota::default());
+    };
+    let count = |key: &str| -> Result<Option<usize>> {
+        quota
+            .get(key)
+            .map(|n| {
+                n.as_integer()
+                    .and_then(|n| usize::try_from(n).ok())
+                    .with_context(|| format!("`fuzz.quota.{}` must be a count", key))
+            })
+            .transpose()
+    };
+    Ok(Quota {
+        max_corpus_size: match quota.get("max_corpus_size") {
+            Some(size) => Some(
+                match size {
+                    toml::Value::Integer(n) => u64::try_from(*n).ok(),
+                    toml::Value::String(size) => corpus::parse_size(size),
+                    _ => None,
+                }
+                .context(
+                    "`fuzz.quota.max_corpus_size` must be bytes, e.g. 1048576 or \"512MiB\"",
+                )?,
+            ),
+            None => None,
+        },
+        max_artifacts_per_bucket: count("max_artifacts_per_bucket")?,
+        max_coverage_history: count("max_coverage_history")?,
+        eviction: match quota.get("eviction").map(|e| e.as_str()) {
+            None => Eviction::Lru,
+            Some(Some("lru")) => Eviction::Lru,
+            Some(Some("least-features")) => Eviction::LeastFeatures,
+            Some(_) => bail!("`fuzz.quota.eviction` must be `lru` or `least-features`"),
+        },
+    })
+}
+
+/// The strings under `key` in `fuzz`, each of them one of `known`.
+fn known_names(fuzz: &toml::Value, key: &str, known: &[&str]) -> Result<Option<Vec<String>>> {
+    let names = strings(fuzz, "fuzz", key)?;
+    if let Some(unknown) = names
+        .iter()
+        .flatten()
+        .find(|name| !known.contains(&name.as_str()))
+    {
+        bail!(
+            "unknown value `{}` in `fuzz.{}`, expected one of {}",
+            unknown,
+            key,
+            known.join(", ")
+        );
+    }
+    Ok(names)
+}
+
+/// Whether `s` is an address literal, as in `0xa11ce`.
+fn is_address(s: &str) -> bool {
+    s.strip_prefix("0x").is_some_and(|hex| {
+        !hex.is_empty() && hex.len() <= 64 && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Declares `target` in the `Move.toml` at `path`, unless it already is, with
+/// its `entry` function and its raw `input` format if any, and the `features`
+/// it is built with. The manifest is created if missing.
+pub(crate) fn declare_target(
+    path: &Path,
+    target: &str,
+    entry: Option<&str>,
+    input: Option<&str>,
+    features: &[&str],
+) -> Result<()> {
+    let mut manifest = read_document(path)?.unwrap_or_default();
+    let targets = table_at(manifest.as_table_mut(), &["fuzz", "targets"])?;
+    if targets.contains_key(target) {
+        return Ok(());
+    }
+    let mut declared = Table::new();
+    declared.decor_mut().set_prefix("\n");
+    if let Some(entry) = entry {
+        declared.insert("entry", toml_edit::value(entry));
+    }
+    if let Some(input) = input {
+        declared.insert("input", toml_edit::value(input));
+    }
+    declared.insert("seeds", toml_edit::value(Array::new()));
+    declared.insert("args", toml_edit::value(Array::new()));
+    if !features.is_empty() {
+        let mut build = Table::new();
+        build.decor_mut().set_prefix("\n");
+        build.insert(
+            "features",
+            toml_edit::value(features.iter().copied().collect::<Array>()),
+        );
+        declared.insert("build", Item::Table(build));
+    }
+    targets.insert(target, Item::Table(declared));
+    write_document(path, &manifest)
+}
+
+/// Removes the declaration of `target`, if any.
+pub(crate) fn remove_target(path: &Path, target: &str) -> Result<()> {
+    edit_targets(path, |targets| targets.remove(target).is_some())
+}
+
+/// Declares `target` as `new_name` instead, if it is declared. Its tables stay
+/// where they were in the manifest.
+pub(crate) fn rename_target(path: &Path, target: &str, new_name: &str) -> Result<()> {
+    edit_targets(path, |targets| match targets.remove(target) {
+        Some(declared) => {
+            targets.insert(new_name, declared);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Adds the package at `local` as the dependency `name` of the `Move.toml` at
+/// `path`, unless it already has one of that name.
+pub(crate) fn add_dependency(path: &Path, name: &str, local: &Path) -> Result<()> {
+    let mut manifest =
+        read_document(path)?.with_context(|| format!("failed to read {}", path.display()))?;
+    let dependencies = table_at(manifest.as_table_mut(), &["dependencies"])?;
+    if dependencies.contains_key(name) {
+        return Ok(());
+    }
+    let mut dependency = InlineTable::new();
+    dependency.insert("local", local.display().to_string().into());
+    dependencies.insert(name, toml_edit::value(dependency));
+    write_document(path, &manifest)
+}
+
+/// A dependency of a Move package, as its `Move.toml` declares it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Dependency {
+    /// A package in the directory `local`, relative to the manifest
+    Local(PathBuf),
+    /// A package fetched from elsewhere, such as a git repository
+    Remote,
+}
+
+/// The `[dependencies]` and `[dev-dependencies]` of the `Move.toml` at
+/// `path`, by name.
+pub(crate) fn dependencies(path: &Path) -> Result<Vec<(String, Dependency)>> {
+    let manifest =
+        read_document(path)?.with_context(|| format!("failed to read {}", path.display()))?;
+    let mut dependencies = Vec::new();
+    for table in DEPENDENCY_TABLES {
+        let Some(table) = manifest.get(table).and_then(Item::as_table_like) else {
+            continue;
+        };
+        for (name, dependency) in table.iter() {
+            let dependency = match dependency.get("local").and_then(Item::as_str) {
+                Some(local) => Dependency::Local(PathBuf::from(local)),
+                None => Dependency::Remote,
+            };
+            dependencies.push((name.to_owned(), dependency));
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Points the local dependency `name` of the `Move.toml` at `path` to the
+/// package at `local` instead.
+pub(crate) fn set_local_dependency(path: &Path, name: &str, local: &str) -> Result<()> {
+    let mut manifest =
+        read_document(path)?.with_context(|| format!("failed to read {}", path.display()))?;
+    for table in DEPENDENCY_TABLES {
+        if let Some(dependency) = manifest.get_mut(table).and_then(|t| t.get_mut(name)) {
+            if dependency.get("local").is_some() {
+                dependency["local"] = toml_edit::value(local);
+            }
+        }
+    }
+    write_document(path, &manifest)
+}
+
+/// Runs `edit` on the `[fuzz.targets]` table of the `Move.toml` at `path`, if
+/// there is one, and writes it back if `edit` changed it.
+fn edit_targets(path: &Path, edit: impl FnOnce(&mut Table) -> bool) -> Result<()> {
+    let Some(mut manifest) = read_document(path)? else {
+        return Ok(());
+    };
+    let Some(targets) = manifest
+        .get_mut("fuzz")
+        .and_then(|fuzz| fuzz.get_mut("targets"))
+        .and_then(Item::as_table_mut)
+    else {
+        return Ok(());
+    };
+    if edit(targets) {
+        write_document(path, &manifest)?;
+    }
+    Ok(())
+}
+
+/// The `Move.toml` at `path`, with its formatting and comments, `None` if it
+/// does not exist.
+fn read_document(path: &Path) -> Result<Option<Document>> {
+    let manifest = match fs::read_to_string(path) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    manifest
+        .parse()
+        .map(Some)
+        .with_context(|| format!("could not decode {}", path.display()))
+}
+
+fn write_document(path: &Path, manifest: &Document) -> Result<()> {
+    fs::write(path, manifest.to_string())
+        .with_context(|| format!("failed to write to {}", path.display()))
+}
+
+/// The table at `keys` under `table`, created as needed without a header of
+/// its own.
+fn table_at<'a>(mut table: &'a mut Table, keys: &[&str]) -> Result<&'a mut Table> {
+    for (i, key) in keys.iter().enumerate() {
+        table = table
+            .entry(key)
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .with_context(|| format!("`{}` must be a table", keys[..=i].join(".")))?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote_sanitizer_values_with_separators() {
+        assert_eq!(quote_sanitizer_value("1").as_deref(), Some("1"));
+        assert_eq!(
+            quote_sanitizer_value(r"C:\fuzz\asan.supp").as_deref(),
+            Some(r#""C:\fuzz\asan.supp""#)
+        );
+        assert_eq!(
+            quote_sanitizer_value(r#"say "a,b""#).as_deref(),
+            Some(r#"'say "a,b"'"#)
+        );
+        assert_eq!(quote_sanitizer_value(r#"'a' "b""#), None);
+    }
+
+    /// A `Move.toml` declaring the target `a` with every setting.
+    fn manifest(dir: &Path) -> PathBuf {
+        let path = dir.join("Move.toml");
+        fs::write(
+            &path,
+            "[package]\nname = \"t\"\n\n[fuzz]\nnatives = [\"table\"]\n\
+             signers = [\"0xa11ce\"]\n\n\
+             [fuzz.defaults]\njobs = 2\n\n\
+             [fuzz.sanitizer.address]\ndetect_leaks = false\nsuppressions = \"asan.supp\"\n\n\
+             [fuzz.targets.a]\ndescription = \"A\"\ntags = [\"vm\"]\nseeds = [\"seeds/a\"]\nmax_len = 65536\nargs = [\"-max_len=8\"]\n\n\
+             [fuzz.targets.a.aborts]\n\"bank::withdraw\" = [2, 1]\n\
+             \"bank::freeze\" = { codes = [3], success = false }\n\n\
+             [fuzz.targets.a.sanitizer.leak]\nmax_leaks = 4\n\n\
+             [fuzz.targets.a.env]\nMOVE_VM_PARANOID = true\nNATIVES = { path = \"natives.toml\" }\n\n\
+             [fuzz.targets.a.build]\nsanitizer = \"memory\"\nfeatures = [\"testing\"]\n",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn read_the_fuzz_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let section = read(&manifest(dir.path())).unwrap();
+        assert_eq!(section.natives, Some(vec!["table".to_owned()]));
+        assert_eq!(section.signers, Some(vec!["0xa11ce".to_owned()]));
+        assert_eq!(section.addresses, None);
+        assert_eq!(section.defaults.get("jobs"), Some(&toml::Value::Integer(2)));
+        let supp = dir.path().canonicalize().unwrap().join("asan.supp");
+        assert_eq!(
+            section.sanitizer["address"],
+            [
+                ("detect_leaks".to_owned(), "0".to_owned()),
+                ("suppressions".to_owned(), supp.display().to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_a_declared_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let section = read(&manifest(dir.path())).unwrap();
+        assert_eq!(
+            section.targets["a"],
+            DeclaredTarget {
+                entry: None,
+                description: Some("A".to_owned()),
+                tags: vec!["vm".to_owned()],
+                seeds: vec![PathBuf::from("seeds/a")],
+                max_len: Some(65536),
+                input: None,
+                args: vec!["-max_len=8".to_owned()],
+                aborts: BTreeMap::from([
+                    (
+                        "bank::freeze".to_owned(),
+                        DeclaredAborts {
+                            codes: vec![3],
+                            success: false,
+                        }
+                    ),
+                    (
+                        "bank::withdraw".to_owned(),
+                        DeclaredAborts {
+                            codes: vec![2, 1],
+                            success: true,
+                        }
+                    ),
+                ]),
+                sanitizer: BTreeMap::from([(
+                    "leak".to_owned(),
+                    vec![("max_leaks".to_owned(), "4".to_owned())]
+                )]),
+                env: BTreeMap::from([
+                    ("MOVE_VM_PARANOID".to_owned(), "1".to_owned()),
+                    (
+                        "NATIVES".to_owned(),
+                        dir.path()
+                            .canonicalize()
+                            .unwrap()
+                            .join("natives.toml")
+                            .display()
+                            .to_string()
+                    ),
+                ]),
+                build: DeclaredBuild {
+                    sanitizer: Some(Sanitizer::Memory),
+                    features: vec!["testing".to_owned()],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn libfuzzer_args_of_a_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = manifest(dir.path());
+        declare_target(&path, "b", None, None, &[]).unwrap();
+        let section = read(&path).unwrap();
+        assert_eq!(
+            section.targets["a"].expected_aborts().as_deref(),
+            Some("bank::freeze=3;bank::withdraw=2,1,success")
+        );
+        assert_eq!(section.targets["b"].expected_aborts(), None);
+        assert_eq!(
+            section.targets["a"].max_len_arg().as_deref(),
+            Some("-max_len=65536")
+        );
+        assert_eq!(section.targets["b"].max_len_arg(), None);
+    }
+
+    #[test]
+    fn declare_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = manifest(dir.path());
+        declare_target(&path, "b", Some("0x1::m::f"), None, &[]).unwrap();
+        declare_target(&path, "a", None, None, &[]).unwrap();
+        declare_target(&path, "v", None, Some("bytecode"), &[]).unwrap();
+        declare_target(&path, "t", None, None, &["libfuzzer/table-extension"]).unwrap();
+        let section = read(&path).unwrap();
+        assert_eq!(
+            section.targets.keys().collect::<Vec<_>>(),
+            ["a", "b", "t", "v"]
+        );
+        assert_eq!(section.targets["a"].args, ["-max_len=8"]);
+        assert_eq!(section.targets["b"].entry.as_deref(), Some("0x1::m::f"));
+        assert_eq!(section.targets["v"].input.as_deref(), Some("bytecode"));
+        assert_eq!(
+            section.targets["t"].build.features,
+            ["libfuzzer/table-extension"]
+        );
+    }
+
+    #[test]
+    fn rename_and_remove_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = manifest(dir.path());
+        declare_target(&path, "b", None, None, &[]).unwrap();
+        rename_target(&path, "a", "c").unwrap();
+        remove_target(&path, "b").unwrap();
+        let section = read(&path).unwrap();
+        assert_eq!(section.targets.keys().collect::<Vec<_>>(), ["c"]);
+        assert_eq!(section.targets["c"].args, ["-max_len=8"]);
+        assert_eq!(section.targets["c"].aborts.len(), 2);
+    }
+
+    #[test]
+    fn read_a_missing_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read(&dir.path().join("missing.toml"))
+            .unwrap()
+            .targets
+            .is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let bad = dir.path().join("Bad.toml");
+        fs::write(&bad, "[fuzz.sanitizer.hwaddress]\nx = 1\n").unwrap();
+        assert!(read(&bad).is_err());
+        fs::write(&bad, "[fuzz.quota]\nmax_corpus_size = \"lots\"\n").unwrap();
+        assert!(read(&bad).is_err());
+        fs::write(&bad, "[fuzz.targets.a.env]\nMOVE_FUZZ_SIGNERS = \"0x1\"\n").unwrap();
+        assert!(read(&bad).is_err());
+        fs::write(&bad, "[fuzz.targets.a.build]\nsanitizer = \"hwaddress\"\n").unwrap();
+        assert!(read(&bad).is_err());
+    }
+
+    #[test]
+    fn read_the_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let quota = dir.path().join("Quota.toml");
+        fs::write(
+            &quota,
+            "[fuzz.quota]\nmax_corpus_size = \"1.5MiB\"\nmax_coverage_history = 10\n\
+             eviction = \"least-features\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read(&quota).unwrap().quota,
+            Quota {
+                max_corpus_size: Some(3 << 19),
+                max_artifacts_per_bucket: None,
+                max_coverage_history: Some(10),
+                eviction: Eviction::LeastFeatures,
+            }
+        );
+        assert!(!read(&manifest(dir.path())).unwrap().quota.is_set());
+    }
+
+    #[test]
+    fn declare_target_creates_the_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Move.toml");
+        declare_target(&path, "t", None, None, &["libfuzzer/table-extension"]).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\n[fuzz.targets.t]\nseeds = []\nargs = []\n\n\
+             [fuzz.targets.t.build]\nfeatures = [\"libfuzzer/table-extension\"]\n"
+        );
+    }
+
+    #[test]
+    fn edits_keep_the_rest_of_the_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Move.toml");
+        fs::write(
+            &path,
+            "[package]\nname = \"t\" # the fuzz package\n\n\
+             [fuzz.targets.a]\nargs = [\"-max_len=8\"]\n\n\
+             [fuzz.targets.a.aborts]\n\"bank::withdraw\" = [2]\n\n\
+             [dependencies]\nbank = { local = \"..\" }\n",
+        )
+        .unwrap();
+        rename_target(&path, "a", "c").unwrap();
+        declare_target(&path, "b", Some("0x1::m::f"), None, &[]).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[package]\nname = \"t\" # the fuzz package\n\n\
+             [fuzz.targets.c]\nargs = [\"-max_len=8\"]\n\n\
+             [fuzz.targets.c.aborts]\n\"bank::withdraw\" = [2]\n\n\
+             [fuzz.targets.b]\nentry = \"0x1::m::f\"\nseeds = []\nargs = []\n\n\
+             [dependencies]\nbank = { local = \"..\" }\n"
+        );
+        remove_target(&path, "c").unwrap();
+        remove_target(&path, "missing").unwrap();
+        assert_eq!(
+            read(&path).unwrap().targets.keys().collect::<Vec<_>>(),
+            ["b"]
+        );
+        remove_target(&dir.path().join("Missing.toml"), "b").unwrap();
+        assert!(!dir.path().join("Missing.toml").exists());
+    }
+
+    #[test]
+    fn read_and_move_local_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Move.toml");
+        fs::write(
+            &path,
+            "[dependencies]\n\
+             bank = { local = \"..\" }\n\
+             MoveStdlib = { git = \"https://github.com/move-language/move.git\", rev = \"main\" }\n\n\
+             [dev-dependencies.oracle]\n\
+             local = \"../oracle\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dependencies(&path).unwrap(),
+            [
+                ("bank".to_owned(), Dependency::Local(PathBuf::from(".."))),
+                ("MoveStdlib".to_owned(), Dependency::Remote),
+                (
+                    "oracle".to_owned(),
+                    Dependency::Local(PathBuf::from("../oracle"))
+                ),
+            ]
+        );
+        set_local_dependency(&path, "bank", "deps/bank").unwrap();
+        set_local_dependency(&path, "oracle", "deps/oracle").unwrap();
+        set_local_dependency(&path, "MoveStdlib", "deps/MoveStdlib").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[dependencies]\n\
+             bank = { local = \"deps/bank\" }\n\
+             MoveStdlib = { git = \"https://github.com/move-language/move.git\", rev = \"main\" }\n\n\
+             [dev-dependencies.oracle]\n\
+             local = \"deps/oracle\"\n"
+        );
+    }
+
+    #[test]
+    fn add_dependency_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Move.toml");
+        fs::write(&path, "[package]\nname = \"t\"\n").unwrap();
+        add_dependency(&path, "bank", Path::new("..")).unwrap();
+        add_dependency(&path, "bank", Path::new("../other")).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[package]\nname = \"t\"\n\n[dependencies]\nbank = { local = \"..\" }\n"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Notifications of the crashes `run` finds, for unattended campaigns.
+//!
+//! The URLs to post to are secrets, so they are taken from the environment
+//! rather than from a committed manifest, as whitespace-separated lists:
+//!
+//! ```sh
+//! export MOVE_FUZZ_NOTIFY_WEBHOOK=https://ci.example.com/fuzz-crashes
+//! export MOVE_FUZZ_NOTIFY_SLACK=https://hooks.slack.com/services/T000/B000/XXXX
+//! export MOVE_FUZZ_NOTIFY_DISCORD=https://discord.com/api/webhooks/1/abc
+//! ```
+//!
+//! A notification is sent once per crash bucket, the first time an artifact
+//! with a signature neither triaged nor notified before is saved. The buckets
+//! notified are remembered in `notified.jsonl` of the fuzz directory.
+//!
+//! A generic webhook gets `{"target", "signature", "artifact"}` as JSON, Slack
+//! and Discord get a message.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The buckets notified, in the fuzz directory.
+pub const NOTIFIED_FILE: &str = "notified.jsonl";
+
+/// What a notification is posted to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Channel {
+    Webhook,
+    Slack,
+    Discord,
+}
+
+impl Channel {
+    const ALL: [Channel; 3] = [Channel::Webhook, Channel::Slack, Channel::Discord];
+
+    /// The environment variable listing the URLs of the channel.
+    fn env_var(self) -> String {
+        format!("MOVE_FUZZ_NOTIFY_{}", self.to_string().to_uppercase())
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Channel::Webhook => "webhook",
+                Channel::Slack => "slack",
+                Channel::Discord => "discord",
+            }
+        )
+    }
+}
+
+/// A crash opening a new bucket.
+#[derive(Clone, Debug)]
+pub struct Crash<'a> {
+    pub target: &'a str,
+    pub signature: &'a str,
+    pub artifact: &'a Path,
+}
+
+/// The notifications of a fuzz project, as URLs to post to.
+#[derive(Clone, Debug, Default)]
+pub struct Notifiers {
+    urls: Vec<(Channel, String)>,
+}
+
+impl Notifiers {
+    /// Reads the URLs of the `MOVE_FUZZ_NOTIFY_*` environment variables.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let mut notifiers = Notifiers::default();
+        for channel in Channel::ALL {
+            if let Some(urls) = var(&channel.env_var()) {
+                notifiers
+                    .urls
+                    .extend(urls.split_whitespace().map(|url| (channel, url.to_owned())));
+            }
+        }
+        notifiers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Posts `crash` to every URL, returning the errors of those that failed.
+    pub fn send(&self, crash: &Crash) -> Vec<anyhow::Error> {
+        self.urls
+            .iter()
+            .filter_map(|(channel, url)| {
+                post(url, &payload(*channel, crash))
+                    .with_context(|| format!("could not notify the {} at {}", channel, url))
+                    .err()
+            })
+            .collect()
+    }
+}
+
+/// The body posted to `channel` about `crash`.
+fn payload(channel: Channel, crash: &Crash) -> serde_json::Value {
+    let message = format!(
+        "New crash in fuzz target `{}`: {}\nArtifact: {}",
+        crash.target,
+        crash.signature,
+        crash.artifact.display()
+    );
+    match channel {
+        Channel::Webhook => json!({
+            "target": crash.target,
+            "signature": crash.signature,
+            "artifact": crash.artifact,
+        }),
+        Channel::Slack => json!({ "text": message }),
+        Channel::Discord => json!({ "content": message }),
+    }
+}
+
+fn post(url: &str, body: &serde_json::Value) -> Result<()> {
+    ureq::post(url)
+        .timeout(Duration::from_secs(30))
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())?;
+    Ok(())
+}
+
+/// A bucket notified, as a line of the notified file.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct Notified {
+    target: String,
+    signature: String,
+}
+
+/// The buckets notified so far, kept apart from the triage database so that
+/// notifying leaves the triage to `move-fuzz triage`.
+#[derive(Clone, Debug)]
+pub struct NotifiedLog {
+    path: PathBuf,
+    notified: HashSet<Notified>,
+}
+
+impl NotifiedLog {
+    /// Reads the log at `path`, skipping the lines that do not decode.
+    pub fn load(path: &Path) -> Result<Self> {
+        let notified = if path.exists() {
+            fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(NotifiedLog {
+            path: path.to_owned(),
+            notified,
+        })
+    }
+
+    /// Records that the bucket of `signature` in `target` is notified,
+    /// returning whether it was not already.
+    pub fn insert(&mut self, target: &str, signature: &str) -> Result<bool> {
+        let notified = Notified {
+            target: target.to_owned(),
+            signature: signature.to_owned(),
+        };
+        if self.notified.contains(&notified) {
+            return Ok(false);
+        }
+        let line = format!("{}\n", serde_json::to_string(&notified)?);
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("failed to write to {}", self.path.display()))?;
+        self.notified.insert(notified);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_notifications_from_the_environment() {
+        let notifiers = Notifiers::from_vars(|name| match name {
+            "MOVE_FUZZ_NOTIFY_WEBHOOK" => Some("https://ci.example.com/crashes".to_owned()),
+            "MOVE_FUZZ_NOTIFY_SLACK" => {
+                Some(" https://hooks.slack.com/a\nhttps://hooks.slack.com/b ".to_owned())
+            }
+            _ => None,
+        });
+        assert_eq!(
+            notifiers.urls,
+            [
+                (
+                    Channel::Webhook,
+                    "https://ci.example.com/crashes".to_owned()
+                ),
+                (Channel::Slack, "https://hooks.slack.com/a".to_owned()),
+                (Channel::Slack, "https://hooks.slack.com/b".to_owned()),
+            ]
+        );
+        assert!(Notifiers::from_vars(|_| None).is_empty());
+    }
+
+    #[test]
+    fn payloads_of_each_channel() {
+        let crash = Crash {
+            target: "t",
+            signature: "panicked at src/lib.rs:3",
+            artifact: Path::new("fuzz/artifacts/t/crash-1"),
+        };
+        assert_eq!(
+            payload(Channel::Webhook, &crash),
+            json!({
+                "target": "t",
+                "signature": "panicked at src/lib.rs:3",
+                "artifact": "fuzz/artifacts/t/crash-1",
+            })
+        );
+        assert_eq!(
+            payload(Channel::Discord, &crash)["content"],
+            "New crash in fuzz target `t`: panicked at src/lib.rs:3\n\
+            Artifact: fuzz/artifacts/t/crash-1"
+        );
+    }
+
+    #[test]
+    fn buckets_are_notified_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NOTIFIED_FILE);
+        let mut log = NotifiedLog::load(&path).unwrap();
+        assert!(log.insert("t", "panic at src/lib.rs:3").unwrap());
+        assert!(!log.insert("t", "panic at src/lib.rs:3").unwrap());
+        assert!(log.insert("u", "panic at src/lib.rs:3").unwrap());
+
+        let mut log = NotifiedLog::load(&path).unwrap();
+        assert!(!log.insert("u", "panic at src/lib.rs:3").unwrap());
+        assert!(log.insert("t", "abort 0x1::m 7").unwrap());
+    }
+}
@@ -1,24 +1,83 @@
 mod add;
+mod autoharness;
+mod bench;
+mod bisect;
 mod build;
 mod check;
+mod clean;
 mod cmin;
+mod corpus;
+mod completions;
 mod coverage;
+mod diff_run;
+mod distill;
+mod docker;
 mod fmt;
+mod gen_dict;
+mod gen_regression;
 mod init;
 mod list;
+mod man;
+mod merge;
+mod process_crash;
+mod remove;
+mod rename;
+mod report;
+mod repro;
 mod run;
+mod schema;
+mod serve;
+mod smoke;
 mod tmin;
+mod triage;
+mod verify;
+mod worker;
 
 pub use self::{
-    add::Add, build::Build, check::Check, cmin::Cmin, coverage::Coverage, fmt::Fmt, init::Init,
-    list::List, run::Run, tmin::Tmin,
+    add::{Add, Extension, TargetKind},
+    autoharness::Autoharness,
+    bench::Bench,
+    bisect::Bisect,
+    build::Build,
+    check::Check,
+    clean::Clean,
+    cmin::Cmin,
+    corpus::{Corpus, CorpusAction},
+    completions::Completions,
+    coverage::{Coverage, CoverageAction, DiffFormat},
+    diff_run::DiffRun,
+    distill::Distill,
+    docker::Docker,
+    fmt::{Fmt, InputEncoding},
+    gen_dict::{DictKind, GenDict},
+    gen_regression::GenRegression,
+    init::Init,
+    list::List,
+    man::Man,
+    merge::Merge,
+    process_crash::ProcessCrash,
+    remove::Remove,
+    rename::Rename,
+    report::{Report, ReportFormat},
+    repro::{Recorder, Repro},
+    run::{LibaflMutator, Run, Schedule, VerifierLimits},
+    schema::{Schema, SchemaKind},
+    serve::Serve,
+    smoke::Smoke,
+    tmin::Tmin,
+    triage::Triage,
+    verify::Verify,
+    worker::Worker,
 };
 
+use crate::utils::quote_arg;
 use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::{fmt as stdfmt, path::PathBuf};
 use std::fmt::Debug;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Sanitizer {
     Address,
     Leak,
@@ -43,13 +102,108 @@ impl stdfmt::Display for Sanitizer {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    /// The libFuzzer bundled with the `libfuzzer` crate
+    Libfuzzer,
+    /// LibAFL, through its libFuzzer-compatible runtime
+    Libafl,
+}
+
+impl stdfmt::Display for Engine {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Engine::Libfuzzer => "libfuzzer",
+                Engine::Libafl => "libafl",
+            }
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum MessageFormat {
+    /// Text meant for people, which may change between releases
+    #[default]
+    Human,
+    /// A line of JSON following `schema/messages.json` once the command is done,
+    /// alone on stdout, what would go there going to stderr
+    Json,
+    /// GitHub Actions workflow commands annotating the crashes and failures,
+    /// at the file and line they happened when known
+    Github,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser)]
+pub struct LogOptions {
+    #[arg(long, global = true, value_enum, default_value = "info")]
+    /// Most verbose level of the messages logged
+    pub log_level: LogLevel,
+
+    #[arg(short, long, global = true)]
+    /// Only log errors, same as `--log-level error`
+    pub quiet: bool,
+
+    #[arg(long, global = true, value_name = "PATH")]
+    /// Also write the log to PATH, with timestamps
+    pub log_file: Option<PathBuf>,
+
+    #[arg(long, global = true)]
+    /// Do not draw progress bars, e.g. in CI logs
+    pub no_progress: bool,
+}
+
+impl LogOptions {
+    /// The level set by `--log-level`, or by `--quiet`.
+    pub fn level(&self) -> LogLevel {
+        if self.quiet {
+            LogLevel::Error
+        } else {
+            self.log_level
+        }
+    }
+}
+
+/// What the commands going through several targets do when one fails, see
+/// [`crate::batch`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser)]
+pub struct BatchOptions {
+    #[arg(long, global = true)]
+    /// Skip the targets left after the first one failing, with `run --all`,
+    /// `cmin --all-targets`, `coverage --all` and `triage`
+    pub fail_fast: bool,
+
+    #[arg(long, global = true, overrides_with = "fail_fast")]
+    /// Go on with the targets left after one failed, the default
+    pub no_fail_fast: bool,
+}
+
+impl BatchOptions {
+    pub fn fail_fast(&self) -> bool {
+        self.fail_fast && !self.no_fail_fast
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum BuildMode {
     Build,
     Check,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Parser)]
+#[derive(Clone, Debug, Eq, PartialEq, Parser, Serialize, Deserialize)]
 pub struct BuildOptions {
     #[arg(short = 'D', long, conflicts_with = "release")]
     /// Build artifacts in development mode, without optimizations
@@ -72,7 +226,7 @@ pub struct BuildOptions {
     pub cargo_options: CargoBuildOptions,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Parser)]
+#[derive(Clone, Debug, Eq, PartialEq, Parser, Serialize, Deserialize)]
 pub struct CargoBuildOptions {
     #[arg(short = 'O', long, conflicts_with = "dev")]
     /// Build artifacts in release mode, with optimizations
@@ -98,6 +252,15 @@ pub struct CargoBuildOptions {
     /// Use a specific sanitizer
     pub sanitizer: Sanitizer,
 
+    #[arg(long, value_enum, default_value = "libfuzzer")]
+    /// Fuzzing engine linked into the fuzz targets
+    pub engine: Engine,
+
+    #[arg(long, value_name = "PATH", required_if_eq("engine", "libafl"))]
+    /// Static library of the LibAFL libFuzzer runtime (`libafl_libfuzzer_runtime.a`),
+    /// linked in place of libFuzzer by `--engine libafl`
+    pub libafl_runtime: Option<PathBuf>,
+
     #[arg(long = "build-std")]
     /// Pass -Zbuild-std to Cargo, which will build the standard library with all the build
     /// settings for the fuzz target, including debug assertions, and a sanitizer if requested.
@@ -107,9 +270,10 @@ pub struct CargoBuildOptions {
     pub build_std: bool,
 
     #[arg(short, long = "careful")]
-    /// enable "careful" mode: inspired by https://github.com/RalfJung/cargo-careful, this enables
+    /// enable "careful" mode: as https://github.com/RalfJung/cargo-careful does, this enables
     /// building the fuzzing harness along with the standard library (implies --build-std) with
-    /// debug assertions and extra const UB and init checks.
+    /// debug assertions, `debug_refcell` and extra const UB and init checks, and the package
+    /// under test with the strict provenance lints. Needs a nightly toolchain with `rust-src`.
     pub careful_mode: bool,
 
     #[arg(long = "target", default_value(crate::utils::default_target()))]
@@ -146,9 +310,24 @@ pub struct CargoBuildOptions {
     /// the `*-trace-compares` instrumentation assumes that the instruction is
     /// available.
     pub no_trace_compares: bool,
+
+    #[arg(long, value_name = "FROM=TO", value_parser = parse_remap)]
+    #[serde(default)]
+    /// Remap the FROM prefix of source paths to TO in the built targets, as rustc's
+    /// `--remap-path-prefix` does. Coverage builds always remap the package directory
+    /// so that coverage reports list paths relative to it
+    pub remap_path_prefix: Vec<String>,
+
+    #[arg(long, conflicts_with_all = &["build_std", "careful_mode", "unstable_flags"])]
+    #[serde(default)]
+    /// Build with a stable toolchain: without a sanitizer or any other nightly-only flag.
+    /// The SanitizerCoverage instrumentation libFuzzer is guided by only needs stable
+    /// `-C` flags, so fuzzing stays coverage-guided, but memory errors in unsafe code
+    /// are no longer caught when they happen.
+    pub stable: bool,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Parser)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser, Serialize, Deserialize)]
 pub struct MoveBuildOptions {
     #[arg(long)]
     /// Bytecode version to compile move code
@@ -178,7 +357,7 @@ impl std::fmt::Display for BuildOptions {
         }
 
         if let Some(target_dir) = &self.target_dir {
-            write!(f, " --target-dir={}", target_dir)?;
+            write!(f, " --target-dir={}", quote_arg(target_dir))?;
         }
 
         Ok(())
@@ -200,7 +379,7 @@ impl std::fmt::Display for CargoBuildOptions {
         }
 
         if let Some(feature) = &self.features {
-            write!(f, " --features={}", feature)?;
+            write!(f, " --features={}", quote_arg(feature))?;
         }
 
         // Handling sanitizer
@@ -210,6 +389,14 @@ impl std::fmt::Display for CargoBuildOptions {
             _ => write!(f, " --sanitizer={}", self.sanitizer)?,
         }
 
+        if self.engine != Engine::Libfuzzer {
+            write!(f, " --engine={}", self.engine)?;
+        }
+
+        if let Some(runtime) = &self.libafl_runtime {
+            write!(f, " --libafl-runtime={}", quote_arg(&runtime.to_string_lossy()))?;
+        }
+
         if self.build_std {
             write!(f, " --build-std")?;
         }
@@ -238,6 +425,14 @@ impl std::fmt::Display for CargoBuildOptions {
             write!(f, " --no-trace-compares")?;
         }
 
+        if self.stable {
+            write!(f, " --stable")?;
+        }
+
+        for remap in &self.remap_path_prefix {
+            write!(f, " --remap-path-prefix={}", quote_arg(remap))?;
+        }
+
         if self.triple != crate::utils::default_target() {
             write!(f, " --target={}", self.triple)?;
         }
@@ -272,6 +467,167 @@ impl std::fmt::Display for MoveBuildOptions {
     }
 }
 
+fn parse_remap(remap: &str) -> Result<String, String> {
+    match remap.split_once('=') {
+        Some((from, _)) if !from.is_empty() => Ok(remap.to_owned()),
+        _ => Err(format!("expected FROM=TO, got `{}`", remap)),
+    }
+}
+
+/// Setters of a builder, each replacing a field of the options being built.
+/// Fields listed under `optional` are `Option`s and set to `Some`.
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(; optional $($opt:ident: $opt_ty:ty),*)? $(,)?) => {
+        $(
+            #[doc = concat!("Sets `", stringify!($field), "`.")]
+            pub fn $field(mut self, $field: impl Into<$ty>) -> Self {
+                self.0.$field = $field.into();
+                self
+            }
+        )*
+        $($(
+            #[doc = concat!("Sets `", stringify!($opt), "`.")]
+            pub fn $opt(mut self, $opt: impl Into<$opt_ty>) -> Self {
+                self.0.$opt = Some($opt.into());
+                self
+            }
+        )*)?
+    };
+}
+
+impl Default for BuildOptions {
+    /// The options of a `move-fuzz build` without flags.
+    fn default() -> Self {
+        BuildOptions {
+            dev: false,
+            verbose: false,
+            target_dir: None,
+            move_options: MoveBuildOptions::default(),
+            cargo_options: CargoBuildOptions::default(),
+        }
+    }
+}
+
+impl Default for CargoBuildOptions {
+    fn default() -> Self {
+        CargoBuildOptions {
+            release: false,
+            debug_assertions: false,
+            no_default_features: false,
+            all_features: false,
+            features: None,
+            sanitizer: Sanitizer::Address,
+            engine: Engine::Libfuzzer,
+            libafl_runtime: None,
+            build_std: false,
+            careful_mode: false,
+            triple: String::from(crate::utils::default_target()),
+            unstable_flags: Vec::new(),
+            coverage: false,
+            strip_dead_code: false,
+            no_cfg_fuzzing: false,
+            no_trace_compares: false,
+            stable: false,
+            remap_path_prefix: Vec::new(),
+        }
+    }
+}
+
+impl BuildOptions {
+    /// Starts from the defaults of the command line.
+    pub fn builder() -> BuildOptionsBuilder {
+        BuildOptionsBuilder(BuildOptions::default())
+    }
+}
+
+impl CargoBuildOptions {
+    pub fn builder() -> CargoBuildOptionsBuilder {
+        CargoBuildOptionsBuilder(CargoBuildOptions::default())
+    }
+
+    /// The sanitizer the targets are built with: none with `--stable`, and one
+    /// the platform supports otherwise.
+    pub fn build_sanitizer(&self) -> Sanitizer {
+        if self.stable {
+            Sanitizer::None
+        } else {
+            crate::platform::build_sanitizer(&self.triple, self.sanitizer)
+        }
+    }
+}
+
+impl MoveBuildOptions {
+    pub fn builder() -> MoveBuildOptionsBuilder {
+        MoveBuildOptionsBuilder(MoveBuildOptions::default())
+    }
+}
+
+/// Builds [`BuildOptions`] without going through the command line.
+#[derive(Clone, Debug)]
+pub struct BuildOptionsBuilder(BuildOptions);
+
+impl BuildOptionsBuilder {
+    setters!(
+        dev: bool,
+        verbose: bool,
+        move_options: MoveBuildOptions,
+        cargo_options: CargoBuildOptions;
+        optional target_dir: String
+    );
+
+    pub fn build(self) -> BuildOptions {
+        self.0
+    }
+}
+
+/// Builds [`CargoBuildOptions`] without going through the command line.
+#[derive(Clone, Debug)]
+pub struct CargoBuildOptionsBuilder(CargoBuildOptions);
+
+impl CargoBuildOptionsBuilder {
+    setters!(
+        release: bool,
+        debug_assertions: bool,
+        no_default_features: bool,
+        all_features: bool,
+        sanitizer: Sanitizer,
+        engine: Engine,
+        build_std: bool,
+        careful_mode: bool,
+        triple: String,
+        unstable_flags: Vec<String>,
+        coverage: bool,
+        strip_dead_code: bool,
+        no_cfg_fuzzing: bool,
+        no_trace_compares: bool,
+        stable: bool,
+        remap_path_prefix: Vec<String>;
+        optional features: String,
+        libafl_runtime: PathBuf
+    );
+
+    pub fn build(self) -> CargoBuildOptions {
+        self.0
+    }
+}
+
+/// Builds [`MoveBuildOptions`] without going through the command line.
+#[derive(Clone, Debug)]
+pub struct MoveBuildOptionsBuilder(MoveBuildOptions);
+
+impl MoveBuildOptionsBuilder {
+    setters!(
+        fetch_deps_only: bool,
+        force: bool,
+        skip_fetch_latest_git_deps: bool;
+        optional bytecode_version: u32
+    );
+
+    pub fn build(self) -> MoveBuildOptions {
+        self.0
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Parser)]
 pub struct FuzzDirWrapper {
     /// The path to the fuzz project directory.
@@ -289,132 +645,86 @@ impl stdfmt::Display for FuzzDirWrapper {
     }
 }
 
+/// Where the corpora and artifacts of the targets are, when not in the fuzz dir.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser)]
+pub struct StateDirs {
+    #[arg(long, value_name = "DIR")]
+    /// Directory holding the corpus of each target, `<fuzz dir>/corpus` by default
+    pub corpus_dir: Option<PathBuf>,
+
+    #[arg(long, value_name = "DIR")]
+    /// Directory holding the artifacts of each target, `<fuzz dir>/artifacts` by default
+    pub artifact_dir: Option<PathBuf>,
+}
+
+/// How a command locks the fuzz project, see `--wait` and `--no-lock`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser)]
+pub struct LockOptions {
+    #[arg(long)]
+    /// Wait for the invocations holding the lock of the project or target to
+    /// finish, instead of failing
+    pub wait: bool,
+
+    #[arg(long, conflicts_with = "wait")]
+    /// Do not lock the project or target, at the risk of racing with other
+    /// invocations on the corpus and artifacts
+    pub no_lock: bool,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn cargo(builder: CargoBuildOptionsBuilder) -> BuildOptions {
+        BuildOptions::builder().cargo_options(builder.build()).build()
+    }
+
+    fn build_options_cases() -> Vec<BuildOptions> {
+        let cargo_opts = CargoBuildOptions::builder;
+        vec![
+            BuildOptions::default(),
+            BuildOptions::builder().dev(true).build(),
+            cargo(cargo_opts().release(true)),
+            cargo(cargo_opts().debug_assertions(true)),
+            BuildOptions::builder().verbose(true).build(),
+            cargo(cargo_opts().no_default_features(true)),
+            cargo(cargo_opts().all_features(true)),
+            cargo(cargo_opts().features("features")),
+            cargo(cargo_opts().sanitizer(Sanitizer::None)),
+            cargo(cargo_opts().triple("custom_triple")),
+            cargo(cargo_opts().stable(true)),
+            cargo(cargo_opts().remap_path_prefix(vec![String::from("/src=src")])),
+            cargo(cargo_opts().unstable_flags(vec![
+                String::from("unstable"),
+                String::from("flags"),
+            ])),
+            cargo(
+                cargo_opts()
+                    .engine(Engine::Libafl)
+                    .libafl_runtime("/tmp/libafl_libfuzzer_runtime.a"),
+            ),
+            BuildOptions::builder()
+                .move_options(MoveBuildOptions::builder().bytecode_version(6u32).build())
+                .build(),
+            BuildOptions::builder().target_dir("/tmp/test").build(),
+        ]
+    }
+
+    #[test]
+    fn serde_build_options() {
+        let mut cases = build_options_cases();
+        // Not a command line flag, so only serde can carry it.
+        cases.push(cargo(CargoBuildOptions::builder().coverage(true)));
+        for case in cases {
+            let json = serde_json::to_string(&case).unwrap();
+            assert_eq!(case, serde_json::from_str::<BuildOptions>(&json).unwrap());
+        }
+    }
+
     #[test]
     fn display_build_options() {
-        let default_cargo_opts = CargoBuildOptions {
-            release: false,
-            debug_assertions: false,
-            no_default_features: false,
-            all_features: false,
-            features: None,
-            sanitizer: Sanitizer::Address,
-            build_std: false,
-            careful_mode: false,
-            triple: String::from(crate::utils::default_target()),
-            unstable_flags: Vec::new(),
-            coverage: false,
-            strip_dead_code: false,
-            no_cfg_fuzzing: false,
-            no_trace_compares: false,
-        };
-
-        let default_move_opts = MoveBuildOptions {
-            bytecode_version: Some(0),
-            fetch_deps_only: false,
-            force: false,
-            skip_fetch_latest_git_deps: false,
-        };
-
-        let default_move_opts = MoveBuildOptions {
-            bytecode_version: None,
-            fetch_deps_only: false,
-            force: false,
-            skip_fetch_latest_git_deps: false,
-        };
-
-        let default_opts = BuildOptions {
-            dev: false,
-            verbose: false,
-            target_dir: None,
-            cargo_options: default_cargo_opts.clone(),
-            move_options: default_move_opts.clone(),
-        };
-
-        let opts = vec![
-            default_opts.clone(),
-            BuildOptions {
-                dev: true,
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    release: true,
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    debug_assertions: true,
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                verbose: true,
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    no_default_features: true,
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    all_features: true,
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    features: Some(String::from("features")),
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    sanitizer: Sanitizer::None,
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    triple: String::from("custom_triple"),
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                cargo_options: CargoBuildOptions {
-                    unstable_flags: vec![String::from("unstable"), String::from("flags")],
-                    ..default_cargo_opts.clone()
-                },
-                ..default_opts.clone()
-            },
-            BuildOptions {
-                target_dir: Some(String::from("/tmp/test")),
-                ..default_opts.clone()
-            },
-            default_opts.clone(), // With coverage false
-        ];
-
-        let mut i = 0;
-        for case in opts {
-            println!("{i}");
-            i+=1;
-            println!("{:?}", case);
-            println!();
-            println!("{}", case);
-            println!();
+        // The `Display` output ends up in the reproduction hints, so it must parse back.
+        for case in build_options_cases() {
             assert_eq!(case, BuildOptions::parse_from(case.to_string().split(' ')));
         }
     }
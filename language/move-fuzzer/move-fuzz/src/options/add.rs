@@ -1,7 +1,7 @@
 use crate::project::{FuzzProject, Manifest};
 use crate::{options::FuzzDirWrapper, RunCommand};
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Clone, Debug, Parser)]
 pub struct Add {
@@ -10,6 +10,157 @@ pub struct Add {
 
     /// Name of the new fuzz target
     pub target: String,
+
+    #[arg(long)]
+    /// Use the plain libFuzzer entry point on raw bytes, without a Move script
+    pub raw: bool,
+
+    #[arg(long, conflicts_with = "raw")]
+    /// Decode inputs into a sequence of entry-function calls on a Move module
+    pub sequence: bool,
+
+    #[arg(long, conflicts_with_all = ["raw", "sequence"])]
+    /// Keep the VM and global storage alive across inputs instead of starting fresh
+    pub stateful: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "EXTENSIONS",
+        value_delimiter = ',',
+        num_args = 1..,
+        conflicts_with_all = ["raw", "sequence", "stateful", "template"]
+    )]
+    /// Run the Move script in sessions carrying the given VM extensions, e.g.
+    /// `table,events`, whose natives the harness registers
+    pub extensions: Vec<Extension>,
+
+    #[arg(long, value_enum, conflicts_with_all = ["raw", "sequence", "stateful"])]
+    /// Harness template to scaffold, the flags above are shorthands for some of them
+    pub template: Option<TargetKind>,
+
+    #[arg(long = "type", value_name = "TYPE")]
+    /// Type round-tripped by `--template bcs-roundtrip`, either a Move type such as
+    /// `vector<u64>` or the path of a Rust type implementing serde's traits
+    pub ty: Option<String>,
+
+    #[arg(long)]
+    /// Seed the corpus of a `--template move-parser` target with the `.move` files
+    /// of the package
+    pub seed_from_sources: bool,
+
+    #[arg(long, value_name = "FUNCTION")]
+    /// Move function exercised by the target, e.g. `0x1::bank::deposit`, recorded
+    /// under `[fuzz.targets]` in `Move.toml`
+    pub entry: Option<String>,
+}
+
+/// The kind of harness scaffolded for a new target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum TargetKind {
+    /// A Rust harness feeding arguments to a Move script
+    Script,
+    /// A plain libFuzzer entry point on raw bytes
+    Raw,
+    /// A Rust harness calling the entry functions of a Move module in sequence
+    Sequence,
+    /// A Rust harness feeding arguments to a Move script over persistent state
+    Stateful,
+    /// A compiler harness on Move source generated from a grammar
+    CompilerGrammar,
+    /// A front-end harness feeding inputs as Move source text to the lexer, parser
+    /// and expansion, with panics and hangs as findings
+    MoveParser,
+    /// A bytecode-verifier harness mutating inputs as compiled modules
+    Verifier,
+    /// A Rust harness comparing metered and unmetered executions of a Move script
+    GasOracle,
+    /// A Rust harness drawing the gas schedule from the input and checking charging
+    /// is monotonic in it
+    GasSchedule,
+    /// A Rust harness running entry-function calls both in the VM and in the
+    /// stackless bytecode interpreter and comparing their results
+    CrossInterpreter,
+    /// A Rust harness comparing executions of a Move script with and without the
+    /// paranoid type checks of the VM
+    Paranoid,
+    /// A disassembler harness checking listings survive a module round trip
+    Disassembler,
+    /// A harness checking BCS serialization round-trips for the type given by `--type`
+    BcsRoundtrip,
+    /// A Rust harness feeding arguments to a Move script in sessions carrying the
+    /// VM extensions, all of them unless `--extensions` selects some
+    Extensions,
+}
+
+/// A VM extension the harnesses of `--extensions` wire in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Extension {
+    /// Table storage, with the natives of the `table` package
+    Table,
+    /// Event streams, with the natives of the `nursery` package
+    Events,
+}
+
+impl Extension {
+    /// The package of natives the extension needs, as the harness names it.
+    pub fn native_package(self) -> &'static str {
+        match self {
+            Extension::Table => "Table",
+            Extension::Events => "Nursery",
+        }
+    }
+
+    /// The features of the harness crate the extension needs, declared as
+    /// those the target is built with.
+    pub fn features(self) -> &'static [&'static str] {
+        match self {
+            Extension::Table => &["libfuzzer/table-extension"],
+            Extension::Events => &[],
+        }
+    }
+}
+
+impl TargetKind {
+    /// The format of the inputs of the raw harnesses of this kind, declared as
+    /// their `input`, with `ty` the type of a `BcsRoundtrip` target. `None` for
+    /// the harnesses declaring their input type and for plain raw ones.
+    pub fn raw_input(self, ty: Option<&str>) -> Option<String> {
+        match self {
+            TargetKind::CompilerGrammar => Some("source-grammar".to_owned()),
+            TargetKind::MoveParser => Some("move-source".to_owned()),
+            TargetKind::Verifier | TargetKind::Disassembler => Some("bytecode".to_owned()),
+            TargetKind::BcsRoundtrip => ty.map(|ty| format!("bcs {}", ty.trim())),
+            _ => None,
+        }
+    }
+}
+
+impl Add {
+    pub fn kind(&self) -> TargetKind {
+        if let Some(template) = self.template {
+            template
+        } else if self.raw {
+            TargetKind::Raw
+        } else if self.sequence {
+            TargetKind::Sequence
+        } else if self.stateful {
+            TargetKind::Stateful
+        } else if !self.extensions.is_empty() {
+            TargetKind::Extensions
+        } else {
+            TargetKind::Script
+        }
+    }
+
+    /// The extensions wired in by an extensions target.
+    pub fn extensions(&self) -> Vec<Extension> {
+        if self.extensions.is_empty() {
+            Extension::value_variants().to_vec()
+        } else {
+            self.extensions.clone()
+        }
+    }
 }
 
 impl RunCommand for Add {
@@ -0,0 +1,27 @@
+use crate::project::FuzzProject;
+use crate::{options::FuzzDirWrapper, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Autoharness {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Path to the Move package to generate fuzz targets for: the public functions
+    /// annotated with `#[fuzz]`, or every public entry function if there are none
+    pub package: PathBuf,
+
+    #[arg(long, value_name = "TARGET")]
+    /// Generate a single target named TARGET calling every entry function, instead of one
+    /// target per function
+    pub combined: Option<String>,
+}
+
+impl RunCommand for Autoharness {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_autoharness(self)
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Bench {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target to benchmark, or benchmark all targets if not supplied
+    pub target: Option<String>,
+
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    /// Replay the corpus for this many seconds per target
+    pub time: u64,
+
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    /// Stop replaying a target after this many executions
+    pub runs: Option<u64>,
+
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    /// Number of slowest corpus entries to report per target
+    pub slowest: usize,
+
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["runs"])]
+    /// Instead of replaying the corpus, write a criterion benchmark crate
+    /// replaying a snapshot of it to DIR
+    pub export_criterion: Option<PathBuf>,
+}
+
+impl RunCommand for Bench {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_bench(self)
+    }
+}
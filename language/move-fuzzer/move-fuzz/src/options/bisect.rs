@@ -0,0 +1,42 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Bisect {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Input to bisect, usually a crash artifact
+    pub artifact: PathBuf,
+
+    #[arg(long, value_name = "REV")]
+    /// A revision the input does not crash at
+    pub good: String,
+
+    #[arg(long, value_name = "REV", default_value = "HEAD")]
+    /// A revision the input crashes at
+    pub bad: String,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Bisect {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_bisect(self)
+    }
+}
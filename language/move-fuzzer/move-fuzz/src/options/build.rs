@@ -1,5 +1,6 @@
 use crate::{
-    options::{BuildMode, BuildOptions, FuzzDirWrapper},
+    messages::{self, Message},
+    options::{BuildMode, BuildOptions, FuzzDirWrapper, MessageFormat},
     project::FuzzProject,
     RunCommand,
 };
@@ -15,13 +16,47 @@ pub struct Build {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     /// Name of the fuzz target to build, or build all targets if not supplied
+    /// (picked from a list on a terminal)
     pub target: Option<String>,
+
+    #[arg(long = "tag", value_name = "TAG", conflicts_with = "target")]
+    /// Only build the targets tagged TAG in `Move.toml`
+    pub tags: Vec<String>,
+
+    #[arg(long, conflicts_with = "target")]
+    /// Build the targets one by one, going on past the ones that fail, and
+    /// print how each went
+    pub keep_going: bool,
 }
 
 impl RunCommand for Build {
     fn run_command(&mut self) -> Result<()> {
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.exec_build(BuildMode::Build, &self.build, self.target.as_deref())
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_tags(&self.tags)?;
+        if self.target.is_none() && !self.keep_going && self.tags.is_empty() {
+            self.target = project.pick_target(true)?;
+        }
+        let result = if self.keep_going {
+            project.exec_build_each(BuildMode::Build, &self.build)
+        } else if !self.tags.is_empty() {
+            project.targets().iter().try_for_each(|target| {
+                project.exec_build(BuildMode::Build, &self.build, Some(target))
+            })
+        } else {
+            project.exec_build(BuildMode::Build, &self.build, self.target.as_deref())
+        };
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            Ok(Message::BuildFinished {
+                targets: match &self.target {
+                    Some(target) => vec![target.clone()],
+                    None => project.targets().to_vec(),
+                },
+            })
+        })
     }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    options::{BuildMode, BuildOptions, FuzzDirWrapper},
+    messages::{self, Message},
+    options::{BuildMode, BuildOptions, FuzzDirWrapper, MessageFormat},
     project::FuzzProject,
     RunCommand,
 };
@@ -14,13 +15,43 @@ pub struct Check {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     /// Name of the fuzz target to check, or check all targets if not supplied
     pub target: Option<String>,
+
+    #[arg(long, conflicts_with = "target")]
+    /// Audit the Move dependencies of the fuzz package instead of building: list
+    /// them and warn about missing dependencies, unregistered or nondeterministic
+    /// natives and enormous dependency closures
+    pub audit: bool,
+
+    #[arg(long, conflicts_with_all = ["target", "audit"])]
+    /// Check the targets one by one, going on past the ones that fail, and
+    /// print how each went
+    pub keep_going: bool,
 }
 
 impl RunCommand for Check {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.exec_build(BuildMode::Check, &self.build, self.target.as_deref())
+        if self.audit {
+            return project.exec_audit();
+        }
+        let result = if self.keep_going {
+            project.exec_build_each(BuildMode::Check, &self.build)
+        } else {
+            project.exec_build(BuildMode::Check, &self.build, self.target.as_deref())
+        };
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            Ok(Message::BuildFinished {
+                targets: match &self.target {
+                    Some(target) => vec![target.clone()],
+                    None => project.targets().to_vec(),
+                },
+            })
+        })
     }
 }
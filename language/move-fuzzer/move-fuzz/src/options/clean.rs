@@ -0,0 +1,51 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Clean {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Only clean the state of this fuzz target
+    pub target: Option<String>,
+
+    #[arg(long)]
+    /// Remove the corpus, which is never removed unless requested
+    pub corpus: bool,
+
+    #[arg(long)]
+    /// Remove the crash artifacts
+    pub artifacts: bool,
+
+    #[arg(long)]
+    /// Remove the coverage data
+    pub coverage: bool,
+
+    #[arg(long)]
+    /// Remove the compiled fuzz targets and Move package
+    pub builds: bool,
+
+    #[arg(long)]
+    /// Target dir the fuzz targets were built in, as given to `build --target-dir`
+    pub target_dir: Option<String>,
+
+    #[arg(short, long)]
+    /// Remove without asking for confirmation
+    pub yes: bool,
+}
+
+impl Clean {
+    /// Without a scope, everything but the corpus is removed, the scheduling
+    /// state of the runs included.
+    pub fn cleans_everything(&self) -> bool {
+        !(self.corpus || self.artifacts || self.coverage || self.builds)
+    }
+}
+
+impl RunCommand for Clean {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_clean(self)
+    }
+}
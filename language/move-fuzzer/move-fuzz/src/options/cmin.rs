@@ -1,11 +1,15 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    batch::Batch,
+    corpus,
+    messages::{self, Message},
+    options::{BuildOptions, FuzzDirWrapper, LockOptions, MessageFormat, StateDirs},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::Result;
 use clap::Parser;
 use std::path::PathBuf;
+use tracing::info;
 
 #[derive(Clone, Debug, Parser)]
 pub struct Cmin {
@@ -15,21 +19,79 @@ pub struct Cmin {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    #[arg(required_unless_present = "all_targets")]
     /// Name of the fuzz target
-    pub target: String,
+    pub target: Option<String>,
 
-    #[arg()]
+    #[arg(conflicts_with = "all_targets")]
     /// The corpus directory to minify into
     pub corpus: Option<PathBuf>,
 
+    #[arg(long, conflicts_with = "target")]
+    /// Minimize the corpus of every target, see `--fail-fast`
+    pub all_targets: bool,
+
+    #[arg(long)]
+    /// Start over instead of resuming an interrupted minimization
+    pub restart: bool,
+
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+impl Cmin {
+    /// The fuzz target, given or set for each target with `--all-targets`.
+    pub fn target(&self) -> &str {
+        self.target
+            .as_deref()
+            .expect("the target is set before the corpus is minimized")
+    }
+
+    fn run_target(&self, project: &FuzzProject) -> Result<()> {
+        let corpus = match &self.corpus {
+            Some(corpus) => corpus.clone(),
+            None => project.corpus_for(self.target())?,
+        };
+        let inputs_before = corpus::entries(&corpus).map_or(0, |entries| entries.len());
+        let result = project.exec_cmin(self);
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            Ok(Message::CminFinished {
+                target: self.target().to_owned(),
+                inputs_after: corpus::entries(&corpus)?.len(),
+                corpus,
+                inputs_before,
+            })
+        })
+    }
+}
+
 impl RunCommand for Cmin {
     fn run_command(&mut self) -> Result<()> {
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.exec_cmin(self)
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
+        if !self.all_targets {
+            return self.run_target(&project);
+        }
+        let mut batch = Batch::default();
+        for target in project.targets() {
+            let cmin = Cmin {
+                target: Some(target.clone()),
+                ..self.clone()
+            };
+            batch.run(target, || cmin.run_target(&project));
+        }
+        info!("{}", batch.summary().trim_end());
+        batch.into_result()
     }
 }
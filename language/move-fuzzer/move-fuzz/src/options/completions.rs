@@ -0,0 +1,23 @@
+use crate::{cli, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+use clap_complete::Shell;
+use std::io::{self, Write};
+
+#[derive(Clone, Debug, Parser)]
+pub struct Completions {
+    #[arg(value_enum)]
+    /// Shell to complete the commands and options for
+    pub shell: Shell,
+}
+
+impl RunCommand for Completions {
+    fn run_command(&mut self) -> Result<()> {
+        let mut cmd = cli::command();
+        let name = cmd.get_name().to_owned();
+        // Generated in memory, as `generate` panics when it cannot write.
+        let mut script = Vec::new();
+        clap_complete::generate(self.shell, &mut cmd, name, &mut script);
+        Ok(io::stdout().write_all(&script)?)
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper, LockOptions},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Corpus {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[command(subcommand)]
+    pub action: CorpusAction,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CorpusAction {
+    /// Copy input files into the corpus of a target, skipping duplicates
+    Add {
+        /// Name of the fuzz target
+        target: String,
+
+        /// Input files to add
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// List the entries of the corpus of a target
+    List {
+        /// Name of the fuzz target
+        target: String,
+    },
+
+    /// Show how an input got into the corpus of a target: found while fuzzing,
+    /// seeded, imported, merged or distilled, and when. Also counts the entries
+    /// of that corpus obtained each way
+    Info {
+        /// Input to look up, in a corpus or anywhere else
+        file: PathBuf,
+
+        #[arg(long)]
+        /// Only look the input up in the corpus of this target
+        target: Option<String>,
+    },
+
+    /// Remove duplicate and oversized entries from the corpus of a target, then
+    /// evict the corpus entries, artifacts and coverage snapshots of the target
+    /// beyond the `[fuzz.quota]` of `Move.toml`
+    Gc {
+        #[command(flatten)]
+        build: BuildOptions,
+
+        #[command(flatten)]
+        lock: LockOptions,
+
+        /// Name of the fuzz target
+        target: String,
+
+        #[arg(long)]
+        /// Remove entries larger than this many bytes
+        max_size: Option<u64>,
+
+        #[arg(long)]
+        /// Also replace the entries identical to one in the corpus or the seed
+        /// directories of the target with hard links to it
+        dedupe: bool,
+    },
+
+    /// Write the corpus of a target into a tar archive
+    Export {
+        /// Name of the fuzz target
+        target: String,
+
+        /// Path of the tar archive to create
+        archive: PathBuf,
+    },
+
+    /// Add the entries of a tar archive to the corpus of a target, skipping duplicates
+    Import {
+        /// Name of the fuzz target
+        target: String,
+
+        /// Path of the tar archive to read
+        archive: PathBuf,
+    },
+
+    /// Re-encode the corpus of a target after its input type changed.
+    ///
+    /// The converter reads an input of the old type on stdin and writes the
+    /// same input of the new type on stdout, or exits with a failure status if
+    /// it cannot convert it. The old corpus is kept in the backup directory.
+    Migrate {
+        /// Name of the fuzz target
+        target: String,
+
+        #[arg(long = "with", value_name = "COMMAND")]
+        /// Shell command converting an input
+        converter: String,
+
+        #[arg(long, value_name = "DIR")]
+        /// Where to move the old corpus, `<corpus>/<target>.orig` by default
+        backup: Option<PathBuf>,
+    },
+
+    /// Turn recorded transactions into seeds for a sequence target.
+    ///
+    /// The file holds a JSON array of `{"function": "0x42::bank::deposit",
+    /// "type_args": [], "signers": ["0xa11ce"], "args": ["<BCS hex>"]}` objects,
+    /// or the same records BCS-encoded. Arguments are re-encoded assuming the
+    /// harness generates them with `u.arbitrary()` in parameter order, reading
+    /// the parameters from the compiled fuzz package and its dependencies.
+    /// Transactions with type arguments are skipped: sequences cannot encode them.
+    ImportTransactions {
+        /// Name of the fuzz target
+        target: String,
+
+        /// JSON or BCS dump of the transactions
+        file: PathBuf,
+    },
+
+    /// Add the inputs a proptest strategy generates to the corpus of a target.
+    ///
+    /// The target registers the strategy with `fuzz_proptest_seeds!`, from the
+    /// `proptest-seeds` feature of libfuzzer, and is run to generate the inputs
+    /// instead of fuzzing.
+    SeedFromProptest {
+        #[command(flatten)]
+        build: BuildOptions,
+
+        /// Name of the fuzz target
+        target: String,
+
+        #[arg(long, value_name = "N", default_value = "256")]
+        /// Number of values to generate
+        cases: u32,
+
+        #[arg(long)]
+        /// Seed of the random number generator, for the same inputs on every run
+        seed: Option<u64>,
+    },
+}
+
+impl RunCommand for Corpus {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_corpus(&self.action)
+    }
+}
@@ -1,46 +1,169 @@
 use std::path::PathBuf;
 
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    batch::Batch,
+    messages::{self, Message},
+    options::{BuildOptions, FuzzDirWrapper, MessageFormat, StateDirs},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::{bail, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use tracing::info;
 
 #[derive(Clone, Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Coverage {
+    #[command(subcommand)]
+    pub action: Option<CoverageAction>,
+
     #[command(flatten)]
     pub build: BuildOptions,
 
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
     /// Sets the path to the LLVM bin directory. By default, it will use the one installed with rustc
     #[arg(long)]
     pub llvm_path: Option<PathBuf>,
 
-    /// Name of the fuzz target
-    pub target: String,
+    /// Name of the fuzz target, picked from a list if not supplied
+    pub target: Option<String>,
 
+    #[arg(conflicts_with = "all")]
     /// Custom corpus directories or artifact files
     pub corpus: Vec<String>,
 
+    #[arg(long, conflicts_with_all = ["target", "coverage_map"])]
+    /// Collect the coverage of every target, see `--fail-fast`
+    pub all: bool,
+
+    #[arg(long)]
+    /// List the regions of the package and the Move VM crates that few corpus inputs hit,
+    /// running every input separately
+    pub rare_branches: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 2,
+        requires = "rare_branches"
+    )]
+    /// Report the regions hit by at most N inputs
+    pub rare_threshold: usize,
+
+    #[arg(long, value_name = "PACKAGE")]
+    /// Also write the Move coverage of the corpus to PACKAGE/.coverage_map.mvcov,
+    /// where `move coverage` and the IDE read it, PACKAGE being the Move package
+    /// under test
+    pub coverage_map: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+#[derive(Clone, Debug, Subcommand)]
+pub enum CoverageAction {
+    /// Compare the Move coverage of two corpora, module by module
+    Diff {
+        /// Coverage before the change: a CSV summary written by `coverage --coverage-map`
+        /// or `move coverage summary --csv`, or a Move package with a coverage map
+        baseline: PathBuf,
+
+        /// Coverage after the change, given as BASELINE is
+        new: PathBuf,
+
+        #[arg(long, value_enum, default_value = "human")]
+        /// Format of the comparison, `markdown` being meant for pull request comments
+        /// and `github` annotating the modules whose coverage regressed
+        format: DiffFormat,
+
+        #[arg(short, long)]
+        /// Write the comparison to this file instead of stdout
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum DiffFormat {
+    /// Text meant for terminals
+    #[default]
+    Human,
+    /// A table of the modules whose coverage changed
+    Markdown,
+    /// GitHub Actions warnings for the modules whose coverage regressed
+    Github,
+}
+
+impl Coverage {
+    /// The fuzz target, once picked.
+    pub fn target(&self) -> &str {
+        self.target
+            .as_deref()
+            .expect("the target is picked before coverage is collected")
+    }
+}
+
 impl RunCommand for Coverage {
     fn run_command(&mut self) -> Result<()> {
+        if let Some(CoverageAction::Diff {
+            baseline,
+            new,
+            format,
+            output,
+        }) = &self.action
+        {
+            return crate::project::exec_coverage_diff(baseline, new, *format, output.as_deref());
+        }
         if self.build.cargo_options.build_std {
             bail!(
                 "-Zbuild-std is currently incompatible with -Zinstrument-coverage, \
                 see https://github.com/rust-lang/wg-cargo-std-aware/issues/63"
             );
         }
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
         self.build.cargo_options.coverage = true;
-        project.exec_coverage(self)
+        // The Move VM only traces the instructions it runs with debug assertions.
+        if self.coverage_map.is_some() {
+            self.build.cargo_options.debug_assertions = true;
+        }
+        if !self.all {
+            if self.target.is_none() {
+                self.target = project.pick_target(false)?;
+            }
+            return self.run_target(&project);
+        }
+        let mut batch = Batch::default();
+        for target in project.targets() {
+            let coverage = Coverage {
+                target: Some(target.clone()),
+                ..self.clone()
+            };
+            batch.run(target, || coverage.run_target(&project));
+        }
+        info!("{}", batch.summary().trim_end());
+        batch.into_result()
+    }
+}
+
+impl Coverage {
+    fn run_target(&self, project: &FuzzProject) -> Result<()> {
+        let (_, profdata) = project.coverage_for(self.target())?;
+        let result = project.exec_coverage(self);
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            Ok(Message::CoverageFinished {
+                target: self.target().to_owned(),
+                profdata,
+            })
+        })
     }
 }
@@ -0,0 +1,34 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct DiffRun {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(long, value_name = "REV_OR_DIR")]
+    /// Git revision or checkout directory of the package to compare against
+    pub baseline: String,
+
+    /// Input files or directories to execute, defaults to the target's corpus
+    pub inputs: Vec<PathBuf>,
+}
+
+impl RunCommand for DiffRun {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_diff_run(self)
+    }
+}
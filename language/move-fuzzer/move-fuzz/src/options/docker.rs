@@ -0,0 +1,42 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Docker {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(long, value_name = "TAG")]
+    /// Tag of the image, defaults to `move-fuzz-<target>`
+    pub tag: Option<String>,
+
+    #[arg(long, value_name = "IMAGE", default_value = "debian:bookworm-slim")]
+    /// Base image, its libc must be compatible with the one the target was built against
+    pub base: String,
+
+    #[arg(long)]
+    /// Run the image once built, with the local corpus and artifacts directories mounted
+    pub run: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments the image runs the target with
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Docker {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_docker(self)
+    }
+}
@@ -1,12 +1,21 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    options::{BuildOptions, FuzzDirWrapper, StateDirs},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
 
+/// A text encoding of inputs that survives being pasted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum InputEncoding {
+    Hex,
+    Base64,
+    /// A Rust array of bytes, for unit tests
+    RustArray,
+}
+
 #[derive(Clone, Debug, Parser)]
 pub struct Fmt {
     #[command(flatten)]
@@ -15,16 +24,43 @@ pub struct Fmt {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
     /// Name of fuzz target
     pub target: String,
 
-    /// Path to the input testcase to debug print
+    /// Path to the input testcase to debug print, or the name of one of the
+    /// artifacts or corpus inputs of the target
     pub input: PathBuf,
+
+    #[arg(long, value_enum, value_name = "ENCODING", conflicts_with = "decode")]
+    /// Print the input in a text encoding instead of debug printing it
+    pub encode: Option<InputEncoding>,
+
+    #[arg(long, value_enum, value_name = "ENCODING")]
+    /// Decode the text in the input file, or stdin if it is `-`, back into the
+    /// input it encodes
+    pub decode: Option<InputEncoding>,
+
+    #[arg(long, conflicts_with_all = ["encode", "decode"])]
+    /// Print the backtrace of the crash the artifact causes, demangled, with the
+    /// source of the frames in the package. It is reproduced if the artifact
+    /// was found before backtraces were kept
+    pub backtrace: bool,
+
+    #[arg(short, long, value_name = "FILE", requires = "decode")]
+    /// Write the decoded input to FILE instead of stdout
+    pub output: Option<PathBuf>,
 }
 
 impl RunCommand for Fmt {
     fn run_command(&mut self) -> Result<()> {
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
+        if self.decode.is_none() {
+            self.input = project.find_input(&self.target, &self.input);
+        }
         project.debug_fmt_input(self)
     }
 }
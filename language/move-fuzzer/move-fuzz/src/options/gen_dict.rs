@@ -0,0 +1,46 @@
+use crate::project::FuzzProject;
+use crate::{options::FuzzDirWrapper, RunCommand};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+/// The targets a dictionary is for, which decides how its tokens are encoded.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum DictKind {
+    /// Targets of this project: addresses and constants as harnesses decode
+    /// them from inputs
+    Arguments,
+    /// Targets deserializing raw bytes: identifiers, addresses and constants as
+    /// serialized in compiled modules
+    Bytecode,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct GenDict {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long, value_name = "PATH")]
+    /// Move package whose modules to read, the fuzz package by default
+    pub package: Option<PathBuf>,
+
+    #[arg(
+        long = "for",
+        value_enum,
+        value_name = "KIND",
+        default_value = "arguments"
+    )]
+    /// Targets the dictionary is for
+    pub kind: DictKind,
+
+    #[arg(short, long, value_name = "FILE")]
+    /// Write the dictionary to FILE instead of stdout, for `-- -dict=FILE`
+    pub output: Option<PathBuf>,
+}
+
+impl RunCommand for GenDict {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_gen_dict(self)
+    }
+}
@@ -1,16 +1,44 @@
-use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use crate::{
+    messages::{self, Message},
+    options::{FuzzDirWrapper, MessageFormat},
+    project::FuzzProject,
+    RunCommand,
+};
 use anyhow::Result;
 use clap::Parser;
 
 #[derive(Clone, Debug, Parser)]
 pub struct List {
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Only list the targets whose harness or Move package changed since their
+    /// binary was built, or that were never built
+    pub outdated: bool,
 }
 
 impl RunCommand for List {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        if self.message_format == MessageFormat::Json {
+            return messages::report(self.message_format, project.fuzz_dir(), Ok(()), || {
+                let targets = if self.outdated {
+                    let outdated = project.outdated_targets()?;
+                    outdated.into_iter().map(|(target, _)| target).collect()
+                } else {
+                    project.targets().to_vec()
+                };
+                Ok(Message::TargetList { targets })
+            });
+        }
+        if self.outdated {
+            return project.list_outdated_targets();
+        }
         project.list_targets()
     }
 }
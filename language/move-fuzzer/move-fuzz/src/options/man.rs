@@ -0,0 +1,48 @@
+use crate::{cli, RunCommand};
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::{fs, io, path::PathBuf};
+
+#[derive(Clone, Debug, Parser)]
+pub struct Man {
+    #[arg(long, value_name = "DIR")]
+    /// Write a page per command to DIR, as `move-fuzz-run.1`, instead of
+    /// printing the page of `move-fuzz` on stdout
+    pub out_dir: Option<PathBuf>,
+}
+
+impl RunCommand for Man {
+    fn run_command(&mut self) -> Result<()> {
+        let cmd = cli::command();
+        match &self.out_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+                clap_mangen::generate_to(cmd, dir)
+                    .with_context(|| format!("failed to write man pages to {}", dir.display()))?;
+            }
+            None => clap_mangen::Man::new(cmd).render(&mut io::stdout())?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn page_per_command() {
+        let dir = tempfile::tempdir().unwrap();
+        Man {
+            out_dir: Some(dir.path().join("man")),
+        }
+        .run_command()
+        .unwrap();
+        let page = |name: &str| fs::read_to_string(dir.path().join("man").join(name)).unwrap();
+        assert!(page("move-fuzz.1").contains("\\-\\-log\\-level"));
+        assert!(page("move-fuzz-corpus-gc.1").contains("corpus"));
+        // The notes on the libFuzzer flags passed through are in the pages too.
+        assert!(page("move-fuzz-run.1").contains("max_len"));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper, LockOptions, StateDirs},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Merge {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(required = true)]
+    /// The corpora to merge the inputs adding coverage from
+    pub from: Vec<PathBuf>,
+
+    #[arg(long, value_name = "DIR")]
+    /// The corpus to merge into, the corpus of the target by default
+    pub into: Option<PathBuf>,
+
+    #[arg(long, value_name = "PATH")]
+    /// The control file recording the progress of the merge, resumed from if it
+    /// lists the same inputs. `<fuzz dir>/.tmp/<target>/merge.txt` by default
+    pub control_file: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Start over instead of resuming an interrupted merge
+    pub restart: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Merge {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
+        project.exec_merge(self)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper, StateDirs},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct ProcessCrash {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// The crash artifact, or the name of one of the artifacts of the target
+    pub artifact: PathBuf,
+
+    #[arg(
+        short = 'r',
+        long,
+        default_value = "255",
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    /// Number of minimization attempts to perform
+    pub runs: u32,
+
+    #[arg(long, value_name = "DIR")]
+    /// Write the bug bundle to DIR instead of `bugs/<target>/<artifact>` in the
+    /// fuzz directory
+    pub out: Option<PathBuf>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for ProcessCrash {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
+        self.artifact = project.find_input(&self.target, &self.artifact);
+        project.exec_process_crash(self)
+    }
+}
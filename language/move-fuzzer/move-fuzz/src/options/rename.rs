@@ -0,0 +1,22 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Rename {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Current name of the fuzz target
+    pub target: String,
+
+    /// New name of the fuzz target
+    pub new_name: String,
+}
+
+impl RunCommand for Rename {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.rename_target(self)
+    }
+}
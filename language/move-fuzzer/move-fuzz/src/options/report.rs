@@ -0,0 +1,32 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Report {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long, value_enum, default_value = "markdown")]
+    /// Format of the report
+    pub format: ReportFormat,
+
+    #[arg(short, long, value_name = "FILE")]
+    /// Write the report to a file instead of stdout
+    pub output: Option<PathBuf>,
+}
+
+impl RunCommand for Report {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_report(self)
+    }
+}
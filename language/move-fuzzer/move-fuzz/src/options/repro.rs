@@ -0,0 +1,66 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Recorder {
+    /// Record with rr, for deterministic replay with `rr replay`
+    Rr,
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Repro {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Input to reproduce, usually a crash artifact
+    pub artifact: PathBuf,
+
+    #[arg(long, value_enum, value_name = "RECORDER")]
+    /// Record the execution and store the recording next to the artifact
+    pub record: Option<Recorder>,
+
+    #[arg(long, conflicts_with = "record")]
+    /// Show the bytecode of the Move function the input fails in, with the
+    /// instruction that aborted or failed marked
+    pub view_bytecode: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "1",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "record"
+    )]
+    /// Run the input N times and report how often and how it fails, to tell
+    /// nondeterministic crashes apart
+    pub times: u32,
+
+    #[arg(long, conflicts_with_all = ["record", "view_bytecode", "times"])]
+    /// Profile the allocations of the input with DHAT and report its peak heap
+    /// usage and hottest allocation sites. Memory limits are lifted so that
+    /// out-of-memory inputs run to the end
+    pub heap_profile: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Repro {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_repro(self)
+    }
+}
@@ -1,18 +1,47 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    flags,
+    messages::{self, Message},
+    options::{BuildOptions, FuzzDirWrapper, LockOptions, MessageFormat, StateDirs},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use std::{path::PathBuf, time};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Schedule {
+    /// Give every target the same share of the budget
+    RoundRobin,
+    /// Favor the targets still discovering new coverage
+    Coverage,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum LibaflMutator {
+    /// Grammar-based mutations of input fragments seen in the corpus
+    Grimoire,
+    /// Mutations that keep strings valid Unicode
+    Unicode,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum VerifierLimits {
+    /// Verify with the metering and limits of production
+    Production,
+    /// Verify without metering or limits
+    Unbounded,
+    /// Verify both ways and save the modules verified differently as crashes
+    Compare,
+}
 
 #[derive(Clone, Debug, Parser)]
 pub struct Run {
     #[command(flatten)]
     pub build: BuildOptions,
 
-    /// Name of the fuzz target
-    pub target: String,
+    /// Name of the fuzz target, picked from a list if not supplied
+    pub target: Option<String>,
 
     /// Custom corpus directories or artifact files.
     pub corpus: Vec<String>,
@@ -20,6 +49,12 @@ pub struct Run {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
+    #[command(flatten)]
+    pub lock: LockOptions,
+
     #[arg(
         short,
         long,
@@ -29,14 +64,256 @@ pub struct Run {
     /// Number of concurrent jobs to run
     pub jobs: u16,
 
+    #[arg(long)]
+    /// Fork the harness after the Move setup and run each input in a copy of it
+    pub snapshot: bool,
+
+    #[arg(long)]
+    /// Re-run crashing inputs with Move VM tracing and store the instruction trace
+    /// in `artifacts/<target>/traces/`
+    pub trace: bool,
+
+    #[arg(long, value_name = "VERSIONS", value_delimiter = ',', num_args = 1..)]
+    /// Also run every input with the package published at each of VERSIONS, e.g.
+    /// `5,6`, and save those whose outcome depends on the version as crashes
+    pub bytecode_versions: Vec<u32>,
+
+    #[arg(long)]
+    /// Also run every input with and without the paranoid type checks of the VM
+    /// and save those whose outcome depends on them as crashes
+    pub paranoid: bool,
+
+    #[arg(long)]
+    /// Also run the calls of sequence targets in the interpreter of the Move Prover
+    /// and save the inputs violating a spec condition (`aborts_if`, `ensures`,
+    /// invariants) of the functions they call as crashes
+    pub check_specs: bool,
+
+    #[arg(long, value_enum, value_name = "LIMITS")]
+    /// Limits the bytecode verifier of verifier targets runs with, `production` by
+    /// default
+    pub verifier_limits: Option<VerifierLimits>,
+
+    #[arg(long, value_name = "UNITS")]
+    /// Meter budget of the verifier per function and per module with production
+    /// limits, instead of that of production
+    pub verifier_meter_units: Option<u64>,
+
+    #[arg(long, value_name = "N")]
+    /// Back edges the verifier allows per function with production limits,
+    /// unlimited by default
+    pub verifier_back_edges: Option<usize>,
+
+    #[arg(long, value_name = "SNAPSHOT")]
+    /// Start stateful targets from the global storage saved in SNAPSHOT instead of
+    /// genesis, see `--save-state`
+    pub state: Option<PathBuf>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Save the global storage of stateful targets to FILE after every input; with
+    /// input files given as the corpus, it holds the state they leave once run in order
+    pub save_state: Option<PathBuf>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Write the crashes found to FILE in SARIF format
+    pub sarif: Option<PathBuf>,
+
+    #[arg(long, value_name = "TEMPLATE")]
+    /// Rename the artifacts found after TEMPLATE, where `{target}`, `{class}`
+    /// (`crash`, `oom`...), `{hash}` (of the input) and `{timestamp}` are
+    /// replaced, instead of keeping the names libFuzzer gives them; usually set
+    /// once for the project as `artifact-name` under `[fuzz.defaults.run]`
+    pub artifact_name: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["target", "corpus"])]
+    /// Fuzz every target in time slices, sharing a time budget
+    pub all: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "corpus", "all", "concurrent", "tags", "hunt_oom", "dry_run", "hunt_timeouts",
+            "concolic", "coverage_after", "time", "max_total_time", "slice", "schedule", "jobs",
+            "state", "save_state", "libafl_tui",
+        ]
+    )]
+    /// Run the target once on FILE, `-` to read it from stdin, instead of fuzzing,
+    /// and report how it went: the output of the target, then the failure, the
+    /// backtrace and the artifact saved if it fails
+    pub input: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Print the flags the engine of `--engine` takes after `--` and exit
+    pub engine_help: bool,
+
+    #[arg(long)]
+    /// Print the binary, environment, libFuzzer arguments and corpus directories
+    /// each target would be fuzzed with, without building or fuzzing
+    pub dry_run: bool,
+
+    #[arg(
+        long,
+        value_name = "TARGET",
+        num_args = 1..,
+        conflicts_with_all = ["target", "corpus", "all", "tags", "hunt_oom"]
+    )]
+    /// Fuzz all the targets TARGET... at once, each in its own process, tagging
+    /// their output with the target and streaming their stats to
+    /// `logs/<target>.stats.jsonl`
+    pub concurrent: Vec<String>,
+
+    #[arg(long = "tag", value_name = "TAG", conflicts_with = "target")]
+    /// Fuzz the targets tagged TAG in `Move.toml` as `--all` fuzzes every target
+    pub tags: Vec<String>,
+
+    #[arg(long, value_name = "SECS", conflicts_with = "target")]
+    /// Time budget of `--all`, one hour by default
+    pub time: Option<u64>,
+
+    #[arg(long, value_name = "SECS", conflicts_with = "all")]
+    /// Stop fuzzing after SECS seconds as on SIGTERM: the inputs the jobs of
+    /// `--jobs` found but did not merge yet are merged into the corpus. A
+    /// libFuzzer `-max_total_time` is handled the same way
+    pub max_total_time: Option<u64>,
+
+    #[arg(long, value_name = "SECS", conflicts_with = "target")]
+    /// Length of the time slices of `--all`, five minutes by default
+    pub slice: Option<u64>,
+
+    #[arg(long, value_enum, conflicts_with = "target")]
+    /// How `--all` picks the target of the next slice, `coverage` by default
+    pub schedule: Option<Schedule>,
+
+    #[arg(long, value_name = "MS")]
+    /// Save every input running for longer than MS milliseconds as a finding,
+    /// along with a profile of it in `artifacts/<target>/profiles/`
+    pub hunt_timeouts: Option<u64>,
+
+    #[arg(long, value_name = "START_MB", conflicts_with = "all")]
+    /// Fuzz under a memory limit halved at each level, from START_MB down to 64 MB,
+    /// and rank the inputs that go over it by peak RSS
+    pub hunt_oom: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value = "300",
+        requires = "hunt_oom"
+    )]
+    /// Seconds spent at each memory limit of `--hunt-oom`
+    pub hunt_oom_time: u64,
+
+    #[arg(long, value_name = "BINARY")]
+    /// SymCC or SymSan build of the target: corpus inputs are periodically replayed
+    /// through it and the inputs solved from its branch constraints added to the corpus
+    pub concolic: Option<PathBuf>,
+
+    #[arg(long, value_name = "SECS", default_value = "60", requires = "concolic")]
+    /// Seconds between two rounds of `--concolic`
+    pub concolic_interval: u64,
+
+    #[arg(long = "libafl-mutator", value_enum, value_name = "MUTATOR")]
+    /// Extra LibAFL mutator stages to enable on top of havoc, with `--engine libafl`
+    pub libafl_mutators: Vec<LibaflMutator>,
+
+    #[arg(long)]
+    /// Show the LibAFL terminal monitor instead of the plain log, with `--engine libafl`
+    pub libafl_tui: bool,
+
+    #[arg(long)]
+    /// Drop the scheduling state earlier runs left in `scheduling/<target>/`, the
+    /// focus function picked by `-focus_function=auto`, and start it over
+    pub fresh_schedule: bool,
+
+    #[arg(long)]
+    /// Once the campaign ends, replay the corpus of each target through a coverage
+    /// build and add the summary to `coverage/<target>/history.jsonl`
+    pub coverage_after: bool,
+
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+impl Run {
+    /// The targets the run fuzzes: those of `--concurrent`, TARGET, or else
+    /// all of `targets`.
+    pub fn targets(&self, targets: &[String]) -> Vec<String> {
+        if !self.concurrent.is_empty() {
+            return self.concurrent.clone();
+        }
+        match &self.target {
+            Some(target) => vec![target.clone()],
+            None => targets.to_vec(),
+        }
+    }
+}
+
 impl RunCommand for Run {
     fn run_command(&mut self) -> Result<()> {
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.exec_fuzz(self)
+        if self.engine_help {
+            flags::print_help(self.build.cargo_options.engine);
+            return Ok(());
+        }
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs)
+            .with_tags(&self.tags)?;
+        if !self.tags.is_empty() {
+            self.all = true;
+        }
+        if self.target.is_none() && !self.all && self.concurrent.is_empty() {
+            self.target = project.pick_target(false)?;
+        }
+        let start = time::SystemTime::now();
+        let result = project.exec_fuzz(self);
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            let targets = self.targets(project.targets());
+            let mut artifacts = Vec::new();
+            for target in &targets {
+                artifacts.extend(project.get_artifacts_since(target, &start)?);
+            }
+            artifacts.sort();
+            Ok(Message::RunFinished { targets, artifacts })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(args: &[&str]) -> clap::error::Result<Run> {
+        Run::try_parse_from(["run"].iter().chain(args))
+    }
+
+    #[test]
+    fn input_runs_the_target_once() {
+        let parsed = run(&["a", "--input", "crash-1", "--", "-rss_limit_mb=0"]).unwrap();
+        assert_eq!(parsed.input, Some(PathBuf::from("crash-1")));
+        assert_eq!(parsed.args, ["-rss_limit_mb=0"]);
+    }
+
+    #[test]
+    fn input_rejects_the_options_of_fuzzing() {
+        for option in [
+            &["--hunt-timeouts", "100"][..],
+            &["--max-total-time", "60"],
+            &["--jobs", "4"],
+            &["--save-state", "state.bcs"],
+            &["--coverage-after"],
+        ] {
+            let e = run(&[&["a", "--input", "crash-1"], option].concat()).unwrap_err();
+            assert_eq!(
+                e.kind(),
+                clap::error::ErrorKind::ArgumentConflict,
+                "{:?}",
+                option
+            );
+        }
     }
 }
@@ -0,0 +1,38 @@
+use crate::{schema, RunCommand};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+
+/// An output of move-fuzz that other tools read.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum SchemaKind {
+    /// The lines printed by `--message-format json`
+    Messages,
+    /// The `.meta.json` files next to the artifacts
+    ArtifactMetadata,
+    /// The crash buckets in `fuzz/triage.json`
+    Triage,
+}
+
+impl SchemaKind {
+    pub fn schema(self) -> &'static str {
+        match self {
+            SchemaKind::Messages => schema::MESSAGES,
+            SchemaKind::ArtifactMetadata => schema::ARTIFACT_METADATA,
+            SchemaKind::Triage => schema::TRIAGE,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Schema {
+    #[arg(value_enum)]
+    /// Output to print the JSON schema of
+    pub kind: SchemaKind,
+}
+
+impl RunCommand for Schema {
+    fn run_command(&mut self) -> Result<()> {
+        print!("{}", self.kind.schema());
+        Ok(())
+    }
+}
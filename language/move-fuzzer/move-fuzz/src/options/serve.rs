@@ -0,0 +1,36 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Serve {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7878")]
+    /// Address to listen on for workers
+    pub listen: String,
+
+    #[arg(long, value_name = "SECS", default_value_t = 600)]
+    /// Length of the time slices assigned to workers
+    pub slice: u64,
+
+    #[arg(long, value_name = "PATH")]
+    /// File holding the token workers must present, shared with `move-fuzz worker --token-file`
+    pub token_file: PathBuf,
+
+    #[arg(long, value_name = "N", default_value_t = 32)]
+    /// Largest number of workers served at once, the others being turned away
+    pub max_connections: usize,
+
+    /// Targets to hand out to workers, or all targets if none are supplied
+    pub targets: Vec<String>,
+}
+
+impl RunCommand for Serve {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_serve(self)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Smoke {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Names of the fuzz targets to test, all targets if none are given
+    pub targets: Vec<String>,
+
+    #[arg(long, value_name = "N", default_value_t = 1000)]
+    /// Executions each target must get through
+    pub runs: u64,
+
+    #[arg(long, value_name = "SECS", conflicts_with = "runs")]
+    /// Fuzz each target for SECS seconds instead of a number of executions
+    pub time: Option<u64>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Smoke {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_smoke(self)
+    }
+}
@@ -1,11 +1,15 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    messages::{self, Message},
+    options::{BuildOptions, FuzzDirWrapper, MessageFormat, StateDirs},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::Result;
 use clap::Parser;
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time,
+};
 
 #[derive(Clone, Debug, Parser)]
 pub struct Tmin {
@@ -15,6 +19,9 @@ pub struct Tmin {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
 
+    #[command(flatten)]
+    pub state_dirs: StateDirs,
+
     /// Name of the fuzz target
     pub target: String,
 
@@ -27,18 +34,66 @@ pub struct Tmin {
     /// Number of minimization attempts to perform
     pub runs: u32,
 
-    #[arg()]
-    /// Path to the failing test case to be minimized
-    pub test_case: PathBuf,
+    #[arg(long, value_name = "MS")]
+    /// Minimize a slow input found by `run --hunt-timeouts`, keeping it slower than MS milliseconds
+    pub hunt_timeouts: Option<u64>,
+
+    #[arg(required_unless_present = "module")]
+    /// Path to the failing test case to be minimized, or the name of one of
+    /// the artifacts of the target
+    pub test_case: Option<PathBuf>,
+
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["test_case", "hunt_timeouts"])]
+    /// Minimize a crashing input holding a compiled Move module, e.g. of a verifier
+    /// target, by removing its functions, struct definitions and instructions
+    pub module: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value = "human")]
+    /// Format of the outcome printed on stdout
+    pub message_format: MessageFormat,
 
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+impl Tmin {
+    /// The input being minimized, the test case or the module.
+    pub fn input(&self) -> &Path {
+        self.module
+            .as_deref()
+            .or(self.test_case.as_deref())
+            .expect("clap requires a test case or a module")
+    }
+}
+
 impl RunCommand for Tmin {
     fn run_command(&mut self) -> Result<()> {
-        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.exec_tmin(self)
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?
+            .with_state_dirs(&self.state_dirs);
+        if let Some(test_case) = &self.test_case {
+            self.test_case = Some(project.find_input(&self.target, test_case));
+        }
+        let start = time::SystemTime::now();
+        let result = match &self.module {
+            Some(module) => project.exec_tmin_module(self, module),
+            None => project.exec_tmin(self),
+        };
+        messages::report(self.message_format, project.fuzz_dir(), result, || {
+            // Same guess as `exec_tmin`: the newest artifact is the minimized one.
+            let minimized = project
+                .get_artifacts_since(&self.target, &start)?
+                .into_iter()
+                .max_by_key(|a| {
+                    a.metadata()
+                        .and_then(|m| m.modified())
+                        .unwrap_or(time::SystemTime::UNIX_EPOCH)
+                });
+            Ok(Message::TminFinished {
+                target: self.target.clone(),
+                test_case: self.input().to_owned(),
+                minimized,
+            })
+        })
     }
 }
@@ -0,0 +1,50 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Triage {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target to triage, or triage all targets if not supplied
+    pub target: Option<String>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Write the crashes found to FILE in SARIF format
+    pub sarif: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "1",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    /// Run each artifact N times and report how often it reproduces its crash,
+    /// listing the crashes that do not reproduce on every run separately
+    pub stability: u32,
+
+    #[arg(long)]
+    /// Do not write the `report.yaml` of each crash bucket, with its decoded
+    /// input, backtrace and reproduction command, to `triage/<target>/`
+    pub no_reports: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Triage {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_triage(self)
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Worker {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Address of the `move-fuzz serve` coordinator, e.g. `10.0.0.1:7878`
+    pub coordinator: String,
+
+    #[arg(long, value_name = "PATH")]
+    /// File holding the token of the coordinator, as given to `move-fuzz serve --token-file`
+    pub token_file: PathBuf,
+
+    #[arg(long, value_name = "N")]
+    /// Stop after this many time slices, instead of running until interrupted
+    pub slices: Option<u64>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Worker {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_worker(self)
+    }
+}
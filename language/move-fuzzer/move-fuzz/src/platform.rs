@@ -0,0 +1,137 @@
+//! What the sanitizers can do on each platform the fuzz targets are built for.
+//!
+//! rustc only supports some sanitizers on some targets, and building for
+//! another fails at link time. The targets are built with the address
+//! sanitizer instead, or without any if it is not supported either:
+//!
+//! | Platform                 | address | leak | memory | thread |
+//! |--------------------------|---------|------|--------|--------|
+//! | Linux                    | yes     | yes  | yes    | yes    |
+//! | macOS on Intel           | yes     | yes  | no     | yes    |
+//! | macOS on Apple Silicon   | yes     | yes  | no     | yes    |
+//! | Windows (MSVC)           | yes     | no   | no     | no     |
+//!
+//! The address sanitizer only detects leaks on Linux: elsewhere a leaking
+//! input is not a finding.
+
+use crate::options::Sanitizer;
+use std::sync::Once;
+use tracing::warn;
+
+/// Whether the targets built for `triple` can use `sanitizer`.
+pub fn supports(triple: &str, sanitizer: Sanitizer) -> bool {
+    let windows = triple.contains("-windows");
+    let apple = triple.contains("-apple-");
+    match sanitizer {
+        Sanitizer::None => true,
+        Sanitizer::Address => !triple.contains("-windows-gnu"),
+        Sanitizer::Leak => !windows,
+        Sanitizer::Memory => !windows && !apple,
+        Sanitizer::Thread => !windows,
+    }
+}
+
+/// The sanitizer the targets built for `triple` get when `sanitizer` is asked
+/// for: that one if supported, else the address sanitizer, else none.
+pub fn build_sanitizer(triple: &str, sanitizer: Sanitizer) -> Sanitizer {
+    if supports(triple, sanitizer) {
+        sanitizer
+    } else if supports(triple, Sanitizer::Address) {
+        Sanitizer::Address
+    } else {
+        Sanitizer::None
+    }
+}
+
+/// Whether the targets built for `triple` with `sanitizer` report leaks.
+pub fn detects_leaks(triple: &str, sanitizer: Sanitizer) -> bool {
+    match sanitizer {
+        Sanitizer::Address => triple.contains("-linux-"),
+        Sanitizer::Leak => supports(triple, Sanitizer::Leak),
+        _ => false,
+    }
+}
+
+/// How building for `triple` with `sanitizer` differs from what was asked.
+pub fn notes(triple: &str, sanitizer: Sanitizer) -> Vec<String> {
+    let mut notes = Vec::new();
+    let built = build_sanitizer(triple, sanitizer);
+    if built != sanitizer {
+        notes.push(match built {
+            Sanitizer::None => format!(
+                "{} does not support the {} sanitizer, building without one",
+                triple, sanitizer
+            ),
+            _ => format!(
+                "{} does not support the {} sanitizer, building with the {} sanitizer",
+                triple, sanitizer, built
+            ),
+        });
+    }
+    if matches!(sanitizer, Sanitizer::Address | Sanitizer::Leak) && !detects_leaks(triple, built) {
+        notes.push(format!(
+            "the {} sanitizer does not detect leaks on {}, leaking inputs are not findings",
+            built, triple
+        ));
+    }
+    notes
+}
+
+/// Warns about the [`notes`] of `triple` and `sanitizer`, once per process.
+pub fn warn_once(triple: &str, sanitizer: Sanitizer) {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        for note in notes(triple, sanitizer) {
+            warn!("{}", note);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sanitizers_by_platform() {
+        let linux = "x86_64-unknown-linux-gnu";
+        let apple_silicon = "aarch64-apple-darwin";
+        let windows = "x86_64-pc-windows-msvc";
+        assert_eq!(build_sanitizer(linux, Sanitizer::Memory), Sanitizer::Memory);
+        assert_eq!(
+            build_sanitizer(apple_silicon, Sanitizer::Leak),
+            Sanitizer::Leak
+        );
+        assert_eq!(
+            build_sanitizer(apple_silicon, Sanitizer::Memory),
+            Sanitizer::Address
+        );
+        assert_eq!(
+            build_sanitizer(apple_silicon, Sanitizer::Thread),
+            Sanitizer::Thread
+        );
+        assert_eq!(
+            build_sanitizer(windows, Sanitizer::Thread),
+            Sanitizer::Address
+        );
+        assert_eq!(
+            build_sanitizer("x86_64-pc-windows-gnu", Sanitizer::Address),
+            Sanitizer::None
+        );
+
+        assert!(notes(linux, Sanitizer::Address).is_empty());
+        assert!(notes(apple_silicon, Sanitizer::Leak).is_empty());
+        assert_eq!(
+            notes(windows, Sanitizer::Leak),
+            [
+                "x86_64-pc-windows-msvc does not support the leak sanitizer, \
+                 building with the address sanitizer",
+                "the address sanitizer does not detect leaks on x86_64-pc-windows-msvc, \
+                 leaking inputs are not findings",
+            ]
+        );
+        assert_eq!(
+            notes(apple_silicon, Sanitizer::Thread),
+            Vec::<String>::new()
+        );
+    }
+}
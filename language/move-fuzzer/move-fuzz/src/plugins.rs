@@ -0,0 +1,138 @@
+use crate::project::FuzzProject;
+use anyhow::{bail, Context, Result};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prefix of the executables run as `move-fuzz <name>`.
+pub const PLUGIN_PREFIX: &str = "move-fuzz-";
+
+/// Runs the subcommand `args[0]` as the `move-fuzz-<name>` executable found on
+/// the `PATH`, with the rest of `args`.
+///
+/// The plugin gets the path of `move-fuzz` in `MOVE_FUZZ`, to call back into
+/// it. Inside a fuzz project, honoring `--fuzz-dir`, it also gets the fuzz
+/// directory in `MOVE_FUZZ_DIR` and the comma-separated targets in `MOVE_FUZZ_TARGETS`.
+pub fn run(args: &[String]) -> Result<()> {
+    let (name, args) = args.split_first().context("no subcommand given")?;
+    let path = env::var_os("PATH").unwrap_or_default();
+    let plugin = find_plugin(name, env::split_paths(&path)).with_context(|| {
+        format!(
+            "no such command: `{}`, and no `{}{}` executable on the PATH",
+            name, PLUGIN_PREFIX, name
+        )
+    })?;
+
+    let mut cmd = Command::new(&plugin);
+    cmd.args(args).env("MOVE_FUZZ", env::current_exe()?);
+    // Plugins may well work outside of a fuzz project, e.g. to create one.
+    if let Ok(project) = FuzzProject::new(fuzz_dir_arg(args)) {
+        cmd.env("MOVE_FUZZ_DIR", project.fuzz_dir())
+            .env("MOVE_FUZZ_TARGETS", project.targets().join(","));
+    }
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to run {}", plugin.display()))?;
+    if !status.success() {
+        bail!("`{}{}` exited with {}", PLUGIN_PREFIX, name, status);
+    }
+    Ok(())
+}
+
+fn find_plugin(name: &str, dirs: impl Iterator<Item = PathBuf>) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, env::consts::EXE_SUFFIX);
+    dirs.map(|dir| dir.join(&file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Whether `path` is a file that can be run, as the shell looks for commands.
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// The value of `--fuzz-dir` among the arguments of a plugin.
+pub(crate) fn fuzz_dir_arg(args: &[String]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--fuzz-dir" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg.strip_prefix("--fuzz-dir=") {
+            return Some(PathBuf::from(dir));
+        }
+        if arg == "--" {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    /// Writes an executable `move-fuzz-<name>` to `dir`.
+    fn write_plugin(dir: &Path, name: &str) -> PathBuf {
+        let plugin = dir.join(format!(
+            "{}{}{}",
+            PLUGIN_PREFIX,
+            name,
+            env::consts::EXE_SUFFIX
+        ));
+        fs::write(&plugin, "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        plugin
+    }
+
+    #[test]
+    fn finds_plugins_on_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = write_plugin(dir.path(), "triage-bot");
+        let dirs = || vec![PathBuf::from("/nonexistent"), dir.path().to_owned()].into_iter();
+        assert_eq!(find_plugin("triage-bot", dirs()), Some(plugin.clone()));
+        assert_eq!(find_plugin("other", dirs()), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_plugins_that_cannot_run() {
+        use std::os::unix::fs::PermissionsExt;
+        let not_executable = tempfile::tempdir().unwrap();
+        let plugin = write_plugin(not_executable.path(), "triage-bot");
+        fs::set_permissions(&plugin, fs::Permissions::from_mode(0o644)).unwrap();
+        let executable = tempfile::tempdir().unwrap();
+        let found = write_plugin(executable.path(), "triage-bot");
+        let dirs = vec![
+            not_executable.path().to_owned(),
+            executable.path().to_owned(),
+        ];
+        assert_eq!(find_plugin("triage-bot", dirs.into_iter()), Some(found));
+    }
+
+    #[test]
+    fn finds_the_fuzz_dir_of_plugins() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            fuzz_dir_arg(&args(&["-v", "--fuzz-dir", "f"])),
+            Some(PathBuf::from("f"))
+        );
+        assert_eq!(
+            fuzz_dir_arg(&args(&["--fuzz-dir=g"])),
+            Some(PathBuf::from("g"))
+        );
+        assert_eq!(fuzz_dir_arg(&args(&["--", "--fuzz-dir=g"])), None);
+    }
+}
@@ -0,0 +1,257 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How [`Progress::terminal`] draws a task of known length.
+const BAR_TEMPLATE: &str = "{msg} [{bar:30}] {pos}/{len} ETA {eta}";
+
+/// How [`Progress::terminal`] draws a task of unknown length.
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} ({pos} done)";
+
+/// Whether `--no-progress` hides the bars of [`Progress::terminal`].
+static HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// A long operation that reports its progress.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Task {
+    /// Building the fuzz targets, one step for cargo and one for the Move package
+    Build,
+    /// Running the inputs of a corpus one after the other
+    Replay,
+    /// Minimizing a corpus with a libFuzzer merge
+    Cmin,
+    /// Minimizing a test case, one step per attempt
+    Tmin,
+    /// Merging raw coverage profiles
+    CoverageMerge,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Task::Build => "Building",
+                Task::Replay => "Replaying",
+                Task::Cmin => "Minimizing",
+                Task::Tmin => "Minimizing test case",
+                Task::CoverageMerge => "Merging coverage",
+            }
+        )
+    }
+}
+
+/// Where a task is at, sent when it starts, after each step and when it ends.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProgressEvent<'a> {
+    pub task: Task,
+    pub target: Option<&'a str>,
+    /// Steps done so far
+    pub done: u64,
+    /// Steps in total, when known up front
+    pub total: Option<u64>,
+    /// Time left, from the time the steps done took, when the total is known
+    pub eta: Option<Duration>,
+    pub finished: bool,
+}
+
+/// A progress callback registered through the library API.
+pub type ProgressCallback = Box<dyn Fn(&ProgressEvent)>;
+
+/// Where the progress of long operations goes: nowhere, to the bar drawn on
+/// the terminal by the CLI, or to a callback of a library user.
+#[derive(Default)]
+pub struct Progress {
+    callback: Option<ProgressCallback>,
+}
+
+impl Progress {
+    /// Draws a bar on stderr, when it is a terminal and `--no-progress` is not
+    /// set.
+    ///
+    /// Builds and the test case minimizations of libFuzzer are left out: cargo
+    /// and libFuzzer print their own progress, which a bar would only get mixed
+    /// with. The bar is drawn by `indicatif`, which redraws it at a bounded rate
+    /// and draws nothing when stderr turns out not to be a terminal it can
+    /// redraw lines of.
+    pub fn terminal() -> Self {
+        if HIDDEN.load(Ordering::Relaxed) || !io::stderr().is_terminal() {
+            return Progress::default();
+        }
+        let bar: RefCell<Option<ProgressBar>> = RefCell::new(None);
+        Progress::callback(Box::new(move |event| {
+            if event.task == Task::Build || (event.task == Task::Tmin && event.total.is_none()) {
+                return;
+            }
+            let mut bar = bar.borrow_mut();
+            let current = bar.get_or_insert_with(|| new_bar(event));
+            current.set_position(event.done);
+            if event.finished {
+                current.finish();
+                *bar = None;
+            }
+        }))
+    }
+
+    /// Has [`Progress::terminal`] draw nothing from now on, as `--no-progress` does.
+    pub fn hide_terminal() {
+        HIDDEN.store(true, Ordering::Relaxed);
+    }
+
+    pub fn callback(callback: ProgressCallback) -> Self {
+        Progress {
+            callback: Some(callback),
+        }
+    }
+
+    /// Starts reporting `task`, of `total` steps if known.
+    pub(crate) fn start<'a>(
+        &'a self,
+        task: Task,
+        target: Option<&'a str>,
+        total: Option<u64>,
+    ) -> TaskProgress<'a> {
+        let progress = TaskProgress {
+            progress: self,
+            task,
+            target,
+            total,
+            done: Cell::new(0),
+            started: Instant::now(),
+        };
+        progress.report(false);
+        progress
+    }
+}
+
+/// The progress of a task being run, which ends when it is dropped.
+pub(crate) struct TaskProgress<'a> {
+    progress: &'a Progress,
+    task: Task,
+    target: Option<&'a str>,
+    total: Option<u64>,
+    done: Cell<u64>,
+    started: Instant,
+}
+
+impl TaskProgress<'_> {
+    /// Counts one more step done.
+    pub(crate) fn step(&self) {
+        self.done.set(self.done.get() + 1);
+        self.report(false);
+    }
+
+    /// Counts `done` steps done so far, when the steps are counted elsewhere.
+    pub(crate) fn set(&self, done: u64) {
+        if done != self.done.get() {
+            self.done.set(done);
+            self.report(false);
+        }
+    }
+
+    fn report(&self, finished: bool) {
+        if let Some(callback) = &self.progress.callback {
+            callback(&ProgressEvent {
+                task: self.task,
+                target: self.target,
+                done: self.done.get(),
+                total: self.total,
+                eta: self.eta(),
+                finished,
+            });
+        }
+    }
+
+    /// The time the steps left take at the pace of those done.
+    fn eta(&self) -> Option<Duration> {
+        let done = self.done.get();
+        let left = self.total?.checked_sub(done)?;
+        if done == 0 || left == 0 {
+            return None;
+        }
+        Some(self.started.elapsed().mul_f64(left as f64 / done as f64))
+    }
+}
+
+impl Drop for TaskProgress<'_> {
+    fn drop(&mut self) {
+        self.report(true);
+    }
+}
+
+/// The bar drawing the task `event` starts.
+fn new_bar(event: &ProgressEvent) -> ProgressBar {
+    let total = event.total.filter(|&total| total > 0);
+    let template = if total.is_some() {
+        BAR_TEMPLATE
+    } else {
+        SPINNER_TEMPLATE
+    };
+    let bar = ProgressBar::with_draw_target(total, ProgressDrawTarget::stderr());
+    bar.set_style(
+        ProgressStyle::with_template(template)
+            .expect("the progress templates are valid")
+            .progress_chars("=> "),
+    );
+    bar.set_message(label(event));
+    bar
+}
+
+/// The task of `event` and its target, e.g. `Replaying deposit`.
+fn label(event: &ProgressEvent) -> String {
+    match event.target {
+        Some(target) => format!("{} {}", event.task, target),
+        None => event.task.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn reports_steps() {
+        let events = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let seen = events.clone();
+        let progress = Progress::callback(Box::new(move |event| {
+            seen.borrow_mut()
+                .push((label(event), event.done, event.total, event.finished));
+        }));
+        {
+            let task = progress.start(Task::Replay, Some("t"), Some(2));
+            task.step();
+            task.set(2);
+            task.set(2);
+        }
+        progress.start(Task::Cmin, None, None);
+        let replaying = || "Replaying t".to_owned();
+        assert_eq!(
+            *events.borrow(),
+            [
+                (replaying(), 0, Some(2), false),
+                (replaying(), 1, Some(2), false),
+                (replaying(), 2, Some(2), false),
+                (replaying(), 2, Some(2), true),
+                ("Minimizing".to_owned(), 0, None, false),
+                ("Minimizing".to_owned(), 0, None, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn eta_from_the_pace_of_the_steps_done() {
+        let progress = Progress::default();
+        let task = progress.start(Task::Tmin, None, Some(4));
+        assert_eq!(task.eta(), None);
+        task.set(2);
+        assert!(task.eta().is_some());
+        task.set(4);
+        assert_eq!(task.eta(), None);
+        assert_eq!(progress.start(Task::Cmin, None, None).eta(), None);
+    }
+}
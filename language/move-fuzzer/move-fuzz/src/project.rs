@@ -1,23 +1,116 @@
-use crate::options::{self, BuildMode, BuildOptions, CargoBuildOptions, Sanitizer};
-use crate::utils::default_target;
+use crate::audit;
+use crate::autoharness;
+use crate::backtrace;
+use crate::batch::{self, Batch};
+use crate::bisect::{self, Verdict};
+use crate::builds::{self, BuildLog, Staleness, BUILD_LOG_FILE};
+use crate::bytecode_view;
+use crate::careful::{self, Careful, Toolchain};
+use crate::cli;
+use crate::cluster::{self, Assignment};
+use crate::concolic::{self, Concolic};
+use crate::config;
+use crate::corpus;
+use crate::coverage_diff::{self, MOVE_COVERAGE_SUMMARY_FILE};
+use crate::crash_report::{self, BuildInfo, CrashReport, CRASH_REPORTS_DIR};
+use crate::dict::Dictionary;
+use crate::encoding;
+use crate::engine::{self, FuzzEngine, LibFuzzer, Outcome};
+use crate::error::{self, Error};
+use crate::events::{self, Event, EventKind, EVENTS_LOG_FILE};
+use crate::flags;
+use crate::heap::HeapReport;
+use crate::hooks::{HookContext, HookPoint, Hooks};
+use crate::lock::{self, Lock};
+use crate::logging::{self, Source};
+use crate::merge::{self, CMIN_CONTROL_FILE, MERGE_CONTROL_FILE, MERGE_DIR};
+use crate::metadata::{self, ArtifactMetadata, NameTemplate};
+use crate::modules;
+use crate::move_package::MovePackage;
+use crate::move_toml::{self, DeclaredTarget, Eviction, FuzzSection, Quota};
+use crate::notify::{self, NotifiedLog, Notifiers};
+use crate::options::{
+    self, BuildMode, BuildOptions, CargoBuildOptions, Engine, Sanitizer, StateDirs, TargetKind,
+};
+use crate::platform;
+use crate::progress::{Progress, ProgressEvent, Task};
+use crate::provenance::{self, Origin, ProvenanceIndex, PROVENANCE_DIR, PROVENANCE_INDEX_FILE};
+use crate::quota;
+use crate::rare::{self, RegionHits, RARE_BRANCHES_FILE};
+use crate::report::{
+    format_time, CoverageSnapshot, CoverageSummary, ProjectReport, RunLog, TargetReport,
+    COVERAGE_HISTORY_FILE, COVERAGE_SUMMARY_FILE, RUN_LOG_FILE,
+};
+use crate::sarif::{self, Finding};
+use crate::schedule::Scheduler;
+use crate::scheduling::{SchedulingState, FEATURES_DIR, SCHEDULING_DIR, SCHEDULING_STATE_FILE};
+use crate::shutdown::{self, IN_FLIGHT_DIR};
+use crate::smoke;
+use crate::symbolize;
+use crate::transactions::{self, SequenceHarness, Translator};
+use crate::triage::{
+    crash_signature, unix_now, Bucket, CrashDatabase, Stability, Status, TRIAGE_DB_FILE,
+};
+use crate::utils::{command_output, default_target, exe_suffix, quote_arg, shell};
 use anyhow::{anyhow, bail, Context, Result};
 use cargo_metadata::MetadataCommand;
-use std::collections::HashSet;
-use std::io::Read;
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use move_binary_format::access::ModuleAccess;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::{
-    env, ffi, fs,
-    process::{Command, Stdio},
-    time,
+    env, ffi, fmt, fs, net,
+    process::{self, Command, Stdio},
+    thread, time,
 };
+use toml_edit::{Document, Item, TableLike, Value};
+use tracing::{info, warn};
 
 const DEFAULT_FUZZ_DIR: &str = "fuzz";
+const REGRESSIONS_DIR: &str = "regressions";
+const BUGS_DIR: &str = "bugs";
+/// Lowest memory limit of `run --hunt-oom`, below it the Move VM setup alone goes over.
+const OOM_FLOOR_MB: u64 = 64;
+/// Allocation sites listed by `repro --heap-profile`.
+const HEAP_PROFILE_SITES: usize = 20;
+/// The libFuzzer flag limiting the time of a run, see `time_limit`.
+const MAX_TOTAL_TIME_FLAG: &str = "-max_total_time=";
+/// Where `repro --heap-profile` writes its profiles and reports, apart from the
+/// artifacts so that they are not taken for inputs.
+const HEAP_PROFILES_DIR: &str = "heap";
 
 pub struct FuzzProject {
     /// The project with fuzz targets
     fuzz_dir: PathBuf,
     targets: Vec<String>,
+    hooks: Hooks,
+    progress: Progress,
+    /// Where the corpora and artifacts go instead of the fuzz dir
+    state_dirs: StateDirs,
+    /// The `[fuzz]` section of `Move.toml`, once read
+    fuzz_section: RefCell<Option<Rc<FuzzSection>>>,
+}
+
+/// A fuzz target of a project, with the paths of its files and state.
+///
+/// The paths are where the files go, which may not exist yet.
+#[derive(Clone, Debug)]
+pub struct FuzzTarget {
+    pub name: String,
+    /// The harness, `fuzz_targets/<name>.rs` unless the `[[bin]]` entry has a `path`
+    pub source: PathBuf,
+    /// The Move module of the target, for the targets that have one
+    pub move_source: Option<PathBuf>,
+    pub corpus: PathBuf,
+    pub artifacts: PathBuf,
+    /// The `[[bin]]` entry of the target in the fuzz manifest
+    pub manifest: toml::value::Table,
+    /// The declaration of the target under `[fuzz.targets]` in `Move.toml`, if any
+    pub declared: Option<DeclaredTarget>,
 }
 
 impl FuzzProject {
@@ -41,9 +134,36 @@ impl FuzzProject {
             );
         }
         project.targets = collect_targets(&manifest);
+        project.hooks = Hooks::from_manifest(&manifest)?;
+        project.progress = Progress::terminal();
         Ok(project)
     }
 
+    /// Keeps the corpora and artifacts where `state_dirs` say.
+    pub fn with_state_dirs(mut self, state_dirs: &StateDirs) -> Self {
+        self.state_dirs = state_dirs.clone();
+        self
+    }
+
+    /// Restricts the targets to those tagged with one of `tags` in `Move.toml`,
+    /// all of them if there are none.
+    pub fn with_tags(mut self, tags: &[String]) -> Result<Self> {
+        if tags.is_empty() {
+            return Ok(self);
+        }
+        let section = self.fuzz_section()?;
+        self.targets.retain(|target| {
+            section
+                .targets
+                .get(target)
+                .is_some_and(|d| d.tags.iter().any(|tag| tags.contains(tag)))
+        });
+        if self.targets.is_empty() {
+            bail!("no fuzz target is tagged {}", tags.join(" or "));
+        }
+        Ok(self)
+    }
+
     /// Creates the fuzz project structure and returns a new instance.
     ///
     /// This will not clone libfuzzer-sys.
@@ -53,6 +173,18 @@ impl FuzzProject {
         let fuzz_project = project.fuzz_dir();
         let manifest = Manifest::parse()?;
         println!("{:?}", manifest);
+        let package = MovePackage::find_from_current_dir()?;
+        match &package {
+            Some(package) => info!(
+                "Fuzzing the Move package {} at {}",
+                package.name,
+                package.root.display()
+            ),
+            None => warn!(
+                "Found no Move.toml above {}, the fuzz package depends on no package under test",
+                env::current_dir()?.display()
+            ),
+        }
         // TODO: check if the project is already initialized
         fs::create_dir(fuzz_project)
             .with_context(|| format!("failed to create directory {}", fuzz_project.display()))?;
@@ -62,6 +194,13 @@ impl FuzzProject {
             format!("failed to create directory {}", fuzz_targets_dir.display())
         })?;
 
+        // The fuzz directory may be elsewhere than in the crate with `--fuzz-dir`.
+        let fuzz_dir = fuzz_project
+            .canonicalize()
+            .with_context(|| format!("could not resolve {}", fuzz_project.display()))?;
+        let crate_path = relative_path(&fuzz_dir, &find_package()?.canonicalize()?);
+        let libfuzzer_path = relative_path(&fuzz_dir, &fuzzer_crate_dir("libfuzzer"));
+        let arbitrary_path = relative_path(&fuzz_dir, &fuzzer_crate_dir("move-arbitrary"));
         let cargo_toml = fuzz_project.join("Cargo.toml");
         let mut cargo = fs::File::create(&cargo_toml)
             .with_context(|| format!("failed to create {}", cargo_toml.display()))?;
@@ -69,19 +208,35 @@ impl FuzzProject {
             .write_fmt(cargo_toml_template!(
                 manifest.crate_name,
                 manifest.edition,
-                init.fuzzing_workspace
+                init.fuzzing_workspace,
+                crate_path.display(),
+                libfuzzer_path.display(),
+                arbitrary_path.display()
             ))
             .with_context(|| format!("failed to write to {}", cargo_toml.display()))?;
 
+        let addresses: String = package
+            .iter()
+            .flat_map(MovePackage::unassigned_addresses)
+            .map(|(name, address)| format!("\n{} = \"{}\"", name, address))
+            .collect();
         let move_toml_path = fuzz_project.join("Move.toml");
         let mut move_toml = fs::File::create(&move_toml_path)
             .with_context(|| format!("failed to create {}", move_toml_path.display()))?;
         move_toml
             .write_fmt(move_toml_template!(
                 manifest.crate_name,
-                manifest.edition
+                manifest.edition,
+                addresses
             ))
             .with_context(|| format!("failed to write to {}", move_toml_path.display()))?;
+        if let Some(package) = &package {
+            let root = package
+                .root
+                .canonicalize()
+                .with_context(|| format!("could not resolve {}", package.root.display()))?;
+            project.add_move_dependency(&package.name, &root)?;
+        }
 
         let gitignore = fuzz_project.join(".gitignore");
         let mut ignore = fs::File::create(&gitignore)
@@ -91,34 +246,273 @@ impl FuzzProject {
             .with_context(|| format!("failed to write to {}", gitignore.display()))?;
 
         project
-            .create_target_template(&init.target, &manifest)
+            .create_target_template(
+                &init.target,
+                &manifest,
+                TargetKind::Script,
+                None,
+                &[],
+                package.as_ref(),
+            )
             .with_context(|| {
                 format!(
                     "could not create template file for target {:?}",
                     init.target
                 )
             })?;
+        move_toml::declare_target(&project.move_manifest_path(), &init.target, None, None, &[])?;
+        project.forget_fuzz_section();
         Ok(project)
     }
 
+    /// Names of the fuzz targets declared in the manifest, those with the tags
+    /// of `with_tags` if any, sorted.
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// The fuzz targets of `targets`, sorted by name, with the details the
+    /// manifests give about them.
+    pub fn fuzz_targets(&self) -> Result<Vec<FuzzTarget>> {
+        let manifest = self.manifest()?;
+        let section = self.fuzz_section()?;
+        let bins: HashMap<&str, &toml::value::Table> = manifest
+            .get("bin")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_table)
+            .filter_map(|bin| Some((bin.get("name")?.as_str()?, bin)))
+            .collect();
+        Ok(self
+            .targets
+            .iter()
+            .filter_map(|name| {
+                let bin = *bins.get(name.as_str())?;
+                let source = match bin.get("path").and_then(toml::Value::as_str) {
+                    Some(path) => self.fuzz_dir().join(path),
+                    None => self.rust_target_path(name),
+                };
+                let move_source = Some(self.move_target_path(name)).filter(|p| p.is_file());
+                Some(FuzzTarget {
+                    name: name.to_owned(),
+                    source,
+                    move_source,
+                    corpus: self.corpus_root().join(name),
+                    artifacts: self.artifacts_root().join(name),
+                    manifest: bin.clone(),
+                    declared: section.targets.get(name).cloned(),
+                })
+            })
+            .collect())
+    }
+
+    /// The fuzz target named `name`.
+    pub fn fuzz_target(&self, name: &str) -> Result<FuzzTarget> {
+        self.ensure_target_exists(name)?;
+        self.fuzz_targets()?
+            .into_iter()
+            .find(|t| t.name == name)
+            .with_context(|| format!("could not find fuzz target {:?}", name))
+    }
+
+    /// The `-max_len` flag declared for `target`, if any.
+    fn max_len_arg(&self, target: &str) -> Result<Option<String>> {
+        Ok(self
+            .fuzz_target(target)?
+            .declared
+            .and_then(|declared| declared.max_len_arg()))
+    }
+
+    /// Whether the binary of `target` built with `build` is missing or older
+    /// than its harness, its Move module or the fuzz manifest.
+    pub fn is_stale(&self, target: &FuzzTarget, build: &BuildOptions) -> Result<bool> {
+        let built = match fs::metadata(self.target_binary(build, &target.name)?) {
+            Ok(metadata) => metadata.modified()?,
+            Err(_) => return Ok(true),
+        };
+        let manifest = self.manifest_path();
+        let sources = [
+            Some(&target.source),
+            target.move_source.as_ref(),
+            Some(&manifest),
+        ];
+        let stale = sources
+            .into_iter()
+            .flatten()
+            .filter_map(|source| fs::metadata(source).and_then(|m| m.modified()).ok())
+            .any(|modified| modified > built);
+        Ok(stale)
+    }
+
+    /// Registers `callback` to run at `point`, after the hooks of the manifest.
+    pub fn add_hook(
+        &mut self,
+        point: HookPoint,
+        callback: impl Fn(&HookContext) -> Result<()> + 'static,
+    ) {
+        self.hooks.add_callback(point, Box::new(callback));
+    }
+
+    /// Sends the progress of long operations to `callback` rather than to the terminal.
+    pub fn on_progress(&mut self, callback: impl Fn(&ProgressEvent) + 'static) {
+        self.progress = Progress::callback(Box::new(callback));
+    }
+
+    /// Runs `f` between the `pre` and `post` hooks
+    fn with_hooks(
+        &self,
+        (pre, post): (HookPoint, HookPoint),
+        target: Option<&str>,
+        f: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        let mut context = HookContext {
+            point: pre,
+            fuzz_dir: self.fuzz_dir(),
+            target,
+            success: None,
+        };
+        self.hooks.fire(&context)?;
+        let result = f();
+        context.point = post;
+        context.success = Some(result.is_ok());
+        match (result, self.hooks.fire(&context)) {
+            (Err(e), Err(hook)) => {
+                warn!("{:#}", hook);
+                Err(e)
+            }
+            (result, hook) => result.and(hook),
+        }
+    }
+
+    /// Lists the targets whose binary is stale, for `list --outdated`.
+    pub fn list_outdated_targets(&self) -> Result<()> {
+        let outdated = self.outdated_targets()?;
+        if outdated.is_empty() {
+            println!("All fuzz targets are up to date");
+            return Ok(());
+        }
+        for (target, staleness) in &outdated {
+            println!("{} ({})", target, staleness);
+        }
+        Ok(())
+    }
+
     pub fn list_targets(&self) -> Result<()> {
-        for bin in &self.targets {
-            println!("{}", bin);
+        for target in self.fuzz_targets()? {
+            let declared = target.declared.unwrap_or_default();
+            let mut line = target.name;
+            if let Some(entry) = &declared.entry {
+                line.push_str(&format!(" ({})", entry));
+            }
+            if !declared.tags.is_empty() {
+                line.push_str(&format!(" [{}]", declared.tags.join(", ")));
+            }
+            if let Some(description) = &declared.description {
+                line.push_str(&format!(": {}", description));
+            }
+            println!("{}", line);
+        }
+        for name in self.fuzz_section()?.targets.keys() {
+            if !self.targets.contains(name) {
+                warn!(
+                    "{} is declared in Move.toml but has no harness, \
+                     add one with `move-fuzz add {}`",
+                    name, name
+                );
+            }
         }
         Ok(())
     }
 
     /// Create a new fuzz target.
     pub fn add_target(&self, add: &options::Add, manifest: &Manifest) -> Result<()> {
+        let kind = add.kind();
+        if kind == TargetKind::BcsRoundtrip && add.ty.is_none() {
+            bail!("`--template bcs-roundtrip` requires `--type`");
+        }
+        if add.seed_from_sources && kind != TargetKind::MoveParser {
+            bail!("`--seed-from-sources` only applies to `--template move-parser`");
+        }
+        let extensions = match kind {
+            TargetKind::Extensions => add.extensions(),
+            _ => Vec::new(),
+        };
+        self.create_target_template(
+            &add.target,
+            manifest,
+            kind,
+            add.ty.as_deref(),
+            &extensions,
+            None,
+        )
+        .with_context(|| format!("could not add target {:?}", add.target))?;
         // Create corpus and artifact directories for the newly added target
         self.corpus_for(&add.target)?;
         self.artifacts_for(&add.target)?;
-        self.create_target_template(&add.target, manifest)
-            .with_context(|| format!("could not add target {:?}", add.target))
+        if add.seed_from_sources {
+            self.seed_from_sources(&add.target)?;
+        }
+        move_toml::declare_target(
+            &self.move_manifest_path(),
+            &add.target,
+            add.entry.as_deref(),
+            kind.raw_input(add.ty.as_deref()).as_deref(),
+            &extensions
+                .iter()
+                .flat_map(|extension| extension.features())
+                .copied()
+                .collect::<Vec<_>>(),
+        )?;
+        self.forget_fuzz_section();
+        Ok(())
+    }
+
+    /// Copies the `.move` files of the package the fuzz directory is in to the
+    /// corpus of `target`.
+    fn seed_from_sources(&self, target: &str) -> Result<()> {
+        let package = self.fuzz_dir().parent().unwrap_or(Path::new("."));
+        let corpus = self.corpus_for(target)?;
+        let mut seeded = Vec::new();
+        for source in move_sources(&package.join(crate::MOVE_TARGETS_DIR))? {
+            let data = fs::read(&source)
+                .with_context(|| format!("failed to read {}", source.display()))?;
+            if let Some(entry) = corpus::insert(&corpus, &data)? {
+                seeded.push((
+                    provenance::entry_name(&entry),
+                    Origin::Seed {
+                        file: provenance::source(&source),
+                    },
+                ));
+            }
+        }
+        if seeded.is_empty() {
+            warn!(
+                "{} has no `.move` files to seed {} with",
+                package.join(crate::MOVE_TARGETS_DIR).display(),
+                target
+            );
+        }
+        self.record_provenance(target, seeded);
+        Ok(())
     }
 
     /// Add a new fuzz target script with a given name
-    fn create_target_template(&self, target: &str, manifest: &Manifest) -> Result<()> {
+    ///
+    /// Raw, compiler, parser, verifier, disassembler and BCS round-trip targets implement the
+    /// libFuzzer entry point directly and have no Move script, sequence and cross-interpreter
+    /// targets get a Move module with entry functions instead of a script. BCS round-trip targets are
+    /// generated for the type `ty`. Move scripts `use` the modules of `package`, the package
+    /// under test.
+    fn create_target_template(
+        &self,
+        target: &str,
+        manifest: &Manifest,
+        kind: TargetKind,
+        ty: Option<&str>,
+        extensions: &[options::Extension],
+        package: Option<&MovePackage>,
+    ) -> Result<()> {
         let rust_target_path = self.rust_target_path(target);
         let move_target_path = self.move_target_path(target);
 
@@ -131,19 +525,83 @@ impl FuzzProject {
         fs::create_dir_all(self.move_targets_dir())
             .context("ensuring that `sources` directory exists failed")?;
 
+        // An unsupported type must not leave an empty harness behind.
+        let bcs_layout = match ty {
+            Some(ty) if kind == TargetKind::BcsRoundtrip && !is_rust_type_path(ty) => {
+                Some(move_type_layout(ty)?)
+            }
+            _ => None,
+        };
+
         let mut rust_script = fs::OpenOptions::new()
             .write(true)
             .create_new(true)
             .open(&rust_target_path)
             .with_context(|| format!("could not create target script file at {:?}", rust_target_path))?;
-        rust_script.write_fmt(rust_target_template!(manifest.edition))?;
+        match kind {
+            TargetKind::Raw => rust_script.write_fmt(rust_raw_target_template!())?,
+            TargetKind::Script => rust_script.write_fmt(rust_target_template!(manifest.edition))?,
+            TargetKind::Sequence => {
+                rust_script.write_fmt(rust_sequence_target_template!(target))?
+            }
+            TargetKind::Stateful => {
+                rust_script.write_fmt(rust_stateful_target_template!(manifest.edition))?
+            }
+            TargetKind::CrossInterpreter => rust_script.write_fmt(
+                rust_sequence_target_template!(target, "cross_interpreter, "),
+            )?,
+            TargetKind::CompilerGrammar => {
+                rust_script.write_fmt(rust_compiler_grammar_target_template!())?
+            }
+            TargetKind::MoveParser => rust_script.write_fmt(rust_move_parser_target_template!())?,
+            TargetKind::Verifier => rust_script.write_fmt(rust_verifier_target_template!())?,
+            TargetKind::Disassembler => {
+                rust_script.write_fmt(rust_disassembler_target_template!())?
+            }
+            TargetKind::GasOracle => {
+                rust_script.write_fmt(rust_gas_oracle_target_template!(manifest.edition))?
+            }
+            TargetKind::GasSchedule => {
+                rust_script.write_fmt(rust_gas_schedule_target_template!(manifest.edition))?
+            }
+            TargetKind::Paranoid => {
+                rust_script.write_fmt(rust_paranoid_target_template!(manifest.edition))?
+            }
+            TargetKind::Extensions => rust_script.write_fmt(rust_extensions_target_template!(
+                manifest.edition,
+                extensions
+            ))?,
+            TargetKind::BcsRoundtrip => {
+                let ty = ty.unwrap_or_default();
+                match bcs_layout {
+                    Some(layout) => rust_script
+                        .write_fmt(rust_bcs_move_roundtrip_target_template!(ty, layout))?,
+                    None => rust_script.write_fmt(rust_bcs_roundtrip_target_template!(ty))?,
+                }
+            }
+        }
 
-        let mut move_script = fs::OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&move_target_path)
-            .with_context(|| format!("could not create target script file at {:?}", move_target_path))?;
-        move_script.write_fmt(move_target_template!(manifest.edition))?;
+        if !matches!(
+            kind,
+            TargetKind::Raw
+                | TargetKind::CompilerGrammar
+                | TargetKind::MoveParser
+                | TargetKind::Verifier
+                | TargetKind::Disassembler
+                | TargetKind::BcsRoundtrip
+        ) {
+            let mut move_script = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&move_target_path)
+                .with_context(|| format!("could not create target script file at {:?}", move_target_path))?;
+            if matches!(kind, TargetKind::Sequence | TargetKind::CrossInterpreter) {
+                move_script.write_fmt(move_sequence_target_template!(target))?;
+            } else {
+                let uses = package.map(MovePackage::script_uses).unwrap_or_default();
+                move_script.write_fmt(move_target_template!(manifest.edition, uses))?;
+            }
+        }
 
         let mut cargo = fs::OpenOptions::new()
             .append(true)
@@ -151,6 +609,523 @@ impl FuzzProject {
         Ok(cargo.write_fmt(toml_bin_template!(target))?)
     }
 
+    /// Generate sequence targets for the public functions of a Move package annotated
+    /// with `#[fuzz]`, or for its public entry functions if none are.
+    ///
+    /// The functions are read from the compiled modules of the package and
+    /// called through a wrapper module in the fuzz package, which depends on it.
+    pub fn exec_autoharness(&self, autoharness: &options::Autoharness) -> Result<()> {
+        let package = autoharness.package.canonicalize().with_context(|| {
+            format!(
+                "could not find the package {}",
+                autoharness.package.display()
+            )
+        })?;
+        let name = move_package_name(&package)?;
+        let move_cmd = |args: &[&str]| move_output(&package, args);
+        // Functions annotated with `#[fuzz]` opt in, otherwise every entry function does.
+        let mut annotated = BTreeSet::new();
+        for source in move_sources(&package.join(crate::MOVE_TARGETS_DIR))? {
+            let text = fs::read_to_string(&source)
+                .with_context(|| format!("failed to read {}", source.display()))?;
+            annotated.extend(autoharness::fuzz_annotated(&text));
+        }
+        let selection = if annotated.is_empty() {
+            autoharness::Selection::Entry
+        } else {
+            autoharness::Selection::Annotated(&annotated)
+        };
+
+        info!("Building {}", package.display());
+        move_cmd(&["build"])?;
+
+        let mut functions = Vec::new();
+        let mut structs = Vec::new();
+        let mut seen = BTreeSet::new();
+        for path in modules::module_files(&modules::build_dir(&package, &name), false)? {
+            let module = modules::read_module(&path)?;
+            let (found, skipped) = autoharness::entry_functions(&module, &selection);
+            for skipped in skipped {
+                warn!("skipping {}: {}", skipped.function, skipped.reason);
+                seen.insert(skipped.function);
+            }
+            seen.extend(
+                found
+                    .iter()
+                    .map(|f| format!("{}::{}", f.module, f.function)),
+            );
+            functions.extend(found);
+            structs.extend(autoharness::structs(&module));
+        }
+        for (module, function) in &annotated {
+            if !seen.contains(&format!("{}::{}", module, function)) {
+                warn!(
+                    "skipping {}::{}: only public functions can be fuzzed",
+                    module, function
+                );
+            }
+        }
+        if functions.is_empty() {
+            bail!(
+                "{} has no {} functions a harness can be generated for",
+                name,
+                match selection {
+                    autoharness::Selection::Entry => "public entry",
+                    autoharness::Selection::Annotated(_) => "#[fuzz]",
+                }
+            );
+        }
+
+        self.add_move_dependency(&name, &package)?;
+        let targets = match &autoharness.combined {
+            Some(target) => {
+                let harness = autoharness::Harness {
+                    wrapper: target,
+                    functions: &functions,
+                    structs: &structs,
+                    wrapper_fun: |f| f.target_name(),
+                };
+                self.create_generated_target(target, &name, None, &harness)?;
+                vec![target.clone()]
+            }
+            None => {
+                let mut targets = Vec::new();
+                for f in &functions {
+                    let target = f.target_name();
+                    if self.targets.contains(&target) {
+                        warn!("skipping {}: the target already exists", target);
+                        continue;
+                    }
+                    let harness = autoharness::Harness {
+                        wrapper: &target,
+                        functions: std::slice::from_ref(f),
+                        structs: &structs,
+                        wrapper_fun: |f| f.function.clone(),
+                    };
+                    let source = format!("0x{}::{}::{}", f.address, f.module, f.function);
+                    self.create_generated_target(&target, &source, Some(&source), &harness)?;
+                    targets.push(target);
+                }
+                targets
+            }
+        };
+        for target in &targets {
+            println!("Added {}", target);
+        }
+        Ok(())
+    }
+
+    /// Writes a libFuzzer dictionary of the names and constants of the modules
+    /// of a Move package.
+    pub fn exec_gen_dict(&self, gen_dict: &options::GenDict) -> Result<()> {
+        let package = match &gen_dict.package {
+            Some(package) => package
+                .canonicalize()
+                .with_context(|| format!("could not find the package {}", package.display()))?,
+            None => self.fuzz_dir().to_owned(),
+        };
+        let name = move_package_name(&package)?;
+        info!("Building {}", package.display());
+        move_output(&package, &["build"])?;
+
+        let mut dictionary = Dictionary::new(gen_dict.kind);
+        let mut modules = 0;
+        for path in modules::module_files(&modules::build_dir(&package, &name), false)? {
+            dictionary.add_module(&modules::read_module(&path)?);
+            modules += 1;
+        }
+        if modules == 0 {
+            bail!("{} has no compiled modules", name);
+        }
+
+        let comment = format!(
+            "Dictionary of the Move package {} for {} targets, generated by `move-fuzz gen-dict`",
+            name,
+            match gen_dict.kind {
+                options::DictKind::Arguments => "harness",
+                options::DictKind::Bytecode => "bytecode",
+            }
+        );
+        let text = dictionary.render(&comment);
+        match &gen_dict.output {
+            Some(output) => {
+                fs::write(output, text)
+                    .with_context(|| format!("failed to write {}", output.display()))?;
+                info!(
+                    "Wrote {} tokens from {} modules to {}",
+                    dictionary.token_count(),
+                    modules,
+                    output.display()
+                );
+            }
+            None => print!("{}", text),
+        }
+        Ok(())
+    }
+
+    fn create_generated_target(
+        &self,
+        target: &str,
+        source: &str,
+        entry: Option<&str>,
+        harness: &autoharness::Harness,
+    ) -> Result<()> {
+        if self.targets.iter().any(|t| t == target) {
+            bail!("the target {} already exists", target);
+        }
+        self.corpus_for(target)?;
+        self.artifacts_for(target)?;
+        fs::create_dir_all(self.fuzz_targets_dir())
+            .context("ensuring that `fuzz_targets` directory exists failed")?;
+        fs::create_dir_all(self.move_targets_dir())
+            .context("ensuring that `sources` directory exists failed")?;
+
+        let (generators, entries) = harness.rust_functions();
+        let files = [
+            (
+                self.rust_target_path(target),
+                rust_autoharness_target_template!(source, generators, entries).to_string(),
+            ),
+            (
+                self.move_target_path(target),
+                move_autoharness_target_template!(target, harness.move_functions()).to_string(),
+            ),
+        ];
+        for (path, content) in files {
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(content.as_bytes()))
+                .with_context(|| format!("could not create target script file at {:?}", path))?;
+        }
+
+        let mut cargo = fs::OpenOptions::new()
+            .append(true)
+            .open(self.manifest_path())?;
+        cargo.write_fmt(toml_bin_template!(target))?;
+        move_toml::declare_target(&self.move_manifest_path(), target, entry, None, &[])?;
+        self.forget_fuzz_section();
+        Ok(())
+    }
+
+    /// Adds `package` as a local dependency of the fuzz package, unless it already is one.
+    fn add_move_dependency(&self, name: &str, package: &Path) -> Result<()> {
+        let fuzz_dir = self
+            .fuzz_dir()
+            .canonicalize()
+            .with_context(|| format!("could not resolve {}", self.fuzz_dir().display()))?;
+        move_toml::add_dependency(
+            &self.move_manifest_path(),
+            name,
+            &relative_path(&fuzz_dir, package),
+        )
+    }
+
+    /// Remove a fuzz target, its `[[bin]]` entry and all of its fuzzing state.
+    pub fn remove_target(&self, remove: &options::Remove) -> Result<()> {
+        self.ensure_target_exists(&remove.target)?;
+
+        let mut manifest = self.cargo_manifest()?;
+        if !remove_cargo_target(&mut manifest, "bin", &remove.target) {
+            bail!("could not find a `[[bin]]` entry for {:?}", remove.target);
+        }
+        // The regression tests run the binary of the target, they go with it.
+        remove_cargo_target(
+            &mut manifest,
+            "test",
+            &format!("{}_regressions", remove.target),
+        );
+        fs::write(self.manifest_path(), manifest.to_string())
+            .with_context(|| format!("failed to write to {}", self.manifest_path().display()))?;
+        move_toml::remove_target(&self.move_manifest_path(), &remove.target)?;
+        self.forget_fuzz_section();
+
+        for file in [
+            self.rust_target_path(&remove.target),
+            self.move_target_path(&remove.target),
+            self.regressions_path(&remove.target),
+        ] {
+            if file.exists() {
+                fs::remove_file(&file)
+                    .with_context(|| format!("failed to remove {}", file.display()))?;
+            }
+        }
+        for dir in self.target_state_dirs(&remove.target) {
+            if dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .with_context(|| format!("failed to remove {}", dir.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename a fuzz target, moving its scripts, corpus, artifacts and coverage data.
+    pub fn rename_target(&self, rename: &options::Rename) -> Result<()> {
+        self.ensure_target_exists(&rename.target)?;
+        if !is_target_name(&rename.new_name) {
+            bail!(
+                "{:?} is not a valid fuzz target name, it must be a Rust identifier",
+                rename.new_name
+            );
+        }
+        if self.targets.contains(&rename.new_name) {
+            bail!("fuzz target {:?} already exists", rename.new_name);
+        }
+
+        let mut manifest = self.cargo_manifest()?;
+        let renamed = rename_cargo_target(
+            &mut manifest,
+            "bin",
+            &rename.target,
+            &rename.new_name,
+            &format!("fuzz_targets/{}.rs", rename.new_name),
+        );
+        if !renamed {
+            bail!("could not find a `[[bin]]` entry for {:?}", rename.target);
+        }
+        rename_cargo_target(
+            &mut manifest,
+            "test",
+            &format!("{}_regressions", rename.target),
+            &format!("{}_regressions", rename.new_name),
+            &format!("{}/{}.rs", REGRESSIONS_DIR, rename.new_name),
+        );
+        fs::write(self.manifest_path(), manifest.to_string())
+            .with_context(|| format!("failed to write to {}", self.manifest_path().display()))?;
+        move_toml::rename_target(&self.move_manifest_path(), &rename.target, &rename.new_name)?;
+        self.forget_fuzz_section();
+
+        // The regression tests name the binary they run after the target.
+        let regressions = self.regressions_path(&rename.target);
+        if regressions.exists() {
+            let tests = fs::read_to_string(&regressions)
+                .with_context(|| format!("failed to read {}", regressions.display()))?;
+            fs::write(
+                &regressions,
+                rename_in_regressions(&tests, &rename.target, &rename.new_name),
+            )
+            .with_context(|| format!("failed to write to {}", regressions.display()))?;
+        }
+
+        let moves = [
+            (
+                self.rust_target_path(&rename.target),
+                self.rust_target_path(&rename.new_name),
+            ),
+            (
+                self.move_target_path(&rename.target),
+                self.move_target_path(&rename.new_name),
+            ),
+            (regressions, self.regressions_path(&rename.new_name)),
+        ]
+        .into_iter()
+        .chain(
+            self.target_state_dirs(&rename.target)
+                .into_iter()
+                .zip(self.target_state_dirs(&rename.new_name)),
+        );
+        for (from, to) in moves {
+            if from.exists() {
+                fs::rename(&from, &to).with_context(|| {
+                    format!("failed to move {} to {}", from.display(), to.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The Cargo manifest of the fuzz directory, to edit.
+    fn cargo_manifest(&self) -> Result<Document> {
+        let path = self.manifest_path();
+        fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?
+            .parse()
+            .with_context(|| format!("could not decode {}", path.display()))
+    }
+
+    /// The regression tests of `target`, written by `gen-regression`.
+    fn regressions_path(&self, target: &str) -> PathBuf {
+        self.fuzz_dir()
+            .join(REGRESSIONS_DIR)
+            .join(format!("{}.rs", target))
+    }
+
+    fn ensure_target_exists(&self, target: &str) -> Result<()> {
+        if !self.targets.iter().any(|t| t == target) {
+            return Err(Error::TargetNotFound {
+                target: target.to_owned(),
+                available: self.targets.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The target of a command given none: the only target of the project, or
+    /// the one picked from a fuzzy-searchable list when stdin and stderr are a
+    /// terminal, failing with [`Error::TargetMissing`] otherwise.
+    ///
+    /// With `all`, the command can run on every target, which the list offers
+    /// first and which is `None`; it is also the choice without a terminal.
+    pub fn pick_target(&self, all: bool) -> Result<Option<String>> {
+        let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+        if all && (self.targets.len() <= 1 || !interactive) {
+            return Ok(None);
+        }
+        if let [target] = self.targets.as_slice() {
+            return Ok(Some(target.clone()));
+        }
+        if self.targets.is_empty() || !interactive {
+            return Err(Error::TargetMissing {
+                available: self.targets.clone(),
+            }
+            .into());
+        }
+
+        let mut items = Vec::new();
+        if all {
+            items.push("all targets".to_owned());
+        }
+        items.extend(self.targets.iter().cloned());
+        let picked = dialoguer::FuzzySelect::new()
+            .with_prompt("Fuzz target")
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .context("failed to ask for a fuzz target")?;
+        match picked {
+            None => bail!("no fuzz target picked"),
+            Some(0) if all => Ok(None),
+            Some(i) => Ok(Some(items[i].clone())),
+        }
+    }
+
+    /// Remove generated state, listing it and asking for confirmation first.
+    pub fn exec_clean(&self, clean: &options::Clean) -> Result<()> {
+        if let Some(target) = &clean.target {
+            self.ensure_target_exists(target)?;
+        }
+        let paths = self.clean_paths(clean)?;
+        if paths.is_empty() {
+            println!("Nothing to clean");
+            return Ok(());
+        }
+
+        for path in &paths {
+            println!(
+                "{:>10}  {}",
+                corpus::human_size(disk_usage(path)),
+                strip_current_dir_prefix(path).display()
+            );
+        }
+        if !clean.yes {
+            if !std::io::stdin().is_terminal() {
+                bail!("refusing to remove files without confirmation, pass --yes to remove them");
+            }
+            eprint!("Remove {} paths? [y/N] ", paths.len());
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+            if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                println!("Nothing removed");
+                return Ok(());
+            }
+        }
+
+        for path in &paths {
+            if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            }
+            .with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+        println!("Removed {} paths", paths.len());
+        Ok(())
+    }
+
+    /// The paths `clean` removes, those that exist.
+    fn clean_paths(&self, clean: &options::Clean) -> Result<Vec<PathBuf>> {
+        let everything = clean.cleans_everything();
+
+        let mut paths = Vec::new();
+        for (selected, dir) in [
+            (clean.corpus, "corpus"),
+            (clean.artifacts || everything, "artifacts"),
+            (clean.coverage || everything, "coverage"),
+            (everything, SCHEDULING_DIR),
+            (everything, HEAP_PROFILES_DIR),
+            (everything, MERGE_DIR),
+            (clean.corpus, PROVENANCE_DIR),
+        ] {
+            if selected {
+                let dir = self.fuzz_dir().join(dir);
+                paths.push(match &clean.target {
+                    Some(target) => dir.join(target),
+                    None => dir,
+                });
+            }
+        }
+        if clean.builds || everything {
+            // A target directory given with `--target-dir` or shared through
+            // `CARGO_TARGET_DIR` holds more than the fuzz targets, only their
+            // binaries are removed from it.
+            let build = BuildOptions {
+                target_dir: clean.target_dir.clone(),
+                ..BuildOptions::default()
+            };
+            let shared = self.target_dir(&build)?;
+            let target_dir = shared
+                .clone()
+                .unwrap_or_else(|| self.fuzz_dir().join("target"));
+            let targets = match &clean.target {
+                Some(target) => vec![target.clone()],
+                None if shared.is_some() => self.targets.clone(),
+                None => Vec::new(),
+            };
+            if clean.target.is_none() {
+                if shared.is_none() {
+                    paths.push(target_dir.clone());
+                }
+                paths.push(self.fuzz_dir().join("build"));
+                paths.push(self.build_log_path());
+            }
+            // `target/<triple>/<profile>/<target>`
+            if !targets.is_empty() && target_dir.exists() {
+                for triple in fs::read_dir(&target_dir)? {
+                    let triple = triple?.path();
+                    let suffix =
+                        exe_suffix(&triple.file_name().unwrap_or_default().to_string_lossy());
+                    for target in &targets {
+                        let bin = format!("{}{}", target, suffix);
+                        for profile in ["release", "debug"] {
+                            paths.push(triple.join(profile).join(&bin));
+                        }
+                    }
+                }
+            }
+        }
+        paths.retain(|path| path.exists());
+        Ok(paths)
+    }
+
+    /// Returns the per-target corpus, artifact, coverage, scheduling and provenance directories,
+    /// without creating them.
+    fn target_state_dirs(&self, target: &str) -> Vec<PathBuf> {
+        [
+            "corpus",
+            "artifacts",
+            "coverage",
+            SCHEDULING_DIR,
+            PROVENANCE_DIR,
+            HEAP_PROFILES_DIR,
+            MERGE_DIR,
+        ]
+        .iter()
+        .map(|dir| self.fuzz_dir().join(dir).join(target))
+        .collect()
+    }
+
     fn cargo_build(&self, subcommand: &str, build: &BuildOptions) -> Result<Command> {
         let mut cmd = Command::new("cargo");
         cmd.arg(subcommand)
@@ -175,14 +1150,50 @@ impl FuzzProject {
         if let Some(ref features) = build.cargo_options.features {
             cmd.arg("--features").arg(features);
         }
+        if let Some(natives) = self.harness_env(&mut cmd)? {
+            if natives.iter().any(|package| package == "table") {
+                cmd.arg("--features").arg("libfuzzer/table-extension");
+            }
+        }
         for flag in &build.cargo_options.unstable_flags {
             cmd.arg("-Z").arg(flag);
         }
+        let careful = if build.cargo_options.careful_mode {
+            Some(Careful::new(&Toolchain::current()?)?)
+        } else {
+            None
+        };
 
-        if (matches!(build.cargo_options.sanitizer, Sanitizer::Memory) || build.cargo_options.build_std || build.cargo_options.careful_mode)
+        let sanitizer = build.cargo_options.build_sanitizer();
+        if !build.cargo_options.stable {
+            platform::warn_once(&build.cargo_options.triple, build.cargo_options.sanitizer);
+        }
+        if (matches!(sanitizer, Sanitizer::Memory)
+            || build.cargo_options.build_std
+            || build.cargo_options.careful_mode)
             && !build.cargo_options.coverage
         {
             cmd.arg("-Z").arg("build-std");
+            if careful.is_some() {
+                cmd.arg("-Z")
+                    .arg(format!("build-std-features={}", careful::STD_FEATURES));
+            }
+        }
+        if let Some(careful) = &careful {
+            let package_rustflags = careful.package_rustflags();
+            if !package_rustflags.is_empty() {
+                // Profile rustflags reach the local packages only, the standard
+                // library would fail on enabling the lint feature twice.
+                cmd.arg("-Z").arg("profile-rustflags");
+                let profile = if build.dev { "dev" } else { "release" };
+                let rustflags = toml::Value::from(package_rustflags);
+                for package in self.local_packages()? {
+                    cmd.arg("--config").arg(format!(
+                        "profile.{}.package.{}.rustflags={}",
+                        profile, package, rustflags
+                    ));
+                }
+            }
         }
 
         let mut rustflags: String = "-Cpasses=sancov-module \
@@ -205,23 +1216,32 @@ impl FuzzProject {
 
         if build.cargo_options.coverage {
             rustflags.push_str(" -Cinstrument-coverage");
+            // Coverage reports then list the sources of the package from its directory,
+            // wherever the build happens.
+            if let Some(package_dir) = self.package_dir() {
+                rustflags.push_str(&format!(
+                    " --remap-path-prefix={}{}=",
+                    package_dir.display(),
+                    std::path::MAIN_SEPARATOR
+                ));
+            }
+        }
+        for remap in &build.cargo_options.remap_path_prefix {
+            rustflags.push_str(&format!(" --remap-path-prefix={}", remap));
         }
 
-        match build.cargo_options.sanitizer {
+        match sanitizer {
             Sanitizer::None => {}
             Sanitizer::Memory => {
                 // Memory sanitizer requires more flags to function than others:
                 // https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html#memorysanitizer
                 rustflags.push_str(" -Zsanitizer=memory -Zsanitizer-memory-track-origins")
             }
-            _ => rustflags.push_str(&format!(
-                " -Zsanitizer={sanitizer}",
-                sanitizer = build.cargo_options.sanitizer
-            )),
+            _ => rustflags.push_str(&format!(" -Zsanitizer={sanitizer}", sanitizer = sanitizer)),
         }
 
-        if build.cargo_options.careful_mode {
-            rustflags.push_str(" -Zextra-const-ub-checks -Zstrict-init-checks --cfg careful");
+        if let Some(careful) = &careful {
+            rustflags.push_str(&careful.rustflags());
         }
         if build.cargo_options.triple.contains("-linux-") {
             rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-stack-depth");
@@ -252,32 +1272,81 @@ impl FuzzProject {
         }
         cmd.env("RUSTFLAGS", rustflags);
 
-        // For asan and tsan we have default options. Merge them to the given
-        // options, so users can still provide their own options to e.g. disable
-        // the leak sanitizer.  Options are colon-separated.
-        match build.cargo_options.sanitizer {
-            Sanitizer::Address => {
-                let mut asan_opts = env::var("ASAN_OPTIONS").unwrap_or_default();
-                if !asan_opts.is_empty() {
-                    asan_opts.push(':');
+        if build.cargo_options.engine == Engine::Libafl {
+            let runtime = build
+                .cargo_options
+                .libafl_runtime
+                .as_ref()
+                .context("`--engine libafl` requires `--libafl-runtime`")?;
+            let runtime = runtime.canonicalize().with_context(|| {
+                format!("could not find the LibAFL runtime at {}", runtime.display())
+            })?;
+            // The `libfuzzer` crate links this archive instead of building its own libFuzzer,
+            // so the targets keep the libFuzzer command line and corpus/artifact layout.
+            // The runtime is written in Rust and needs no C++ standard library.
+            cmd.env("CUSTOM_LIBFUZZER_PATH", runtime)
+                .env("CUSTOM_LIBFUZZER_STD_CXX", "none");
+        }
+
+        self.sanitizer_options(&mut cmd, build, None)?;
+
+        Ok(cmd)
+    }
+
+    /// Sets the runtime options of the sanitizers on `cmd`. For asan and tsan
+    /// we have default options, followed by those of `[fuzz.sanitizer]` and of
+    /// `target` in `Move.toml`, then by those of the environment, so users can
+    /// still provide their own options to e.g. disable the leak sanitizer.
+    /// Options are colon-separated and the last one wins.
+    fn sanitizer_options(
+        &self,
+        cmd: &mut Command,
+        build: &BuildOptions,
+        target: Option<&str>,
+    ) -> Result<()> {
+        let build = &match target {
+            Some(target) => self.build_for(build, target)?,
+            None => build.clone(),
+        };
+        let section = self.fuzz_section()?;
+        let declared = target
+            .and_then(|target| section.targets.get(target).cloned())
+            .unwrap_or_default();
+        for (kind, variable) in move_toml::SANITIZER_VARIABLES {
+            let sanitizer = build.cargo_options.build_sanitizer();
+            let defaults: &[(&str, &str)] = match (sanitizer, *kind) {
+                // Asking for leak detection where there is none aborts the target.
+                (Sanitizer::Address, "address")
+                    if !platform::detects_leaks(&build.cargo_options.triple, sanitizer) =>
+                {
+                    &[("detect_odr_violation", "0"), ("detect_leaks", "0")]
                 }
-                asan_opts.push_str("detect_odr_violation=0");
-                cmd.env("ASAN_OPTIONS", asan_opts);
+                (Sanitizer::Address, "address") => &[("detect_odr_violation", "0")],
+                (Sanitizer::Thread, "thread") => &[("report_signal_unsafe", "0")],
+                _ => &[],
+            };
+            let mut options: Vec<String> = defaults
+                .iter()
+                .map(|(option, value)| format!("{}={}", option, value))
+                .chain(
+                    [&section.sanitizer, &declared.sanitizer]
+                        .into_iter()
+                        .filter_map(|options| options.get(*kind))
+                        .flatten()
+                        .map(|(option, value)| format!("{}={}", option, value)),
+                )
+                .collect();
+            if options.is_empty() {
+                continue;
             }
-
-            Sanitizer::Thread => {
-                let mut tsan_opts = env::var("TSAN_OPTIONS").unwrap_or_default();
-                if !tsan_opts.is_empty() {
-                    tsan_opts.push(':');
+            if let Ok(user) = env::var(variable) {
+                if !user.is_empty() {
+                    options.push(user);
                 }
-                tsan_opts.push_str("report_signal_unsafe=0");
-                cmd.env("TSAN_OPTIONS", tsan_opts);
             }
-
-            _ => {}
+            cmd.env(variable, options.join(":"));
         }
-
-        Ok(cmd)
+        Ok(())
     }
 
     fn move_build(&self, subcommand: &str, build: &BuildOptions) -> Result<Command> {
@@ -307,14 +1376,22 @@ impl FuzzProject {
         Ok(cmd)
     }
 
-    fn cargo_run(&self, build: &options::BuildOptions, fuzz_target: &str) -> Result<Command> {
+    pub(crate) fn cargo_run(
+        &self,
+        build: &options::BuildOptions,
+        fuzz_target: &str,
+    ) -> Result<Command> {
+        let build = &self.build_for(build, fuzz_target)?;
         let mut cmd = self.cargo_build("run", build)?;
         cmd.arg("--bin").arg(fuzz_target);
 
-        if let Some(target_dir) = &build.target_dir {
+        if let Some(target_dir) = self.target_dir(build)? {
             cmd.arg("--target-dir").arg(target_dir);
         }
 
+        self.sanitizer_options(&mut cmd, build, Some(fuzz_target))?;
+        self.target_env(&mut cmd, fuzz_target)?;
+
         let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
         artifact_arg.push(self.artifacts_for(fuzz_target)?);
         cmd.arg("--").arg(artifact_arg);
@@ -324,22 +1401,25 @@ impl FuzzProject {
 
     // note: never returns Ok(None) if build.coverage is true
     fn target_dir(&self, build: &options::BuildOptions) -> Result<Option<PathBuf>> {
-        // Use the user-provided target directory, if provided. Otherwise if building for coverage,
-        // use the coverage directory
+        // Use the user-provided target directory, if provided, then the one Cargo is told to
+        // use through the environment. Otherwise if building for coverage, use the coverage
+        // directory
         if let Some(target_dir) = build.target_dir.as_ref() {
             return Ok(Some(PathBuf::from(target_dir)));
-        } else if build.cargo_options.coverage {
+        }
+        let shared = env::var_os("CARGO_TARGET_DIR")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
+        if build.cargo_options.coverage {
             // To ensure that fuzzing and coverage-output generation can run in parallel, we
             // produce a separate binary for the coverage command.
-            let current_dir = env::current_dir()?;
-            Ok(Some(
-                current_dir
-                    .join("target")
-                    .join(default_target())
-                    .join("coverage"),
-            ))
+            let base = match shared {
+                Some(dir) => dir,
+                None => env::current_dir()?.join("target"),
+            };
+            Ok(Some(base.join(default_target()).join("coverage")))
         } else {
-            Ok(None)
+            Ok(shared)
         }
     }
 
@@ -348,51 +1428,348 @@ impl FuzzProject {
         mode: options::BuildMode,
         build: &options::BuildOptions,
         fuzz_target: Option<&str>,
+    ) -> Result<()> {
+        let hooks = (HookPoint::PreBuild, HookPoint::PostBuild);
+        self.with_hooks(hooks, fuzz_target, || {
+            events::logged(
+                &self.events_log_path(),
+                EventKind::Build,
+                fuzz_target,
+                || match fuzz_target {
+                    Some(target) => {
+                        self.build_targets(mode, &self.build_for(build, target)?, &[target])
+                    }
+                    None => self.build_all(mode, build),
+                },
+            )
+        })?;
+        if mode == options::BuildMode::Build {
+            if let Err(e) = self.record_builds(build, fuzz_target) {
+                warn!("could not record the build: {:#}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the sources the binaries of `fuzz_target`, or of every target,
+    /// were built from, warning about those Cargo did not rebuild although
+    /// their sources changed.
+    fn record_builds(&self, build: &BuildOptions, fuzz_target: Option<&str>) -> Result<()> {
+        let path = self.build_log_path();
+        let mut log = BuildLog::load(&path)?;
+        for target in self.fuzz_targets()? {
+            if fuzz_target.is_some_and(|name| name != target.name) {
+                continue;
+            }
+            let binary = self.target_binary(build, &target.name)?;
+            if !binary.exists() {
+                continue;
+            }
+            let sources = builds::hash_sources(&self.target_sources(&target)?)?;
+            if !log.record(&target.name, &binary, &sources)? {
+                warn!(
+                    "{} was not rebuilt although its sources changed, it may run stale Move code; \
+                     rebuild it with `move-fuzz clean --builds {}` first",
+                    target.name, target.name
+                );
+            }
+        }
+        log.save(&path)
+    }
+
+    /// The sources of `target`: its harness and Move module, the Cargo manifest,
+    /// and the manifests and sources of the fuzz package, of the package under
+    /// test and of their Move dependencies.
+    fn target_sources(&self, target: &FuzzTarget) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let fuzz_dir = self.fuzz_dir().to_owned();
+        let mut sources = vec![(fuzz_dir.clone(), target.source.clone())];
+        sources.extend(
+            target
+                .move_source
+                .iter()
+                .map(|path| (fuzz_dir.clone(), path.clone())),
+        );
+        sources.push((fuzz_dir, self.manifest_path()));
+        let mut packages = audit::package_sources(self.fuzz_dir(), &move_home())?;
+        if let Some(package) = self.fuzz_dir().parent() {
+            packages.extend(audit::package_sources(package, &move_home())?);
+        }
+        // Dependencies shared by both packages are hashed once.
+        let mut seen = HashSet::new();
+        sources.extend(
+            packages
+                .into_iter()
+                .filter(|(_, path)| seen.insert(path.clone())),
+        );
+        Ok(sources)
+    }
+
+    /// The targets whose binary is missing or older than their sources, by
+    /// the sources they were last built from.
+    pub fn outdated_targets(&self) -> Result<Vec<(String, Staleness)>> {
+        let log = BuildLog::load(&self.build_log_path())?;
+        let mut outdated = Vec::new();
+        for target in self.fuzz_targets()? {
+            let sources = builds::hash_sources(&self.target_sources(&target)?)?;
+            if let Some(staleness) = log.staleness(&target.name, &sources) {
+                outdated.push((target.name, staleness));
+            }
+        }
+        Ok(outdated)
+    }
+
+    /// Builds every target on its own, going on past the ones that fail, and
+    /// prints how each went and how long it took
+    pub fn exec_build_each(
+        &self,
+        mode: options::BuildMode,
+        build: &options::BuildOptions,
+    ) -> Result<()> {
+        if self.targets.is_empty() {
+            bail!("no fuzz targets to build");
+        }
+        let mut batch = Batch::default();
+        for target in &self.targets {
+            batch.run(target, || self.exec_build(mode, build, Some(target)));
+        }
+        print!("{}", batch.summary());
+        batch.into_result()
+    }
+
+    /// Builds each target of `smoke` and fuzzes it for a number of executions or
+    /// seconds, from an empty corpus and leaving no inputs or artifacts behind,
+    /// then prints how each went.
+    pub fn exec_smoke(&self, smoke: &options::Smoke) -> Result<()> {
+        let targets = if smoke.targets.is_empty() {
+            self.targets.clone()
+        } else {
+            for target in &smoke.targets {
+                self.ensure_target_exists(target)?;
+            }
+            smoke.targets.clone()
+        };
+        if targets.is_empty() {
+            bail!("no fuzz targets to test");
+        }
+        let mut batch = Batch::default();
+        for target in &targets {
+            if batch.stopped() {
+                batch.skip_rest(&targets);
+                break;
+            }
+            let start = time::Instant::now();
+            let smoked = match self.exec_build(BuildMode::Build, &smoke.build, Some(target)) {
+                Ok(()) => self.smoke(smoke, target),
+                Err(e) => Ok((
+                    smoke::Verdict::BuildFailed(format!("{:#}", e)),
+                    engine::Stats::default(),
+                )),
+            };
+            let (outcome, detail) = match smoked {
+                Ok((verdict, stats)) => {
+                    if !verdict.passed() {
+                        warn!("{} {}", target, verdict);
+                    }
+                    verdict.into_outcome(&stats)
+                }
+                Err(e) => {
+                    warn!("could not smoke test {}: {:#}", target, e);
+                    (batch::Outcome::Failed(e), None)
+                }
+            };
+            batch.record_outcome(target, outcome, start.elapsed(), detail);
+        }
+
+        info!("\n{:─<80}\n", "");
+        print!("{}", batch.summary());
+        batch.into_result()
+    }
+
+    /// Fuzzes the built `target` as `smoke` says, in a temporary directory.
+    fn smoke(
+        &self,
+        smoke: &options::Smoke,
+        target: &str,
+    ) -> Result<(smoke::Verdict, engine::Stats)> {
+        let engine = LibFuzzer::new(self, &smoke.build);
+        let tmp = tempfile::tempdir().context("failed to create temp dir")?;
+        let corpus = tmp.path().join("corpus");
+        fs::create_dir(&corpus)
+            .with_context(|| format!("failed to create directory {}", corpus.display()))?;
+        let declared = self.fuzz_target(target)?.declared.unwrap_or_default();
+
+        let mut cmd = engine.command(target)?;
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(tmp.path().join(""));
+        cmd.stdin(Stdio::null())
+            .arg(artifact_arg)
+            .args(declared.max_len_arg())
+            .args(&declared.args)
+            .arg(match smoke.time {
+                Some(secs) => format!("-max_total_time={}", secs),
+                None => format!("-runs={}", smoke.runs),
+            })
+            .args(&smoke.args)
+            .arg(&corpus);
+        let outcome = engine.run(target, &mut cmd)?;
+        let output: Vec<&str> = outcome.tail.iter().map(String::as_str).collect();
+        Ok((
+            smoke::Verdict::of_run(outcome.status.success(), &output),
+            outcome.stats,
+        ))
+    }
+
+    /// The options `target` is built with: `build` with the sanitizer and the
+    /// features `Move.toml` pins for it. Those differing from `build` get a
+    /// target directory of their own, so that the binaries of each
+    /// configuration stay built.
+    pub(crate) fn build_for(&self, build: &BuildOptions, target: &str) -> Result<BuildOptions> {
+        let declared = self
+            .fuzz_section()?
+            .targets
+            .get(target)
+            .map(|target| target.build.clone())
+            .unwrap_or_default();
+        let mut own = build.clone();
+        let cargo = &mut own.cargo_options;
+        if let Some(sanitizer) = declared.sanitizer {
+            if !config::is_explicit("sanitizer") {
+                cargo.sanitizer = sanitizer;
+            }
+        }
+        if !cargo.all_features {
+            let mut features: Vec<String> = cargo
+                .features
+                .iter()
+                .flat_map(|features| features.split([',', ' ']))
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_owned)
+                .collect();
+            let given = features.len();
+            for feature in declared.features {
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+            if features.len() > given {
+                cargo.features = Some(features.join(","));
+            }
+        }
+        if own.cargo_options == build.cargo_options {
+            return Ok(own);
+        }
+
+        let mut configuration = match own.cargo_options.sanitizer {
+            Sanitizer::None => "none".to_owned(),
+            sanitizer => sanitizer.to_string(),
+        };
+        if let Some(features) = &own.cargo_options.features {
+            configuration.push('-');
+            configuration.push_str(&features.replace(',', "+").replace('/', "-"));
+        }
+        let base = self
+            .target_dir(build)?
+            .unwrap_or_else(|| self.fuzz_dir().join("target"));
+        own.target_dir = Some(
+            base.join("pinned")
+                .join(configuration)
+                .to_string_lossy()
+                .into_owned(),
+        );
+        Ok(own)
+    }
+
+    /// Builds every target, in one go unless `Move.toml` pins the build of
+    /// some, then once for each configuration.
+    fn build_all(&self, mode: options::BuildMode, build: &BuildOptions) -> Result<()> {
+        let mut configurations: Vec<(BuildOptions, Vec<&str>)> = Vec::new();
+        for target in &self.targets {
+            let own = self.build_for(build, target)?;
+            match configurations.iter_mut().find(|(other, _)| *other == own) {
+                Some((_, targets)) => targets.push(target),
+                None => configurations.push((own, vec![target])),
+            }
+        }
+        match &configurations[..] {
+            [] => self.build_targets(mode, build, &[]),
+            [(own, _)] if own == build => self.build_targets(mode, build, &[]),
+            _ => {
+                for (own, targets) in &configurations {
+                    info!("Building {}{}", targets.join(", "), own.cargo_options);
+                    self.build_targets(mode, own, targets)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Builds `fuzz_targets`, every target if empty.
+    fn build_targets(
+        &self,
+        mode: options::BuildMode,
+        build: &options::BuildOptions,
+        fuzz_targets: &[&str],
     ) -> Result<()> {
         let cargo_subcommand = match mode {
             options::BuildMode::Build => "build",
             options::BuildMode::Check => "check",
         };
+        match self.audit() {
+            Ok(audit) => {
+                for warning in audit.warnings {
+                    warn!("{}", warning);
+                }
+            }
+            Err(e) => warn!("could not audit the dependencies: {:#}", e),
+        }
         let mut cargo_cmd = self.cargo_build(cargo_subcommand, build)?;
         let mut move_cmd = self.move_build(cargo_subcommand, build)?;
 
-        if let Some(fuzz_target) = fuzz_target {
-            cargo_cmd.arg("--bin").arg(fuzz_target);
-        } else {
+        if fuzz_targets.is_empty() {
             cargo_cmd.arg("--bins");
         }
+        for fuzz_target in fuzz_targets {
+            cargo_cmd.arg("--bin").arg(fuzz_target);
+        }
 
         if let Some(target_dir) = self.target_dir(&build)? {
             cargo_cmd.arg("--target-dir").arg(&target_dir);
             move_cmd.arg("--path").arg(&target_dir);
-        }
-        else {
+        } else {
             move_cmd.arg("--path").arg(&self.fuzz_dir());
         }
 
-        let status = cargo_cmd
-            .status()
-            .with_context(||format!("failed to execute: {:?}", cargo_cmd))?;
+        let fuzz_target = match fuzz_targets {
+            [fuzz_target] => Some(*fuzz_target),
+            _ => None,
+        };
+        let progress = self.progress.start(Task::Build, fuzz_target, Some(2));
+        let status = logging::status(&mut cargo_cmd, Source::Cargo)?;
         if !status.success() {
-            bail!("failed to build fuzz script: {:?}", cargo_cmd);
+            return Err(Error::BuildFailed {
+                command: format!("{:?}", cargo_cmd),
+            }
+            .into());
         }
+        progress.step();
 
         let mut move_build = Command::new("move");
         move_build.arg(cargo_subcommand)
             .current_dir("fuzz");
 
-        let move_status = move_build
-            .status()
-            .with_context(|| format!("failed to execute: {:?}", move_build))?;
+        let move_status = logging::status(&mut move_build, Source::Move)?;
         if !move_status.success() {
-            //bail!("failed to build fuzz script: {:?}", move_build);
-            bail!("failed to build fuzz script: {:?}", move_build);
+            return Err(Error::BuildFailed {
+                command: format!("{:?}", move_build),
+            }
+            .into());
         }
+        progress.step();
 
         Ok(())
     }
 
-    fn get_artifacts_since(
+    pub fn get_artifacts_since(
         &self,
         target: &str,
         since: &time::SystemTime,
@@ -420,7 +1797,7 @@ impl FuzzProject {
             let modified = metadata
                 .modified()
                 .context("failed to get artifact modification time")?;
-            if !metadata.is_file() || modified <= *since {
+            if !metadata.is_file() || modified <= *since || metadata::is_sidecar(&entry.path()) {
                 continue;
             }
 
@@ -473,6 +1850,9 @@ impl FuzzProject {
 
     /// Prints the debug output of an input test case
     pub fn debug_fmt_input(&self, debugfmt: &options::Fmt) -> Result<()> {
+        if let Some(encoding) = debugfmt.decode {
+            return decode_input(encoding, &debugfmt.input, debugfmt.output.as_deref());
+        }
         if !debugfmt.input.exists() {
             bail!(
                 "Input test case does not exist: {}",
@@ -480,6 +1860,16 @@ impl FuzzProject {
             );
         }
 
+        if debugfmt.backtrace {
+            return self.print_backtrace(debugfmt);
+        }
+        if let Some(encoding) = debugfmt.encode {
+            let data = fs::read(&debugfmt.input)
+                .with_context(|| format!("failed to read {}", debugfmt.input.display()))?;
+            println!("{}", encoding::encode(encoding, &data));
+            return Ok(());
+        }
+
         let debug = self
             .run_fuzz_target_debug_formatter(&debugfmt.build, &debugfmt.target, &debugfmt.input)
             .with_context(|| {
@@ -489,597 +1879,6013 @@ impl FuzzProject {
                 )
             })?;
 
-        eprintln!("\nOutput of `std::fmt::Debug`:\n");
+        info!("\nOutput of `std::fmt::Debug`:\n");
         for l in debug.lines() {
-            eprintln!("{}", l);
+            info!("{}", l);
         }
 
         Ok(())
     }
 
+    /// Prints the backtrace kept in the metadata of an artifact, or that of the
+    /// crash it reproduces.
+    fn print_backtrace(&self, debugfmt: &options::Fmt) -> Result<()> {
+        let artifact = &debugfmt.input;
+        let kept = ArtifactMetadata::load(artifact)
+            .ok()
+            .and_then(|meta| meta.backtrace);
+        let backtrace = match kept {
+            Some(backtrace) => backtrace,
+            None => {
+                info!(
+                    "No backtrace kept for {}, reproducing it",
+                    artifact.display()
+                );
+                self.exec_build(BuildMode::Build, &debugfmt.build, Some(&debugfmt.target))?;
+                let mut cmd = self.cargo_run(&debugfmt.build, &debugfmt.target)?;
+                cmd.stdin(Stdio::null()).arg(artifact);
+                let output = cmd
+                    .output()
+                    .with_context(|| format!("failed to run command: {:?}", cmd))?;
+                if output.status.success() {
+                    bail!("{} does not reproduce a failure", artifact.display());
+                }
+                backtrace::capture(String::from_utf8_lossy(&output.stderr).lines()).with_context(
+                    || format!("the crash on {} has no backtrace", artifact.display()),
+                )?
+            }
+        };
+        let fuzz_dir = self.fuzz_dir().to_owned();
+        let package_dir = self.package_dir().unwrap_or_else(|| fuzz_dir.clone());
+        print!(
+            "{}",
+            backtrace::render(
+                &backtrace,
+                &[&package_dir, &fuzz_dir],
+                std::io::stdout().is_terminal()
+            )
+        );
+        Ok(())
+    }
+
     /// Fuzz a given fuzz target
     pub fn exec_fuzz(&self, run: &options::Run) -> Result<()> {
-        // todo: gestione parametri build
-        self.exec_build(BuildMode::Build, &run.build, Some(&run.target))?;
-        let mut cmd = self.cargo_run(&run.build, &run.target)?;
-
-        for arg in &run.args {
-            cmd.arg(arg);
+        if run.build.cargo_options.engine != Engine::Libafl
+            && (!run.libafl_mutators.is_empty() || run.libafl_tui)
+        {
+            bail!("`--libafl-mutator` and `--libafl-tui` require `--engine libafl`");
         }
-
-        if !run.corpus.is_empty() {
-            for corpus in &run.corpus {
-                cmd.arg(corpus);
-            }
-        } else {
-            cmd.arg(self.corpus_for(&run.target)?);
+        if let Some(binary) = &run.concolic {
+            concolic::check_binary(binary)?;
         }
-
+        if let Some(template) = &run.artifact_name {
+            NameTemplate::parse(template)?;
+        }
+        let mut set = vec!["artifact_prefix"];
         if run.jobs != 1 {
-            cmd.arg(format!("-fork={}", run.jobs));
+            set.push("fork");
         }
-
-        // When libfuzzer finds failing inputs, those inputs will end up in the
-        // artifacts directory. To easily filter old artifacts from new ones,
-        // get the current time, and then later we only consider files modified
-        // after now.
-        let before_fuzzing = time::SystemTime::now();
-
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if status.success() {
+        if run.all || run.max_total_time.is_some() {
+            set.push("max_total_time");
+        }
+        if run.libafl_tui {
+            set.push("tui");
+        }
+        set.extend(run.libafl_mutators.iter().map(|mutator| match mutator {
+            options::LibaflMutator::Grimoire => "grimoire",
+            options::LibaflMutator::Unicode => "unicode",
+        }));
+        check_engine_args(&run.build, &run.args, &set);
+        if run.dry_run {
+            let targets = run.targets(&self.targets);
+            for target in &targets {
+                self.ensure_target_exists(target)?;
+                self.print_fuzz_command(run, target)?;
+            }
             return Ok(());
         }
-
-        // Get and print the `Debug` formatting of any new artifacts, along with
-        // tips about how to reproduce failures and/or minimize test cases.
-
-        let new_artifacts = self.get_artifacts_since(&run.target, &before_fuzzing)?;
-
-        for artifact in new_artifacts {
-            // To make the artifact a little easier to read, strip the current
-            // directory prefix when possible.
-            let artifact = strip_current_dir_prefix(&artifact);
-
-            eprintln!("\n{:─<80}", "");
-            eprintln!("\nFailing input:\n\n\t{}\n", artifact.display());
-
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&run.build, &run.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
-                }
-                eprintln!();
+        if let Some(input) = &run.input {
+            return self.exec_input(run, input);
+        }
+        shutdown::install();
+        if !run.concurrent.is_empty() {
+            let result = self.exec_fuzz_concurrent(run);
+            self.coverage_after(run, &result);
+            self.gc_after(run);
+            return result;
+        }
+        let scope = match &run.target {
+            Some(target) => lock::Scope::Target(target),
+            None => lock::Scope::Project,
+        };
+        let _lock = Lock::acquire(self.fuzz_dir(), scope, &run.lock)?;
+        self.drop_schedules(run)?;
+        let hooks = (HookPoint::PreRun, HookPoint::PostRun);
+        let result = self.with_hooks(hooks, run.target.as_deref(), || match &run.target {
+            Some(target) if run.hunt_oom.is_some() => {
+                self.exec_build(BuildMode::Build, &run.build, Some(target))?;
+                self.exec_hunt_oom(run, target)
+            }
+            Some(target) => {
+                self.exec_build(BuildMode::Build, &run.build, Some(target))?;
+                events::logged(
+                    &self.events_log_path(),
+                    EventKind::Run,
+                    Some(target),
+                    || self.fuzz(run, target, None).map(|_| ()),
+                )
             }
+            None => self.exec_fuzz_all(run),
+        });
+        self.coverage_after(run, &result);
+        self.gc_after(run);
+        result
+    }
 
-            let fuzz_dir = if self.fuzz_dir_is_default_path() {
-                String::new()
+    /// Keeps the targets of `run` within the `[fuzz.quota]` of `Move.toml`
+    /// once it ended, warning about what could not be collected. The targets
+    /// of `run --concurrent` are locked again for it, the others are still
+    /// locked by the run.
+    fn gc_after(&self, run: &options::Run) {
+        let quota = match self.fuzz_section() {
+            Ok(section) if section.quota.is_set() => section.quota.clone(),
+            _ => return,
+        };
+        let targets = run.targets(&self.targets);
+        for target in targets {
+            let result = if !run.concurrent.is_empty() {
+                Lock::acquire(self.fuzz_dir(), lock::Scope::Target(&target), &run.lock)
+                    .and_then(|_lock| self.apply_quota(&quota, &run.build, &target))
             } else {
-                format!(" --fuzz-dir {}", self.fuzz_dir().display())
+                self.apply_quota(&quota, &run.build, &target)
             };
+            if let Err(e) = result {
+                warn!("could not keep {} within its quota: {:#}", target, e);
+            }
+        }
+    }
 
-            eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &run.build,
-                target = &run.target,
-                artifact = artifact.display()
+    /// Records the coverage of the corpora of `run` once it ended with
+    /// `result`, for `--coverage-after`. Failing to is only worth a warning, the
+    /// outcome of the campaign is what the command reports.
+    fn coverage_after(&self, run: &options::Run, result: &Result<()>) {
+        if !run.coverage_after {
+            return;
+        }
+        // A campaign that could not start has nothing to measure.
+        if let Err(e) = result {
+            if !matches!(error::find(e), Some(Error::CrashFound { .. })) {
+                return;
+            }
+        }
+        if run.build.cargo_options.build_std {
+            warn!(
+                "not collecting coverage, -Zbuild-std is incompatible with -Zinstrument-coverage"
             );
-            eprintln!(
-                "Minimize test case with:\n\n\tcargo fuzz tmin{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &run.build,
-                target = &run.target,
-                artifact = artifact.display()
+            return;
+        }
+        let targets = run.targets(&self.targets);
+        let mut build = run.build.clone();
+        build.cargo_options.coverage = true;
+        // The coverage build goes to its own directory rather than over the
+        // binaries that were fuzzed.
+        build.target_dir = None;
+        for target in targets {
+            let coverage = options::Coverage {
+                action: None,
+                build: build.clone(),
+                fuzz_dir_wrapper: run.fuzz_dir_wrapper.clone(),
+                state_dirs: run.state_dirs.clone(),
+                llvm_path: None,
+                target: Some(target.clone()),
+                corpus: Vec::new(),
+                all: false,
+                rare_branches: false,
+                rare_threshold: 2,
+                coverage_map: None,
+                message_format: options::MessageFormat::Human,
+                args: Vec::new(),
+            };
+            if let Err(e) = self.exec_coverage(&coverage) {
+                warn!("could not collect the coverage of {}: {:#}", target, e);
+                continue;
+            }
+            let history = self
+                .coverage_for(&target)
+                .and_then(|(_, profdata)| {
+                    CoverageSnapshot::load_all(&profdata.with_file_name(COVERAGE_HISTORY_FILE))
+                })
+                .unwrap_or_default();
+            let Some((last, earlier)) = history.split_last() else {
+                continue;
+            };
+            let summary = &last.summary;
+            let gained = match earlier.last() {
+                Some(previous) => format!(
+                    ", {:+} since the previous snapshot",
+                    summary.lines_covered as i64 - previous.summary.lines_covered as i64
+                ),
+                None => String::new(),
+            };
+            println!(
+                "Coverage of {} after the run: {:.1}% ({}/{} lines{})",
+                target, summary.percent, summary.lines_covered, summary.lines_total, gained
             );
         }
-
-        eprintln!("{:─<80}\n", "");
-        bail!("Fuzz target exited with {}", status)
     }
 
-    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
-        self.exec_build(BuildMode::Build, &tmin.build, Some(&tmin.target))?;
-        let mut cmd = self.cargo_run(&tmin.build, &tmin.target)?;
-        cmd.arg("-minimize_crash=1")
-            .arg(format!("-runs={}", tmin.runs))
-            .arg(&tmin.test_case);
-
-        for arg in &tmin.args {
-            cmd.arg(arg);
+    /// Fuzz the targets of `run --concurrent` at once, each on its own thread
+    /// and with its own instance of the project, hooks included
+    fn exec_fuzz_concurrent(&self, run: &options::Run) -> Result<()> {
+        let targets = run.concurrent.clone();
+        let mut locks = Vec::new();
+        for (i, target) in targets.iter().enumerate() {
+            self.ensure_target_exists(target)?;
+            if targets[..i].contains(target) {
+                bail!("fuzz target {} is given more than once", target);
+            }
+            locks.push(Lock::acquire(
+                self.fuzz_dir(),
+                lock::Scope::Target(target),
+                &run.lock,
+            )?);
         }
-
-        let before_tmin = time::SystemTime::now();
-
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if !status.success() {
-            eprintln!("\n{:─<80}\n", "");
-            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status)).with_context(|| {
-                "Test case minimization failed.\n\
-                 \n\
-                 Usually this isn't a hard error, and just means that libfuzzer\n\
-                 doesn't know how to minimize the test case any further while\n\
-                 still reproducing the original crash.\n\
-                 \n\
-                 See the logs above for details."
-            });
+        self.drop_schedules(run)?;
+        for target in &targets {
+            self.exec_build(BuildMode::Build, &run.build, Some(target))?;
         }
 
-        // Find and display the most recently modified artifact, which is
-        // presumably the result of minification. Yeah, this is a little hacky,
-        // but it seems to work. I don't want to parse libfuzzer's stderr output
-        // and hope it never changes.
-        let minimized_artifact = self
-            .get_artifacts_since(&tmin.target, &before_tmin)?
-            .into_iter()
-            .max_by_key(|a| {
-                a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(time::SystemTime::UNIX_EPOCH)
-            });
-
-        if let Some(artifact) = minimized_artifact {
-            let artifact = strip_current_dir_prefix(&artifact);
+        let results: Vec<_> = thread::scope(|scope| {
+            let runs: Vec<_> = targets
+                .iter()
+                .map(|target| {
+                    let mut single = run.clone();
+                    single.target = Some(target.clone());
+                    let fuzz_dir = self.fuzz_dir().to_owned();
+                    scope.spawn(move || {
+                        logging::with_prefix(target, || {
+                            let project = FuzzProject::new(Some(fuzz_dir))?
+                                .with_state_dirs(&single.state_dirs);
+                            let hooks = (HookPoint::PreRun, HookPoint::PostRun);
+                            project.with_hooks(hooks, Some(target), || {
+                                events::logged(
+                                    &project.events_log_path(),
+                                    EventKind::Run,
+                                    Some(target),
+                                    || project.fuzz(&single, target, None).map(|_| ()),
+                                )
+                            })
+                        })
+                    })
+                })
+                .collect();
+            runs.into_iter()
+                .zip(&targets)
+                .map(|(handle, target)| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow!("fuzzing {} panicked", target)))
+                })
+                .collect()
+        });
 
-            eprintln!("\n{:─<80}\n", "");
-            eprintln!("Minimized artifact:\n\n\t{}\n", artifact.display());
+        let mut crashed = Vec::new();
+        let mut artifacts = Vec::new();
+        let mut failure = None;
+        for (target, result) in targets.iter().zip(results) {
+            let Err(e) = result else {
+                continue;
+            };
+            match error::find(&e) {
+                Some(Error::CrashFound {
+                    targets,
+                    artifacts: found,
+                }) => {
+                    crashed.extend(targets.iter().cloned());
+                    artifacts.extend(found.iter().cloned());
+                }
+                _ => {
+                    warn!("fuzzing {} failed: {:#}", target, e);
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        if !crashed.is_empty() {
+            return Err(Error::CrashFound {
+                targets: crashed,
+                artifacts,
+            }
+            .into());
+        }
+        Ok(())
+    }
 
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&tmin.build, &tmin.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
+    /// Fuzz `target` under a memory limit halved at each level and rank the
+    /// inputs that went over it by their peak RSS on their own
+    fn exec_hunt_oom(&self, run: &options::Run, target: &str) -> Result<()> {
+        let start_mb = run.hunt_oom.unwrap_or_default();
+        let mut found = Vec::new();
+        let mut limit = start_mb;
+        while limit >= OOM_FLOOR_MB {
+            info!(
+                "Fuzzing {} with a {} MB memory limit for {} seconds",
+                target, limit, run.hunt_oom_time
+            );
+            let mut level = run.clone();
+            level.args.push(format!("-malloc_limit_mb={}", limit));
+            level.args.push(format!("-rss_limit_mb={}", limit));
+            let before = time::SystemTime::now();
+            let result = events::logged(
+                &self.events_log_path(),
+                EventKind::Run,
+                Some(target),
+                || self.fuzz(&level, target, Some(run.hunt_oom_time)),
+            );
+            let ooms: Vec<_> = self
+                .get_artifacts_since(target, &before)?
+                .into_iter()
+                .filter(|a| metadata::class(a).as_deref() == Some("oom"))
+                .collect();
+            // Anything but running out of memory is a real crash and ends the hunt.
+            if let Err(e) = result {
+                if ooms.is_empty() {
+                    return Err(e);
                 }
-                eprintln!();
             }
+            found.extend(ooms.into_iter().map(|artifact| (artifact, limit)));
+            limit /= 2;
+        }
 
-            let fuzz_dir = if self.fuzz_dir_is_default_path() {
-                String::new()
-            } else {
-                format!(" --fuzz-dir {}", self.fuzz_dir().display())
-            };
+        // Measured with some headroom over the first limit, but still capped
+        // so a pathological input cannot take the whole machine down.
+        let cap_mb = start_mb * 2;
+        let mut offenders = Vec::new();
+        for (artifact, limit) in found {
+            let peak = self.peak_rss(&run.build, target, &artifact, cap_mb)?;
+            match ArtifactMetadata::load(&artifact) {
+                Ok(mut meta) => {
+                    meta.peak_rss_mb = peak;
+                    if let Err(e) = meta.write(&artifact) {
+                        warn!("could not update the artifact metadata: {:#}", e);
+                    }
+                }
+                Err(e) => warn!("could not update the artifact metadata: {:#}", e),
+            }
+            offenders.push((artifact, limit, peak));
+        }
+        if offenders.is_empty() {
+            println!("No input went over {} MB", OOM_FLOOR_MB);
+            return Ok(());
+        }
 
-            eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &tmin.build,
-                target = &tmin.target,
-                artifact = artifact.display()
+        offenders.sort_by_key(|&(_, _, peak)| std::cmp::Reverse(peak));
+        println!("{:>10} {:>10}  ARTIFACT", "PEAK RSS", "LIMIT");
+        for (artifact, limit, peak) in &offenders {
+            println!(
+                "{:>10} {:>10}  {}",
+                peak.map_or_else(|| format!(">{} MB", cap_mb), |mb| format!("{} MB", mb)),
+                format!("{} MB", limit),
+                strip_current_dir_prefix(artifact).display()
             );
         }
-
         Ok(())
     }
 
-    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
-        self.exec_build(BuildMode::Build, &cmin.build, Some(&cmin.target))?;
-        let mut cmd = self.cargo_run(&cmin.build, &cmin.target)?;
+    /// Runs `artifact` on its own under a `cap_mb` memory limit and returns its peak RSS,
+    /// `None` if it went over the limit by allocating too much at once
+    fn peak_rss(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+        cap_mb: u64,
+    ) -> Result<Option<u64>> {
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .arg("-print_final_stats=1")
+            .arg(format!("-malloc_limit_mb={}", cap_mb))
+            .arg(format!("-rss_limit_mb={}", cap_mb))
+            .arg(artifact);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        Ok(metadata::peak_rss_mb(&String::from_utf8_lossy(
+            &output.stderr,
+        )))
+    }
 
-        for arg in &cmin.args {
-            cmd.arg(arg);
+    /// Fuzz every target in time slices, giving each slice to the target picked by the schedule
+    fn exec_fuzz_all(&self, run: &options::Run) -> Result<()> {
+        if self.targets.is_empty() {
+            bail!("no fuzz targets to run");
         }
+        self.exec_build(BuildMode::Build, &run.build, None)?;
 
-        let corpus = if let Some(corpus) = cmin.corpus.clone() {
-            corpus
+        let mut scheduler = Scheduler::new(
+            &self.targets,
+            run.schedule.unwrap_or(options::Schedule::Coverage),
+        );
+        let budget = time::Duration::from_secs(run.time.unwrap_or(3600));
+        let start = time::Instant::now();
+        let mut batch = Batch::default();
+        while let Some(index) = scheduler.next() {
+            if batch.stopped() {
+                batch.skip_rest(&self.targets);
+                break;
+            }
+            let remaining = budget.saturating_sub(start.elapsed()).as_secs();
+            if remaining == 0 || shutdown::requested() {
+                break;
+            }
+            let target = &self.targets[index];
+            let secs = run.slice.unwrap_or(300).min(remaining);
+            info!(
+                "Fuzzing {} for {} seconds ({} seconds left)",
+                target, secs, remaining
+            );
+
+            let slice_start = time::Instant::now();
+            let result = events::logged(
+                &self.events_log_path(),
+                EventKind::Run,
+                Some(target),
+                || self.fuzz(run, target, Some(secs)),
+            );
+            let elapsed = slice_start.elapsed().as_secs_f64();
+            match result {
+                Ok(features) => {
+                    scheduler.record(index, features, elapsed, false);
+                    batch.record_result(target, Ok(()));
+                }
+                Err(e) => {
+                    warn!("not scheduling {} anymore: {:#}", target, e);
+                    scheduler.record(index, None, elapsed, true);
+                    batch.record_result(target, Err(e));
+                }
+            }
+        }
+
+        println!(
+            "{:<24} {:>8} {:>10}  STATUS",
+            "TARGET", "SLICES", "FEATURES"
+        );
+        for stats in scheduler.stats() {
+            println!(
+                "{:<24} {:>8} {:>10}  {}",
+                stats.target,
+                stats.slices,
+                stats
+                    .features
+                    .map_or_else(|| "-".to_owned(), |f| f.to_string()),
+                batch
+                    .outcome(&stats.target)
+                    .map_or_else(|| "-".to_owned(), |outcome| outcome.to_string())
+            );
+        }
+        batch.into_result()
+    }
+
+    /// Runs the target of `run` once on `input`, read from stdin if it is `-`,
+    /// logging the output of the target and then what went wrong, if anything:
+    /// the failure, the backtrace and the artifact libFuzzer saved.
+    fn exec_input(&self, run: &options::Run, input: &Path) -> Result<()> {
+        let Some(target) = &run.target else {
+            bail!("`--input` needs a fuzz target");
+        };
+        self.ensure_target_exists(target)?;
+        // libFuzzer only runs files, and piped inputs can only be read once.
+        let tmp = tempfile::tempdir().context("failed to create temp dir")?;
+        let (input, name) = if input == Path::new("-") {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .context("failed to read the input from stdin")?;
+            let path = tmp.path().join("stdin");
+            fs::write(&path, bytes)
+                .with_context(|| format!("failed to write to {}", path.display()))?;
+            (path, "<stdin>".to_owned())
+        } else if input.is_file() {
+            (input.to_owned(), input.display().to_string())
         } else {
-            self.corpus_for(&cmin.target)?
+            bail!("{} is not a file", input.display());
         };
-        let corpus = corpus
-            .to_str()
-            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
-            .to_owned();
-
-        let tmp = tempfile::TempDir::new_in(self.fuzz_dir())?;
-        let tmp_corpus = tmp.path().join("corpus");
-        fs::create_dir(&tmp_corpus)?;
+        let size = fs::metadata(&input)
+            .with_context(|| format!("failed to read {}", input.display()))?
+            .len();
+
+        self.exec_build(BuildMode::Build, &run.build, Some(target))?;
+        let engine = LibFuzzer::new(self, &run.build);
+        let declared = self.fuzz_target(target)?.declared.unwrap_or_default();
+        let mut cmd = engine.command(target)?;
+        cmd.stdin(Stdio::null())
+            .args(&declared.args)
+            .args(&run.args)
+            .arg(&input);
+        info!("Running {} on {} ({} bytes)", target, name, size);
+        let start = time::Instant::now();
+        let outcome = engine.run(target, &mut cmd)?;
+        let elapsed = start.elapsed();
+
+        info!("\n{:─<80}\n", "");
+        info!(
+            "{} ({} bytes) ran in {:.1} ms, the target exited with {}",
+            name,
+            size,
+            elapsed.as_secs_f64() * 1000.0,
+            outcome.status
+        );
+        if outcome.status.success() {
+            println!("{} does not make {} fail", name, target);
+            return Ok(());
+        }
+        let output = outcome
+            .tail
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(signature) = crash_signature(&output) {
+            info!("Failure: {}", signature);
+        }
+        let mut artifacts: Vec<_> = outcome.artifacts.into_iter().collect();
+        artifacts.sort();
+        for artifact in &artifacts {
+            info!("Saved as {}", strip_current_dir_prefix(artifact).display());
+        }
+        if let Some(backtrace) = backtrace::capture(output.lines()) {
+            let fuzz_dir = self.fuzz_dir().to_owned();
+            let package_dir = self.package_dir().unwrap_or_else(|| fuzz_dir.clone());
+            info!(
+                "\nBacktrace:\n{}",
+                backtrace::render(
+                    &backtrace,
+                    &[&package_dir, &fuzz_dir],
+                    std::io::stderr().is_terminal()
+                )
+                .trim_end()
+            );
+        }
+        Err(Error::crash(target, artifacts).into())
+    }
 
-        cmd.arg("-merge=1").arg(&tmp_corpus).arg(&corpus);
+    /// Runs libFuzzer on `target`, for `slice` seconds if given. Slices have
+    /// their output scanned for the coverage features reached, which are returned.
+    fn fuzz(&self, run: &options::Run, target: &str, slice: Option<u64>) -> Result<Option<u64>> {
+        // todo: gestione parametri build
+        let engine = LibFuzzer::new(self, &run.build);
+        // libFuzzer only reloads the first corpus directory it was given, and
+        // only writes new inputs there.
+        let corpus = match run.corpus.first() {
+            Some(dir) if Path::new(dir).is_dir() => PathBuf::from(dir),
+            _ => self.corpus_for(target)?,
+        };
+        let mut schedule = self.prepare_schedule(target)?;
+        let mut cmd = self.fuzz_command(&engine, run, target)?;
+
+        RunLog::record(&self.run_log_path(), target)?;
+
+        // libFuzzer keeps the corpora of the `-fork` jobs under TMPDIR, apart
+        // from those of other runs of the target.
+        let work_dir = self
+            .work_dir_for(target)
+            .join(format!("run-{}", process::id()));
+        fs::create_dir_all(&work_dir)
+            .with_context(|| format!("failed to create directory {}", work_dir.display()))?;
+        cmd.env("TMPDIR", &work_dir);
+        let running = Arc::new(AtomicBool::new(true));
+        let watcher = {
+            let deadline = self
+                .time_limit(run, target, slice)?
+                .map(|secs| time::Instant::now() + time::Duration::from_secs(secs));
+            let (running, target, work_dir) =
+                (running.clone(), target.to_owned(), work_dir.clone());
+            thread::spawn(move || watch_shutdown(&running, &target, &work_dir, deadline))
+        };
 
-        // Spawn cmd in child process instead of exec-ing it
-        let status = cmd
-            .status()
-            .with_context(|| format!("could not execute command: {:?}", cmd))?;
-        if status.success() {
-            // move corpus directory into tmp to auto delete it
-            fs::rename(&corpus, tmp.path().join("old"))?;
-            fs::rename(tmp.path().join("corpus"), corpus)?;
+        // Only the corpus of the target has its provenance recorded.
+        let indexed = corpus == self.corpus_root().join(target);
+        let before = provenance::entry_names(&corpus)?;
+        let before_fuzzing = time::SystemTime::now();
+        let run_started = unix_now();
+        let concolic = match &run.concolic {
+            Some(binary) => {
+                let helper = Concolic {
+                    binary: binary.clone(),
+                    corpus: corpus.clone(),
+                    work_dir: self.fuzz_dir().join("concolic").join(target),
+                    provenance: indexed.then(|| self.provenance_index(target)),
+                };
+                let stop = Arc::new(AtomicBool::new(false));
+                let interval = time::Duration::from_secs(run.concolic_interval);
+                Some((helper.spawn(interval, stop.clone()), stop))
+            }
+            None => None,
+        };
+        let outcome = if !run.concurrent.is_empty() {
+            let path = self.stats_log_path(target);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create directory {}", dir.display()))?;
+            }
+            let mut stream = fs::File::create(&path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            let mut failed = false;
+            engine.run_with(target, &mut cmd, &mut |line| {
+                schedule.record_output(line);
+                let stats = engine.parse_stats(line);
+                if let Err(e) = engine::write_stats(&mut stream, unix_now(), stats) {
+                    if !std::mem::replace(&mut failed, true) {
+                        warn!("could not write to {}: {:#}", path.display(), e);
+                    }
+                }
+            })
         } else {
-            println!("Failed to minimize corpus: {}", status);
+            engine.run_with(target, &mut cmd, &mut |line| schedule.record_output(line))
+        };
+        if let Some((helper, stop)) = concolic {
+            stop.store(true, Ordering::Relaxed);
+            let _ = helper.join();
+        }
+        running.store(false, Ordering::Relaxed);
+        let stopped = watcher.join().unwrap_or(false);
+        let Outcome {
+            status,
+            stats,
+            tail,
+            artifacts: new_artifacts,
+        } = outcome?;
+        if let Err(e) = self.finish_run(&engine, target, &corpus, &work_dir, stats) {
+            warn!("could not wrap up the run of {}: {:#}", target, e);
+        }
+        if indexed {
+            let args: Vec<String> = env::args().skip(1).collect();
+            self.record_new_entries(target, &corpus, &before, |_| Origin::Fuzzed {
+                run: run_started,
+                args: args.clone(),
+            });
         }
+        if run.build.cargo_options.engine == Engine::Libfuzzer {
+            let state = self.scheduling_dir_for(target).join(SCHEDULING_STATE_FILE);
+            if let Err(e) = schedule.save(&state) {
+                warn!("could not save the scheduling state of {}: {:#}", target, e);
+            }
+        }
+        // libFuzzer exits with a failure when interrupted.
+        if status.success() || (stopped && new_artifacts.is_empty()) {
+            return Ok(stats.features);
+        }
+        let template = run
+            .artifact_name
+            .as_deref()
+            .map(NameTemplate::parse)
+            .transpose()?;
+        let new_artifacts = name_artifacts(target, template.as_ref(), new_artifacts);
 
-        Ok(())
-    }
+        // Get and print the `Debug` formatting of any new artifacts, along with
+        // tips about how to reproduce failures and/or minimize test cases.
 
-    /// Produce coverage information for a given corpus
-    pub fn exec_coverage(self, coverage: &options::Coverage) -> Result<()> {
-        // Build project with source-based coverage generation enabled.
-        self.exec_build(BuildMode::Build, &coverage.build, Some(&coverage.target))?;
+        let mut findings = Vec::new();
+        let mut meta = crash_metadata(
+            target,
+            &run.build,
+            metadata::libfuzzer_args(cmd.get_args()),
+            &tail,
+        );
 
-        // Retrieve corpus directories.
-        let corpora = if coverage.corpus.is_empty() {
-            vec![self.corpus_for(&coverage.target)?]
+        let notifiers = if new_artifacts.is_empty() {
+            Notifiers::default()
         } else {
-            coverage
-                .corpus
-                .iter()
-                .map(|name| Path::new(name).to_path_buf())
-                .collect()
+            Notifiers::from_env()
+        };
+        // The triage database is only read: a bucket triaged already is not
+        // news, and recording the crash is up to `move-fuzz triage`.
+        let mut notified = if notifiers.is_empty() {
+            None
+        } else {
+            Some((
+                CrashDatabase::load(&self.triage_db_path())?,
+                NotifiedLog::load(&self.fuzz_dir().join(notify::NOTIFIED_FILE))?,
+            ))
         };
 
-        // Collect the (non-directory) readable input files from the corpora.
-        let files_and_dirs = corpora.iter().flat_map(fs::read_dir).flatten().flatten();
-        let mut readable_input_files = files_and_dirs
-            .filter(|file| match file.file_type() {
-                Ok(ft) => ft.is_file(),
-                _ => false,
-            })
-            .peekable();
-        if readable_input_files.peek().is_none() {
-            bail!(
-                "The corpus does not contain program-input files. \
-                 Coverage information requires existing input files. \
-                 Try running the fuzzer first (`cargo fuzz run ...`) to generate a corpus, \
-                 or provide a nonempty corpus directory."
-            )
-        }
+        for (artifact, class) in &new_artifacts {
+            meta.class = class.clone();
+            // To make the artifact a little easier to read, strip the current
+            // directory prefix when possible.
+            let artifact = strip_current_dir_prefix(artifact);
+
+            info!("\n{:─<80}", "");
+            info!("\nFailing input:\n\n\t{}\n", artifact.display());
+            let move_frames = symbolize::move_frames(&meta.sanitizer_output);
+            if !move_frames.is_empty() {
+                info!("Failed in Move code:\n");
+                for frame in &move_frames {
+                    info!("\t{}", frame);
+                }
+                info!("");
+            }
+
+            let mut crash = Event::new(
+                EventKind::Crash,
+                Some(target),
+                before_fuzzing.elapsed().unwrap_or_default(),
+            );
+            crash.success = false;
+            crash.detail = Some(artifact.display().to_string());
+            if let Err(e) = events::append(&self.events_log_path(), &crash) {
+                warn!("could not record the event: {:#}", e);
+            }
+            if run.hunt_timeouts.is_some() {
+                match self.profile_artifact(&run.build, target, artifact) {
+                    Ok((elapsed_ms, perf_data)) => {
+                        meta.elapsed_ms = elapsed_ms;
+                        if let Some(ms) = elapsed_ms {
+                            info!("The input took {} ms on its own\n", ms);
+                        }
+                        if let Some(perf_data) = perf_data {
+                            info!(
+                                "Profile saved in:\n\n\t{}\n",
+                                strip_current_dir_prefix(&perf_data).display()
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        warn!("could not profile {}: {:#}", artifact.display(), e)
+                    }
+                }
+            }
+            if let Err(e) = meta.write(artifact) {
+                warn!("could not write the artifact metadata: {:#}", e);
+            }
 
-        let (coverage_out_raw_dir, coverage_out_file) = self.coverage_for(&coverage.target)?;
+            // Note: ignore errors when running the debug formatter. This most
+            // likely just means that we're dealing with a fuzz target that uses
+            // an older version of the libfuzzer crate, and doesn't support
+            // `RUST_LIBFUZZER_DEBUG_PATH`.
+            if let Ok(debug) = self.run_fuzz_target_debug_formatter(&run.build, target, artifact) {
+                info!("Output of `std::fmt::Debug`:\n");
+                for l in debug.lines() {
+                    info!("\t{}", l);
+                }
+                info!("");
+            }
 
-        for corpus in corpora.iter() {
-            // _tmp_dir is deleted when it goes of of scope.
-            let (mut cmd, _tmp_dir) =
-                self.create_coverage_cmd(coverage, &coverage_out_raw_dir, &corpus.as_path())?;
-            eprintln!("Generating coverage data for corpus {:?}", corpus);
-            let status = cmd
-                .status()
-                .with_context(|| format!("Failed to run command: {:?}", cmd))?;
-            if !status.success() {
-                Err(anyhow!(
-                    "Command exited with failure status {}: {:?}",
-                    status,
-                    cmd
-                ))
-                .context("Failed to generage coverage data")?;
+            let fuzz_dir = if self.fuzz_dir_is_default_path() {
+                String::new()
+            } else {
+                format!(
+                    " --fuzz-dir {}",
+                    quote_arg(&self.fuzz_dir().to_string_lossy())
+                )
+            };
+
+            info!(
+                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
+                fuzz_dir = &fuzz_dir,
+                options = &run.build,
+                target = target,
+                artifact = quote_arg(&artifact.to_string_lossy())
+            );
+            info!(
+                "Minimize test case with:\n\n\tcargo fuzz tmin{fuzz_dir}{options}{hunt} {target} {artifact}\n",
+                fuzz_dir = &fuzz_dir,
+                options = &run.build,
+                hunt = run
+                    .hunt_timeouts
+                    .map(|ms| format!(" --hunt-timeouts={}", ms))
+                    .unwrap_or_default(),
+                target = target,
+                artifact = quote_arg(&artifact.to_string_lossy())
+            );
+
+            if run.trace {
+                match self.trace_artifact(&run.build, target, artifact) {
+                    Ok(trace) => info!(
+                        "Move VM trace saved in:\n\n\t{}\n",
+                        strip_current_dir_prefix(&trace).display()
+                    ),
+                    Err(e) => warn!("could not trace {}: {:#}", artifact.display(), e),
+                }
+            }
+
+            let signature = if run.sarif.is_some() || notified.is_some() {
+                // The crash is reported already: failing to reproduce it must
+                // not hide the other artifacts.
+                match self.reproduce_signature(&run.build, target, artifact) {
+                    Ok(signature) => Some(signature),
+                    Err(e) => {
+                        warn!("Could not reproduce {}: {:#}", artifact.display(), e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            if let (Some((db, log)), Some(signature)) = (&mut notified, &signature) {
+                // Only the first artifact of a bucket is worth a notification.
+                let triaged = db
+                    .buckets
+                    .iter()
+                    .any(|b| b.target == target && b.signature == *signature);
+                if !triaged && log.insert(target, signature)? {
+                    let crash = notify::Crash {
+                        target,
+                        signature,
+                        artifact,
+                    };
+                    for e in notifiers.send(&crash) {
+                        warn!("{:#}", e);
+                    }
+                }
+            }
+            if let Some(signature) = signature.filter(|_| run.sarif.is_some()) {
+                findings.push(Finding {
+                    target: target.to_owned(),
+                    signature,
+                    artifacts: vec![artifact.to_owned()],
+                });
             }
         }
 
-        let mut profdata_bin_path = coverage.llvm_path.clone().unwrap_or(rustlib()?);
-        profdata_bin_path.push(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
-        self.merge_coverage(
-            &profdata_bin_path,
-            &coverage_out_raw_dir,
-            &coverage_out_file,
-        )?;
+        info!("{:─<80}\n", "");
+        if let Some(path) = &run.sarif {
+            sarif::write(path, &findings)?;
+        }
+        info!("Fuzz target exited with {}", status);
+        let artifacts = new_artifacts.into_iter().map(|(artifact, _)| artifact);
+        Err(Error::crash(target, artifacts.collect()).into())
+    }
 
+    /// Merges the inputs in flight when the run of `target` stopped into
+    /// `corpus`, removes the temporary files of libFuzzer and appends the final
+    /// `stats` to the stats log.
+    /// Drops the scheduling state earlier runs left for the targets of `run`,
+    /// for `--fresh-schedule`. It is done once for the whole campaign, which
+    /// may fuzz a target in several slices.
+    fn drop_schedules(&self, run: &options::Run) -> Result<()> {
+        if !run.fresh_schedule {
+            return Ok(());
+        }
+        let targets = run.targets(&self.targets);
+        for target in targets {
+            let dir = self.scheduling_dir_for(&target);
+            if dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .with_context(|| format!("failed to remove {}", dir.display()))?;
+            }
+        }
         Ok(())
     }
 
-    fn create_coverage_cmd(
+    /// The scheduling state earlier runs of `target` left. The input features
+    /// earlier versions had libFuzzer write, and never read back, are removed.
+    fn prepare_schedule(&self, target: &str) -> Result<SchedulingState> {
+        let dir = self.scheduling_dir_for(target);
+        let features = dir.join(FEATURES_DIR);
+        if features.is_dir() {
+            fs::remove_dir_all(&features)
+                .with_context(|| format!("failed to remove {}", features.display()))?;
+        }
+        SchedulingState::load(&dir.join(SCHEDULING_STATE_FILE))
+    }
+
+    fn finish_run(
         &self,
-        coverage: &options::Coverage,
-        coverage_dir: &Path,
-        corpus_dir: &Path,
-    ) -> Result<(Command, tempfile::TempDir)> {
-        let bin_path = {
-            let profile_subdir = if coverage.build.dev {
-                "debug"
-            } else {
-                "release"
-            };
+        engine: &LibFuzzer,
+        target: &str,
+        corpus: &Path,
+        work_dir: &Path,
+        stats: engine::Stats,
+    ) -> Result<()> {
+        // Job corpora left behind by a run killed before it could save them.
+        save_in_flight(work_dir)?;
+        let in_flight = work_dir.join(IN_FLIGHT_DIR);
+        if in_flight.is_dir() {
+            let entries = files_in(&in_flight)?.len();
+            if entries > 0 {
+                info!(
+                    "Merging {} corpus entries in flight into {}",
+                    entries,
+                    corpus.display()
+                );
+                engine.merge(target, corpus, &[in_flight], &[], &mut |_| {})?;
+            }
+        }
+        fs::remove_dir_all(work_dir)
+            .with_context(|| format!("failed to remove {}", work_dir.display()))?;
+        // Left to the other runs of the target while they use it.
+        if let Some(dir) = work_dir.parent() {
+            let _ = fs::remove_dir(dir);
+        }
 
-            let target_dir = self
-                .target_dir(&coverage.build)?
-                .expect("target dir for coverage command should never be None");
-            target_dir
-                .join(&coverage.build.cargo_options.triple)
-                .join(profile_subdir)
-                .join(&coverage.target)
-        };
+        let path = self.stats_log_path(target);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let mut log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        engine::write_stats(&mut log, unix_now(), stats)
+    }
 
-        let mut cmd = Command::new(bin_path);
+    /// Prints what fuzzing `target` as `run` says would run, for `run --dry-run`.
+    fn print_fuzz_command(&self, run: &options::Run, target: &str) -> Result<()> {
+        let engine = LibFuzzer::new(self, &run.build);
+        let cmd = self.fuzz_command(&engine, run, target)?;
+        let (corpora, args): (Vec<_>, Vec<_>) = metadata::libfuzzer_args(cmd.get_args())
+            .into_iter()
+            .partition(|arg| !arg.starts_with('-'));
 
-        // Raw coverage data will be saved in `coverage/<target>` directory.
-        let corpus_dir_name = corpus_dir
-            .file_name()
-            .and_then(|x| x.to_str())
-            .with_context(|| format!("Invalid corpus directory: {:?}", corpus_dir))?;
-        cmd.env(
-            "LLVM_PROFILE_FILE",
-            coverage_dir.join(format!("default-{}.profraw", corpus_dir_name)),
+        println!("Target:    {}", target);
+        println!("Engine:    {}", run.build.cargo_options.engine);
+        println!(
+            "Binary:    {}",
+            self.target_binary(&run.build, target)?.display()
         );
-        cmd.arg("-merge=1");
-        let dummy_corpus = tempfile::tempdir()?;
-        cmd.arg(dummy_corpus.path());
-        cmd.arg(corpus_dir);
+        println!("Command:   {:?}", cmd);
+        if let Some(secs) = self.time_limit(run, target, run.slice)? {
+            println!("Stopped:   after {}s, merging the inputs in flight", secs);
+        }
+        println!("Environment:");
+        for (key, value) in cmd.get_envs() {
+            if let Some(value) = value {
+                println!("    {}={}", key.to_string_lossy(), value.to_string_lossy());
+            }
+        }
+        println!("libFuzzer arguments:");
+        for arg in &args {
+            println!("    {}", arg);
+        }
+        println!("Corpus directories:");
+        for corpus in &corpora {
+            println!("    {}", corpus);
+        }
+        println!();
+        Ok(())
+    }
 
-        for arg in &coverage.args {
+    /// How many seconds fuzzing `target` as `run` says lasts, if limited:
+    /// `--max-total-time`, the `slice` of `--all`, or else libFuzzer's
+    /// `-max_total_time`. The run stops as on SIGTERM once they elapse rather
+    /// than have libFuzzer exit and drop the inputs in flight.
+    fn time_limit(
+        &self,
+        run: &options::Run,
+        target: &str,
+        slice: Option<u64>,
+    ) -> Result<Option<u64>> {
+        if let Some(secs) = run.max_total_time.or(slice) {
+            return Ok(Some(secs));
+        }
+        let declared = self.fuzz_target(target)?.declared.unwrap_or_default();
+        // libFuzzer takes the last value of a flag, and 0 for no limit.
+        Ok(declared
+            .args
+            .iter()
+            .chain(&run.args)
+            .rev()
+            .find_map(|arg| arg.strip_prefix(MAX_TOTAL_TIME_FLAG))
+            .and_then(|secs| secs.parse().ok())
+            .filter(|secs| *secs > 0))
+    }
+
+    /// The command running libFuzzer on `target` as `run` says, with no time
+    /// limit: [`Self::time_limit`] is enforced by the run.
+    fn fuzz_command(
+        &self,
+        engine: &LibFuzzer,
+        run: &options::Run,
+        target: &str,
+    ) -> Result<Command> {
+        let mut cmd = engine.command(target)?;
+        let declared = self.fuzz_target(target)?.declared.unwrap_or_default();
+        let untimed = |arg: &&String| !arg.starts_with(MAX_TOTAL_TIME_FLAG);
+        // libFuzzer takes the last value of a flag, the command line wins.
+        cmd.args(declared.max_len_arg())
+            .args(declared.args.iter().filter(untimed));
+        if run.build.cargo_options.engine == Engine::Libfuzzer {
+            let given = [declared.args.clone(), run.args.clone()].concat();
+            if !run.fresh_schedule {
+                let state = self.scheduling_dir_for(target).join(SCHEDULING_STATE_FILE);
+                let reused = SchedulingState::load(&state)?.args(&given);
+                if !reused.is_empty() {
+                    info!(
+                        "Reusing the schedule earlier runs of {} learned: {}",
+                        target,
+                        reused.join(" ")
+                    );
+                }
+                cmd.args(reused);
+            }
+        }
+        for arg in run.args.iter().filter(untimed) {
             cmd.arg(arg);
         }
 
-        Ok((cmd, dummy_corpus))
+        if !run.corpus.is_empty() {
+            for corpus in &run.corpus {
+                cmd.arg(corpus);
+            }
+        } else {
+            cmd.arg(self.corpus_for(target)?);
+        }
+        // New inputs only go to the first directory, the seeds are left alone.
+        for seeds in &declared.seeds {
+            cmd.arg(self.fuzz_dir().join(seeds));
+        }
+
+        if run.jobs != 1 {
+            cmd.arg(format!("-fork={}", run.jobs));
+        }
+
+        if run.snapshot {
+            cmd.env("MOVE_FUZZ_SNAPSHOT", "1");
+        }
+
+        if let Some(ms) = run.hunt_timeouts {
+            cmd.env("MOVE_FUZZ_HUNT_TIMEOUT_MS", ms.to_string());
+        }
+
+        // The target runs from the package root, paths are made absolute.
+        if let Some(state) = &run.state {
+            let state = env::current_dir()?.join(state);
+            if !state.is_file() {
+                bail!("state snapshot {} does not exist", state.display());
+            }
+            cmd.env("MOVE_FUZZ_STATE", state);
+        }
+        if let Some(save_state) = &run.save_state {
+            cmd.env("MOVE_FUZZ_SAVE_STATE", env::current_dir()?.join(save_state));
+        }
+
+        if !run.bytecode_versions.is_empty() {
+            let versions: Vec<_> = run.bytecode_versions.iter().map(u32::to_string).collect();
+            cmd.env("MOVE_FUZZ_BYTECODE_VERSIONS", versions.join(","));
+        }
+
+        if run.paranoid {
+            cmd.env("MOVE_FUZZ_PARANOID", "1");
+        }
+
+        if run.check_specs {
+            cmd.env("MOVE_FUZZ_CHECK_SPECS", "1");
+        }
+
+        if let Some(limits) = run.verifier_limits {
+            cmd.env(
+                "MOVE_FUZZ_VERIFIER_LIMITS",
+                match limits {
+                    options::VerifierLimits::Production => "production",
+                    options::VerifierLimits::Unbounded => "unbounded",
+                    options::VerifierLimits::Compare => "compare",
+                },
+            );
+        }
+        if let Some(units) = run.verifier_meter_units {
+            cmd.env("MOVE_FUZZ_VERIFIER_METER_UNITS", units.to_string());
+        }
+        if let Some(back_edges) = run.verifier_back_edges {
+            cmd.env("MOVE_FUZZ_VERIFIER_BACK_EDGES", back_edges.to_string());
+        }
+
+        for mutator in &run.libafl_mutators {
+            cmd.arg(match mutator {
+                options::LibaflMutator::Grimoire => "-grimoire=1",
+                options::LibaflMutator::Unicode => "-unicode=1",
+            });
+        }
+        if run.libafl_tui {
+            cmd.arg("-tui=1");
+        }
+        Ok(cmd)
     }
 
-    fn merge_coverage(
+    /// Re-runs `artifact` with Move VM tracing enabled and returns the path of the trace
+    ///
+    /// The VM only traces when built with debug assertions, so they are forced on.
+    fn trace_artifact(
         &self,
-        profdata_bin_path: &Path,
-        profdata_raw_path: &Path,
-        profdata_out_path: &Path,
-    ) -> Result<()> {
-        let mut merge_cmd = Command::new(profdata_bin_path);
-        merge_cmd.arg("merge").arg("-sparse");
-        merge_cmd.arg(profdata_raw_path);
-        merge_cmd.arg("-o").arg(profdata_out_path);
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+    ) -> Result<PathBuf> {
+        let traces_dir = self.artifacts_for(target)?.join("traces");
+        fs::create_dir_all(&traces_dir)
+            .with_context(|| format!("failed to create directory {}", traces_dir.display()))?;
+        let file_name = artifact
+            .file_name()
+            .with_context(|| format!("invalid artifact path {}", artifact.display()))?;
+        let mut trace = traces_dir.join(file_name);
+        trace.set_extension("trace");
+        // The VM appends to the trace file.
+        if trace.exists() {
+            fs::remove_file(&trace)
+                .with_context(|| format!("failed to remove {}", trace.display()))?;
+        }
+        let trace = env::current_dir()?.join(trace);
+
+        let mut build = build.clone();
+        build.cargo_options.debug_assertions = true;
+        self.exec_build(BuildMode::Build, &build, Some(target))?;
+        let mut cmd = self.cargo_run(&build, target)?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .env("MOVE_VM_TRACE", &trace)
+            .arg(artifact);
+        cmd.status()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        if !trace.exists() {
+            bail!("the harness did not execute any Move instruction");
+        }
+        Ok(trace)
+    }
+
+    /// Re-runs a slow `artifact` on its own, under `perf record` when it is available,
+    /// and returns how long it took and where the profile was saved
+    fn profile_artifact(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+    ) -> Result<(Option<u64>, Option<PathBuf>)> {
+        let mut cmd = self.cargo_run(build, target)?;
+        let mut perf_data = None;
+        if command_output(Command::new("perf").arg("--version")).is_some() {
+            let profiles_dir = self.artifacts_for(target)?.join("profiles");
+            fs::create_dir_all(&profiles_dir).with_context(|| {
+                format!("failed to create directory {}", profiles_dir.display())
+            })?;
+            let file_name = artifact
+                .file_name()
+                .with_context(|| format!("invalid artifact path {}", artifact.display()))?;
+            let mut path = profiles_dir.join(file_name);
+            path.set_extension("perf.data");
+
+            let mut perf = Command::new("perf");
+            perf.args(["record", "--call-graph", "dwarf", "--quiet", "-o"])
+                .arg(&path)
+                .arg("--")
+                .arg(cmd.get_program())
+                .args(cmd.get_args());
+            for (key, value) in cmd.get_envs() {
+                match value {
+                    Some(value) => perf.env(key, value),
+                    None => perf.env_remove(key),
+                };
+            }
+            cmd = perf;
+            perf_data = Some(path);
+        }
+        cmd.stdin(Stdio::null()).arg(artifact);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        let elapsed_ms = metadata::executed_ms(&String::from_utf8_lossy(&output.stderr));
+        Ok((elapsed_ms, perf_data.filter(|path| path.exists())))
+    }
+
+    /// Runs `artifact` once more and extracts the signature of the crash it causes
+    fn reproduce_signature(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+    ) -> Result<String> {
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.stdin(Stdio::null()).arg(artifact);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        Ok(crash_signature(&String::from_utf8_lossy(&output.stderr))
+            .unwrap_or_else(|| format!("exit status {}", output.status)))
+    }
+
+    /// Runs `artifact` `times` times, returning the signature of the crash of
+    /// each run, `None` for the runs that did not crash.
+    fn reproduce_runs(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        args: &[String],
+        artifact: &Path,
+        times: u32,
+    ) -> Result<Vec<Option<String>>> {
+        Ok(self
+            .reproduce_crashes(build, target, args, artifact, times)?
+            .into_iter()
+            .map(|crash| crash.map(|(signature, _)| signature))
+            .collect())
+    }
+
+    /// Like `reproduce_runs`, with the stderr of each crash after its
+    /// signature.
+    fn reproduce_crashes(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        args: &[String],
+        artifact: &Path,
+        times: u32,
+    ) -> Result<Vec<Option<(String, String)>>> {
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.stdin(Stdio::null()).args(args).arg(artifact);
+        (0..times)
+            .map(|_| {
+                let output = cmd
+                    .output()
+                    .with_context(|| format!("failed to run command: {:?}", cmd))?;
+                Ok((!output.status.success()).then(|| {
+                    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+                    let signature = crash_signature(&stderr)
+                        .unwrap_or_else(|| format!("exit status {}", output.status));
+                    (signature, stderr)
+                }))
+            })
+            .collect()
+    }
+
+    pub fn exec_repro(&self, repro: &options::Repro) -> Result<()> {
+        self.ensure_target_exists(&repro.target)?;
+        if repro.heap_profile {
+            return self.repro_heap_profile(repro);
+        }
+        self.exec_build(BuildMode::Build, &repro.build, Some(&repro.target))?;
+        if repro.times > 1 {
+            return self.repro_times(repro);
+        }
+
+        let mut cmd = match repro.record {
+            None => self.cargo_run(&repro.build, &repro.target)?,
+            Some(options::Recorder::Rr) => {
+                let file_name = repro
+                    .artifact
+                    .file_name()
+                    .with_context(|| format!("invalid input path {}", repro.artifact.display()))?;
+                let trace_dir = repro
+                    .artifact
+                    .with_file_name(format!("{}.rr", file_name.to_string_lossy()));
+                if trace_dir.exists() {
+                    fs::remove_dir_all(&trace_dir)
+                        .with_context(|| format!("failed to remove {}", trace_dir.display()))?;
+                }
+                // Record the fuzz target itself rather than `cargo run`.
+                let mut cmd = Command::new("rr");
+                cmd.arg("record")
+                    .arg("--output-trace-dir")
+                    .arg(&trace_dir)
+                    .arg(self.target_binary(&repro.build, &repro.target)?);
+                self.sanitizer_options(&mut cmd, &repro.build, Some(&repro.target))?;
+                self.target_env(&mut cmd, &repro.target)?;
+                info!(
+                    "Recording into {}, replay with:\n\n\trr replay {}\n",
+                    strip_current_dir_prefix(&trace_dir).display(),
+                    strip_current_dir_prefix(&trace_dir).display()
+                );
+                cmd
+            }
+        };
+        cmd.args(&repro.args).arg(&repro.artifact);
+
+        let status = logging::status(&mut cmd, Source::LibFuzzer)?;
+        if status.success() {
+            println!("{} does not reproduce a failure", repro.artifact.display());
+            Ok(())
+        } else {
+            info!("Fuzz target exited with {}", status);
+            if repro.view_bytecode {
+                if let Err(e) = self.view_bytecode(&repro.build, &repro.target, &repro.artifact) {
+                    warn!("could not show the bytecode: {:#}", e);
+                }
+            }
+            Err(Error::crash(&repro.target, vec![repro.artifact.clone()]).into())
+        }
+    }
+
+    /// Bisects the history of the package for the commit that made an input
+    /// crash, rebuilding the target at each revision.
+    pub fn exec_bisect(&self, bisect: &options::Bisect) -> Result<()> {
+        self.ensure_target_exists(&bisect.target)?;
+        let package_dir = self
+            .package_dir()
+            .context("could not find the directory of the package")?;
+        // The artifact may not be part of every revision.
+        let tmp = tempfile::tempdir().context("failed to create temp dir")?;
+        let artifact = tmp.path().join("input");
+        fs::copy(&bisect.artifact, &artifact)
+            .with_context(|| format!("failed to copy {}", bisect.artifact.display()))?;
+        // Another crash on the way can hide the one being bisected.
+        let expected = ArtifactMetadata::load(&bisect.artifact)
+            .ok()
+            .and_then(|meta| crash_signature(&meta.sanitizer_output));
+        if expected.is_none() {
+            warn!(
+                "{} has no metadata telling how it crashed, any crash counts as reproducing it",
+                bisect.artifact.display()
+            );
+        }
+
+        let outcome = bisect::run(&package_dir, &bisect.good, &bisect.bad, |commit| {
+            info!("Testing {}", &commit[..12]);
+            if let Err(e) = self.exec_build(BuildMode::Build, &bisect.build, Some(&bisect.target)) {
+                warn!(
+                    "skipping {}, the target does not build: {:#}",
+                    &commit[..12],
+                    e
+                );
+                return Ok(Verdict::Skip);
+            }
+            let runs =
+                self.reproduce_runs(&bisect.build, &bisect.target, &bisect.args, &artifact, 1)?;
+            let signature = runs.into_iter().next().flatten();
+            match &signature {
+                Some(signature) => info!("{} crashes: {}", &commit[..12], signature),
+                None => info!("{} does not crash", &commit[..12]),
+            }
+            let verdict = bisect_verdict(expected.as_deref(), signature.as_deref());
+            if verdict == Verdict::Skip {
+                warn!("skipping {}, it crashes differently", &commit[..12]);
+            }
+            Ok(verdict)
+        })?;
+        match outcome {
+            bisect::Outcome::FirstBad(commit) => {
+                println!(
+                    "The first commit crashing on {}:",
+                    bisect.artifact.display()
+                );
+                println!("{}", commit);
+            }
+            bisect::Outcome::Inconclusive(commits) => {
+                println!(
+                    "The target does not build at the commits that may have introduced the \
+                     crash on {}:",
+                    bisect.artifact.display()
+                );
+                for commit in commits {
+                    println!("{}", commit);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs an input under DHAT's allocator and reports its peak heap usage and
+    /// the sites allocating the most, next to the input.
+    fn repro_heap_profile(&self, repro: &options::Repro) -> Result<()> {
+        // The profile counts allocations through its own global allocator,
+        // which cannot coexist with one the harness sets.
+        let source = self.fuzz_target(&repro.target)?.source;
+        let harness = fs::read_to_string(&source)
+            .with_context(|| format!("failed to read {}", source.display()))?;
+        if harness.contains("#[global_allocator]") {
+            bail!(
+                "{} sets its own global allocator, which the heap profile would have to \
+                 replace; profile a copy of the harness without it",
+                source.display()
+            );
+        }
+        let mut build = repro.build.clone();
+        build.cargo_options.features = Some(match build.cargo_options.features.take() {
+            Some(features) => format!("{},libfuzzer/heap-profile", features),
+            None => "libfuzzer/heap-profile".to_owned(),
+        });
+        // As for coverage, the profiled binary is kept apart from the one fuzzing.
+        if build.target_dir.is_none() {
+            let base = match self.target_dir(&repro.build)? {
+                Some(dir) => dir,
+                None => env::current_dir()?.join("target"),
+            };
+            let dir = base.join(default_target()).join("heap-profile");
+            build.target_dir = Some(dir.to_string_lossy().into_owned());
+        }
+        self.exec_build(BuildMode::Build, &build, Some(&repro.target))?;
+
+        let file_name = repro
+            .artifact
+            .file_name()
+            .with_context(|| format!("invalid input path {}", repro.artifact.display()))?
+            .to_string_lossy();
+        let profiles_dir = self.fuzz_dir().join(HEAP_PROFILES_DIR).join(&repro.target);
+        fs::create_dir_all(&profiles_dir)
+            .with_context(|| format!("failed to create directory {}", profiles_dir.display()))?;
+        let profile_path = profiles_dir.join(format!("{}.dhat.json", file_name));
+        let report_path = profiles_dir.join(format!("{}.heap.txt", file_name));
+        if profile_path.exists() {
+            fs::remove_file(&profile_path)
+                .with_context(|| format!("failed to remove {}", profile_path.display()))?;
+        }
+
+        let mut cmd = self.cargo_run(&build, &repro.target)?;
+        // libFuzzer exits without running `atexit` handlers on reaching a limit.
+        cmd.arg("-rss_limit_mb=0")
+            .arg("-malloc_limit_mb=0")
+            .args(&repro.args)
+            .arg(&repro.artifact)
+            .env("MOVE_FUZZ_HEAP_PROFILE_PATH", &profile_path);
+        let status = logging::status(&mut cmd, Source::LibFuzzer)?;
+
+        let json = fs::read_to_string(&profile_path).with_context(|| {
+            format!(
+                "the fuzz target wrote no heap profile to {}",
+                profile_path.display()
+            )
+        })?;
+        let report = HeapReport::parse(&json)?.render(HEAP_PROFILE_SITES);
+        fs::write(&report_path, &report)
+            .with_context(|| format!("failed to write {}", report_path.display()))?;
+        println!("{}", report);
+        info!(
+            "Heap report written to {}, the full profile to {}, which DHAT's viewer \
+             (dh_view.html) opens",
+            strip_current_dir_prefix(&report_path).display(),
+            strip_current_dir_prefix(&profile_path).display()
+        );
+        if status.success() {
+            Ok(())
+        } else {
+            info!("Fuzz target exited with {}", status);
+            Err(Error::crash(&repro.target, vec![repro.artifact.clone()]).into())
+        }
+    }
+
+    /// Reproduces an input `repro --times` times and reports how it failed.
+    fn repro_times(&self, repro: &options::Repro) -> Result<()> {
+        let runs = self.reproduce_runs(
+            &repro.build,
+            &repro.target,
+            &repro.args,
+            &repro.artifact,
+            repro.times,
+        )?;
+        let Some((_, stability)) = Stability::measure(&runs) else {
+            println!(
+                "{} does not reproduce a failure in {} runs",
+                repro.artifact.display(),
+                repro.times
+            );
+            return Ok(());
+        };
+        let crashes = runs.iter().flatten().count();
+        println!(
+            "{} failed in {} of {} runs ({:.0}%):\n",
+            repro.artifact.display(),
+            crashes,
+            repro.times,
+            100.0 * crashes as f64 / f64::from(repro.times)
+        );
+        let mut signatures: Vec<(&str, usize)> = Vec::new();
+        for signature in runs.iter().flatten() {
+            match signatures.iter_mut().find(|(s, _)| s == signature) {
+                Some((_, count)) => *count += 1,
+                None => signatures.push((signature, 1)),
+            }
+        }
+        for (signature, count) in &signatures {
+            println!("  {:>5}  {}", count, signature);
+        }
+        if stability.is_flaky() {
+            warn!(
+                "the failure is nondeterministic, it may depend on uninitialized state, \
+                address randomization or timing"
+            );
+        }
+        if repro.view_bytecode {
+            if let Err(e) = self.view_bytecode(&repro.build, &repro.target, &repro.artifact) {
+                warn!("could not show the bytecode: {:#}", e);
+            }
+        }
+        Err(Error::crash(&repro.target, vec![repro.artifact.clone()]).into())
+    }
+
+    /// Prints the code of the Move function `artifact` fails in, with the
+    /// failing instruction marked, from the VM trace of it.
+    fn view_bytecode(&self, build: &BuildOptions, target: &str, artifact: &Path) -> Result<()> {
+        let trace = self.trace_artifact(build, target, artifact)?;
+        let trace = fs::read_to_string(&trace)
+            .with_context(|| format!("failed to read {}", trace.display()))?;
+        let location = bytecode_view::last_location(&trace)
+            .context("the trace of the input has no instruction")?;
+
+        let build_dir = modules::build_dir(self.fuzz_dir(), &move_package_name(self.fuzz_dir())?);
+        let view = match location.module_id() {
+            Some((address, name)) => {
+                // The module is of the fuzz package or of one of its dependencies.
+                let mut view = None;
+                for path in modules::module_files(&build_dir, true)? {
+                    let module = modules::read_module(&path)?;
+                    if *module.address() == address && module.name().as_str() == name {
+                        view = bytecode_view::annotate_module(&module, &location);
+                        break;
+                    }
+                }
+                view.with_context(|| {
+                    format!(
+                        "the build of the fuzz package has no function {}::{}",
+                        name, location.function
+                    )
+                })?
+            }
+            None => {
+                let path = build_dir
+                    .join("bytecode_scripts")
+                    .join(format!("{}.mv", location.function));
+                bytecode_view::annotate_script(&modules::read_script(&path)?, &location)
+            }
+        };
+        println!("The Move VM stopped in {}\n\n{}", location, view);
+        Ok(())
+    }
+
+    /// Path of the fuzz target binary produced by `exec_build`
+    fn target_binary(&self, build: &BuildOptions, target: &str) -> Result<PathBuf> {
+        let build = &self.build_for(build, target)?;
+        let target_dir = self
+            .target_dir(build)?
+            .unwrap_or_else(|| self.fuzz_dir().join("target"));
+        let profile_subdir = if build.dev { "debug" } else { "release" };
+        Ok(target_dir
+            .join(&build.cargo_options.triple)
+            .join(profile_subdir)
+            .join(format!(
+                "{}{}",
+                target,
+                exe_suffix(&build.cargo_options.triple)
+            )))
+    }
+
+    /// Minimizes `module`, a crashing input holding a compiled Move module, by
+    /// delta debugging: the target writes the reductions of the module, one
+    /// step smaller each, and the first to still crash the target replaces it,
+    /// until none does or `tmin.runs` reductions were tried. Byte-level
+    /// minimization rarely keeps a module well-formed.
+    pub fn exec_tmin_module(&self, tmin: &options::Tmin, module: &Path) -> Result<()> {
+        check_engine_args(&tmin.build, &tmin.args, &["artifact_prefix"]);
+        self.exec_build(BuildMode::Build, &tmin.build, Some(&tmin.target))?;
+        let engine = LibFuzzer::new(self, &tmin.build);
+        let original =
+            fs::read(module).with_context(|| format!("failed to read {}", module.display()))?;
+        let work = tempfile::tempdir().context("failed to create temp dir")?;
+        if !self.crashes(&engine, tmin, work.path(), module)? {
+            bail!("{} does not crash {}", module.display(), tmin.target);
+        }
+
+        let mut current = original.clone();
+        let mut runs = 0;
+        let progress = self
+            .progress
+            .start(Task::Tmin, Some(&tmin.target), Some(tmin.runs.into()));
+        'reduce: loop {
+            for candidate in self.module_reductions(&engine, &tmin.target, work.path(), &current)? {
+                if runs == tmin.runs {
+                    break 'reduce;
+                }
+                runs += 1;
+                progress.step();
+                if self.crashes(&engine, tmin, work.path(), &candidate)? {
+                    current = fs::read(&candidate)
+                        .with_context(|| format!("failed to read {}", candidate.display()))?;
+                    info!("reduced to {} bytes", current.len());
+                    continue 'reduce;
+                }
+            }
+            break;
+        }
+        drop(progress);
+
+        let artifact = self.artifacts_for(&tmin.target)?.join(format!(
+            "minimized-from-{}",
+            crate::corpus::content_hash(&original)
+        ));
+        fs::write(&artifact, &current)
+            .with_context(|| format!("failed to write to {}", artifact.display()))?;
+        info!("\n{:─<80}\n", "");
+        info!(
+            "Minimized module from {} to {} bytes in {} runs:\n\n\t{}\n",
+            original.len(),
+            current.len(),
+            runs,
+            strip_current_dir_prefix(&artifact).display()
+        );
+        Ok(())
+    }
+
+    /// The reductions of the module `bytes`, written by the target to `dir` in
+    /// the order to try them.
+    fn module_reductions(
+        &self,
+        engine: &LibFuzzer,
+        target: &str,
+        dir: &Path,
+        bytes: &[u8],
+    ) -> Result<Vec<PathBuf>> {
+        for entry in fs::read_dir(dir)? {
+            fs::remove_file(entry?.path())?;
+        }
+        fs::write(dir.join("module.mv"), bytes)?;
+        let mut cmd = engine.command(target)?;
+        cmd.env("MOVE_FUZZ_REDUCE_MODULE", dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let status = cmd.status().map_err(|e| error::spawn_failed(&cmd, e))?;
+        if !status.success() {
+            bail!("{} could not reduce the module: {}", target, status);
+        }
+
+        let mut reductions = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let number = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<usize>().ok());
+            if let Some(number) = number {
+                reductions.push((number, path));
+            }
+        }
+        reductions.sort();
+        Ok(reductions.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Whether running `input` alone crashes the target of `tmin`, writing the
+    /// crash artifact to `dir` rather than among those of the target.
+    fn crashes(
+        &self,
+        engine: &LibFuzzer,
+        tmin: &options::Tmin,
+        dir: &Path,
+        input: &Path,
+    ) -> Result<bool> {
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(dir.join(""));
+        let mut cmd = engine.command(&tmin.target)?;
+        cmd.arg(artifact_arg)
+            .args(&tmin.args)
+            .arg(input)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let status = cmd.status().map_err(|e| error::spawn_failed(&cmd, e))?;
+        Ok(!status.success())
+    }
+
+    /// The libFuzzer command minimizing a crashing input of `target` in `runs`
+    /// attempts, to which the caller adds the input and its own arguments.
+    fn minimize_command(
+        &self,
+        engine: &LibFuzzer,
+        target: &str,
+        runs: u32,
+        hunt_timeouts: Option<u64>,
+    ) -> Result<Command> {
+        let mut cmd = engine.command(target)?;
+        if let Some(ms) = hunt_timeouts {
+            // The harness panics on inputs over the threshold, so libFuzzer
+            // keeps shrinking the input for as long as it stays that slow.
+            cmd.env("MOVE_FUZZ_HUNT_TIMEOUT_MS", ms.to_string());
+        }
+        cmd.arg("-minimize_crash=1")
+            .arg(format!("-runs={}", runs))
+            .args(self.max_len_arg(target)?);
+        Ok(cmd)
+    }
+
+    /// Runs the minimization `cmd` of `target`, showing its progress.
+    fn run_minimize(&self, engine: &LibFuzzer, target: &str, cmd: &mut Command) -> Result<Outcome> {
+        // libFuzzer tries smaller inputs until none crashes, so there is no
+        // telling how many attempts it takes.
+        let progress = self.progress.start(Task::Tmin, Some(target), None);
+        engine.run_with(target, cmd, &mut |line| {
+            if line.starts_with("CRASH_MIN: minimizing crash input") {
+                progress.step();
+            }
+        })
+    }
+
+    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
+        let set = ["artifact_prefix", "minimize_crash", "runs"];
+        check_engine_args(&tmin.build, &tmin.args, &set);
+        self.exec_build(BuildMode::Build, &tmin.build, Some(&tmin.target))?;
+        let engine = LibFuzzer::new(self, &tmin.build);
+        let mut cmd =
+            self.minimize_command(&engine, &tmin.target, tmin.runs, tmin.hunt_timeouts)?;
+        cmd.arg(tmin.input()).args(&tmin.args);
+        let Outcome {
+            status, artifacts, ..
+        } = self.run_minimize(&engine, &tmin.target, &mut cmd)?;
+        if !status.success() {
+            info!("\n{:─<80}\n", "");
+            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status)).with_context(|| {
+                "Test case minimization failed.\n\
+                 \n\
+                 Usually this isn't a hard error, and just means that libfuzzer\n\
+                 doesn't know how to minimize the test case any further while\n\
+                 still reproducing the original crash.\n\
+                 \n\
+                 See the logs above for details."
+            });
+        }
+
+        // Find and display the most recently modified artifact, which is
+        // presumably the result of minification. Yeah, this is a little hacky,
+        // but it seems to work. I don't want to parse libfuzzer's stderr output
+        // and hope it never changes.
+        let minimized_artifact = artifacts.into_iter().max_by_key(|a| {
+            a.metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(time::SystemTime::UNIX_EPOCH)
+        });
+
+        if let Some(artifact) = minimized_artifact {
+            let artifact = strip_current_dir_prefix(&artifact);
+
+            info!("\n{:─<80}\n", "");
+            info!("Minimized artifact:\n\n\t{}\n", artifact.display());
+
+            // Note: ignore errors when running the debug formatter. This most
+            // likely just means that we're dealing with a fuzz target that uses
+            // an older version of the libfuzzer crate, and doesn't support
+            // `RUST_LIBFUZZER_DEBUG_PATH`.
+            if let Ok(debug) =
+                self.run_fuzz_target_debug_formatter(&tmin.build, &tmin.target, artifact)
+            {
+                info!("Output of `std::fmt::Debug`:\n");
+                for l in debug.lines() {
+                    info!("\t{}", l);
+                }
+                info!("");
+            }
+
+            let fuzz_dir = if self.fuzz_dir_is_default_path() {
+                String::new()
+            } else {
+                format!(
+                    " --fuzz-dir {}",
+                    quote_arg(&self.fuzz_dir().to_string_lossy())
+                )
+            };
+
+            info!(
+                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
+                fuzz_dir = &fuzz_dir,
+                options = &tmin.build,
+                target = &tmin.target,
+                artifact = quote_arg(&artifact.to_string_lossy())
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Evicts what is beyond `quota` from the corpus, artifacts and coverage
+    /// history of `target`, measuring the features of its corpus entries with
+    /// its binary built with `build` if they are evicted by those.
+    fn apply_quota(&self, quota: &Quota, build: &BuildOptions, target: &str) -> Result<()> {
+        let mut done = Vec::new();
+        if let Some(max_size) = quota.max_corpus_size {
+            let corpus_dir = self.corpus_for(target)?;
+            let entries = corpus::entries(&corpus_dir)?;
+            let total: u64 = entries.iter().map(|entry| entry.size).sum();
+            if total > max_size {
+                let features = match quota.eviction {
+                    Eviction::Lru => HashMap::new(),
+                    Eviction::LeastFeatures => self
+                        .feature_counts(build, target, &corpus_dir)
+                        .unwrap_or_else(|e| {
+                            warn!(
+                                "could not measure the features of the corpus of {}, \
+                                 evicting the oldest entries: {:#}",
+                                target, e
+                            );
+                            HashMap::new()
+                        }),
+                };
+                let candidates = entries
+                    .into_iter()
+                    .map(|entry| {
+                        let modified = fs::metadata(&entry.path)
+                            .and_then(|metadata| metadata.modified())
+                            .with_context(|| {
+                                format!("failed to read the metadata of {}", entry.path.display())
+                            })?;
+                        Ok(quota::Candidate {
+                            features: entry
+                                .path
+                                .file_name()
+                                .and_then(|name| features.get(name))
+                                .copied(),
+                            path: entry.path,
+                            size: entry.size,
+                            modified,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let evicted = quota::evict(&candidates, max_size, quota.eviction);
+                let mut freed = 0;
+                for candidate in candidates.iter().filter(|c| evicted.contains(&c.path)) {
+                    fs::remove_file(&candidate.path).with_context(|| {
+                        format!("failed to remove {}", candidate.path.display())
+                    })?;
+                    freed += candidate.size;
+                }
+                done.push(format!(
+                    "evicted {} corpus entries ({})",
+                    evicted.len(),
+                    corpus::human_size(freed)
+                ));
+            }
+        }
+
+        if let Some(max) = quota.max_artifacts_per_bucket {
+            let path = self.triage_db_path();
+            let mut db = CrashDatabase::load(&path)?;
+            let mut removed = 0;
+            for bucket in db.buckets.iter_mut().filter(|b| b.target == target) {
+                let artifacts: Vec<_> = bucket
+                    .artifacts
+                    .iter()
+                    .filter_map(|artifact| {
+                        let modified = fs::metadata(artifact).ok()?.modified().ok()?;
+                        Some((artifact.clone(), modified))
+                    })
+                    .collect();
+                for artifact in quota::excess_artifacts(&artifacts, max) {
+                    fs::remove_file(&artifact)
+                        .with_context(|| format!("failed to remove {}", artifact.display()))?;
+                    let sidecar = metadata::sidecar_path(&artifact);
+                    if sidecar.exists() {
+                        fs::remove_file(&sidecar)
+                            .with_context(|| format!("failed to remove {}", sidecar.display()))?;
+                    }
+                    bucket.artifacts.retain(|a| *a != artifact);
+                    removed += 1;
+                }
+            }
+            if removed > 0 {
+                db.save(&path)?;
+                done.push(format!("removed {} artifacts", removed));
+            }
+        }
+
+        if let Some(max) = quota.max_coverage_history {
+            let (_, profdata) = self.coverage_for(target)?;
+            let history = profdata.with_file_name(COVERAGE_HISTORY_FILE);
+            let dropped = CoverageSnapshot::keep_latest(&history, max)?;
+            if dropped > 0 {
+                done.push(format!("dropped {} coverage snapshots", dropped));
+            }
+        }
+
+        if !done.is_empty() {
+            info!("Quota of {}: {}", target, done.join(", "));
+        }
+        Ok(())
+    }
+
+    /// The number of coverage features each entry of the corpus in `corpus_dir`
+    /// adds, by file name, from the feature sets `target` dumps while loading
+    /// the corpus, which leaves it as it is.
+    fn feature_counts(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        corpus_dir: &Path,
+    ) -> Result<HashMap<ffi::OsString, usize>> {
+        self.exec_build(BuildMode::Build, build, Some(target))?;
+        let engine = LibFuzzer::new(self, build);
+        let features = tempfile::TempDir::new_in(self.fuzz_dir())?;
+        let mut cmd = engine.command(target)?;
+        cmd.args(self.max_len_arg(target)?)
+            .arg("-runs=0")
+            .arg(format!("-features_dir={}", features.path().display()))
+            .arg(corpus_dir);
+        let outcome = engine.run(target, &mut cmd)?;
+        if !outcome.status.success() {
+            bail!(
+                "loading the corpus of {} failed: {}",
+                target,
+                outcome.status
+            );
+        }
+        let entries: Vec<_> = corpus::entries(corpus_dir)?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        Ok(quota::dumped_features(features.path(), &entries)?
+            .into_iter()
+            .filter_map(|(path, features)| Some((path.file_name()?.to_owned(), features)))
+            .collect())
+    }
+
+    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
+        check_engine_args(&cmin.build, &cmin.args, &["artifact_prefix", "merge"]);
+        let _lock = Lock::acquire(self.fuzz_dir(), lock::Scope::Project, &cmin.lock)?;
+        events::logged(
+            &self.events_log_path(),
+            EventKind::Cmin,
+            Some(cmin.target()),
+            || self.minimize_corpus(cmin),
+        )
+    }
+
+    fn minimize_corpus(&self, cmin: &options::Cmin) -> Result<()> {
+        self.exec_build(BuildMode::Build, &cmin.build, Some(cmin.target()))?;
+        let engine = LibFuzzer::new(self, &cmin.build);
+
+        let corpus = if let Some(corpus) = cmin.corpus.clone() {
+            corpus
+        } else {
+            self.corpus_for(cmin.target())?
+        };
+        let corpus = corpus
+            .to_str()
+            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
+            .to_owned();
+
+        let tmp = tempfile::TempDir::new_in(self.fuzz_dir())?;
+        let tmp_corpus = tmp.path().join("corpus");
+        fs::create_dir(&tmp_corpus)?;
+
+        let mut args: Vec<_> = self.max_len_arg(cmin.target())?.into_iter().collect();
+        args.extend(cmin.args.iter().cloned());
+
+        let control = self.merge_dir_for(cmin.target()).join(CMIN_CONTROL_FILE);
+        if cmin.restart {
+            remove_file_if_exists(&control)?;
+        }
+
+        let inputs = files_in(Path::new(&corpus))?.len() as u64;
+        let progress = self
+            .progress
+            .start(Task::Cmin, Some(cmin.target()), Some(inputs));
+        let merged = self.resumable_merge(
+            &engine,
+            cmin.target(),
+            &tmp_corpus,
+            &[PathBuf::from(&corpus)],
+            &control,
+            &args,
+            &mut |runs| progress.set(runs.min(inputs)),
+        );
+        progress.set(inputs);
+        match merged {
+            Ok(()) => {
+                // move corpus directory into tmp to auto delete it
+                fs::rename(&corpus, tmp.path().join("old"))?;
+                fs::rename(tmp.path().join("corpus"), &corpus)?;
+                if Path::new(&corpus) == self.corpus_root().join(cmin.target()) {
+                    self.prune_provenance(cmin.target(), Path::new(&corpus));
+                }
+            }
+            Err(e) => println!("Failed to minimize corpus: {:#}", e),
+        }
+
+        Ok(())
+    }
+
+    pub fn exec_merge(&self, merge: &options::Merge) -> Result<()> {
+        self.ensure_target_exists(&merge.target)?;
+        check_engine_args(
+            &merge.build,
+            &merge.args,
+            &["artifact_prefix", "merge", "merge_control_file"],
+        );
+        let _lock = Lock::acquire(
+            self.fuzz_dir(),
+            lock::Scope::Target(&merge.target),
+            &merge.lock,
+        )?;
+        self.exec_build(BuildMode::Build, &merge.build, Some(&merge.target))?;
+        let engine = LibFuzzer::new(self, &merge.build);
+
+        let into = match &merge.into {
+            Some(into) => {
+                fs::create_dir_all(into)
+                    .with_context(|| format!("could not make a corpus directory at {:?}", into))?;
+                into.clone()
+            }
+            None => self.corpus_for(&merge.target)?,
+        };
+        let control = match &merge.control_file {
+            Some(control) => control.clone(),
+            None => self.merge_dir_for(&merge.target).join(MERGE_CONTROL_FILE),
+        };
+        if merge.restart {
+            remove_file_if_exists(&control)?;
+        }
+        let mut args: Vec<_> = self.max_len_arg(&merge.target)?.into_iter().collect();
+        args.extend(merge.args.iter().cloned());
+
+        let before = provenance::entry_names(&into)?;
+        let mut inputs = before.len() as u64;
+        for corpus in &merge.from {
+            inputs += files_in(corpus)?.len() as u64;
+        }
+        let progress = self
+            .progress
+            .start(Task::Replay, Some(&merge.target), Some(inputs));
+        self.resumable_merge(
+            &engine,
+            &merge.target,
+            &into,
+            &merge.from,
+            &control,
+            &args,
+            &mut |runs| progress.set(runs.min(inputs)),
+        )
+        .with_context(|| format!("failed to merge into {}", into.display()))?;
+        drop(progress);
+
+        let added = files_in(&into)?.len().saturating_sub(before.len());
+        if added > 0 && into == self.corpus_root().join(&merge.target) {
+            self.record_new_entries(&merge.target, &into, &before, |name| Origin::Merged {
+                from: provenance::dir_of(&merge.from, name).map(provenance::source),
+            });
+        }
+        println!(
+            "{}: {} new inputs in {}",
+            merge.target,
+            added,
+            strip_current_dir_prefix(&into).display()
+        );
+        Ok(())
+    }
+
+    /// Merges `from` into `into` by `target`, recording the progress of
+    /// libFuzzer in `control` so that an interrupted merge of the same inputs
+    /// resumes where it stopped. `runs` counts the inputs merged before too.
+    #[allow(clippy::too_many_arguments)]
+    fn resumable_merge(
+        &self,
+        engine: &LibFuzzer,
+        target: &str,
+        into: &Path,
+        from: &[PathBuf],
+        control: &Path,
+        args: &[String],
+        runs: &mut dyn FnMut(u64),
+    ) -> Result<()> {
+        let mut resumed = 0;
+        if control.exists() {
+            let mut files = files_in(into)?;
+            for corpus in from {
+                files.extend(files_in(corpus)?);
+            }
+            let text = fs::read_to_string(control)
+                .with_context(|| format!("failed to read {}", control.display()))?;
+            match merge::Control::parse(&text) {
+                Some(progress) if progress.resumable(&files) => {
+                    info!(
+                        "Resuming the merge of {} after {} of {} inputs",
+                        target,
+                        progress.started,
+                        progress.inputs.len()
+                    );
+                    resumed = progress.started as u64;
+                }
+                _ => {
+                    info!(
+                        "Starting the merge of {} over, its inputs changed since it stopped",
+                        target
+                    );
+                    remove_file_if_exists(control)?;
+                }
+            }
+        } else if let Some(dir) = control.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+
+        let mut args = args.to_vec();
+        args.push(format!("-merge_control_file={}", control.display()));
+        engine.merge(target, into, from, &args, &mut |n| runs(resumed + n))?;
+        remove_file_if_exists(control)
+    }
+
+    /// Merge the corpora of targets sharing an input type into each other,
+    /// keeping the inputs that add coverage to the receiving target.
+    pub fn exec_distill(&self, distill: &options::Distill) -> Result<()> {
+        let targets = if distill.targets.is_empty() {
+            self.targets.clone()
+        } else {
+            for target in &distill.targets {
+                self.ensure_target_exists(target)?;
+            }
+            distill.targets.clone()
+        };
+
+        let section = self.fuzz_section()?;
+        let mut input_types = HashMap::new();
+        for target in &self.targets {
+            let path = self.rust_target_path(target);
+            let source = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let input = section.targets.get(target).and_then(|d| d.input.as_deref());
+            input_types.insert(target, input_type(&source, input));
+        }
+
+        let mut total = 0;
+        for target in &targets {
+            let input_type = match &input_types[target] {
+                Some(input_type) => input_type,
+                None => {
+                    info!("Skipping {}: could not determine its input type", target);
+                    continue;
+                }
+            };
+            let donors: Vec<_> = self
+                .targets
+                .iter()
+                .filter(|other| *other != target)
+                .filter(|other| input_types[other].as_ref() == Some(input_type))
+                .map(|other| self.corpus_root().join(other))
+                .filter(|corpus| files_in(corpus).is_ok_and(|files| !files.is_empty()))
+                .collect();
+            if donors.is_empty() {
+                info!(
+                    "Skipping {}: no other target takes `{}` inputs",
+                    target, input_type
+                );
+                continue;
+            }
+
+            self.exec_build(BuildMode::Build, &distill.build, Some(target))?;
+            let corpus = self.corpus_for(target)?;
+            let before = provenance::entry_names(&corpus)?;
+
+            let mut inputs = 0;
+            for donor in &donors {
+                inputs += files_in(donor)?.len() as u64;
+            }
+            let progress = self
+                .progress
+                .start(Task::Replay, Some(target), Some(inputs));
+            LibFuzzer::new(self, &distill.build)
+                .merge(target, &corpus, &donors, &distill.args, &mut |runs| {
+                    progress.set(runs.min(inputs))
+                })
+                .with_context(|| format!("failed to distill into {}", target))?;
+            drop(progress);
+
+            let added = files_in(&corpus)?.len().saturating_sub(before.len());
+            if added > 0 {
+                self.record_new_entries(target, &corpus, &before, |name| Origin::Distilled {
+                    from: provenance::dir_of(&donors, name).map(provenance::entry_name),
+                });
+            }
+            total += added;
+            println!(
+                "{}: {} new inputs from {}",
+                target,
+                added,
+                donors
+                    .iter()
+                    .filter_map(|corpus| corpus.file_name())
+                    .map(|name| name.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        println!("Distilled {} new inputs", total);
+        Ok(())
+    }
+
+    /// Produce coverage information for a given corpus
+    pub fn exec_coverage(&self, coverage: &options::Coverage) -> Result<()> {
+        let log = self.events_log_path();
+        events::logged(&log, EventKind::Coverage, Some(coverage.target()), || {
+            self.collect_coverage(coverage)
+        })
+    }
+
+    fn collect_coverage(&self, coverage: &options::Coverage) -> Result<()> {
+        // Build project with source-based coverage generation enabled.
+        self.exec_build(BuildMode::Build, &coverage.build, Some(coverage.target()))?;
+
+        // Retrieve corpus directories.
+        let corpora = if coverage.corpus.is_empty() {
+            vec![self.corpus_for(coverage.target())?]
+        } else {
+            coverage
+                .corpus
+                .iter()
+                .map(|name| Path::new(name).to_path_buf())
+                .collect()
+        };
+
+        // Collect the (non-directory) readable input files from the corpora.
+        let files_and_dirs = corpora.iter().flat_map(fs::read_dir).flatten().flatten();
+        let mut readable_input_files = files_and_dirs
+            .filter(|file| match file.file_type() {
+                Ok(ft) => ft.is_file(),
+                _ => false,
+            })
+            .peekable();
+        if readable_input_files.peek().is_none() {
+            bail!(
+                "The corpus does not contain program-input files. \
+                 Coverage information requires existing input files. \
+                 Try running the fuzzer first (`cargo fuzz run ...`) to generate a corpus, \
+                 or provide a nonempty corpus directory."
+            )
+        }
+
+        let (coverage_out_raw_dir, coverage_out_file) = self.coverage_for(coverage.target())?;
+        let move_coverage = match &coverage.coverage_map {
+            Some(package) => Some(self.move_coverage_paths(package, &coverage_out_file)?),
+            None => None,
+        };
+
+        let mut inputs = 0;
+        for corpus in corpora.iter() {
+            inputs += files_in(corpus)?.len() as u64;
+        }
+        let progress = self
+            .progress
+            .start(Task::Replay, Some(coverage.target()), Some(inputs));
+        let mut replayed = 0;
+        for corpus in corpora.iter() {
+            // _tmp_dir is deleted when it goes of of scope.
+            let (mut cmd, _tmp_dir) =
+                self.create_coverage_cmd(coverage, &coverage_out_raw_dir, &corpus.as_path())?;
+            if let Some((trace, map)) = &move_coverage {
+                cmd.env("MOVE_VM_TRACE", trace)
+                    .env("MOVE_FUZZ_COVERAGE_MAP", map);
+            }
+            info!("Generating coverage data for corpus {:?}", corpus);
+            let corpus_inputs = files_in(corpus)?.len() as u64;
+            let status = logging::replay_status(&mut cmd, Source::LibFuzzer, |line| {
+                if let Some(runs) = engine::parse_libfuzzer_stats(line).execs {
+                    progress.set(replayed + runs.min(corpus_inputs));
+                }
+            })?;
+            if !status.success() {
+                Err(anyhow!(
+                    "Command exited with failure status {}: {:?}",
+                    status,
+                    cmd
+                ))
+                .context("Failed to generage coverage data")?;
+            }
+            replayed += corpus_inputs;
+            progress.set(replayed);
+        }
+        drop(progress);
+
+        let mut profdata_bin_path = coverage.llvm_path.clone().unwrap_or(rustlib()?);
+        profdata_bin_path.push(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
+        let progress = self
+            .progress
+            .start(Task::CoverageMerge, Some(coverage.target()), None);
+        self.merge_coverage(
+            &profdata_bin_path,
+            &coverage_out_raw_dir,
+            &coverage_out_file,
+        )?;
+        drop(progress);
+
+        // The report only needs the totals, a missing llvm-cov must not fail the command.
+        if let Err(e) = self.save_coverage_summary(coverage, &coverage_out_file) {
+            warn!("could not summarize coverage: {:#}", e);
+        }
+
+        if let (Some((_, map)), Some(package)) = (&move_coverage, &coverage.coverage_map) {
+            if map.exists() {
+                info!(
+                    "Wrote the Move coverage map to {}, see it with `move coverage summary --path {}`",
+                    strip_current_dir_prefix(map).display(),
+                    package.display()
+                );
+                // A summary to compare with the coverage of later corpora.
+                let summary = coverage_out_file.with_file_name(MOVE_COVERAGE_SUMMARY_FILE);
+                match move_output(package, &["coverage", "summary", "--csv"]) {
+                    Ok(csv) => {
+                        fs::write(&summary, csv)?;
+                        info!(
+                            "Wrote the Move coverage summary to {}, compare it with `move-fuzz coverage diff`",
+                            strip_current_dir_prefix(&summary).display()
+                        );
+                    }
+                    Err(e) => warn!("could not summarize the Move coverage: {:#}", e),
+                }
+            } else {
+                warn!("the corpus did not execute any function of a Move module");
+            }
+        }
+
+        if coverage.rare_branches {
+            let report = coverage_out_file.with_file_name(RARE_BRANCHES_FILE);
+            self.report_rare_branches(coverage, &corpora, &report)?;
+        }
+
+        Ok(())
+    }
+
+    /// The trace the Move VM writes while replaying the corpus, and where the
+    /// coverage map of `package` made from it goes.
+    fn move_coverage_paths(
+        &self,
+        package: &Path,
+        coverage_file: &Path,
+    ) -> Result<(PathBuf, PathBuf)> {
+        if !package.join("Move.toml").is_file() {
+            bail!("{} is not a Move package", package.display());
+        }
+        let trace = env::current_dir()?.join(coverage_file.with_file_name("move.trace"));
+        // The VM appends to the trace file.
+        if trace.exists() {
+            fs::remove_file(&trace)
+                .with_context(|| format!("failed to remove {}", trace.display()))?;
+        }
+        let map = env::current_dir()?
+            .join(package)
+            .join(".coverage_map.mvcov");
+        Ok((trace, map))
+    }
+
+    /// Runs every input alone and lists the regions of the package and the Move
+    /// VM crates that at most `--rare-threshold` of them hit.
+    fn report_rare_branches(
+        &self,
+        coverage: &options::Coverage,
+        corpora: &[PathBuf],
+        report: &Path,
+    ) -> Result<()> {
+        let llvm_dir = coverage.llvm_path.clone().unwrap_or(rustlib()?);
+        let tool = |name: &str| llvm_dir.join(format!("{}{}", name, env::consts::EXE_SUFFIX));
+        let bin = self.target_binary(&coverage.build, coverage.target())?;
+
+        let fuzz_dir = self.fuzz_dir().canonicalize()?;
+        let package_dir = fuzz_dir.parent().unwrap_or(&fuzz_dir).to_owned();
+        let in_scope = |file: &str| {
+            // The sources of the package are relative to it in coverage builds.
+            let file = &package_dir.join(file);
+            rare::is_vm_source(&file.to_string_lossy())
+                || (file.starts_with(&package_dir) && !file.starts_with(&fuzz_dir))
+        };
+
+        let mut inputs = Vec::new();
+        for corpus in corpora {
+            if corpus.is_dir() {
+                inputs.extend(files_in(corpus)?);
+            } else {
+                inputs.push(corpus.clone());
+            }
+        }
+
+        let tmp_dir = tempfile::tempdir()?;
+        let profraw = tmp_dir.path().join("input.profraw");
+        let profdata = tmp_dir.path().join("input.profdata");
+        let mut hits = RegionHits::default();
+        let mut failed = 0;
+        let progress = self.progress.start(
+            Task::Replay,
+            Some(coverage.target()),
+            Some(inputs.len() as u64),
+        );
+        for input in &inputs {
+            if profraw.exists() {
+                fs::remove_file(&profraw)?;
+            }
+            let mut cmd = Command::new(&bin);
+            self.harness_env(&mut cmd)?;
+            self.target_env(&mut cmd, coverage.target())?;
+            cmd.env("LLVM_PROFILE_FILE", &profraw)
+                .args(&coverage.args)
+                .arg(input)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            // Crashing inputs don't write a profile.
+            if !status.success() || !profraw.exists() {
+                failed += 1;
+                progress.step();
+                continue;
+            }
+
+            let mut merge = Command::new(tool("llvm-profdata"));
+            merge
+                .arg("merge")
+                .arg("-sparse")
+                .arg(&profraw)
+                .arg("-o")
+                .arg(&profdata);
+            let status = merge
+                .status()
+                .with_context(|| format!("failed to run command: {:?}", merge))?;
+            if !status.success() {
+                bail!("command exited with failure status {}: {:?}", status, merge);
+            }
+            let mut export = Command::new(tool("llvm-cov"));
+            export
+                .arg("export")
+                .arg(format!("-instr-profile={}", profdata.display()))
+                .arg(&bin);
+            let output = export
+                .output()
+                .with_context(|| format!("failed to run command: {:?}", export))?;
+            if !output.status.success() {
+                bail!(
+                    "command exited with failure status {}: {:?}",
+                    output.status,
+                    export
+                );
+            }
+            hits.add_input(&String::from_utf8_lossy(&output.stdout), in_scope)?;
+            progress.step();
+        }
+        drop(progress);
+        if failed > 0 {
+            warn!(
+                "{} inputs crashed or wrote no profile and were left out",
+                failed
+            );
+        }
+
+        let rare = hits.rare(coverage.rare_threshold);
+        let mut listing = String::new();
+        for (region, count) in &rare {
+            listing.push_str(&format!("{:>4}  {}\n", count, region));
+        }
+        fs::write(report, &listing)
+            .with_context(|| format!("failed to write to {}", report.display()))?;
+
+        const SHOWN: usize = 50;
+        println!("{:>4}  REGION", "HITS");
+        for line in listing.lines().take(SHOWN) {
+            println!("{}", line);
+        }
+        if rare.len() > SHOWN {
+            println!("... and {} more", rare.len() - SHOWN);
+        }
+        println!(
+            "\n{} regions hit by at most {} of {} inputs, listed in {}",
+            rare.len(),
+            coverage.rare_threshold,
+            inputs.len() - failed,
+            strip_current_dir_prefix(report).display()
+        );
+        Ok(())
+    }
+
+    /// Save the line coverage totals next to the profile, for `move-fuzz report`
+    fn save_coverage_summary(&self, coverage: &options::Coverage, profdata: &Path) -> Result<()> {
+        let mut llvm_cov = coverage.llvm_path.clone().unwrap_or(rustlib()?);
+        llvm_cov.push(format!("llvm-cov{}", env::consts::EXE_SUFFIX));
+        let mut cmd = Command::new(llvm_cov);
+        cmd.arg("export")
+            .arg("-summary-only")
+            .arg(format!("-instr-profile={}", profdata.display()))
+            .arg(self.target_binary(&coverage.build, coverage.target())?);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "command exited with failure status {}: {:?}",
+                output.status,
+                cmd
+            );
+        }
+        let summary =
+            CoverageSummary::from_llvm_cov_export(&String::from_utf8_lossy(&output.stdout))?;
+        let path = profdata.with_file_name(COVERAGE_SUMMARY_FILE);
+        fs::write(&path, serde_json::to_string_pretty(&summary)?)
+            .with_context(|| format!("failed to write to {}", path.display()))?;
+        CoverageSnapshot::append(&profdata.with_file_name(COVERAGE_HISTORY_FILE), &summary)
+    }
+
+    fn create_coverage_cmd(
+        &self,
+        coverage: &options::Coverage,
+        coverage_dir: &Path,
+        corpus_dir: &Path,
+    ) -> Result<(Command, tempfile::TempDir)> {
+        let mut cmd = Command::new(self.target_binary(&coverage.build, coverage.target())?);
+        self.harness_env(&mut cmd)?;
+        self.target_env(&mut cmd, coverage.target())?;
+
+        // Raw coverage data will be saved in `coverage/<target>` directory.
+        let corpus_dir_name = corpus_dir
+            .file_name()
+            .and_then(|x| x.to_str())
+            .with_context(|| format!("Invalid corpus directory: {:?}", corpus_dir))?;
+        cmd.env(
+            "LLVM_PROFILE_FILE",
+            coverage_dir.join(format!("default-{}.profraw", corpus_dir_name)),
+        );
+        cmd.arg("-merge=1");
+        let dummy_corpus = tempfile::tempdir()?;
+        cmd.arg(dummy_corpus.path());
+        cmd.arg(corpus_dir);
+
+        for arg in &coverage.args {
+            cmd.arg(arg);
+        }
+
+        Ok((cmd, dummy_corpus))
+    }
+
+    fn merge_coverage(
+        &self,
+        profdata_bin_path: &Path,
+        profdata_raw_path: &Path,
+        profdata_out_path: &Path,
+    ) -> Result<()> {
+        let mut merge_cmd = Command::new(profdata_bin_path);
+        merge_cmd.arg("merge").arg("-sparse");
+        merge_cmd.arg(profdata_raw_path);
+        merge_cmd.arg("-o").arg(profdata_out_path);
+
+        info!("Merging raw coverage data...");
+        let status = merge_cmd
+            .status()
+            .map_err(|e| error::spawn_failed(&merge_cmd, e))
+            .with_context(|| "Merging raw coverage files failed.\n\
+                              \n\
+                              Do you have LLVM coverage tools installed?\n\
+                              https://doc.rust-lang.org/rustc/instrument-coverage.html#installing-llvm-coverage-tools")?;
+        if !status.success() {
+            Err(anyhow!(
+                "Command exited with failure status {}: {:?}",
+                status,
+                merge_cmd
+            ))
+            .context("Merging raw coverage files failed")?;
+        }
+
+        if profdata_out_path.exists() {
+            info!("Coverage data merged and saved in {:?}.", profdata_out_path);
+            Ok(())
+        } else {
+            bail!("Coverage data could not be merged.")
+        }
+    }
+
+    /// Reproduce every artifact, bucket the crashes by signature and update the triage database
+    pub fn exec_triage(&self, triage: &options::Triage) -> Result<()> {
+        let targets = match &triage.target {
+            Some(target) => {
+                self.ensure_target_exists(target)?;
+                vec![target.clone()]
+            }
+            None => self.targets.clone(),
+        };
+
+        // Reproduce under the dev build: debug assertions and unoptimized
+        // frames give more precise signatures than the fuzzing build.
+        let mut build = triage.build.clone();
+        build.dev = true;
+        build.cargo_options.release = false;
+
+        let db_path = self.triage_db_path();
+        let mut db = CrashDatabase::load(&db_path)?;
+        let previously_known: HashSet<_> = db
+            .buckets
+            .iter()
+            .filter(|b| b.status != Status::Fixed)
+            .map(|b| (b.target.clone(), b.signature.clone()))
+            .collect();
+        let mut reproduced = HashSet::new();
+        let mut crash_logs = HashMap::new();
+        // Stability is only meaningful as measured by this triage.
+        for bucket in db.buckets.iter_mut().filter(|b| targets.contains(&b.target)) {
+            bucket.stability = None;
+        }
+
+        let mut batch = Batch::default();
+        for target in &targets {
+            batch.run(target, || {
+                self.exec_build(BuildMode::Build, &build, Some(target))?;
+                for artifact in self.artifact_files(target)? {
+                    let artifact = strip_current_dir_prefix(&artifact).to_owned();
+                    info!("Reproducing {}", artifact.display());
+
+                    let crashes = self.reproduce_crashes(
+                        &build,
+                        target,
+                        &triage.args,
+                        &artifact,
+                        triage.stability,
+                    )?;
+                    let runs: Vec<_> = crashes
+                        .iter()
+                        .map(|crash| crash.as_ref().map(|(signature, _)| signature.clone()))
+                        .collect();
+                    let Some((signature, stability)) = Stability::measure(&runs) else {
+                        continue;
+                    };
+                    // The report of the bucket is written from this crash
+                    // rather than from another run.
+                    if let Some((_, stderr)) = crashes
+                        .into_iter()
+                        .flatten()
+                        .find(|(crashed, _)| *crashed == signature)
+                    {
+                        crash_logs.insert(artifact.clone(), stderr);
+                    }
+                    if triage.stability > 1 {
+                        db.record_runs(target, &signature, &artifact, stability);
+                    } else {
+                        db.record(target, &signature, &artifact);
+                    }
+                    reproduced.insert((target.clone(), signature));
+                }
+                Ok(())
+            });
+        }
+
+        // The crashes of the targets that failed are not fixed for all we know.
+        let triaged: Vec<&str> = batch.succeeded().collect();
+        for bucket in db
+            .buckets
+            .iter_mut()
+            .filter(|b| triaged.contains(&b.target.as_str()))
+        {
+            let key = (bucket.target.clone(), bucket.signature.clone());
+            bucket.status = if !reproduced.contains(&key) {
+                Status::Fixed
+            } else if previously_known.contains(&key) {
+                Status::Known
+            } else {
+                Status::New
+            };
+        }
+        db.save(&db_path)?;
+
+        let buckets: Vec<_> = db
+            .prioritized()
+            .into_iter()
+            .filter(|b| targets.contains(&b.target))
+            .collect();
+        if let Some(path) = &triage.sarif {
+            let findings: Vec<_> = buckets
+                .iter()
+                .filter(|b| b.status != Status::Fixed)
+                .map(|b| Finding {
+                    target: b.target.clone(),
+                    signature: b.signature.clone(),
+                    artifacts: b.artifacts.clone(),
+                })
+                .collect();
+            sarif::write(path, &findings)?;
+        }
+        if !triage.no_reports {
+            for bucket in buckets.iter().filter(|b| b.status != Status::Fixed) {
+                if let Err(e) = self.write_crash_report(&build, bucket, &triage.args, &crash_logs) {
+                    warn!(
+                        "could not write the report of {} in {}: {:#}",
+                        bucket.signature, bucket.target, e
+                    );
+                }
+            }
+        }
+        info!("\n{:─<80}\n", "");
+        if targets.len() > 1 {
+            info!("{}", batch.summary());
+        }
+        if buckets.is_empty() {
+            println!("No crashes found in the artifact directories.");
+            return batch.into_result();
+        }
+        let (flaky, stable): (Vec<_>, Vec<_>) = buckets
+            .iter()
+            .partition(|b| b.stability.is_some_and(|s| s.is_flaky()));
+        print_buckets(&stable);
+        if !flaky.is_empty() {
+            println!(
+                "\nNondeterministic crashes, which did not reproduce on every run \
+                and may depend on uninitialized state or address randomization:\n"
+            );
+            print_buckets(&flaky);
+        }
+        let count = |status| buckets.iter().filter(|b| b.status == status).count();
+        println!(
+            "\n{} new, {} known, {} fixed{}. Database saved in {}",
+            count(Status::New),
+            count(Status::Known),
+            count(Status::Fixed),
+            if triage.stability > 1 {
+                format!(", {} nondeterministic", flaky.len())
+            } else {
+                String::new()
+            },
+            strip_current_dir_prefix(&db_path).display()
+        );
+        batch.into_result()
+    }
+
+    /// Writes the `report.yaml` of `bucket` from the crash of its first
+    /// artifact left, as logged in `crash_logs` when triage just reproduced
+    /// it, reproducing it again otherwise.
+    fn write_crash_report(
+        &self,
+        build: &BuildOptions,
+        bucket: &Bucket,
+        args: &[String],
+        crash_logs: &HashMap<PathBuf, String>,
+    ) -> Result<PathBuf> {
+        let target = &bucket.target;
+        let artifact = bucket
+            .artifacts
+            .iter()
+            .find(|artifact| artifact.is_file())
+            .context("none of its artifacts is left")?;
+        let stderr = match crash_logs.get(artifact) {
+            Some(stderr) => stderr.clone(),
+            None => {
+                let mut cmd = self.cargo_run(build, target)?;
+                cmd.stdin(Stdio::null()).args(args).arg(artifact);
+                let output = cmd
+                    .output()
+                    .with_context(|| format!("failed to run command: {:?}", cmd))?;
+                if output.status.success() {
+                    bail!("{} does not reproduce a failure", artifact.display());
+                }
+                String::from_utf8_lossy(&output.stderr).into_owned()
+            }
+        };
+        let tail = metadata::tail(&stderr);
+
+        let data =
+            fs::read(artifact).with_context(|| format!("failed to read {}", artifact.display()))?;
+        let decoded = match self.run_fuzz_target_debug_formatter(build, target, artifact) {
+            Ok(decoded) => Some(decoded),
+            Err(e) => {
+                warn!("could not decode {}: {:#}", artifact.display(), e);
+                None
+            }
+        };
+        let input = crash_report::Input {
+            path: artifact.clone(),
+            size: data.len() as u64,
+            base64: encoding::encode(options::InputEncoding::Base64, &data),
+            decoded,
+        };
+        // The pinned target directory is an implementation detail of the build.
+        let own = BuildOptions {
+            target_dir: build.target_dir.clone(),
+            ..self.build_for(build, target)?
+        };
+        let found_with = ArtifactMetadata::load(artifact).ok().map(|meta| {
+            let mut flags = format!("move-fuzz run{} {}", meta.build, meta.target);
+            if !meta.libfuzzer_args.is_empty() {
+                flags.push_str(" -- ");
+                flags.push_str(&meta.libfuzzer_args.join(" "));
+            }
+            flags
+        });
+        let info = BuildInfo {
+            found_with,
+            ..BuildInfo::current(own.to_string().trim().to_owned())
+        };
+        let mut report = CrashReport::new(bucket, input, metadata::crash_report(&tail), info);
+        report.reproduce = format!("move-fuzz repro{} {} {}", build, target, artifact.display());
+        if !args.is_empty() {
+            report.reproduce.push_str(" -- ");
+            report.reproduce.push_str(&args.join(" "));
+        }
+        let dir = self
+            .fuzz_dir()
+            .join(CRASH_REPORTS_DIR)
+            .join(target)
+            .join(crash_report::bucket_dir(&bucket.signature));
+        report.write(&dir)
+    }
+
+    /// Reports the dependency closure of the fuzz package and the problems the
+    /// audit finds in it.
+    pub fn exec_audit(&self) -> Result<()> {
+        let audit = self.audit()?;
+        println!("Packages:");
+        for package in &audit.packages {
+            println!(
+                "  {:<24} {:>4} modules {:>4} natives  {}",
+                package.name,
+                package.modules,
+                package.natives,
+                strip_current_dir_prefix(&package.path).display()
+            );
+        }
+        if !audit.unfetched.is_empty() {
+            println!(
+                "Not fetched yet, so not audited: {}",
+                audit.unfetched.join(", ")
+            );
+        }
+        if audit.warnings.is_empty() {
+            println!("No problems found.");
+        }
+        for warning in &audit.warnings {
+            println!("warning: {}", warning);
+        }
+        Ok(())
+    }
+
+    /// Audits the dependencies of the fuzz package against the natives its
+    /// harnesses register.
+    fn audit(&self) -> Result<audit::Audit> {
+        let natives = self.fuzz_section()?.natives.clone().unwrap_or_default();
+        audit::audit(self.fuzz_dir(), &natives, &move_home())
+    }
+
+    pub fn exec_verify(&self, verify: &options::Verify) -> Result<()> {
+        let target = &verify.target;
+        self.ensure_target_exists(target)?;
+        let artifacts = match &verify.artifact {
+            Some(artifact) => {
+                if !artifact.is_file() {
+                    bail!("artifact {} does not exist", artifact.display());
+                }
+                vec![artifact.clone()]
+            }
+            None => self.artifact_files(target)?,
+        };
+        if artifacts.is_empty() {
+            println!("No artifacts to verify for {}.", target);
+            return Ok(());
+        }
+
+        // Build like `triage` does, so the signatures match the recorded buckets.
+        let mut build = verify.build.clone();
+        build.dev = true;
+        build.cargo_options.release = false;
+        self.exec_build(BuildMode::Build, &build, Some(target))?;
+
+        let mut results = Vec::new();
+        for artifact in artifacts {
+            let artifact = strip_current_dir_prefix(&artifact).to_owned();
+            info!("Verifying {}", artifact.display());
+
+            let mut cmd = self.cargo_run(&build, target)?;
+            cmd.stdin(Stdio::null()).args(&verify.args).arg(&artifact);
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            let signature = if output.status.success() {
+                None
+            } else {
+                Some(
+                    crash_signature(&String::from_utf8_lossy(&output.stderr))
+                        .unwrap_or_else(|| format!("exit status {}", output.status)),
+                )
+            };
+            results.push((artifact, signature));
+        }
+
+        let db_path = self.triage_db_path();
+        let mut db = CrashDatabase::load(&db_path)?;
+        let fixed = db.verify(target, &results);
+        db.save(&db_path)?;
+
+        info!("\n{:─<80}\n", "");
+        println!("{:<8} {:<40}  SIGNATURE", "STATUS", "ARTIFACT");
+        for (artifact, signature) in &results {
+            let status = if signature.is_some() {
+                "crashes"
+            } else {
+                "fixed"
+            };
+            println!(
+                "{:<8} {:<40}  {}",
+                status,
+                artifact.display(),
+                signature.as_deref().unwrap_or("-")
+            );
+        }
+        let crashing = results.iter().filter(|(_, s)| s.is_some()).count();
+        println!(
+            "\n{} of {} artifacts still reproduce, {} crash buckets marked fixed in {}",
+            crashing,
+            results.len(),
+            fixed.len(),
+            strip_current_dir_prefix(&db_path).display()
+        );
+        if crashing > 0 {
+            bail!("{} artifacts of {} still reproduce", crashing, target);
+        }
+        Ok(())
+    }
+
+    /// Append a `#[test]` reproducing `artifact` to the regression tests of its target
+    pub fn gen_regression(&self, gen: &options::GenRegression) -> Result<()> {
+        self.ensure_target_exists(&gen.target)?;
+        let input = fs::read(&gen.artifact)
+            .with_context(|| format!("failed to read artifact {}", gen.artifact.display()))?;
+        let name = regression_test_name(&gen.artifact);
+
+        let test_file = self.regressions_path(&gen.target);
+        let regressions_dir = self.fuzz_dir().join(REGRESSIONS_DIR);
+        fs::create_dir_all(&regressions_dir).with_context(|| {
+            format!("failed to create directory {}", regressions_dir.display())
+        })?;
+        if test_file.exists() {
+            let tests = fs::read_to_string(&test_file)?;
+            if tests.contains(&format!("fn {}()", name)) {
+                bail!("{} already contains test `{}`", test_file.display(), name);
+            }
+        } else {
+            fs::write(&test_file, regression_file_template!(gen.target).to_string())
+                .with_context(|| format!("failed to create {}", test_file.display()))?;
+            let mut cargo = fs::OpenOptions::new()
+                .append(true)
+                .open(self.manifest_path())?;
+            cargo.write_fmt(toml_regression_test_template!(gen.target))?;
+        }
+
+        let bytes = input
+            .chunks(16)
+            .map(|chunk| {
+                let line: Vec<_> = chunk.iter().map(|b| format!("0x{:02x},", b)).collect();
+                format!("\n        {}", line.join(" "))
+            })
+            .collect::<String>()
+            + if input.is_empty() { "" } else { "\n    " };
+        let mut tests = fs::OpenOptions::new().append(true).open(&test_file)?;
+        tests
+            .write_fmt(regression_test_template!(
+                name,
+                strip_current_dir_prefix(&gen.artifact).display(),
+                bytes
+            ))
+            .with_context(|| format!("failed to write to {}", test_file.display()))?;
+
+        info!(
+            "Added regression test `{}` to {}",
+            name,
+            strip_current_dir_prefix(&test_file).display()
+        );
+        Ok(())
+    }
+
+    /// Turns a raw crash artifact into a bug bundle: minimizes it, decodes it,
+    /// reproduces it for its crash report and metadata, adds a regression test
+    /// for it and records it in the triage database.
+    pub fn exec_process_crash(&self, process: &options::ProcessCrash) -> Result<()> {
+        let target = &process.target;
+        let artifact = strip_current_dir_prefix(&process.artifact);
+        self.ensure_target_exists(target)?;
+        if !artifact.is_file() {
+            bail!("artifact {} does not exist", artifact.display());
+        }
+        let set = ["exact_artifact_path", "minimize_crash", "runs"];
+        check_engine_args(&process.build, &process.args, &set);
+        self.exec_build(BuildMode::Build, &process.build, Some(target))?;
+
+        let name = artifact
+            .file_name()
+            .with_context(|| format!("invalid input path {}", artifact.display()))?;
+        let bundle = match &process.out {
+            Some(dir) => dir.clone(),
+            None => self.fuzz_dir().join(BUGS_DIR).join(target).join(name),
+        };
+        fs::create_dir_all(&bundle)
+            .with_context(|| format!("failed to create directory {}", bundle.display()))?;
+        let original = bundle.join(format!("{}.orig", name.to_string_lossy()));
+        fs::copy(artifact, &original)
+            .with_context(|| format!("failed to copy {}", artifact.display()))?;
+
+        // The minimized input takes the name of the artifact, which the
+        // regression test is named after.
+        let input = bundle.join(name);
+        let engine = LibFuzzer::new(self, &process.build);
+        let mut cmd = self.minimize_command(&engine, target, process.runs, None)?;
+        cmd.arg(format!("-exact_artifact_path={}", input.display()))
+            .args(&process.args)
+            .arg(artifact);
+        let outcome = self.run_minimize(&engine, target, &mut cmd)?;
+        if !outcome.status.success() || !input.is_file() {
+            warn!(
+                "could not minimize {}, keeping it as it is",
+                artifact.display()
+            );
+            fs::copy(artifact, &input)
+                .with_context(|| format!("failed to copy {}", artifact.display()))?;
+        }
+
+        let debug = match self.run_fuzz_target_debug_formatter(&process.build, target, &input) {
+            Ok(debug) => {
+                let path = bundle.join("debug.txt");
+                fs::write(&path, &debug)
+                    .with_context(|| format!("failed to write to {}", path.display()))?;
+                Some(debug)
+            }
+            Err(e) => {
+                warn!("could not decode {}: {:#}", input.display(), e);
+                None
+            }
+        };
+
+        let mut cmd = self.cargo_run(&process.build, target)?;
+        cmd.stdin(Stdio::null()).args(&process.args).arg(&input);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        if output.status.success() {
+            bail!("{} does not reproduce a failure", artifact.display());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let log = bundle.join("crash.log");
+        fs::write(&log, stderr.as_bytes())
+            .with_context(|| format!("failed to write to {}", log.display()))?;
+        let signature =
+            crash_signature(&stderr).unwrap_or_else(|| format!("exit status {}", output.status));
+        let meta = ArtifactMetadata {
+            class: metadata::class(artifact),
+            elapsed_ms: metadata::executed_ms(&stderr),
+            peak_rss_mb: metadata::peak_rss_mb(&stderr),
+            ..crash_metadata(
+                target,
+                &process.build,
+                process.args.clone(),
+                &metadata::tail(&stderr),
+            )
+        };
+        meta.write(&input)?;
+
+        let gen = options::GenRegression {
+            fuzz_dir_wrapper: options::FuzzDirWrapper { fuzz_dir: None },
+            target: target.clone(),
+            artifact: input.clone(),
+        };
+        if let Err(e) = self.gen_regression(&gen) {
+            warn!("could not add a regression test: {:#}", e);
+        }
+
+        let db_path = self.triage_db_path();
+        let mut db = CrashDatabase::load(&db_path)?;
+        let new = db.record(target, &signature, artifact);
+        db.save(&db_path)?;
+
+        let report = BugReport {
+            meta: &meta,
+            signature: &signature,
+            new,
+            input: &input,
+            original_len: fs::metadata(artifact)
+                .with_context(|| format!("failed to read the metadata of {}", artifact.display()))?
+                .len(),
+            debug: debug.as_deref(),
+        };
+        let readme = bundle.join("README.md");
+        fs::write(&readme, report.to_string())
+            .with_context(|| format!("failed to write to {}", readme.display()))?;
+
+        println!(
+            "{} crash: {}\nBug bundle written to {}",
+            if new { "New" } else { "Known" },
+            signature,
+            strip_current_dir_prefix(&bundle).display()
+        );
+        Ok(())
+    }
+
+    /// Run one of the `corpus` management actions
+    pub fn exec_corpus(&self, action: &options::CorpusAction) -> Result<()> {
+        match action {
+            options::CorpusAction::Add { target, files } => {
+                self.ensure_target_exists(target)?;
+                let corpus_dir = self.corpus_for(target)?;
+                let mut added = Vec::new();
+                for file in files {
+                    let data = fs::read(file)
+                        .with_context(|| format!("failed to read {}", file.display()))?;
+                    if let Some(entry) = corpus::insert(&corpus_dir, &data)? {
+                        let file = provenance::source(file);
+                        added.push((provenance::entry_name(&entry), Origin::Added { file }));
+                    }
+                }
+                info!(
+                    "Added {} new entries to {} ({} duplicates skipped)",
+                    added.len(),
+                    strip_current_dir_prefix(&corpus_dir).display(),
+                    files.len() - added.len()
+                );
+                self.record_provenance(target, added);
+            }
+            options::CorpusAction::List { target } => {
+                self.ensure_target_exists(target)?;
+                let entries = corpus::entries(&self.corpus_for(target)?)?;
+                for entry in &entries {
+                    println!(
+                        "{:>10}  {}",
+                        corpus::human_size(entry.size),
+                        strip_current_dir_prefix(&entry.path).display()
+                    );
+                }
+                let total: u64 = entries.iter().map(|e| e.size).sum();
+                println!(
+                    "{} entries, {} total",
+                    entries.len(),
+                    corpus::human_size(total)
+                );
+            }
+            options::CorpusAction::Info { file, target } => {
+                let data =
+                    fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+                let entry = corpus::content_hash(&data);
+                let targets = match target {
+                    Some(target) => {
+                        self.ensure_target_exists(target)?;
+                        vec![target.clone()]
+                    }
+                    None => self.targets.clone(),
+                };
+                let mut found = false;
+                for target in &targets {
+                    let records = self.provenance_index(target).load()?;
+                    let corpus_dir = self.corpus_root().join(target);
+                    let in_corpus = corpus_dir.join(&entry).is_file();
+                    let record = records.get(&entry);
+                    if record.is_none() && !in_corpus {
+                        continue;
+                    }
+                    found = true;
+                    println!(
+                        "{} {} the corpus of {}:",
+                        entry,
+                        if in_corpus { "in" } else { "no longer in" },
+                        target
+                    );
+                    match record {
+                        Some(record) => {
+                            println!("    {}", record.origin);
+                            println!("    added {}", format_time(Some(record.time)));
+                        }
+                        None => println!("    origin unknown, added before it was recorded"),
+                    }
+                    let entries = if corpus_dir.is_dir() {
+                        provenance::entry_names(&corpus_dir)?
+                    } else {
+                        HashSet::new()
+                    };
+                    let counts =
+                        provenance::breakdown(&records, entries.iter().map(String::as_str))
+                            .into_iter()
+                            .map(|(kind, count)| format!("{} {}", count, kind))
+                            .collect::<Vec<_>>();
+                    println!("    corpus of {}: {}", target, counts.join(", "));
+                }
+                if !found {
+                    bail!(
+                        "{} is in no corpus and has no recorded provenance",
+                        file.display()
+                    );
+                }
+            }
+            options::CorpusAction::Gc {
+                build,
+                lock: lock_options,
+                target,
+                max_size,
+                dedupe,
+            } => {
+                self.ensure_target_exists(target)?;
+                let _lock =
+                    Lock::acquire(self.fuzz_dir(), lock::Scope::Target(target), lock_options)?;
+                let corpus_dir = self.corpus_for(target)?;
+                let mut seen = HashSet::new();
+                let (mut removed, mut reclaimed) = (0, 0);
+                for entry in corpus::entries(&corpus_dir)? {
+                    let oversized = max_size.is_some_and(|max| entry.size > max);
+                    let duplicate = !seen.insert(corpus::content_hash(&fs::read(&entry.path)?));
+                    if oversized || duplicate {
+                        fs::remove_file(&entry.path).with_context(|| {
+                            format!("failed to remove {}", entry.path.display())
+                        })?;
+                        removed += 1;
+                        reclaimed += entry.size;
+                    }
+                }
+                info!(
+                    "Removed {} entries, reclaimed {}",
+                    removed,
+                    corpus::human_size(reclaimed)
+                );
+                if *dedupe {
+                    let declared = self.fuzz_target(target)?.declared.unwrap_or_default();
+                    let mut dirs = vec![corpus_dir.clone()];
+                    dirs.extend(
+                        declared
+                            .seeds
+                            .iter()
+                            .map(|seeds| self.fuzz_dir().join(seeds))
+                            .filter(|seeds| seeds.is_dir()),
+                    );
+                    let dedupe = corpus::dedupe(&dirs)?;
+                    for entry in &dedupe.failed {
+                        warn!(
+                            "could not replace {} with a hard link",
+                            strip_current_dir_prefix(entry).display()
+                        );
+                    }
+                    info!(
+                        "Linked {} duplicate entries, reclaimed {}",
+                        dedupe.linked,
+                        corpus::human_size(dedupe.reclaimed)
+                    );
+                }
+                let quota = self.fuzz_section()?.quota.clone();
+                if quota.is_set() {
+                    self.apply_quota(&quota, build, target)?;
+                }
+                self.prune_provenance(target, &corpus_dir);
+            }
+            options::CorpusAction::Export { target, archive } => {
+                self.ensure_target_exists(target)?;
+                let file = fs::File::create(archive)
+                    .with_context(|| format!("failed to create {}", archive.display()))?;
+                let mut builder = tar::Builder::new(file);
+                let entries = corpus::entries(&self.corpus_for(target)?)?;
+                for entry in &entries {
+                    let name = entry.path.file_name().expect("corpus entries are files");
+                    builder
+                        .append_path_with_name(&entry.path, name)
+                        .with_context(|| format!("failed to archive {}", entry.path.display()))?;
+                }
+                builder
+                    .finish()
+                    .with_context(|| format!("failed to write to {}", archive.display()))?;
+                info!(
+                    "Exported {} entries to {}",
+                    entries.len(),
+                    archive.display()
+                );
+            }
+            options::CorpusAction::Import { target, archive } => {
+                self.ensure_target_exists(target)?;
+                let corpus_dir = self.corpus_for(target)?;
+                let file = fs::File::open(archive)
+                    .with_context(|| format!("failed to open {}", archive.display()))?;
+                let mut archive_reader = tar::Archive::new(file);
+                let (mut added, mut skipped) = (Vec::new(), 0);
+                for entry in archive_reader
+                    .entries()
+                    .with_context(|| format!("failed to read {}", archive.display()))?
+                {
+                    let mut entry = entry?;
+                    if !entry.header().entry_type().is_file() {
+                        continue;
+                    }
+                    let mut data = Vec::new();
+                    entry.read_to_end(&mut data)?;
+                    // Entries are renamed by content hash, so archive paths can't
+                    // escape the corpus directory.
+                    match corpus::insert(&corpus_dir, &data)? {
+                        Some(entry) => added.push((
+                            provenance::entry_name(&entry),
+                            Origin::Imported {
+                                archive: provenance::source(archive),
+                            },
+                        )),
+                        None => skipped += 1,
+                    }
+                }
+                info!(
+                    "Imported {} new entries into {} ({} duplicates skipped)",
+                    added.len(),
+                    strip_current_dir_prefix(&corpus_dir).display(),
+                    skipped
+                );
+                self.record_provenance(target, added);
+            }
+            options::CorpusAction::Migrate {
+                target,
+                converter,
+                backup,
+            } => {
+                self.ensure_target_exists(target)?;
+                let corpus_dir = self.corpus_for(target)?;
+                let backup = backup
+                    .clone()
+                    .unwrap_or_else(|| self.corpus_root().join(format!("{}.orig", target)));
+                if backup.exists() {
+                    bail!(
+                        "{} already exists, remove it or pick another backup directory",
+                        backup.display()
+                    );
+                }
+                let migrated = self.corpus_root().join(format!("{}.migrating", target));
+                if migrated.exists() {
+                    fs::remove_dir_all(&migrated)
+                        .with_context(|| format!("failed to remove {}", migrated.display()))?;
+                }
+                fs::create_dir_all(&migrated)
+                    .with_context(|| format!("could not make a directory at {:?}", migrated))?;
+
+                let migration = corpus::migrate(&corpus_dir, &migrated, |data| {
+                    self.convert_input(converter, target, data)
+                })?;
+                if migration.converted == 0 && !migration.failed.is_empty() {
+                    fs::remove_dir_all(&migrated)
+                        .with_context(|| format!("failed to remove {}", migrated.display()))?;
+                    bail!(
+                        "`{}` converted none of the {} entries of the corpus of {}, leaving it as it was",
+                        converter,
+                        migration.failed.len(),
+                        target
+                    );
+                }
+                if let Some(parent) = backup.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("could not make a directory at {:?}", parent))?;
+                }
+                fs::rename(&corpus_dir, &backup).with_context(|| {
+                    format!(
+                        "failed to move {} to {}",
+                        corpus_dir.display(),
+                        backup.display()
+                    )
+                })?;
+                fs::rename(&migrated, &corpus_dir).with_context(|| {
+                    format!(
+                        "failed to move {} to {}",
+                        migrated.display(),
+                        corpus_dir.display()
+                    )
+                })?;
+                for entry in &migration.failed {
+                    warn!(
+                        "`{}` could not convert {}",
+                        converter,
+                        strip_current_dir_prefix(entry).display()
+                    );
+                }
+                info!(
+                    "Migrated {} entries of {} ({} duplicates, {} failed), the old corpus is in {}",
+                    migration.converted,
+                    strip_current_dir_prefix(&corpus_dir).display(),
+                    migration.duplicates,
+                    migration.failed.len(),
+                    strip_current_dir_prefix(&backup).display()
+                );
+            }
+            options::CorpusAction::ImportTransactions { target, file } => {
+                self.ensure_target_exists(target)?;
+                let source_path = self.rust_target_path(target);
+                let source = fs::read_to_string(&source_path)
+                    .with_context(|| format!("failed to read {}", source_path.display()))?;
+                let signers = self.fuzz_section()?.signers();
+                let harness = SequenceHarness::parse(&source, &signers)
+                    .with_context(|| format!("cannot import transactions into {}", target))?;
+                // The parameters of the functions called are read from the
+                // modules of the fuzz package and of its dependencies.
+                let name = move_package_name(self.fuzz_dir())?;
+                info!("Building {}", self.fuzz_dir().display());
+                move_output(self.fuzz_dir(), &["build"])?;
+                let compiled =
+                    modules::module_files(&modules::build_dir(self.fuzz_dir(), &name), true)?
+                        .iter()
+                        .map(|path| modules::read_module(path))
+                        .collect::<Result<Vec<_>>>()?;
+
+                let mut translator = Translator::new(&harness);
+                let (mut calls, mut skipped) = (Vec::new(), 0);
+                for txn in transactions::load(file)? {
+                    let (module, function) = txn.module_and_function()?;
+                    let address = txn.address()?;
+                    let params = compiled
+                        .iter()
+                        .filter(|m| *m.address() == address && m.name().as_str() == module)
+                        .find_map(|m| transactions::entry_params(m, function));
+                    let call = match params {
+                        Some(params) if txn.type_args.is_empty() => {
+                            translator.translate(&txn, &params?)?
+                        }
+                        _ => None,
+                    };
+                    match call {
+                        Some(call) => calls.push(call),
+                        None => skipped += 1,
+                    }
+                }
+
+                let corpus_dir = self.corpus_for(target)?;
+                let mut added = Vec::new();
+                for sequence in calls.chunks(harness.max_calls) {
+                    if let Some(entry) = corpus::insert(&corpus_dir, &harness.encode(sequence)?)? {
+                        added.push((
+                            provenance::entry_name(&entry),
+                            Origin::Transactions {
+                                file: provenance::source(file),
+                            },
+                        ));
+                    }
+                }
+                info!(
+                    "Imported {} transactions as {} new entries into {} ({} transactions skipped)",
+                    calls.len(),
+                    added.len(),
+                    strip_current_dir_prefix(&corpus_dir).display(),
+                    skipped
+                );
+                self.record_provenance(target, added);
+            }
+            options::CorpusAction::SeedFromProptest {
+                build,
+                target,
+                cases,
+                seed,
+            } => {
+                self.ensure_target_exists(target)?;
+                self.exec_build(BuildMode::Build, build, Some(target))?;
+                let seeds_dir = tempfile::tempdir().context("failed to create temp dir")?;
+                let mut cmd = self.cargo_run(build, target)?;
+                // Without a strategy registered, the target runs no input either.
+                cmd.arg("-runs=0")
+                    .env("MOVE_FUZZ_PROPTEST_SEEDS", seeds_dir.path())
+                    .env("MOVE_FUZZ_PROPTEST_CASES", cases.to_string());
+                if let Some(seed) = seed {
+                    cmd.env("MOVE_FUZZ_PROPTEST_RNG_SEED", seed.to_string());
+                }
+                let status = logging::status(&mut cmd, Source::LibFuzzer)?;
+                if !status.success() {
+                    bail!("generating the seeds of {} failed: {}", target, status);
+                }
+                let seeds = files_in(seeds_dir.path())?;
+                if seeds.is_empty() {
+                    bail!(
+                        "{} generated no seeds, register a strategy with `fuzz_proptest_seeds!` \
+                         and enable the `proptest-seeds` feature of libfuzzer",
+                        target
+                    );
+                }
+
+                let corpus_dir = self.corpus_for(target)?;
+                let mut added = Vec::new();
+                for path in &seeds {
+                    let data = fs::read(path)
+                        .with_context(|| format!("failed to read {}", path.display()))?;
+                    if let Some(entry) = corpus::insert(&corpus_dir, &data)? {
+                        added.push((
+                            provenance::entry_name(&entry),
+                            Origin::Proptest { seed: *seed },
+                        ));
+                    }
+                }
+                info!(
+                    "Added {} new entries to {} ({} duplicates skipped)",
+                    added.len(),
+                    strip_current_dir_prefix(&corpus_dir).display(),
+                    seeds.len() - added.len()
+                );
+                self.record_provenance(target, added);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `converter` from the fuzz directory on an input of `target`,
+    /// returning what it wrote on stdout, or `None` if it failed.
+    fn convert_input(&self, converter: &str, target: &str, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let mut child = shell(converter)
+            .current_dir(self.fuzz_dir())
+            .env("MOVE_FUZZ_DIR", self.fuzz_dir())
+            .env("MOVE_FUZZ_TARGET", target)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run `{}`", converter))?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        // Written from another thread, as the converter may fill its stdout
+        // before reading all of its input.
+        let output = thread::scope(|scope| {
+            scope.spawn(move || {
+                // A converter may exit without reading the whole input.
+                let _ = stdin.write_all(data);
+            });
+            child.wait_with_output()
+        })
+        .with_context(|| format!("failed to run `{}`", converter))?;
+        Ok(output.status.success().then_some(output.stdout))
+    }
+
+    /// The directory of the package under test, which holds the fuzz directory.
+    fn package_dir(&self) -> Option<PathBuf> {
+        let fuzz_dir = self.fuzz_dir().canonicalize().ok()?;
+        fuzz_dir.parent().map(Path::to_path_buf)
+    }
+
+    pub fn fuzz_dir(&self) -> &Path {
+        &self.fuzz_dir
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.fuzz_dir().join("Cargo.toml")
+    }
+
+    /// The `Move.toml` of the Move package of the fuzz targets.
+    pub(crate) fn move_manifest_path(&self) -> PathBuf {
+        self.fuzz_dir().join("Move.toml")
+    }
+
+    /// The `[fuzz]` section of `Move.toml`, read once per project.
+    pub(crate) fn fuzz_section(&self) -> Result<Rc<FuzzSection>> {
+        let mut cached = self.fuzz_section.borrow_mut();
+        if let Some(section) = &*cached {
+            return Ok(section.clone());
+        }
+        let section = Rc::new(move_toml::read(&self.move_manifest_path())?);
+        *cached = Some(section.clone());
+        Ok(section)
+    }
+
+    /// Has the next `fuzz_section` read `Move.toml` again, after editing it.
+    fn forget_fuzz_section(&self) {
+        self.fuzz_section.borrow_mut().take();
+    }
+
+    /// Has the harnesses run by `cmd` follow the `[fuzz]` section of `Move.toml`:
+    /// register the natives listed there and draw signers and addresses as set
+    /// there. Returns the natives, if listed.
+    fn harness_env(&self, cmd: &mut Command) -> Result<Option<Vec<String>>> {
+        let section = self.fuzz_section()?;
+        let lists = [
+            ("MOVE_FUZZ_NATIVES", &section.natives),
+            ("MOVE_FUZZ_SIGNERS", &section.signers),
+            ("MOVE_FUZZ_ADDRESSES", &section.addresses),
+        ];
+        for (var, list) in lists {
+            if let Some(list) = list {
+                cmd.env(var, list.join(","));
+            }
+        }
+        Ok(section.natives.clone())
+    }
+
+    /// Sets the environment of `target`: backtraces on, and what it is declared
+    /// with in the manifest.
+    fn target_env(&self, cmd: &mut Command, target: &str) -> Result<()> {
+        // Panics print their backtrace, which is kept with the artifacts.
+        if env::var_os("RUST_BACKTRACE").is_none() {
+            cmd.env("RUST_BACKTRACE", "1");
+        }
+        let section = self.fuzz_section()?;
+        let Some(declared) = section.targets.get(target) else {
+            return Ok(());
+        };
+        // Sequence targets check the aborts of their calls against the declared ones.
+        if let Some(aborts) = declared.expected_aborts() {
+            cmd.env("MOVE_FUZZ_EXPECTED_ABORTS", aborts);
+        }
+        cmd.envs(&declared.env);
+        Ok(())
+    }
+
+    /// Returns paths to the `coverage/<target>/raw` directory and `coverage/<target>/coverage.profdata` file.
+    pub fn coverage_for(&self, target: &str) -> Result<(PathBuf, PathBuf)> {
+        let mut coverage_data = self.fuzz_dir().to_owned();
+        coverage_data.push("coverage");
+        coverage_data.push(target);
+        let mut coverage_raw = coverage_data.clone();
+        coverage_data.push("coverage.profdata");
+        coverage_raw.push("raw");
+        fs::create_dir_all(&coverage_raw).with_context(|| {
+            format!("could not make a coverage directory at {:?}", coverage_raw)
+        })?;
+        Ok((coverage_raw, coverage_data))
+    }
+
+    /// The directory holding the corpus of each target.
+    fn corpus_root(&self) -> PathBuf {
+        match &self.state_dirs.corpus_dir {
+            Some(dir) => dir.clone(),
+            None => self.fuzz_dir().join("corpus"),
+        }
+    }
+
+    /// The directory holding the artifacts of each target.
+    fn artifacts_root(&self) -> PathBuf {
+        match &self.state_dirs.artifact_dir {
+            Some(dir) => dir.clone(),
+            None => self.fuzz_dir().join("artifacts"),
+        }
+    }
+
+    /// `input` as given, or else the artifact or corpus input of `target` of
+    /// that name, so that inputs can be named as they are listed.
+    pub fn find_input(&self, target: &str, input: &Path) -> PathBuf {
+        if input.exists() || input.components().count() != 1 {
+            return input.to_owned();
+        }
+        [self.artifacts_root(), self.corpus_root()]
+            .into_iter()
+            .map(|root| root.join(target).join(input))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| input.to_owned())
+    }
+
+    pub fn corpus_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.corpus_root();
+        p.push(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
+        Ok(p)
+    }
+
+    pub fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.artifacts_root();
+        p.push(target);
+
+        // This adds a trailing slash, which is necessary for libFuzzer, because
+        // it does simple string concatenation when joining paths.
+        p.push("");
+
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+
+        Ok(p)
+    }
+
+    /// Execute inputs on the package and on a baseline revision of it,
+    /// saving the inputs whose outcomes differ as `diff-*` artifacts.
+    pub fn exec_diff_run(&self, diff: &options::DiffRun) -> Result<()> {
+        self.ensure_target_exists(&diff.target)?;
+
+        let project_dir = find_package()?;
+        let baseline_dir = self.baseline_checkout(&project_dir, &diff.baseline)?;
+        let baseline = FuzzProject {
+            fuzz_dir: baseline_dir.join(
+                self.fuzz_dir
+                    .strip_prefix(&project_dir)
+                    .unwrap_or_else(|_| Path::new(DEFAULT_FUZZ_DIR)),
+            ),
+            targets: self.targets.clone(),
+            hooks: Hooks::default(),
+            progress: Progress::default(),
+            state_dirs: StateDirs::default(),
+            fuzz_section: RefCell::default(),
+        };
+
+        self.exec_build(BuildMode::Build, &diff.build, Some(&diff.target))?;
+        // The harness and `move build` resolve the fuzz directory against the
+        // current directory, so let the baseline build itself from its checkout.
+        let mut baseline_build = cli::self_command()?;
+        baseline_build
+            .arg("build")
+            .args(diff.build.to_string().split_whitespace())
+            .arg(&diff.target)
+            .current_dir(&baseline_dir);
+        let status = baseline_build
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", baseline_build))?;
+        if !status.success() {
+            bail!("failed to build the baseline in {}", baseline_dir.display());
+        }
+
+        let inputs = if diff.inputs.is_empty() {
+            files_in(&self.corpus_for(&diff.target)?)?
+        } else {
+            let mut inputs = Vec::new();
+            for input in &diff.inputs {
+                if input.is_dir() {
+                    inputs.extend(files_in(input)?);
+                } else {
+                    inputs.push(input.clone());
+                }
+            }
+            inputs
+        };
+
+        let mut diverging = 0;
+        for input in &inputs {
+            let input = input
+                .canonicalize()
+                .with_context(|| format!("could not find input {}", input.display()))?;
+            let current = self.record_outcome(&diff.build, &diff.target, &input, &project_dir)?;
+            let previous =
+                baseline.record_outcome(&diff.build, &diff.target, &input, &baseline_dir)?;
+            if current == previous {
+                continue;
+            }
+
+            diverging += 1;
+            let data = fs::read(&input)?;
+            let artifact = self
+                .artifacts_for(&diff.target)?
+                .join(format!("diff-{}", corpus::content_hash(&data)));
+            fs::write(&artifact, &data)
+                .with_context(|| format!("failed to write to {}", artifact.display()))?;
+            println!(
+                "Outcomes differ for {}, saved as {}",
+                strip_current_dir_prefix(&input).display(),
+                strip_current_dir_prefix(&artifact).display()
+            );
+            for line in previous.lines() {
+                println!("  - {}", line);
+            }
+            for line in current.lines() {
+                println!("  + {}", line);
+            }
+        }
+
+        if diverging > 0 {
+            bail!(
+                "{} of {} inputs diverge from baseline {}",
+                diverging,
+                inputs.len(),
+                diff.baseline
+            );
+        }
+        println!(
+            "All {} inputs behave like baseline {}",
+            inputs.len(),
+            diff.baseline
+        );
+        Ok(())
+    }
+
+    /// Returns the package directory of `baseline`: either the directory
+    /// itself or a git worktree of the revision, cached under `target/`.
+    /// Replay the corpus of each target without mutation and report its throughput
+    pub fn exec_bench(&self, bench: &options::Bench) -> Result<()> {
+        let targets = match &bench.target {
+            Some(target) => {
+                self.ensure_target_exists(target)?;
+                vec![target.clone()]
+            }
+            None => self.targets.clone(),
+        };
+        if let Some(dir) = &bench.export_criterion {
+            return self.export_criterion(dir, &targets);
+        }
+        let budget = time::Duration::from_secs(bench.time);
+
+        let mut reports = Vec::new();
+        for target in &targets {
+            let inputs = files_in(&self.corpus_for(target)?)?;
+            if inputs.is_empty() {
+                info!("Skipping {}: its corpus is empty", target);
+                continue;
+            }
+            self.exec_build(BuildMode::Build, &bench.build, Some(target))?;
+
+            // The passes run the fuzz target itself, `cargo run` would check
+            // the build again before each of them.
+            let binary = self.target_binary(&bench.build, target)?;
+            // Input path -> (executions, total milliseconds, slowest milliseconds)
+            let mut timings: HashMap<String, (u64, u64, u64)> = HashMap::new();
+            let mut execs = 0;
+            let start = time::Instant::now();
+            loop {
+                // The last pass only runs the inputs left to `--runs`.
+                let pass = match bench.runs {
+                    Some(runs) => &inputs[..inputs.len().min((runs - execs) as usize)],
+                    None => &inputs[..],
+                };
+                // Passing files makes libFuzzer run each of them once, without mutating them.
+                let mut cmd = Command::new(&binary);
+                self.sanitizer_options(&mut cmd, &bench.build, Some(target))?;
+                self.target_env(&mut cmd, target)?;
+                let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+                artifact_arg.push(self.artifacts_for(target)?);
+                cmd.stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .arg(artifact_arg)
+                    .args(pass);
+                let output = cmd
+                    .output()
+                    .with_context(|| format!("failed to run command: {:?}", cmd))?;
+                if !output.status.success() {
+                    bail!(
+                        "{} failed on its corpus, run `move-fuzz triage {}` first",
+                        target,
+                        target
+                    );
+                }
+                for (input, ms) in String::from_utf8_lossy(&output.stderr)
+                    .lines()
+                    .filter_map(parse_executed)
+                {
+                    let timing = timings.entry(input.to_owned()).or_default();
+                    timing.0 += 1;
+                    timing.1 += ms;
+                    timing.2 = timing.2.max(ms);
+                    execs += 1;
+                }
+                if start.elapsed() >= budget || bench.runs.is_some_and(|runs| execs >= runs) {
+                    break;
+                }
+            }
+            reports.push((target, execs, start.elapsed(), timings));
+        }
+
+        info!("\n{:─<80}\n", "");
+        println!(
+            "{:<24} {:>10} {:>10} {:>12}",
+            "TARGET", "EXECS", "EXECS/S", "AVG INPUT"
+        );
+        for (target, execs, elapsed, timings) in &reports {
+            let total_ms: u64 = timings.values().map(|t| t.1).sum();
+            println!(
+                "{:<24} {:>10} {:>10.1} {:>9.2} ms",
+                target,
+                execs,
+                *execs as f64 / elapsed.as_secs_f64(),
+                total_ms as f64 / (*execs).max(1) as f64
+            );
+        }
+        for (target, _, _, timings) in &reports {
+            let mut slowest: Vec<_> = timings.iter().collect();
+            slowest.sort_by(|a, b| b.1 .2.cmp(&a.1 .2).then_with(|| a.0.cmp(b.0)));
+            println!("\nSlowest corpus entries of {}:", target);
+            for (input, (_, _, max_ms)) in slowest.into_iter().take(bench.slowest) {
+                println!("  {:>8} ms  {}", max_ms, input);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a criterion benchmark crate to `dir` replaying a snapshot of the
+    /// corpora of `targets` through their harnesses, for `bench --export-criterion`.
+    fn export_criterion(&self, dir: &Path, targets: &[String]) -> Result<()> {
+        let mut corpora = Vec::new();
+        for target in targets {
+            let inputs = files_in(&self.corpus_for(target)?)?;
+            if inputs.is_empty() {
+                info!("Skipping {}: its corpus is empty", target);
+            } else {
+                corpora.push((target, inputs));
+            }
+        }
+        if corpora.is_empty() {
+            bail!("no target has a corpus to benchmark");
+        }
+
+        fs::create_dir_all(dir.join("benches"))
+            .with_context(|| format!("could not create {}", dir.display()))?;
+        let dir = dir.canonicalize()?;
+        let fuzz_dir = self.fuzz_dir().canonicalize()?;
+        let project_dir = fuzz_dir.parent().unwrap_or(&fuzz_dir);
+
+        let manifest = self.manifest()?;
+        let package = manifest.get("package").and_then(toml::Value::as_table);
+        let name = package
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+            .context("the fuzz manifest has no package name")?;
+        let edition = package
+            .and_then(|package| package.get("edition"))
+            .and_then(toml::Value::as_str);
+        let dependencies = manifest
+            .get("dependencies")
+            .and_then(toml::Value::as_table)
+            .cloned()
+            .unwrap_or_default();
+        let mut dependencies_toml = toml::value::Table::new();
+        dependencies_toml.insert(
+            "dependencies".to_owned(),
+            toml::Value::Table(bench_dependencies(dependencies, &fuzz_dir, &dir)),
+        );
+
+        let mut cargo_toml = fs::File::create(dir.join("Cargo.toml"))?;
+        cargo_toml.write_fmt(criterion_cargo_toml_template!(
+            name,
+            edition,
+            toml::to_string(&dependencies_toml)?
+        ))?;
+
+        for (target, inputs) in &corpora {
+            let snapshot = dir.join("corpus").join(target);
+            if snapshot.exists() {
+                fs::remove_dir_all(&snapshot)?;
+            }
+            fs::create_dir_all(&snapshot)?;
+            for input in inputs {
+                fs::copy(input, snapshot.join(input.file_name().unwrap_or_default()))
+                    .with_context(|| format!("could not copy {}", input.display()))?;
+            }
+
+            let source = self.fuzz_target(target)?.source.canonicalize()?;
+            let harness = relative_path(&dir.join("benches"), &source);
+            let mut bench = fs::File::create(dir.join("benches").join(format!("{}.rs", target)))?;
+            bench.write_fmt(criterion_bench_template!(
+                target,
+                harness.display(),
+                relative_path(&dir, project_dir).display()
+            ))?;
+            cargo_toml.write_fmt(toml_bench_template!(target))?;
+            info!(
+                "Exported {} with a snapshot of {} corpus entries",
+                target,
+                inputs.len()
+            );
+        }
+        info!(
+            "Wrote the benchmark crate to {}, run it with `cargo bench`",
+            dir.display()
+        );
+        Ok(())
+    }
+
+    /// Package a target, its corpus and the Move package into a container image
+    pub fn exec_docker(&self, docker: &options::Docker) -> Result<()> {
+        let target = &docker.target;
+        self.ensure_target_exists(target)?;
+        self.exec_build(BuildMode::Build, &docker.build, Some(target))?;
+
+        let staging = tempfile::TempDir::new_in(self.fuzz_dir())?;
+        let context = staging.path();
+        fs::copy(
+            self.target_binary(&docker.build, target)?,
+            context.join(target),
+        )
+        .with_context(|| format!("failed to copy the binary of {}", target))?;
+        // The harness loads the Move package from `./fuzz` at runtime.
+        let staged = context.join("fuzz");
+        for name in ["Move.toml", crate::MOVE_TARGETS_DIR, "build"] {
+            let path = self.fuzz_dir().join(name);
+            if path.exists() {
+                copy_recursively(&path, &staged.join(name))?;
+            }
+        }
+        if staged.join("Move.toml").exists() {
+            stage_move_dependencies(
+                self.fuzz_dir(),
+                &staged,
+                "deps",
+                &staged.join("deps"),
+                &mut HashSet::new(),
+            )?;
+        }
+        copy_recursively(&self.corpus_for(target)?, &context.join("corpus"))?;
+
+        // Pin the run configuration in the entrypoint.
+        let mut entrypoint = vec![
+            format!("./{}", target),
+            "-artifact_prefix=artifacts/".to_owned(),
+        ];
+        entrypoint.extend(docker.args.iter().cloned());
+        entrypoint.push("corpus".to_owned());
+        let dockerfile =
+            dockerfile_template!(docker.base, target, serde_json::to_string(&entrypoint)?);
+        fs::write(context.join("Dockerfile"), dockerfile.to_string())?;
+
+        let tag = docker
+            .tag
+            .clone()
+            .unwrap_or_else(|| format!("move-fuzz-{}", target.to_lowercase()));
+        let mut cmd = Command::new("docker");
+        cmd.arg("build").arg("--tag").arg(&tag).arg(context);
+        let status = logging::status(&mut cmd, Source::Docker)?;
+        if !status.success() {
+            bail!("failed to build image {}", tag);
+        }
+        info!("Built image {}", tag);
+
+        if docker.run {
+            let mount = |dir: PathBuf, to: &str| -> Result<String> {
+                Ok(format!("{}:{}", dir.canonicalize()?.display(), to))
+            };
+            let mut cmd = Command::new("docker");
+            cmd.arg("run")
+                .arg("--rm")
+                .arg("--volume")
+                .arg(mount(self.corpus_for(target)?, "/work/corpus")?)
+                .arg("--volume")
+                .arg(mount(self.artifacts_for(target)?, "/work/artifacts")?)
+                .arg(&tag);
+            let status = logging::status(&mut cmd, Source::Docker)?;
+            if !status.success() {
+                info!("Fuzz target exited with {}", status);
+                return Err(Error::crash(target, Vec::new()).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Assign time slices on the targets to workers and merge back their findings
+    pub fn exec_serve(&self, serve: &options::Serve) -> Result<()> {
+        let targets = if serve.targets.is_empty() {
+            self.targets.clone()
+        } else {
+            for target in &serve.targets {
+                self.ensure_target_exists(target)?;
+            }
+            serve.targets.clone()
+        };
+        if targets.is_empty() {
+            bail!("no fuzz targets to hand out");
+        }
+        let mut dirs = HashMap::new();
+        for target in &targets {
+            dirs.insert(
+                target.clone(),
+                (self.corpus_for(target)?, self.artifacts_for(target)?),
+            );
+        }
+        let coordinator = Coordinator {
+            scheduler: cluster::Scheduler::new(targets, serve.slice),
+            token: cluster::read_token(&serve.token_file)?,
+            dirs,
+        };
+
+        let listener = net::TcpListener::bind(&serve.listen)
+            .with_context(|| format!("could not listen on {}", serve.listen))?;
+        info!(
+            "Serving {} targets on {}, start workers with:\n\n\t\
+             move-fuzz worker --token-file {} {}\n",
+            coordinator.scheduler.targets().len(),
+            serve.listen,
+            serve.token_file.display(),
+            serve.listen
+        );
+        // Every worker gets a thread, so one stalling its connection does not
+        // hold up the others; past `max_connections` they are turned away.
+        let connections = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("failed to accept a connection: {}", e);
+                        continue;
+                    }
+                };
+                if connections.fetch_add(1, Ordering::SeqCst) >= serve.max_connections {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    let _ = stream.set_write_timeout(Some(cluster::IO_TIMEOUT));
+                    let _ = cluster::write_response(&stream, 503, b"too many workers");
+                    continue;
+                }
+                let (coordinator, connections) = (&coordinator, &connections);
+                scope.spawn(move || {
+                    if let Err(e) = coordinator.answer(stream) {
+                        warn!("failed to answer a worker: {:#}", e);
+                    }
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        Ok(())
+    }
+
+    /// Fuzz the targets assigned by a coordinator, syncing corpora and crashes with it
+    pub fn exec_worker(&self, worker: &options::Worker) -> Result<()> {
+        let coordinator = worker.coordinator.as_str();
+        let token = cluster::read_token(&worker.token_file)?;
+        let mut built = HashSet::new();
+        for slice in 0.. {
+            if worker.slices == Some(slice) {
+                break;
+            }
+            let assignment: Assignment = serde_json::from_slice(&cluster::request(
+                coordinator,
+                &token,
+                "GET",
+                "/assignment",
+                &[],
+            )?)
+            .context("could not decode the assignment")?;
+            let target = &assignment.target;
+            self.ensure_target_exists(target)?;
+            if built.insert(target.clone()) {
+                self.exec_build(BuildMode::Build, &worker.build, Some(target))?;
+            }
+
+            let corpus_dir = self.corpus_for(target)?;
+            let corpus_path = format!("/corpus/{}", target);
+            for data in cluster::unpack(&cluster::request(
+                coordinator,
+                &token,
+                "GET",
+                &corpus_path,
+                &[],
+            )?)? {
+                corpus::insert(&corpus_dir, &data)?;
+            }
+            let known: HashSet<_> = files_in(&corpus_dir)?.into_iter().collect();
+
+            let before_fuzzing = time::SystemTime::now();
+            let mut cmd = self.cargo_run(&worker.build, target)?;
+            cmd.args(&worker.args)
+                .arg(format!("-max_total_time={}", assignment.seconds))
+                .arg(&corpus_dir);
+            let status =
+                logging::with_prefix(target, || logging::status(&mut cmd, Source::LibFuzzer))?;
+
+            let new_inputs = files_in(&corpus_dir)?
+                .into_iter()
+                .filter(|input| !known.contains(input))
+                .map(fs::read)
+                .collect::<std::io::Result<Vec<_>>>()?;
+            cluster::request(
+                coordinator,
+                &token,
+                "POST",
+                &corpus_path,
+                &cluster::pack(&new_inputs)?,
+            )?;
+            let artifacts = self.get_artifacts_since(target, &before_fuzzing)?;
+            for artifact in &artifacts {
+                let name = artifact
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                cluster::request(
+                    coordinator,
+                    &token,
+                    "POST",
+                    &format!("/crashes/{}/{}", target, name),
+                    &fs::read(artifact)?,
+                )?;
+            }
+            info!(
+                "Finished {} seconds on {} ({}): sent {} new inputs and {} crashes",
+                assignment.seconds,
+                target,
+                status,
+                new_inputs.len(),
+                artifacts.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Aggregate targets, corpora, coverage, crash buckets and run times into a report
+    pub fn exec_report(&self, report: &options::Report) -> Result<()> {
+        let db = CrashDatabase::load(&self.triage_db_path())?;
+        let runs = RunLog::load(&self.run_log_path())?;
+        let events = events::load(&self.events_log_path())?;
+        let package = find_package()?
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut targets = Vec::new();
+        for fuzz_target in self.fuzz_targets()? {
+            let target = &fuzz_target.name;
+            let corpus = if fuzz_target.corpus.is_dir() {
+                files_in(&fuzz_target.corpus)?
+            } else {
+                Vec::new()
+            };
+            let corpus_bytes = corpus
+                .iter()
+                .filter_map(|f| fs::metadata(f).ok())
+                .map(|m| m.len())
+                .sum();
+            let summary = self
+                .fuzz_dir()
+                .join("coverage")
+                .join(target)
+                .join(COVERAGE_SUMMARY_FILE);
+            let coverage = fs::read_to_string(&summary)
+                .ok()
+                .and_then(|data| serde_json::from_str(&data).ok());
+            targets.push(TargetReport {
+                name: target.clone(),
+                corpus_entries: corpus.len(),
+                corpus_bytes,
+                artifacts: self.artifact_files(target)?.len(),
+                coverage,
+                open_crashes: db
+                    .prioritized()
+                    .into_iter()
+                    .filter(|b| &b.target == target && b.status != Status::Fixed)
+                    .map(Into::into)
+                    .collect(),
+                last_run: runs.last_run.get(target).copied(),
+                fuzzing_secs: events
+                    .iter()
+                    .filter(|e| e.kind == EventKind::Run && e.target.as_ref() == Some(target))
+                    .map(|e| e.duration_ms / 1000)
+                    .sum(),
+                crashes_found: events
+                    .iter()
+                    .filter(|e| e.kind == EventKind::Crash && e.target.as_ref() == Some(target))
+                    .count(),
+            });
+        }
+
+        let project_report = ProjectReport {
+            package,
+            generated_at: unix_now(),
+            targets,
+        };
+        let rendered = match report.format {
+            options::ReportFormat::Markdown => project_report.to_markdown(),
+            options::ReportFormat::Html => project_report.to_html(),
+            options::ReportFormat::Json => project_report.to_json()?,
+        };
+        match &report.output {
+            Some(path) => fs::write(path, rendered)
+                .with_context(|| format!("failed to write to {}", path.display()))?,
+            None => print!("{}", rendered),
+        }
+        Ok(())
+    }
+
+    fn baseline_checkout(&self, project_dir: &Path, baseline: &str) -> Result<PathBuf> {
+        let dir = Path::new(baseline);
+        if dir.is_dir() {
+            return dir
+                .canonicalize()
+                .with_context(|| format!("could not resolve {}", dir.display()));
+        }
+
+        // `git -C` resolves the worktree path from the project directory, so
+        // both are made absolute.
+        let project_dir = project_dir
+            .canonicalize()
+            .with_context(|| format!("could not resolve {}", project_dir.display()))?;
+        let git = |args: &[&str]| -> Result<String> {
+            let mut cmd = Command::new("git");
+            cmd.arg("-C").arg(&project_dir).args(args);
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            if !output.status.success() {
+                bail!(
+                    "{:?} failed: {}",
+                    cmd,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+        };
+        let commit = git(&["rev-parse", "--verify", &format!("{}^{{commit}}", baseline)])
+            .with_context(|| format!("{:?} is neither a directory nor a git revision", baseline))?;
+        // The package may live in a subdirectory of the repository.
+        let prefix = git(&["rev-parse", "--show-prefix"])?;
+
+        let fuzz_dir = self.fuzz_dir();
+        let checkout = fuzz_dir
+            .canonicalize()
+            .with_context(|| format!("could not resolve {}", fuzz_dir.display()))?
+            .join("target")
+            .join("baselines")
+            .join(&commit[..12]);
+        if !checkout.exists() {
+            let checkout = checkout.to_string_lossy();
+            git(&["worktree", "add", "--detach", &checkout, &commit])
+                .with_context(|| format!("could not check out baseline {:?}", baseline))?;
+        }
+        Ok(checkout.join(prefix))
+    }
+
+    /// Runs `target` on `input` from `dir` and returns the outcome recorded by the harness.
+    fn record_outcome(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        input: &Path,
+        dir: &Path,
+    ) -> Result<String> {
+        let outcome_file = tempfile::NamedTempFile::new()?;
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.env("MOVE_FUZZ_OUTCOME_PATH", outcome_file.path())
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .arg(input);
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        if !status.success() {
+            return Ok(format!("crashed: {}\n", status));
+        }
+        fs::read_to_string(outcome_file.path())
+            .with_context(|| format!("{} did not record an outcome", target))
+    }
+
+    /// Returns all the artifacts of `target`, sorted by name, without their metadata files.
+    fn artifact_files(&self, target: &str) -> Result<Vec<PathBuf>> {
+        let mut files = files_in(&self.artifacts_for(target)?)?;
+        files.retain(|file| !metadata::is_sidecar(file));
+        Ok(files)
+    }
+
+    fn events_log_path(&self) -> PathBuf {
+        self.fuzz_dir().join(EVENTS_LOG_FILE)
+    }
+
+    /// Where the scheduling state of `target` is kept between runs.
+    fn scheduling_dir_for(&self, target: &str) -> PathBuf {
+        self.fuzz_dir().join(SCHEDULING_DIR).join(target)
+    }
+
+    fn provenance_index(&self, target: &str) -> ProvenanceIndex {
+        ProvenanceIndex::new(
+            self.fuzz_dir()
+                .join(PROVENANCE_DIR)
+                .join(target)
+                .join(PROVENANCE_INDEX_FILE),
+        )
+    }
+
+    /// Records the `origins` of the entries just added to the corpus of
+    /// `target`. Provenance only informs, failing to record it is no failure
+    /// of the command.
+    fn record_provenance(&self, target: &str, origins: Vec<(String, Origin)>) {
+        if let Err(e) = self.provenance_index(target).record(origins) {
+            warn!(
+                "could not record the provenance of the new inputs of {}: {:#}",
+                target, e
+            );
+        }
+    }
+
+    /// Drops from the provenance index of `target` the entries that left
+    /// `corpus`, its corpus.
+    fn prune_provenance(&self, target: &str, corpus: &Path) {
+        let pruned = provenance::entry_names(corpus)
+            .and_then(|names| self.provenance_index(target).retain(&names));
+        if let Err(e) = pruned {
+            warn!(
+                "could not prune the provenance index of {}: {:#}",
+                target, e
+            );
+        }
+    }
+
+    /// Records the origin of the entries added to `corpus`, the corpus of
+    /// `target`, since it held `before`, `origin` telling it from their name.
+    fn record_new_entries(
+        &self,
+        target: &str,
+        corpus: &Path,
+        before: &HashSet<String>,
+        origin: impl Fn(&str) -> Origin,
+    ) {
+        match provenance::entry_names(corpus) {
+            Ok(names) => {
+                let origins = names
+                    .into_iter()
+                    .filter(|name| !before.contains(name))
+                    .map(|name| {
+                        let origin = origin(&name);
+                        (name, origin)
+                    })
+                    .collect();
+                self.record_provenance(target, origins);
+            }
+            Err(e) => warn!(
+                "could not record the provenance of the new inputs of {}: {:#}",
+                target, e
+            ),
+        }
+    }
+
+    /// Where the merges of `target` keep their progress until they are done.
+    fn merge_dir_for(&self, target: &str) -> PathBuf {
+        self.fuzz_dir().join(MERGE_DIR).join(target)
+    }
+
+    /// Where libFuzzer keeps its temporary files while fuzzing `target`.
+    fn work_dir_for(&self, target: &str) -> PathBuf {
+        self.fuzz_dir().join(".tmp").join(target)
+    }
+
+    /// Where `run --concurrent` streams the stats of `target`, and every run
+    /// appends its final stats.
+    fn stats_log_path(&self, target: &str) -> PathBuf {
+        self.fuzz_dir()
+            .join("logs")
+            .join(format!("{}.stats.jsonl", target))
+    }
+
+    fn build_log_path(&self) -> PathBuf {
+        self.fuzz_dir().join(BUILD_LOG_FILE)
+    }
+
+    fn run_log_path(&self) -> PathBuf {
+        self.fuzz_dir().join(RUN_LOG_FILE)
+    }
+
+    fn triage_db_path(&self) -> PathBuf {
+        self.fuzz_dir().join(TRIAGE_DB_FILE)
+    }
+
+    fn fuzz_targets_dir(&self) -> PathBuf {
+        let mut root = self.fuzz_dir().to_owned();
+        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
+            println!(
+                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
+                 Please rename the directory as such. This will become a hard error in the \
+                 future."
+            );
+            root.push(crate::FUZZ_TARGETS_DIR_OLD);
+        } else {
+            root.push(crate::FUZZ_TARGETS_DIR);
+        }
+        root
+    }
+
+    fn move_targets_dir(&self) -> PathBuf {
+        let mut root = self.fuzz_dir().to_owned();
+        root.push(crate::MOVE_TARGETS_DIR);
+        root
+    }
+
+    fn rust_target_path(&self, target: &str) -> PathBuf {
+        let mut root = self.fuzz_targets_dir();
+        root.push(target);
+        root.set_extension("rs");
+        root
+    }
+
+    fn move_target_path(&self, target: &str) -> PathBuf {
+        let mut root = self.move_targets_dir();
+        root.push(target);
+        root.set_extension("move");
+        root
+    }
+
+    /// The names of the fuzz package and of its path dependencies, such as the
+    /// package under test.
+    fn local_packages(&self) -> Result<Vec<String>> {
+        let manifest = self.manifest()?;
+        let mut packages: Vec<String> = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+            .map(str::to_owned)
+            .into_iter()
+            .collect();
+        if let Some(dependencies) = manifest.get("dependencies").and_then(toml::Value::as_table) {
+            for (name, dependency) in dependencies {
+                if dependency.get("path").is_some() {
+                    let package = dependency.get("package").and_then(toml::Value::as_str);
+                    packages.push(package.unwrap_or(name).to_owned());
+                }
+            }
+        }
+        Ok(packages)
+    }
+
+    pub(crate) fn manifest(&self) -> Result<toml::Value> {
+        let filename = self.manifest_path();
+        let mut file = fs::File::open(&filename)
+            .with_context(|| format!("could not read the manifest file: {}", filename.display()))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        toml::from_slice(&data).with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                filename.display()
+            )
+        })
+    }
+
+    // If `fuzz_dir_opt` is `None`, returns a new instance with the default fuzz project
+    // path. Otherwise, returns a new instance with the inner content of `fuzz_dir_opt`.
+    fn manage_initial_instance(fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
+        let project_dir = find_package()?;
+        let fuzz_dir = if let Some(el) = fuzz_dir_opt {
+            el
+        } else {
+            project_dir.join(DEFAULT_FUZZ_DIR)
+        };
+        Ok(FuzzProject {
+            fuzz_dir,
+            targets: Vec::new(),
+            hooks: Hooks::default(),
+            progress: Progress::default(),
+            state_dirs: StateDirs::default(),
+            fuzz_section: RefCell::default(),
+        })
+    }
+
+    fn fuzz_dir_is_default_path(&self) -> bool {
+        self.fuzz_dir.ends_with(DEFAULT_FUZZ_DIR)
+    }
+}
+
+fn sysroot() -> Result<String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc).arg("--print").arg("sysroot").output()?;
+    // Note: We must trim() to remove the `\n` from the end of stdout
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+fn rustlib() -> Result<PathBuf> {
+    let sysroot = sysroot()?;
+    let mut pathbuf = PathBuf::from(sysroot);
+    pathbuf.push("lib");
+    pathbuf.push("rustlib");
+    pathbuf.push(rustc_version::version_meta()?.host);
+    pathbuf.push("bin");
+    Ok(pathbuf)
+}
+
+fn collect_targets(value: &toml::Value) -> Vec<String> {
+    let bins = value
+        .as_table()
+        .and_then(|v| v.get("bin"))
+        .and_then(toml::Value::as_array);
+    let mut bins = if let Some(bins) = bins {
+        bins.iter()
+            .map(|bin| {
+                bin.as_table()
+                    .and_then(|v| v.get("name"))
+                    .and_then(toml::Value::as_str)
+            })
+            .filter_map(|name| name.map(String::from))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    // Always sort them, so that we have deterministic output.
+    bins.sort();
+    bins
+}
+
+/// Whether `name` can name a fuzz target: it names the binary, the Rust and
+/// Move files and the regression tests of the target, so it must be a Rust
+/// identifier.
+fn is_target_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        Some('_') if name.len() > 1 => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The `kind` entry of a Cargo manifest named `name`, e.g. the `bin` of a
+/// fuzz target, whether the entries are `[[bin]]` tables or inline ones.
+fn cargo_target_mut<'a>(
+    manifest: &'a mut Document,
+    kind: &str,
+    name: &str,
+) -> Option<&'a mut dyn TableLike> {
+    let is_named = |entry: &dyn TableLike| entry.get("name").and_then(Item::as_str) == Some(name);
+    match manifest.get_mut(kind)? {
+        Item::ArrayOfTables(tables) => tables
+            .iter_mut()
+            .map(|table| table as &mut dyn TableLike)
+            .find(|table| is_named(&**table)),
+        Item::Value(Value::Array(array)) => array
+            .iter_mut()
+            .filter_map(Value::as_inline_table_mut)
+            .map(|table| table as &mut dyn TableLike)
+            .find(|table| is_named(&**table)),
+        _ => None,
+    }
+}
+
+/// Removes the `kind` entry named `name` from a Cargo manifest, returning
+/// whether it had one.
+fn remove_cargo_target(manifest: &mut Document, kind: &str, name: &str) -> bool {
+    let is_named = |entry: &dyn TableLike| entry.get("name").and_then(Item::as_str) == Some(name);
+    match manifest.get_mut(kind) {
+        Some(Item::ArrayOfTables(tables)) => {
+            let position = tables.iter().position(|table| is_named(table));
+            match position {
+                Some(i) => tables.remove(i),
+                None => return false,
+            }
+        }
+        Some(Item::Value(Value::Array(array))) => {
+            let position = array
+                .iter()
+                .position(|entry| entry.as_inline_table().is_some_and(|t| is_named(t)));
+            match position {
+                Some(i) => drop(array.remove(i)),
+                None => return false,
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Renames the `kind` entry named `name` of a Cargo manifest to `new_name`,
+/// with its source at `path`, returning whether it had one. Its other keys
+/// stay as they were.
+fn rename_cargo_target(
+    manifest: &mut Document,
+    kind: &str,
+    name: &str,
+    new_name: &str,
+    path: &str,
+) -> bool {
+    let Some(entry) = cargo_target_mut(manifest, kind, name) else {
+        return false;
+    };
+    entry.insert("name", toml_edit::value(new_name));
+    entry.insert("path", toml_edit::value(path));
+    true
+}
+
+#[derive(Debug)]
+pub struct Manifest {
+    crate_name: String,
+    edition: Option<String>,
+}
+
+impl Manifest {
+    pub fn parse() -> Result<Self> {
+        let metadata = MetadataCommand::new().no_deps().exec()?;
+        let package = metadata.packages.last().with_context(|| {
+            anyhow!(
+                "Expected to find at least one package in {}",
+                metadata.target_directory
+            )
+        })?;
+        let crate_name = package.name.clone();
+        let edition = Some(String::from(package.edition.as_str()));
+
+        Ok(Manifest {
+            crate_name,
+            edition,
+        })
+    }
+}
+
+fn is_fuzz_manifest(value: &toml::Value) -> bool {
+    let is_fuzz = value
+        .as_table()
+        .and_then(|v| v.get("package"))
+        .and_then(toml::Value::as_table)
+        .and_then(|v| v.get("metadata"))
+        .and_then(toml::Value::as_table)
+        .and_then(|v| v.get("cargo-fuzz"))
+        .and_then(toml::Value::as_bool);
+    is_fuzz == Some(true)
+}
+
+/// Returns the path for the first found non-fuzz Cargo package
+fn find_package() -> Result<PathBuf> {
+    let mut dir = env::current_dir()?;
+    let mut data = Vec::new();
+    loop {
+        let manifest_path = dir.join("Cargo.toml");
+        match fs::File::open(&manifest_path) {
+            Err(_) => {}
+            Ok(mut f) => {
+                data.clear();
+                f.read_to_end(&mut data)
+                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+                let value: toml::Value = toml::from_slice(&data).with_context(|| {
+                    format!(
+                        "could not decode the manifest file at {}",
+                        manifest_path.display()
+                    )
+                })?;
+                if !is_fuzz_manifest(&value) {
+                    // Not a cargo-fuzz project => must be a proper cargo project :)
+                    return Ok(dir);
+                }
+            }
+        }
+        if !dir.pop() {
+            break;
+        }
+    }
+    Err(Error::ProjectNotFound {
+        dir: env::current_dir()?,
+    }
+    .into())
+}
+
+/// The name of the Move package at `package`, from its `Move.toml`.
+fn move_package_name(package: &Path) -> Result<String> {
+    let manifest_path = package.join("Move.toml");
+    let manifest: toml::Value = toml::from_str(
+        &fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?,
+    )
+    .with_context(|| format!("could not decode {}", manifest_path.display()))?;
+    manifest
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(str::to_owned)
+        .with_context(|| format!("{} has no package name", manifest_path.display()))
+}
+
+/// Compares the Move coverage summaries of `baseline` and `new`, for `coverage diff`.
+pub fn exec_coverage_diff(
+    baseline: &Path,
+    new: &Path,
+    format: options::DiffFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let summary = |path: &Path| -> Result<coverage_diff::Summary> {
+        let csv = if path.is_dir() {
+            move_output(path, &["coverage", "summary", "--csv"])?
+        } else {
+            fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?
+        };
+        coverage_diff::Summary::parse_csv(&csv)
+            .with_context(|| format!("{} is not a Move coverage summary", path.display()))
+    };
+    let text = coverage_diff::render(&summary(baseline)?, &summary(new)?, format);
+    match output {
+        Some(output) => {
+            fs::write(output, text)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            info!("Wrote the coverage comparison to {}", output.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Waits for the run of `target` to be asked to stop, by a signal or at
+/// `deadline`, while `running`. Then saves the inputs in flight from `work_dir`
+/// and stops the target, until it exits. Returns whether it was stopped.
+fn watch_shutdown(
+    running: &AtomicBool,
+    target: &str,
+    work_dir: &Path,
+    deadline: Option<time::Instant>,
+) -> bool {
+    let mut stopping = None;
+    while running.load(Ordering::Relaxed) {
+        let timed_out = deadline.is_some_and(|deadline| time::Instant::now() >= deadline);
+        match stopping {
+            None if timed_out || shutdown::requested() => {
+                if timed_out {
+                    info!("Reached the time limit, stopping {}", target);
+                }
+                if let Err(e) = save_in_flight(work_dir) {
+                    warn!("could not save the corpus entries in flight: {:#}", e);
+                }
+                shutdown::terminate(target);
+                stopping = Some(time::Instant::now());
+            }
+            // The target may not have been running yet.
+            Some(since) if since.elapsed() >= time::Duration::from_secs(2) => {
+                shutdown::terminate(target);
+                stopping = Some(time::Instant::now());
+            }
+            _ => {}
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    stopping.is_some()
+}
+
+/// Links the inputs the jobs of `-fork` found but libFuzzer did not merge yet,
+/// in the `C<job>` directories of its temporary directories under `work_dir`,
+/// into [`IN_FLIGHT_DIR`].
+fn save_in_flight(work_dir: &Path) -> Result<()> {
+    let in_flight = work_dir.join(IN_FLIGHT_DIR);
+    for temp in fs::read_dir(work_dir)? {
+        let temp = temp?.path();
+        let is_fork_dir = temp
+            .file_name()
+            .and_then(ffi::OsStr::to_str)
+            .is_some_and(|name| name.starts_with("libFuzzerTemp.FuzzWithFork"));
+        if !is_fork_dir || !temp.is_dir() {
+            continue;
+        }
+        for job in fs::read_dir(&temp)? {
+            let job = job?.path();
+            let is_job_corpus = job
+                .file_name()
+                .and_then(ffi::OsStr::to_str)
+                .and_then(|name| name.strip_prefix('C'))
+                .is_some_and(|n| n.parse::<u32>().is_ok());
+            if !is_job_corpus || !job.is_dir() {
+                continue;
+            }
+            fs::create_dir_all(&in_flight)?;
+            for input in files_in(&job)? {
+                let saved = in_flight.join(input.file_name().unwrap_or_default());
+                if !saved.exists() && fs::hard_link(&input, &saved).is_err() {
+                    fs::copy(&input, &saved)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `move` CLI in `package` and returns what it printed.
+fn move_output(package: &Path, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("move");
+    cmd.args(args).current_dir(package);
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", cmd))?;
+    if !output.status.success() {
+        bail!(
+            "{:?} failed: {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the `.move` files under `dir` and its subdirectories, sorted by path.
+fn move_sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)
+            .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some(ffi::OsStr::new("move")) {
+                sources.push(path);
+            }
+        }
+    }
+    sources.sort();
+    Ok(sources)
+}
+
+/// Where `move build` fetches git dependencies: `MOVE_HOME`, by default
+/// `~/.move`.
+fn move_home() -> PathBuf {
+    match env::var_os("MOVE_HOME") {
+        Some(home) => PathBuf::from(home),
+        None => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".move"),
+    }
+}
+
+/// Returns all the files in `dir`, sorted by name.
+fn files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn remove_file_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Turns an artifact file name such as `crash-0a1b...` into a valid test function name.
+fn regression_test_name(artifact: &Path) -> String {
+    let stem = artifact
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut name: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "input_");
+    }
+    name
+}
+
+/// Whether `ty` is the path of a Rust type, such as `bank::Deposit`, rather
+/// than a Move type, such as `0x1::string::String` or `vector<u8>`.
+fn is_rust_type_path(ty: &str) -> bool {
+    ty.contains("::") && !ty.starts_with("0x")
+}
+
+/// Turns a Move type such as `vector<u64>` into the Rust expression building its
+/// `MoveTypeLayout`, as re-exported by `libfuzzer::run_move::bcs_round_trip`.
+fn move_type_layout(ty: &str) -> Result<String> {
+    let ty = ty.trim();
+    let layout = match ty {
+        "bool" => "MoveTypeLayout::Bool".to_owned(),
+        "u8" => "MoveTypeLayout::U8".to_owned(),
+        "u16" => "MoveTypeLayout::U16".to_owned(),
+        "u32" => "MoveTypeLayout::U32".to_owned(),
+        "u64" => "MoveTypeLayout::U64".to_owned(),
+        "u128" => "MoveTypeLayout::U128".to_owned(),
+        "u256" => "MoveTypeLayout::U256".to_owned(),
+        "address" => "MoveTypeLayout::Address".to_owned(),
+        "signer" => "MoveTypeLayout::Signer".to_owned(),
+        _ => match ty
+            .strip_prefix("vector<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            Some(inner) => format!(
+                "MoveTypeLayout::Vector(Box::new({}))",
+                move_type_layout(inner)?
+            ),
+            None => bail!(
+                "unsupported Move type `{}`, struct layouts must be written by hand: \
+                 pass the path of a Rust type mirroring the struct instead",
+                ty
+            ),
+        },
+    };
+    Ok(layout)
+}
+
+/// The input type of a fuzz target: the type of its `fuzz_target!` closure
+/// argument, or the declared `input` format of a raw target. `None` if the
+/// type is inferred or a raw target declares no format, since raw bytes mean
+/// something else to each harness.
+fn input_type(source: &str, input: Option<&str>) -> Option<String> {
+    let invocation = source.match_indices("fuzz_target").find_map(|(start, _)| {
+        let rest = source[start..].trim_start_matches(|c: char| c.is_alphanumeric() || c == '_');
+        rest.strip_prefix('!')
+    });
+    if let Some(rest) = invocation {
+        let (_, rest) = rest.split_once('|')?;
+        let (param, _) = rest.split_once('|')?;
+        let (_, ty) = param.split_once(':')?;
+        return Some(ty.split_whitespace().collect());
+    }
+    if source.contains("fn rust_fuzzer_test_input(") {
+        return input.map(|input| format!("&[u8] as {}", input));
+    }
+    None
+}
+
+/// Total size of the files under `path`.
+fn disk_usage(path: &Path) -> u64 {
+    match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| disk_usage(&entry.path()))
+            .sum(),
+        Err(_) => fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+    }
+}
+
+fn copy_recursively(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)
+            .with_context(|| format!("failed to create directory {}", to.display()))?;
+        for entry in fs::read_dir(from)
+            .with_context(|| format!("failed to read directory entries of {}", from.display()))?
+        {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(dir) = to.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::copy(from, to)
+            .with_context(|| format!("failed to copy {} to {}", from.display(), to.display()))?;
+    }
+    Ok(())
+}
+
+/// Copies the local Move dependencies of `package`, and theirs, to
+/// `deps/<name>`, pointing the manifest staged in `staged` at the copies as
+/// `<prefix>/<name>`, so that the package resolves without the files around
+/// it, as in a docker image. Dependencies fetched from elsewhere are fetched
+/// again.
+fn stage_move_dependencies(
+    package: &Path,
+    staged: &Path,
+    prefix: &str,
+    deps: &Path,
+    copied: &mut HashSet<String>,
+) -> Result<()> {
+    let manifest = staged.join("Move.toml");
+    for (name, dependency) in move_toml::dependencies(&manifest)? {
+        let move_toml::Dependency::Local(local) = dependency else {
+            warn!(
+                "the Move dependency {} is not local, it is fetched as the target starts",
+                name
+            );
+            continue;
+        };
+        move_toml::set_local_dependency(&manifest, &name, &format!("{}/{}", prefix, name))?;
+        if !copied.insert(name.clone()) {
+            continue;
+        }
+        let from = package.join(&local);
+        let to = deps.join(&name);
+        // Only what resolving the package needs, a package above the fuzz
+        // directory holding it and its builds.
+        for entry in ["Move.toml", "sources", "scripts"] {
+            if from.join(entry).exists() {
+                copy_recursively(&from.join(entry), &to.join(entry))?;
+            }
+        }
+        stage_move_dependencies(&from, &to, "..", deps, copied)?;
+    }
+    Ok(())
+}
+
+/// The directory of the crate `name` of the Move fuzzer, next to the sources
+/// `move-fuzz` was built from.
+fn fuzzer_crate_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(name);
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// The path of `to` relative to the directory `from`, both absolute.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path: PathBuf = from.components().skip(common).map(|_| "..").collect();
+    path.extend(to.components().skip(common));
+    path
+}
+
+/// The `dependencies` of the fuzz manifest in `fuzz_dir` for a crate in `dir`:
+/// path dependencies are made relative to `dir`, and `libfuzzer` no longer links
+/// libFuzzer, whose `main` would take over the benchmarks.
+fn bench_dependencies(
+    mut dependencies: toml::value::Table,
+    fuzz_dir: &Path,
+    dir: &Path,
+) -> toml::value::Table {
+    for (name, dependency) in dependencies.iter_mut() {
+        // `libfuzzer = "x"` needs to be a table to turn off its features.
+        if name == "libfuzzer" && dependency.is_str() {
+            let mut table = toml::value::Table::new();
+            table.insert("version".to_owned(), dependency.clone());
+            *dependency = toml::Value::Table(table);
+        }
+        let Some(dependency) = dependency.as_table_mut() else {
+            continue;
+        };
+        if let Some(relative) = dependency.get("path").and_then(toml::Value::as_str) {
+            let mut path = fuzz_dir.to_owned();
+            for component in Path::new(relative).components() {
+                match component {
+                    Component::ParentDir => {
+                        path.pop();
+                    }
+                    component => path.push(component),
+                }
+            }
+            dependency.insert(
+                "path".to_owned(),
+                toml::Value::String(relative_path(dir, &path).display().to_string()),
+            );
+        }
+        if name == "libfuzzer" {
+            dependency.insert("default-features".to_owned(), toml::Value::Boolean(false));
+        }
+    }
+    dependencies
+}
+
+/// How a revision fares on an input crashing with the `expected` signature,
+/// if known, when it crashes with `signature`. A different crash tells
+/// nothing about the one being bisected.
+fn bisect_verdict(expected: Option<&str>, signature: Option<&str>) -> Verdict {
+    match (expected, signature) {
+        (_, None) => Verdict::Good,
+        (Some(expected), Some(signature)) if expected != signature => Verdict::Skip,
+        (_, Some(_)) => Verdict::Bad,
+    }
+}
+
+/// Parses the `Executed <input> in <ms> ms` line libFuzzer prints after running an input file.
+fn parse_executed(line: &str) -> Option<(&str, u64)> {
+    let (input, ms) = line.strip_prefix("Executed ")?.rsplit_once(" in ")?;
+    Some((input, ms.strip_suffix(" ms")?.parse().ok()?))
+}
+
+/// Warns about the `args` passed through to the engine of `build` that it
+/// does not know or that override the flags `set` by the command.
+fn check_engine_args(build: &BuildOptions, args: &[String], set: &[&str]) {
+    for problem in flags::check(build.cargo_options.engine, args, set) {
+        warn!("{}", problem);
+    }
+}
+
+/// The new `artifacts` of `target` along with their class, renamed after
+/// `template` if there is one. An artifact that cannot be renamed keeps the
+/// name libFuzzer gave it.
+/// The `README.md` of a bug bundle from `process-crash`.
+struct BugReport<'a> {
+    meta: &'a ArtifactMetadata,
+    signature: &'a str,
+    /// Whether the crash opened a new bucket in the triage database
+    new: bool,
+    /// The minimized input
+    input: &'a Path,
+    /// The size of the artifact before minimization
+    original_len: u64,
+    /// The `Debug` formatting of the input
+    debug: Option<&'a str>,
+}
+
+impl fmt::Display for BugReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input = strip_current_dir_prefix(self.input);
+        let len = fs::metadata(self.input).map_or(0, |m| m.len());
+        writeln!(f, "# Crash in fuzz target `{}`\n", self.meta.target)?;
+        writeln!(f, "- Signature: `{}`", self.signature)?;
+        writeln!(
+            f,
+            "- Triage: {} crash bucket",
+            if self.new { "new" } else { "known" }
+        )?;
+        writeln!(
+            f,
+            "- Input: `{}`, {} bytes minimized from {}",
+            input.display(),
+            len,
+            self.original_len
+        )?;
+        for (what, value) in [
+            ("Commit", &self.meta.git_commit),
+            ("Rust", &self.meta.rustc_version),
+            ("Move", &self.meta.move_version),
+        ] {
+            if let Some(value) = value {
+                writeln!(f, "- {}: {}", what, value)?;
+            }
+        }
+        writeln!(f, "\n## Reproduce\n")?;
+        writeln!(
+            f,
+            "```\nmove-fuzz repro{} {} {}\n```",
+            self.meta.build,
+            self.meta.target,
+            input.display()
+        )?;
+        if let Some(debug) = self.debug {
+            writeln!(f, "\n## Input\n\n```\n{}\n```", debug.trim_end())?;
+        }
+        writeln!(
+            f,
+            "\n## Crash report\n\n```\n{}\n```",
+            self.meta.sanitizer_output.trim_end()
+        )
+    }
+}
+
+/// Prints a table of crash buckets, with how reliably they reproduce when
+/// `triage --stability` measured it.
+fn print_buckets(buckets: &[&Bucket]) {
+    if buckets.iter().any(|b| b.stability.is_some()) {
+        println!(
+            "{:<6} {:<24} {:>9} {:>9}  SIGNATURE",
+            "STATUS", "TARGET", "ARTIFACTS", "REPRO"
+        );
+        for bucket in buckets {
+            println!(
+                "{:<6} {:<24} {:>9} {:>9}  {}",
+                bucket.status,
+                bucket.target,
+                bucket.artifacts.len(),
+                bucket
+                    .stability
+                    .map_or_else(|| "-".to_owned(), |s| s.to_string()),
+                bucket.signature
+            );
+        }
+        return;
+    }
+    println!("{:<6} {:<24} {:>9}  SIGNATURE", "STATUS", "TARGET", "ARTIFACTS");
+    for bucket in buckets {
+        println!(
+            "{:<6} {:<24} {:>9}  {}",
+            bucket.status,
+            bucket.target,
+            bucket.artifacts.len(),
+            bucket.signature
+        );
+    }
+}
+
+/// The metadata of a crash of `target` built with `build` and run with
+/// `libfuzzer_args`, from the `tail` of its output. The caller fills in what
+/// the run tells about the artifact itself.
+fn crash_metadata(
+    target: &str,
+    build: &BuildOptions,
+    libfuzzer_args: Vec<String>,
+    tail: &VecDeque<String>,
+) -> ArtifactMetadata {
+    let info = BuildInfo::current(build.to_string());
+    ArtifactMetadata {
+        target: target.to_owned(),
+        class: None,
+        timestamp: unix_now(),
+        git_commit: info.git_commit,
+        build: build.clone(),
+        libfuzzer_args,
+        sanitizer_output: metadata::crash_report(tail),
+        rustc_version: info.rustc_version,
+        move_version: info.move_version,
+        elapsed_ms: None,
+        peak_rss_mb: None,
+        backtrace: backtrace::capture(tail.iter().map(String::as_str)),
+    }
+}
+
+fn name_artifacts(
+    target: &str,
+    template: Option<&NameTemplate>,
+    artifacts: HashSet<PathBuf>,
+) -> Vec<(PathBuf, Option<String>)> {
+    let timestamp = unix_now();
+    let mut named: Vec<_> = artifacts
+        .into_iter()
+        .map(|artifact| {
+            let class = artifact
+                .file_name()
+                .and_then(|name| metadata::libfuzzer_class(&name.to_string_lossy()))
+                .map(str::to_owned);
+            let Some(template) = template else {
+                return (artifact, class);
+            };
+            match rename_artifact(&artifact, template, target, class.as_deref(), timestamp) {
+                Ok(renamed) => (renamed, class),
+                Err(e) => {
+                    warn!("could not rename {}: {:#}", artifact.display(), e);
+                    (artifact, class)
+                }
+            }
+        })
+        .collect();
+    named.sort();
+    named
+}
+
+fn rename_artifact(
+    artifact: &Path,
+    template: &NameTemplate,
+    target: &str,
+    class: Option<&str>,
+    timestamp: u64,
+) -> Result<PathBuf> {
+    let data =
+        fs::read(artifact).with_context(|| format!("failed to read {}", artifact.display()))?;
+    let dir = artifact.parent().unwrap_or(Path::new(""));
+    // libFuzzer names artifacts after the SHA-1 of the input, so an input found
+    // again overwrites its artifact. Renamed ones are looked up by content to
+    // keep it that way.
+    if let Some(existing) = same_input(dir, artifact, &data)? {
+        fs::remove_file(artifact)
+            .with_context(|| format!("failed to remove {}", artifact.display()))?;
+        return Ok(existing);
+    }
+    let name = template.render(target, class, &data, timestamp);
+    let mut renamed = dir.join(&name);
+    // Inputs found in the same second get the same name without `{hash}`.
+    let mut n = 1;
+    while renamed.exists() && renamed != artifact {
+        renamed = dir.join(format!("{}-{}", name, n));
+        n += 1;
+    }
+    fs::rename(artifact, &renamed).with_context(|| {
+        format!(
+            "failed to rename {} to {}",
+            artifact.display(),
+            renamed.display()
+        )
+    })?;
+    Ok(renamed)
+}
+
+/// The artifact in `dir` other than `artifact` whose input is `data`.
+fn same_input(dir: &Path, artifact: &Path, data: &[u8]) -> Result<Option<PathBuf>> {
+    for file in files_in(dir)? {
+        if file == artifact || metadata::is_sidecar(&file) {
+            continue;
+        }
+        if fs::metadata(&file).is_ok_and(|m| m.len() == data.len() as u64)
+            && fs::read(&file).with_context(|| format!("failed to read {}", file.display()))?
+                == data
+        {
+            return Ok(Some(file));
+        }
+    }
+    Ok(None)
+}
+
+/// Decodes the text `encoding` of an input, read from `input` or stdin if it
+/// is `-`, writing the input to `output`, or stdout if it is not a terminal.
+fn decode_input(
+    encoding: options::InputEncoding,
+    input: &Path,
+    output: Option<&Path>,
+) -> Result<()> {
+    let text = if input == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("failed to read stdin")?;
+        text
+    } else {
+        fs::read_to_string(input).with_context(|| format!("failed to read {}", input.display()))?
+    };
+    let data = encoding::decode(encoding, &text)
+        .with_context(|| format!("could not decode {}", input.display()))?;
+    match output {
+        Some(output) => fs::write(output, &data)
+            .with_context(|| format!("failed to write to {}", output.display())),
+        None if std::io::stdout().is_terminal() => {
+            bail!("not writing a binary input to a terminal, pass `--output` or redirect stdout")
+        }
+        None => Ok(std::io::stdout().write_all(&data)?),
+    }
+}
+
+/// The state `move-fuzz serve` answers workers from, shared by the threads
+/// answering them.
+struct Coordinator {
+    scheduler: cluster::Scheduler,
+    token: String,
+    /// The corpus and artifacts directories of the targets handed out
+    dirs: HashMap<String, (PathBuf, PathBuf)>,
+}
+
+impl Coordinator {
+    fn answer(&self, stream: net::TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(cluster::IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(cluster::IO_TIMEOUT))?;
+        let request = match cluster::read_request(&stream) {
+            Ok(request) => request,
+            Err(e) => return cluster::write_response(&stream, 400, format!("{:#}", e).as_bytes()),
+        };
+        if !request.is_authorized(&self.token) {
+            let peer = stream.peer_addr().map(|addr| addr.to_string());
+            warn!(
+                "Refused {} {} from {}: wrong token",
+                request.method,
+                request.path,
+                peer.unwrap_or_default()
+            );
+            return cluster::write_response(&stream, 401, b"wrong token");
+        }
+        match self.handle(&request) {
+            Ok(body) => cluster::write_response(&stream, 200, &body),
+            Err(e) => cluster::write_response(&stream, 400, format!("{:#}", e).as_bytes()),
+        }
+    }
+
+    fn handle(&self, request: &cluster::Request) -> Result<Vec<u8>> {
+        let segments = request.segments();
+        let known = |target: &str| self.dirs.contains_key(target);
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["assignment"]) => {
+                let assignment = self.scheduler.assign();
+                info!(
+                    "Assigned {} for {} seconds",
+                    assignment.target, assignment.seconds
+                );
+                Ok(serde_json::to_vec(&assignment)?)
+            }
+            ("GET", ["corpus", target]) if known(target) => {
+                let inputs = files_in(&self.dirs[*target].0)?
+                    .iter()
+                    .map(fs::read)
+                    .collect::<std::io::Result<Vec<_>>>()?;
+                cluster::pack(&inputs)
+            }
+            ("POST", ["corpus", target]) if known(target) => {
+                let corpus_dir = &self.dirs[*target].0;
+                let mut added = 0;
+                for data in cluster::unpack(&request.body)? {
+                    if corpus::insert(corpus_dir, &data)?.is_some() {
+                        added += 1;
+                    }
+                }
+                info!("Received {} new inputs for {}", added, target);
+                Ok(added.to_string().into_bytes())
+            }
+            ("POST", ["crashes", target, name]) if known(target) => {
+                // Names come from the network: keep them inside the artifacts directory.
+                if name.starts_with('.')
+                    || !name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+                {
+                    bail!("invalid artifact name {}", name);
+                }
+                let artifact = self.dirs[*target].1.join(name);
+                fs::write(&artifact, &request.body)
+                    .with_context(|| format!("failed to write to {}", artifact.display()))?;
+                info!(
+                    "Received crash for {}: {}",
+                    target,
+                    strip_current_dir_prefix(&artifact).display()
+                );
+                Ok(Vec::new())
+            }
+            _ => bail!("unexpected request {} {}", request.method, request.path),
+        }
+    }
+}
+
+/// The regression tests of `tests` with the places naming the target, from
+/// `regression_file_template!`, naming `new_name` instead of `target`.
+fn rename_in_regressions(tests: &str, target: &str, new_name: &str) -> String {
+    let places = |name: &str| {
+        [
+            format!("`{}` fuzz target", name),
+            format!("CARGO_BIN_EXE_{}\")", name),
+            format!("\"{}-{{}}\"", name),
+        ]
+    };
+    places(target)
+        .iter()
+        .zip(places(new_name))
+        .fold(tests.to_owned(), |tests, (from, to)| {
+            tests.replace(from, &to)
+        })
+}
+
+fn strip_current_dir_prefix(path: &Path) -> &Path {
+    env::current_dir()
+        .ok()
+        .and_then(|curdir| path.strip_prefix(curdir).ok())
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MANIFEST: &str = "[package]\nname = \"x-fuzz\"\n\n[workspace]\n\n\
+                            [[bin]]\nname = \"a\"\npath = \"fuzz_targets/a.rs\"\n\n\
+                            [[bin]]\nname = \"b\"\npath = \"fuzz_targets/b.rs\"\n";
+
+    #[test]
+    fn remove_bin_entry() {
+        let mut manifest: Document = MANIFEST.parse().unwrap();
+        assert!(remove_cargo_target(&mut manifest, "bin", "a"));
+        assert_eq!(
+            manifest.to_string(),
+            "[package]\nname = \"x-fuzz\"\n\n[workspace]\n\n\
+             [[bin]]\nname = \"b\"\npath = \"fuzz_targets/b.rs\"\n"
+        );
+
+        let mut manifest: Document = MANIFEST.parse().unwrap();
+        assert!(remove_cargo_target(&mut manifest, "bin", "b"));
+        assert_eq!(
+            manifest.to_string(),
+            "[package]\nname = \"x-fuzz\"\n\n[workspace]\n\n\
+             [[bin]]\nname = \"a\"\npath = \"fuzz_targets/a.rs\"\n"
+        );
 
-        eprintln!("Merging raw coverage data...");
-        let status = merge_cmd
-            .status()
-            .with_context(|| format!("Failed to run command: {:?}", merge_cmd))
-            .with_context(|| "Merging raw coverage files failed.\n\
-                              \n\
-                              Do you have LLVM coverage tools installed?\n\
-                              https://doc.rust-lang.org/rustc/instrument-coverage.html#installing-llvm-coverage-tools")?;
-        if !status.success() {
-            Err(anyhow!(
-                "Command exited with failure status {}: {:?}",
-                status,
-                merge_cmd
-            ))
-            .context("Merging raw coverage files failed")?;
+        let mut manifest: Document = MANIFEST.parse().unwrap();
+        assert!(!remove_cargo_target(&mut manifest, "bin", "c"));
+        assert_eq!(manifest.to_string(), MANIFEST);
+    }
+
+    #[test]
+    fn remove_bin_entry_with_comments_and_keys_in_any_order() {
+        let mut manifest: Document = "[[bin]]\npath = \"fuzz_targets/a.rs\" # the bank\n\
+                                      name = \"a\"\n\n\
+                                      # The vault\n[[bin]]\nname = \"b\"\n"
+            .parse()
+            .unwrap();
+        assert!(remove_cargo_target(&mut manifest, "bin", "a"));
+        assert_eq!(
+            manifest.to_string(),
+            "\n# The vault\n[[bin]]\nname = \"b\"\n"
+        );
+    }
+
+    #[test]
+    fn remove_inline_bin_entry() {
+        let manifest = "bin = [\n  { name = \"a\", path = \"fuzz_targets/a.rs\" },\n  \
+                        { name = \"b\", path = \"fuzz_targets/b.rs\" },\n]\n";
+        let mut manifest: Document = manifest.parse().unwrap();
+        assert!(remove_cargo_target(&mut manifest, "bin", "a"));
+        let value: toml::Value = toml::from_str(&manifest.to_string()).unwrap();
+        assert_eq!(collect_targets(&value), ["b"]);
+    }
+
+    fn clean(args: &[&str]) -> options::Clean {
+        <options::Clean as clap::Parser>::parse_from(["clean"].iter().chain(args))
+    }
+
+    /// A project in `dir` of the targets of the fuzz `manifest`.
+    fn project(dir: &Path, manifest: &str) -> FuzzProject {
+        fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+        FuzzProject {
+            fuzz_dir: dir.to_owned(),
+            targets: collect_targets(&toml::from_str(manifest).unwrap()),
+            hooks: Hooks::default(),
+            progress: Progress::default(),
+            state_dirs: StateDirs::default(),
+            fuzz_section: RefCell::default(),
         }
+    }
 
-        if profdata_out_path.exists() {
-            eprintln!("Coverage data merged and saved in {:?}.", profdata_out_path);
-            Ok(())
-        } else {
-            bail!("Coverage data could not be merged.")
+    /// A project of the targets `a` and `b` in `dir`, with all of its state.
+    fn built_project(dir: &Path) -> FuzzProject {
+        for state in [
+            "corpus/a",
+            "artifacts/a",
+            "artifacts/b",
+            "build",
+            "target/x86_64/release",
+        ] {
+            fs::create_dir_all(dir.join(state)).unwrap();
         }
+        fs::write(dir.join("target/x86_64/release/a"), "").unwrap();
+        project(dir, MANIFEST)
     }
 
-    pub(crate) fn fuzz_dir(&self) -> &Path {
-        &self.fuzz_dir
+    #[test]
+    fn clean_keeps_the_corpus() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = built_project(dir.path());
+        let target_dir = dir.path().join("target");
+        let target_dir = target_dir.to_str().unwrap();
+        let paths = project
+            .clean_paths(&clean(&["--target-dir", target_dir]))
+            .unwrap();
+        assert!(paths.contains(&dir.path().join("artifacts")));
+        assert!(paths.contains(&dir.path().join("build")));
+        assert!(!paths.contains(&dir.path().join("corpus")));
     }
 
-    fn manifest_path(&self) -> PathBuf {
-        self.fuzz_dir().join("Cargo.toml")
+    #[test]
+    fn clean_the_state_of_one_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = built_project(dir.path());
+        assert_eq!(
+            project.clean_paths(&clean(&["--artifacts", "b"])).unwrap(),
+            [dir.path().join("artifacts/b")]
+        );
     }
 
-    /// Returns paths to the `coverage/<target>/raw` directory and `coverage/<target>/coverage.profdata` file.
-    fn coverage_for(&self, target: &str) -> Result<(PathBuf, PathBuf)> {
-        let mut coverage_data = self.fuzz_dir().to_owned();
-        coverage_data.push("coverage");
-        coverage_data.push(target);
-        let mut coverage_raw = coverage_data.clone();
-        coverage_data.push("coverage.profdata");
-        coverage_raw.push("raw");
-        fs::create_dir_all(&coverage_raw).with_context(|| {
-            format!("could not make a coverage directory at {:?}", coverage_raw)
-        })?;
-        Ok((coverage_raw, coverage_data))
+    #[test]
+    fn clean_only_the_binaries_from_a_custom_target_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = built_project(dir.path());
+        let target_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(target_dir.path().join("x86_64/debug")).unwrap();
+        fs::write(target_dir.path().join("x86_64/debug/a"), "").unwrap();
+        fs::write(target_dir.path().join("x86_64/debug/other"), "").unwrap();
+        let paths = project
+            .clean_paths(&clean(&[
+                "--builds",
+                "--target-dir",
+                target_dir.path().to_str().unwrap(),
+            ]))
+            .unwrap();
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("build"),
+                target_dir.path().join("x86_64/debug/a")
+            ]
+        );
     }
 
-    fn corpus_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.fuzz_dir().to_owned();
-        p.push("corpus");
-        p.push(target);
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
-        Ok(p)
+    #[test]
+    fn stage_local_move_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, contents: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        };
+        write(
+            "bank/Move.toml",
+            "[dependencies]\noracle = { local = \"../oracle\" }\n",
+        );
+        write("bank/sources/bank.move", "");
+        write(
+            "bank/fuzz/Move.toml",
+            "[dependencies]\nbank = { local = \"..\" }\n",
+        );
+        write("oracle/Move.toml", "[dependencies]\n");
+        write("oracle/sources/oracle.move", "");
+        let staged = dir.path().join("staged");
+        write(
+            "staged/Move.toml",
+            "[dependencies]\nbank = { local = \"..\" }\n",
+        );
+
+        let fuzz_dir = dir.path().join("bank/fuzz");
+        let deps = staged.join("deps");
+        stage_move_dependencies(&fuzz_dir, &staged, "deps", &deps, &mut HashSet::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(staged.join("Move.toml")).unwrap(),
+            "[dependencies]\nbank = { local = \"deps/bank\" }\n"
+        );
+        assert_eq!(
+            fs::read_to_string(deps.join("bank/Move.toml")).unwrap(),
+            "[dependencies]\noracle = { local = \"../oracle\" }\n"
+        );
+        assert!(deps.join("bank/sources/bank.move").exists());
+        assert!(!deps.join("bank/fuzz").exists());
+        assert!(deps.join("oracle/sources/oracle.move").exists());
     }
 
-    fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.fuzz_dir().to_owned();
-        p.push("artifacts");
-        p.push(target);
+    #[test]
+    fn rename_an_artifact_found_again_to_the_first_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = NameTemplate::parse("{class}-{timestamp}").unwrap();
+        let first = dir.path().join("crash-a9993e36");
+        fs::write(&first, b"abc").unwrap();
+        let first = rename_artifact(&first, &template, "t", Some("crash"), 1).unwrap();
+        assert_eq!(first, dir.path().join("crash-1"));
+
+        let again = dir.path().join("crash-a9993e36");
+        fs::write(&again, b"abc").unwrap();
+        assert_eq!(
+            rename_artifact(&again, &template, "t", Some("crash"), 2).unwrap(),
+            first
+        );
+        assert!(!again.exists());
 
-        // This adds a trailing slash, which is necessary for libFuzzer, because
-        // it does simple string concatenation when joining paths.
-        p.push("");
+        let other = dir.path().join("crash-589c2233");
+        fs::write(&other, b"abd").unwrap();
+        assert_eq!(
+            rename_artifact(&other, &template, "t", Some("crash"), 1).unwrap(),
+            dir.path().join("crash-1-1")
+        );
+    }
 
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+    #[test]
+    fn remove_regression_entry() {
+        let with_tests = format!("{}{}", MANIFEST, toml_regression_test_template!("a"));
+        let mut manifest: Document = with_tests.parse().unwrap();
+        assert!(!remove_cargo_target(&mut manifest, "test", "b_regressions"));
+        assert!(remove_cargo_target(&mut manifest, "test", "a_regressions"));
+        assert_eq!(manifest.to_string(), MANIFEST);
+    }
 
-        Ok(p)
+    #[test]
+    fn rename_regression_tests() {
+        let tests = format!(
+            "{}{}",
+            regression_file_template!("deposit"),
+            regression_test_template!("crash_0af3", "artifacts/deposit/crash-0af3", "")
+        );
+        let renamed = rename_in_regressions(&tests, "deposit", "deposit_v2");
+        assert_eq!(
+            renamed,
+            regression_file_template!("deposit_v2").to_string()
+                + &regression_test_template!("crash_0af3", "artifacts/deposit/crash-0af3", "")
+                    .to_string()
+        );
     }
 
-    fn fuzz_targets_dir(&self) -> PathBuf {
-        let mut root = self.fuzz_dir().to_owned();
-        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
-            println!(
-                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
-                 Please rename the directory as such. This will become a hard error in the \
-                 future."
-            );
-            root.push(crate::FUZZ_TARGETS_DIR_OLD);
-        } else {
-            root.push(crate::FUZZ_TARGETS_DIR);
-        }
-        root
+    #[test]
+    fn enumerate_fuzz_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = MANIFEST.replace("fuzz_targets/b.rs", "harnesses/b.rs");
+        let project = project(dir.path(), &manifest);
+        fs::create_dir(dir.path().join(crate::MOVE_TARGETS_DIR)).unwrap();
+        fs::write(dir.path().join("sources/a.move"), "").unwrap();
+
+        let targets = project.fuzz_targets().unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].name, "a");
+        assert_eq!(
+            targets[0].move_source,
+            Some(dir.path().join("sources/a.move"))
+        );
+        assert_eq!(targets[0].corpus, dir.path().join("corpus/a"));
+        assert_eq!(targets[1].source, dir.path().join("harnesses/b.rs"));
+        assert_eq!(targets[1].move_source, None);
+        assert_eq!(targets[1].manifest["path"].as_str(), Some("harnesses/b.rs"));
+        assert!(!dir.path().join("corpus").exists());
     }
 
-    fn move_targets_dir(&self) -> PathBuf {
-        let mut root = self.fuzz_dir().to_owned();
-        root.push(crate::MOVE_TARGETS_DIR);
-        root
+    #[test]
+    fn unbuilt_targets_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project(dir.path(), MANIFEST);
+        let targets = project.fuzz_targets().unwrap();
+        let build = BuildOptions::default();
+        assert!(project.is_stale(&targets[0], &build).unwrap());
     }
 
-    fn rust_target_path(&self, target: &str) -> PathBuf {
-        let mut root = self.fuzz_targets_dir();
-        root.push(target);
-        root.set_extension("rs");
-        root
+    #[test]
+    fn corpus_dir_holds_the_corpora() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = tempfile::tempdir().unwrap();
+        let project = project(dir.path(), MANIFEST).with_state_dirs(&StateDirs {
+            corpus_dir: Some(shared.path().join("corpora")),
+            artifact_dir: None,
+        });
+        let targets = project.fuzz_targets().unwrap();
+        assert_eq!(targets[0].corpus, shared.path().join("corpora/a"));
+        assert_eq!(targets[0].artifacts, dir.path().join("artifacts/a"));
     }
 
-    fn move_target_path(&self, target: &str) -> PathBuf {
-        let mut root = self.move_targets_dir();
-        root.push(target);
-        root.set_extension("move");
-        root
+    #[test]
+    fn find_inputs_in_the_corpus_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = tempfile::tempdir().unwrap();
+        let project = project(dir.path(), MANIFEST).with_state_dirs(&StateDirs {
+            corpus_dir: Some(shared.path().join("corpora")),
+            artifact_dir: None,
+        });
+        fs::create_dir_all(shared.path().join("corpora/a")).unwrap();
+        fs::write(shared.path().join("corpora/a/seed"), "").unwrap();
+        assert_eq!(
+            project.find_input("a", Path::new("seed")),
+            shared.path().join("corpora/a/seed")
+        );
     }
 
-    fn manifest(&self) -> Result<toml::Value> {
-        let filename = self.manifest_path();
-        let mut file = fs::File::open(&filename)
-            .with_context(|| format!("could not read the manifest file: {}", filename.display()))?;
-        let mut data = Vec::new();
-        file.read_to_end(&mut data)?;
-        toml::from_slice(&data).with_context(|| {
+    #[test]
+    fn restricting_the_targets_restricts_them_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut project = project(dir.path(), MANIFEST);
+        // As `with_tags` does.
+        project.targets.retain(|target| target == "b");
+        let targets = project.fuzz_targets().unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].name, "b");
+    }
+
+    #[test]
+    fn regression_names() {
+        assert_eq!(
+            regression_test_name(Path::new("fuzz/artifacts/t/crash-0aF3")),
+            "crash_0af3"
+        );
+        assert_eq!(regression_test_name(Path::new("1.bin")), "input_1_bin");
+    }
+
+    #[test]
+    fn bug_bundle_readme() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("crash-1");
+        fs::write(&input, b"ab").unwrap();
+        let meta = ArtifactMetadata {
+            target: "t".to_owned(),
+            class: Some("crash".to_owned()),
+            timestamp: 0,
+            git_commit: Some("abc123".to_owned()),
+            build: BuildOptions::default(),
+            libfuzzer_args: vec![],
+            sanitizer_output: "thread '<unnamed>' panicked at src/lib.rs:3:5:\nboom\n".to_owned(),
+            rustc_version: None,
+            move_version: None,
+            elapsed_ms: None,
+            peak_rss_mb: None,
+            backtrace: None,
+        };
+        let report = BugReport {
+            meta: &meta,
+            signature: "panic at src/lib.rs:3:5: boom",
+            new: true,
+            input: &input,
+            original_len: 40,
+            debug: Some("[1, 2]\n"),
+        };
+        assert_eq!(
+            report.to_string(),
             format!(
-                "could not decode the manifest file at {}",
-                filename.display()
+                "# Crash in fuzz target `t`\n\n\
+                - Signature: `panic at src/lib.rs:3:5: boom`\n\
+                - Triage: new crash bucket\n\
+                - Input: `{input}`, 2 bytes minimized from 40\n\
+                - Commit: abc123\n\n\
+                ## Reproduce\n\n\
+                ```\nmove-fuzz repro{build} t {input}\n```\n\n\
+                ## Input\n\n```\n[1, 2]\n```\n\n\
+                ## Crash report\n\n\
+                ```\nthread '<unnamed>' panicked at src/lib.rs:3:5:\nboom\n```\n",
+                input = input.display(),
+                build = BuildOptions::default()
             )
-        })
+        );
     }
 
-    // If `fuzz_dir_opt` is `None`, returns a new instance with the default fuzz project
-    // path. Otherwise, returns a new instance with the inner content of `fuzz_dir_opt`.
-    fn manage_initial_instance(fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
-        let project_dir = find_package()?;
-        let fuzz_dir = if let Some(el) = fuzz_dir_opt {
-            el
-        } else {
-            project_dir.join(DEFAULT_FUZZ_DIR)
-        };
-        Ok(FuzzProject {
-            fuzz_dir,
-            targets: Vec::new(),
-        })
+    #[test]
+    fn executed_lines() {
+        assert_eq!(
+            parse_executed("Executed fuzz/corpus/t/in put in 12 ms"),
+            Some(("fuzz/corpus/t/in put", 12))
+        );
+        assert_eq!(parse_executed("Running: fuzz/corpus/t/a"), None);
+        assert_eq!(parse_executed("Executed a in many ms"), None);
     }
 
-    fn fuzz_dir_is_default_path(&self) -> bool {
-        self.fuzz_dir.ends_with(DEFAULT_FUZZ_DIR)
+    #[test]
+    fn input_types() {
+        let script = "use libfuzzer::fuzz_target;\n\
+                      fuzz_target!(|data: Vec< u8 >| {\n    vec![Box::new(data)]\n});\n";
+        assert_eq!(input_type(script, None).as_deref(), Some("Vec<u8>"));
+        let stateful = "fuzz_target_stateful!(reset_every = 10_000, |data| { data });";
+        assert_eq!(input_type(stateful, None), None);
+        let raw = "pub extern \"C\" fn rust_fuzzer_test_input(data: &[u8]) -> i32 { 0 }";
+        assert_eq!(input_type(raw, None), None);
+        assert_eq!(
+            input_type(raw, Some("bytecode")).as_deref(),
+            Some("&[u8] as bytecode")
+        );
     }
-}
 
-fn sysroot() -> Result<String> {
-    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
-    let output = Command::new(rustc).arg("--print").arg("sysroot").output()?;
-    // Note: We must trim() to remove the `\n` from the end of stdout
-    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
-}
+    #[test]
+    fn relative_paths() {
+        assert_eq!(
+            relative_path(Path::new("/p/fuzz"), Path::new("/p/pkg")),
+            Path::new("../pkg")
+        );
+        assert_eq!(
+            relative_path(Path::new("/p/fuzz"), Path::new("/p/fuzz/a")),
+            Path::new("a")
+        );
+    }
 
-fn rustlib() -> Result<PathBuf> {
-    let sysroot = sysroot()?;
-    let mut pathbuf = PathBuf::from(sysroot);
-    pathbuf.push("lib");
-    pathbuf.push("rustlib");
-    pathbuf.push(rustc_version::version_meta()?.host);
-    pathbuf.push("bin");
-    Ok(pathbuf)
-}
+    #[test]
+    fn save_job_corpora_in_flight() {
+        let work = tempfile::tempdir().unwrap();
+        let fork = work.path().join("libFuzzerTemp.FuzzWithFork42.dir");
+        for (dir, input) in [("C1", "a1"), ("C2", "b2"), ("C2", "a1"), ("F1", "f1")] {
+            fs::create_dir_all(fork.join(dir)).unwrap();
+            fs::write(fork.join(dir).join(input), input).unwrap();
+        }
+        fs::write(fork.join("merge.txt"), "").unwrap();
+
+        save_in_flight(work.path()).unwrap();
+        let saved: Vec<_> = files_in(&work.path().join(IN_FLIGHT_DIR))
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(saved, ["a1", "b2"]);
+        // Saving again, once libFuzzer removed its directory, keeps them.
+        fs::remove_dir_all(&fork).unwrap();
+        save_in_flight(work.path()).unwrap();
+        assert_eq!(files_in(&work.path().join(IN_FLIGHT_DIR)).unwrap().len(), 2);
+    }
 
-fn collect_targets(value: &toml::Value) -> Vec<String> {
-    let bins = value
-        .as_table()
-        .and_then(|v| v.get("bin"))
-        .and_then(toml::Value::as_array);
-    let mut bins = if let Some(bins) = bins {
-        bins.iter()
-            .map(|bin| {
-                bin.as_table()
-                    .and_then(|v| v.get("name"))
-                    .and_then(toml::Value::as_str)
-            })
-            .filter_map(|name| name.map(String::from))
-            .collect()
-    } else {
-        Vec::new()
-    };
-    // Always sort them, so that we have deterministic output.
-    bins.sort();
-    bins
-}
+    #[test]
+    fn pinned_builds() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project(dir.path(), MANIFEST);
+        fs::write(
+            dir.path().join("Move.toml"),
+            "[fuzz.targets.b.build]\nsanitizer = \"memory\"\nfeatures = [\"testing\"]\n",
+        )
+        .unwrap();
+        let build = BuildOptions::builder()
+            .cargo_options(
+                CargoBuildOptions::builder()
+                    .features("extra".to_owned())
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(project.build_for(&build, "a").unwrap(), build);
+        let pinned = project.build_for(&build, "b").unwrap();
+        assert_eq!(pinned.cargo_options.sanitizer, Sanitizer::Memory);
+        assert_eq!(
+            pinned.cargo_options.features.as_deref(),
+            Some("extra,testing")
+        );
+        let base = project
+            .target_dir(&build)
+            .unwrap()
+            .unwrap_or_else(|| dir.path().join("target"));
+        let pinned_dir = base.join("pinned").join("memory-extra+testing");
+        assert_eq!(
+            pinned.target_dir.as_deref(),
+            Some(pinned_dir.to_str().unwrap())
+        );
+        assert_eq!(project.build_for(&pinned, "b").unwrap(), pinned);
+        assert!(project
+            .target_binary(&build, "b")
+            .unwrap()
+            .starts_with(&pinned_dir));
+    }
 
-#[derive(Debug)]
-pub struct Manifest {
-    crate_name: String,
-    edition: Option<String>,
-}
+    #[test]
+    fn bench_dependency_paths() {
+        let dependencies: toml::value::Table = toml::from_str(
+            r#"
+            libfuzzer = { path = "/p/libfuzzer", features = ["table-extension"] }
+            move-arbitrary = { path = "/p/move-arbitrary" }
+            bcs = "0.1"
+            "#,
+        )
+        .unwrap();
+        let dependencies = bench_dependencies(
+            dependencies,
+            Path::new("/p/pkg/fuzz"),
+            Path::new("/p/bench"),
+        );
+        assert_eq!(
+            dependencies["libfuzzer"]["path"].as_str(),
+            Some("../libfuzzer")
+        );
+        assert_eq!(
+            dependencies["libfuzzer"]["default-features"].as_bool(),
+            Some(false)
+        );
+        assert!(dependencies["libfuzzer"]["features"].is_array());
+        assert!(dependencies["move-arbitrary"]
+            .get("default-features")
+            .is_none());
+        assert_eq!(dependencies["bcs"].as_str(), Some("0.1"));
+    }
 
-impl Manifest {
-    pub fn parse() -> Result<Self> {
-        let metadata = MetadataCommand::new().no_deps().exec()?;
-        let package = metadata.packages.last().with_context(|| {
-            anyhow!(
-                "Expected to find at least one package in {}",
-                metadata.target_directory
-            )
-        })?;
-        let crate_name = package.name.clone();
-        let edition = Some(String::from(package.edition.as_str()));
+    #[test]
+    fn bisect_only_the_original_crash() {
+        assert_eq!(bisect_verdict(Some("panic at x"), None), Verdict::Good);
+        assert_eq!(
+            bisect_verdict(Some("panic at x"), Some("panic at x")),
+            Verdict::Bad
+        );
+        assert_eq!(
+            bisect_verdict(Some("panic at x"), Some("panic at y")),
+            Verdict::Skip
+        );
+        assert_eq!(bisect_verdict(None, Some("panic at y")), Verdict::Bad);
+    }
 
-        Ok(Manifest {
-            crate_name,
-            edition,
-        })
+    #[test]
+    fn bench_dependency_on_a_libfuzzer_version() {
+        let dependencies: toml::value::Table = toml::from_str(r#"libfuzzer = "0.1""#).unwrap();
+        let dependencies = bench_dependencies(
+            dependencies,
+            Path::new("/p/pkg/fuzz"),
+            Path::new("/p/bench"),
+        );
+        assert_eq!(dependencies["libfuzzer"]["version"].as_str(), Some("0.1"));
+        assert_eq!(
+            dependencies["libfuzzer"]["default-features"].as_bool(),
+            Some(false)
+        );
     }
-}
 
-fn is_fuzz_manifest(value: &toml::Value) -> bool {
-    let is_fuzz = value
-        .as_table()
-        .and_then(|v| v.get("package"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("metadata"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("cargo-fuzz"))
-        .and_then(toml::Value::as_bool);
-    is_fuzz == Some(true)
-}
+    #[test]
+    fn move_type_layouts() {
+        assert_eq!(move_type_layout("u64").unwrap(), "MoveTypeLayout::U64");
+        assert_eq!(
+            move_type_layout("vector<vector<u8>>").unwrap(),
+            "MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8))))"
+        );
+        assert!(move_type_layout("0x1::string::String").is_err());
+        assert!(move_type_layout("vector<u64").is_err());
+        assert!(is_rust_type_path("bank::Deposit"));
+        assert!(!is_rust_type_path("0x1::string::String"));
+        assert!(!is_rust_type_path("vector<u8>"));
+    }
 
-/// Returns the path for the first found non-fuzz Cargo package
-fn find_package() -> Result<PathBuf> {
-    let mut dir = env::current_dir()?;
-    let mut data = Vec::new();
-    loop {
-        let manifest_path = dir.join("Cargo.toml");
-        match fs::File::open(&manifest_path) {
-            Err(_) => {}
-            Ok(mut f) => {
-                data.clear();
-                f.read_to_end(&mut data)
-                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
-                let value: toml::Value = toml::from_slice(&data).with_context(|| {
-                    format!(
-                        "could not decode the manifest file at {}",
-                        manifest_path.display()
-                    )
-                })?;
-                if !is_fuzz_manifest(&value) {
-                    // Not a cargo-fuzz project => must be a proper cargo project :)
-                    return Ok(dir);
-                }
-            }
+    #[test]
+    fn rename_bin_entry() {
+        let mut manifest: Document = MANIFEST.parse().unwrap();
+        assert!(rename_cargo_target(
+            &mut manifest,
+            "bin",
+            "a",
+            "c",
+            "fuzz_targets/c.rs"
+        ));
+        assert!(!rename_cargo_target(
+            &mut manifest,
+            "bin",
+            "a",
+            "d",
+            "fuzz_targets/d.rs"
+        ));
+        let value: toml::Value = toml::from_str(&manifest.to_string()).unwrap();
+        assert_eq!(collect_targets(&value), ["b", "c"]);
+    }
+
+    #[test]
+    fn rename_bin_entry_keeps_its_other_keys() {
+        let mut manifest: Document = "[[bin]]\ntest = false # not a test\n\
+                                      path = \"fuzz_targets/a.rs\"\nname = \"a\"\n"
+            .parse()
+            .unwrap();
+        rename_cargo_target(&mut manifest, "bin", "a", "c", "fuzz_targets/c.rs");
+        assert_eq!(
+            manifest.to_string(),
+            "[[bin]]\ntest = false # not a test\n\
+             path = \"fuzz_targets/c.rs\"\nname = \"c\"\n"
+        );
+    }
+
+    #[test]
+    fn target_names_are_rust_identifiers() {
+        for name in ["deposit", "deposit_v2", "_deposit", "Deposit2"] {
+            assert!(is_target_name(name), "{}", name);
         }
-        if !dir.pop() {
-            break;
+        for name in ["", "_", "2deposit", "deposit-v2", "../deposit", "dépôt"] {
+            assert!(!is_target_name(name), "{}", name);
         }
     }
-    bail!("could not find a cargo project")
-}
-
-fn strip_current_dir_prefix(path: &Path) -> &Path {
-    env::current_dir()
-        .ok()
-        .and_then(|curdir| path.strip_prefix(curdir).ok())
-        .unwrap_or(path)
 }
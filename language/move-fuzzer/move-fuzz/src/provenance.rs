@@ -0,0 +1,388 @@
+//! Where the entries of the corpus of each target came from, recorded in
+//! `provenance/<target>/index.jsonl` of the fuzz directory as they are added
+//! and shown by `corpus info`.
+//!
+//! The index is a line of JSON per entry added, appended to by the commands
+//! adding entries: fuzzing, seeding, importing, merging and distilling.
+//! Entries are named after the SHA-1 of their content, as libFuzzer names
+//! them, so the first line of an entry keeps telling how it was first
+//! obtained. `cmin` and `corpus gc` drop the lines of the entries that left
+//! the corpus, so that the index does not outgrow it.
+
+use crate::corpus;
+use crate::report::format_time;
+use crate::triage::unix_now;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The provenance of the corpus of each target, in the fuzz directory.
+pub const PROVENANCE_DIR: &str = "provenance";
+
+/// The index of a target, in its provenance directory.
+pub const PROVENANCE_INDEX_FILE: &str = "index.jsonl";
+
+/// How an entry got into the corpus.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "origin", rename_all = "kebab-case")]
+pub enum Origin {
+    /// Copied from the Move sources of the package by `add --seed-from-sources`
+    Seed { file: PathBuf },
+    /// Found by the engine during the run started at `run`, in seconds since
+    /// the Unix epoch, by the `move-fuzz` command line `args`
+    Fuzzed {
+        run: u64,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Solved by `run --concolic` from the branch constraints of the entry `from`
+    Concolic { from: String },
+    /// Copied from a file by `corpus add`
+    Added { file: PathBuf },
+    /// Extracted from a tar archive by `corpus import`
+    Imported { archive: PathBuf },
+    /// Encoded from the recorded transactions of `corpus import-transactions`
+    Transactions { file: PathBuf },
+    /// Generated by the proptest strategy of `corpus seed-from-proptest`, from
+    /// the random seed given if any
+    Proptest { seed: Option<u64> },
+    /// Kept by `merge` from one of the corpus directories merged, if known
+    Merged { from: Option<PathBuf> },
+    /// Kept by `distill` from the corpus of another target, if known
+    Distilled { from: Option<String> },
+}
+
+impl Origin {
+    /// The way the entry was obtained, whatever the details.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Origin::Seed { .. } => "seed",
+            Origin::Fuzzed { .. } => "fuzzed",
+            Origin::Concolic { .. } => "concolic",
+            Origin::Added { .. } => "added",
+            Origin::Imported { .. } => "imported",
+            Origin::Transactions { .. } => "transactions",
+            Origin::Proptest { .. } => "proptest",
+            Origin::Merged { .. } => "merged",
+            Origin::Distilled { .. } => "distilled",
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Seed { file } => write!(f, "seeded from {}", file.display()),
+            Origin::Fuzzed { run, args } => {
+                write!(
+                    f,
+                    "found while fuzzing, in the run started {}",
+                    format_time(Some(*run))
+                )?;
+                if !args.is_empty() {
+                    write!(f, " by `move-fuzz {}`", args.join(" "))?;
+                }
+                Ok(())
+            }
+            Origin::Concolic { from } => write!(f, "solved concolically from {}", from),
+            Origin::Added { file } => write!(f, "added from {}", file.display()),
+            Origin::Imported { archive } => write!(f, "imported from {}", archive.display()),
+            Origin::Transactions { file } => {
+                write!(f, "encoded from the transactions of {}", file.display())
+            }
+            Origin::Proptest { seed: Some(seed) } => {
+                write!(f, "generated by a proptest strategy with seed {}", seed)
+            }
+            Origin::Proptest { seed: None } => write!(f, "generated by a proptest strategy"),
+            Origin::Merged { from: Some(dir) } => write!(f, "merged from {}", dir.display()),
+            Origin::Merged { from: None } => write!(f, "merged from another corpus"),
+            Origin::Distilled { from: Some(target) } => {
+                write!(f, "distilled from the corpus of {}", target)
+            }
+            Origin::Distilled { from: None } => {
+                write!(f, "distilled from the corpus of another target")
+            }
+        }
+    }
+}
+
+/// A line of the index.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Record {
+    /// The name of the entry, the SHA-1 of its content
+    pub entry: String,
+    /// When the entry was added, in seconds since the Unix epoch
+    pub time: u64,
+    #[serde(flatten)]
+    pub origin: Origin,
+}
+
+/// The provenance index of the corpus of a target.
+#[derive(Clone, Debug)]
+pub struct ProvenanceIndex {
+    path: PathBuf,
+}
+
+impl ProvenanceIndex {
+    pub fn new(path: PathBuf) -> Self {
+        ProvenanceIndex { path }
+    }
+
+    /// Appends the origins of the `entries` just added, by name.
+    pub fn record(&self, entries: impl IntoIterator<Item = (String, Origin)>) -> Result<()> {
+        let time = unix_now();
+        let mut lines = String::new();
+        for (entry, origin) in entries {
+            let record = Record {
+                entry,
+                time,
+                origin,
+            };
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .with_context(|| format!("failed to write to {}", self.path.display()))
+    }
+
+    /// The first record of each entry, by name. Lines that do not decode, such
+    /// as one cut short by an interrupted run, are skipped.
+    pub fn load(&self) -> Result<HashMap<String, Record>> {
+        let mut records = HashMap::new();
+        if !self.path.exists() {
+            return Ok(records);
+        }
+        let text = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))?;
+        for record in text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Record>(line).ok())
+        {
+            records.entry(record.entry.clone()).or_insert(record);
+        }
+        Ok(records)
+    }
+
+    /// Drops the lines of the entries not in `entries`, returning how many.
+    pub fn retain(&self, entries: &HashSet<String>) -> Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let text = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))?;
+        let mut kept = String::new();
+        let mut dropped = 0;
+        for line in text.lines() {
+            match serde_json::from_str::<Record>(line) {
+                Ok(record) if entries.contains(&record.entry) => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+                _ => dropped += 1,
+            }
+        }
+        if dropped > 0 {
+            // Written aside first, so an interrupted prune loses no records.
+            let tmp = self.path.with_extension("jsonl.tmp");
+            fs::write(&tmp, kept)
+                .with_context(|| format!("failed to write to {}", tmp.display()))?;
+            fs::rename(&tmp, &self.path)
+                .with_context(|| format!("failed to replace {}", self.path.display()))?;
+        }
+        Ok(dropped)
+    }
+}
+
+/// The name of the corpus entry at `path`.
+pub fn entry_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+/// `path` as recorded in an origin, absolute if it exists so that it still
+/// points to the file from another directory.
+pub fn source(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+/// The names of the entries of `corpus`, to tell the new ones later.
+pub fn entry_names(corpus: &Path) -> Result<HashSet<String>> {
+    Ok(corpus::entries(corpus)?
+        .iter()
+        .map(|entry| entry_name(&entry.path))
+        .collect())
+}
+
+/// The directory among `dirs` the new entry `entry` came from, without
+/// reading their inputs: the only one, or else the first holding an input of
+/// that name, as libFuzzer names them.
+pub fn dir_of<'a>(dirs: &'a [PathBuf], entry: &str) -> Option<&'a Path> {
+    match dirs {
+        [dir] => Some(dir),
+        _ => dirs
+            .iter()
+            .find(|dir| dir.join(entry).is_file())
+            .map(PathBuf::as_path),
+    }
+}
+
+/// How many of the `entries` of a corpus were obtained each way, the most
+/// first, those with no record counted as `unknown`.
+pub fn breakdown<'a>(
+    records: &HashMap<String, Record>,
+    entries: impl IntoIterator<Item = &'a str>,
+) -> Vec<(&'static str, usize)> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for entry in entries {
+        let kind = records
+            .get(entry)
+            .map_or("unknown", |record| record.origin.kind());
+        *counts.entry(kind).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fuzzed() -> Origin {
+        Origin::Fuzzed {
+            run: 1_700_000_000,
+            args: vec!["run".to_owned(), "deposit".to_owned()],
+        }
+    }
+
+    fn distilled() -> Origin {
+        Origin::Distilled {
+            from: Some("withdraw".to_owned()),
+        }
+    }
+
+    #[test]
+    fn record_a_line_per_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ProvenanceIndex::new(dir.path().join("deposit").join(PROVENANCE_INDEX_FILE));
+        index.record([("3f2a".to_owned(), fuzzed())]).unwrap();
+        index.record([]).unwrap();
+        let line = fs::read_to_string(dir.path().join("deposit/index.jsonl")).unwrap();
+        assert!(line.starts_with(r#"{"entry":"3f2a","time":"#));
+        assert!(line.ends_with(
+            "\"origin\":\"fuzzed\",\"run\":1700000000,\"args\":[\"run\",\"deposit\"]}\n"
+        ));
+    }
+
+    #[test]
+    fn load_the_first_origin_of_each_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ProvenanceIndex::new(dir.path().join(PROVENANCE_INDEX_FILE));
+        assert!(index.load().unwrap().is_empty());
+        index.record([("3f2a".to_owned(), fuzzed())]).unwrap();
+        index.record([("3f2a".to_owned(), distilled())]).unwrap();
+        assert_eq!(index.load().unwrap()["3f2a"].origin, fuzzed());
+    }
+
+    #[test]
+    fn skip_lines_cut_short() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ProvenanceIndex::new(dir.path().join(PROVENANCE_INDEX_FILE));
+        index.record([("3f2a".to_owned(), fuzzed())]).unwrap();
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(PROVENANCE_INDEX_FILE))
+            .unwrap();
+        file.write_all(br#"{"entry":"9c1b","ti"#).unwrap();
+        assert_eq!(index.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn describe_origins() {
+        assert_eq!(
+            fuzzed().to_string(),
+            "found while fuzzing, in the run started 2023-11-14 22:13 UTC by `move-fuzz run deposit`"
+        );
+        assert_eq!(
+            distilled().to_string(),
+            "distilled from the corpus of withdraw"
+        );
+    }
+
+    #[test]
+    fn break_down_entries_by_origin() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ProvenanceIndex::new(dir.path().join(PROVENANCE_INDEX_FILE));
+        let seed = Origin::Seed {
+            file: PathBuf::from("sources/bank.move"),
+        };
+        index
+            .record([("3f2a".to_owned(), fuzzed()), ("9c1b".to_owned(), seed)])
+            .unwrap();
+        assert_eq!(
+            breakdown(&index.load().unwrap(), ["3f2a", "9c1b", "77aa", "88bb"]),
+            [("unknown", 2), ("fuzzed", 1), ("seed", 1)]
+        );
+    }
+
+    #[test]
+    fn find_the_dir_of_a_new_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let corpus = dir.path().join("corpus");
+        fs::create_dir(&corpus).unwrap();
+        fs::write(corpus.join("hand-written"), "deposit 5").unwrap();
+        let dirs = [dir.path().join("none"), corpus.clone()];
+        assert_eq!(dir_of(&dirs, "hand-written"), Some(corpus.as_path()));
+        assert_eq!(dir_of(&dirs, "3f2a"), None);
+        assert_eq!(dir_of(&dirs[..1], "3f2a"), Some(dirs[0].as_path()));
+    }
+
+    #[test]
+    fn name_the_entries_of_a_corpus() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hand-written"), "deposit 5").unwrap();
+        assert_eq!(
+            entry_names(dir.path()).unwrap(),
+            HashSet::from(["hand-written".to_owned()])
+        );
+    }
+
+    #[test]
+    fn prune_the_entries_that_left_the_corpus() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ProvenanceIndex::new(dir.path().join(PROVENANCE_INDEX_FILE));
+        assert_eq!(index.retain(&HashSet::new()).unwrap(), 0);
+        let added = |file: &str| Origin::Added {
+            file: PathBuf::from(file),
+        };
+        index
+            .record([
+                ("3f2a".to_owned(), added("a")),
+                ("9c1b".to_owned(), added("b")),
+                ("3f2a".to_owned(), added("c")),
+            ])
+            .unwrap();
+
+        let kept = HashSet::from(["3f2a".to_owned()]);
+        assert_eq!(index.retain(&kept).unwrap(), 1);
+        let records = index.load().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records["3f2a"].origin, added("a"));
+        assert_eq!(index.retain(&kept).unwrap(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+//! Keeping the state of the fuzz targets within the `[fuzz.quota]` of `Move.toml`.
+//!
+//! Evicting corpus entries by the features they add relies on the feature
+//! sets libFuzzer dumps with `-features_dir` while loading a corpus: a file
+//! per input adding features to those of the inputs loaded before it, named
+//! after the SHA-1 of the input and holding its features as 32-bit integers.
+
+use crate::corpus;
+use crate::move_toml::Eviction;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// An entry of a corpus over its quota.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    /// Coverage features the entry adds, `None` if unknown
+    pub features: Option<usize>,
+}
+
+/// The entries to remove for a corpus of `entries` to fit in `max_size` bytes.
+/// Entries whose features are unknown are only evicted by age.
+pub fn evict(entries: &[Candidate], max_size: u64, eviction: Eviction) -> Vec<PathBuf> {
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut order: Vec<&Candidate> = entries.iter().collect();
+    match eviction {
+        Eviction::Lru => order.sort_by_key(|entry| entry.modified),
+        Eviction::LeastFeatures => {
+            order.sort_by_key(|entry| (entry.features.unwrap_or(usize::MAX), entry.modified))
+        }
+    }
+    let mut evicted = Vec::new();
+    for entry in order {
+        if total <= max_size {
+            break;
+        }
+        total -= entry.size;
+        evicted.push(entry.path.clone());
+    }
+    evicted
+}
+
+/// The number of features each of the `entries` added, from the feature sets
+/// dumped in `features_dir`. Entries without one added none.
+pub fn dumped_features(
+    features_dir: &Path,
+    entries: &[PathBuf],
+) -> Result<HashMap<PathBuf, usize>> {
+    let mut features = HashMap::new();
+    for entry in entries {
+        let data =
+            fs::read(entry).with_context(|| format!("failed to read {}", entry.display()))?;
+        let dumped = features_dir.join(corpus::content_hash(&data));
+        let count = fs::metadata(&dumped).map_or(0, |metadata| metadata.len() as usize / 4);
+        features.insert(entry.clone(), count);
+    }
+    Ok(features)
+}
+
+/// The artifacts of a bucket beyond the newest `max`, by modification time.
+pub fn excess_artifacts(artifacts: &[(PathBuf, SystemTime)], max: usize) -> Vec<PathBuf> {
+    let mut newest: Vec<_> = artifacts.iter().collect();
+    newest.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    newest
+        .into_iter()
+        .skip(max)
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn evict_by_age_or_features() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let dir = tempfile::tempdir().unwrap();
+        let corpus = dir.path().join("c");
+        let dumped = dir.path().join("features");
+        fs::create_dir_all(&corpus).unwrap();
+        fs::create_dir_all(&dumped).unwrap();
+        for (name, features) in [("old", 2), ("rich", 4), ("new", 0)] {
+            fs::write(corpus.join(name), name).unwrap();
+            if features > 0 {
+                let hash = corpus::content_hash(name.as_bytes());
+                fs::write(dumped.join(hash), vec![0; features * 4]).unwrap();
+            }
+        }
+        let entries: Vec<_> = ["old", "rich", "new"]
+            .iter()
+            .map(|name| corpus.join(name))
+            .collect();
+        let features = dumped_features(&dumped, &entries).unwrap();
+        assert_eq!(features[&corpus.join("rich")], 4);
+        assert_eq!(features[&corpus.join("new")], 0);
+
+        let entries: Vec<_> = [("c/old", 10, 1), ("c/rich", 20, 2), ("c/new", 30, 3)]
+            .into_iter()
+            .map(|(path, size, modified)| Candidate {
+                path: PathBuf::from(path),
+                size,
+                modified: at(modified),
+                features: features.get(&dir.path().join(path)).copied(),
+            })
+            .collect();
+        assert_eq!(evict(&entries, 60, Eviction::Lru), Vec::<PathBuf>::new());
+        assert_eq!(evict(&entries, 50, Eviction::Lru), [PathBuf::from("c/old")]);
+        assert_eq!(
+            evict(&entries, 50, Eviction::LeastFeatures),
+            [PathBuf::from("c/new")]
+        );
+        assert_eq!(
+            evict(&entries, 25, Eviction::LeastFeatures),
+            [PathBuf::from("c/new"), PathBuf::from("c/old")]
+        );
+
+        let artifacts = [
+            (PathBuf::from("crash-1"), at(5)),
+            (PathBuf::from("crash-2"), at(9)),
+            (PathBuf::from("crash-3"), at(7)),
+        ];
+        assert_eq!(excess_artifacts(&artifacts, 2), [PathBuf::from("crash-1")]);
+        assert!(excess_artifacts(&artifacts, 3).is_empty());
+    }
+}
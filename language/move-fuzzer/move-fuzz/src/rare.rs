@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+pub const RARE_BRANCHES_FILE: &str = "rare-branches.txt";
+
+/// Crates of the Move VM whose regions are reported, matched against the components of source paths.
+const VM_CRATES: &[&str] = &[
+    "move-binary-format",
+    "move-bytecode-verifier",
+    "move-core",
+    "move-stdlib",
+    "move-vm",
+];
+
+/// `kind` of a code region in `llvm-cov export`, as opposed to expansions, gaps and branches.
+const CODE_REGION: u64 = 0;
+
+/// Start of a code region.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Region {
+    pub file: String,
+    pub line: u64,
+    pub column: u64,
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// How many inputs hit each region of the functions the corpus reaches.
+///
+/// Regions of functions no input enters are left out: they point at missing
+/// harness features rather than at constraints the generator fails to satisfy.
+#[derive(Clone, Debug, Default)]
+pub struct RegionHits {
+    hits: BTreeMap<Region, usize>,
+}
+
+impl RegionHits {
+    /// Adds the coverage of a single input, given as the output of `llvm-cov export`
+    /// on its profile, counting only the regions of files accepted by `in_scope`.
+    pub fn add_input(&mut self, export: &str, in_scope: impl Fn(&str) -> bool) -> Result<()> {
+        let value: serde_json::Value =
+            serde_json::from_str(export).context("could not decode the llvm-cov export")?;
+        let functions = value["data"][0]["functions"]
+            .as_array()
+            .context("the llvm-cov export has no functions")?;
+
+        // Generic functions show up once per instantiation, an input counts once per region.
+        let mut hit = HashSet::new();
+        for function in functions {
+            let regions = function["regions"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let filenames = function["filenames"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let count = |region: &serde_json::Value| region[4].as_u64().unwrap_or_default();
+            match regions.first() {
+                Some(entry) if count(entry) > 0 => {}
+                _ => continue,
+            }
+            for region in regions {
+                if region[7].as_u64() != Some(CODE_REGION) {
+                    continue;
+                }
+                let file = match filenames[..]
+                    .get(region[5].as_u64().unwrap_or_default() as usize)
+                    .and_then(|f| f.as_str())
+                {
+                    Some(file) if in_scope(file) => file,
+                    _ => continue,
+                };
+                let region_start = Region {
+                    file: file.to_owned(),
+                    line: region[0].as_u64().unwrap_or_default(),
+                    column: region[1].as_u64().unwrap_or_default(),
+                };
+                if count(region) > 0 {
+                    hit.insert(region_start.clone());
+                }
+                self.hits.entry(region_start).or_insert(0);
+            }
+        }
+        for region in hit {
+            *self.hits.entry(region).or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
+    /// Regions hit by at most `threshold` inputs, least hit first.
+    pub fn rare(&self, threshold: usize) -> Vec<(&Region, usize)> {
+        let mut rare: Vec<_> = self
+            .hits
+            .iter()
+            .filter(|(_, &hits)| hits <= threshold)
+            .map(|(region, &hits)| (region, hits))
+            .collect();
+        rare.sort_by_key(|&(region, hits)| (hits, region));
+        rare
+    }
+}
+
+/// Whether `file` belongs to one of the Move VM crates.
+pub fn is_vm_source(file: &str) -> bool {
+    Path::new(file).components().any(|c| {
+        let c = c.as_os_str().to_string_lossy();
+        VM_CRATES.iter().any(|name| c.starts_with(name))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn export(entry: u64, branch: u64) -> String {
+        format!(
+            r#"{{"data":[{{"functions":[
+                {{"name":"f","filenames":["/m/move-vm/runtime/src/a.rs"],
+                 "regions":[[1,1,9,2,{entry},0,0,0],[3,5,4,6,{branch},0,0,0],[5,1,5,2,0,0,0,3]]}},
+                {{"name":"g","filenames":["/cargo/registry/serde/src/b.rs"],
+                 "regions":[[1,1,2,2,{entry},0,0,0]]}}
+            ]}}]}}"#,
+            entry = entry,
+            branch = branch,
+        )
+    }
+
+    #[test]
+    fn rare_regions() {
+        let mut hits = RegionHits::default();
+        for (entry, branch) in [(1, 0), (3, 0), (2, 5), (0, 0)] {
+            hits.add_input(&export(entry, branch), is_vm_source)
+                .unwrap();
+        }
+        let rare: Vec<_> = hits
+            .rare(1)
+            .into_iter()
+            .map(|(region, hits)| (region.to_string(), hits))
+            .collect();
+        assert_eq!(rare, [("/m/move-vm/runtime/src/a.rs:3:5".to_owned(), 1)]);
+        assert_eq!(hits.rare(3).len(), 2);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::triage::{unix_now, Bucket, Status};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+
+pub const RUN_LOG_FILE: &str = "runs.json";
+pub const COVERAGE_SUMMARY_FILE: &str = "summary.json";
+pub const COVERAGE_HISTORY_FILE: &str = "history.jsonl";
+
+/// When each target was last fuzzed, stored in `fuzz/runs.json`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RunLog {
+    /// Seconds since the Unix epoch, by target.
+    pub last_run: BTreeMap<String, u64>,
+}
+
+impl RunLog {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("could not decode the run log at {}", path.display()))
+    }
+
+    /// Records that `target` is being fuzzed now.
+    pub fn record(path: &Path, target: &str) -> Result<()> {
+        let mut log = Self::load(path)?;
+        log.last_run.insert(target.to_owned(), unix_now());
+        let data = serde_json::to_string_pretty(&log)?;
+        fs::write(path, data).with_context(|| format!("failed to write to {}", path.display()))
+    }
+}
+
+/// Line coverage of a target, saved next to its profile by `move-fuzz coverage`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoverageSummary {
+    pub lines_covered: u64,
+    pub lines_total: u64,
+    pub percent: f64,
+}
+
+impl CoverageSummary {
+    /// Extracts the line totals from the output of `llvm-cov export -summary-only`.
+    pub fn from_llvm_cov_export(export: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(export).context("could not decode the llvm-cov export")?;
+        let lines = &value["data"][0]["totals"]["lines"];
+        Ok(Self {
+            lines_covered: lines["covered"].as_u64().unwrap_or_default(),
+            lines_total: lines["count"].as_u64().unwrap_or_default(),
+            percent: lines["percent"].as_f64().unwrap_or_default(),
+        })
+    }
+}
+
+/// A coverage summary and when it was taken, one per line of the history next
+/// to the summary.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoverageSnapshot {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    #[serde(flatten)]
+    pub summary: CoverageSummary,
+}
+
+impl CoverageSnapshot {
+    /// Appends `summary`, taken now, to the history at `path`.
+    pub fn append(path: &Path, summary: &CoverageSummary) -> Result<()> {
+        let snapshot = Self {
+            time: unix_now(),
+            summary: summary.clone(),
+        };
+        let mut history = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        writeln!(history, "{}", serde_json::to_string(&snapshot)?)
+            .with_context(|| format!("failed to write to {}", path.display()))
+    }
+
+    /// Drops all but the latest `max` snapshots of the history at `path`,
+    /// returning how many were dropped.
+    pub fn keep_latest(path: &Path, max: usize) -> Result<usize> {
+        let history = Self::load_all(path)?;
+        let dropped = history.len().saturating_sub(max);
+        if dropped == 0 {
+            return Ok(0);
+        }
+        let lines = history[dropped..]
+            .iter()
+            .map(|snapshot| Ok(format!("{}\n", serde_json::to_string(snapshot)?)))
+            .collect::<Result<String>>()?;
+        fs::write(path, lines).with_context(|| format!("failed to write to {}", path.display()))?;
+        Ok(dropped)
+    }
+
+    /// The snapshots of the history at `path`, oldest first.
+    pub fn load_all(path: &Path) -> Result<Vec<Self>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        data.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).with_context(|| {
+                    format!(
+                        "could not decode the coverage history at {}",
+                        path.display()
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CrashSummary {
+    pub signature: String,
+    pub status: Status,
+    pub artifacts: usize,
+}
+
+impl From<&Bucket> for CrashSummary {
+    fn from(bucket: &Bucket) -> Self {
+        Self {
+            signature: bucket.signature.clone(),
+            status: bucket.status,
+            artifacts: bucket.artifacts.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TargetReport {
+    pub name: String,
+    pub corpus_entries: usize,
+    pub corpus_bytes: u64,
+    pub artifacts: usize,
+    pub coverage: Option<CoverageSummary>,
+    /// Crash buckets that are not fixed, most urgent first.
+    pub open_crashes: Vec<CrashSummary>,
+    /// Seconds since the Unix epoch.
+    pub last_run: Option<u64>,
+    /// Time spent fuzzing and crashes found over all runs in the event log.
+    pub fuzzing_secs: u64,
+    pub crashes_found: usize,
+}
+
+/// Everything move-fuzz knows about the fuzz targets of a project.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProjectReport {
+    pub package: String,
+    /// Seconds since the Unix epoch.
+    pub generated_at: u64,
+    pub targets: Vec<TargetReport>,
+}
+
+impl ProjectReport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Fuzzing report for `{}`\n", self.package);
+        let _ = writeln!(out, "Generated {}.\n", format_time(Some(self.generated_at)));
+        let _ = writeln!(
+            out,
+            "| Target | Corpus | Coverage | Open crashes | Artifacts | Fuzzed | Crashes found | Last run |"
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|---:|---|");
+        for t in &self.targets {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} ({}) | {} | {} | {} | {} | {} | {} |",
+                t.name,
+                t.corpus_entries,
+                format_bytes(t.corpus_bytes),
+                format_coverage(t.coverage.as_ref()),
+                t.open_crashes.len(),
+                t.artifacts,
+                format_secs(t.fuzzing_secs),
+                t.crashes_found,
+                format_time(t.last_run)
+            );
+        }
+
+        let crashes: Vec<_> = self
+            .targets
+            .iter()
+            .flat_map(|t| t.open_crashes.iter().map(move |c| (&t.name, c)))
+            .collect();
+        if !crashes.is_empty() {
+            let _ = writeln!(out, "\n## Open crashes\n");
+            let _ = writeln!(out, "| Status | Target | Artifacts | Signature |");
+            let _ = writeln!(out, "|---|---|---:|---|");
+            for (target, crash) in crashes {
+                let _ = writeln!(
+                    out,
+                    "| {} | `{}` | {} | `{}` |",
+                    crash.status,
+                    target,
+                    crash.artifacts,
+                    crash.signature.replace('|', "\\|").replace('`', "'")
+                );
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Fuzzing report for {package}</title>\n</head>\n<body>\n\
+             <h1>Fuzzing report for <code>{package}</code></h1>\n<p>Generated {time}.</p>",
+            package = escape_html(&self.package),
+            time = format_time(Some(self.generated_at))
+        );
+        let _ = writeln!(
+            out,
+            "<table>\n<tr><th>Target</th><th>Corpus</th><th>Coverage</th>\
+             <th>Open crashes</th><th>Artifacts</th><th>Fuzzed</th><th>Crashes found</th>\
+             <th>Last run</th></tr>"
+        );
+        for t in &self.targets {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{} ({})</td><td>{}</td><td>{}</td>\
+                 <td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&t.name),
+                t.corpus_entries,
+                format_bytes(t.corpus_bytes),
+                format_coverage(t.coverage.as_ref()),
+                t.open_crashes.len(),
+                t.artifacts,
+                format_secs(t.fuzzing_secs),
+                t.crashes_found,
+                format_time(t.last_run)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+
+        let crashes: Vec<_> = self
+            .targets
+            .iter()
+            .flat_map(|t| t.open_crashes.iter().map(move |c| (&t.name, c)))
+            .collect();
+        if !crashes.is_empty() {
+            let _ = writeln!(
+                out,
+                "<h2>Open crashes</h2>\n<table>\n<tr><th>Status</th><th>Target</th>\
+                 <th>Artifacts</th><th>Signature</th></tr>"
+            );
+            for (target, crash) in crashes {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td><code>{}</code></td></tr>",
+                    crash.status,
+                    escape_html(target),
+                    crash.artifacts,
+                    escape_html(&crash.signature)
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+        let _ = writeln!(out, "</body>\n</html>");
+        out
+    }
+}
+
+fn format_coverage(coverage: Option<&CoverageSummary>) -> String {
+    match coverage {
+        Some(c) => format!(
+            "{:.1}% ({}/{} lines)",
+            c.percent, c.lines_covered, c.lines_total
+        ),
+        None => "n/a".to_owned(),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KiB", b as f64 / (1 << 10) as f64),
+        b => format!("{} B", b),
+    }
+}
+
+/// Formats a duration in seconds as hours and minutes, e.g. `2h 05m`.
+fn format_secs(secs: u64) -> String {
+    format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time.
+pub(crate) fn format_time(secs: Option<u64>) -> String {
+    let secs = match secs {
+        Some(secs) => secs,
+        None => return "never".to_owned(),
+    };
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64;
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs % 86400 / 3600,
+        secs % 3600 / 60
+    )
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report() -> ProjectReport {
+        ProjectReport {
+            package: "bank".to_owned(),
+            generated_at: 1_700_000_000,
+            targets: vec![TargetReport {
+                name: "deposit".to_owned(),
+                corpus_entries: 12,
+                corpus_bytes: 2048,
+                artifacts: 1,
+                coverage: Some(CoverageSummary {
+                    lines_covered: 50,
+                    lines_total: 200,
+                    percent: 25.0,
+                }),
+                open_crashes: vec![CrashSummary {
+                    signature: "panic at src/lib.rs:1: a < b".to_owned(),
+                    status: Status::New,
+                    artifacts: 1,
+                }],
+                last_run: None,
+                fuzzing_secs: 3600,
+                crashes_found: 2,
+            }],
+        }
+    }
+
+    #[test]
+    fn markdown() {
+        let md = report().to_markdown();
+        assert!(md.contains("Generated 2023-11-14 22:13 UTC."));
+        assert!(md.contains(
+            "| `deposit` | 12 (2.0 KiB) | 25.0% (50/200 lines) | 1 | 1 | 1h 00m | 2 | never |"
+        ));
+        assert!(md.contains("| new | `deposit` | 1 | `panic at src/lib.rs:1: a < b` |"));
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = report().to_html();
+        assert!(html.contains("<code>panic at src/lib.rs:1: a &lt; b</code>"));
+    }
+
+    #[test]
+    fn html_has_the_fuzzing_time_and_crashes_found() {
+        let html = report().to_html();
+        assert!(html.contains("<th>Fuzzed</th><th>Crashes found</th>"));
+        assert!(html.contains("<td>1h 00m</td><td>2</td><td>never</td>"));
+    }
+
+    #[test]
+    fn llvm_cov_summary() {
+        let export = r#"{"data":[{"totals":{"lines":{"count":10,"covered":4,"percent":40.0}}}]}"#;
+        assert_eq!(
+            CoverageSummary::from_llvm_cov_export(export).unwrap(),
+            CoverageSummary {
+                lines_covered: 4,
+                lines_total: 10,
+                percent: 40.0
+            }
+        );
+    }
+
+    #[test]
+    fn coverage_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(COVERAGE_HISTORY_FILE);
+        assert!(CoverageSnapshot::load_all(&path).unwrap().is_empty());
+        for lines_covered in [4, 6] {
+            let summary = CoverageSummary {
+                lines_covered,
+                lines_total: 10,
+                percent: lines_covered as f64 * 10.0,
+            };
+            CoverageSnapshot::append(&path, &summary).unwrap();
+        }
+        let history = CoverageSnapshot::load_all(&path).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].summary.lines_covered, 6);
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with(r#"{"time":"#));
+        assert_eq!(CoverageSnapshot::keep_latest(&path, 1).unwrap(), 1);
+        assert_eq!(CoverageSnapshot::load_all(&path).unwrap(), history[1..]);
+        assert_eq!(CoverageSnapshot::keep_latest(&path, 1).unwrap(), 0);
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A crash of one target with a given signature, along with its reproducing inputs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Finding {
+    pub target: String,
+    pub signature: String,
+    pub artifacts: Vec<PathBuf>,
+}
+
+/// The rule a signature falls under, e.g. `panic`, `heap-buffer-overflow` or `timeout`.
+pub fn crash_class(signature: &str) -> String {
+    let class = if signature.starts_with("panic") {
+        "panic"
+    } else if let Some(report) = signature.strip_prefix("libFuzzer: ") {
+        report
+    } else {
+        // `AddressSanitizer: heap-buffer-overflow in func`
+        signature
+            .split(" in ")
+            .next()
+            .and_then(|report| report.split(": ").nth(1))
+            .unwrap_or(signature)
+    };
+    let slug: String = class
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if slug.is_empty() {
+        "crash".to_owned()
+    } else {
+        slug
+    }
+}
+
+/// Where a signature points to: a `file:line` for panics, the top frame for sanitizer reports.
+fn location(signature: &str) -> Option<Value> {
+    if let Some(rest) = signature.strip_prefix("panic at ") {
+        // `src/lib.rs:10:5: message`, or `'message', src/lib.rs:10:5` before Rust 1.73.
+        let position = rest.rsplit_once("', ").map_or(rest, |(_, pos)| pos);
+        let mut parts = position.splitn(3, ':');
+        let (file, line) = (parts.next()?, parts.next()?.parse::<u64>().ok()?);
+        return Some(json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": file },
+                "region": { "startLine": line },
+            }
+        }));
+    }
+    let (_, frame) = signature.split_once(" in ")?;
+    Some(json!({
+        "logicalLocations": [{ "fullyQualifiedName": frame, "kind": "function" }]
+    }))
+}
+
+/// Builds a SARIF 2.1.0 log with a rule per crash class and a result per finding.
+pub fn to_sarif(findings: &[Finding]) -> Value {
+    let mut rules = BTreeMap::new();
+    for finding in findings {
+        rules.entry(crash_class(&finding.signature)).or_insert_with(|| {
+            json!({
+                "id": crash_class(&finding.signature),
+                "shortDescription": { "text": format!("Fuzzing crash: {}", crash_class(&finding.signature)) },
+                "defaultConfiguration": { "level": "error" },
+            })
+        });
+    }
+
+    let results: Vec<_> = findings
+        .iter()
+        .map(|finding| {
+            let artifacts: Vec<_> = finding
+                .artifacts
+                .iter()
+                .map(|a| a.display().to_string())
+                .collect();
+            let mut result = json!({
+                "ruleId": crash_class(&finding.signature),
+                "level": "error",
+                "message": {
+                    "text": format!(
+                        "Fuzz target `{}` crashed: {}. Reproduce with `move-fuzz run {} {}`",
+                        finding.target,
+                        finding.signature,
+                        finding.target,
+                        artifacts.first().map(String::as_str).unwrap_or_default()
+                    )
+                },
+                "partialFingerprints": {
+                    "crashSignature/v1": format!("{}:{}", finding.target, finding.signature)
+                },
+                "attachments": artifacts
+                    .iter()
+                    .map(|a| json!({ "artifactLocation": { "uri": a } }))
+                    .collect::<Vec<_>>(),
+                "properties": { "target": finding.target, "artifacts": artifacts },
+            });
+            if let Some(location) = location(&finding.signature) {
+                result["locations"] = json!([location]);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "move-fuzz",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.into_values().collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+pub fn write(path: &Path, findings: &[Finding]) -> Result<()> {
+    let data = serde_json::to_string_pretty(&to_sarif(findings))?;
+    fs::write(path, data).with_context(|| format!("failed to write to {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crash_classes() {
+        assert_eq!(crash_class("panic at src/lib.rs:1:1: boom"), "panic");
+        assert_eq!(
+            crash_class("AddressSanitizer: heap-buffer-overflow in f"),
+            "heap-buffer-overflow"
+        );
+        assert_eq!(crash_class("libFuzzer: out-of-memory"), "out-of-memory");
+        assert_eq!(crash_class("exit status 1"), "exit-status-1");
+    }
+
+    #[test]
+    fn sarif_log() {
+        let findings = [
+            Finding {
+                target: "a".to_owned(),
+                signature: "panic at src/run_move/mod.rs:31:9: VMError".to_owned(),
+                artifacts: vec![PathBuf::from("fuzz/artifacts/a/crash-1")],
+            },
+            Finding {
+                target: "b".to_owned(),
+                signature: "AddressSanitizer: SEGV in loader::load".to_owned(),
+                artifacts: vec![PathBuf::from("fuzz/artifacts/b/crash-2")],
+            },
+        ];
+        let log = to_sarif(&findings);
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "panic");
+        assert_eq!(run["tool"]["driver"]["rules"][1]["id"], "segv");
+
+        let panic = &run["results"][0];
+        assert_eq!(panic["ruleId"], "panic");
+        let physical = &panic["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "src/run_move/mod.rs");
+        assert_eq!(physical["region"]["startLine"], 31);
+        assert_eq!(
+            panic["attachments"][0]["artifactLocation"]["uri"],
+            "fuzz/artifacts/a/crash-1"
+        );
+
+        let segv = &run["results"][1];
+        assert_eq!(
+            segv["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+            "loader::load"
+        );
+    }
+}
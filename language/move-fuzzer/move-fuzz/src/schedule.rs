@@ -0,0 +1,154 @@
+use crate::options::Schedule;
+
+/// How a target has done in the slices it was given so far.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetStats {
+    pub target: String,
+    pub slices: u32,
+    /// Coverage features reported by libFuzzer at the end of the last slice.
+    pub features: Option<u64>,
+    /// New features per second in the last slice.
+    pub gain: f64,
+    /// Index of the last slice given to the target.
+    last_slice: Option<usize>,
+    /// Crashed targets are not scheduled again.
+    pub crashed: bool,
+}
+
+/// Picks the target of each time slice of `run --all`.
+///
+/// With [`Schedule::Coverage`], slices go to the target that gained coverage
+/// the fastest in its last slice. Targets that are not gaining anything still
+/// get a slice every few rounds, in case their next slice finds a way in.
+#[derive(Clone, Debug)]
+pub struct Scheduler {
+    schedule: Schedule,
+    stats: Vec<TargetStats>,
+    slices: usize,
+}
+
+impl Scheduler {
+    pub fn new(targets: &[String], schedule: Schedule) -> Self {
+        Self {
+            schedule,
+            stats: targets
+                .iter()
+                .map(|target| TargetStats {
+                    target: target.clone(),
+                    slices: 0,
+                    features: None,
+                    gain: 0.0,
+                    last_slice: None,
+                    crashed: false,
+                })
+                .collect(),
+            slices: 0,
+        }
+    }
+
+    pub fn stats(&self) -> &[TargetStats] {
+        &self.stats
+    }
+
+    /// Index of the target to fuzz next, `None` once every target crashed.
+    pub fn next(&self) -> Option<usize> {
+        let candidates = || self.stats.iter().enumerate().filter(|(_, s)| !s.crashed);
+        // Least recently fuzzed first, never fuzzed before anything else.
+        let least_recent = candidates()
+            .min_by_key(|(_, s)| s.last_slice.map_or(0, |slice| slice + 1))
+            .map(|(i, _)| i)?;
+        if self.schedule == Schedule::RoundRobin {
+            return Some(least_recent);
+        }
+
+        let starved = |s: &TargetStats| match s.last_slice {
+            Some(slice) => self.slices - slice > 4 * self.stats.len(),
+            None => true,
+        };
+        if starved(&self.stats[least_recent]) {
+            return Some(least_recent);
+        }
+        candidates()
+            .filter(|(_, s)| s.gain > 0.0)
+            .max_by(|(_, a), (_, b)| a.gain.total_cmp(&b.gain))
+            .map(|(i, _)| i)
+            .or(Some(least_recent))
+    }
+
+    /// Records the outcome of a slice of `secs` seconds given to target `index`.
+    pub fn record(&mut self, index: usize, features: Option<u64>, secs: f64, crashed: bool) {
+        let stats = &mut self.stats[index];
+        stats.gain = match (stats.features, features) {
+            (Some(before), Some(after)) => after.saturating_sub(before) as f64 / secs.max(1.0),
+            // The first slice says nothing about saturation yet.
+            (None, Some(_)) => f64::MAX,
+            (_, None) => 0.0,
+        };
+        stats.features = features.or(stats.features);
+        stats.slices += 1;
+        stats.last_slice = Some(self.slices);
+        stats.crashed |= crashed;
+        self.slices += 1;
+    }
+}
+
+/// Parses the coverage features out of a libFuzzer status line such as
+/// `#4096 pulse cov: 120 ft: 340 corp: 20/1Kb exec/s: 2048 rss: 40Mb`.
+pub fn parse_features(line: &str) -> Option<u64> {
+    let mut words = line.split_whitespace();
+    words.find(|w| *w == "ft:")?;
+    words.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn targets() -> Vec<String> {
+        vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]
+    }
+
+    #[test]
+    fn features() {
+        let line = "#4096\tpulse  cov: 120 ft: 340 corp: 20/1Kb exec/s: 2048 rss: 40Mb";
+        assert_eq!(parse_features(line), Some(340));
+        assert_eq!(parse_features("INFO: Seed: 1"), None);
+    }
+
+    #[test]
+    fn coverage_schedule() {
+        let mut scheduler = Scheduler::new(&targets(), Schedule::Coverage);
+        for (features, i) in [100, 100, 100].into_iter().zip(0..) {
+            assert_eq!(scheduler.next(), Some(i));
+            scheduler.record(i, Some(features), 10.0, false);
+        }
+        // Only `b` keeps finding new coverage.
+        for (i, features) in [(0, 100), (1, 150), (2, 100)] {
+            scheduler.record(i, Some(features), 10.0, false);
+        }
+        assert_eq!(scheduler.next(), Some(1));
+        scheduler.record(1, Some(200), 10.0, false);
+        assert_eq!(scheduler.next(), Some(1));
+
+        // Saturated targets still get a slice now and then.
+        let mut picked = Vec::new();
+        for _ in 0..20 {
+            let i = scheduler.next().unwrap();
+            picked.push(i);
+            let features = scheduler.stats()[i].features.unwrap() + u64::from(i == 1);
+            scheduler.record(i, Some(features), 10.0, false);
+        }
+        assert!(picked.contains(&0) && picked.contains(&2));
+        assert!(picked.iter().filter(|&&i| i == 1).count() > 10);
+    }
+
+    #[test]
+    fn crashed_targets_are_skipped() {
+        let mut scheduler = Scheduler::new(&targets(), Schedule::RoundRobin);
+        scheduler.record(0, None, 1.0, true);
+        assert_eq!(scheduler.next(), Some(1));
+        scheduler.record(1, None, 1.0, true);
+        scheduler.record(2, None, 1.0, true);
+        assert_eq!(scheduler.next(), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! The scheduling state of libFuzzer, kept between the runs of a target in
+//! `scheduling/<target>/` of the fuzz directory so that short repeated runs,
+//! such as those of CI, pick up where the previous one left off.
+//!
+//! libFuzzer learns what to focus on over a run and forgets it when it exits.
+//! The function `-focus_function=auto` picked from a data flow trace is kept,
+//! so that later runs focus on it without a trace unless given another
+//! `-focus_function`. Flags given on the command line are not carried over.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// The scheduling state of each target, in the fuzz directory.
+pub const SCHEDULING_DIR: &str = "scheduling";
+
+/// The flags of a target, in its scheduling directory.
+pub const SCHEDULING_STATE_FILE: &str = "state.json";
+
+/// The features libFuzzer wrote for earlier versions, which it never reads back.
+pub const FEATURES_DIR: &str = "features";
+
+/// The libFuzzer flag picked by `-focus_function=auto`, carried over to the
+/// next runs.
+const FOCUS_FUNCTION: &str = "focus_function";
+
+/// What libFuzzer prints once `-focus_function=auto` picked a function, as in
+/// `INFO: AUTOFOCUS: 12 bank::deposit`.
+const AUTOFOCUS_PREFIX: &str = "INFO: AUTOFOCUS: ";
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SchedulingState {
+    /// The values of the scheduling flags learned by the last runs, by flag name
+    pub flags: BTreeMap<String, String>,
+}
+
+impl SchedulingState {
+    /// The state saved at `path`, an empty one if there is none.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut state: Self = serde_json::from_str(&json)
+            .with_context(|| format!("could not decode {}", path.display()))?;
+        // Earlier versions also kept the flags given on the command line.
+        state.flags.retain(|name, _| name == FOCUS_FUNCTION);
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).with_context(|| format!("failed to write to {}", path.display()))
+    }
+
+    /// The saved flags a run passing `args` to libFuzzer does not set itself.
+    pub fn args(&self, args: &[String]) -> Vec<String> {
+        let given: HashSet<&str> = args
+            .iter()
+            .filter_map(|arg| flag(arg))
+            .map(|f| f.0)
+            .collect();
+        self.flags
+            .iter()
+            .filter(|(name, _)| !given.contains(name.as_str()))
+            .map(|(name, value)| format!("-{}={}", name, value))
+            .collect()
+    }
+
+    /// Keeps what libFuzzer learned about its schedule from a `line` of its output.
+    pub fn record_output(&mut self, line: &str) {
+        let Some(rest) = line.trim().strip_prefix(AUTOFOCUS_PREFIX) else {
+            return;
+        };
+        if let Some((_, function)) = rest.split_once(' ') {
+            self.flags
+                .insert(FOCUS_FUNCTION.to_owned(), function.trim().to_owned());
+        }
+    }
+}
+
+/// The name and value of the libFuzzer flag `-name=value`.
+fn flag(arg: &str) -> Option<(&str, &str)> {
+    arg.strip_prefix('-')?.split_once('=')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keep_the_autofocus_function() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SCHEDULING_STATE_FILE);
+        let mut state = SchedulingState::load(&path).unwrap();
+        assert_eq!(state, SchedulingState::default());
+
+        state.record_output("INFO: AUTOFOCUS: 12 bank::deposit");
+        state.record_output("#2 INITED cov: 3 ft: 3 corp: 1/1b exec/s: 0 rss: 30Mb");
+        state.save(&path).unwrap();
+        let state = SchedulingState::load(&path).unwrap();
+        assert_eq!(state.args(&[]), ["-focus_function=bank::deposit"]);
+    }
+
+    #[test]
+    fn given_flags_win_over_the_saved_ones() {
+        let mut state = SchedulingState::default();
+        state.record_output("INFO: AUTOFOCUS: 12 bank::deposit");
+        assert!(state
+            .args(&["-focus_function=bank::withdraw".to_owned()])
+            .is_empty());
+        assert_eq!(
+            state.args(&["-entropic=0".to_owned()]),
+            ["-focus_function=bank::deposit"]
+        );
+    }
+
+    #[test]
+    fn drop_the_flags_earlier_versions_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SCHEDULING_STATE_FILE);
+        fs::write(
+            &path,
+            r#"{"flags":{"entropic":"0","focus_function":"bank::deposit"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SchedulingState::load(&path).unwrap().args(&[]),
+            ["-focus_function=bank::deposit"]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! The JSON schemas of what move-fuzz writes for other tools to read, printed
+//! by `move-fuzz schema`.
+//!
+//! Each output has a `version` field, bumped whenever a field is renamed,
+//! removed or changes type. Fields are only ever added within a version.
+
+use serde::Serialize;
+
+pub const MESSAGES: &str = include_str!("../schema/messages.json");
+pub const ARTIFACT_METADATA: &str = include_str!("../schema/artifact-metadata.json");
+pub const TRIAGE: &str = include_str!("../schema/triage.json");
+
+/// `value` with the `version` of its schema in front of its fields.
+#[derive(Serialize)]
+pub struct Versioned<'a, T> {
+    pub version: u32,
+    #[serde(flatten)]
+    pub value: &'a T,
+}
+
+/// Checks that the JSON object `value` only has the fields `schema` declares
+/// and has all those it requires, `version` being `version`.
+#[cfg(test)]
+pub fn assert_follows(schema: &serde_json::Value, value: &serde_json::Value, version: u32) {
+    assert_eq!(schema["properties"]["version"]["const"], version);
+    assert_eq!(value["version"], version);
+    let properties = schema["properties"].as_object().unwrap();
+    for field in value.as_object().unwrap().keys() {
+        assert!(
+            properties.contains_key(field),
+            "`{}` is not in the schema",
+            field
+        );
+    }
+    for field in schema["required"].as_array().unwrap() {
+        assert!(
+            value.get(field.as_str().unwrap()).is_some(),
+            "{} is required",
+            field
+        );
+    }
+}
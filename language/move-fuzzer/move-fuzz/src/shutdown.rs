@@ -0,0 +1,125 @@
+//! Stopping fuzzing runs gracefully on SIGINT, SIGTERM or at their time limit.
+//!
+//! libFuzzer removes its temporary directories when interrupted, and with
+//! `-fork` those hold the inputs the running jobs found but did not merge into
+//! the corpus yet. Once [`install`]ed, the signals only mark the shutdown as
+//! [`requested`]: the run copies those inputs out of the way before it
+//! [`terminate`]s the target, then merges them into the corpus. A second
+//! signal exits at once.
+//!
+//! The targets run in their own process group so that the jobs of `-fork` stop
+//! along with libFuzzer.
+
+use anyhow::Result;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use tracing::{info, warn};
+
+/// Where the inputs in flight are saved, in the temporary directory of a run.
+pub const IN_FLIGHT_DIR: &str = "in-flight";
+
+static INSTALL: Once = Once::new();
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+/// The targets running and their processes
+static CHILDREN: Mutex<Vec<(String, u32)>> = Mutex::new(Vec::new());
+
+/// Handles SIGINT and SIGTERM for the rest of the process.
+pub fn install() {
+    INSTALL.call_once(|| {
+        let handler = || {
+            if REQUESTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(130);
+            }
+            info!("Stopping, saving the corpus entries in flight (interrupt again to exit now)");
+        };
+        match ctrlc::set_handler(handler) {
+            Ok(()) => INSTALLED.store(true, Ordering::SeqCst),
+            Err(e) => warn!("could not handle interruptions: {}", e),
+        }
+    });
+}
+
+/// Whether the runs should stop.
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Runs `cmd` in its own process group when the signals are handled here, so
+/// that they no longer reach it directly.
+pub fn prepare(cmd: &mut Command) {
+    #[cfg(unix)]
+    if INSTALLED.load(Ordering::SeqCst) {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// A process of a target, known until dropped.
+pub struct Tracked {
+    target: String,
+    pid: u32,
+}
+
+/// Records that `child` runs `target`, for [`terminate`].
+pub fn track(target: &str, child: &Child) -> Tracked {
+    CHILDREN
+        .lock()
+        .unwrap()
+        .push((target.to_owned(), child.id()));
+    Tracked {
+        target: target.to_owned(),
+        pid: child.id(),
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        CHILDREN
+            .lock()
+            .unwrap()
+            .retain(|(target, pid)| (target, *pid) != (&self.target, self.pid));
+    }
+}
+
+/// Asks the processes running `target` to stop.
+pub fn terminate(target: &str) {
+    let pids: Vec<u32> = CHILDREN
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(running, _)| running == target)
+        .map(|(_, pid)| *pid)
+        .collect();
+    for pid in pids {
+        if let Err(e) = terminate_group(pid) {
+            warn!("could not stop process {}: {:#}", pid, e);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn terminate_group(pid: u32) -> Result<()> {
+    // libFuzzer exits once it printed its final stats on SIGTERM.
+    let group = if INSTALLED.load(Ordering::SeqCst) {
+        -(pid as i32)
+    } else {
+        pid as i32
+    };
+    if unsafe { libc::kill(group, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn terminate_group(pid: u32) -> Result<()> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status()?;
+    anyhow::ensure!(status.success(), "taskkill exited with {}", status);
+    Ok(())
+}
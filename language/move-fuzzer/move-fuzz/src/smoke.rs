@@ -0,0 +1,161 @@
+//! The verdicts of `smoke`, which builds every target and fuzzes it briefly to
+//! tell whether its harness starts, decodes inputs and runs them.
+//!
+//! What `check` cannot see are failures at run time: a Move package that does
+//! not publish, natives that are not registered, a harness panicking on the
+//! first input. They are told apart by whether libFuzzer got to run the seed
+//! corpus, which it reports with its `INITED` line.
+
+use crate::batch::Outcome;
+use crate::crash_report;
+use crate::engine::Stats;
+use std::fmt;
+
+/// How the smoke test of a target went.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// The target ran its executions without failing
+    Passed,
+    /// The target does not build
+    BuildFailed(String),
+    /// The harness failed before running any input, as told by the line
+    SetupFailed(String),
+    /// An input made the harness fail, as told by the line
+    Crashed(String),
+}
+
+impl Verdict {
+    /// The verdict of a run exiting with `success`, which printed `output`.
+    pub fn of_run(success: bool, output: &[&str]) -> Self {
+        if success {
+            return Verdict::Passed;
+        }
+        let failure = failure(output);
+        if output.iter().any(|line| is_inited(line)) {
+            Verdict::Crashed(failure)
+        } else {
+            Verdict::SetupFailed(failure)
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        *self == Verdict::Passed
+    }
+
+    /// The outcome of the target in the summary of `smoke`, and what to show
+    /// of it: what failed, or how many inputs ran as `stats` tell.
+    pub fn into_outcome(self, stats: &Stats) -> (Outcome, Option<String>) {
+        match self {
+            Verdict::Passed => (
+                Outcome::Ok,
+                stats.execs.map(|execs| format!("{} execs", execs)),
+            ),
+            Verdict::BuildFailed(detail) => (Outcome::BuildFailed(detail), None),
+            Verdict::SetupFailed(detail) => (
+                Outcome::Crashed(Vec::new()),
+                Some(format!("setup failed: {}", detail)),
+            ),
+            Verdict::Crashed(detail) => (Outcome::Crashed(Vec::new()), Some(detail)),
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Verdict::Passed => "ok",
+            Verdict::BuildFailed(_) => "build failed",
+            Verdict::SetupFailed(_) => "setup failed",
+            Verdict::Crashed(_) => "crashed",
+        })
+    }
+}
+
+/// Whether libFuzzer says it ran the seed corpus, as in `#2 INITED cov: 3 ...`.
+fn is_inited(line: &str) -> bool {
+    line.starts_with('#') && line.split_whitespace().nth(1) == Some("INITED")
+}
+
+/// The line telling best what went wrong in `output`: the error of the
+/// sanitizer or libFuzzer, the panic, or the last line.
+fn failure(output: &[&str]) -> String {
+    let panic = output.iter().position(|line| line.contains("panicked at "));
+    if let Some(i) = panic {
+        return crash_report::panic_message(output, i);
+    }
+    output
+        .iter()
+        .find_map(|line| line.split_once("ERROR: ").map(|(_, error)| error.trim()))
+        .or_else(|| {
+            output
+                .iter()
+                .rev()
+                .map(|line| line.trim())
+                .find(|l| !l.is_empty())
+        })
+        .unwrap_or("exited without output")
+        .to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn panics_before_inited_are_setup_failures() {
+        let output = [
+            "INFO: Seed: 1",
+            "thread '<unnamed>' panicked at src/harness.rs:12:9:",
+            "failed to publish 0x1::bank: MISSING_DEPENDENCY",
+            "==1== ERROR: libFuzzer: deadly signal",
+        ];
+        assert_eq!(
+            Verdict::of_run(false, &output),
+            Verdict::SetupFailed(
+                "thread '<unnamed>' panicked at src/harness.rs:12:9: failed to publish 0x1::bank: \
+                 MISSING_DEPENDENCY"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn failures_after_inited_are_crashes() {
+        let output = [
+            "#2\tINITED cov: 12 ft: 14 corp: 1/1b exec/s: 0 rss: 40Mb",
+            "==1== ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602",
+        ];
+        assert_eq!(
+            Verdict::of_run(false, &output),
+            Verdict::Crashed("AddressSanitizer: heap-buffer-overflow on address 0x602".to_owned())
+        );
+    }
+
+    #[test]
+    fn successful_runs_pass() {
+        let output = ["#2\tINITED cov: 12 ft: 14 corp: 1/1b exec/s: 0 rss: 40Mb"];
+        assert_eq!(Verdict::of_run(true, &output), Verdict::Passed);
+    }
+
+    #[test]
+    fn silent_failures_fall_back_to_a_message() {
+        assert_eq!(
+            Verdict::of_run(false, &[]),
+            Verdict::SetupFailed("exited without output".to_owned())
+        );
+    }
+
+    #[test]
+    fn passing_targets_show_their_executions() {
+        let stats = Stats {
+            execs: Some(1000),
+            ..Stats::default()
+        };
+        let (outcome, detail) = Verdict::Passed.into_outcome(&stats);
+        assert!(matches!(outcome, Outcome::Ok));
+        assert_eq!(detail.as_deref(), Some("1000 execs"));
+        let (outcome, detail) = Verdict::SetupFailed("no natives".to_owned()).into_outcome(&stats);
+        assert!(matches!(outcome, Outcome::Crashed(_)));
+        assert_eq!(detail.as_deref(), Some("setup failed: no natives"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Symbolization of the crash reports of fuzz targets.
+//!
+//! The sanitizers do not always demangle Rust symbols, and a crash caused by
+//! a failed Move execution only shows the interpreter and harness frames. The
+//! harness describes the Move frames of a failed execution after its error,
+//! one per line, innermost first:
+//!
+//! ```text
+//! Move execution failed with ARITHMETIC_ERROR
+//!     in 0x1::bank::withdraw at code offset 7
+//!     in 0x1::bank::transfer at code offset 12
+//! ```
+//!
+//! and symbolizing a report splices them into its backtrace, where the Move
+//! VM or the harness called into Move code.
+
+use std::fmt;
+
+/// A frame of Move code in a crash report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveFrame {
+    /// `0x1::bank::withdraw`, or `script`
+    pub function: String,
+    pub offset: u16,
+}
+
+impl fmt::Display for MoveFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at code offset {}", self.function, self.offset)
+    }
+}
+
+/// The Move frames described in `report`, innermost first.
+pub fn move_frames(report: &str) -> Vec<MoveFrame> {
+    report.lines().filter_map(move_frame).collect()
+}
+
+fn move_frame(line: &str) -> Option<MoveFrame> {
+    let rest = line.strip_prefix("    in ")?;
+    let (function, offset) = rest.split_once(" at code offset ")?;
+    if function.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(MoveFrame {
+        function: function.to_owned(),
+        offset: offset.trim_end().parse().ok()?,
+    })
+}
+
+/// Demangles the Rust symbols of `report` and splices its Move frames into
+/// its first backtrace, above the first frame of the Move VM or harness, or on
+/// top of it if there is none.
+pub fn symbolize(report: &str) -> String {
+    let mut lines: Vec<String> = report.lines().map(demangle_line).collect();
+    let frames = move_frames(report);
+    if frames.is_empty() {
+        return lines.join("\n");
+    }
+
+    let Some(start) = lines.iter().position(|l| backtrace_frame(l).is_some()) else {
+        return lines.join("\n");
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|l| backtrace_frame(l).is_none())
+        .map_or(lines.len(), |len| start + len);
+    let at = (start..end)
+        .find(|&i| {
+            backtrace_frame(&lines[i]).is_some_and(|(_, function)| {
+                function.starts_with("move_vm_runtime::")
+                    || function.starts_with("libfuzzer::run_move::")
+            })
+        })
+        .unwrap_or(start);
+    let (first, _) = backtrace_frame(&lines[at]).expect("a frame of the backtrace");
+
+    // The frames below move down the backtrace.
+    for line in &mut lines[at..end] {
+        if let Some((number, _)) = backtrace_frame(line) {
+            *line = line.replacen(
+                &format!("#{} ", number),
+                &format!("#{} ", number + frames.len()),
+                1,
+            );
+        }
+    }
+    let indent: String = lines[at]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    let spliced = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| format!("{}#{} <move> in {}", indent, first + i, frame));
+    lines.splice(at..at, spliced);
+    lines.join("\n")
+}
+
+/// The number and function of a frame of a sanitizer backtrace, as in
+/// `#3 0x55d1 in move_vm_runtime::interpreter::Interpreter::execute_main /src/...`.
+pub(crate) fn backtrace_frame(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start().strip_prefix('#')?;
+    let (number, rest) = line.split_once(' ')?;
+    let function = rest.split(" in ").nth(1)?.split_whitespace().next()?;
+    Some((number.parse().ok()?, function))
+}
+
+/// Demangles the Rust symbols in `line`, leaving the rest as it is.
+fn demangle_line(line: &str) -> String {
+    line.split(' ')
+        .map(|word| match rustc_demangle::try_demangle(word) {
+            Ok(demangled) => format!("{:#}", demangled),
+            Err(_) => word.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splice_move_frames() {
+        let report = "\
+thread '<unnamed>' panicked at src/run_move/mod.rs:158:9:
+Terminating execution: Move execution failed with ARITHMETIC_ERROR
+    in 0x1::bank::withdraw at code offset 7
+    in 0x1::bank::transfer at code offset 12
+==1== ERROR: libFuzzer: deadly signal
+    #0 0x55d1 in __sanitizer_print_stack_trace
+    #1 0x55d2 in _ZN9libfuzzer8run_move3run17h0123456789abcdefE /src/run_move/mod.rs:158:9
+    #2 0x55d3 in main
+
+SUMMARY: libFuzzer: deadly signal";
+        let frames = move_frames(report);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            frames[0].to_string(),
+            "0x1::bank::withdraw at code offset 7"
+        );
+        assert_eq!(
+            symbolize(report),
+            "\
+thread '<unnamed>' panicked at src/run_move/mod.rs:158:9:
+Terminating execution: Move execution failed with ARITHMETIC_ERROR
+    in 0x1::bank::withdraw at code offset 7
+    in 0x1::bank::transfer at code offset 12
+==1== ERROR: libFuzzer: deadly signal
+    #0 0x55d1 in __sanitizer_print_stack_trace
+    #1 <move> in 0x1::bank::withdraw at code offset 7
+    #2 <move> in 0x1::bank::transfer at code offset 12
+    #3 0x55d2 in libfuzzer::run_move::run /src/run_move/mod.rs:158:9
+    #4 0x55d3 in main
+
+SUMMARY: libFuzzer: deadly signal"
+        );
+        assert_eq!(symbolize("no backtrace\n    #x"), "no backtrace\n    #x");
+    }
+}
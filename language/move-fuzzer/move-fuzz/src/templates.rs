@@ -1,5 +1,5 @@
 macro_rules! cargo_toml_template {
-    ($name:expr, $edition:expr, $fuzzing_workspace:expr) => {
+    ($name:expr, $edition:expr, $fuzzing_workspace:expr, $package_path:expr, $libfuzzer_path:expr, $arbitrary_path:expr) => {
         format_args!(
             r##"[package]
 name = "{name}-fuzz"
@@ -10,12 +10,16 @@ publish = false
 cargo-fuzz = true
 
 [dependencies]
-libfuzzer = {{ path = "../../libfuzzer" }}
+libfuzzer = {{ path = "{libfuzzer_path}" }}
+move-arbitrary = {{ path = "{arbitrary_path}" }}
 
 [dependencies.{name}]
-path = ".."
+path = "{package_path}"
 {workspace}"##,
             name = $name,
+            package_path = $package_path,
+            libfuzzer_path = $libfuzzer_path,
+            arbitrary_path = $arbitrary_path,
             edition = if let Some(edition) = &$edition {
                 format!("edition = \"{}\"\n", edition)
             } else {
@@ -35,7 +39,7 @@ members = ["."]
 }
 
 macro_rules! move_toml_template {
-    ($name:expr, $edition:expr) => {
+    ($name:expr, $edition:expr, $addresses:expr) => {
         format_args!(
             r##"[package]
 name = "{name}_target"
@@ -47,8 +51,9 @@ MoveStdlib = {{ git = "https://github.com/move-language/move.git", subdir = "lan
 MoveNursery = {{ git = "https://github.com/move-language/move.git", subdir = "language/move-stdlib/nursery", rev = "main" }}
 
 [addresses]
-std =  "0x1""##,
+std =  "0x1"{addresses}"##,
             name = $name,
+            addresses = $addresses,
             edition = if let Some(edition) = &$edition {
                 format!("edition = \"{}\"\n", edition)
             } else {
@@ -74,6 +79,25 @@ bench = false
     };
 }
 
+macro_rules! dockerfile_template {
+    ($base:expr, $target:expr, $entrypoint:expr) => {
+        format_args!(
+            r##"FROM {base}
+WORKDIR /work
+COPY fuzz/ fuzz/
+COPY {target} {target}
+COPY corpus/ corpus/
+RUN mkdir -p artifacts
+VOLUME ["/work/corpus", "/work/artifacts"]
+ENTRYPOINT {entrypoint}
+"##,
+            base = $base,
+            target = $target,
+            entrypoint = $entrypoint,
+        )
+    };
+}
+
 macro_rules! gitignore_template {
     () => {
         format_args!(
@@ -81,6 +105,14 @@ macro_rules! gitignore_template {
 corpus
 artifacts
 coverage
+scheduling
+provenance
+heap
+merge
+triage
+notified.jsonl
+.locks
+.tmp
 "##
         )
     };
@@ -107,15 +139,525 @@ fuzz_target!(|data: Vec<u8>| {{
     };
 }
 
-macro_rules! move_target_template {
+macro_rules! rust_stateful_target_template {
+    ($edition:expr) => {
+        format_args!(
+            r##"#![no_main]
+{extern_crate}
+use libfuzzer::fuzz_target_stateful;
+use libfuzzer::run_move::move_args::MoveArg;
+
+// Global storage persists across inputs and is rebuilt every 10000 executions
+fuzz_target_stateful!(reset_every = 10_000, |data| {{
+    // data generation logic goes here
+    let args: MoveArg = vec![Box::new(data)];
+    args
+}});
+"##,
+            extern_crate = match $edition.as_deref() {
+                None | Some("2015") => "\nextern crate libfuzzer;\n",
+                Some(_) => "",
+            },
+        )
+    };
+}
+
+macro_rules! rust_gas_oracle_target_template {
     ($edition:expr) => {
+        format_args!(
+            r##"#![no_main]
+{extern_crate}
+use libfuzzer::fuzz_target_gas;
+use libfuzzer::run_move::move_args::MoveArg;
+
+// Each input runs metered twice and unmetered once, divergent results are crashes
+fuzz_target_gas!(|data| {{
+    // data generation logic goes here
+    let args: MoveArg = vec![Box::new(data)];
+    args
+}});
+"##,
+            extern_crate = match $edition.as_deref() {
+                None | Some("2015") => "\nextern crate libfuzzer;\n",
+                Some(_) => "",
+            },
+        )
+    };
+}
+
+macro_rules! rust_gas_schedule_target_template {
+    ($edition:expr) => {
+        format_args!(
+            r##"#![no_main]
+{extern_crate}
+use libfuzzer::fuzz_target_gas;
+use libfuzzer::run_move::move_args::MoveArg;
+
+// The start of each input is a gas schedule, the script runs under it and a costlier
+// variant of it, panics in the cost math and non-monotonic charging are crashes
+fuzz_target_gas!(fuzzed_schedule, |data| {{
+    // data generation logic goes here
+    let args: MoveArg = vec![Box::new(data)];
+    args
+}});
+"##,
+            extern_crate = match $edition.as_deref() {
+                None | Some("2015") => "\nextern crate libfuzzer;\n",
+                Some(_) => "",
+            },
+        )
+    };
+}
+
+macro_rules! rust_paranoid_target_template {
+    ($edition:expr) => {
+        format_args!(
+            r##"#![no_main]
+{extern_crate}
+use libfuzzer::fuzz_target_paranoid;
+use libfuzzer::run_move::move_args::MoveArg;
+
+// Each input runs with and without paranoid type checks, divergent results are crashes
+fuzz_target_paranoid!(|data| {{
+    // data generation logic goes here
+    let args: MoveArg = vec![Box::new(data)];
+    args
+}});
+"##,
+            extern_crate = match $edition.as_deref() {
+                None | Some("2015") => "\nextern crate libfuzzer;\n",
+                Some(_) => "",
+            },
+        )
+    };
+}
+
+macro_rules! rust_extensions_target_template {
+    ($edition:expr, $extensions:expr) => {
+        format_args!(
+            r##"#![no_main]
+{extern_crate}
+use libfuzzer::fuzz_target_extensions;
+use libfuzzer::run_move::move_args::MoveArg;
+
+// The start of each input is the state of the extensions, the rest goes here
+fuzz_target_extensions!(natives = [{natives}], |data| {{
+    // data generation logic goes here
+    let args: MoveArg = vec![Box::new(data)];
+    args
+}});
+"##,
+            extern_crate = match $edition.as_deref() {
+                None | Some("2015") => "\nextern crate libfuzzer;\n",
+                Some(_) => "",
+            },
+            natives = $extensions
+                .iter()
+                .map(|extension: &crate::options::Extension| extension.native_package())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    };
+}
+
+macro_rules! rust_raw_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    // raw input handling goes here, e.g. deserializing a `CompiledModule`
+    let _ = data;
+    0
+}}
+"##
+        )
+    };
+}
+
+macro_rules! rust_compiler_grammar_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+use move_arbitrary::arbitrary::Unstructured;
+use move_arbitrary::source::SourceGen;
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    // Decode a module from the grammar, then apply AST mutations with the remaining bytes
+    let mut u = Unstructured::new(data);
+    let module = match SourceGen::default().mutated_module(&mut u) {{
+        Ok(module) => module,
+        Err(_) => return -1,
+    }};
+    libfuzzer::run_move::compiler::check_source(&module.to_string());
+    0
+}}
+"##
+        )
+    };
+}
+
+macro_rules! rust_move_parser_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+// Inputs are Move source text: seed the corpus with `.move` files, e.g. with
+// `move-fuzz add --seed-from-sources`. Panics of the front end are findings,
+// and so are inputs retiring more instructions than `MOVE_FUZZ_PARSER_BUDGET`.
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    libfuzzer::run_move::parser::check_source(data);
+    0
+}}
+"##
+        )
+    };
+}
+
+macro_rules! rust_verifier_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+use libfuzzer::fuzz_mutator;
+use libfuzzer::run_move::bytecode::{{verify, BytecodeMutator}};
+
+// Seed the corpus with compiled modules (`.mv` files): the mutator keeps
+// inputs deserializable so that they reach the verifier passes.
+fuzz_mutator!(mutator = BytecodeMutator);
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    verify(data);
+    0
+}}
+"##
+        )
+    };
+}
+
+macro_rules! rust_disassembler_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+use libfuzzer::fuzz_mutator;
+use libfuzzer::run_move::bytecode::BytecodeMutator;
+use libfuzzer::run_move::disassembler::round_trip;
+
+// Seed the corpus with compiled modules (`.mv` files), only verified modules
+// are disassembled.
+fuzz_mutator!(mutator = BytecodeMutator);
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    round_trip(data);
+    0
+}}
+"##
+        )
+    };
+}
+
+macro_rules! rust_bcs_roundtrip_target_template {
+    ($ty:expr) => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+use libfuzzer::run_move::bcs_round_trip::round_trip;
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    round_trip::<{ty}>(data);
+    0
+}}
+"##,
+            ty = $ty,
+        )
+    };
+}
+
+macro_rules! rust_bcs_move_roundtrip_target_template {
+    ($ty:expr, $layout:expr) => {
+        format_args!(
+            r##"#![no_main]
+
+extern crate libfuzzer;
+
+use libfuzzer::run_move::bcs_round_trip::{{round_trip_move, MoveTypeLayout}};
+
+#[no_mangle]
+pub extern "C" fn rust_fuzzer_test_input(data: &[u8]) -> i32 {{
+    // Layout of `{ty}`
+    let layout = {layout};
+    round_trip_move(data, &layout);
+    0
+}}
+"##,
+            ty = $ty,
+            layout = $layout,
+        )
+    };
+}
+
+macro_rules! rust_sequence_target_template {
+    ($target:expr) => {
+        rust_sequence_target_template!($target, "")
+    };
+    ($target:expr, $oracle:expr) => {
+        format_args!(
+            r##"#![no_main]
+
+use libfuzzer::arbitrary::{{Result, Unstructured}};
+use libfuzzer::fuzz_sequence;
+use libfuzzer::run_move::addresses::signers;
+use libfuzzer::run_move::move_args::MoveArg;
+use libfuzzer::run_move::sequence::{{decode_sequence, EntryFunction}};
+
+fn step_args(u: &mut Unstructured) -> Result<MoveArg> {{
+    // argument generation logic goes here
+    Ok(vec![Box::new(u.arbitrary::<Vec<u8>>()?)])
+}}
+
+// The entry functions the fuzzer can call, in any order and any number of times
+const FUNCTIONS: &[EntryFunction] = &[EntryFunction::new("{target}", "step", 1, step_args)];
+
+// The signers are set by `[fuzz] signers` in Move.toml
+fuzz_sequence!({oracle}|u| decode_sequence(u, signers(), FUNCTIONS, 16));
+"##,
+            target = $target,
+            oracle = $oracle,
+        )
+    };
+}
+
+macro_rules! move_target_template {
+    ($edition:expr, $uses:expr) => {
         format_args!(
             r##"script {{
-    fun main(data: vector<u8>) {{
+{uses}    fun main(data: vector<u8>) {{
         // fuzzing code goes here
     }}
 }}
-"##
+"##,
+            uses = $uses,
+        )
+    };
+}
+
+macro_rules! move_sequence_target_template {
+    ($target:expr) => {
+        format_args!(
+            r##"module 0x42::{target} {{
+    public entry fun step(account: &signer, data: vector<u8>) {{
+        // fuzzing code goes here
+    }}
+}}
+"##,
+            target = $target,
+        )
+    };
+}
+
+macro_rules! rust_autoharness_target_template {
+    ($source:expr, $generators:expr, $entries:expr) => {
+        format_args!(
+            r##"#![no_main]
+
+use libfuzzer::arbitrary::{{Result, Unstructured}};
+use libfuzzer::fuzz_sequence;
+use libfuzzer::run_move::addresses::{{address, signers}};
+use libfuzzer::run_move::move_args::MoveArg;
+use libfuzzer::run_move::sequence::{{decode_sequence, EntryFunction}};
+
+// Generated by `move-fuzz autoharness` from {source}
+
+{generators}// The entry functions the fuzzer can call, in any order and any number of times
+const FUNCTIONS: &[EntryFunction] = &[
+{entries}];
+
+// The signers are set by `[fuzz] signers` in Move.toml
+fuzz_sequence!(|u| decode_sequence(u, signers(), FUNCTIONS, 16));
+"##,
+            source = $source,
+            generators = $generators,
+            entries = $entries,
+        )
+    };
+}
+
+macro_rules! move_autoharness_target_template {
+    ($target:expr, $functions:expr) => {
+        format_args!(
+            r##"module 0x42::{target} {{
+{functions}}}
+"##,
+            target = $target,
+            functions = $functions,
+        )
+    };
+}
+
+macro_rules! toml_regression_test_template {
+    ($name: expr) => {
+        format_args!(
+            r#"
+[[test]]
+name = "{0}_regressions"
+path = "regressions/{0}.rs"
+"#,
+            $name
+        )
+    };
+}
+
+macro_rules! regression_file_template {
+    ($target:expr) => {
+        format_args!(
+            r##"//! Regression tests for the `{target}` fuzz target, generated by `move-fuzz gen-regression`.
+
+use std::process::Command;
+
+fn run_input(name: &str, input: &[u8]) {{
+    let path = std::env::temp_dir().join(format!("{target}-{{}}", name));
+    std::fs::write(&path, input).expect("failed to write the regression input");
+    let status = Command::new(env!("CARGO_BIN_EXE_{target}"))
+        .arg(&path)
+        // The Move harness resolves its package relative to the project root.
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+        .status()
+        .expect("failed to run the fuzz target");
+    let _ = std::fs::remove_file(&path);
+    assert!(status.success(), "fuzz target exited with {{}}", status);
+}}
+"##,
+            target = $target,
+        )
+    };
+}
+
+macro_rules! regression_test_template {
+    ($name:expr, $artifact:expr, $bytes:expr) => {
+        format_args!(
+            r##"
+/// Reproduces `{artifact}`.
+#[test]
+fn {name}() {{
+    const INPUT: &[u8] = &[{bytes}];
+    run_input("{name}", INPUT);
+}}
+"##,
+            name = $name,
+            artifact = $artifact,
+            bytes = $bytes,
+        )
+    };
+}
+
+macro_rules! criterion_cargo_toml_template {
+    ($name:expr, $edition:expr, $dependencies:expr) => {
+        format_args!(
+            r##"[package]
+name = "{name}-bench"
+version = "0.0.0"
+publish = false
+{edition}
+# Generated by `move-fuzz bench --export-criterion`, run with `cargo bench`
+
+{dependencies}
+[dev-dependencies]
+criterion = "0.3.4"
+
+[workspace]
+"##,
+            name = $name,
+            edition = if let Some(edition) = &$edition {
+                format!("edition = \"{}\"\n", edition)
+            } else {
+                String::new()
+            },
+            dependencies = $dependencies,
+        )
+    };
+}
+
+macro_rules! toml_bench_template {
+    ($name: expr) => {
+        format_args!(
+            r#"
+[[bench]]
+name = "{0}"
+harness = false
+"#,
+            $name
+        )
+    };
+}
+
+macro_rules! criterion_bench_template {
+    ($target:expr, $harness:expr, $project:expr) => {
+        format_args!(
+            r##"extern crate criterion;
+extern crate libfuzzer;
+
+use criterion::{{criterion_group, criterion_main, Criterion, Throughput}};
+use std::path::Path;
+
+#[allow(unused_attributes)]
+#[path = "{harness}"]
+mod harness;
+
+fn replay_corpus(c: &mut Criterion) {{
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut paths: Vec<_> = std::fs::read_dir(root.join("corpus/{target}"))
+        .expect("failed to read the corpus snapshot")
+        .map(|entry| entry.expect("failed to read the corpus snapshot").path())
+        .collect();
+    paths.sort();
+    let inputs: Vec<Vec<u8>> = paths
+        .iter()
+        .map(|path| std::fs::read(path).expect("failed to read a corpus entry"))
+        .collect();
+
+    // The Move harness resolves its package relative to the project root.
+    std::env::set_current_dir(root.join("{project}"))
+        .expect("failed to enter the project directory");
+    libfuzzer::initialize(std::ptr::null(), std::ptr::null());
+
+    let mut group = c.benchmark_group("{target}");
+    group.throughput(Throughput::Elements(inputs.len() as u64));
+    group.bench_function("corpus", |b| {{
+        b.iter(|| {{
+            for input in &inputs {{
+                unsafe {{ libfuzzer::test_input_wrap(input.as_ptr(), input.len()) }};
+            }}
+        }})
+    }});
+    group.finish();
+}}
+
+criterion_group!(benches, replay_corpus);
+criterion_main!(benches);
+"##,
+            target = $target,
+            harness = $harness,
+            project = $project,
         )
     };
 }
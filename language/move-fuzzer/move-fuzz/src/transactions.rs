@@ -0,0 +1,545 @@
+use crate::modules;
+use anyhow::{anyhow, bail, Context, Result};
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{CompiledModule, SignatureToken};
+use move_core_types::account_address::AccountAddress;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A transaction recorded on a network: an entry-function call and the accounts signing it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct RecordedTransaction {
+    /// Fully qualified entry function, e.g. `0x42::bank::deposit`
+    pub function: String,
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// Addresses of the signers, in parameter order
+    #[serde(default)]
+    pub signers: Vec<String>,
+    /// BCS-encoded non-signer arguments, as hex strings
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl RecordedTransaction {
+    /// Splits `function` into its module and function names.
+    pub fn module_and_function(&self) -> Result<(&str, &str)> {
+        let mut parts = self.function.rsplitn(3, "::");
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(function), Some(module), Some(_address)) => Ok((module, function)),
+            _ => bail!(
+                "`{}` is not a fully qualified function such as `0x1::module::function`",
+                self.function
+            ),
+        }
+    }
+
+    /// The address of the module of `function`.
+    pub fn address(&self) -> Result<AccountAddress> {
+        match self.function.split_once("::") {
+            Some((address, _)) => AccountAddress::from_hex_literal(address)
+                .map_err(|_| anyhow!("invalid address {}", address)),
+            None => bail!(
+                "`{}` is not a fully qualified function such as `0x1::module::function`",
+                self.function
+            ),
+        }
+    }
+}
+
+/// Reads a JSON array of transactions, or a BCS-encoded vector of them when the
+/// file does not start with `[`. In BCS, signers are addresses and arguments are
+/// byte vectors rather than hex strings.
+pub fn load(path: &Path) -> Result<Vec<RecordedTransaction>> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        serde_json::from_slice(&data)
+            .with_context(|| format!("could not decode the transactions in {}", path.display()))
+    } else {
+        from_bcs(&data)
+            .with_context(|| format!("could not decode the transactions in {}", path.display()))
+    }
+}
+
+fn from_bcs(mut data: &[u8]) -> Result<Vec<RecordedTransaction>> {
+    let input = &mut data;
+    let txns = (0..read_uleb128(input)?)
+        .map(|_| {
+            Ok(RecordedTransaction {
+                function: read_string(input)?,
+                type_args: (0..read_uleb128(input)?)
+                    .map(|_| read_string(input))
+                    .collect::<Result<_>>()?,
+                signers: (0..read_uleb128(input)?)
+                    .map(|_| Ok(format!("0x{}", to_hex(take(input, ADDRESS_LENGTH)?))))
+                    .collect::<Result<_>>()?,
+                args: (0..read_uleb128(input)?)
+                    .map(|_| {
+                        let len = read_uleb128(input)?;
+                        Ok(to_hex(take(input, len)?))
+                    })
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    if !data.is_empty() {
+        bail!("{} trailing bytes", data.len());
+    }
+    Ok(txns)
+}
+
+const ADDRESS_LENGTH: usize = 32;
+
+/// Type of a non-signer entry-function parameter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArgType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Address,
+    Vector(Box<ArgType>),
+}
+
+impl ArgType {
+    /// The type of a parameter of a compiled function, `None` for those no
+    /// transaction argument has.
+    pub fn from_signature(ty: &SignatureToken) -> Option<Self> {
+        Some(match ty {
+            SignatureToken::Bool => ArgType::Bool,
+            SignatureToken::U8 => ArgType::U8,
+            SignatureToken::U16 => ArgType::U16,
+            SignatureToken::U32 => ArgType::U32,
+            SignatureToken::U64 => ArgType::U64,
+            SignatureToken::U128 => ArgType::U128,
+            SignatureToken::Address => ArgType::Address,
+            SignatureToken::Vector(elem) => ArgType::Vector(Box::new(Self::from_signature(elem)?)),
+            _ => return None,
+        })
+    }
+
+    /// Re-encodes a BCS value of this type the way `Unstructured::arbitrary` reads it back.
+    fn bcs_to_arbitrary(&self, bcs: &mut &[u8], out: &mut Vec<u8>) -> Result<()> {
+        match self {
+            ArgType::Bool => out.extend_from_slice(take(bcs, 1)?),
+            // Integers are little-endian in both encodings.
+            ArgType::U8 => out.extend_from_slice(take(bcs, 1)?),
+            ArgType::U16 => out.extend_from_slice(take(bcs, 2)?),
+            ArgType::U32 => out.extend_from_slice(take(bcs, 4)?),
+            ArgType::U64 => out.extend_from_slice(take(bcs, 8)?),
+            ArgType::U128 => out.extend_from_slice(take(bcs, 16)?),
+            ArgType::Address => out.extend_from_slice(take(bcs, ADDRESS_LENGTH)?),
+            ArgType::Vector(elem) => {
+                // `Vec<T>` reads a continuation flag before each element.
+                for _ in 0..read_uleb128(bcs)? {
+                    out.push(1);
+                    elem.bcs_to_arbitrary(bcs, out)?;
+                }
+                out.push(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The types of the non-signer parameters of `function` in the compiled
+/// `module`, or `None` if the module does not define it.
+pub fn entry_params(module: &CompiledModule, function: &str) -> Option<Result<Vec<ArgType>>> {
+    let handle = module.function_defs().iter().find_map(|def| {
+        let handle = module.function_handle_at(def.function);
+        (module.identifier_at(handle.name).as_str() == function).then_some(handle)
+    })?;
+    Some(
+        module
+            .signature_at(handle.parameters)
+            .0
+            .iter()
+            .filter(|ty| match ty {
+                SignatureToken::Signer => false,
+                SignatureToken::Reference(inner) => **inner != SignatureToken::Signer,
+                _ => true,
+            })
+            .map(|ty| {
+                ArgType::from_signature(ty).ok_or_else(|| {
+                    anyhow!(
+                        "unsupported entry function parameter type `{}`",
+                        modules::type_name(module, ty)
+                    )
+                })
+            })
+            .collect(),
+    )
+}
+
+/// The shape of a `fuzz_sequence!` harness built on `decode_sequence`, read from its source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SequenceHarness {
+    pub accounts: Vec<[u8; ADDRESS_LENGTH]>,
+    /// The `FUNCTIONS` entries, in order
+    pub functions: Vec<HarnessFunction>,
+    pub max_calls: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HarnessFunction {
+    pub module: String,
+    pub function: String,
+    pub signers: usize,
+}
+
+impl SequenceHarness {
+    /// Reads the shape of the harness in `source`. `signers` are the accounts of
+    /// harnesses taking them from `signers()`, as set in `Move.toml`.
+    pub fn parse(source: &str, signers: &[String]) -> Result<Self> {
+        // The value of each `field: value` in the source, in order.
+        let fields = |field: &str| -> Vec<String> {
+            source
+                .split(&format!("{}: ", field))
+                .skip(1)
+                .filter_map(|rest| rest.split([',', '}']).next())
+                .map(|value| value.trim().trim_matches('"').to_owned())
+                .collect()
+        };
+        // The module, function and signer count of each `EntryFunction::new(...)`,
+        // or else of each struct literal of the harnesses written before it.
+        let mut entries: Vec<_> = source
+            .split("EntryFunction::new(")
+            .skip(1)
+            .filter_map(call_args)
+            .filter_map(|args| {
+                let mut args = args
+                    .split(',')
+                    .map(|arg| arg.trim().trim_matches('"').to_owned());
+                Some(((args.next()?, args.next()?), args.next()?))
+            })
+            .collect();
+        if entries.is_empty() {
+            entries = fields("module")
+                .into_iter()
+                .zip(fields("function"))
+                .zip(fields("signers"))
+                .collect();
+        }
+        let functions = entries
+            .into_iter()
+            .map(|((module, function), signers)| {
+                Ok(HarnessFunction {
+                    module,
+                    function,
+                    signers: signers
+                        .parse()
+                        .with_context(|| format!("invalid signer count `{}`", signers))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut accounts = source
+            .split("from_hex_literal(\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .map(parse_address)
+            .collect::<Result<Vec<_>>>()?;
+        if accounts.is_empty() && source.contains("signers()") {
+            accounts = signers
+                .iter()
+                .map(|signer| parse_address(signer))
+                .collect::<Result<_>>()?;
+        }
+        let max_calls = source
+            .split("decode_sequence(")
+            .nth(1)
+            .and_then(call_args)
+            .and_then(|args| args.rsplit(',').next())
+            .and_then(|n| n.trim().replace('_', "").parse().ok());
+
+        match max_calls {
+            Some(max_calls) if !functions.is_empty() && !accounts.is_empty() => Ok(Self {
+                accounts,
+                functions,
+                max_calls,
+            }),
+            _ => bail!(
+                "not a sequence target: expected `EntryFunction` entries, accounts built \
+                 with `AccountAddress::from_hex_literal` or `signers()` and a \
+                 `decode_sequence` call"
+            ),
+        }
+    }
+
+    /// Encodes calls as `decode_sequence` reads them back. Arguments are assumed to
+    /// be generated with `u.arbitrary()` in parameter order, as in the template.
+    pub fn encode(&self, calls: &[Call]) -> Result<Vec<u8>> {
+        if calls.is_empty() || calls.len() > self.max_calls {
+            bail!("a sequence holds 1 to {} calls", self.max_calls);
+        }
+        let mut out = Vec::new();
+        push_int_in_range(&mut out, calls.len() - 1, self.max_calls.max(1) - 1);
+        for call in calls {
+            push_int_in_range(&mut out, call.function, self.functions.len() - 1);
+            for &signer in &call.signers {
+                push_int_in_range(&mut out, signer, self.accounts.len() - 1);
+            }
+            out.extend_from_slice(&call.args);
+        }
+        Ok(out)
+    }
+}
+
+/// A transaction translated to the indices and argument encoding of a harness.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+    pub function: usize,
+    pub signers: Vec<usize>,
+    pub args: Vec<u8>,
+}
+
+/// Translates recorded transactions into calls of `harness`.
+#[derive(Debug)]
+pub struct Translator<'a> {
+    harness: &'a SequenceHarness,
+    /// Signers outside the harness accounts, mapped round-robin onto them
+    signer_indices: HashMap<[u8; ADDRESS_LENGTH], usize>,
+}
+
+impl<'a> Translator<'a> {
+    pub fn new(harness: &'a SequenceHarness) -> Self {
+        let signer_indices = harness
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (*a, i))
+            .collect();
+        Self {
+            harness,
+            signer_indices,
+        }
+    }
+
+    /// Returns `Ok(None)` for transactions calling functions the harness doesn't know.
+    pub fn translate(
+        &mut self,
+        txn: &RecordedTransaction,
+        params: &[ArgType],
+    ) -> Result<Option<Call>> {
+        let (module, function) = txn.module_and_function()?;
+        let index = match self
+            .harness
+            .functions
+            .iter()
+            .position(|f| f.module == module && f.function == function)
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let signer_count = self.harness.functions[index].signers;
+        if txn.signers.len() != signer_count || txn.args.len() != params.len() {
+            bail!(
+                "{} takes {} signers and {} arguments, the transaction has {} and {}",
+                txn.function,
+                signer_count,
+                params.len(),
+                txn.signers.len(),
+                txn.args.len()
+            );
+        }
+
+        let mut signers = Vec::new();
+        for signer in &txn.signers {
+            let next = self.signer_indices.len() % self.harness.accounts.len();
+            let address = parse_address(signer)?;
+            signers.push(*self.signer_indices.entry(address).or_insert(next));
+        }
+        let mut args = Vec::new();
+        for (arg, ty) in txn.args.iter().zip(params) {
+            let bcs = from_hex(arg)?;
+            let mut bcs = bcs.as_slice();
+            ty.bcs_to_arbitrary(&mut bcs, &mut args).with_context(|| {
+                format!("invalid `{:?}` argument {} of {}", ty, arg, txn.function)
+            })?;
+        }
+        Ok(Some(Call {
+            function: index,
+            signers,
+            args,
+        }))
+    }
+}
+
+/// Appends the bytes `Unstructured::int_in_range(0..=delta)` consumes to return `value`.
+fn push_int_in_range(out: &mut Vec<u8>, value: usize, delta: usize) {
+    let len = (0..std::mem::size_of::<usize>())
+        .take_while(|i| delta >> (i * 8) > 0)
+        .count();
+    out.extend_from_slice(&value.to_be_bytes()[std::mem::size_of::<usize>() - len..]);
+}
+
+/// The arguments of a call, from after its opening parenthesis to the matching one.
+fn call_args(rest: &str) -> Option<&str> {
+    let mut depth = 0;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(&rest[..i]),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_address(address: &str) -> Result<[u8; ADDRESS_LENGTH]> {
+    let hex = address.trim().trim_start_matches("0x");
+    if hex.len() > 2 * ADDRESS_LENGTH {
+        bail!("address {} is too long", address);
+    }
+    let bytes = from_hex(&format!("{:0>64}", hex))
+        .with_context(|| format!("invalid address {}", address))?;
+    Ok(bytes.try_into().expect("64 hex digits are 32 bytes"))
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.trim().trim_start_matches("0x");
+    if hex.len() & 1 == 1 {
+        bail!("odd number of hex digits in {}", hex);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| anyhow!("invalid hex {}", hex)))
+        .collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        bail!("unexpected end of input");
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+fn read_uleb128(input: &mut &[u8]) -> Result<usize> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = take(input, 1)?[0];
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value as usize);
+        }
+    }
+    bail!("invalid ULEB128 length")
+}
+
+fn read_string(input: &mut &[u8]) -> Result<String> {
+    let len = read_uleb128(input)?;
+    Ok(String::from_utf8(take(input, len)?.to_vec())?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HARNESS: &str = r#"
+const FUNCTIONS: &[EntryFunction] = &[
+    EntryFunction::new("bank", "deposit", 1, deposit_args),
+    EntryFunction::new("bank", "transfer", 1, transfer_args).with_type_args(transfer_type_args),
+];
+
+fuzz_sequence!(|u| {
+    let accounts = [
+        AccountAddress::from_hex_literal("0xa11ce").unwrap(),
+        AccountAddress::from_hex_literal("0xb0b").unwrap(),
+    ];
+    decode_sequence(u, &accounts, FUNCTIONS, 16)
+});
+"#;
+
+    fn bank() -> CompiledModule {
+        modules::test::compile(
+            "module 0x42.bank {
+                public entry deposit(account: &signer, amount: u64) { label b0: return; }
+                public entry transfer(from: &signer, to: address, amounts: vector<u8>) {
+                    label b0: return;
+                }
+                public entry burn(account: signer, amount: u256) { label b0: return; }
+            }",
+        )
+    }
+
+    #[test]
+    fn entry_params_skip_signers() {
+        assert_eq!(
+            entry_params(&bank(), "transfer").unwrap().unwrap(),
+            vec![ArgType::Address, ArgType::Vector(Box::new(ArgType::U8))]
+        );
+        assert!(entry_params(&bank(), "withdraw").is_none());
+    }
+
+    #[test]
+    fn entry_params_reject_unsupported_types() {
+        let e = entry_params(&bank(), "burn").unwrap().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unsupported entry function parameter type `u256`"
+        );
+    }
+
+    #[test]
+    fn address_of_the_function() {
+        let txn = RecordedTransaction {
+            function: "0x0042::bank::deposit".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            txn.address().unwrap(),
+            AccountAddress::from_hex_literal("0x42").unwrap()
+        );
+        assert_eq!(txn.module_and_function().unwrap(), ("bank", "deposit"));
+    }
+
+    #[test]
+    fn encode_sequence() {
+        let harness = SequenceHarness::parse(HARNESS, &[]).unwrap();
+        let generated = SequenceHarness::parse(
+            "EntryFunction { module: \"bank\", function: \"deposit\", signers: 1, args: a }\n\
+             fuzz_sequence!(|u| decode_sequence(u, signers(), FUNCTIONS, 8));",
+            &["0xa11ce".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(generated.accounts, [parse_address("0xa11ce").unwrap()]);
+        assert_eq!(generated.max_calls, 8);
+        assert_eq!(
+            harness.functions[1],
+            HarnessFunction {
+                module: "bank".to_owned(),
+                function: "transfer".to_owned(),
+                signers: 1
+            }
+        );
+        assert_eq!(harness.max_calls, 16);
+
+        let txn = RecordedTransaction {
+            function: "0x42::bank::transfer".to_owned(),
+            type_args: vec![],
+            signers: vec!["0xb0b".to_owned()],
+            args: vec![format!("0x{:0>64}", "ca11"), "0x020709".to_owned()],
+        };
+        let params = entry_params(&bank(), "transfer").unwrap().unwrap();
+        let call = Translator::new(&harness)
+            .translate(&txn, &params)
+            .unwrap()
+            .unwrap();
+        let encoded = harness.encode(&[call]).unwrap();
+
+        let mut expected = vec![0, 1, 1];
+        expected.extend_from_slice(&parse_address("0xca11").unwrap());
+        expected.extend_from_slice(&[1, 7, 1, 9, 0]);
+        assert_eq!(encoded, expected);
+    }
+}
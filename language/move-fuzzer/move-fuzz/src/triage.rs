@@ -0,0 +1,560 @@
+use crate::schema::Versioned;
+use crate::symbolize;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::{fs, time};
+
+pub const TRIAGE_DB_FILE: &str = "triage.json";
+
+/// Version of `schema/triage.json` the triage database follows.
+///
+/// Version 2 changed the signatures: sanitizer frames are demangled and
+/// failed Move executions name the Move function they failed in. The buckets
+/// of older databases are kept, their signature replaced as their artifacts
+/// are triaged again.
+pub const TRIAGE_VERSION: u32 = 2;
+
+/// Where a crash bucket stands after the latest triage.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// First seen in the latest triage, or reproducing again after being fixed.
+    New,
+    /// Seen in an earlier triage and still reproducing.
+    Known,
+    /// None of the recorded artifacts reproduce anymore.
+    Fixed,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Status::New => "new",
+                Status::Known => "known",
+                Status::Fixed => "fixed",
+            }
+        )
+    }
+}
+
+/// A set of artifacts of one target sharing the same crash signature.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Bucket {
+    pub target: String,
+    pub signature: String,
+    pub status: Status,
+    pub artifacts: Vec<PathBuf>,
+    /// Seconds since the Unix epoch.
+    pub first_seen: u64,
+    /// Seconds since the Unix epoch.
+    pub last_seen: u64,
+    /// How reliably the artifacts reproduced the crash, when the latest triage
+    /// ran them several times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<Stability>,
+    /// Whether the signature was computed by an older version, to be replaced
+    /// by the current one of the first artifact of the bucket that crashes again.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy_signature: bool,
+}
+
+/// How many of the runs of some artifacts crashed with a given signature.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Stability {
+    pub runs: u32,
+    pub crashes: u32,
+}
+
+impl Stability {
+    /// Measures how reliably `runs` of an artifact, given with the signature
+    /// of their crash if they crashed, reproduce their most frequent crash.
+    /// Returns that signature with the measure, `None` if no run crashed.
+    pub fn measure(runs: &[Option<String>]) -> Option<(String, Stability)> {
+        let mut counts: Vec<(&str, u32)> = Vec::new();
+        for signature in runs.iter().flatten() {
+            match counts.iter_mut().find(|(s, _)| s == signature) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((signature, 1)),
+            }
+        }
+        // The first signature seen wins ties.
+        let (signature, crashes) = counts.into_iter().rev().max_by_key(|(_, count)| *count)?;
+        let stability = Stability {
+            runs: runs.len() as u32,
+            crashes,
+        };
+        Some((signature.to_owned(), stability))
+    }
+
+    /// Whether some runs did not reproduce the crash, or crashed differently.
+    pub fn is_flaky(&self) -> bool {
+        self.crashes < self.runs
+    }
+
+    fn add(&mut self, other: Stability) {
+        self.runs += other.runs;
+        self.crashes += other.crashes;
+    }
+}
+
+impl std::fmt::Display for Stability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}/{}", self.crashes, self.runs)
+    }
+}
+
+impl Bucket {
+    /// Lower is more urgent: memory-safety findings first, then panics, then resource exhaustion.
+    pub fn priority(&self) -> u8 {
+        let status = match self.status {
+            Status::New => 0,
+            Status::Known => 1,
+            Status::Fixed => 2,
+        };
+        status * 10 + signature_severity(&self.signature)
+    }
+}
+
+/// The crash database stored in `fuzz/triage.json`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CrashDatabase {
+    pub buckets: Vec<Bucket>,
+}
+
+/// The triage database as stored, with the version it was written with.
+#[derive(Deserialize)]
+struct Stored {
+    /// Missing in the databases written before there were versions
+    #[serde(default)]
+    version: Option<u32>,
+    buckets: Vec<Bucket>,
+}
+
+impl CrashDatabase {
+    /// Reads the database at `path`, marking the signatures of an older
+    /// version as legacy.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let stored: Stored = serde_json::from_str(&data).with_context(|| {
+            format!("could not decode the triage database at {}", path.display())
+        })?;
+        let version = stored.version.unwrap_or(1);
+        if version > TRIAGE_VERSION {
+            bail!(
+                "the triage database at {} has version {}, newer than the {} of this move-fuzz",
+                path.display(),
+                version,
+                TRIAGE_VERSION
+            );
+        }
+        let mut buckets = stored.buckets;
+        if version < TRIAGE_VERSION {
+            for bucket in &mut buckets {
+                bucket.legacy_signature = true;
+            }
+        }
+        Ok(CrashDatabase { buckets })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(&Versioned {
+            version: TRIAGE_VERSION,
+            value: self,
+        })?;
+        fs::write(path, data).with_context(|| format!("failed to write to {}", path.display()))
+    }
+
+    pub fn bucket_mut(&mut self, target: &str, signature: &str) -> Option<&mut Bucket> {
+        self.buckets
+            .iter_mut()
+            .find(|b| b.target == target && b.signature == signature)
+    }
+
+    /// Records a reproduced crash, returning whether it opened a new bucket.
+    /// A bucket with a legacy signature holding `artifact` takes `signature`.
+    pub fn record(&mut self, target: &str, signature: &str, artifact: &Path) -> bool {
+        let now = unix_now();
+        if let Some(bucket) = self.bucket_mut(target, signature) {
+            if !bucket.artifacts.iter().any(|a| a == artifact) {
+                bucket.artifacts.push(artifact.to_owned());
+            }
+            bucket.last_seen = now;
+            false
+        } else if let Some(bucket) = self.buckets.iter_mut().find(|b| {
+            b.legacy_signature && b.target == target && b.artifacts.iter().any(|a| a == artifact)
+        }) {
+            bucket.signature = signature.to_owned();
+            bucket.legacy_signature = false;
+            bucket.last_seen = now;
+            false
+        } else {
+            self.buckets.push(Bucket {
+                target: target.to_owned(),
+                signature: signature.to_owned(),
+                status: Status::New,
+                artifacts: vec![artifact.to_owned()],
+                first_seen: now,
+                last_seen: now,
+                stability: None,
+                legacy_signature: false,
+            });
+            true
+        }
+    }
+
+    /// Records an artifact run several times like [`record`](Self::record),
+    /// adding how reliably it reproduced the crash to its bucket.
+    pub fn record_runs(
+        &mut self,
+        target: &str,
+        signature: &str,
+        artifact: &Path,
+        stability: Stability,
+    ) -> bool {
+        let new = self.record(target, signature, artifact);
+        if let Some(bucket) = self.bucket_mut(target, signature) {
+            bucket
+                .stability
+                .get_or_insert_with(Stability::default)
+                .add(stability);
+        }
+        new
+    }
+
+    /// Updates the buckets of `target` after re-running some of its artifacts,
+    /// given with the signature they still crash with, if any.
+    ///
+    /// A bucket is fixed once none of its re-run artifacts crash with its
+    /// signature anymore, and reopened if a fixed crash shows up again.
+    /// Returns the signatures of the buckets newly marked fixed.
+    pub fn verify(&mut self, target: &str, results: &[(PathBuf, Option<String>)]) -> Vec<String> {
+        let mut reproduced = HashSet::new();
+        for (artifact, signature) in results {
+            if let Some(signature) = signature {
+                self.record(target, signature, artifact);
+                reproduced.insert(signature.as_str());
+            }
+        }
+
+        let mut fixed = Vec::new();
+        for bucket in self.buckets.iter_mut().filter(|b| b.target == target) {
+            if reproduced.contains(bucket.signature.as_str()) {
+                if bucket.status == Status::Fixed {
+                    bucket.status = Status::New;
+                }
+            } else if bucket.status != Status::Fixed
+                && results.iter().any(|(a, _)| bucket.artifacts.contains(a))
+            {
+                bucket.status = Status::Fixed;
+                fixed.push(bucket.signature.clone());
+            }
+        }
+        fixed
+    }
+
+    /// Buckets sorted from most to least urgent.
+    pub fn prioritized(&self) -> Vec<&Bucket> {
+        let mut buckets: Vec<_> = self.buckets.iter().collect();
+        buckets.sort_by_key(|b| (b.priority(), std::cmp::Reverse(b.artifacts.len())));
+        buckets
+    }
+}
+
+pub fn unix_now() -> u64 {
+    time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Extracts a stable signature from the output of a crashing fuzz target.
+///
+/// Sanitizer reports win over Rust panics, which win over libFuzzer's own
+/// findings (a panic always ends in a libFuzzer "deadly signal" report).
+/// Addresses and process ids are stripped so the same bug buckets together.
+pub fn crash_signature(output: &str) -> Option<String> {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut panic = None;
+    let mut libfuzzer = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(pos) = line.find("ERROR: ") {
+            let report = &line[pos + "ERROR: ".len()..];
+            if report.starts_with("libFuzzer:") {
+                libfuzzer.get_or_insert_with(|| strip_report_details(report));
+                continue;
+            }
+            let mut signature = strip_report_details(report);
+            if let Some(frame) = lines[i..].iter().find_map(|l| first_frame(l)) {
+                signature.push_str(" in ");
+                signature.push_str(frame);
+            }
+            return Some(signature);
+        }
+
+        if panic.is_none() {
+            if let Some(pos) = line.find("panicked at ") {
+                let rest = line[pos + "panicked at ".len()..].trim_end_matches(':');
+                // Since Rust 1.73 the message is printed on the line after the location.
+                let message = if rest.starts_with('\'') {
+                    rest.to_owned()
+                } else {
+                    let message = lines.get(i + 1).map(|l| l.trim()).unwrap_or_default();
+                    format!("{}: {}", rest, message)
+                };
+                let mut signature = format!("panic at {}", truncate(&message, 200));
+                // A failed Move execution is told apart by where it failed.
+                if let Some(frame) = symbolize::move_frames(output).first() {
+                    signature.push_str(" in ");
+                    signature.push_str(&frame.function);
+                }
+                panic = Some(signature);
+            }
+        }
+    }
+
+    panic.or(libfuzzer)
+}
+
+/// Relative severity of a signature, lower is more severe.
+fn signature_severity(signature: &str) -> u8 {
+    if signature.starts_with("panic") {
+        1
+    } else if signature.starts_with("libFuzzer: timeout")
+        || signature.starts_with("libFuzzer: out-of-memory")
+    {
+        3
+    } else if signature.starts_with("libFuzzer:") {
+        2
+    } else {
+        0
+    }
+}
+
+/// Keeps the bug class of a sanitizer or libFuzzer report, dropping
+/// addresses, sizes and thread ids.
+fn strip_report_details(report: &str) -> String {
+    let report = [" on address", " on unknown address", " after ", " ("]
+        .iter()
+        .fold(report, |report, sep| report.split(sep).next().unwrap_or(report));
+    report
+        .split_whitespace()
+        .filter(|word| !word.starts_with("0x") && !word.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the function name of a `#0 0x... in func file:line` backtrace frame.
+fn first_frame(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if !line.starts_with("#0 ") {
+        return None;
+    }
+    let func = line.split(" in ").nth(1)?;
+    func.split_whitespace().next()
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sanitizer_signature() {
+        let output = "\
+==4242==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011 at pc 0x55
+READ of size 1 at 0x602000000011 thread T0
+    #0 0x55d1 in move_vm_runtime::loader::Loader::load /src/loader.rs:10:5
+    #1 0x55d2 in main
+==4242==ERROR: libFuzzer: deadly signal";
+        assert_eq!(
+            crash_signature(output).as_deref(),
+            Some("AddressSanitizer: heap-buffer-overflow in move_vm_runtime::loader::Loader::load")
+        );
+    }
+
+    #[test]
+    fn panic_signature() {
+        let output = "\
+thread '<unnamed>' panicked at src/run_move/mod.rs:31:9:
+VMError with status ABORTED
+==1== ERROR: libFuzzer: deadly signal";
+        assert_eq!(
+            crash_signature(output).as_deref(),
+            Some("panic at src/run_move/mod.rs:31:9: VMError with status ABORTED")
+        );
+
+        let output = "\
+thread '<unnamed>' panicked at src/run_move/mod.rs:158:9:
+Terminating execution: Move execution aborted with code 3
+    in 0x1::bank::withdraw at code offset 7
+    in 0x1::bank::transfer at code offset 12";
+        assert_eq!(
+            crash_signature(output).as_deref(),
+            Some(
+                "panic at src/run_move/mod.rs:158:9: Terminating execution: \
+                Move execution aborted with code 3 in 0x1::bank::withdraw"
+            )
+        );
+
+        let output = "thread 'main' panicked at 'boom', src/lib.rs:1:1";
+        assert_eq!(
+            crash_signature(output).as_deref(),
+            Some("panic at 'boom', src/lib.rs:1:1")
+        );
+    }
+
+    #[test]
+    fn libfuzzer_signature() {
+        let output = "==77== ERROR: libFuzzer: timeout after 1201 seconds";
+        assert_eq!(
+            crash_signature(output).as_deref(),
+            Some("libFuzzer: timeout")
+        );
+        assert_eq!(crash_signature("INFO: Done 1 runs in 0 second(s)"), None);
+    }
+
+    #[test]
+    fn prioritized_buckets() {
+        let mut db = CrashDatabase::default();
+        db.record("a", "libFuzzer: timeout", Path::new("t"));
+        db.record("a", "panic at x", Path::new("p"));
+        db.record("b", "AddressSanitizer: SEGV in f", Path::new("s"));
+        db.bucket_mut("b", "AddressSanitizer: SEGV in f").unwrap().status = Status::Fixed;
+        let order: Vec<_> = db.prioritized().iter().map(|b| b.signature.as_str()).collect();
+        assert_eq!(
+            order,
+            ["panic at x", "libFuzzer: timeout", "AddressSanitizer: SEGV in f"]
+        );
+    }
+
+    #[test]
+    fn measure_stability() {
+        let panic = || Some("panic at x".to_owned());
+        let timeout = || Some("libFuzzer: timeout".to_owned());
+        let runs = [panic(), None, timeout(), panic()];
+        let (signature, stability) = Stability::measure(&runs).unwrap();
+        assert_eq!(signature, "panic at x");
+        assert_eq!(stability.to_string(), "2/4");
+        assert!(stability.is_flaky());
+        assert_eq!(Stability::measure(&[None, None]), None);
+
+        let mut db = CrashDatabase::default();
+        let (signature, stability) = Stability::measure(&[timeout(), panic()]).unwrap();
+        assert_eq!(signature, "libFuzzer: timeout");
+        db.record_runs("a", &signature, Path::new("t1"), stability);
+        let (signature, stability) = Stability::measure(&[timeout(), timeout()]).unwrap();
+        assert!(!stability.is_flaky());
+        db.record_runs("a", &signature, Path::new("t2"), stability);
+        let bucket = db.bucket_mut("a", "libFuzzer: timeout").unwrap();
+        assert_eq!(
+            bucket.stability,
+            Some(Stability {
+                runs: 4,
+                crashes: 3
+            })
+        );
+    }
+
+    #[test]
+    fn verify_marks_fixed_buckets() {
+        let mut db = CrashDatabase::default();
+        db.record("a", "panic at x", Path::new("p1"));
+        db.record("a", "panic at x", Path::new("p2"));
+        db.record("a", "libFuzzer: timeout", Path::new("t"));
+        db.record("b", "panic at y", Path::new("q"));
+
+        // `p2` still panics, so the bucket stays open even though `p1` passes.
+        let results = [
+            (PathBuf::from("p1"), None),
+            (PathBuf::from("p2"), Some("panic at x".to_owned())),
+            (PathBuf::from("t"), None),
+        ];
+        assert_eq!(db.verify("a", &results), ["libFuzzer: timeout"]);
+        assert_eq!(
+            db.bucket_mut("a", "panic at x").unwrap().status,
+            Status::New
+        );
+        assert_eq!(
+            db.bucket_mut("b", "panic at y").unwrap().status,
+            Status::New
+        );
+
+        let results = [(PathBuf::from("t"), Some("libFuzzer: timeout".to_owned()))];
+        assert!(db.verify("a", &results).is_empty());
+        let bucket = db.bucket_mut("a", "libFuzzer: timeout").unwrap();
+        assert_eq!(bucket.status, Status::New);
+    }
+
+    #[test]
+    fn legacy_signatures_are_replaced_when_triaged_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TRIAGE_DB_FILE);
+        fs::write(
+            &path,
+            r#"{"version":1,"buckets":[{"target":"a","signature":"panic at x","status":"known",
+            "artifacts":["p1","p2"],"first_seen":1,"last_seen":2}]}"#,
+        )
+        .unwrap();
+        let mut db = CrashDatabase::load(&path).unwrap();
+        assert!(db.buckets[0].legacy_signature);
+
+        assert!(!db.record("a", "panic at x in 0x1::m::f", Path::new("p2")));
+        assert!(db.record("b", "panic at x in 0x1::m::f", Path::new("p1")));
+        let bucket = db.bucket_mut("a", "panic at x in 0x1::m::f").unwrap();
+        assert!(!bucket.legacy_signature);
+        assert_eq!((bucket.status, bucket.first_seen), (Status::Known, 1));
+        assert_eq!(bucket.artifacts, [Path::new("p1"), Path::new("p2")]);
+
+        db.save(&path).unwrap();
+        assert_eq!(CrashDatabase::load(&path).unwrap(), db);
+        fs::write(&path, r#"{"version":3,"buckets":[]}"#).unwrap();
+        assert!(CrashDatabase::load(&path).is_err());
+    }
+
+    #[test]
+    fn database_follows_the_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TRIAGE_DB_FILE);
+        let mut db = CrashDatabase::default();
+        db.record_runs(
+            "a",
+            "panic at x",
+            Path::new("p"),
+            Stability {
+                runs: 2,
+                crashes: 1,
+            },
+        );
+        db.save(&path).unwrap();
+        assert_eq!(CrashDatabase::load(&path).unwrap(), db);
+
+        let schema: serde_json::Value = serde_json::from_str(crate::schema::TRIAGE).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        crate::schema::assert_follows(&schema, &json, TRIAGE_VERSION);
+        let bucket = &schema["$defs"]["bucket"];
+        for field in json["buckets"][0].as_object().unwrap().keys() {
+            assert!(
+                bucket["properties"].get(field).is_some(),
+                "`{}` is not in the schema",
+                field
+            );
+        }
+    }
+}
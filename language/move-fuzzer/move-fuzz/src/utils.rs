@@ -1,4 +1,93 @@
+use std::borrow::Cow;
+use std::process::{Command, Stdio};
+
 /// The default target to pass to cargo, to workaround issue #11.
 pub fn default_target() -> &'static str {
     current_platform::CURRENT_PLATFORM
 }
+
+/// The suffix of the executables built for `triple`, which may not be the
+/// host's.
+pub fn exe_suffix(triple: &str) -> &'static str {
+    if triple.contains("-windows") {
+        ".exe"
+    } else {
+        ""
+    }
+}
+
+/// `arg` as it can be pasted in the shell of the platform, double-quoted if it
+/// has spaces as Windows paths often do.
+pub fn quote_arg(arg: &str) -> Cow<'_, str> {
+    quote_arg_for(arg, cfg!(windows))
+}
+
+/// `arg` double-quoted for `cmd` on `windows`, which takes `""` for a quote
+/// within quotes, or for `sh`, which takes a backslash before the characters
+/// it would expand.
+fn quote_arg_for(arg: &str, windows: bool) -> Cow<'_, str> {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return Cow::Borrowed(arg);
+    }
+    let quoted = if windows {
+        arg.replace('"', "\"\"")
+    } else {
+        arg.chars().fold(String::new(), |mut quoted, c| {
+            if matches!(c, '"' | '\\' | '$' | '`') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+            quoted
+        })
+    };
+    Cow::Owned(format!("\"{}\"", quoted))
+}
+
+/// The trimmed stdout of `cmd`, or `None` if it can't be run or fails.
+pub fn command_output(cmd: &mut Command) -> Option<String> {
+    let output = cmd.stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// A command running `command` in the shell of the platform.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn windows_executables_and_paths() {
+        assert_eq!(exe_suffix("x86_64-pc-windows-msvc"), ".exe");
+        assert_eq!(exe_suffix("x86_64-unknown-linux-gnu"), "");
+        assert_eq!(
+            quote_arg("fuzz/artifacts/t/crash-1"),
+            "fuzz/artifacts/t/crash-1"
+        );
+        assert_eq!(
+            quote_arg_for(r"C:\Users\Jo Doe\fuzz", true),
+            r#""C:\Users\Jo Doe\fuzz""#
+        );
+        assert_eq!(quote_arg(""), r#""""#);
+    }
+
+    #[test]
+    fn quotes_within_quotes() {
+        assert_eq!(quote_arg_for(r#"say "hi""#, true), r#""say ""hi""""#);
+        assert_eq!(quote_arg_for(r#"say "hi""#, false), r#""say \"hi\"""#);
+        assert_eq!(quote_arg_for(r"$HOME\my dir", false), r#""\$HOME\\my dir""#);
+    }
+}
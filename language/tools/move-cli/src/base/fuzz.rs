@@ -0,0 +1,65 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::{bail, Context};
+use clap::*;
+use move_package::BuildConfig;
+use std::{path::PathBuf, process::Command};
+
+/// The fuzzer run by `move fuzz`, looked up on `PATH` like the plugins of cargo.
+const MOVE_FUZZ: &str = "move-fuzz";
+
+/// Fuzz the package at `path`. If no path is provided defaults to current directory.
+///
+/// Everything after `fuzz` goes to `move-fuzz`, e.g. `move fuzz init` then
+/// `move fuzz run <TARGET>`. It is a separate tool, installed with
+/// `cargo install --path language/move-fuzzer/move-fuzz`.
+#[derive(Parser)]
+#[clap(
+    name = "fuzz",
+    trailing_var_arg = true,
+    allow_hyphen_values = true,
+    disable_help_flag = true
+)]
+pub struct Fuzz {
+    /// The `move-fuzz` command to run and its arguments, see `move fuzz --help`.
+    #[clap(multiple_values = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+impl Fuzz {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        reroot_path(path)?;
+        let mut cmd = Command::new(MOVE_FUZZ);
+        cmd.args(&self.args);
+        // Hand the options of `move build` that also apply to the Move package of
+        // the fuzz targets to `move-fuzz`, which reads them from its environment.
+        // The others, e.g. `--dev`, mean something else there or do not apply.
+        if config.force_recompilation {
+            cmd.env("MOVE_FUZZ_FORCE", "true");
+        }
+        if config.fetch_deps_only {
+            cmd.env("MOVE_FUZZ_FETCH_DEPS_ONLY", "true");
+        }
+        if config.skip_fetch_latest_git_deps {
+            cmd.env("MOVE_FUZZ_SKIP_FETCH_LATEST_GIT_DEPS", "true");
+        }
+        if let Some(version) = config.bytecode_version {
+            cmd.env("MOVE_FUZZ_BYTECODE_VERSION", version.to_string());
+        }
+        let status = match cmd.status() {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!(
+                "`move fuzz` runs `{}`, which is not on PATH; install it with \
+                 `cargo install --path language/move-fuzzer/move-fuzz`",
+                MOVE_FUZZ
+            ),
+            status => status.with_context(|| format!("failed to run {}", MOVE_FUZZ))?,
+        };
+        if !status.success() {
+            // Keep the exit code, which tells crashes from other failures.
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ pub mod coverage;
 pub mod disassemble;
 pub mod docgen;
 pub mod errmap;
+pub mod fuzz;
 pub mod info;
 pub mod new;
 pub mod prove;
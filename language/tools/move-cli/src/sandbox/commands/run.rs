@@ -10,7 +10,7 @@ use crate::{
     NativeFunctionRecord,
 };
 use anyhow::{anyhow, bail, Result};
-use move_binary_format::file_format::CompiledModule;
+use move_binary_format::{errors::VMError, file_format::CompiledModule};
 use move_command_line_common::env::get_bytecode_version_from_env;
 use move_core_types::{
     account_address::AccountAddress,
@@ -76,6 +76,43 @@ pub fn run_and_check(
     dry_run: bool,
     verbose: bool,
 ) -> Result<bool> {
+    run_and_report(
+        natives,
+        cost_table,
+        error_descriptions,
+        state,
+        package,
+        script_path,
+        script_name_opt,
+        signers,
+        txn_args,
+        vm_type_args,
+        gas_budget,
+        bytecode_version,
+        dry_run,
+        verbose,
+    ).map(|error| error.is_none())
+}
+
+/// Like `run_and_check`, but returns the error the execution failed with, if
+/// any, once explained, rather than whether it succeeded.
+#[allow(clippy::too_many_arguments)]
+pub fn run_and_report(
+    natives: impl IntoIterator<Item = NativeFunctionRecord>,
+    cost_table: &CostTable,
+    error_descriptions: &ErrorMapping,
+    state: &OnDiskStateView,
+    package: &CompiledPackage,
+    script_path: &Path,
+    script_name_opt: &Option<String>,
+    signers: &[String],
+    txn_args: &[TransactionArgument],
+    vm_type_args: Vec<TypeTag>,
+    gas_budget: Option<u64>,
+    bytecode_version: Option<u32>,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<Option<VMError>> {
     if !script_path.exists() {
         bail!("Script file {:?} does not exist", script_path)
     };
@@ -153,19 +190,19 @@ pub fn run_and_check(
         println!("ERR: {:?}", err);
         explain_execution_error(
             error_descriptions,
-            err,
+            err.clone(),
             state,
             &script_type_parameters,
             &script_parameters,
             &vm_type_args,
             &signer_addresses,
             txn_args,
-        ).map(|_| false)
+        ).map(|_| Some(err))
     } else {
         let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
         if verbose {
             explain_execution_effects(&changeset, &events, state)?
         }
-        maybe_commit_effects(!dry_run, changeset, events, state).map(|_| true)
+        maybe_commit_effects(!dry_run, changeset, events, state).map(|_| None)
     }
 }
\ No newline at end of file